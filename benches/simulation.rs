@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use evolution_plants::board::{Board, Coord, Fields, Multipliers, Size};
+use evolution_plants::genome::{Genome, MutationConfig};
+use evolution_plants::population::{Plant, Population};
+use evolution_plants::simulation::{ReproductionConfig, ReproductionMode, Simulation, SimulationConfig};
+use evolution_plants::water::{WaterConfig, WaterField};
+
+/// The widths of the square boards the benchmarks are run on
+const SIZES: [usize; 3] = [64, 256, 1024];
+
+/// Creates a simulation with a plant in every other cell of a board with a light gradient
+fn simulation(width: usize, mode: ReproductionMode) -> Simulation {
+    let size = Size::new(width, width);
+    let light: Vec<f32> = (0..size.len()).map(|index| (index % width) as f32 / width as f32).collect();
+    let board = Board::new(Multipliers::new(30).unwrap(), Fields::new(size, &light).unwrap());
+
+    let mut population = Population::new(size);
+    for y in 0..width {
+        for x in (y % 2..width).step_by(2) {
+            population.insert(Coord::new(x, y), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+        }
+    }
+
+    let reproduction = ReproductionConfig { mode, ..Default::default() };
+    let config = SimulationConfig { reproduction, ..Default::default() };
+
+    Simulation::new(board, population, config).unwrap()
+}
+
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+
+    for width in SIZES {
+        for (name, mode) in [("asexual", ReproductionMode::Asexual), ("sexual", ReproductionMode::Sexual)] {
+            let simulation = simulation(width, mode);
+
+            group.bench_with_input(BenchmarkId::new(name, width), &simulation, |b, simulation| {
+                b.iter_batched_ref(|| simulation.clone(), |simulation| simulation.step(), BatchSize::LargeInput)
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn water_diffusion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("water_diffusion");
+    let config = WaterConfig::default();
+
+    for width in SIZES {
+        let size = Size::new(width, width);
+        let water: Vec<f32> = (0..size.len()).map(|index| (index % 7) as f32).collect();
+        let light = vec![0.5; size.len()];
+        let mut field = WaterField::new(size, &water);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        group.bench_function(BenchmarkId::from_parameter(width), |b| {
+            b.iter(|| field.step(&config, black_box(&light), &mut rng))
+        });
+    }
+
+    group.finish();
+}
+
+fn genome_mutation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("genome_mutate");
+    let config = MutationConfig::new(0.1, 0.1);
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+    for len in [2, 16, 256] {
+        let mut genome = Genome::new(&vec![0.5; len]).unwrap();
+
+        group.bench_function(BenchmarkId::from_parameter(len), |b| {
+            b.iter(|| genome.mutate(black_box(&config), &mut rng))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step, water_diffusion, genome_mutation);
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#![no_main]
+
+use evolution_plants::checkpoint::{CheckpointChain, CheckpointConfig};
+use evolution_plants::migrate;
+use libfuzzer_sys::fuzz_target;
+
+// Loading a checkpoint file must give an error for damaged bytes and never panic or allocate without bounds
+fuzz_target!(|data: &[u8]| {
+    let _ = migrate::upgrade(data);
+
+    if let Ok(chain) = CheckpointChain::from_bytes(data, CheckpointConfig::default()) {
+        let _ = chain.latest();
+        let _ = chain.to_bytes();
+    }
+});
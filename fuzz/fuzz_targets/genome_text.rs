@@ -0,0 +1,12 @@
+#![no_main]
+
+use evolution_plants::genome::Genome;
+use libfuzzer_sys::fuzz_target;
+
+// Parsing a genome must give an error for malformed text and never panic, a parsed genome must survive a round trip
+fuzz_target!(|text: &str| {
+    if let Ok(genome) = text.parse::<Genome>() {
+        let again: Genome = genome.to_string_repr().parse().expect("a formatted genome parses");
+        assert_eq!(genome, again);
+    }
+});
@@ -0,0 +1,54 @@
+/* The C API of evolution_plants, generated by evolution_plants::ffi::header */
+#ifndef EVOLUTION_PLANTS_H
+#define EVOLUTION_PLANTS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A running simulation, only ever handled through a pointer */
+typedef struct Simulation Simulation;
+
+/* The fields which can be read with sim_get_field */
+#define SIM_FIELD_LIGHT 0
+#define SIM_FIELD_WATER 1
+#define SIM_FIELD_NUTRIENTS 2
+#define SIM_FIELD_TOXIN 3
+#define SIM_FIELD_ENERGY 4
+
+/* The statuses returned by sim_step */
+#define SIM_OK 0
+#define SIM_ERROR_NULL 1
+#define SIM_ERROR_PANIC 2
+
+/* Creates a simulation from its settings in JSON, returns NULL if the settings are invalid */
+Simulation *sim_new(const char *config_json);
+
+/* Runs a single step, returns SIM_OK, SIM_ERROR_NULL if sim is NULL or SIM_ERROR_PANIC if the step failed,
+   after which the simulation must only be freed */
+int32_t sim_step(Simulation *sim);
+
+/* Returns the number of steps which have been run */
+uint64_t sim_tick(const Simulation *sim);
+
+/* Returns the width of the board in cells */
+size_t sim_width(const Simulation *sim);
+
+/* Returns the height of the board in cells */
+size_t sim_height(const Simulation *sim);
+
+/* Copies a field with one value per cell with the rows in order into out, at most len values are copied.
+   Returns the number of cells of the board, or 0 if the field is unknown */
+size_t sim_get_field(const Simulation *sim, uint32_t field, float *out, size_t len);
+
+/* Frees a simulation created by sim_new, NULL is ignored */
+void sim_free(Simulation *sim);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
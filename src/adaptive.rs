@@ -0,0 +1,274 @@
+use thiserror::Error;
+
+/// The settings for adjusting the mutation rate from the diversity of the population
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveMutationConfig {
+    /// The number of steps between every adjustment
+    pub interval: u64,
+    /// The mutation rate is raised when the diversity is below this value
+    pub low_diversity: f32,
+    /// The mutation rate is lowered when the diversity is above this value
+    pub high_diversity: f32,
+    /// The factor the mutation rate is multiplied or divided by in an adjustment
+    pub factor: f32,
+    /// The lowest allowed mutation rate
+    pub min_rate: f32,
+    /// The highest allowed mutation rate
+    pub max_rate: f32,
+}
+
+impl Default for AdaptiveMutationConfig {
+    fn default() -> Self {
+        Self {
+            interval: 100,
+            low_diversity: 0.01,
+            high_diversity: 0.1,
+            factor: 1.5,
+            min_rate: 0.0001,
+            max_rate: 0.5,
+        }
+    }
+}
+
+impl AdaptiveMutationConfig {
+    /// Checks that the limits of the mutation rate can be used by a controller
+    /// 
+    /// # Errors
+    /// 
+    /// AdaptiveMutationError::Limits: This will occur if the limits are not finite or not ordered within 0 to 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::adaptive::{AdaptiveMutationConfig, AdaptiveMutationError};
+    /// 
+    /// assert_eq!(Ok(()), AdaptiveMutationConfig::default().validate());
+    /// assert_eq!(Err(AdaptiveMutationError::Limits { min_rate: 0.5, max_rate: 0.1 }), AdaptiveMutationConfig { min_rate: 0.5, max_rate: 0.1, ..Default::default() }.validate());
+    /// ```
+    pub fn validate(&self) -> Result<(), AdaptiveMutationError> {
+        // The comparisons are false for NaN
+        if !(0.0 <= self.min_rate && self.min_rate <= self.max_rate && self.max_rate <= 1.0) {
+            return Err(AdaptiveMutationError::Limits { min_rate: self.min_rate, max_rate: self.max_rate });
+        }
+
+        Ok(())
+    }
+}
+
+/// A single change of the mutation rate made by the controller
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationAdjustment {
+    /// The tick at which the adjustment was made
+    pub tick: u64,
+    /// The diversity of the population which caused the adjustment
+    pub diversity: f32,
+    /// The mutation rate before the adjustment
+    pub old_rate: f32,
+    /// The mutation rate after the adjustment
+    pub new_rate: f32,
+}
+
+/// Adjusts the baseline mutation rate, raising it when the population is stuck and lowering it when it is diverse
+#[derive(Clone, Debug, PartialEq)]
+pub struct MutationController {
+    /// The settings of the controller
+    config: AdaptiveMutationConfig,
+    /// The current mutation rate
+    rate: f32,
+    /// All changes made to the mutation rate
+    log: Vec<MutationAdjustment>,
+}
+
+impl MutationController {
+    /// Creates a new controller
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the controller
+    /// rate: The initial mutation rate, it is clamped between the smallest and largest rate
+    /// 
+    /// # Errors
+    /// 
+    /// AdaptiveMutationError::Limits: This will occur if the limits of the rate are not finite or not ordered within 0 to 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::adaptive::{AdaptiveMutationConfig, MutationController};
+    /// 
+    /// let controller = MutationController::new(AdaptiveMutationConfig::default(), 0.01).unwrap();
+    /// 
+    /// assert_eq!(0.01, controller.rate());
+    /// assert!(MutationController::new(AdaptiveMutationConfig { min_rate: f32::NAN, ..Default::default() }, 0.01).is_err());
+    /// ```
+    pub fn new(config: AdaptiveMutationConfig, rate: f32) -> Result<Self, AdaptiveMutationError> {
+        config.validate()?;
+        let rate = rate.clamp(config.min_rate, config.max_rate);
+
+        Ok(Self { config, rate, log: Vec::new() })
+    }
+
+    /// Returns the current mutation rate
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Replaces the current mutation rate, it is clamped between the smallest and largest rate
+    pub(crate) fn set_rate(&mut self, rate: f32) {
+        self.rate = rate.clamp(self.config.min_rate, self.config.max_rate);
+    }
+
+    /// Returns all changes made to the mutation rate in the order they were made
+    pub fn log(&self) -> &[MutationAdjustment] {
+        &self.log
+    }
+
+    /// Adjusts the mutation rate if it is time for an adjustment and returns the adjustment if one was made
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The current tick of the simulation
+    /// diversity: The current diversity of the population
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::adaptive::{AdaptiveMutationConfig, MutationController};
+    /// 
+    /// let config = AdaptiveMutationConfig { interval: 10, low_diversity: 0.01, high_diversity: 0.1, factor: 2.0, min_rate: 0.0, max_rate: 1.0 };
+    /// let mut controller = MutationController::new(config, 0.01).unwrap();
+    /// 
+    /// assert!(controller.update(5, 0.0).is_none());
+    /// assert_eq!(0.02, controller.update(10, 0.0).unwrap().new_rate);
+    /// ```
+    pub fn update(&mut self, tick: u64, diversity: f32) -> Option<MutationAdjustment> {
+        if self.config.interval == 0 || !tick.is_multiple_of(self.config.interval) {
+            return None;
+        }
+
+        // Find the new rate
+        let new_rate = if diversity < self.config.low_diversity {
+            self.rate * self.config.factor
+        } else if diversity > self.config.high_diversity {
+            self.rate / self.config.factor
+        } else {
+            return None;
+        }.clamp(self.config.min_rate, self.config.max_rate);
+
+        if new_rate == self.rate {
+            return None;
+        }
+
+        let adjustment = MutationAdjustment { tick, diversity, old_rate: self.rate, new_rate };
+        self.rate = new_rate;
+        self.log.push(adjustment);
+
+        Some(adjustment)
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum AdaptiveMutationError {
+    #[error("The mutation rate limits are ({:?}, {:?}) but should be finite with 0 <= min_rate <= max_rate <= 1", min_rate, max_rate)]
+    Limits {
+        min_rate: f32,
+        max_rate: f32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AdaptiveMutationConfig {
+        AdaptiveMutationConfig {
+            interval: 10,
+            low_diversity: 0.01,
+            high_diversity: 0.1,
+            factor: 2.0,
+            min_rate: 0.005,
+            max_rate: 0.05,
+        }
+    }
+
+    #[test]
+    fn mutation_controller_new() {
+        let controller = MutationController::new(config(), 0.01).unwrap();
+
+        assert_eq!(config(), controller.config);
+        assert_eq!(0.01, controller.rate);
+        assert!(controller.log.is_empty());
+    }
+
+    #[test]
+    fn mutation_controller_new_invalid() {
+        for (min_rate, max_rate) in [(0.5, 0.1), (-0.1, 0.5), (0.1, 1.5), (f32::NAN, 0.5), (0.1, f32::NAN), (0.1, f32::INFINITY)] {
+            let config = AdaptiveMutationConfig { min_rate, max_rate, ..config() };
+
+            assert!(matches!(MutationController::new(config, 0.01), Err(AdaptiveMutationError::Limits { .. })));
+        }
+    }
+
+    #[test]
+    fn mutation_controller_new_clamp() {
+        assert_eq!(0.05, MutationController::new(config(), 1.0).unwrap().rate);
+        assert_eq!(0.005, MutationController::new(config(), 0.0).unwrap().rate);
+    }
+
+    #[test]
+    fn mutation_controller_set_rate() {
+        let mut controller = MutationController::new(config(), 0.01).unwrap();
+        controller.set_rate(0.02);
+
+        assert_eq!(0.02, controller.rate);
+
+        controller.set_rate(1.0);
+
+        assert_eq!(0.05, controller.rate);
+    }
+
+    #[test]
+    fn mutation_controller_update_interval() {
+        let mut controller = MutationController::new(config(), 0.01).unwrap();
+
+        assert_eq!(None, controller.update(15, 0.0));
+        assert_eq!(0.01, controller.rate);
+    }
+
+    #[test]
+    fn mutation_controller_update_raise() {
+        let mut controller = MutationController::new(config(), 0.01).unwrap();
+        let adjustment = controller.update(20, 0.0);
+
+        assert_eq!(Some(MutationAdjustment { tick: 20, diversity: 0.0, old_rate: 0.01, new_rate: 0.02 }), adjustment);
+        assert_eq!(0.02, controller.rate);
+        assert_eq!(vec![adjustment.unwrap()], controller.log);
+    }
+
+    #[test]
+    fn mutation_controller_update_lower() {
+        let mut controller = MutationController::new(config(), 0.02).unwrap();
+        let adjustment = controller.update(20, 0.5);
+
+        assert_eq!(Some(MutationAdjustment { tick: 20, diversity: 0.5, old_rate: 0.02, new_rate: 0.01 }), adjustment);
+        assert_eq!(0.01, controller.rate);
+    }
+
+    #[test]
+    fn mutation_controller_update_keep() {
+        let mut controller = MutationController::new(config(), 0.02).unwrap();
+
+        assert_eq!(None, controller.update(20, 0.05));
+        assert_eq!(0.02, controller.rate);
+        assert!(controller.log.is_empty());
+    }
+
+    #[test]
+    fn mutation_controller_update_bounds() {
+        let mut controller = MutationController::new(config(), 0.04).unwrap();
+
+        assert_eq!(0.05, controller.update(10, 0.0).unwrap().new_rate);
+        assert_eq!(None, controller.update(20, 0.0));
+        assert_eq!(1, controller.log.len());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::board::{Coord, Rect, Size};
+use crate::memory::{vec_bytes, HeapSize};
+use crate::population::Population;
+
+/// A summed-area table of a value for every cell, after building it once in a single pass
+/// the total of any rectangle is found in constant time from four corners. The sums are kept as f64
+/// such that the totals of large boards do not lose the small values
+#[derive(Clone, Debug, PartialEq)]
+pub struct SummedArea {
+    /// The size of the board
+    size: Size,
+    /// The total of every rectangle from the top left corner of the board, with an extra row and column of zeros first
+    sums: Vec<f64>,
+}
+
+impl SummedArea {
+    /// Builds the table from a value for every cell with the rows in order
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// value: Finds the value of the cell with an index
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{aggregate::SummedArea, board::{Rect, Size}};
+    /// 
+    /// let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    /// let table = SummedArea::new(Size::new(3, 2), |index| values[index] as f64);
+    /// 
+    /// assert_eq!(21.0, table.total());
+    /// assert_eq!(16.0, table.sum(Rect::new(1, 0, 2, 2)));
+    /// assert_eq!(6.0, table.sum(Rect::new(2, 1, 5, 5)));
+    /// ```
+    pub fn new<F: FnMut(usize) -> f64>(size: Size, mut value: F) -> Self {
+        let (w, h) = size.size();
+        let stride = w + 1;
+        let mut sums = vec![0.0; stride * (h + 1)];
+
+        for y in 0..h {
+            let mut row = 0.0;
+            for x in 0..w {
+                row += value(x + y * w);
+                sums[x + 1 + (y + 1) * stride] = sums[x + 1 + y * stride] + row;
+            }
+        }
+
+        Self { size, sums }
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Finds the total of the values in a rectangle, the rectangle is clamped to the board
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to add up
+    pub fn sum(&self, rect: Rect) -> f64 {
+        let rect = rect.clamp(self.size);
+        let stride = self.size.size().0 + 1;
+        let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.w, rect.y + rect.h);
+
+        self.sums[right + bottom * stride] - self.sums[left + bottom * stride] - self.sums[right + top * stride] + self.sums[left + top * stride]
+    }
+
+    /// Finds the mean of the values in a rectangle, the rectangle is clamped to the board and the mean of an empty rectangle is 0
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to average
+    pub fn mean(&self, rect: Rect) -> f64 {
+        let cells = rect.clamp(self.size).area();
+
+        if cells == 0 { 0.0 } else { self.sum(rect) / cells as f64 }
+    }
+
+    /// Returns the total of the values on the whole board
+    pub fn total(&self) -> f64 {
+        *self.sums.last().unwrap()
+    }
+}
+
+impl HeapSize for SummedArea {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.sums)
+    }
+}
+
+/// The totals of the light, the plants and their energy in any rectangle of the board answered in constant time,
+/// built once for a state of the board. Rebuild it after the board changes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aggregates {
+    /// The light in every cell
+    light: SummedArea,
+    /// The energy of the plant in every cell
+    biomass: SummedArea,
+    /// 1 for every cell with a plant
+    plants: SummedArea,
+}
+
+impl Aggregates {
+    /// Builds the tables for a state of the board
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The light in every cell, such as Simulation::light or the light field of the board
+    /// population: The plants living on the board
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if there is not a light value for every cell of the population
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{aggregate::Aggregates, board::{Coord, Rect, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// population.insert(Coord::new(3, 3), Plant::new(50, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let aggregates = Aggregates::new(&[0.5; 16], &population);
+    /// 
+    /// assert_eq!(1, aggregates.plants(Rect::new(0, 0, 2, 2)));
+    /// assert_eq!(150.0, aggregates.biomass(Rect::new(0, 0, 4, 4)));
+    /// assert_eq!(2.0, aggregates.light(Rect::new(0, 0, 2, 2)));
+    /// assert_eq!(0.25, aggregates.density(Coord::new(0, 0), 1));
+    /// ```
+    pub fn new(light: &[f32], population: &Population) -> Self {
+        let size = population.size();
+        assert_eq!(size.len(), light.len(), "There must be a light value for every cell");
+
+        Self {
+            light: SummedArea::new(size, |index| light[index] as f64),
+            biomass: SummedArea::new(size, |index| population.plant(index).map_or(0.0, |plant| plant.energy as f64)),
+            plants: SummedArea::new(size, |index| if population.plant(index).is_some() { 1.0 } else { 0.0 }),
+        }
+    }
+
+    /// Finds the total light in a rectangle, the rectangle is clamped to the board
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to add up
+    pub fn light(&self, rect: Rect) -> f64 {
+        self.light.sum(rect)
+    }
+
+    /// Finds the total energy of the plants in a rectangle, the rectangle is clamped to the board
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to add up
+    pub fn biomass(&self, rect: Rect) -> f64 {
+        self.biomass.sum(rect)
+    }
+
+    /// Finds the number of plants in a rectangle, the rectangle is clamped to the board
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to count
+    pub fn plants(&self, rect: Rect) -> usize {
+        self.plants.sum(rect).round() as usize
+    }
+
+    /// Finds the fraction of the cells within a distance of a cell in each direction which have a plant,
+    /// the cell itself is included and the cells outside the board are not counted
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The cell in the middle
+    /// radius: The largest distance in cells in each direction
+    pub fn density(&self, coord: Coord, radius: usize) -> f32 {
+        let (x, y) = (coord.x.saturating_sub(radius), coord.y.saturating_sub(radius));
+        let rect = Rect::new(x, y, coord.x + radius + 1 - x, coord.y + radius + 1 - y);
+
+        self.plants.mean(rect) as f32
+    }
+}
+
+impl HeapSize for Aggregates {
+    fn heap_bytes(&self) -> usize {
+        self.light.heap_bytes() + self.biomass.heap_bytes() + self.plants.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use crate::genome::Genome;
+    use crate::population::Plant;
+
+    #[test]
+    fn summed_area_matches_scan() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let size = Size::new(13, 9);
+        let values: Vec<f64> = (0..size.len()).map(|_| rng.gen_range(0.0..1.0)).collect();
+        let table = SummedArea::new(size, |index| values[index]);
+
+        for _ in 0..100 {
+            let rect = Rect::new(rng.gen_range(0..15), rng.gen_range(0..11), rng.gen_range(0..15), rng.gen_range(0..11));
+            let scan: f64 = rect.clamp(size).coords().map(|coord| values[size.index_of(coord).unwrap()]).sum();
+
+            assert!((scan - table.sum(rect)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn summed_area_empty() {
+        let table = SummedArea::new(Size::new(3, 3), |_| 1.0);
+
+        assert_eq!(0.0, table.sum(Rect::new(1, 1, 0, 2)));
+        assert_eq!(0.0, table.mean(Rect::new(5, 5, 2, 2)));
+        assert_eq!(1.0, table.mean(Rect::new(0, 0, 3, 3)));
+    }
+
+    #[test]
+    fn aggregates_density_edges() {
+        let mut population = Population::new(Size::new(3, 3));
+        for coord in [Coord::new(0, 0), Coord::new(1, 0), Coord::new(2, 2)] {
+            population.insert(coord, Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+        }
+        let aggregates = Aggregates::new(&[1.0; 9], &population);
+
+        assert_eq!(0.5, aggregates.density(Coord::new(0, 0), 1));
+        assert_eq!(3.0 / 9.0, aggregates.density(Coord::new(1, 1), 1));
+        assert_eq!(3, aggregates.plants(Rect::new(0, 0, 3, 3)));
+    }
+}
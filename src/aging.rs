@@ -0,0 +1,108 @@
+use crate::genome::{self, Genome};
+
+/// The settings for how plants age, plants pay extra upkeep for every step they live past their lifespan
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AgingConfig {
+    /// The lifespan in steps of a plant with a lifespan gene of 1
+    pub max_lifespan: u64,
+    /// The extra energy a plant pays every step for every step it has lived past its lifespan
+    pub respiration: f32,
+}
+
+impl Default for AgingConfig {
+    fn default() -> Self {
+        Self {
+            max_lifespan: 1000,
+            respiration: 1.0,
+        }
+    }
+}
+
+impl AgingConfig {
+    /// Finds the number of steps a plant lives before it starts to age,
+    /// returns None if the genome does not have a lifespan gene and therefore never ages
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{aging::AgingConfig, genome::Genome};
+    /// 
+    /// let config = AgingConfig { max_lifespan: 100, respiration: 1.0 };
+    /// 
+    /// assert_eq!(Some(25), config.lifespan(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.25]).unwrap()));
+    /// assert_eq!(None, config.lifespan(&Genome::new(&[0.0, 0.0]).unwrap()));
+    /// ```
+    pub fn lifespan(&self, genome: &Genome) -> Option<u64> {
+        let lifespan = genome.get(genome::GENE_LIFESPAN)?;
+
+        Some((lifespan * self.max_lifespan as f32) as u64)
+    }
+
+    /// Finds the extra upkeep a plant pays at an age, this is 0 until the plant has outlived its lifespan
+    /// and then rises by the respiration for every step
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// age: The number of steps the plant has lived
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{aging::AgingConfig, genome::Genome};
+    /// 
+    /// let config = AgingConfig { max_lifespan: 100, respiration: 2.0 };
+    /// let genome = Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.5]).unwrap();
+    /// 
+    /// assert_eq!(0, config.respiration_cost(&genome, 50));
+    /// assert_eq!(20, config.respiration_cost(&genome, 60));
+    /// ```
+    pub fn respiration_cost(&self, genome: &Genome, age: u64) -> u32 {
+        self.respiration_for(genome.get(genome::GENE_LIFESPAN), age)
+    }
+
+    /// Finds the extra upkeep a plant pays at an age from the fraction of the largest lifespan it lives,
+    /// a plant without a lifespan never ages
+    pub(crate) fn respiration_for(&self, lifespan: Option<f32>, age: u64) -> u32 {
+        match lifespan.map(|lifespan| (lifespan * self.max_lifespan as f32) as u64) {
+            Some(lifespan) if age > lifespan => ((age - lifespan) as f32 * self.respiration.max(0.0)) as u32,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AgingConfig {
+        AgingConfig { max_lifespan: 40, respiration: 0.5 }
+    }
+
+    #[test]
+    fn aging_config_lifespan() {
+        assert_eq!(Some(0), config().lifespan(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0]).unwrap()));
+        assert_eq!(Some(40), config().lifespan(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        assert_eq!(None, config().lifespan(&Genome::new(&[0.0, 0.0, 0.0, 0.0]).unwrap()));
+    }
+
+    #[test]
+    fn aging_config_respiration_cost() {
+        let genome = Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.5]).unwrap();
+
+        assert_eq!(0, config().respiration_cost(&genome, 0));
+        assert_eq!(0, config().respiration_cost(&genome, 20));
+        assert_eq!(1, config().respiration_cost(&genome, 22));
+        assert_eq!(10, config().respiration_cost(&genome, 40));
+        assert_eq!(0, AgingConfig { respiration: -1.0, ..config() }.respiration_cost(&genome, 40));
+    }
+
+    #[test]
+    fn aging_config_respiration_cost_without_gene() {
+        assert_eq!(0, config().respiration_cost(&Genome::new(&[0.0, 0.0]).unwrap(), 1_000_000));
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::VecDeque;
+
+use crate::checkpoint::{CheckpointChain, CheckpointConfig};
+use crate::snapshot::StateSnapshot;
+use crate::stats::TickStats;
+
+/// A condition on the statistics of a simulation which raises an alert when it starts to hold
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertRule {
+    /// The population dropped by more than a fraction of the largest population within a number of ticks
+    PopulationDrop {
+        /// The fraction of the population lost, between 0 and 1
+        fraction: f32,
+        /// The number of ticks the largest population is looked for within
+        within: u64,
+    },
+    /// There are fewer living plants than a limit
+    PopulationBelow(usize),
+    /// The genetic diversity of the living plants is lower than a limit
+    DiversityBelow(f32),
+    /// There are fewer living species than a limit, species must be tracked for this to be useful
+    SpeciesBelow(usize),
+}
+
+impl AlertRule {
+    /// Returns the value which makes the condition hold, None if it does not hold
+    /// 
+    /// # Parameters
+    /// 
+    /// stats: The statistics of the latest step
+    /// populations: The populations of the earlier steps by their ticks, oldest first
+    fn measure(&self, stats: &TickStats, populations: &VecDeque<(u64, usize)>) -> Option<f32> {
+        match *self {
+            AlertRule::PopulationDrop { fraction, within } => {
+                let start = stats.tick.saturating_sub(within);
+                let peak = populations.iter()
+                    .filter(|(tick, _)| *tick >= start)
+                    .map(|(_, population)| *population)
+                    .max()
+                    .unwrap_or(0);
+                if peak == 0 {
+                    return None;
+                }
+                let drop = 1.0 - stats.population as f32 / peak as f32;
+
+                (drop > fraction).then_some(drop)
+            }
+            AlertRule::PopulationBelow(limit) => (stats.population < limit).then_some(stats.population as f32),
+            AlertRule::DiversityBelow(limit) => (stats.diversity < limit).then_some(stats.diversity),
+            AlertRule::SpeciesBelow(limit) => (stats.species < limit).then_some(stats.species as f32),
+        }
+    }
+
+    /// Returns the number of ticks of populations the rule looks back at
+    fn window(&self) -> u64 {
+        match *self {
+            AlertRule::PopulationDrop { within, .. } => within,
+            _ => 0,
+        }
+    }
+}
+
+/// A rule which started to hold after a step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alert {
+    /// The tick of the step after which the rule started to hold
+    pub tick: u64,
+    /// The rule which started to hold
+    pub rule: AlertRule,
+    /// The value which made the rule hold, the fraction lost for a population drop
+    pub value: f32,
+}
+
+impl Alert {
+    /// Describes the alert in a single line of upper case text
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::alert::{Alert, AlertRule};
+    /// 
+    /// let alert = Alert { tick: 1200, rule: AlertRule::PopulationDrop { fraction: 0.5, within: 1000 }, value: 0.625 };
+    /// 
+    /// assert_eq!("POPULATION DROPPED 62% WITHIN 1000 TICKS", alert.message());
+    /// ```
+    pub fn message(&self) -> String {
+        match self.rule {
+            AlertRule::PopulationDrop { within, .. } => format!("POPULATION DROPPED {:.0}% WITHIN {} TICKS", (self.value * 100.0).floor(), within),
+            AlertRule::PopulationBelow(limit) => format!("POPULATION {:.0} BELOW {}", self.value, limit),
+            AlertRule::DiversityBelow(limit) => format!("DIVERSITY {:.3} BELOW {:.3}", self.value, limit),
+            AlertRule::SpeciesBelow(limit) => format!("SPECIES {:.0} BELOW {}", self.value, limit),
+        }
+    }
+}
+
+/// The settings for the alerts of a simulation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlertConfig {
+    /// The rules checked after every step the statistics are due
+    pub rules: Vec<AlertRule>,
+    /// The settings of the chain the state after every step raising an alert is saved to, None if the states are not saved
+    pub checkpoints: Option<CheckpointConfig>,
+}
+
+/// Checks the rules of the alerts against the statistics of every step. A rule raises an alert when it starts to hold
+/// and not again until it has stopped holding, so a population staying small raises a single alert
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlertMonitor {
+    /// The rules checked
+    rules: Vec<AlertRule>,
+    /// The populations of the latest steps by their ticks within the longest window of the rules, oldest first
+    populations: VecDeque<(u64, usize)>,
+    /// Whether every rule held at the latest check
+    holding: Vec<bool>,
+    /// All the alerts raised, oldest first
+    alerts: Vec<Alert>,
+    /// The states after the steps raising alerts if they are saved
+    checkpoints: Option<CheckpointChain>,
+}
+
+impl AlertMonitor {
+    /// Creates a new monitor which has not raised any alerts
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings for the alerts
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            holding: vec![false; config.rules.len()],
+            rules: config.rules,
+            populations: VecDeque::new(),
+            alerts: Vec::new(),
+            checkpoints: config.checkpoints.map(CheckpointChain::new),
+        }
+    }
+
+    /// Returns the rules checked
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Returns all the alerts raised, oldest first
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Returns the latest alert raised, None if no alerts have been raised
+    pub fn latest(&self) -> Option<&Alert> {
+        self.alerts.last()
+    }
+
+    /// Returns the states after the steps raising alerts, None if the states are not saved
+    pub fn checkpoints(&self) -> Option<&CheckpointChain> {
+        self.checkpoints.as_ref()
+    }
+
+    /// Returns the state after the step raising an alert, None if the states are not saved
+    /// 
+    /// # Parameters
+    /// 
+    /// alert: The alert to find the state of
+    pub fn state(&self, alert: &Alert) -> Option<StateSnapshot> {
+        self.checkpoints.as_ref()?.state(alert.tick)
+    }
+
+    /// Checks the rules against the statistics of a step and returns the alerts raised by it.
+    /// Statistics older than the ones seen before, such as after going back in time, replace the newer ones
+    /// 
+    /// # Parameters
+    /// 
+    /// stats: The statistics of the step
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::alert::{AlertConfig, AlertMonitor, AlertRule};
+    /// use evolution_plants::stats::TickStats;
+    /// 
+    /// let mut monitor = AlertMonitor::new(AlertConfig { rules: vec![AlertRule::PopulationBelow(10)], ..Default::default() });
+    /// let stats = |tick, population| TickStats { tick, population, births: 0, deaths: 0, mean_energy: 0.0, diversity: 0.0, mutation_rate: 0.0, species: 0, fittest: None };
+    /// 
+    /// assert!(monitor.check(&stats(1, 20)).is_empty());
+    /// assert_eq!(1, monitor.check(&stats(2, 5)).len());
+    /// // The rule still holds so no new alert is raised
+    /// assert!(monitor.check(&stats(3, 4)).is_empty());
+    /// assert!(monitor.check(&stats(4, 12)).is_empty());
+    /// assert_eq!(1, monitor.check(&stats(5, 8)).len());
+    /// ```
+    pub fn check(&mut self, stats: &TickStats) -> Vec<Alert> {
+        while self.populations.back().is_some_and(|(tick, _)| *tick >= stats.tick) {
+            self.populations.pop_back();
+        }
+        self.populations.push_back((stats.tick, stats.population));
+        let window = self.rules.iter().map(AlertRule::window).max().unwrap_or(0);
+        let start = stats.tick.saturating_sub(window);
+        while self.populations.front().is_some_and(|(tick, _)| *tick < start) {
+            self.populations.pop_front();
+        }
+
+        let mut raised = Vec::new();
+        for (rule, holding) in self.rules.iter().zip(self.holding.iter_mut()) {
+            let value = rule.measure(stats, &self.populations);
+            if let (Some(value), false) = (value, *holding) {
+                raised.push(Alert { tick: stats.tick, rule: *rule, value });
+            }
+            *holding = value.is_some();
+        }
+        self.alerts.extend_from_slice(&raised);
+
+        raised
+    }
+
+    /// Returns true if the states after the steps raising alerts are saved
+    pub(crate) fn saves_states(&self) -> bool {
+        self.checkpoints.is_some()
+    }
+
+    /// Saves the state after a step raising an alert if the states are saved
+    /// 
+    /// # Parameters
+    /// 
+    /// snapshot: The state after the step
+    pub(crate) fn preserve(&mut self, snapshot: &StateSnapshot) {
+        if let Some(checkpoints) = &mut self.checkpoints {
+            checkpoints.push(snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(tick: u64, population: usize, diversity: f32, species: usize) -> TickStats {
+        TickStats { tick, population, births: 0, deaths: 0, mean_energy: 0.0, diversity, mutation_rate: 0.0, species, fittest: None }
+    }
+
+    #[test]
+    fn alert_monitor_population_drop() {
+        let mut monitor = AlertMonitor::new(AlertConfig { rules: vec![AlertRule::PopulationDrop { fraction: 0.5, within: 10 }], ..Default::default() });
+
+        assert!(monitor.check(&stats(1, 100, 0.0, 0)).is_empty());
+        assert!(monitor.check(&stats(5, 60, 0.0, 0)).is_empty());
+        let raised = monitor.check(&stats(8, 40, 0.0, 0));
+
+        assert_eq!(vec![Alert { tick: 8, rule: AlertRule::PopulationDrop { fraction: 0.5, within: 10 }, value: 0.6 }], raised);
+        // The peak of 100 has left the window, the drop from 60 is too small
+        assert!(monitor.check(&stats(16, 40, 0.0, 0)).is_empty());
+        assert_eq!(1, monitor.alerts().len());
+    }
+
+    #[test]
+    fn alert_monitor_slow_decline() {
+        let mut monitor = AlertMonitor::new(AlertConfig { rules: vec![AlertRule::PopulationDrop { fraction: 0.5, within: 3 }], ..Default::default() });
+
+        for (tick, population) in [100, 80, 64, 51, 41, 33].into_iter().enumerate() {
+            assert!(monitor.check(&stats(tick as u64, population, 0.0, 0)).is_empty());
+        }
+    }
+
+    #[test]
+    fn alert_monitor_thresholds() {
+        let rules = vec![AlertRule::DiversityBelow(0.1), AlertRule::SpeciesBelow(2), AlertRule::PopulationBelow(5)];
+        let mut monitor = AlertMonitor::new(AlertConfig { rules, ..Default::default() });
+
+        assert!(monitor.check(&stats(1, 10, 0.2, 3)).is_empty());
+        assert_eq!(vec![AlertRule::DiversityBelow(0.1), AlertRule::SpeciesBelow(2)], monitor.check(&stats(2, 10, 0.05, 1)).iter().map(|alert| alert.rule).collect::<Vec<_>>());
+        assert_eq!(vec![3.0], monitor.check(&stats(3, 3, 0.05, 1)).iter().map(|alert| alert.value).collect::<Vec<_>>());
+        assert_eq!("SPECIES 1 BELOW 2", monitor.alerts()[1].message());
+        assert_eq!(Some(3), monitor.latest().map(|alert| alert.tick));
+    }
+
+    #[test]
+    fn alert_monitor_rewound() {
+        let mut monitor = AlertMonitor::new(AlertConfig { rules: vec![AlertRule::PopulationDrop { fraction: 0.5, within: 10 }], ..Default::default() });
+        monitor.check(&stats(1, 10, 0.0, 0));
+        monitor.check(&stats(2, 100, 0.0, 0));
+
+        // Going back to before the peak forgets it
+        assert!(monitor.check(&stats(2, 10, 0.0, 0)).is_empty());
+        assert!(monitor.check(&stats(3, 6, 0.0, 0)).is_empty());
+    }
+}
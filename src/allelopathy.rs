@@ -0,0 +1,182 @@
+use crate::board::{Rect, Size};
+use crate::field::Field;
+use crate::genome::{self, Genome};
+use crate::memory::HeapSize;
+use crate::population::Plant;
+
+/// The settings for allelopathy. Plants can release a toxin into the neighbouring cells which makes the plants
+/// there collect less light, the toxin decays over time. How much toxin a plant releases is set by its toxin gene,
+/// and releasing toxin costs upkeep, so plants trade energy for holding their neighbours back
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AllelopathyConfig {
+    /// The toxin a plant with a toxin gene of 1 releases into every neighbouring cell every step
+    pub emission: f32,
+    /// The extra energy a plant with a toxin gene of 1 pays every step, this scales with the toxin gene
+    pub cost: f32,
+    /// The fraction of the toxin in a cell which decays every step
+    pub decay_rate: f32,
+    /// How strongly the toxin holds back growth, the light collected is divided by 1 plus this times the toxin
+    pub inhibition: f32,
+}
+
+impl Default for AllelopathyConfig {
+    fn default() -> Self {
+        Self {
+            emission: 0.5,
+            cost: 2.0,
+            decay_rate: 0.2,
+            inhibition: 1.0,
+        }
+    }
+}
+
+impl AllelopathyConfig {
+    /// Finds how much a plant invests in toxin from 0 to 1, plants without a toxin gene release no toxin
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    pub fn investment(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_TOXIN).unwrap_or(0.0)
+    }
+
+    /// Finds the extra upkeep a plant pays in a step for releasing toxin
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant paying the upkeep
+    pub fn upkeep(&self, plant: &Plant) -> u32 {
+        (self.investment(&plant.genome) * self.cost.max(0.0)) as u32
+    }
+
+    /// Finds the factor the light collected by a plant is multiplied by for the toxin in its cell
+    /// 
+    /// # Parameters
+    /// 
+    /// toxin: The toxin in the cell of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::allelopathy::AllelopathyConfig;
+    /// 
+    /// let config = AllelopathyConfig { inhibition: 0.5, ..Default::default() };
+    /// 
+    /// assert_eq!(1.0, config.growth_factor(0.0));
+    /// assert_eq!(0.5, config.growth_factor(2.0));
+    /// ```
+    pub fn growth_factor(&self, toxin: f32) -> f32 {
+        1.0 / (1.0 + self.inhibition.max(0.0) * toxin.max(0.0))
+    }
+}
+
+/// The toxin released by the plants in every cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToxinField {
+    /// The toxin in every cell
+    toxin: Field,
+}
+
+impl ToxinField {
+    /// Creates a new field without any toxin
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn new(size: Size) -> Self {
+        Self { toxin: Field::filled(size, 0.0) }
+    }
+
+    /// Returns the toxin in every cell
+    pub fn values(&self) -> &[f32] {
+        &self.toxin
+    }
+
+    /// Returns the total amount of toxin on the board
+    pub fn total(&self) -> f32 {
+        self.toxin.sum()
+    }
+
+    /// Lets a plant release toxin into the cells next to it, toxin released past the edges of the board is lost
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell of the plant
+    /// amount: The toxin released into every neighbouring cell
+    pub(crate) fn emit(&mut self, index: usize, amount: f32) {
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            if let Some(neighbour) = self.toxin.offset(index, dx, dy) {
+                self.toxin[neighbour] += amount;
+            }
+        }
+    }
+
+    /// Lets a step of the toxin decay
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings for allelopathy
+    pub fn decay(&mut self, config: &AllelopathyConfig) {
+        let remaining = 1.0 - config.decay_rate.clamp(0.0, 1.0);
+
+        for toxin in self.toxin.iter_mut() {
+            *toxin *= remaining;
+        }
+    }
+
+    /// Puts the toxin of some cells back to an earlier state
+    pub(crate) fn restore(&mut self, earlier: &Self, cells: &[usize]) {
+        for &index in cells {
+            self.toxin[index] = earlier.toxin[index];
+        }
+    }
+
+    /// Makes a rectangle of the board the new board, the cells outside the board have no toxin
+    pub(crate) fn reframe(&mut self, rect: Rect) {
+        self.toxin.reframe(rect, || 0.0);
+    }
+}
+
+impl HeapSize for ToxinField {
+    fn heap_bytes(&self) -> usize {
+        self.toxin.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toxin_field_emit_decay() {
+        let mut field = ToxinField::new(Size::new(3, 2));
+        field.emit(0, 1.0);
+        field.emit(4, 0.5);
+
+        assert_eq!(&[0.0, 1.5, 0.0, 1.5, 0.0, 0.5], field.values());
+
+        field.decay(&AllelopathyConfig { decay_rate: 0.5, ..Default::default() });
+
+        assert_eq!(&[0.0, 0.75, 0.0, 0.75, 0.0, 0.25], field.values());
+        assert_eq!(1.75, field.total());
+    }
+
+    #[test]
+    fn allelopathy_investment_upkeep() {
+        let config = AllelopathyConfig { cost: 4.0, ..Default::default() };
+        let mut genes = [0.0; 11];
+        genes[genome::GENE_TOXIN] = 0.5;
+
+        assert_eq!(0.0, config.investment(&Genome::new(&[0.5, 0.5]).unwrap()));
+        assert_eq!(2, config.upkeep(&Plant::new(10, Genome::new(&genes).unwrap())));
+    }
+
+    #[test]
+    fn toxin_field_reframe() {
+        let mut field = ToxinField::new(Size::new(2, 2));
+        field.emit(1, 1.0);
+        field.reframe(Rect::new(1, 0, 1, 2));
+
+        assert_eq!(&[0.0, 1.0], field.values());
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+
+use crate::board::Board;
+use crate::genome::{Genome, MutationConfig};
+use crate::population::{Plant, PlantId, Population};
+use crate::simulation::{Simulation, SimulationConfig, SimulationCreateError};
+
+/// A straight line fitted to a time series
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trend {
+    /// The change of the value per sample
+    pub slope: f64,
+    /// The fitted value at the first sample
+    pub intercept: f64,
+}
+
+impl Trend {
+    /// Fits a straight line to a time series with least squares, returns None if there are fewer than two samples
+    /// 
+    /// # Parameters
+    /// 
+    /// series: The values of the time series in order
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::analysis::Trend;
+    /// 
+    /// let trend = Trend::fit(&[1.0, 3.0, 5.0]).unwrap();
+    /// 
+    /// assert_eq!(2.0, trend.slope);
+    /// assert_eq!(1.0, trend.intercept);
+    /// ```
+    pub fn fit(series: &[f64]) -> Option<Self> {
+        if series.len() < 2 {
+            return None;
+        }
+
+        let n = series.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = series.iter().sum::<f64>() / n;
+
+        let (covariance, variance) = series.iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(covariance, variance), (x, y)| {
+                let dx = x as f64 - mean_x;
+                (covariance + dx * (y - mean_y), variance + dx * dx)
+            });
+
+        let slope = covariance / variance;
+
+        Some(Self { slope, intercept: mean_y - slope * mean_x })
+    }
+}
+
+/// Finds the points where the mean of a time series changes using the PELT method with a squared error cost,
+/// returns the index of the first sample of every new segment in increasing order
+/// 
+/// # Parameters
+/// 
+/// series: The values of the time series in order
+/// penalty: The cost of adding a changepoint, higher values give fewer changepoints.
+/// A good starting point is 2 log(n) times the variance of the noise
+/// min_segment: The smallest number of samples between two changepoints, a value of 0 is treated as 1
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::analysis;
+/// 
+/// let series = [1.0, 1.1, 0.9, 1.0, 5.0, 5.1, 4.9, 5.0];
+/// 
+/// assert_eq!(vec![4], analysis::changepoints(&series, 1.0, 2));
+/// ```
+pub fn changepoints(series: &[f64], penalty: f64, min_segment: usize) -> Vec<usize> {
+    let n = series.len();
+    let min_segment = min_segment.max(1);
+
+    // Prefix sums give the cost of any segment in constant time
+    let mut sums = vec![(0.0, 0.0); n + 1];
+    for (index, value) in series.iter().enumerate() {
+        sums[index + 1] = (sums[index].0 + value, sums[index].1 + value * value);
+    }
+
+    let cost = |start: usize, end: usize| {
+        let len = (end - start) as f64;
+        let sum = sums[end].0 - sums[start].0;
+        let squares = sums[end].1 - sums[start].1;
+
+        (squares - sum * sum / len).max(0.0)
+    };
+
+    // The best total cost of the series up to every index and the start of its last segment
+    let mut best = vec![f64::INFINITY; n + 1];
+    let mut last = vec![0; n + 1];
+    best[0] = -penalty;
+    let mut candidates = vec![0];
+
+    for end in min_segment..=n {
+        let mut found = None;
+        for &start in candidates.iter().filter(|&&start| end - start >= min_segment) {
+            let total = best[start] + cost(start, end) + penalty;
+            if found.is_none_or(|(best_total, _)| total < best_total) {
+                found = Some((total, start));
+            }
+        }
+
+        if let Some((total, start)) = found {
+            best[end] = total;
+            last[end] = start;
+        }
+
+        // Remove the candidates which can never be the best start again
+        let current = best[end];
+        candidates.retain(|&start| end - start < min_segment || best[start] + cost(start, end) <= current);
+
+        candidates.push(end);
+    }
+
+    // Follow the segments back from the end
+    let mut points = Vec::new();
+    let mut end = n;
+    while end > 0 && best[end].is_finite() {
+        let start = last[end];
+        if start > 0 {
+            points.push(start);
+        }
+        end = start;
+    }
+    points.reverse();
+
+    points
+}
+
+/// How well the lineage of a single genome did in a sandboxed simulation
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FitnessReport {
+    /// The number of steps the lineage had at least one living plant
+    pub survived: u64,
+    /// The number of plants of the lineage alive at the end
+    pub population: usize,
+    /// The mean number of plants of the lineage alive after every step
+    pub mean_population: f64,
+    /// The number of plants born into the lineage
+    pub births: usize,
+    /// The energy stored by all plants of the lineage at the end
+    pub energy: u64,
+}
+
+impl FitnessReport {
+    /// Returns the fitness of the lineage as a single number, this is the mean population
+    pub fn fitness(&self) -> f64 {
+        self.mean_population
+    }
+}
+
+/// The settings for running a genome in a sandboxed simulation to measure its fitness.
+/// The founder is placed as close to the middle of the board as possible and the run is deterministic
+/// for a given seed, so the fitness of a genome before and after a mutation can be compared directly
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitnessProbe {
+    /// The settings of the sandboxed simulation, the default has no mutation such that only the genome is measured
+    pub config: SimulationConfig,
+    /// The energy of the founder
+    pub energy: u32,
+    /// A frozen population the lineage competes against, these plants never die, age or reproduce.
+    /// It must have the size of the board and the genome is run in isolation if it is empty
+    pub background: Option<Population>,
+}
+
+impl Default for FitnessProbe {
+    fn default() -> Self {
+        Self {
+            config: SimulationConfig { mutation: MutationConfig::new(0.0, 0.0), ..Default::default() },
+            energy: 100,
+            background: None,
+        }
+    }
+}
+
+impl FitnessProbe {
+    /// Runs a genome on a copy of a board and reports how well its lineage did
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the founder
+    /// board: The board to run on, it is not changed
+    /// ticks: The number of steps to run
+    /// 
+    /// # Errors
+    /// 
+    /// SimulationCreateError::Size: This will occur if the background population does not have the size of the board
+    /// 
+    /// SimulationCreateError::Blocked: This will occur if a plant of the background population is on blocked ground
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{analysis::FitnessProbe, board::{Board, Multipliers, Fields, Size}, genome::Genome};
+    /// 
+    /// let size = Size::new(5, 5);
+    /// let board = Board::new(Multipliers::new(100).unwrap(), Fields::new(size, &[1.0; 25]).unwrap());
+    /// let report = FitnessProbe::default().evaluate(&Genome::new(&[0.1, 0.5]).unwrap(), &board, 20).unwrap();
+    /// 
+    /// assert_eq!(20, report.survived);
+    /// assert!(report.births > 0);
+    /// ```
+    pub fn evaluate(&self, genome: &Genome, board: &Board, ticks: u64) -> Result<FitnessReport, SimulationCreateError> {
+        let size = board.fields.size;
+        let background = self.background.clone().unwrap_or_else(|| Population::new(size));
+        let mut simulation = Simulation::new(board.clone(), background, self.config.clone())?;
+        let frozen: Vec<(usize, Plant)> = simulation.population()
+            .iter()
+            .map(|(coord, plant)| (size.index_of(coord).unwrap(), plant.clone()))
+            .collect();
+
+        // Place the founder in the free cell closest to the middle
+        let middle = (size.size().0 as f32 / 2.0, size.size().1 as f32 / 2.0);
+        let mut cells: Vec<usize> = (0..size.len()).collect();
+        cells.sort_by(|&a, &b| {
+            let distance = |index: usize| {
+                let coord = size.coord_of(index).unwrap();
+                (coord.x as f32 + 0.5 - middle.0).powi(2) + (coord.y as f32 + 0.5 - middle.1).powi(2)
+            };
+            distance(a).total_cmp(&distance(b))
+        });
+
+        let founder = match cells.into_iter().find(|&index| simulation.plant_founder(index, Plant::new(self.energy, genome.clone()))) {
+            Some(index) => index,
+            None => return Ok(FitnessReport::default()),
+        };
+
+        let mut lineage = HashSet::new();
+        lineage.insert(simulation.population().get(size.coord_of(founder).unwrap()).unwrap().id());
+        let frozen_ids: HashSet<PlantId> = frozen.iter().map(|(_, plant)| plant.id()).collect();
+
+        let mut report = FitnessReport::default();
+        let mut total = 0;
+        for _ in 0..ticks {
+            simulation.step();
+
+            // Put the frozen plants back as they were and remove their seeds
+            for (index, plant) in &frozen {
+                simulation.restore_plant(*index, Some(plant.clone()));
+            }
+
+            let mut alive = 0;
+            for index in 0..size.len() {
+                let (id, parent) = match simulation.population().get(size.coord_of(index).unwrap()) {
+                    Some(plant) => (plant.id(), plant.parent()),
+                    None => continue,
+                };
+
+                if frozen_ids.contains(&id) {
+                    continue;
+                }
+
+                if lineage.contains(&id) {
+                    alive += 1;
+                } else if parent.is_some_and(|parent| lineage.contains(&parent)) {
+                    lineage.insert(id);
+                    report.births += 1;
+                    alive += 1;
+                } else {
+                    simulation.remove_plant(index);
+                }
+            }
+
+            if alive == 0 {
+                break;
+            }
+
+            report.survived += 1;
+            total += alive;
+        }
+
+        let plants = simulation.population().iter().filter(|(_, plant)| lineage.contains(&plant.id()));
+        for (_, plant) in plants {
+            report.population += 1;
+            report.energy += plant.energy as u64;
+        }
+        if ticks > 0 {
+            report.mean_population = total as f64 / ticks as f64;
+        }
+
+        Ok(report)
+    }
+
+    /// Measures the fitness of a genome with one gene set to evenly spaced values between 0 and 1,
+    /// giving a slice through the local fitness landscape around the genome
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to vary
+    /// gene: The index of the gene to vary
+    /// samples: The number of values of the gene, at least 2 values are used
+    /// board: The board to run on, it is not changed
+    /// ticks: The number of steps to run for every value
+    /// 
+    /// # Errors
+    /// 
+    /// See evaluate
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the genome does not have the gene
+    pub fn landscape(&self, genome: &Genome, gene: usize, samples: usize, board: &Board, ticks: u64) -> Result<Vec<(f32, FitnessReport)>, SimulationCreateError> {
+        assert!(gene < genome.genes().len(), "The genome must have the gene");
+
+        let samples = samples.max(2);
+        (0..samples)
+            .map(|sample| {
+                let value = sample as f32 / (samples - 1) as f32;
+                let mut genes = genome.genes().to_vec();
+                genes[gene] = value;
+                let varied = Genome::new(&genes).expect("The genes are between 0 and 1");
+
+                Ok((value, self.evaluate(&varied, board, ticks)?))
+            })
+            .collect()
+    }
+}
+
+/// Runs a genome alone on a copy of a board with the default probe and reports how well its lineage did
+/// 
+/// # Parameters
+/// 
+/// genome: The genome of the founder
+/// board: The board to run on, it is not changed
+/// ticks: The number of steps to run
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{analysis, board::{Board, Multipliers, Fields, Size}, genome::Genome};
+/// 
+/// let size = Size::new(5, 5);
+/// let board = Board::new(Multipliers::new(100).unwrap(), Fields::new(size, &[0.0; 25]).unwrap());
+/// let report = analysis::evaluate_genome(&Genome::new(&[0.5, 0.5]).unwrap(), &board, 50);
+/// 
+/// // Nothing grows in the dark
+/// assert!(report.survived < 50);
+/// assert_eq!(0, report.population);
+/// ```
+pub fn evaluate_genome(genome: &Genome, board: &Board, ticks: u64) -> FitnessReport {
+    FitnessProbe::default().evaluate(genome, board, ticks).expect("An empty population always fits the board")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Fields, Multipliers, Size};
+
+    fn board(light: f32) -> Board {
+        let size = Size::new(5, 5);
+
+        Board::new(Multipliers::new(100).unwrap(), Fields::new(size, &vec![light; size.len()]).unwrap())
+    }
+
+    #[test]
+    fn fitness_probe_deterministic() {
+        let probe = FitnessProbe::default();
+        let genome = Genome::new(&[0.2, 0.5]).unwrap();
+
+        assert_eq!(probe.evaluate(&genome, &board(1.0), 30).unwrap(), probe.evaluate(&genome, &board(1.0), 30).unwrap());
+    }
+
+    #[test]
+    fn fitness_probe_background() {
+        // A frozen wall of plants leaves only one free cell, so the lineage can never grow
+        let size = Size::new(5, 5);
+        let mut background = Population::new(size);
+        for index in 1..size.len() {
+            background.insert(size.coord_of(index).unwrap(), Plant::new(1000, Genome::new(&[0.0, 0.5]).unwrap()));
+        }
+        let probe = FitnessProbe { background: Some(background.clone()), ..Default::default() };
+        let report = probe.evaluate(&Genome::new(&[0.1, 0.5]).unwrap(), &board(1.0), 10).unwrap();
+
+        assert_eq!(0, report.births);
+        assert_eq!(1, report.population);
+        assert_eq!(10, report.survived);
+    }
+
+    #[test]
+    fn fitness_probe_background_size() {
+        let probe = FitnessProbe { background: Some(Population::new(Size::new(2, 2))), ..Default::default() };
+
+        assert!(probe.evaluate(&Genome::new(&[0.1, 0.5]).unwrap(), &board(1.0), 10).is_err());
+    }
+
+    #[test]
+    fn fitness_probe_landscape() {
+        let landscape = FitnessProbe::default().landscape(&Genome::new(&[0.5, 0.5]).unwrap(), 0, 3, &board(1.0), 20).unwrap();
+
+        assert_eq!(vec![0.0, 0.5, 1.0], landscape.iter().map(|(value, _)| *value).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn evaluate_genome_dark() {
+        let report = evaluate_genome(&Genome::new(&[0.5, 0.5]).unwrap(), &board(0.0), 100);
+
+        assert_eq!(0, report.population);
+        assert_eq!(0, report.births);
+        assert!(report.survived < 100);
+    }
+
+    #[test]
+    fn trend_fit() {
+        let trend = Trend::fit(&[4.0, 3.0, 2.0, 1.0]).unwrap();
+
+        assert_eq!(-1.0, trend.slope);
+        assert_eq!(4.0, trend.intercept);
+    }
+
+    #[test]
+    fn trend_fit_short() {
+        assert_eq!(None, Trend::fit(&[]));
+        assert_eq!(None, Trend::fit(&[1.0]));
+    }
+
+    #[test]
+    fn changepoints_none() {
+        let series = [2.0, 2.1, 1.9, 2.0, 2.05, 1.95, 2.0, 2.0];
+
+        assert!(changepoints(&series, 1.0, 2).is_empty());
+    }
+
+    #[test]
+    fn changepoints_steps() {
+        let mut series = vec![0.0; 20];
+        series.extend(vec![10.0; 15]);
+        series.extend(vec![-5.0; 10]);
+
+        assert_eq!(vec![20, 35], changepoints(&series, 5.0, 3));
+    }
+
+    #[test]
+    fn changepoints_penalty() {
+        let series = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+
+        assert_eq!(vec![3], changepoints(&series, 0.5, 1));
+        assert!(changepoints(&series, 10.0, 1).is_empty());
+    }
+
+    #[test]
+    fn changepoints_min_segment() {
+        // A single outlier is not a segment of its own when segments must be longer
+        let series = [0.0, 0.0, 0.0, 0.0, 9.0, 0.0, 0.0, 0.0, 0.0];
+
+        assert_eq!(vec![4, 5], changepoints(&series, 1.0, 1));
+        assert!(changepoints(&series, 1.0, 3).windows(2).all(|pair| pair[1] - pair[0] >= 3));
+    }
+
+    #[test]
+    fn changepoints_empty() {
+        assert!(changepoints(&[], 1.0, 1).is_empty());
+        assert!(changepoints(&[1.0], 1.0, 5).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::genome::Genome;
+use crate::memory::{vec_bytes, HeapSize};
+use crate::phylogeny::Phylogeny;
+use crate::population::{Plant, PlantId};
+
+/// What a plant is scored by when deciding whether its genome belongs in the hall of fame
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Criterion {
+    /// The number of steps the plant survived
+    #[default]
+    Longevity,
+    /// The number of children of the plant in the lineage tree
+    Descendants,
+    /// The total energy the plant collected during its life
+    EnergyGathered,
+}
+
+impl Criterion {
+    /// Finds the score of a plant, higher is better
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant to score
+    /// phylogeny: The lineage tree the children of the plant are counted in
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{archive::Criterion, genome::Genome, phylogeny::Phylogeny, population::Plant};
+    /// 
+    /// let mut plant = Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap());
+    /// plant.age = 12;
+    /// plant.gathered = 340;
+    /// 
+    /// assert_eq!(12, Criterion::Longevity.score(&plant, &Phylogeny::new()));
+    /// assert_eq!(340, Criterion::EnergyGathered.score(&plant, &Phylogeny::new()));
+    /// assert_eq!(0, Criterion::Descendants.score(&plant, &Phylogeny::new()));
+    /// ```
+    pub fn score(&self, plant: &Plant, phylogeny: &Phylogeny) -> u64 {
+        match self {
+            Criterion::Longevity => plant.age,
+            Criterion::Descendants => phylogeny.get(plant.id()).map_or(0, |lineage| lineage.children.len() as u64),
+            Criterion::EnergyGathered => plant.gathered,
+        }
+    }
+}
+
+/// The settings for keeping the best genomes of a run
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArchiveConfig {
+    /// The largest number of genomes kept
+    pub capacity: usize,
+    /// What the plants are scored by
+    pub criterion: Criterion,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 10,
+            criterion: Criterion::default(),
+        }
+    }
+}
+
+/// A genome kept in the hall of fame
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArchiveEntry {
+    /// The id of the plant which had the genome
+    pub id: PlantId,
+    /// The genome of the plant
+    pub genome: Genome,
+    /// The score of the plant by the criterion of the hall of fame
+    pub score: u64,
+    /// The tick at which the plant died
+    pub death: u64,
+}
+
+/// The best genomes ever seen in a run, a plant is scored when it dies and its genome is kept if it is among
+/// the best scores so far. It is saved with the history of the simulation so going back in time restores it
+#[derive(Clone, Debug, PartialEq)]
+pub struct HallOfFame {
+    /// The settings of the hall of fame
+    config: ArchiveConfig,
+    /// The kept genomes with the best first, equal scores are in the order they were added
+    entries: Vec<ArchiveEntry>,
+}
+
+impl HallOfFame {
+    /// Creates a new empty hall of fame
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the hall of fame
+    pub fn new(config: ArchiveConfig) -> Self {
+        Self { config, entries: Vec::new() }
+    }
+
+    /// Returns the settings of the hall of fame
+    pub fn config(&self) -> ArchiveConfig {
+        self.config
+    }
+
+    /// Returns the kept genomes with the best first
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// Returns the kept genomes with the best first, ready to seed a new simulation with
+    pub fn genomes(&self) -> Vec<Genome> {
+        self.entries.iter().map(|entry| entry.genome.clone()).collect()
+    }
+
+    /// Returns the number of kept genomes
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no genomes are kept
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an entry if its score is among the best, the worst entry is dropped when there are too many.
+    /// Returns true if the entry was kept
+    /// 
+    /// # Parameters
+    /// 
+    /// entry: The entry to add
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{archive::{ArchiveConfig, ArchiveEntry, HallOfFame}, genome::Genome, population::PlantId};
+    /// 
+    /// let mut hall = HallOfFame::new(ArchiveConfig { capacity: 2, ..Default::default() });
+    /// let entry = |id, score| ArchiveEntry { id: PlantId(id), genome: Genome::new(&[0.5, 0.5]).unwrap(), score, death: 0 };
+    /// 
+    /// assert!(hall.consider(entry(0, 5)));
+    /// assert!(hall.consider(entry(1, 9)));
+    /// assert!(!hall.consider(entry(2, 5)));
+    /// assert!(hall.consider(entry(3, 7)));
+    /// 
+    /// assert_eq!(vec![PlantId(1), PlantId(3)], hall.entries().iter().map(|entry| entry.id).collect::<Vec<_>>());
+    /// ```
+    pub fn consider(&mut self, entry: ArchiveEntry) -> bool {
+        let position = self.entries.partition_point(|kept| kept.score >= entry.score);
+        if position >= self.config.capacity {
+            return false;
+        }
+
+        self.entries.insert(position, entry);
+        self.entries.truncate(self.config.capacity);
+
+        true
+    }
+
+    /// Scores a plant which died and keeps its genome if the score is among the best
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant which died
+    /// phylogeny: The lineage tree of the simulation
+    /// tick: The tick at which the plant died
+    pub(crate) fn record(&mut self, plant: &Plant, phylogeny: &Phylogeny, tick: u64) -> bool {
+        let score = self.config.criterion.score(plant, phylogeny);
+
+        self.consider(ArchiveEntry { id: plant.id(), genome: plant.genome.clone(), score, death: tick })
+    }
+}
+
+impl HeapSize for HallOfFame {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, score: u64) -> ArchiveEntry {
+        ArchiveEntry { id: PlantId(id), genome: Genome::new(&[0.5, 0.5]).unwrap(), score, death: 0 }
+    }
+
+    fn ids(hall: &HallOfFame) -> Vec<u64> {
+        hall.entries().iter().map(|entry| entry.id.0).collect()
+    }
+
+    #[test]
+    fn hall_of_fame_consider_order() {
+        let mut hall = HallOfFame::new(ArchiveConfig { capacity: 3, ..Default::default() });
+        for (id, score) in [(0, 2), (1, 4), (2, 2), (3, 1), (4, 3)] {
+            hall.consider(entry(id, score));
+        }
+
+        assert_eq!(vec![1, 4, 0], ids(&hall));
+    }
+
+    #[test]
+    fn hall_of_fame_capacity_zero() {
+        let mut hall = HallOfFame::new(ArchiveConfig { capacity: 0, ..Default::default() });
+
+        assert!(!hall.consider(entry(0, 10)));
+        assert!(hall.is_empty());
+    }
+
+    #[test]
+    fn hall_of_fame_record_descendants() {
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+        phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 1, genome.clone());
+        phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 2, genome.clone());
+        let mut hall = HallOfFame::new(ArchiveConfig { capacity: 1, criterion: Criterion::Descendants });
+
+        assert!(hall.record(&Plant::new(10, genome), &phylogeny, 5));
+        assert_eq!(2, hall.entries()[0].score);
+        assert_eq!(5, hall.entries()[0].death);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::profile::Phase;
+
+/// The energy on the board when a phase of a step started and ended together with the energy which entered and left
+/// the board during the phase. The energy on the board is the energy of the plants, the dormant seeds and the seeds
+/// which have been produced but have not landed yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseLedger {
+    /// The phase
+    pub phase: Phase,
+    /// The energy on the board when the phase started
+    pub before: u64,
+    /// The energy the plants collected from the light
+    pub light: u64,
+    /// The energy the plants spent on upkeep, on growing, on producing seeds and lost when sharing energy
+    pub respiration: u64,
+    /// The energy of the plants which died and the seeds which did not survive
+    pub decay: u64,
+    /// The energy of the seeds which left the board for another board
+    pub exported: u64,
+    /// The energy on the board when the phase ended
+    pub after: u64,
+}
+
+impl PhaseLedger {
+    /// Returns the energy which should be on the board when the phase ended given the flows of energy during the phase
+    pub fn expected(&self) -> i64 {
+        self.before as i64 + self.light as i64 - self.respiration as i64 - self.decay as i64 - self.exported as i64
+    }
+
+    /// Returns the energy on the board when the phase ended which is not explained by the flows of energy,
+    /// positive if energy was created and negative if energy disappeared
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{audit::PhaseLedger, profile::Phase};
+    /// 
+    /// let ledger = PhaseLedger { phase: Phase::Energy, before: 100, light: 30, respiration: 0, decay: 10, exported: 0, after: 125 };
+    /// 
+    /// assert_eq!(120, ledger.expected());
+    /// assert_eq!(5, ledger.discrepancy());
+    /// ```
+    pub fn discrepancy(&self) -> i64 {
+        self.after as i64 - self.expected()
+    }
+}
+
+/// The books of the energy of a single step, one ledger for every phase in the order they ran.
+/// The fields are updated twice in a step so that phase has two ledgers
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditReport {
+    /// The tick the step reached
+    pub tick: u64,
+    /// The ledgers of the phases in the order they ran
+    pub phases: Vec<PhaseLedger>,
+}
+
+impl AuditReport {
+    /// Returns the energy the plants collected from the light during the step
+    pub fn light(&self) -> u64 {
+        self.phases.iter().map(|ledger| ledger.light).sum()
+    }
+
+    /// Returns the energy spent by the plants during the step
+    pub fn respiration(&self) -> u64 {
+        self.phases.iter().map(|ledger| ledger.respiration).sum()
+    }
+
+    /// Returns the energy of the plants and seeds which died during the step
+    pub fn decay(&self) -> u64 {
+        self.phases.iter().map(|ledger| ledger.decay).sum()
+    }
+
+    /// Returns the energy of the seeds which left the board during the step
+    pub fn exported(&self) -> u64 {
+        self.phases.iter().map(|ledger| ledger.exported).sum()
+    }
+
+    /// Returns the energy not explained by the flows of energy summed over all phases
+    pub fn discrepancy(&self) -> i64 {
+        self.phases.iter().map(PhaseLedger::discrepancy).sum()
+    }
+
+    /// Finds the phase with the largest discrepancy, None if every phase balances exactly
+    pub fn worst(&self) -> Option<&PhaseLedger> {
+        self.phases.iter()
+            .filter(|ledger| ledger.discrepancy() != 0)
+            .max_by_key(|ledger| ledger.discrepancy().unsigned_abs())
+    }
+
+    /// Returns true if the discrepancy of every phase is at most epsilon
+    /// 
+    /// # Parameters
+    /// 
+    /// epsilon: The largest discrepancy allowed in a phase
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{audit::{AuditReport, PhaseLedger}, profile::Phase};
+    /// 
+    /// let energy = PhaseLedger { phase: Phase::Energy, before: 100, light: 30, respiration: 0, decay: 0, exported: 0, after: 130 };
+    /// let death = PhaseLedger { phase: Phase::Death, before: 130, light: 0, respiration: 20, decay: 12, exported: 0, after: 100 };
+    /// let report = AuditReport { tick: 1, phases: vec![energy, death] };
+    /// 
+    /// assert_eq!(2, report.discrepancy());
+    /// assert_eq!(Some(&death), report.worst());
+    /// assert!(!report.is_balanced(1));
+    /// assert!(report.is_balanced(2));
+    /// ```
+    pub fn is_balanced(&self, epsilon: u64) -> bool {
+        self.phases.iter().all(|ledger| ledger.discrepancy().unsigned_abs() <= epsilon)
+    }
+
+    /// Creates the lines of a table with the flows of every phase and its discrepancy
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("{:<13}{:>12}{:>10}{:>12}{:>10}{:>10}{:>12}{:>8}", "phase", "before", "light", "respiration", "decay", "exported", "after", "error")];
+
+        lines.extend(self.phases.iter().map(|ledger| format!(
+            "{:<13}{:>12}{:>10}{:>12}{:>10}{:>10}{:>12}{:>8}",
+            ledger.phase.name(), ledger.before, ledger.light, ledger.respiration, ledger.decay, ledger.exported, ledger.after, ledger.discrepancy(),
+        )));
+
+        lines
+    }
+}
+
+/// The energy which entered and left the board since the latest phase was closed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Flows {
+    /// The energy collected from the light
+    pub light: u64,
+    /// The energy spent by the plants
+    pub respiration: u64,
+    /// The energy of the plants and seeds which died
+    pub decay: u64,
+    /// The energy of the seeds which left the board
+    pub exported: u64,
+}
+
+/// Keeps the books of the energy of the steps while auditing, the ledger of a phase is closed when the phase ends
+/// and the report of the step is kept until the next step
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct EnergyAudit {
+    /// The largest discrepancy allowed in a phase
+    epsilon: u64,
+    /// The energy which entered and left the board in the running phase
+    pub flows: Flows,
+    /// The energy on the board when the running phase started
+    stock: u64,
+    /// The ledgers of the phases of the running step which have ended
+    phases: Vec<PhaseLedger>,
+    /// The report of the latest step
+    latest: Option<AuditReport>,
+}
+
+impl EnergyAudit {
+    /// Creates a new audit without any steps
+    /// 
+    /// # Parameters
+    /// 
+    /// epsilon: The largest discrepancy allowed in a phase
+    pub fn new(epsilon: u64) -> Self {
+        Self { epsilon, flows: Flows::default(), stock: 0, phases: Vec::new(), latest: None }
+    }
+
+    /// Returns the largest discrepancy allowed in a phase
+    pub fn epsilon(&self) -> u64 {
+        self.epsilon
+    }
+
+    /// Returns the report of the latest step
+    pub fn latest(&self) -> Option<&AuditReport> {
+        self.latest.as_ref()
+    }
+
+    /// Forgets the report of the latest step
+    pub fn clear(&mut self) {
+        self.latest = None;
+    }
+
+    /// Starts the books of a step, the flows counted before are forgotten
+    /// 
+    /// # Parameters
+    /// 
+    /// stock: The energy on the board when the step started
+    pub fn begin(&mut self, stock: u64) {
+        self.flows = Flows::default();
+        self.stock = stock;
+        self.phases.clear();
+    }
+
+    /// Closes the ledger of a phase with the flows counted since the previous phase ended
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase which ended
+    /// stock: The energy on the board when the phase ended
+    pub fn close(&mut self, phase: Phase, stock: u64) {
+        let flows = std::mem::take(&mut self.flows);
+        self.phases.push(PhaseLedger {
+            phase,
+            before: self.stock,
+            light: flows.light,
+            respiration: flows.respiration,
+            decay: flows.decay,
+            exported: flows.exported,
+            after: stock,
+        });
+        self.stock = stock;
+    }
+
+    /// Ends the books of a step and returns its report
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick the step reached
+    pub fn finish(&mut self, tick: u64) -> &AuditReport {
+        self.latest.insert(AuditReport { tick, phases: std::mem::take(&mut self.phases) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn energy_audit_books() {
+        let mut audit = EnergyAudit::new(0);
+        audit.begin(100);
+        audit.flows.light = 40;
+        audit.close(Phase::Energy, 140);
+        audit.flows.respiration = 15;
+        audit.flows.decay = 25;
+        audit.close(Phase::Death, 100);
+        audit.flows.exported = 5;
+        audit.close(Phase::Reproduction, 96);
+        let report = audit.finish(3).clone();
+
+        assert_eq!(3, report.tick);
+        assert_eq!(vec![Phase::Energy, Phase::Death, Phase::Reproduction], report.phases.iter().map(|ledger| ledger.phase).collect::<Vec<_>>());
+        assert_eq!((40, 15, 25, 5), (report.light(), report.respiration(), report.decay(), report.exported()));
+        assert_eq!(1, report.discrepancy());
+        assert_eq!(Phase::Reproduction, report.worst().unwrap().phase);
+        assert!(!report.is_balanced(audit.epsilon()));
+        assert_eq!(4, report.lines().len());
+        assert_eq!(Some(&report), audit.latest());
+
+        audit.begin(96);
+        audit.flows.light = 4;
+        audit.close(Phase::Energy, 100);
+
+        assert!(audit.finish(4).is_balanced(0));
+        assert_eq!(None, audit.latest().unwrap().worst());
+        audit.clear();
+        assert_eq!(None, audit.latest());
+    }
+}
@@ -0,0 +1,334 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use thiserror::Error;
+
+use crate::checkpoint::{CheckpointChain, CheckpointConfig, CheckpointError};
+use crate::snapshot::StateSnapshot;
+
+/// The start of the name of every autosave file
+pub const AUTOSAVE_PREFIX: &str = "autosave-";
+/// The extension of every autosave file
+pub const AUTOSAVE_EXTENSION: &str = "epck";
+/// The extension of an autosave file while it is being written
+const TEMPORARY_EXTENSION: &str = "tmp";
+
+/// Saves checkpoints of a simulation to a directory on a background thread such that the state a long run reached
+/// can be inspected after a crash. Every file is written under a temporary name, flushed to the disk and then renamed, so a file
+/// with the final name is always complete. Only the latest files are kept. Autosaving is not copied when a simulation is cloned.
+/// The checkpoints hold the plants, the water, the nutrients and the position of the random number generator but not the settings,
+/// the toxin, the species, the phylogeny or the other trackers, so a simulation cannot be resumed from them
+#[derive(Default)]
+pub(crate) struct Autosave {
+    /// The number of ticks between every save, 0 if autosaving is disabled
+    every: u64,
+    /// The channel the snapshots are sent to the background thread through
+    sender: Option<SyncSender<StateSnapshot>>,
+    /// The background thread writing the files
+    thread: Option<JoinHandle<()>>,
+    /// The latest error of the background thread
+    error: Arc<Mutex<Option<AutosaveError>>>,
+    /// The tick of the latest snapshot sent to the background thread
+    last_saved: Option<u64>,
+}
+
+impl Autosave {
+    /// Starts the background thread saving to a directory, the directory is created if it does not exist
+    /// 
+    /// # Parameters
+    /// 
+    /// dir: The directory to save to
+    /// every: The number of ticks between every save, 0 disables autosaving
+    /// keep_last: The number of the latest files to keep, at least 1 is kept
+    /// 
+    /// # Errors
+    /// 
+    /// AutosaveError::Io: This will occur if the directory could not be created
+    pub fn start(dir: &Path, every: u64, keep_last: usize) -> Result<Self, AutosaveError> {
+        if every == 0 {
+            return Ok(Self::default());
+        }
+        fs::create_dir_all(dir).map_err(|error| AutosaveError::io(dir, error))?;
+
+        // At most one snapshot waits while a file is written, the simulation waits for slower disks instead of using more memory
+        let (sender, receiver) = mpsc::sync_channel::<StateSnapshot>(1);
+        let error = Arc::new(Mutex::new(None));
+        let thread_error = Arc::clone(&error);
+        let dir = dir.to_path_buf();
+        let thread = thread::spawn(move || {
+            for snapshot in receiver {
+                if let Err(error) = write(&dir, &snapshot, keep_last) {
+                    *thread_error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(error);
+                }
+            }
+        });
+
+        Ok(Self { every, sender: Some(sender), thread: Some(thread), error, last_saved: None })
+    }
+
+    /// Returns true if a snapshot should be saved at a tick
+    pub fn is_due(&self, tick: u64) -> bool {
+        self.sender.is_some() && self.every != 0 && tick.is_multiple_of(self.every)
+    }
+
+    /// Returns true if snapshots are being saved
+    pub fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Returns the tick of the latest snapshot sent to be saved, None if nothing has been saved
+    pub fn last_saved(&self) -> Option<u64> {
+        self.last_saved
+    }
+
+    /// Sends a snapshot to the background thread to be saved
+    pub fn save(&mut self, snapshot: StateSnapshot) {
+        if let Some(sender) = &self.sender {
+            self.last_saved = Some(snapshot.tick);
+            // The thread only stops when the sender is dropped
+            let _ = sender.send(snapshot);
+        }
+    }
+
+    /// Stops saving and waits for the files waiting to be written, returns the latest error of the background thread
+    pub fn finish(&mut self) -> Option<AutosaveError> {
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        self.error()
+    }
+
+    /// Returns the latest error of the background thread
+    pub fn error(&self) -> Option<AutosaveError> {
+        self.error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+impl Drop for Autosave {
+    /// Waits for the files waiting to be written such that the latest save is on the disk
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl Clone for Autosave {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for Autosave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Autosave({})", self.every)
+    }
+}
+
+/// Finds the name of the autosave file of a tick, the tick is padded such that the names sort in the order of the ticks
+fn file_name(tick: u64) -> String {
+    format!("{}{:020}.{}", AUTOSAVE_PREFIX, tick, AUTOSAVE_EXTENSION)
+}
+
+/// Writes a snapshot to a new autosave file and removes the oldest files
+fn write(dir: &Path, snapshot: &StateSnapshot, keep_last: usize) -> Result<(), AutosaveError> {
+    let mut chain = CheckpointChain::new(CheckpointConfig::default());
+    chain.push(snapshot);
+
+    let path = dir.join(file_name(snapshot.tick));
+    let temporary = path.with_extension(TEMPORARY_EXTENSION);
+    let mut file = File::create(&temporary).map_err(|error| AutosaveError::io(&temporary, error))?;
+    file.write_all(&chain.to_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|error| AutosaveError::io(&temporary, error))?;
+    drop(file);
+    fs::rename(&temporary, &path).map_err(|error| AutosaveError::io(&path, error))?;
+
+    // Make the rename itself survive a power cut, directories cannot be opened for this on every platform
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+
+    let files = autosave_files(dir)?;
+    for old in files.iter().take(files.len().saturating_sub(keep_last.max(1))) {
+        fs::remove_file(old).map_err(|error| AutosaveError::io(old, error))?;
+    }
+
+    Ok(())
+}
+
+/// Finds all complete autosave files in a directory from the oldest to the newest
+/// 
+/// # Parameters
+/// 
+/// dir: The directory the simulation autosaves to
+/// 
+/// # Errors
+/// 
+/// AutosaveError::Io: This will occur if the directory could not be read
+pub fn autosave_files<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, AutosaveError> {
+    let dir = dir.as_ref();
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|error| AutosaveError::io(dir, error))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|extension| extension == AUTOSAVE_EXTENSION)
+                && path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(AUTOSAVE_PREFIX))
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+/// Reads the newest autosave in a directory, None if there are no autosaves. The snapshot is for inspection, such as
+/// comparing it with another run or extracting genomes, there is no way to resume a simulation from it
+/// 
+/// # Parameters
+/// 
+/// dir: The directory the simulation autosaves to
+/// 
+/// # Errors
+/// 
+/// AutosaveError::Io: This will occur if the directory or the file could not be read
+/// 
+/// AutosaveError::Checkpoint: This will occur if the file is not a valid checkpoint
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// use evolution_plants::autosave;
+/// 
+/// if let Some(snapshot) = autosave::load_latest("runs/long").unwrap() {
+///     println!("The run got to tick {}", snapshot.tick);
+/// }
+/// ```
+pub fn load_latest<P: AsRef<Path>>(dir: P) -> Result<Option<StateSnapshot>, AutosaveError> {
+    let Some(path) = autosave_files(dir)?.pop() else {
+        return Ok(None);
+    };
+
+    let bytes = fs::read(&path).map_err(|error| AutosaveError::io(&path, error))?;
+
+    Ok(CheckpointChain::from_bytes(&bytes, CheckpointConfig::default())?.latest())
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum AutosaveError {
+    #[error("Unable to read or write {:?}: {}", path, message)]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+}
+
+impl AutosaveError {
+    /// Creates the error of a failed file operation
+    fn io(path: &Path, error: io::Error) -> Self {
+        Self::Io { path: path.to_path_buf(), message: error.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    fn dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("evolution_plants_{}_{}", std::process::id(), name))
+    }
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn autosave_rotation() {
+        let dir = dir("autosave_rotation");
+        let _ = fs::remove_dir_all(&dir);
+        let mut simulation = simulation();
+        simulation.enable_autosave(&dir, 5, 2).unwrap();
+        for _ in 0..23 {
+            simulation.step();
+        }
+        let expected = simulation.clone();
+        for _ in 0..2 {
+            simulation.step();
+        }
+        let latest = simulation.snapshot();
+        drop(simulation);
+
+        let files: Vec<String> = autosave_files(&dir).unwrap().iter().map(|path| path.file_name().unwrap().to_str().unwrap().to_string()).collect();
+
+        assert_eq!(vec![file_name(20), file_name(25)], files);
+        assert_eq!(Some(latest), load_latest(&dir).unwrap());
+        assert!(expected.autosave_error().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn autosave_disabled() {
+        let dir = dir("autosave_disabled");
+        let mut simulation = simulation();
+        simulation.enable_autosave(&dir, 0, 2).unwrap();
+        simulation.step();
+
+        assert!(!dir.exists());
+        assert!(matches!(autosave_files(&dir), Err(AutosaveError::Io { .. })));
+    }
+
+    #[test]
+    fn autosave_finish() {
+        let dir = dir("autosave_finish");
+        let _ = fs::remove_dir_all(&dir);
+        let mut simulation = simulation();
+        simulation.enable_autosave(&dir, 5, 3).unwrap();
+        for _ in 0..7 {
+            simulation.step();
+        }
+        fs::write(dir.join(file_name(9)).with_extension(TEMPORARY_EXTENSION), b"EPCK").unwrap();
+        simulation.finish_autosave().unwrap();
+
+        // The final tick is saved once and a file left half written by a killed run is skipped
+        assert_eq!(vec![dir.join(file_name(5)), dir.join(file_name(7))], autosave_files(&dir).unwrap());
+        assert_eq!(Some(simulation.snapshot()), load_latest(&dir).unwrap());
+        for _ in 0..3 {
+            simulation.step();
+        }
+        assert_eq!(2, autosave_files(&dir).unwrap().len());
+
+        simulation.enable_autosave(&dir, 5, 3).unwrap();
+        simulation.step();
+        simulation.finish_autosave().unwrap();
+        assert_eq!(3, autosave_files(&dir).unwrap().len());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn autosave_load_corrupt() {
+        let dir = dir("autosave_load_corrupt");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(file_name(3)), b"EPCK").unwrap();
+        fs::write(dir.join("notes.txt"), b"not an autosave").unwrap();
+
+        assert!(matches!(load_latest(&dir), Err(AutosaveError::Checkpoint(_))));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use evolution_plants::{board, genome::Genome, population::{Cull, Plant, Population}, scenario::{Scenario, ScenarioAction}, shutdown::Shutdown};
+use evolution_plants::{simulation::{Simulation, SimulationConfig}, stats};
+
+/// The help printed for --help and for invalid arguments
+const USAGE: &str = "Usage: evolution_plants_cli [--ticks N] [--seed N] [--autosave DIR] [--every N] [--keep N] [--stats FILE] [--cull TICK:FRACTION]...
+
+Runs the simulation without a window until the number of ticks, until every plant has died or until Ctrl-C or SIGTERM.
+On a signal the current tick is finished, a final autosave is written and the statistics are flushed before exiting.
+A second signal exits at once, the autosaves already written stay complete.
+
+  --ticks N       Stop after N ticks, runs until stopped if left out
+  --seed N        The seed of the random number generator, 0 by default
+  --autosave DIR  Save checkpoints to DIR
+  --every N       The number of ticks between autosaves, 1000 by default
+  --keep N        The number of the latest autosaves to keep, 3 by default
+  --stats FILE    Write the statistics of every tick to FILE as CSV
+  --cull T:F      Remove the fraction F of the plants at random at the start of tick T, may be given several times";
+
+/// The settings of a run read from the arguments
+struct Options {
+    /// The number of ticks to run, None to run until stopped
+    ticks: Option<u64>,
+    /// The seed of the random number generator
+    seed: u64,
+    /// The directory to autosave to
+    autosave: Option<PathBuf>,
+    /// The number of ticks between autosaves
+    every: u64,
+    /// The number of the latest autosaves to keep
+    keep: usize,
+    /// The file to write the statistics to
+    stats: Option<PathBuf>,
+    /// The bottlenecks of the run as the tick and the fraction of the plants removed
+    culls: Vec<(u64, f32)>,
+}
+
+impl Options {
+    /// Reads the options from the arguments, returns the message to print if they are invalid or help was asked for
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Self { ticks: None, seed: 0, autosave: None, every: 1000, keep: 3, stats: None, culls: Vec::new() };
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Err(USAGE.to_string());
+            }
+
+            let value = args.next().ok_or_else(|| format!("{} needs a value\n\n{}", arg, USAGE))?;
+            let number = || value.parse::<u64>().map_err(|_| format!("{} must be a whole number, got {:?}", arg, value));
+            match arg.as_str() {
+                "--ticks" => options.ticks = Some(number()?),
+                "--seed" => options.seed = number()?,
+                "--autosave" => options.autosave = Some(PathBuf::from(&value)),
+                "--every" => options.every = number()?,
+                "--keep" => options.keep = number()? as usize,
+                "--stats" => options.stats = Some(PathBuf::from(&value)),
+                "--cull" => {
+                    let cull = value.split_once(':')
+                        .and_then(|(tick, fraction)| Some((tick.parse::<u64>().ok()?, fraction.parse::<f32>().ok()?)))
+                        .ok_or_else(|| format!("--cull must be a tick and a fraction such as 500:0.9, got {:?}", value))?;
+                    options.culls.push(cull);
+                }
+                _ => return Err(format!("Unknown argument {:?}\n\n{}", arg, USAGE)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the simulation until it is stopped and prints a summary
+fn run(options: Options) -> evolution_plants::Result<()> {
+    let shutdown = Shutdown::install()?;
+
+    // Create a board with the light increasing from left to right like the windowed runner
+    let (w, h) = (128, 96);
+    let board = board::BoardBuilder::new()
+        .size(w, h)
+        .light_generator(move |coord| coord.x as f32 / (w - 1) as f32)
+        .multiplier_light(30)
+        .build()?;
+    let size = board.fields.size;
+
+    let mut population = Population::new(size);
+    population.insert(board::Coord::new(w / 2, h / 2), Plant::new(100, Genome::new(&[0.1, 0.5])?));
+
+    let scenario = options.culls.iter()
+        .try_fold(Scenario::new(), |scenario, &(tick, fraction)| scenario.at(tick, ScenarioAction::Cull(Cull::Random { fraction })))?;
+    let config = SimulationConfig { seed: options.seed, scenario, ..Default::default() };
+    let mut simulation = Simulation::new(board, population, config)?;
+    if let Some(dir) = &options.autosave {
+        simulation.enable_autosave(dir, options.every, options.keep)?;
+    }
+
+    let mut subscription = stats::subscribe(&mut simulation, 64);
+    let mut writer = match &options.stats {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            writeln!(writer, "tick,population,births,deaths,mean_energy,diversity,species")?;
+            Some(writer)
+        }
+        None => None,
+    };
+
+    // The signal only sets a flag, so it is seen between steps and the current step always finishes
+    let start = Instant::now();
+    let reason = loop {
+        if shutdown.is_requested() {
+            break "stopped by a signal";
+        }
+        if options.ticks.is_some_and(|ticks| simulation.tick() >= ticks) {
+            break "reached the tick limit";
+        }
+        if simulation.population().count() == 0 {
+            break "every plant died";
+        }
+
+        simulation.step();
+        while let Some(stats) = subscription.try_next() {
+            if let Some(writer) = &mut writer {
+                writeln!(writer, "{},{},{},{},{},{},{}", stats.tick, stats.population, stats.births, stats.deaths, stats.mean_energy, stats.diversity, stats.species)?;
+            }
+        }
+    };
+    let elapsed = start.elapsed();
+
+    if let Some(mut writer) = writer {
+        writer.flush()?;
+    }
+    simulation.finish_autosave()?;
+
+    println!("Run {} at tick {}", reason, simulation.tick());
+    println!("  Living plants: {}", simulation.population().count());
+    println!("  Time: {:.1} s, {:.1} ticks per second", elapsed.as_secs_f64(), simulation.tick() as f64 / elapsed.as_secs_f64().max(1e-9));
+    if let Some(dir) = &options.autosave {
+        println!("  Final autosave in {}", dir.display());
+    }
+    if let Some(path) = &options.stats {
+        println!("  Statistics in {}", path.display());
+    }
+
+    Ok(())
+}
@@ -1,5 +1,16 @@
+use std::fmt;
+
 use thiserror::Error;
 
+use crate::field::Field;
+use crate::memory::{vec_bytes, HeapSize};
+use crate::seedbank::SeedBank;
+use crate::spectrum::CHANNELS;
+use crate::view::{FieldView, FieldViewMut};
+
+/// The text added to the names of the regions of the mirror image when a board is mirrored
+pub const MIRROR_SUFFIX: &str = " (mirrored)";
+
 /// Defines the board on which the plants evolve
 #[derive(Clone, Debug, PartialEq)]
 pub struct Board {
@@ -7,6 +18,12 @@ pub struct Board {
     pub multipliers: Multipliers,
     /// The fields of the map
     pub fields: Fields,
+    /// The dormant seeds in the ground of every cell
+    pub seed_bank: SeedBank,
+    /// The named regions of the board used to group the statistics
+    pub regions: Vec<Region>,
+    /// The adjustment of the light energy collected in every cell, such as a fertilized patch
+    pub modifiers: Field<Modifier>,
 }
 
 impl Board {
@@ -25,227 +42,2265 @@ impl Board {
     /// let size = board::Size::new(2, 2);
     /// let light_field = [0.0, 0.5, 0.5, 1.0];
     /// let fields = board::Fields::new(size, &light_field).unwrap();
-    /// let multipliers = board::Multipliers::new(1024);
+    /// let multipliers = board::Multipliers::new(1024).unwrap();
     /// let board = board::Board::new(multipliers, fields);
     /// ```
     pub fn new(multipliers: Multipliers, fields: Fields) -> Self {
-        Self { multipliers, fields }
-    }
-}
+        let seed_bank = SeedBank::new(fields.size);
+        let modifiers = Field::filled(fields.size, Modifier::default());
 
-/// All the multipliers for the fields
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Multipliers {
-    /// The multiplier for the light field
-    pub light: u32,
-}
+        Self { multipliers, fields, seed_bank, regions: Vec::new(), modifiers }
+    }
 
-impl Multipliers {
-    /// Creates a new set of multipliers
+    /// Adds a named region to the board, a region with the same name is replaced
     /// 
     /// # Parameters
     /// 
-    /// light: The multiplier of the light field
+    /// name: The name of the region
+    /// shape: The cells of the region, it may reach outside the board
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use evolution_plants::board;
+    /// use evolution_plants::board::{BoardBuilder, Rect, Shape};
     /// 
-    /// let multipliers = board::Multipliers::new(1024);
+    /// let mut board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// board.add_region("oasis", Rect::new(1, 1, 2, 2));
+    /// board.add_region("valley", Shape::Polygon(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)]));
+    /// board.add_region("oasis", Rect::new(0, 0, 1, 1));
     /// 
-    /// assert_eq!(1024, multipliers.light);
+    /// assert_eq!(2, board.regions.len());
+    /// assert_eq!(Shape::Rect(Rect::new(0, 0, 1, 1)), board.region("oasis").unwrap().shape);
     /// ```
-    pub fn new(light: u32) -> Self {
-        Self { light }
+    pub fn add_region<S: Into<Shape>>(&mut self, name: &str, shape: S) {
+        let region = Region { name: name.to_string(), shape: shape.into() };
+
+        match self.regions.iter_mut().find(|region| region.name == name) {
+            Some(existing) => *existing = region,
+            None => self.regions.push(region),
+        }
     }
-}
 
-/// All the fields
-#[derive(Clone, Debug, PartialEq)]
-pub struct Fields {
-    /// The size of the field
-    pub size: Size,
-    /// The relative value of the light
-    pub light: Vec<f32>,
-}
+    /// Returns the region with a name, None if there is no such region
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the region
+    pub fn region(&self, name: &str) -> Option<&Region> {
+        self.regions.iter().find(|region| region.name == name)
+    }
 
-impl Fields {
-    /// Creates a new set of fields
+    /// Removes the region with a name and returns it, None if there is no such region
     /// 
-    /// Parameters 
+    /// # Parameters
     /// 
-    /// size: The size of the board to put the fields
-    /// light: The values of the light field
+    /// name: The name of the region
+    pub fn remove_region(&mut self, name: &str) -> Option<Region> {
+        let index = self.regions.iter().position(|region| region.name == name)?;
+
+        Some(self.regions.remove(index))
+    }
+
+    /// Sets the adjustment of the light energy collected in a cell, the modifier replaces the one already in the cell.
+    /// Returns false if the cell is outside the board
     /// 
-    /// # Errors
+    /// # Parameters
     /// 
-    /// BoardError::FieldSize: This will occur if any of the fields are not the correct size for the board
+    /// coord: The coordinate of the cell
+    /// modifier: The adjustment of the cell, Modifier::default() removes the adjustment
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use evolution_plants::board;
+    /// use evolution_plants::board::{BoardBuilder, Coord, Modifier};
     /// 
-    /// let light_field = [0.0, 1.5, 2.3, 3.9];
-    /// let size = board::Size::new(2, 2);
-    /// let fields = board::Fields::new(size, &light_field).unwrap();
+    /// let mut board = BoardBuilder::new().size(2, 2).light_uniform(0.5).build().unwrap();
     /// 
-    /// assert_eq!([0.0, 1.5, 2.3, 3.9].to_vec(), fields.light);
-    /// assert_eq!(size, fields.size);
+    /// assert!(board.set_modifier(Coord::new(1, 0), Modifier::new(0.25, 2.0)));
+    /// assert!(!board.set_modifier(Coord::new(2, 0), Modifier::new(0.25, 2.0)));
+    /// assert_eq!(1.25, board.modifier(Coord::new(1, 0)).apply(0.5));
+    /// assert_eq!(Modifier::default(), board.modifier(Coord::new(0, 0)));
     /// ```
-    pub fn new(size: Size, light: &[f32]) -> Result<Self, FieldCreateError> {
-        // Make sure the fields are the correct size
-        let len = size.len();
-
-        if light.len() != len {
-            return Err(FieldCreateError::Size {name: "Light".to_string(), len: light.len(), size});
+    pub fn set_modifier(&mut self, coord: Coord, modifier: Modifier) -> bool {
+        match self.fields.size.index_of(coord) {
+            Some(index) => {
+                self.modifiers[index] = modifier;
+                true
+            }
+            None => false,
         }
+    }
 
-        let light = light.to_vec();
+    /// Returns the adjustment of the light energy collected in a cell, this does nothing outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate of the cell
+    pub fn modifier(&self, coord: Coord) -> Modifier {
+        self.fields.size.index_of(coord).map_or_else(Modifier::default, |index| self.modifiers[index])
+    }
 
-        Ok(Self { size, light })
+    /// Removes the adjustments of every cell
+    pub fn clear_modifiers(&mut self) {
+        self.modifiers.fill(Modifier::default());
     }
-}
 
-/// The size of the map
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Size {
-    /// The width of the map
-    w: usize,
-    /// The height of the map
-    h: usize,
-}
+    /// Changes the size of the board keeping the top left corner in place, new cells get the values of the fill
+    /// and the cells which no longer fit on the board are removed together with their dormant seeds
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The new size of the board
+    /// fill: The values of the new cells
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{BoardBuilder, Fill, Size, Terrain};
+    /// 
+    /// let mut board = BoardBuilder::new().size(2, 1).light_from_slice(&[0.5, 1.0]).build().unwrap();
+    /// board.resize(Size::new(3, 2), &Fill { light: 0.25, terrain: Terrain::Rock, ..Default::default() });
+    /// 
+    /// assert_eq!(vec![0.5, 1.0, 0.25, 0.25, 0.25, 0.25], board.fields.light);
+    /// assert_eq!(Terrain::Rock, board.fields.terrain[2]);
+    /// ```
+    pub fn resize(&mut self, size: Size, fill: &Fill) {
+        let (w, h) = size.size();
 
-impl Size {
-    /// Create a new size
+        self.reframe(Rect::new(0, 0, w, h), fill);
+    }
+
+    /// Cuts out a rectangle of the board which becomes the new board, the rectangle is clamped to the board
     /// 
     /// # Parameters
     /// 
-    /// w: The width of the size
-    /// h: The height of the size
+    /// x: The x coordinate of the left edge
+    /// y: The y coordinate of the top edge
+    /// w: The width
+    /// h: The height
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use evolution_plants::board::Size;
+    /// use evolution_plants::board::{BoardBuilder, Size};
     /// 
-    /// let size = Size::new(512, 512);
+    /// let mut board = BoardBuilder::new().size(3, 2).light_from_slice(&[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).build().unwrap();
+    /// board.crop(1, 1, 5, 5);
+    /// 
+    /// assert_eq!(Size::new(2, 1), board.fields.size);
+    /// assert_eq!(vec![0.4, 0.5], board.fields.light);
     /// ```
-    pub fn new(w: usize, h: usize) -> Self {
-        Self { w, h }
+    pub fn crop(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        let rect = Rect::new(x, y, w, h).clamp(self.fields.size);
+
+        self.reframe(rect, &Fill::default());
     }
 
-    /// Returns the size as a tuple of (w, h)
+    /// Creates a new board with another board put to the right of this one, the dormant seeds and regions of both boards are kept.
+    /// Regions of the other board with the same name as a region of this board replace it
     /// 
-    /// Examples
+    /// # Parameters
+    /// 
+    /// other: The board to put to the right
+    /// 
+    /// # Errors
+    /// 
+    /// BoardConcatError::Height: This will occur if the boards do not have the same height
+    /// 
+    /// BoardConcatError::Multiplier: This will occur if the boards do not have the same light multiplier
+    /// 
+    /// # Examples
     /// 
     /// ```
-    /// use evolution_plants::board::Size;
+    /// use evolution_plants::board::{BoardBuilder, Size};
     /// 
-    /// let size = Size::new(512, 256);
-    /// assert_eq!((512, 256), size.size());
+    /// let desert = BoardBuilder::new().size(1, 2).light_uniform(1.0).build().unwrap();
+    /// let forest = BoardBuilder::new().size(2, 2).light_uniform(0.25).build().unwrap();
+    /// let world = desert.concat_horizontal(&forest).unwrap();
+    /// 
+    /// assert_eq!(Size::new(3, 2), world.fields.size);
+    /// assert_eq!(vec![1.0, 0.25, 0.25, 1.0, 0.25, 0.25], world.fields.light);
     /// ```
-    pub fn size(&self) -> (usize, usize) {
-        (self.w, self.h)
+    pub fn concat_horizontal(&self, other: &Board) -> Result<Board, BoardConcatError> {
+        let (w1, h1) = self.fields.size.size();
+        let (w2, h2) = other.fields.size.size();
+        if h1 != h2 {
+            return Err(BoardConcatError::Height { first: h1, second: h2 });
+        }
+
+        self.concat(other, Size::new(w1 + w2, h1), true)
     }
 
-    /// Returns the number of elements on the board
+    /// Creates a new board with another board put below this one, the dormant seeds and regions of both boards are kept.
+    /// Regions of the other board with the same name as a region of this board replace it
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The board to put below
+    /// 
+    /// # Errors
+    /// 
+    /// BoardConcatError::Width: This will occur if the boards do not have the same width
+    /// 
+    /// BoardConcatError::Multiplier: This will occur if the boards do not have the same light multiplier
     /// 
     /// # Examples
     /// 
     /// ```
-    /// use evolution_plants::board::Size;
+    /// use evolution_plants::board::{BoardBuilder, Size};
     /// 
-    /// let size = Size::new(512, 256);
-    /// assert_eq!(512 * 256, size.len());
+    /// let north = BoardBuilder::new().size(2, 1).light_uniform(0.5).build().unwrap();
+    /// let south = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+    /// let world = north.concat_vertical(&south).unwrap();
+    /// 
+    /// assert_eq!(Size::new(2, 2), world.fields.size);
+    /// assert_eq!(vec![0.5, 0.5, 1.0, 1.0], world.fields.light);
     /// ```
-    pub fn len(&self) -> usize {
-        self.w * self.h
+    pub fn concat_vertical(&self, other: &Board) -> Result<Board, BoardConcatError> {
+        let (w1, h1) = self.fields.size.size();
+        let (w2, h2) = other.fields.size.size();
+        if w1 != w2 {
+            return Err(BoardConcatError::Width { first: w1, second: w2 });
+        }
+
+        self.concat(other, Size::new(w1, h1 + h2), false)
     }
 
-    /// Gets the stride of the fields for moving in the y direction
-    pub(crate) fn stride(&self) -> usize {
-        self.w
+    /// Creates a new board with the mirror image of this board put to the right of it, the new board is symmetric around
+    /// its vertical center line. The dormant seeds are mirrored too and every region gets a mirrored copy with MIRROR_SUFFIX
+    /// added to its name. Running a simulation on the board with mirrored plants lets the two halves be compared
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{BoardBuilder, Rect, Size};
+    /// 
+    /// let mut board = BoardBuilder::new().size(2, 1).light_from_slice(&[0.25, 1.0]).build().unwrap();
+    /// board.add_region("shade", Rect::new(0, 0, 1, 1));
+    /// let world = board.mirror_horizontal();
+    /// 
+    /// assert_eq!(Size::new(4, 1), world.fields.size);
+    /// assert_eq!(vec![0.25, 1.0, 1.0, 0.25], world.fields.light);
+    /// assert!(world.region("shade (mirrored)").unwrap().shape.contains(evolution_plants::board::Coord::new(3, 0)));
+    /// ```
+    pub fn mirror_horizontal(&self) -> Board {
+        self.mirror(true)
+    }
+
+    /// Creates a new board with the mirror image of this board put below it, the new board is symmetric around
+    /// its horizontal center line. The dormant seeds are mirrored too and every region gets a mirrored copy with MIRROR_SUFFIX
+    /// added to its name
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{BoardBuilder, Size};
+    /// 
+    /// let board = BoardBuilder::new().size(1, 2).light_from_slice(&[0.25, 1.0]).build().unwrap();
+    /// let world = board.mirror_vertical();
+    /// 
+    /// assert_eq!(Size::new(1, 4), world.fields.size);
+    /// assert_eq!(vec![0.25, 1.0, 1.0, 0.25], world.fields.light);
+    /// ```
+    pub fn mirror_vertical(&self) -> Board {
+        self.mirror(false)
+    }
+
+    /// Puts the mirror image of the board next to it or below it
+    fn mirror(&self, horizontal: bool) -> Board {
+        let (w, h) = self.fields.size.size();
+        let mut mirror = self.clone();
+        mirror.fields.flip(horizontal);
+        mirror.seed_bank.flip(horizontal);
+        flip_cells(&mut mirror.modifiers, self.fields.size, horizontal);
+        for region in &mut mirror.regions {
+            region.name.push_str(MIRROR_SUFFIX);
+            region.shape = region.shape.flip(self.fields.size, horizontal);
+        }
+
+        let size = if horizontal { Size::new(2 * w, h) } else { Size::new(w, 2 * h) };
+        self.concat(&mirror, size, horizontal).expect("A board has the same size and multipliers as its mirror image")
+    }
+
+    /// Puts two boards of compatible sizes next to each other or on top of each other
+    fn concat(&self, other: &Board, size: Size, horizontal: bool) -> Result<Board, BoardConcatError> {
+        if self.multipliers != other.multipliers {
+            return Err(BoardConcatError::Multiplier { first: self.multipliers.light, second: other.multipliers.light });
+        }
+
+        let (first, second) = (&self.fields, &other.fields);
+        if first.spectrum.len() != second.spectrum.len() {
+            return Err(BoardConcatError::Spectrum { first: first.spectrum.len(), second: second.spectrum.len() });
+        }
+        let join = |name: &str, a: &[f32], b: &[f32]| {
+            Field::from_vec(name, size, concat_cells(a, first.size, b, second.size, horizontal)).expect("The joined field fills the joined board")
+        };
+        let fields = Fields {
+            size,
+            light: join("Light", &first.light, &second.light),
+            elevation: join("Elevation", &first.elevation, &second.elevation),
+            water: join("Water", &first.water, &second.water),
+            temperature: join("Temperature", &first.temperature, &second.temperature),
+            terrain: Field::from_vec("Terrain", size, concat_cells(&first.terrain, first.size, &second.terrain, second.size, horizontal))
+                .expect("The joined field fills the joined board"),
+            spectrum: first.spectrum.iter().zip(&second.spectrum).map(|(a, b)| join("Spectrum", a, b)).collect(),
+        };
+        let seed_bank = SeedBank::concat(&self.seed_bank, &other.seed_bank, size, horizontal);
+        let modifiers = Field::from_vec("Modifiers", size, concat_cells(&self.modifiers, first.size, &other.modifiers, second.size, horizontal))
+            .expect("The joined field fills the joined board");
+        let mut board = Board { multipliers: self.multipliers, fields, seed_bank, regions: self.regions.clone(), modifiers };
+
+        let (dx, dy) = if horizontal { (first.size.size().0 as isize, 0) } else { (0, first.size.size().1 as isize) };
+        for region in &other.regions {
+            board.add_region(&region.name, region.shape.translate(dx, dy));
+        }
+
+        Ok(board)
+    }
+
+    /// Makes a rectangle of the board the new board, the rectangle may reach outside the board
+    /// in which case the cells outside get the values of the fill
+    pub(crate) fn reframe(&mut self, rect: Rect, fill: &Fill) {
+        let fields = &mut self.fields;
+
+        fields.light.reframe(rect, || fill.light);
+        fields.elevation.reframe(rect, || fill.elevation);
+        fields.water.reframe(rect, || fill.water);
+        fields.temperature.reframe(rect, || fill.temperature);
+        fields.terrain.reframe(rect, || fill.terrain);
+        let share = 1.0 / fields.spectrum.len().max(1) as f32;
+        for channel in &mut fields.spectrum {
+            channel.reframe(rect, || share);
+        }
+        fields.size = Size::new(rect.w, rect.h);
+        self.seed_bank.reframe(rect);
+        self.modifiers.reframe(rect, Modifier::default);
+        for region in &mut self.regions {
+            region.shape = region.shape.translate(-(rect.x as isize), -(rect.y as isize));
+        }
     }
 }
 
-#[derive(Clone, Error, Debug, PartialEq)]
-pub enum FieldCreateError {
-    #[error("{:?} field has wrong size ({:?}) should be ({:?}) on board with size {:?}", name, len, size.len(), size)]
-    Size {
-        name: String,
-        len: usize,
-        size: Size,
-    },
+/// An adjustment of the light energy collected in a single cell applied on top of the light of the board,
+/// the light is first multiplied and then added to. The default modifier leaves the light unchanged
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Modifier {
+    /// The relative value of light added to the cell, negative values are penalties
+    pub add: f32,
+    /// The factor the light of the cell is multiplied by
+    pub multiply: f32,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Modifier {
+    /// Creates a new modifier
+    /// 
+    /// # Parameters
+    /// 
+    /// add: The relative value of light added to the cell
+    /// multiply: The factor the light of the cell is multiplied by
+    pub fn new(add: f32, multiply: f32) -> Self {
+        Self { add, multiply }
+    }
 
-    #[test]
-    fn size_new() {
-        let size = Size::new(40, 55);
-        assert_eq!((40, 55), (size.w, size.h));
+    /// Returns true if the modifier leaves the light unchanged
+    pub fn is_identity(&self) -> bool {
+        self.add == 0.0 && self.multiply == 1.0
     }
 
-    #[test]
-    fn size_size() {
-        let size = Size::new(40, 55);
-        assert_eq!((40, 55), size.size());      
+    /// Adjusts the light of a cell, the light never becomes negative
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The relative value of the light
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Modifier;
+    /// 
+    /// assert_eq!(0.75, Modifier::new(0.25, 1.0).apply(0.5));
+    /// assert_eq!(0.0, Modifier::new(-0.5, 0.5).apply(0.5));
+    /// ```
+    pub fn apply(&self, light: f32) -> f32 {
+        (light * self.multiply + self.add).max(0.0)
     }
+}
 
-    #[test]
-    fn size_len() {
-        let size = Size::new(40, 55);
-        assert_eq!(40 * 55, size.len());      
+impl Default for Modifier {
+    fn default() -> Self {
+        Self {
+            add: 0.0,
+            multiply: 1.0,
+        }
     }
+}
 
-    #[test]
-    fn size_stride() {
-        let size = Size::new(40, 55);
-        assert_eq!(40, size.stride());   
+/// The values given to the cells added when a board grows
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Fill {
+    /// The relative value of the light
+    pub light: f32,
+    /// The height of the terrain
+    pub elevation: f32,
+    /// The initial water
+    pub water: f32,
+    /// The temperature
+    pub temperature: f32,
+    /// The kind of ground
+    pub terrain: Terrain,
+}
+
+/// Moves the values of the cells of a board into a rectangle of it which becomes the new board,
+/// cells of the rectangle outside the board get the value of the fill. Returns the new cells
+/// and the values of the cells outside the rectangle together with their old index
+pub(crate) fn reframe_cells<T, F: FnMut() -> T>(values: Vec<T>, from: Size, rect: Rect, mut fill: F) -> (Vec<T>, Vec<(usize, T)>) {
+    let mut old: Vec<Option<T>> = values.into_iter().map(Some).collect();
+
+    let cells = rect.coords()
+        .map(|coord| match from.index_of(coord) {
+            Some(index) => old[index].take().unwrap(),
+            None => fill(),
+        })
+        .collect();
+    let removed = old.into_iter()
+        .enumerate()
+        .filter_map(|(index, value)| value.map(|value| (index, value)))
+        .collect();
+
+    (cells, removed)
+}
+
+/// Joins the cells of two boards into the cells of a single board, either with the second board to the right of
+/// the first or below it. The boards must have the same height or width respectively
+pub(crate) fn concat_cells<T: Clone>(first: &[T], first_size: Size, second: &[T], second_size: Size, horizontal: bool) -> Vec<T> {
+    if !horizontal {
+        return first.iter().chain(second).cloned().collect();
     }
 
-    #[test]
-    fn fields_new() -> Result<(), FieldCreateError> {
-        let size = Size::new(2, 2);
-        let light_field = [1.0, 2.0, 3.0, 4.0];
-        let fields = Fields::new(size, &light_field)?;
+    let (w1, w2) = (first_size.stride(), second_size.stride());
+    let (_, h) = first_size.size();
 
-        assert_eq!(size, fields.size);
-        assert_eq!(light_field.to_vec(), fields.light);
+    (0..h).flat_map(|y| first[y * w1..(y + 1) * w1].iter().chain(&second[y * w2..(y + 1) * w2]))
+        .cloned()
+        .collect()
+}
 
-        Ok(())
+/// Mirrors the cells of a board in place, either left to right or top to bottom
+pub(crate) fn flip_cells<T>(cells: &mut [T], size: Size, horizontal: bool) {
+    let (w, h) = (size.stride(), size.size().1);
+    if w == 0 {
+        return;
     }
 
-    #[test]
-    fn fields_new_error_light() {
-        let size = Size::new(2, 2);
-        let light_field = [1.0, 2.0, 3.0];
-        let fields = Fields::new(size, &light_field);
+    if horizontal {
+        for row in cells.chunks_mut(w) {
+            row.reverse();
+        }
+    } else {
+        for y in 0..h / 2 {
+            let (top, bottom) = cells.split_at_mut((h - 1 - y) * w);
+            top[y * w..(y + 1) * w].swap_with_slice(&mut bottom[..w]);
+        }
+    }
+}
 
-        assert!(fields.is_err());
-        assert_eq!(FieldCreateError::Size {name: "Light".to_string(), len: 3, size}, fields.unwrap_err())
+/// Copies the first half of the cells of a board onto the second half mirrored around an axis,
+/// the middle row or column of a board with an odd size is kept
+fn symmetrize_cells<T: Clone>(cells: &mut [T], size: Size, axis: Axis) {
+    let (w, h) = (size.stride(), size.size().1);
+
+    for coord in size.coords() {
+        let (x, y) = (coord.x, coord.y);
+        let from = match axis {
+            Axis::Vertical if 2 * x >= w => y * w + (w - 1 - x),
+            Axis::Horizontal if 2 * y >= h => (h - 1 - y) * w + x,
+            _ => continue,
+        };
+        cells[y * w + x] = cells[from].clone();
     }
+}
 
-    #[test]
-    fn multipliers_new() {
-        let multipliers = Multipliers::new(1024);
+/// A line through the center of a board which the board is mirrored around
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The vertical line between the left and the right half
+    Vertical,
+    /// The horizontal line between the top and the bottom half
+    Horizontal,
+}
 
-        assert_eq!(1024, multipliers.light);
+/// Where the values of the light field come from when building a board
+enum LightSource {
+    /// The same light in every cell
+    Uniform(f32),
+    /// The light of every cell given row by row
+    Slice(Vec<f32>),
+    /// A function giving the light of a cell from its coordinate
+    Generator(Box<dyn Fn(Coord) -> f32>),
+}
+
+/// Builds a board step by step and validates everything at once when it is built
+pub struct BoardBuilder {
+    /// The size of the board
+    size: Option<Size>,
+    /// The source of the light field
+    light: Option<LightSource>,
+    /// The multiplier of the light field
+    multiplier_light: u32,
+    /// The elevation field
+    elevation: Option<Vec<f32>>,
+    /// The initial water field
+    water: Option<Vec<f32>>,
+    /// The temperature field
+    temperature: Option<Vec<f32>>,
+    /// The terrain field
+    terrain: Option<Vec<Terrain>>,
+}
+
+impl BoardBuilder {
+    /// Creates a new builder without a size or a light field and with a light multiplier of 1
+    pub fn new() -> Self {
+        Self {
+            size: None,
+            light: None,
+            multiplier_light: 1,
+            elevation: None,
+            water: None,
+            temperature: None,
+            terrain: None,
+        }
     }
 
-    #[test]
-    fn board_new() {
-        let size = Size::new(2, 2);
-        let light_field = [1.0, 2.0, 3.0, 4.0];
-        let fields = Fields::new(size, &light_field).unwrap();
-        let multipliers = Multipliers::new(1024);
-        let board = Board::new(multipliers, fields.clone());
+    /// Sets the size of the board
+    /// 
+    /// # Parameters
+    /// 
+    /// w: The width of the board
+    /// h: The height of the board
+    pub fn size(mut self, w: usize, h: usize) -> Self {
+        self.size = Some(Size::new(w, h));
+        self
+    }
 
-        assert_eq!(multipliers, board.multipliers);
-        assert_eq!(fields, board.fields);
+    /// Sets the light to the same value in every cell
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The light in every cell
+    pub fn light_uniform(mut self, light: f32) -> Self {
+        self.light = Some(LightSource::Uniform(light));
+        self
     }
-}
\ No newline at end of file
+
+    /// Sets the light of every cell, given row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The light in every cell
+    pub fn light_from_slice(mut self, light: &[f32]) -> Self {
+        self.light = Some(LightSource::Slice(light.to_vec()));
+        self
+    }
+
+    /// Sets the light of every cell from a function of its coordinate
+    /// 
+    /// # Parameters
+    /// 
+    /// generator: The function giving the light in a cell
+    pub fn light_generator<F: Fn(Coord) -> f32 + 'static>(mut self, generator: F) -> Self {
+        self.light = Some(LightSource::Generator(Box::new(generator)));
+        self
+    }
+
+    /// Sets the multiplier of the light field
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The multiplier of the light field
+    pub fn multiplier_light(mut self, light: u32) -> Self {
+        self.multiplier_light = light;
+        self
+    }
+
+    /// Sets the elevation of every cell, given row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// elevation: The height of the terrain in every cell measured in cells
+    pub fn elevation(mut self, elevation: &[f32]) -> Self {
+        self.elevation = Some(elevation.to_vec());
+        self
+    }
+
+    /// Sets the initial water of every cell, given row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// water: The initial water in every cell
+    pub fn water(mut self, water: &[f32]) -> Self {
+        self.water = Some(water.to_vec());
+        self
+    }
+
+    /// Sets the temperature of every cell, given row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// temperature: The temperature in every cell
+    pub fn temperature(mut self, temperature: &[f32]) -> Self {
+        self.temperature = Some(temperature.to_vec());
+        self
+    }
+
+    /// Sets the terrain of every cell, given row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// terrain: The kind of ground in every cell
+    pub fn terrain(mut self, terrain: &[Terrain]) -> Self {
+        self.terrain = Some(terrain.to_vec());
+        self
+    }
+
+    /// Builds the board
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Missing: This will occur if the size or the light field has not been set
+    /// 
+    /// FieldCreateError::Size: This will occur if any of the fields are not the correct size for the board
+    /// 
+    /// FieldCreateError::Value: This will occur if the light is negative or not finite in any cell,
+    /// or any other field is not finite in any cell
+    /// 
+    /// FieldCreateError::Multiplier: This will occur if the light multiplier is 0 or too large
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::BoardBuilder;
+    /// 
+    /// let board = BoardBuilder::new()
+    ///     .size(3, 2)
+    ///     .light_generator(|coord| coord.x as f32 / 2.0)
+    ///     .multiplier_light(1024)
+    ///     .build()
+    ///     .unwrap();
+    /// 
+    /// assert_eq!(vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0], board.fields.light);
+    /// assert_eq!(1024, board.multipliers.light);
+    /// ```
+    pub fn build(self) -> Result<Board, FieldCreateError> {
+        let size = self.size.ok_or(FieldCreateError::Missing {name: "Size".to_string()})?;
+
+        let light = match self.light.ok_or(FieldCreateError::Missing {name: "Light".to_string()})? {
+            LightSource::Uniform(light) => vec![light; size.len()],
+            LightSource::Slice(light) => light,
+            LightSource::Generator(generator) => (0..size.len()).map(|index| generator(size.coord_of(index).unwrap())).collect(),
+        };
+
+        // Make sure all fields have the correct size and all values are valid
+        let mut fields = Fields::new(size, &light)?;
+        fields.light.check_non_negative("Light")?;
+        for (name, values, field) in [
+            ("Elevation", self.elevation, &mut fields.elevation),
+            ("Water", self.water, &mut fields.water),
+            ("Temperature", self.temperature, &mut fields.temperature),
+        ] {
+            if let Some(values) = values {
+                *field = Field::from_vec(name, size, values)?;
+                field.check_finite(name)?;
+            }
+        }
+        if let Some(terrain) = self.terrain {
+            fields.terrain = Field::from_vec("Terrain", size, terrain)?;
+        }
+
+        Ok(Board::new(Multipliers::new(self.multiplier_light)?, fields))
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// All the multipliers for the fields
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Multipliers {
+    /// The multiplier for the light field
+    pub light: u32,
+    /// The relative multiplier of every light channel when the light is split into channels, 1 for every channel by default
+    pub channels: [f32; CHANNELS],
+}
+
+impl Multipliers {
+    /// The largest multiplier allowed, above this the scaled values can no longer be represented exactly
+    pub const MAX: u32 = 1 << 24;
+
+    /// Creates a new set of multipliers
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The multiplier of the light field
+    /// 
+    /// # Errors
+    /// 
+    /// MultiplierError::Zero: This will occur if the light multiplier is 0 so no plant could ever gain energy
+    /// 
+    /// MultiplierError::TooLarge: This will occur if the light multiplier is larger than Multipliers::MAX
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board;
+    /// 
+    /// let multipliers = board::Multipliers::new(1024).unwrap();
+    /// 
+    /// assert_eq!(1024, multipliers.light);
+    /// assert!(board::Multipliers::new(0).is_err());
+    /// ```
+    pub fn new(light: u32) -> Result<Self, MultiplierError> {
+        if light == 0 {
+            return Err(MultiplierError::Zero { name: "Light".to_string() });
+        }
+        if light > Self::MAX {
+            return Err(MultiplierError::TooLarge { name: "Light".to_string(), value: light, max: Self::MAX });
+        }
+
+        Ok(Self { light, channels: [1.0; CHANNELS] })
+    }
+
+    /// Sets the relative multiplier of every light channel, the channels the board does not have are ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// channels: The multiplier of every channel
+    pub fn with_channels(mut self, channels: [f32; CHANNELS]) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Scales a value of light to the energy it gives in a single step
+    /// 
+    /// # Parameters
+    /// 
+    /// light: The relative value of the light
+    pub fn scale_light(&self, light: f32) -> u32 {
+        (light * self.light as f32) as u32
+    }
+
+    /// Finds the energy the light of a cell gives in a single step, this is 0 outside the board.
+    /// Shadows and other effects applied by a simulation are not included
+    /// 
+    /// # Parameters
+    /// 
+    /// fields: The fields holding the light
+    /// coord: The coordinate of the cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Fields, Multipliers, Size};
+    /// 
+    /// let fields = Fields::new(Size::new(2, 1), &[0.25, 0.5]).unwrap();
+    /// let multipliers = Multipliers::new(100).unwrap();
+    /// 
+    /// assert_eq!(50, multipliers.effective_light(&fields, Coord::new(1, 0)));
+    /// assert_eq!(0, multipliers.effective_light(&fields, Coord::new(2, 0)));
+    /// ```
+    pub fn effective_light(&self, fields: &Fields, coord: Coord) -> u32 {
+        match fields.size.index_of(coord) {
+            Some(index) => self.scale_light(fields.light[index]),
+            None => 0,
+        }
+    }
+}
+
+/// All the fields
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fields {
+    /// The size of the field
+    pub size: Size,
+    /// The relative value of the light
+    pub light: Field,
+    /// The height of the terrain measured in cells, this is 0 everywhere unless set with with_elevation
+    pub elevation: Field,
+    /// The initial water in every cell, this is 0 everywhere unless set with with_water
+    pub water: Field,
+    /// The temperature in every cell, this is 0 everywhere unless set with with_temperature
+    pub temperature: Field,
+    /// The kind of ground in every cell, this is open everywhere unless set with with_terrain
+    pub terrain: Field<Terrain>,
+    /// The share of the light in every channel of every cell, this is empty unless set with with_spectrum
+    /// in which case the light is a single channel
+    pub spectrum: Vec<Field>,
+}
+
+impl Fields {
+    /// Creates a new set of fields
+    /// 
+    /// Parameters 
+    /// 
+    /// size: The size of the board to put the fields
+    /// light: The values of the light field
+    /// 
+    /// # Errors
+    /// 
+    /// BoardError::FieldSize: This will occur if any of the fields are not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board;
+    /// 
+    /// let light_field = [0.0, 1.5, 2.3, 3.9];
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &light_field).unwrap();
+    /// 
+    /// assert_eq!([0.0, 1.5, 2.3, 3.9].to_vec(), fields.light);
+    /// assert_eq!(size, fields.size);
+    /// ```
+    pub fn new(size: Size, light: &[f32]) -> Result<Self, FieldCreateError> {
+        let light = Field::from_slice("Light", size, light)?;
+        let elevation = Field::filled(size, 0.0);
+        let water = Field::filled(size, 0.0);
+        let temperature = Field::filled(size, 0.0);
+        let terrain = Field::filled(size, Terrain::Open);
+
+        Ok(Self { size, light, elevation, water, temperature, terrain, spectrum: Vec::new() })
+    }
+
+    /// Sets the elevation field
+    /// 
+    /// # Parameters
+    /// 
+    /// elevation: The height of the terrain in every cell measured in cells
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if the elevation field is not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board;
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[1.0; 4]).unwrap().with_elevation(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+    /// 
+    /// assert_eq!(vec![0.0, 1.0, 2.0, 3.0], fields.elevation);
+    /// ```
+    pub fn with_elevation(mut self, elevation: &[f32]) -> Result<Self, FieldCreateError> {
+        self.elevation = Field::from_slice("Elevation", self.size, elevation)?;
+
+        Ok(self)
+    }
+
+    /// Sets the initial water field
+    /// 
+    /// # Parameters
+    /// 
+    /// water: The initial water in every cell
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if the water field is not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board;
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[1.0; 4]).unwrap().with_water(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+    /// 
+    /// assert_eq!(vec![0.0, 1.0, 2.0, 3.0], fields.water);
+    /// ```
+    pub fn with_water(mut self, water: &[f32]) -> Result<Self, FieldCreateError> {
+        self.water = Field::from_slice("Water", self.size, water)?;
+
+        Ok(self)
+    }
+
+    /// Sets the temperature field
+    /// 
+    /// # Parameters
+    /// 
+    /// temperature: The temperature in every cell
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if the temperature field is not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, climate};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let temperature = climate::latitudinal_gradient(size, 0.0, 20.0);
+    /// let fields = board::Fields::new(size, &[1.0; 4]).unwrap().with_temperature(&temperature).unwrap();
+    /// 
+    /// assert_eq!(vec![0.0, 0.0, 20.0, 20.0], fields.temperature);
+    /// ```
+    pub fn with_temperature(mut self, temperature: &[f32]) -> Result<Self, FieldCreateError> {
+        self.temperature = Field::from_slice("Temperature", self.size, temperature)?;
+
+        Ok(self)
+    }
+
+    /// Splits the light into channels such as red and blue light, each channel has a share of the light in every cell.
+    /// The shares of a cell usually add up to 1 so a plant absorbing every channel equally collects the same light as with a single channel
+    /// 
+    /// # Parameters
+    /// 
+    /// shares: The share of the light in every cell for each channel
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Channels: This will occur if there are more than spectrum::CHANNELS channels
+    /// 
+    /// FieldCreateError::Size: This will occur if a channel is not the correct size for the board
+    /// 
+    /// FieldCreateError::Value: This will occur if a share is negative or not finite
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board;
+    /// 
+    /// let size = board::Size::new(2, 1);
+    /// let fields = board::Fields::new(size, &[1.0; 2]).unwrap().with_spectrum(&[&[0.8, 0.2], &[0.2, 0.8]]).unwrap();
+    /// 
+    /// assert_eq!(2, fields.spectrum.len());
+    /// assert!(board::Fields::new(size, &[1.0; 2]).unwrap().with_spectrum(&[&[0.5, -0.5]]).is_err());
+    /// ```
+    pub fn with_spectrum(mut self, shares: &[&[f32]]) -> Result<Self, FieldCreateError> {
+        if shares.len() > CHANNELS {
+            return Err(FieldCreateError::Channels { count: shares.len(), max: CHANNELS });
+        }
+
+        self.spectrum = shares.iter()
+            .map(|share| {
+                let field = Field::from_slice("Spectrum", self.size, share)?;
+                field.check_finite("Spectrum")?;
+                field.check_non_negative("Spectrum")?;
+                Ok(field)
+            })
+            .collect::<Result<_, FieldCreateError>>()?;
+
+        Ok(self)
+    }
+
+    /// Sets the terrain field
+    /// 
+    /// # Parameters
+    /// 
+    /// terrain: The kind of ground in every cell
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if the terrain field is not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{self, Terrain};
+    /// 
+    /// let size = board::Size::new(2, 1);
+    /// let fields = board::Fields::new(size, &[1.0; 2]).unwrap().with_terrain(&[Terrain::Open, Terrain::Rock]).unwrap();
+    /// 
+    /// assert!(fields.is_blocked(1));
+    /// ```
+    pub fn with_terrain(mut self, terrain: &[Terrain]) -> Result<Self, FieldCreateError> {
+        self.terrain = Field::from_slice("Terrain", self.size, terrain)?;
+
+        Ok(self)
+    }
+
+    /// Makes every field symmetric around an axis by copying the left half onto the right half for Axis::Vertical
+    /// or the top half onto the bottom half for Axis::Horizontal, the middle column or row of a board with an odd size is kept
+    /// 
+    /// # Parameters
+    /// 
+    /// axis: The line to make the fields symmetric around
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{self, Axis};
+    /// 
+    /// let size = board::Size::new(3, 2);
+    /// let mut fields = board::Fields::new(size, &[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).unwrap();
+    /// fields.symmetrize(Axis::Vertical);
+    /// 
+    /// assert_eq!(vec![0.0, 0.1, 0.0, 0.3, 0.4, 0.3], fields.light);
+    /// 
+    /// fields.symmetrize(Axis::Horizontal);
+    /// 
+    /// assert_eq!(vec![0.0, 0.1, 0.0, 0.0, 0.1, 0.0], fields.light);
+    /// ```
+    pub fn symmetrize(&mut self, axis: Axis) {
+        let size = self.size;
+
+        symmetrize_cells(&mut self.light, size, axis);
+        symmetrize_cells(&mut self.elevation, size, axis);
+        symmetrize_cells(&mut self.water, size, axis);
+        symmetrize_cells(&mut self.temperature, size, axis);
+        symmetrize_cells(&mut self.terrain, size, axis);
+        for channel in &mut self.spectrum {
+            symmetrize_cells(channel, size, axis);
+        }
+    }
+
+    /// Mirrors every field in place, either left to right or top to bottom
+    fn flip(&mut self, horizontal: bool) {
+        let size = self.size;
+
+        flip_cells(&mut self.light, size, horizontal);
+        flip_cells(&mut self.elevation, size, horizontal);
+        flip_cells(&mut self.water, size, horizontal);
+        flip_cells(&mut self.temperature, size, horizontal);
+        flip_cells(&mut self.terrain, size, horizontal);
+        for channel in &mut self.spectrum {
+            flip_cells(channel, size, horizontal);
+        }
+    }
+
+    /// Returns true if plants cannot grow in the cell with the given index
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the index is outside the board
+    pub fn is_blocked(&self, index: usize) -> bool {
+        self.terrain[index].is_blocked()
+    }
+
+    /// Borrows the light as a 2D view without copying it
+    pub fn light_view(&self) -> FieldView<'_> {
+        self.light.view()
+    }
+
+    /// Borrows the light as a mutable 2D view without copying it
+    pub fn light_view_mut(&mut self) -> FieldViewMut<'_> {
+        self.light.view_mut()
+    }
+
+    /// Borrows the elevation as a 2D view without copying it
+    pub fn elevation_view(&self) -> FieldView<'_> {
+        self.elevation.view()
+    }
+
+    /// Borrows the elevation as a mutable 2D view without copying it
+    pub fn elevation_view_mut(&mut self) -> FieldViewMut<'_> {
+        self.elevation.view_mut()
+    }
+
+    /// Borrows the water as a 2D view without copying it
+    pub fn water_view(&self) -> FieldView<'_> {
+        self.water.view()
+    }
+
+    /// Borrows the water as a mutable 2D view without copying it
+    pub fn water_view_mut(&mut self) -> FieldViewMut<'_> {
+        self.water.view_mut()
+    }
+
+    /// Borrows the temperature as a 2D view without copying it
+    pub fn temperature_view(&self) -> FieldView<'_> {
+        self.temperature.view()
+    }
+
+    /// Borrows the temperature as a mutable 2D view without copying it
+    pub fn temperature_view_mut(&mut self) -> FieldViewMut<'_> {
+        self.temperature.view_mut()
+    }
+
+    /// Borrows the terrain as a 2D view without copying it
+    pub fn terrain_view(&self) -> FieldView<'_, Terrain> {
+        self.terrain.view()
+    }
+
+    /// Borrows the terrain as a mutable 2D view without copying it
+    pub fn terrain_view_mut(&mut self) -> FieldViewMut<'_, Terrain> {
+        self.terrain.view_mut()
+    }
+}
+
+/// The kind of ground in a cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Terrain {
+    /// Ground plants can grow on
+    #[default]
+    Open,
+    /// Bare rock where seeds cannot germinate
+    Rock,
+    /// Open water where seeds cannot germinate
+    Water,
+}
+
+impl Terrain {
+    /// Returns true if plants cannot grow on this kind of ground
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Terrain;
+    /// 
+    /// assert!(!Terrain::Open.is_blocked());
+    /// assert!(Terrain::Rock.is_blocked());
+    /// ```
+    pub fn is_blocked(&self) -> bool {
+        !matches!(self, Terrain::Open)
+    }
+}
+
+/// The size of the map
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Size {
+    /// The width of the map
+    w: usize,
+    /// The height of the map
+    h: usize,
+}
+
+impl Size {
+    /// Create a new size
+    /// 
+    /// # Parameters
+    /// 
+    /// w: The width of the size
+    /// h: The height of the size
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Size;
+    /// 
+    /// let size = Size::new(512, 512);
+    /// ```
+    pub fn new(w: usize, h: usize) -> Self {
+        Self { w, h }
+    }
+
+    /// Returns the size as a tuple of (w, h)
+    /// 
+    /// Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Size;
+    /// 
+    /// let size = Size::new(512, 256);
+    /// assert_eq!((512, 256), size.size());
+    /// ```
+    pub fn size(&self) -> (usize, usize) {
+        (self.w, self.h)
+    }
+
+    /// Returns the number of elements on the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Size;
+    /// 
+    /// let size = Size::new(512, 256);
+    /// assert_eq!(512 * 256, size.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.w * self.h
+    }
+
+    /// Returns true if the board has no elements
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Size;
+    /// 
+    /// assert!(Size::new(0, 256).is_empty());
+    /// assert!(!Size::new(512, 256).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if a coordinate is on the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate to check
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Size};
+    /// 
+    /// let size = Size::new(3, 2);
+    /// assert!(size.contains(Coord::new(2, 1)));
+    /// assert!(!size.contains(Coord::new(3, 0)));
+    /// ```
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.x < self.w && coord.y < self.h
+    }
+
+    /// Finds the index in the fields of a coordinate, the fields are stored row by row.
+    /// Returns None if the coordinate is outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate to find the index of
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Size};
+    /// 
+    /// let size = Size::new(3, 2);
+    /// assert_eq!(Some(5), size.index_of(Coord::new(2, 1)));
+    /// assert_eq!(None, size.index_of(Coord::new(0, 2)));
+    /// ```
+    pub fn index_of(&self, coord: Coord) -> Option<usize> {
+        if !self.contains(coord) {
+            return None;
+        }
+
+        Some(coord.x + coord.y * self.stride())
+    }
+
+    /// Finds the index in the fields of a coordinate, see index_of
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate to find the index of
+    #[deprecated(note = "use index_of")]
+    pub fn index(&self, coord: Coord) -> Option<usize> {
+        self.index_of(coord)
+    }
+
+    /// Finds the coordinate of an index in the fields, returns None if the index is outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index to find the coordinate of
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Size};
+    /// 
+    /// let size = Size::new(3, 2);
+    /// assert_eq!(Some(Coord::new(2, 1)), size.coord_of(5));
+    /// assert_eq!(None, size.coord_of(6));
+    /// ```
+    pub fn coord_of(&self, index: usize) -> Option<Coord> {
+        if index >= self.len() {
+            return None;
+        }
+
+        Some(Coord::new(index % self.stride(), index / self.stride()))
+    }
+
+    /// Iterates over all coordinates on the board row by row, in the same order as the fields
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Size};
+    /// 
+    /// let coords: Vec<Coord> = Size::new(2, 2).coords().collect();
+    /// assert_eq!(vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(0, 1), Coord::new(1, 1)], coords);
+    /// ```
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let (w, h) = (self.w, self.h);
+
+        (0..h).flat_map(move |y| (0..w).map(move |x| Coord::new(x, y)))
+    }
+
+    /// Gets the stride of the fields for moving in the y direction
+    pub(crate) fn stride(&self) -> usize {
+        self.w
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.w, self.h)
+    }
+}
+
+/// A position on the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Coord {
+    /// The x position
+    pub x: usize,
+    /// The y position
+    pub y: usize,
+}
+
+impl Coord {
+    /// Create a new coordinate
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position
+    /// y: The y position
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Coord;
+    /// 
+    /// let coord = Coord::new(3, 5);
+    /// assert_eq!((3, 5), (coord.x, coord.y));
+    /// ```
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    /// Moves the coordinate a distance in each direction, returns None if it would move past the top or left edge
+    /// 
+    /// # Parameters
+    /// 
+    /// dx: The distance to move in the x direction
+    /// dy: The distance to move in the y direction
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Coord;
+    /// 
+    /// assert_eq!(Some(Coord::new(2, 6)), Coord::new(3, 5).offset(-1, 1));
+    /// assert_eq!(None, Coord::new(3, 5).offset(-4, 0));
+    /// ```
+    pub fn offset(&self, dx: isize, dy: isize) -> Option<Coord> {
+        Some(Coord::new(self.x.checked_add_signed(dx)?, self.y.checked_add_signed(dy)?))
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+/// A rectangle of cells on the board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    /// The x position of the top left corner
+    pub x: usize,
+    /// The y position of the top left corner
+    pub y: usize,
+    /// The width of the rectangle
+    pub w: usize,
+    /// The height of the rectangle
+    pub h: usize,
+}
+
+impl Rect {
+    /// Creates a new rectangle
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position of the top left corner
+    /// y: The y position of the top left corner
+    /// w: The width of the rectangle
+    /// h: The height of the rectangle
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Rect;
+    /// 
+    /// let rect = Rect::new(1, 2, 3, 4);
+    /// assert_eq!((1, 2, 3, 4), (rect.x, rect.y, rect.w, rect.h));
+    /// ```
+    pub fn new(x: usize, y: usize, w: usize, h: usize) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// Creates the smallest rectangle containing two corners
+    /// 
+    /// # Parameters
+    /// 
+    /// corner1: The first corner
+    /// corner2: The opposite corner
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Rect};
+    /// 
+    /// let rect = Rect::from_corners(Coord::new(4, 1), Coord::new(2, 3));
+    /// assert_eq!(Rect::new(2, 1, 3, 3), rect);
+    /// ```
+    pub fn from_corners(corner1: Coord, corner2: Coord) -> Self {
+        let x = corner1.x.min(corner2.x);
+        let y = corner1.y.min(corner2.y);
+        let w = corner1.x.max(corner2.x) - x + 1;
+        let h = corner1.y.max(corner2.y) - y + 1;
+
+        Self { x, y, w, h }
+    }
+
+    /// Returns the number of cells in the rectangle
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Rect;
+    /// 
+    /// assert_eq!(12, Rect::new(1, 2, 3, 4).area());
+    /// ```
+    pub fn area(&self) -> usize {
+        self.w * self.h
+    }
+
+    /// Returns true if a coordinate is inside the rectangle
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate to check
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Rect};
+    /// 
+    /// let rect = Rect::new(1, 2, 3, 4);
+    /// assert!(rect.contains(Coord::new(3, 5)));
+    /// assert!(!rect.contains(Coord::new(4, 5)));
+    /// ```
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.x >= self.x && coord.x < self.x + self.w && coord.y >= self.y && coord.y < self.y + self.h
+    }
+
+    /// Returns the part of the rectangle which is inside a board
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Rect, Size};
+    /// 
+    /// let rect = Rect::new(1, 2, 3, 4).clamp(Size::new(3, 3));
+    /// assert_eq!(Rect::new(1, 2, 2, 1), rect);
+    /// ```
+    pub fn clamp(&self, size: Size) -> Self {
+        let (w, h) = size.size();
+        let x = self.x.min(w);
+        let y = self.y.min(h);
+
+        Self { x, y, w: self.w.min(w - x), h: self.h.min(h - y) }
+    }
+
+    /// Returns the part of the rectangle which is also inside another rectangle, the result has no area if they do not overlap
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The other rectangle
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::Rect;
+    /// 
+    /// assert_eq!(Rect::new(2, 3, 2, 1), Rect::new(0, 0, 4, 4).intersect(Rect::new(2, 3, 5, 5)));
+    /// assert_eq!(0, Rect::new(0, 0, 2, 2).intersect(Rect::new(3, 0, 2, 2)).area());
+    /// ```
+    pub fn intersect(&self, other: Rect) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.w).min(other.x + other.w).max(x);
+        let bottom = (self.y + self.h).min(other.y + other.h).max(y);
+
+        Self { x, y, w: right - x, h: bottom - y }
+    }
+
+    /// Iterates over all coordinates inside the rectangle row by row
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Rect};
+    /// 
+    /// let coords: Vec<Coord> = Rect::new(1, 2, 2, 1).coords().collect();
+    /// assert_eq!(vec![Coord::new(1, 2), Coord::new(2, 2)], coords);
+    /// ```
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let rect = *self;
+
+        (rect.y..rect.y + rect.h).flat_map(move |y| (rect.x..rect.x + rect.w).map(move |x| Coord::new(x, y)))
+    }
+}
+
+/// The cells covered by a region of the board
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A rectangle of cells
+    Rect(Rect),
+    /// A polygon with the corners given in cell units where cell (x, y) spans from (x, y) to (x + 1, y + 1),
+    /// a cell is inside if its center is inside the polygon
+    Polygon(Vec<(f32, f32)>),
+}
+
+impl Shape {
+    /// Returns true if a cell is inside the shape
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate of the cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Coord, Shape};
+    /// 
+    /// let triangle = Shape::Polygon(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)]);
+    /// 
+    /// assert!(triangle.contains(Coord::new(1, 1)));
+    /// assert!(!triangle.contains(Coord::new(2, 2)));
+    /// ```
+    pub fn contains(&self, coord: Coord) -> bool {
+        match self {
+            Shape::Rect(rect) => rect.contains(coord),
+            Shape::Polygon(corners) => {
+                let (x, y) = (coord.x as f32 + 0.5, coord.y as f32 + 0.5);
+
+                // Count the edges crossed by a ray going right from the center of the cell
+                let mut inside = false;
+                for (index, &(x1, y1)) in corners.iter().enumerate() {
+                    let (x2, y2) = corners[(index + 1) % corners.len()];
+                    if (y1 > y) != (y2 > y) && x < x1 + (y - y1) / (y2 - y1) * (x2 - x1) {
+                        inside = !inside;
+                    }
+                }
+
+                inside
+            }
+        }
+    }
+
+    /// Returns the smallest rectangle containing all cells of the shape
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Rect, Shape};
+    /// 
+    /// let triangle = Shape::Polygon(vec![(0.5, 1.0), (4.0, 1.0), (0.5, 3.5)]);
+    /// 
+    /// assert_eq!(Rect::new(0, 1, 4, 3), triangle.bounds());
+    /// ```
+    pub fn bounds(&self) -> Rect {
+        match self {
+            Shape::Rect(rect) => *rect,
+            Shape::Polygon(corners) => {
+                if corners.is_empty() {
+                    return Rect::new(0, 0, 0, 0);
+                }
+
+                let min_x = corners.iter().map(|corner| corner.0).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+                let min_y = corners.iter().map(|corner| corner.1).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+                let max_x = corners.iter().map(|corner| corner.0).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize;
+                let max_y = corners.iter().map(|corner| corner.1).fold(f32::NEG_INFINITY, f32::max).ceil().max(0.0) as usize;
+
+                Rect::new(min_x, min_y, max_x.saturating_sub(min_x), max_y.saturating_sub(min_y))
+            }
+        }
+    }
+
+    /// Iterates over all cells of the shape which are on a board row by row
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn coords(&self, size: Size) -> impl Iterator<Item = Coord> + '_ {
+        self.bounds().clamp(size).coords().filter(move |&coord| self.contains(coord))
+    }
+
+    /// Mirrors the shape on a board either left to right or top to bottom
+    fn flip(&self, size: Size, horizontal: bool) -> Self {
+        let (w, h) = size.size();
+        match self {
+            Shape::Rect(rect) if horizontal => Shape::Rect(Rect::new(w.saturating_sub(rect.x + rect.w), rect.y, rect.w, rect.h)),
+            Shape::Rect(rect) => Shape::Rect(Rect::new(rect.x, h.saturating_sub(rect.y + rect.h), rect.w, rect.h)),
+            Shape::Polygon(corners) => Shape::Polygon(corners.iter()
+                .map(|&(x, y)| if horizontal { (w as f32 - x, y) } else { (x, h as f32 - y) })
+                .collect()),
+        }
+    }
+
+    /// Moves the shape, the part of a rectangle moved past the top or left edge of the board is cut off
+    fn translate(&self, dx: isize, dy: isize) -> Self {
+        match self {
+            Shape::Rect(rect) => {
+                let (x, w) = translate_span(rect.x, rect.w, dx);
+                let (y, h) = translate_span(rect.y, rect.h, dy);
+
+                Shape::Rect(Rect::new(x, y, w, h))
+            }
+            Shape::Polygon(corners) => Shape::Polygon(corners.iter().map(|&(x, y)| (x + dx as f32, y + dy as f32)).collect()),
+        }
+    }
+}
+
+impl From<Rect> for Shape {
+    fn from(rect: Rect) -> Self {
+        Shape::Rect(rect)
+    }
+}
+
+/// Moves a span of cells cutting off the part which is moved below 0
+fn translate_span(start: usize, len: usize, offset: isize) -> (usize, usize) {
+    let start = start as isize + offset;
+    if start >= 0 {
+        (start as usize, len)
+    } else {
+        (0, len.saturating_sub(start.unsigned_abs()))
+    }
+}
+
+/// A named part of the board such as a valley or a plateau
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    /// The name of the region
+    pub name: String,
+    /// The cells of the region
+    pub shape: Shape,
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum FieldCreateError {
+    #[error("{:?} field has wrong size ({:?}) should be ({:?}) on board with size {:?}", name, len, size.len(), size)]
+    Size {
+        name: String,
+        len: usize,
+        size: Size,
+    },
+    #[error("{:?} has not been set", name)]
+    Missing {
+        name: String,
+    },
+    #[error("{:?} field has the invalid value {:?} at index {:?}", name, value, index)]
+    Value {
+        name: String,
+        index: usize,
+        value: f32,
+    },
+    #[error("The light has {:?} channels but at most {:?} are allowed", count, max)]
+    Channels {
+        count: usize,
+        max: usize,
+    },
+    #[error(transparent)]
+    Multiplier(#[from] MultiplierError),
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum BoardConcatError {
+    #[error("Boards with heights {:?} and {:?} cannot be put next to each other", first, second)]
+    Height {
+        first: usize,
+        second: usize,
+    },
+    #[error("Boards with widths {:?} and {:?} cannot be put on top of each other", first, second)]
+    Width {
+        first: usize,
+        second: usize,
+    },
+    #[error("Boards with light multipliers {:?} and {:?} cannot be joined", first, second)]
+    Multiplier {
+        first: u32,
+        second: u32,
+    },
+    #[error("Boards with {:?} and {:?} light channels cannot be joined", first, second)]
+    Spectrum {
+        first: usize,
+        second: usize,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum MultiplierError {
+    #[error("{:?} multiplier is 0", name)]
+    Zero {
+        name: String,
+    },
+    #[error("{:?} multiplier {:?} is larger than the maximum {:?}", name, value, max)]
+    TooLarge {
+        name: String,
+        value: u32,
+        max: u32,
+    },
+}
+
+impl HeapSize for Fields {
+    fn heap_bytes(&self) -> usize {
+        self.light.heap_bytes() + self.elevation.heap_bytes() + self.water.heap_bytes() + self.temperature.heap_bytes() + self.terrain.heap_bytes()
+            + vec_bytes(&self.spectrum) + self.spectrum.iter().map(HeapSize::heap_bytes).sum::<usize>()
+    }
+}
+
+impl HeapSize for Board {
+    fn heap_bytes(&self) -> usize {
+        let regions = self.regions.iter()
+            .map(|region| region.name.capacity() + match &region.shape {
+                Shape::Rect(_) => 0,
+                Shape::Polygon(corners) => vec_bytes(corners),
+            })
+            .sum::<usize>();
+
+        self.fields.heap_bytes() + self.seed_bank.heap_bytes() + vec_bytes(&self.regions) + regions + self.modifiers.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_new() {
+        let size = Size::new(40, 55);
+        assert_eq!((40, 55), (size.w, size.h));
+    }
+
+    #[test]
+    fn size_size() {
+        let size = Size::new(40, 55);
+        assert_eq!((40, 55), size.size());      
+    }
+
+    #[test]
+    fn size_len() {
+        let size = Size::new(40, 55);
+        assert_eq!(40 * 55, size.len());      
+    }
+
+    #[test]
+    fn size_stride() {
+        let size = Size::new(40, 55);
+        assert_eq!(40, size.stride());   
+    }
+
+    #[test]
+    fn size_is_empty() {
+        assert!(Size::new(0, 55).is_empty());
+        assert!(Size::new(40, 0).is_empty());
+        assert!(!Size::new(40, 55).is_empty());
+    }
+
+    #[test]
+    fn size_index_of() {
+        let size = Size::new(40, 55);
+        assert_eq!(Some(0), size.index_of(Coord::new(0, 0)));
+        assert_eq!(Some(3 + 5 * 40), size.index_of(Coord::new(3, 5)));
+        assert_eq!(None, size.index_of(Coord::new(40, 5)));
+        assert_eq!(None, size.index_of(Coord::new(3, 55)));
+    }
+
+    #[test]
+    fn size_coord_of() {
+        let size = Size::new(40, 55);
+        assert_eq!(Some(Coord::new(0, 0)), size.coord_of(0));
+        assert_eq!(Some(Coord::new(3, 5)), size.coord_of(3 + 5 * 40));
+        assert_eq!(None, size.coord_of(40 * 55));
+    }
+
+    #[test]
+    fn size_coords() {
+        let size = Size::new(4, 3);
+        let coords: Vec<Coord> = size.coords().collect();
+        assert_eq!(size.len(), coords.len());
+        for (index, coord) in coords.into_iter().enumerate() {
+            assert_eq!(Some(index), size.index_of(coord));
+            assert_eq!(Some(coord), size.coord_of(index));
+        }
+        assert_eq!(0, Size::new(0, 3).coords().count());
+    }
+
+    #[test]
+    fn size_display() {
+        assert_eq!("40x55", Size::new(40, 55).to_string());
+        assert_eq!(Size::new(0, 0), Size::default());
+    }
+
+    #[test]
+    fn coord_new() {
+        let coord = Coord::new(3, 5);
+        assert_eq!((3, 5), (coord.x, coord.y));
+    }
+
+    #[test]
+    fn coord_display() {
+        assert_eq!("(3, 5)", Coord::new(3, 5).to_string());
+    }
+
+    #[test]
+    fn rect_new() {
+        let rect = Rect::new(1, 2, 3, 4);
+        assert_eq!((1, 2, 3, 4), (rect.x, rect.y, rect.w, rect.h));
+    }
+
+    #[test]
+    fn rect_from_corners() {
+        assert_eq!(Rect::new(2, 1, 3, 3), Rect::from_corners(Coord::new(4, 1), Coord::new(2, 3)));
+        assert_eq!(Rect::new(2, 1, 3, 3), Rect::from_corners(Coord::new(2, 3), Coord::new(4, 1)));
+        assert_eq!(Rect::new(2, 1, 1, 1), Rect::from_corners(Coord::new(2, 1), Coord::new(2, 1)));
+    }
+
+    #[test]
+    fn rect_area() {
+        assert_eq!(12, Rect::new(1, 2, 3, 4).area());
+        assert_eq!(0, Rect::new(1, 2, 0, 4).area());
+    }
+
+    #[test]
+    fn rect_contains() {
+        let rect = Rect::new(1, 2, 3, 4);
+        assert!(rect.contains(Coord::new(1, 2)));
+        assert!(rect.contains(Coord::new(3, 5)));
+        assert!(!rect.contains(Coord::new(0, 2)));
+        assert!(!rect.contains(Coord::new(1, 6)));
+    }
+
+    #[test]
+    fn rect_clamp() {
+        let size = Size::new(3, 3);
+        assert_eq!(Rect::new(1, 2, 2, 1), Rect::new(1, 2, 3, 4).clamp(size));
+        assert_eq!(Rect::new(0, 0, 2, 2), Rect::new(0, 0, 2, 2).clamp(size));
+        assert_eq!(0, Rect::new(5, 5, 2, 2).clamp(size).area());
+    }
+
+    #[test]
+    fn rect_coords() {
+        let coords: Vec<Coord> = Rect::new(1, 2, 2, 2).coords().collect();
+        assert_eq!(vec![Coord::new(1, 2), Coord::new(2, 2), Coord::new(1, 3), Coord::new(2, 3)], coords);
+    }
+
+    #[test]
+    fn fields_new() -> Result<(), FieldCreateError> {
+        let size = Size::new(2, 2);
+        let light_field = [1.0, 2.0, 3.0, 4.0];
+        let fields = Fields::new(size, &light_field)?;
+
+        assert_eq!(size, fields.size);
+        assert_eq!(light_field.to_vec(), fields.light);
+        assert_eq!(vec![0.0; 4], fields.elevation);
+        assert_eq!(vec![0.0; 4], fields.water);
+        assert_eq!(vec![0.0; 4], fields.temperature);
+        assert_eq!(vec![Terrain::Open; 4], fields.terrain);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fields_with_elevation() -> Result<(), FieldCreateError> {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4])?.with_elevation(&[1.0, 2.0, 3.0, 4.0])?;
+
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], fields.elevation);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fields_with_water() -> Result<(), FieldCreateError> {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4])?.with_water(&[1.0, 2.0, 3.0, 4.0])?;
+
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], fields.water);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fields_with_water_error_size() {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4]).unwrap().with_water(&[1.0; 3]);
+
+        assert_eq!(FieldCreateError::Size {name: "Water".to_string(), len: 3, size}, fields.unwrap_err())
+    }
+
+    #[test]
+    fn fields_with_temperature() -> Result<(), FieldCreateError> {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4])?.with_temperature(&[1.0, 2.0, 3.0, 4.0])?;
+
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], fields.temperature);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fields_with_temperature_error_size() {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4]).unwrap().with_temperature(&[1.0; 3]);
+
+        assert_eq!(FieldCreateError::Size {name: "Temperature".to_string(), len: 3, size}, fields.unwrap_err())
+    }
+
+    #[test]
+    fn fields_with_terrain() -> Result<(), FieldCreateError> {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[1.0; 3])?.with_terrain(&[Terrain::Rock, Terrain::Open, Terrain::Water])?;
+
+        assert_eq!(vec![Terrain::Rock, Terrain::Open, Terrain::Water], fields.terrain);
+        assert_eq!(vec![true, false, true], (0..3).map(|index| fields.is_blocked(index)).collect::<Vec<_>>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fields_with_terrain_error_size() {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4]).unwrap().with_terrain(&[Terrain::Open; 3]);
+
+        assert_eq!(FieldCreateError::Size {name: "Terrain".to_string(), len: 3, size}, fields.unwrap_err())
+    }
+
+    #[test]
+    fn terrain_is_blocked() {
+        assert!(!Terrain::Open.is_blocked());
+        assert!(Terrain::Rock.is_blocked());
+        assert!(Terrain::Water.is_blocked());
+    }
+
+    #[test]
+    fn fields_with_elevation_error_size() {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[1.0; 4]).unwrap().with_elevation(&[1.0; 5]);
+
+        assert_eq!(FieldCreateError::Size {name: "Elevation".to_string(), len: 5, size}, fields.unwrap_err())
+    }
+
+    #[test]
+    fn fields_new_error_light() {
+        let size = Size::new(2, 2);
+        let light_field = [1.0, 2.0, 3.0];
+        let fields = Fields::new(size, &light_field);
+
+        assert!(fields.is_err());
+        assert_eq!(FieldCreateError::Size {name: "Light".to_string(), len: 3, size}, fields.unwrap_err())
+    }
+
+    #[test]
+    fn multipliers_new() -> Result<(), MultiplierError> {
+        let multipliers = Multipliers::new(1024)?;
+
+        assert_eq!(1024, multipliers.light);
+
+        Ok(())
+    }
+
+    #[test]
+    fn multipliers_new_error() {
+        assert_eq!(MultiplierError::Zero { name: "Light".to_string() }, Multipliers::new(0).unwrap_err());
+        assert_eq!(MultiplierError::TooLarge { name: "Light".to_string(), value: Multipliers::MAX + 1, max: Multipliers::MAX }, Multipliers::new(Multipliers::MAX + 1).unwrap_err());
+        assert!(Multipliers::new(Multipliers::MAX).is_ok());
+    }
+
+    #[test]
+    fn multipliers_effective_light() {
+        let fields = Fields::new(Size::new(2, 2), &[0.0, 0.5, 1.0, 0.25]).unwrap();
+        let multipliers = Multipliers::new(100).unwrap();
+
+        assert_eq!(vec![0, 50, 100, 25], (0..4).map(|index| multipliers.effective_light(&fields, fields.size.coord_of(index).unwrap())).collect::<Vec<_>>());
+        assert_eq!(0, multipliers.effective_light(&fields, Coord::new(0, 2)));
+        assert_eq!(12, multipliers.scale_light(0.125));
+    }
+
+    #[test]
+    fn board_builder_error_multiplier() {
+        let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).multiplier_light(0).build();
+
+        assert_eq!(FieldCreateError::Multiplier(MultiplierError::Zero { name: "Light".to_string() }), board.unwrap_err());
+    }
+
+    #[test]
+    fn board_new() {
+        let size = Size::new(2, 2);
+        let light_field = [1.0, 2.0, 3.0, 4.0];
+        let fields = Fields::new(size, &light_field).unwrap();
+        let multipliers = Multipliers::new(1024).unwrap();
+        let board = Board::new(multipliers, fields.clone());
+
+        assert_eq!(multipliers, board.multipliers);
+        assert_eq!(fields, board.fields);
+    }
+
+    #[test]
+    fn board_builder_build() -> Result<(), FieldCreateError> {
+        let board = BoardBuilder::new()
+            .size(2, 2)
+            .light_from_slice(&[0.0, 0.5, 1.0, 2.0])
+            .multiplier_light(100)
+            .elevation(&[1.0; 4])
+            .water(&[2.0; 4])
+            .temperature(&[3.0; 4])
+            .terrain(&[Terrain::Rock; 4])
+            .build()?;
+
+        assert_eq!(Size::new(2, 2), board.fields.size);
+        assert_eq!(vec![0.0, 0.5, 1.0, 2.0], board.fields.light);
+        assert_eq!(100, board.multipliers.light);
+        assert_eq!(vec![1.0; 4], board.fields.elevation);
+        assert_eq!(vec![2.0; 4], board.fields.water);
+        assert_eq!(vec![3.0; 4], board.fields.temperature);
+        assert_eq!(vec![Terrain::Rock; 4], board.fields.terrain);
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_builder_light() -> Result<(), FieldCreateError> {
+        let uniform = BoardBuilder::new().size(2, 1).light_uniform(0.5).build()?;
+        let generated = BoardBuilder::new().size(2, 2).light_generator(|coord| coord.y as f32).build()?;
+
+        assert_eq!(vec![0.5, 0.5], uniform.fields.light);
+        assert_eq!(1, uniform.multipliers.light);
+        assert_eq!(vec![0.0, 0.0, 1.0, 1.0], generated.fields.light);
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_builder_error_missing() {
+        assert_eq!(FieldCreateError::Missing {name: "Size".to_string()}, BoardBuilder::new().light_uniform(1.0).build().unwrap_err());
+        assert_eq!(FieldCreateError::Missing {name: "Light".to_string()}, BoardBuilder::new().size(2, 2).build().unwrap_err());
+    }
+
+    #[test]
+    fn board_builder_error_size() {
+        let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).water(&[1.0; 3]).build();
+
+        assert_eq!(FieldCreateError::Size {name: "Water".to_string(), len: 3, size: Size::new(2, 2)}, board.unwrap_err());
+    }
+
+    #[test]
+    fn board_builder_error_value() {
+        let light = BoardBuilder::new().size(2, 1).light_from_slice(&[1.0, -1.0]).build();
+        let temperature = BoardBuilder::new().size(2, 1).light_uniform(1.0).temperature(&[f32::INFINITY, 0.0]).build();
+
+        assert_eq!(FieldCreateError::Value {name: "Light".to_string(), index: 1, value: -1.0}, light.unwrap_err());
+        assert_eq!(FieldCreateError::Value {name: "Temperature".to_string(), index: 0, value: f32::INFINITY}, temperature.unwrap_err());
+    }
+
+    #[test]
+    fn reframe_cells_grow_shift() {
+        let (cells, removed) = reframe_cells(vec![0, 1, 2, 3, 4, 5], Size::new(3, 2), Rect::new(1, 1, 3, 2), || 9);
+
+        assert_eq!(vec![4, 5, 9, 9, 9, 9], cells);
+        assert_eq!(vec![(0, 0), (1, 1), (2, 2), (3, 3)], removed);
+    }
+
+    #[test]
+    fn board_resize() {
+        let mut board = BoardBuilder::new().size(2, 2).light_from_slice(&[0.1, 0.2, 0.3, 0.4]).water(&[1.0; 4]).build().unwrap();
+        board.seed_bank.bury(3, crate::population::Plant::new(1, crate::genome::Genome::new(&[0.5, 0.5]).unwrap()), 0, 4);
+        board.resize(Size::new(3, 1), &Fill { light: 1.0, water: 2.0, ..Default::default() });
+
+        assert_eq!(Size::new(3, 1), board.fields.size);
+        assert_eq!(vec![0.1, 0.2, 1.0], board.fields.light);
+        assert_eq!(vec![1.0, 1.0, 2.0], board.fields.water);
+        assert_eq!(vec![0.0; 3], board.fields.elevation);
+        assert_eq!(vec![Terrain::Open; 3], board.fields.terrain);
+        assert_eq!(Size::new(3, 1), board.seed_bank.size());
+        assert_eq!(0, board.seed_bank.count());
+    }
+
+    #[test]
+    fn board_crop() {
+        let mut board = BoardBuilder::new().size(3, 3).light_generator(|coord| (coord.x + 3 * coord.y) as f32 / 10.0)
+            .terrain(&[Terrain::Open, Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Water, Terrain::Open, Terrain::Open, Terrain::Open])
+            .build()
+            .unwrap();
+        board.seed_bank.bury(5, crate::population::Plant::new(1, crate::genome::Genome::new(&[0.5, 0.5]).unwrap()), 0, 4);
+        board.crop(1, 0, 2, 2);
+
+        assert_eq!(Size::new(2, 2), board.fields.size);
+        assert_eq!(vec![0.1, 0.2, 0.4, 0.5], board.fields.light);
+        assert_eq!(vec![Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Water], board.fields.terrain);
+        assert_eq!(1, board.seed_bank.seeds(Coord::new(1, 1)).len());
+    }
+
+    #[test]
+    fn board_crop_outside() {
+        let mut board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        board.crop(5, 1, 2, 2);
+
+        assert!(board.fields.size.is_empty());
+        assert!(board.fields.light.is_empty());
+    }
+
+    #[test]
+    fn concat_cells_directions() {
+        let first = [0, 1, 2, 3];
+        let second = [4, 5];
+
+        assert_eq!(vec![0, 1, 4, 2, 3, 5], concat_cells(&first, Size::new(2, 2), &second, Size::new(1, 2), true));
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], concat_cells(&first, Size::new(2, 2), &second, Size::new(2, 1), false));
+    }
+
+    #[test]
+    fn board_modifiers_reframe() {
+        let mut left = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        left.set_modifier(Coord::new(1, 1), Modifier::new(0.5, 1.0));
+        let right = BoardBuilder::new().size(1, 2).light_uniform(1.0).build().unwrap();
+        let mut board = left.concat_horizontal(&right).unwrap().mirror_vertical();
+
+        assert_eq!(Size::new(3, 4), board.modifiers.size());
+        assert_eq!(vec![Coord::new(1, 1), Coord::new(1, 2)], board.fields.size.coords().filter(|&coord| !board.modifier(coord).is_identity()).collect::<Vec<_>>());
+
+        board.crop(1, 2, 2, 2);
+        assert_eq!(Modifier::new(0.5, 1.0), board.modifier(Coord::new(0, 0)));
+        board.resize(Size::new(3, 3), &Fill::default());
+        assert!(board.modifier(Coord::new(2, 2)).is_identity());
+
+        board.clear_modifiers();
+        assert!(board.modifiers.iter().all(Modifier::is_identity));
+    }
+
+    #[test]
+    fn flip_cells_directions() {
+        let mut cells = [0, 1, 2, 3, 4, 5];
+        flip_cells(&mut cells, Size::new(3, 2), true);
+        assert_eq!([2, 1, 0, 5, 4, 3], cells);
+
+        let mut cells = [0, 1, 2, 3, 4, 5];
+        flip_cells(&mut cells, Size::new(2, 3), false);
+        assert_eq!([4, 5, 2, 3, 0, 1], cells);
+    }
+
+    #[test]
+    fn fields_symmetrize() {
+        let size = Size::new(4, 3);
+        let mut fields = Fields::new(size, &(0..12).map(|value| value as f32).collect::<Vec<f32>>()).unwrap()
+            .with_terrain(&[Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Rock]).unwrap();
+        fields.symmetrize(Axis::Vertical);
+
+        assert_eq!(vec![0.0, 1.0, 1.0, 0.0, 4.0, 5.0, 5.0, 4.0, 8.0, 9.0, 9.0, 8.0], fields.light);
+        assert_eq!(Terrain::Rock, fields.terrain[3]);
+        assert_eq!(Terrain::Open, fields.terrain[11]);
+
+        fields.symmetrize(Axis::Horizontal);
+
+        assert_eq!(vec![0.0, 1.0, 1.0, 0.0, 4.0, 5.0, 5.0, 4.0, 0.0, 1.0, 1.0, 0.0], fields.light);
+        assert_eq!(Terrain::Rock, fields.terrain[11]);
+    }
+
+    #[test]
+    fn board_mirror_horizontal() {
+        let mut board = BoardBuilder::new().size(3, 2).light_from_slice(&[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).temperature(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).build().unwrap();
+        board.seed_bank.bury(3, crate::population::Plant::new(1, crate::genome::Genome::new(&[0.5, 0.5]).unwrap()), 0, 4);
+        board.add_region("edge", Rect::new(0, 0, 1, 2));
+        board.add_region("wedge", Shape::Polygon(vec![(0.0, 0.0), (2.2, 0.0), (0.0, 2.2)]));
+        let world = board.mirror_horizontal();
+
+        assert_eq!(Size::new(6, 2), world.fields.size);
+        assert_eq!(vec![1.0, 2.0, 3.0, 3.0, 2.0, 1.0, 4.0, 5.0, 6.0, 6.0, 5.0, 4.0], world.fields.temperature);
+        assert_eq!(1, world.seed_bank.seeds(Coord::new(0, 1)).len());
+        assert_eq!(1, world.seed_bank.seeds(Coord::new(5, 1)).len());
+        assert_eq!(2, world.seed_bank.count());
+
+        assert_eq!(4, world.regions.len());
+        assert_eq!(Shape::Rect(Rect::new(5, 0, 1, 2)), world.region("edge (mirrored)").unwrap().shape);
+        let wedge: Vec<Coord> = world.region("wedge").unwrap().shape.coords(world.fields.size).map(|coord| Coord::new(5 - coord.x, coord.y)).collect();
+        let mirrored: Vec<Coord> = world.region("wedge (mirrored)").unwrap().shape.coords(world.fields.size).collect();
+        assert_eq!(3, mirrored.len());
+        assert_eq!(wedge.len(), mirrored.len());
+        assert!(wedge.iter().all(|coord| mirrored.contains(coord)));
+    }
+
+    #[test]
+    fn board_mirror_vertical() {
+        let mut board = BoardBuilder::new().size(2, 2).light_uniform(1.0).terrain(&[Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Open]).build().unwrap();
+        board.add_region("top", Rect::new(0, 0, 2, 1));
+        let world = board.mirror_vertical();
+
+        assert_eq!(Size::new(2, 4), world.fields.size);
+        assert_eq!(vec![Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Rock, Terrain::Open], world.fields.terrain);
+        assert_eq!(Shape::Rect(Rect::new(0, 3, 2, 1)), world.region("top (mirrored)").unwrap().shape);
+        assert_eq!(world.fields.light, world.mirror_vertical().fields.light[..8].to_vec());
+    }
+
+    #[test]
+    fn board_concat_horizontal() {
+        let mut left = BoardBuilder::new().size(1, 2).light_uniform(0.5).terrain(&[Terrain::Rock, Terrain::Open]).build().unwrap();
+        let right = BoardBuilder::new().size(2, 2).light_uniform(1.0).temperature(&[1.0, 2.0, 3.0, 4.0]).build().unwrap();
+        left.seed_bank.bury(1, crate::population::Plant::new(1, crate::genome::Genome::new(&[0.5, 0.5]).unwrap()), 0, 4);
+        let board = left.concat_horizontal(&right).unwrap();
+
+        assert_eq!(Size::new(3, 2), board.fields.size);
+        assert_eq!(vec![0.0, 1.0, 2.0, 0.0, 3.0, 4.0], board.fields.temperature);
+        assert_eq!(vec![Terrain::Rock, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open, Terrain::Open], board.fields.terrain);
+        assert_eq!(1, board.seed_bank.seeds(Coord::new(0, 1)).len());
+        assert_eq!(Size::new(3, 2), board.seed_bank.size());
+    }
+
+    #[test]
+    fn board_concat_vertical() {
+        let top = BoardBuilder::new().size(2, 1).light_uniform(0.5).elevation(&[1.0, 2.0]).build().unwrap();
+        let bottom = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        let board = top.concat_vertical(&bottom).unwrap();
+
+        assert_eq!(Size::new(2, 3), board.fields.size);
+        assert_eq!(vec![1.0, 2.0, 0.0, 0.0, 0.0, 0.0], board.fields.elevation);
+        assert_eq!(vec![0.5, 0.5, 1.0, 1.0, 1.0, 1.0], board.fields.light);
+    }
+
+    #[test]
+    fn board_concat_error() {
+        let board = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+        let tall = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        let wide = BoardBuilder::new().size(3, 1).light_uniform(1.0).build().unwrap();
+        let bright = BoardBuilder::new().size(2, 1).light_uniform(1.0).multiplier_light(10).build().unwrap();
+
+        assert_eq!(BoardConcatError::Height { first: 1, second: 2 }, board.concat_horizontal(&tall).unwrap_err());
+        assert_eq!(BoardConcatError::Width { first: 2, second: 3 }, board.concat_vertical(&wide).unwrap_err());
+        assert_eq!(BoardConcatError::Multiplier { first: 1, second: 10 }, board.concat_vertical(&bright).unwrap_err());
+    }
+
+    #[test]
+    fn board_regions_reframe() {
+        let mut board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+        board.add_region("corner", Rect::new(0, 0, 2, 2));
+        board.add_region("valley", Shape::Polygon(vec![(2.0, 2.0), (4.0, 2.0), (4.0, 4.0)]));
+        board.crop(1, 1, 3, 3);
+
+        assert_eq!(Shape::Rect(Rect::new(0, 0, 1, 1)), board.region("corner").unwrap().shape);
+        assert_eq!(Shape::Polygon(vec![(1.0, 1.0), (3.0, 1.0), (3.0, 3.0)]), board.region("valley").unwrap().shape);
+        assert_eq!(Some("corner".to_string()), board.remove_region("corner").map(|region| region.name));
+        assert!(board.region("corner").is_none());
+    }
+
+    #[test]
+    fn board_regions_concat() {
+        let mut left = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+        let mut right = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+        left.add_region("plateau", Rect::new(0, 0, 2, 1));
+        right.add_region("valley", Rect::new(1, 0, 1, 1));
+        let board = left.concat_horizontal(&right).unwrap();
+
+        assert_eq!(Shape::Rect(Rect::new(0, 0, 2, 1)), board.region("plateau").unwrap().shape);
+        assert_eq!(Shape::Rect(Rect::new(3, 0, 1, 1)), board.region("valley").unwrap().shape);
+    }
+
+    #[test]
+    fn shape_coords_polygon() {
+        let diamond = Shape::Polygon(vec![(1.5, 0.0), (3.0, 1.5), (1.5, 3.0), (0.0, 1.5)]);
+        let coords: Vec<Coord> = diamond.coords(Size::new(2, 5)).collect();
+
+        assert_eq!(vec![Coord::new(1, 0), Coord::new(0, 1), Coord::new(1, 1), Coord::new(1, 2)], coords);
+        assert_eq!(0, Shape::Polygon(Vec::new()).coords(Size::new(2, 2)).count());
+    }
+}
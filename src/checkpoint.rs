@@ -0,0 +1,1024 @@
+use thiserror::Error;
+
+use crate::board::Size;
+use crate::genome::Genome;
+use crate::migrate;
+use crate::phenotype::Phenotype;
+use crate::population::{Plant, PlantId};
+use crate::snapshot::StateSnapshot;
+use crate::tuning::Parallelism;
+
+/// The first bytes of a saved checkpoint chain
+pub const MAGIC: [u8; 4] = *b"EPCK";
+/// The version of the format of a saved checkpoint chain, chains saved with older versions are upgraded by the migrate module
+pub const VERSION: u8 = 3;
+
+/// The tag of a full checkpoint
+pub(crate) const KIND_FULL: u8 = 0;
+/// The tag of a delta checkpoint
+pub(crate) const KIND_DELTA: u8 = 1;
+
+/// How the bytes of a checkpoint are compressed when saved. The only compression is a run-length encoding built into
+/// the crate, there is no zstd or other general purpose compressor, so the savings come from the runs of empty cells
+/// and zero bytes rather than from repeated patterns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// The bytes are saved as they are
+    None,
+    /// Runs of repeated bytes are saved once with their length, the empty cells and the unused parts of the numbers
+    /// of a sparse board shrink to almost nothing
+    #[default]
+    RunLength,
+}
+
+impl Compression {
+    /// Returns the tag of the compression in the saved bytes
+    pub(crate) fn tag(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::RunLength => 1,
+        }
+    }
+
+    /// Finds the compression with a tag
+    pub(crate) fn from_tag(tag: u8) -> Result<Self, CheckpointError> {
+        match tag {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::RunLength),
+            _ => Err(CheckpointError::Corrupt),
+        }
+    }
+}
+
+/// The changes to every cell since a full checkpoint
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotDelta {
+    /// The tick of the full checkpoint the changes are against
+    pub base: u64,
+    /// The tick the snapshot was taken at
+    pub tick: u64,
+    /// The position in the stream of the random number generator
+    pub rng_position: u128,
+    /// The index and the new plant of every cell whose plant changed
+    pub cells: Vec<(usize, Option<Plant>)>,
+    /// The index and the new water of every cell whose water changed
+    pub water: Vec<(usize, f32)>,
+    /// The index and the new nutrients of every cell whose nutrients changed
+    pub nutrients: Vec<(usize, f32)>,
+    /// How the work on the cells was split over threads when the snapshot was taken
+    pub parallelism: Parallelism,
+}
+
+impl StateSnapshot {
+    /// Finds the changes to every cell since an earlier snapshot
+    /// 
+    /// # Parameters
+    /// 
+    /// base: The earlier snapshot the changes are against
+    /// 
+    /// # Errors
+    /// 
+    /// CheckpointError::Size: This will occur if the snapshots have boards of different sizes
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let base = simulation.snapshot();
+    /// simulation.step();
+    /// let delta = simulation.snapshot().delta(&base).unwrap();
+    /// 
+    /// // Only the plant gained energy
+    /// assert_eq!(1, delta.cells.len());
+    /// assert_eq!(simulation.snapshot(), delta.apply(&base).unwrap());
+    /// ```
+    pub fn delta(&self, base: &StateSnapshot) -> Result<SnapshotDelta, CheckpointError> {
+        if self.size != base.size {
+            return Err(CheckpointError::Size { expected: base.size, found: self.size });
+        }
+
+        let cells = self.cells.iter().zip(base.cells.iter()).enumerate()
+            .filter(|(_, (cell, old))| cell != old)
+            .map(|(index, (cell, _))| (index, cell.clone()))
+            .collect();
+        let water = changed_values(&self.water, &base.water);
+        let nutrients = changed_values(&self.nutrients, &base.nutrients);
+
+        Ok(SnapshotDelta { base: base.tick, tick: self.tick, rng_position: self.rng_position, cells, water, nutrients, parallelism: self.parallelism })
+    }
+}
+
+impl SnapshotDelta {
+    /// Applies the changes to the snapshot they are against
+    /// 
+    /// # Parameters
+    /// 
+    /// base: The snapshot the changes are against
+    /// 
+    /// # Errors
+    /// 
+    /// CheckpointError::Base: This will occur if the snapshot is not from the tick the changes are against
+    /// 
+    /// CheckpointError::Corrupt: This will occur if a change is outside the board of the snapshot
+    pub fn apply(&self, base: &StateSnapshot) -> Result<StateSnapshot, CheckpointError> {
+        if self.base != base.tick {
+            return Err(CheckpointError::Base { expected: self.base, found: base.tick });
+        }
+
+        let mut snapshot = StateSnapshot { tick: self.tick, rng_position: self.rng_position, parallelism: self.parallelism, ..base.clone() };
+        for (index, cell) in &self.cells {
+            *snapshot.cells.get_mut(*index).ok_or(CheckpointError::Corrupt)? = cell.clone();
+        }
+        for &(index, water) in &self.water {
+            *snapshot.water.get_mut(index).ok_or(CheckpointError::Corrupt)? = water;
+        }
+        for &(index, nutrients) in &self.nutrients {
+            *snapshot.nutrients.get_mut(index).ok_or(CheckpointError::Corrupt)? = nutrients;
+        }
+
+        Ok(snapshot)
+    }
+}
+
+/// A single saved state in a checkpoint chain
+#[derive(Clone, Debug, PartialEq)]
+pub enum Checkpoint {
+    /// The full state
+    Full(StateSnapshot),
+    /// The changes since the latest full checkpoint before it
+    Delta(SnapshotDelta),
+}
+
+impl Checkpoint {
+    /// Returns the tick the state was saved at
+    pub fn tick(&self) -> u64 {
+        match self {
+            Checkpoint::Full(snapshot) => snapshot.tick,
+            Checkpoint::Delta(delta) => delta.tick,
+        }
+    }
+
+    /// Returns true if this is a full checkpoint
+    pub fn is_full(&self) -> bool {
+        matches!(self, Checkpoint::Full(_))
+    }
+
+    /// Copies the genomes of the plants in the checkpoint which pass a filter, in the order of their cells, such that they can be
+    /// introduced into another simulation. A full checkpoint holds every plant while a delta only holds the plants in the cells
+    /// which changed since its base, use CheckpointChain::extract_genomes to get every plant of a delta
+    /// 
+    /// # Parameters
+    /// 
+    /// filter: Returns true for the plants whose genomes are copied
+    pub fn extract_genomes<F: FnMut(&Plant) -> bool>(&self, mut filter: F) -> Vec<Genome> {
+        let plants: Box<dyn Iterator<Item = &Plant>> = match self {
+            Checkpoint::Full(snapshot) => Box::new(snapshot.cells.iter().flatten()),
+            Checkpoint::Delta(delta) => Box::new(delta.cells.iter().filter_map(|(_, cell)| cell.as_ref())),
+        };
+
+        plants.filter(|plant| filter(plant)).map(|plant| plant.genome.clone()).collect()
+    }
+
+    /// Writes the uncompressed bytes of the checkpoint
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Checkpoint::Full(snapshot) => {
+                let (width, height) = snapshot.size.size();
+                out.extend_from_slice(&snapshot.tick.to_le_bytes());
+                out.extend_from_slice(&(width as u64).to_le_bytes());
+                out.extend_from_slice(&(height as u64).to_le_bytes());
+                out.extend_from_slice(&snapshot.rng_position.to_le_bytes());
+                for cell in &snapshot.cells {
+                    put_cell(out, cell.as_ref());
+                }
+                for value in snapshot.water.iter().chain(snapshot.nutrients.iter()) {
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+                put_parallelism(out, snapshot.parallelism);
+            }
+
+            Checkpoint::Delta(delta) => {
+                out.extend_from_slice(&delta.base.to_le_bytes());
+                out.extend_from_slice(&delta.tick.to_le_bytes());
+                out.extend_from_slice(&delta.rng_position.to_le_bytes());
+                out.extend_from_slice(&(delta.cells.len() as u64).to_le_bytes());
+                for (index, cell) in &delta.cells {
+                    out.extend_from_slice(&(*index as u64).to_le_bytes());
+                    put_cell(out, cell.as_ref());
+                }
+                put_values(out, &delta.water);
+                put_values(out, &delta.nutrients);
+                put_parallelism(out, delta.parallelism);
+            }
+        }
+    }
+
+    /// Reads a checkpoint from its uncompressed bytes
+    fn decode(kind: u8, bytes: &[u8]) -> Result<Self, CheckpointError> {
+        let mut reader = Reader::new(bytes);
+
+        let checkpoint = match kind {
+            KIND_FULL => {
+                let tick = reader.u64()?;
+                let (width, height) = (reader.len()?, reader.len()?);
+                let rng_position = reader.u128()?;
+
+                // Every cell takes at least a byte for the plant and 4 for both the water and the nutrients,
+                // so a corrupt size is caught before anything is allocated for it
+                if width.checked_mul(height).and_then(|cells| cells.checked_mul(9)).is_none_or(|len| len > reader.remaining()) {
+                    return Err(CheckpointError::Corrupt);
+                }
+                let size = Size::new(width, height);
+
+                let cells = (0..size.len()).map(|_| reader.cell()).collect::<Result<_, _>>()?;
+                let water = (0..size.len()).map(|_| reader.f32()).collect::<Result<_, _>>()?;
+                let nutrients = (0..size.len()).map(|_| reader.f32()).collect::<Result<_, _>>()?;
+                let parallelism = reader.parallelism()?;
+
+                Checkpoint::Full(StateSnapshot { tick, size, cells, water, nutrients, rng_position, parallelism })
+            }
+
+            KIND_DELTA => {
+                let base = reader.u64()?;
+                let tick = reader.u64()?;
+                let rng_position = reader.u128()?;
+                let cells = (0..reader.len()?).map(|_| Ok((reader.len()?, reader.cell()?))).collect::<Result<_, _>>()?;
+                let water = reader.values()?;
+                let nutrients = reader.values()?;
+                let parallelism = reader.parallelism()?;
+
+                Checkpoint::Delta(SnapshotDelta { base, tick, rng_position, cells, water, nutrients, parallelism })
+            }
+
+            _ => return Err(CheckpointError::Corrupt),
+        };
+
+        if !reader.is_done() {
+            return Err(CheckpointError::Corrupt);
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+/// The settings for saving a checkpoint chain
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CheckpointConfig {
+    /// The largest number of delta checkpoints after a full checkpoint before the next full checkpoint,
+    /// every checkpoint is full if this is 0
+    pub full_interval: usize,
+    /// How the checkpoints are compressed when saved, a run-length encoding by default
+    pub compression: Compression,
+}
+
+impl Default for CheckpointConfig {
+    fn default() -> Self {
+        Self {
+            full_interval: 10,
+            compression: Compression::default(),
+        }
+    }
+}
+
+/// A series of saved states of a simulation. Most checkpoints only hold the cells which changed since the
+/// latest full checkpoint, so a long run can be saved often without copying the entire board every time.
+/// Every delta is against a full checkpoint and never against another delta, so any state is rebuilt from
+/// at most two checkpoints
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckpointChain {
+    /// The settings of the chain
+    config: CheckpointConfig,
+    /// The checkpoints from the oldest to the newest, the first is always full
+    checkpoints: Vec<Checkpoint>,
+    /// The position of the latest full checkpoint
+    last_full: usize,
+}
+
+impl CheckpointChain {
+    /// Creates a new empty chain
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the chain
+    pub fn new(config: CheckpointConfig) -> Self {
+        Self { config, checkpoints: Vec::new(), last_full: 0 }
+    }
+
+    /// Returns the settings of the chain
+    pub fn config(&self) -> CheckpointConfig {
+        self.config
+    }
+
+    /// Returns the checkpoints from the oldest to the newest
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Returns the number of checkpoints
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    /// Returns true if there are no checkpoints
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    /// Adds a checkpoint of a state. It holds only the changes since the latest full checkpoint unless the chain is empty,
+    /// the full interval has been reached or the board has changed size
+    /// 
+    /// # Parameters
+    /// 
+    /// snapshot: The state to save
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::checkpoint::{CheckpointChain, CheckpointConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut chain = CheckpointChain::new(CheckpointConfig { full_interval: 2, ..Default::default() });
+    /// for _ in 0..4 {
+    ///     chain.push(&simulation.snapshot());
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(vec![true, false, false, true], chain.checkpoints().iter().map(|checkpoint| checkpoint.is_full()).collect::<Vec<_>>());
+    /// ```
+    pub fn push(&mut self, snapshot: &StateSnapshot) {
+        let deltas = self.checkpoints.len().saturating_sub(self.last_full + 1);
+        let delta = match self.checkpoints.get(self.last_full) {
+            Some(Checkpoint::Full(base)) if deltas < self.config.full_interval => snapshot.delta(base).ok(),
+            _ => None,
+        };
+
+        match delta {
+            Some(delta) => self.checkpoints.push(Checkpoint::Delta(delta)),
+            None => {
+                self.last_full = self.checkpoints.len();
+                self.checkpoints.push(Checkpoint::Full(snapshot.clone()));
+            }
+        }
+    }
+
+    /// Rebuilds the state saved at a tick, None if no checkpoint was saved at that tick
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the state
+    pub fn state(&self, tick: u64) -> Option<StateSnapshot> {
+        let position = self.checkpoints.iter().rposition(|checkpoint| checkpoint.tick() == tick)?;
+
+        self.rebuild(position).ok()
+    }
+
+    /// Rebuilds the newest state, None if the chain is empty
+    pub fn latest(&self) -> Option<StateSnapshot> {
+        self.rebuild(self.checkpoints.len().checked_sub(1)?).ok()
+    }
+
+    /// Copies the genomes of the plants of the newest state which pass a filter, in the order of their cells. The chain can be read
+    /// from bytes without building a simulation, so plants evolved in one run can be introduced into another
+    /// 
+    /// # Parameters
+    /// 
+    /// filter: Returns true for the plants whose genomes are copied
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::checkpoint::{CheckpointChain, CheckpointConfig};
+    /// use evolution_plants::simulation::{Placement, Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(80, Genome::new(&[0.4, 0.6]).unwrap()));
+    /// population.insert(Coord::new(2, 2), Plant::new(10, Genome::new(&[0.9, 0.1]).unwrap()));
+    /// let donor = Simulation::new(board.clone(), population, SimulationConfig::default()).unwrap();
+    /// let mut chain = CheckpointChain::new(CheckpointConfig::default());
+    /// chain.push(&donor.snapshot());
+    /// let bytes = chain.to_bytes();
+    /// 
+    /// let invaders = CheckpointChain::from_bytes(&bytes, CheckpointConfig::default()).unwrap().extract_genomes(|plant| plant.energy > 50);
+    /// let mut host = Simulation::new(board, Population::new(donor.board().fields.size), SimulationConfig::default()).unwrap();
+    /// host.introduce(&invaders, Placement::Random, 0);
+    /// host.step();
+    /// 
+    /// assert_eq!(vec![Genome::new(&[0.4, 0.6]).unwrap()], invaders);
+    /// assert_eq!(1, host.population().count());
+    /// ```
+    pub fn extract_genomes<F: FnMut(&Plant) -> bool>(&self, filter: F) -> Vec<Genome> {
+        self.latest().map_or_else(Vec::new, |state| Checkpoint::Full(state).extract_genomes(filter))
+    }
+
+    /// Rebuilds the newest state and compacts the chain into a single full checkpoint of it,
+    /// such that the next checkpoints are deltas against the loaded state. None if the chain is empty
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::checkpoint::{CheckpointChain, CheckpointConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut chain = CheckpointChain::new(CheckpointConfig::default());
+    /// for _ in 0..3 {
+    ///     simulation.step();
+    ///     chain.push(&simulation.snapshot());
+    /// }
+    /// 
+    /// assert_eq!(Some(simulation.snapshot()), chain.load());
+    /// assert_eq!(1, chain.len());
+    /// ```
+    pub fn load(&mut self) -> Option<StateSnapshot> {
+        let state = self.latest()?;
+        self.checkpoints = vec![Checkpoint::Full(state.clone())];
+        self.last_full = 0;
+
+        Some(state)
+    }
+
+    /// Saves the chain as bytes, every checkpoint is run-length encoded on its own unless the compression is None
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+
+        for checkpoint in &self.checkpoints {
+            let mut raw = Vec::new();
+            checkpoint.encode(&mut raw);
+            put_entry(&mut out, if checkpoint.is_full() { KIND_FULL } else { KIND_DELTA }, self.config.compression, &raw);
+        }
+
+        out
+    }
+
+    /// Reads a chain saved with to_bytes, chains saved with an older version of the format are upgraded first
+    /// 
+    /// # Parameters
+    /// 
+    /// bytes: The saved chain
+    /// config: The settings of the chain for the following checkpoints
+    /// 
+    /// # Errors
+    /// 
+    /// CheckpointError::Format: This will occur if the bytes are not a checkpoint chain
+    /// 
+    /// CheckpointError::Version: This will occur if the chain was saved with a newer version of the format or one too old to upgrade
+    /// 
+    /// CheckpointError::Corrupt: This will occur if the bytes end early, hold invalid values or the first checkpoint is not full
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::{checkpoint::{CheckpointChain, CheckpointConfig}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(64, 64).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(3, 3), Plant::new(50, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let mut chain = CheckpointChain::new(CheckpointConfig::default());
+    /// for _ in 0..5 {
+    ///     chain.push(&simulation.snapshot());
+    ///     simulation.step();
+    /// }
+    /// let bytes = chain.to_bytes();
+    /// 
+    /// // The mostly empty board compresses well
+    /// assert!(bytes.len() < 64 * 64);
+    /// assert_eq!(chain, CheckpointChain::from_bytes(&bytes, CheckpointConfig::default()).unwrap());
+    /// ```
+    pub fn from_bytes(bytes: &[u8], config: CheckpointConfig) -> Result<Self, CheckpointError> {
+        let upgraded;
+        let bytes = match migrate::version(bytes)? {
+            VERSION => bytes,
+            _ => {
+                upgraded = migrate::upgrade(bytes)?;
+                &upgraded
+            }
+        };
+
+        let mut chain = Self::new(config);
+        for (kind, _, raw) in entries(&bytes[MAGIC.len() + 1..])? {
+            let checkpoint = Checkpoint::decode(kind, &raw)?;
+
+            match &checkpoint {
+                Checkpoint::Full(_) => chain.last_full = chain.checkpoints.len(),
+                Checkpoint::Delta(delta) => {
+                    if chain.checkpoints.get(chain.last_full).is_none_or(|base| base.tick() != delta.base) {
+                        return Err(CheckpointError::Corrupt);
+                    }
+                }
+            }
+            chain.checkpoints.push(checkpoint);
+        }
+
+        Ok(chain)
+    }
+
+    /// Rebuilds the state of the checkpoint at a position from it and the full checkpoint before it
+    fn rebuild(&self, position: usize) -> Result<StateSnapshot, CheckpointError> {
+        match &self.checkpoints[position] {
+            Checkpoint::Full(snapshot) => Ok(snapshot.clone()),
+            Checkpoint::Delta(delta) => {
+                let base = self.checkpoints[..position].iter().rev().find_map(|checkpoint| match checkpoint {
+                    Checkpoint::Full(snapshot) => Some(snapshot),
+                    Checkpoint::Delta(_) => None,
+                }).ok_or(CheckpointError::Corrupt)?;
+
+                delta.apply(base)
+            }
+        }
+    }
+}
+
+/// Writes the number of threads and the chunk size of a parallel setting
+pub(crate) fn put_parallelism(out: &mut Vec<u8>, parallelism: Parallelism) {
+    out.extend_from_slice(&(parallelism.threads as u64).to_le_bytes());
+    out.extend_from_slice(&(parallelism.chunk as u64).to_le_bytes());
+}
+
+/// Writes the plant in a cell, a single 0 for an empty cell
+pub(crate) fn put_cell(out: &mut Vec<u8>, cell: Option<&Plant>) {
+    let plant = match cell {
+        Some(plant) => plant,
+        None => {
+            out.push(0);
+            return;
+        }
+    };
+
+    out.push(1);
+    out.extend_from_slice(&plant.id().0.to_le_bytes());
+    put_option(out, plant.parent().map(|id| id.0));
+    put_option(out, plant.mate().map(|id| id.0));
+    out.extend_from_slice(&plant.energy.to_le_bytes());
+    out.extend_from_slice(&plant.age.to_le_bytes());
+    out.extend_from_slice(&plant.growth.to_le_bytes());
+    out.extend_from_slice(&plant.infection.to_le_bytes());
+    out.extend_from_slice(&plant.gathered.to_le_bytes());
+
+    let phenotype = &plant.phenotype;
+    out.extend_from_slice(&phenotype.photosynthesis.to_le_bytes());
+    out.extend_from_slice(&phenotype.seed_size.to_le_bytes());
+    out.extend_from_slice(&phenotype.reproduction_threshold.to_le_bytes());
+    out.extend_from_slice(&(phenotype.dispersal as u64).to_le_bytes());
+    match phenotype.lifespan {
+        Some(lifespan) => {
+            out.push(1);
+            out.extend_from_slice(&lifespan.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+
+    let genes = plant.genome.genes();
+    out.extend_from_slice(&(genes.len() as u64).to_le_bytes());
+    for gene in genes {
+        out.extend_from_slice(&gene.to_le_bytes());
+    }
+}
+
+/// Finds the index and the new value of every cell whose value changed, values are compared bit by bit
+fn changed_values(values: &[f32], old: &[f32]) -> Vec<(usize, f32)> {
+    values.iter().zip(old.iter()).enumerate()
+        .filter(|(_, (value, old))| value.to_bits() != old.to_bits())
+        .map(|(index, (&value, _))| (index, value))
+        .collect()
+}
+
+/// Writes the number of changed values followed by the index and the new value of every change
+fn put_values(out: &mut Vec<u8>, values: &[(usize, f32)]) {
+    out.extend_from_slice(&(values.len() as u64).to_le_bytes());
+    for (index, value) in values {
+        out.extend_from_slice(&(*index as u64).to_le_bytes());
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// Writes a single checkpoint of a chain, its kind, its compression and the length of its compressed bytes come first
+pub(crate) fn put_entry(out: &mut Vec<u8>, kind: u8, compression: Compression, raw: &[u8]) {
+    let compressed;
+    let data = match compression {
+        Compression::None => raw,
+        Compression::RunLength => {
+            compressed = compress(raw);
+            &compressed
+        }
+    };
+
+    out.push(kind);
+    out.push(compression.tag());
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Reads the kind, the compression and the uncompressed bytes of every checkpoint of a chain after the header
+pub(crate) fn entries(bytes: &[u8]) -> Result<Vec<(u8, Compression, Vec<u8>)>, CheckpointError> {
+    let mut reader = Reader::new(bytes);
+    let mut entries = Vec::new();
+
+    while !reader.is_done() {
+        let kind = reader.u8()?;
+        let compression = Compression::from_tag(reader.u8()?)?;
+        let len = reader.len()?;
+        let data = reader.take(len)?;
+        let raw = match compression {
+            Compression::None => data.to_vec(),
+            Compression::RunLength => decompress(data)?,
+        };
+
+        entries.push((kind, compression, raw));
+    }
+
+    Ok(entries)
+}
+
+/// Writes an optional id, a single 0 for None
+fn put_option(out: &mut Vec<u8>, value: Option<u64>) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+}
+
+/// Compresses bytes with a run-length encoding by saving runs of at least 3 repeated bytes once. Every block starts with a header byte,
+/// a header below 128 is followed by that many plus 1 bytes copied as they are,
+/// a header of 128 or more is followed by a single byte repeated the header minus 125 times
+fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(bytes.len() / 2);
+    let mut literal_start = 0;
+    let mut index = 0;
+
+    let flush = |out: &mut Vec<u8>, literal: &[u8]| {
+        for chunk in literal.chunks(128) {
+            out.push((chunk.len() - 1) as u8);
+            out.extend_from_slice(chunk);
+        }
+    };
+
+    while index < bytes.len() {
+        let run = bytes[index..].iter().take(130).take_while(|&&byte| byte == bytes[index]).count();
+        if run >= 3 {
+            flush(&mut out, &bytes[literal_start..index]);
+            out.push((run + 125) as u8);
+            out.push(bytes[index]);
+            index += run;
+            literal_start = index;
+        } else {
+            index += 1;
+        }
+    }
+    flush(&mut out, &bytes[literal_start..]);
+
+    out
+}
+
+/// Reverses compress
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, CheckpointError> {
+    let mut out = Vec::with_capacity(bytes.len() * 2);
+    let mut reader = Reader::new(bytes);
+
+    while !reader.is_done() {
+        let header = reader.u8()? as usize;
+        if header < 128 {
+            out.extend_from_slice(reader.take(header + 1)?);
+        } else {
+            let byte = reader.u8()?;
+            out.resize(out.len() + header - 125, byte);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Reads the values of a checkpoint one after another
+pub(crate) struct Reader<'a> {
+    /// The bytes which have not been read yet
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a new reader at the start of the bytes
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    /// Returns true if all bytes have been read
+    pub(crate) fn is_done(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns the number of bytes which have not been read yet
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Reads a number of bytes
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], CheckpointError> {
+        if len > self.bytes.len() {
+            return Err(CheckpointError::Corrupt);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    /// Reads a fixed number of bytes
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CheckpointError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, CheckpointError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, CheckpointError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, CheckpointError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn u128(&mut self) -> Result<u128, CheckpointError> {
+        Ok(u128::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn f32(&mut self) -> Result<f32, CheckpointError> {
+        Ok(f32::from_le_bytes(self.array()?))
+    }
+
+    /// Reads a length or an index saved as 64 bits
+    pub(crate) fn len(&mut self) -> Result<usize, CheckpointError> {
+        usize::try_from(self.u64()?).map_err(|_| CheckpointError::Corrupt)
+    }
+
+    /// Reads the changed values written by put_values
+    fn values(&mut self) -> Result<Vec<(usize, f32)>, CheckpointError> {
+        (0..self.len()?).map(|_| Ok((self.len()?, self.f32()?))).collect()
+    }
+
+    /// Reads the setting written by put_parallelism
+    fn parallelism(&mut self) -> Result<Parallelism, CheckpointError> {
+        Ok(Parallelism { threads: self.len()?, chunk: self.len()? })
+    }
+
+    /// Reads an optional id
+    fn option(&mut self) -> Result<Option<u64>, CheckpointError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u64()?)),
+            _ => Err(CheckpointError::Corrupt),
+        }
+    }
+
+    /// Reads the plant in a cell
+    pub(crate) fn cell(&mut self) -> Result<Option<Plant>, CheckpointError> {
+        match self.u8()? {
+            0 => return Ok(None),
+            1 => (),
+            _ => return Err(CheckpointError::Corrupt),
+        }
+
+        let id = PlantId(self.u64()?);
+        let parent = self.option()?.map(PlantId);
+        let mate = self.option()?.map(PlantId);
+        let energy = self.u32()?;
+        let age = self.u64()?;
+        let growth = self.f32()?;
+        let infection = self.u32()?;
+        let gathered = self.u64()?;
+
+        let photosynthesis = self.f32()?;
+        let seed_size = self.f32()?;
+        let reproduction_threshold = self.f32()?;
+        let dispersal = self.len()?;
+        let lifespan = match self.u8()? {
+            0 => None,
+            1 => Some(self.f32()?),
+            _ => return Err(CheckpointError::Corrupt),
+        };
+
+        let len = self.len()?;
+        let genes = (0..len).map(|_| self.f32()).collect::<Result<Vec<_>, _>>()?;
+        let genome = Genome::new(&genes).map_err(|_| CheckpointError::Corrupt)?;
+
+        let mut plant = Plant::new(energy, genome).with_lineage(id, parent, mate);
+        plant.age = age;
+        plant.growth = growth;
+        plant.infection = infection;
+        plant.gathered = gathered;
+        plant.phenotype = Phenotype { photosynthesis, seed_size, reproduction_threshold, dispersal, lifespan };
+
+        Ok(Some(plant))
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum CheckpointError {
+    #[error("The snapshot has size {:?} but the base has size {:?}", found, expected)]
+    Size {
+        expected: Size,
+        found: Size,
+    },
+    #[error("The delta is against tick {:?} but the base is from tick {:?}", expected, found)]
+    Base {
+        expected: u64,
+        found: u64,
+    },
+    #[error("The bytes are not a checkpoint chain")]
+    Format,
+    #[error("The checkpoint chain has version {:?} but only versions {:?} to {:?} can be read", found, migrate::OLDEST_VERSION, VERSION)]
+    Version {
+        found: u8,
+    },
+    #[error("The checkpoint chain ends early or holds invalid values")]
+    Corrupt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    fn snapshot(tick: u64, plants: &[(usize, u32)]) -> StateSnapshot {
+        let size = Size::new(3, 2);
+        let mut cells = vec![None; size.len()];
+        for &(index, energy) in plants {
+            let mut plant = Plant::new(energy, Genome::new(&[0.25, 0.75, 0.5]).unwrap()).with_lineage(PlantId(index as u64), Some(PlantId(9)), None);
+            plant.age = tick;
+            plant.phenotype.lifespan = Some(0.5);
+            cells[index] = Some(plant);
+        }
+
+        StateSnapshot { tick, size, cells, water: vec![0.5; size.len()], nutrients: vec![0.0; size.len()], rng_position: tick as u128 * 7, parallelism: Parallelism::default() }
+    }
+
+    #[test]
+    fn compress_round_trip() {
+        let mut bytes: Vec<u8> = (0..300).map(|index| (index % 7) as u8).collect();
+        bytes.extend(std::iter::repeat_n(0, 1000));
+        bytes.extend([1, 1, 2, 2, 2, 3]);
+
+        let compressed = compress(&bytes);
+
+        assert!(compressed.len() < bytes.len());
+        assert_eq!(bytes, decompress(&compressed).unwrap());
+        assert_eq!(Vec::<u8>::new(), decompress(&compress(&[])).unwrap());
+    }
+
+    #[test]
+    fn delta_changes_only() {
+        let base = snapshot(0, &[(0, 10), (4, 20)]);
+        let mut next = snapshot(1, &[(0, 10), (5, 20)]);
+        next.water[2] = 1.0;
+        let delta = next.delta(&base).unwrap();
+
+        assert_eq!(vec![0, 4, 5], delta.cells.iter().map(|(index, _)| *index).collect::<Vec<_>>());
+        assert_eq!(vec![(2, 1.0)], delta.water);
+        assert!(delta.nutrients.is_empty());
+        assert_eq!(next, delta.apply(&base).unwrap());
+        assert_eq!(Err(CheckpointError::Base { expected: 0, found: 1 }), delta.apply(&next));
+    }
+
+    #[test]
+    fn delta_size() {
+        let base = snapshot(0, &[]);
+        let other = StateSnapshot { size: Size::new(2, 3), ..base.clone() };
+
+        assert_eq!(Err(CheckpointError::Size { expected: Size::new(3, 2), found: Size::new(2, 3) }), other.delta(&base).map(|_| ()));
+    }
+
+    #[test]
+    fn chain_round_trip() {
+        for compression in [Compression::None, Compression::RunLength] {
+            let mut chain = CheckpointChain::new(CheckpointConfig { full_interval: 1, compression });
+            for tick in 0..3 {
+                chain.push(&snapshot(tick, &[(tick as usize, 5 * tick as u32)]));
+            }
+
+            let loaded = CheckpointChain::from_bytes(&chain.to_bytes(), chain.config()).unwrap();
+
+            assert_eq!(chain, loaded);
+            assert_eq!(Some(snapshot(1, &[(1, 5)])), loaded.state(1));
+            assert_eq!(Some(snapshot(2, &[(2, 10)])), loaded.latest());
+            assert_eq!(None, loaded.state(7));
+        }
+    }
+
+    #[test]
+    fn checkpoint_extract_genomes() {
+        let base = snapshot(0, &[(0, 10), (4, 20)]);
+        let next = snapshot(1, &[(0, 10), (5, 30)]);
+        let mut chain = CheckpointChain::new(CheckpointConfig::default());
+        chain.push(&base);
+        chain.push(&next);
+        let genome = Genome::new(&[0.25, 0.75, 0.5]).unwrap();
+
+        assert_eq!(vec![genome.clone(); 2], chain.checkpoints()[0].extract_genomes(|_| true));
+        // The delta holds the plant which aged and the plant which was born but not the cell which was emptied
+        assert_eq!(vec![genome.clone(); 2], chain.checkpoints()[1].extract_genomes(|_| true));
+        assert!(!chain.checkpoints()[1].is_full());
+        assert_eq!(vec![genome.clone()], chain.extract_genomes(|plant| plant.energy > 15));
+        assert!(CheckpointChain::new(CheckpointConfig::default()).extract_genomes(|_| true).is_empty());
+    }
+
+    #[test]
+    fn chain_push_after_load() {
+        let mut chain = CheckpointChain::new(CheckpointConfig::default());
+        chain.push(&snapshot(0, &[]));
+        chain.push(&snapshot(1, &[(3, 1)]));
+        chain.load();
+        chain.push(&snapshot(2, &[(3, 1)]));
+
+        assert!(chain.checkpoints()[0].is_full());
+        match &chain.checkpoints()[1] {
+            Checkpoint::Delta(delta) => assert_eq!(1, delta.base),
+            Checkpoint::Full(_) => panic!("Expected a delta"),
+        }
+        assert_eq!(Some(snapshot(2, &[(3, 1)])), chain.latest());
+    }
+
+    #[test]
+    fn chain_from_bytes_errors() {
+        let mut chain = CheckpointChain::new(CheckpointConfig::default());
+        chain.push(&snapshot(0, &[(1, 3)]));
+        let mut bytes = chain.to_bytes();
+
+        assert_eq!(Err(CheckpointError::Format), CheckpointChain::from_bytes(b"EP", chain.config()));
+        assert_eq!(Err(CheckpointError::Corrupt), CheckpointChain::from_bytes(&bytes[..bytes.len() - 1], chain.config()));
+
+        bytes[4] = VERSION + 1;
+        assert_eq!(Err(CheckpointError::Version { found: VERSION + 1 }), CheckpointChain::from_bytes(&bytes, chain.config()));
+    }
+
+    #[test]
+    fn chain_from_bytes_huge_size() {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&0u64.to_le_bytes());
+        raw.extend_from_slice(&u64::MAX.to_le_bytes());
+        raw.extend_from_slice(&u64::MAX.to_le_bytes());
+        raw.extend_from_slice(&0u128.to_le_bytes());
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        put_entry(&mut bytes, KIND_FULL, Compression::None, &raw);
+
+        assert_eq!(Err(CheckpointError::Corrupt), CheckpointChain::from_bytes(&bytes, CheckpointConfig::default()));
+    }
+
+    #[test]
+    fn chain_from_bytes_fuzz() {
+        // Randomly damaged chains never panic, they either load or give an error
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        for compression in [Compression::None, Compression::RunLength] {
+            let mut chain = CheckpointChain::new(CheckpointConfig { full_interval: 2, compression });
+            for tick in 0..4 {
+                chain.push(&snapshot(tick, &[(tick as usize, 3), (5, 9)]));
+            }
+            let valid = chain.to_bytes();
+
+            for _ in 0..3000 {
+                let mut bytes = valid.clone();
+                for _ in 0..rng.gen_range(1..4) {
+                    match rng.gen_range(0..3) {
+                        0 => {
+                            let index = rng.gen_range(0..bytes.len());
+                            bytes[index] = rng.gen();
+                        }
+                        1 => bytes.truncate(rng.gen_range(0..bytes.len())),
+                        _ => {
+                            let index = rng.gen_range(0..=bytes.len());
+                            bytes.insert(index, rng.gen());
+                        }
+                    }
+                    if bytes.is_empty() {
+                        break;
+                    }
+                }
+
+                if let Ok(chain) = CheckpointChain::from_bytes(&bytes, CheckpointConfig::default()) {
+                    let _ = chain.latest();
+                }
+            }
+        }
+    }
+}
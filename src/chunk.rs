@@ -0,0 +1,432 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::board::{Coord, Rect, Size};
+use crate::field::Field;
+use crate::memory::{vec_bytes, HeapSize};
+
+/// The storage of a single chunk
+#[derive(Clone, Debug, PartialEq)]
+enum Chunk<T> {
+    /// Every cell has the fill value and nothing is stored
+    Empty,
+    /// The values are in memory with the rows of the chunk in order
+    Resident(Vec<T>),
+    /// The values have been written to a file
+    Paged(PathBuf),
+}
+
+/// A value for every cell of a board stored in square chunks, meant for worlds too large to keep contiguous.
+/// Chunks where every cell has the fill value take no memory, every chunk in memory can be processed on its own,
+/// and chunks of numbers can be paged out to disk while they are inactive. Fields and plants are converted
+/// to chunks with from_field and back with to_field, plants are stored as chunks of Option<Plant> filled with None
+#[derive(Debug, PartialEq)]
+pub struct ChunkedField<T> {
+    /// The size of the board
+    size: Size,
+    /// The width and height of every chunk, the chunks at the right and bottom edges may be smaller
+    chunk_size: usize,
+    /// The chunks with the rows of chunks in order
+    chunks: Vec<Chunk<T>>,
+    /// The value of every cell of an empty chunk
+    fill: T,
+}
+
+impl<T: Clone + PartialEq> ChunkedField<T> {
+    /// Creates a new field where every cell has the fill value, no chunk takes any memory
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// chunk_size: The width and height of every chunk
+    /// fill: The value of every cell
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::ChunkSize: This will occur if the chunk size is 0
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Rect, Size}, chunk::ChunkedField};
+    /// 
+    /// let mut field = ChunkedField::new(Size::new(10, 5), 4, 0.0).unwrap();
+    /// field.set(Coord::new(9, 4), 2.0).unwrap();
+    /// 
+    /// assert_eq!((3, 2), field.chunk_count());
+    /// assert_eq!(Rect::new(8, 4, 2, 1), field.chunk_rect(5));
+    /// assert_eq!(Some(&2.0), field.get(Coord::new(9, 4)));
+    /// assert_eq!(Some(&0.0), field.get(Coord::new(0, 0)));
+    /// assert_eq!(1, field.resident());
+    /// ```
+    pub fn new(size: Size, chunk_size: usize, fill: T) -> Result<Self, ChunkError> {
+        if chunk_size == 0 {
+            return Err(ChunkError::ChunkSize);
+        }
+
+        let (w, h) = size.size();
+        let chunks = (0..w.div_ceil(chunk_size) * h.div_ceil(chunk_size)).map(|_| Chunk::Empty).collect();
+
+        Ok(Self { size, chunk_size, chunks, fill })
+    }
+
+    /// Splits a field into chunks, chunks where every cell has the fill value take no memory
+    /// 
+    /// # Parameters
+    /// 
+    /// field: The field to split
+    /// chunk_size: The width and height of every chunk
+    /// fill: The value of the cells of empty chunks
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::ChunkSize: This will occur if the chunk size is 0
+    pub fn from_field(field: &Field<T>, chunk_size: usize, fill: T) -> Result<Self, ChunkError> {
+        let mut chunked = Self::new(field.size(), chunk_size, fill)?;
+
+        for chunk in 0..chunked.chunks.len() {
+            let rect = chunked.chunk_rect(chunk);
+            let values: Vec<T> = rect.coords().map(|coord| field[field.size().index_of(coord).unwrap()].clone()).collect();
+            if values.iter().any(|value| *value != chunked.fill) {
+                chunked.chunks[chunk] = Chunk::Resident(values);
+            }
+        }
+
+        Ok(chunked)
+    }
+
+    /// Joins the chunks into a contiguous field
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::Paged: This will occur if a chunk is paged out
+    pub fn to_field(&self) -> Result<Field<T>, ChunkError> {
+        let mut field = Field::filled(self.size, self.fill.clone());
+
+        for (chunk, storage) in self.chunks.iter().enumerate() {
+            match storage {
+                Chunk::Empty => (),
+                Chunk::Resident(values) => {
+                    for (coord, value) in self.chunk_rect(chunk).coords().zip(values) {
+                        field[self.size.index_of(coord).unwrap()] = value.clone();
+                    }
+                }
+                Chunk::Paged(_) => return Err(ChunkError::Paged { chunk }),
+            }
+        }
+
+        Ok(field)
+    }
+
+    /// Returns the value of a cell, None if the cell is outside the board or its chunk is paged out
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The cell to read
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        let (chunk, offset) = self.locate(coord)?;
+
+        match &self.chunks[chunk] {
+            Chunk::Empty => Some(&self.fill),
+            Chunk::Resident(values) => Some(&values[offset]),
+            Chunk::Paged(_) => None,
+        }
+    }
+
+    /// Sets the value of a cell, the chunk is given memory if it was empty
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The cell to write
+    /// value: The new value
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::Outside: This will occur if the cell is outside the board
+    /// 
+    /// ChunkError::Paged: This will occur if the chunk of the cell is paged out
+    pub fn set(&mut self, coord: Coord, value: T) -> Result<(), ChunkError> {
+        let (chunk, offset) = self.locate(coord).ok_or(ChunkError::Outside { coord })?;
+        let len = self.chunk_rect(chunk).area();
+
+        match &mut self.chunks[chunk] {
+            Chunk::Empty => {
+                let mut values = vec![self.fill.clone(); len];
+                values[offset] = value;
+                self.chunks[chunk] = Chunk::Resident(values);
+            }
+            Chunk::Resident(values) => values[offset] = value,
+            Chunk::Paged(_) => return Err(ChunkError::Paged { chunk }),
+        }
+
+        Ok(())
+    }
+
+    /// Frees the chunks in memory where every cell has the fill value again
+    pub fn compact(&mut self) {
+        for chunk in self.chunks.iter_mut() {
+            if let Chunk::Resident(values) = chunk {
+                if values.iter().all(|value| *value == self.fill) {
+                    *chunk = Chunk::Empty;
+                }
+            }
+        }
+    }
+}
+
+impl<T> ChunkedField<T> {
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the width and height of every chunk, the chunks at the right and bottom edges may be smaller
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the number of columns and rows of chunks
+    pub fn chunk_count(&self) -> (usize, usize) {
+        let (w, h) = self.size.size();
+
+        (w.div_ceil(self.chunk_size), h.div_ceil(self.chunk_size))
+    }
+
+    /// Returns the cells of a chunk on the board
+    /// 
+    /// # Parameters
+    /// 
+    /// chunk: The index of the chunk with the rows of chunks in order
+    pub fn chunk_rect(&self, chunk: usize) -> Rect {
+        let (columns, _) = self.chunk_count();
+        let (w, h) = self.size.size();
+        let (x, y) = (chunk % columns * self.chunk_size, chunk / columns * self.chunk_size);
+
+        Rect::new(x, y, self.chunk_size.min(w - x), self.chunk_size.min(h - y))
+    }
+
+    /// Returns the number of chunks in memory
+    pub fn resident(&self) -> usize {
+        self.chunks.iter().filter(|chunk| matches!(chunk, Chunk::Resident(_))).count()
+    }
+
+    /// Returns true if a chunk is paged out
+    /// 
+    /// # Parameters
+    /// 
+    /// chunk: The index of the chunk
+    pub fn is_paged(&self, chunk: usize) -> bool {
+        matches!(self.chunks.get(chunk), Some(Chunk::Paged(_)))
+    }
+
+    /// Lets a closure process every chunk in memory on its own, the closure gets the cells of the chunk on the board
+    /// and its values with the rows of the chunk in order. Empty and paged out chunks are skipped
+    /// 
+    /// # Parameters
+    /// 
+    /// process: The closure processing a chunk
+    pub fn for_each_chunk_mut<F: FnMut(Rect, &mut [T])>(&mut self, mut process: F) {
+        let rects: Vec<Rect> = (0..self.chunks.len()).map(|chunk| self.chunk_rect(chunk)).collect();
+
+        for (rect, chunk) in rects.into_iter().zip(self.chunks.iter_mut()) {
+            if let Chunk::Resident(values) = chunk {
+                process(rect, values);
+            }
+        }
+    }
+
+    /// Finds the chunk of a cell and the index of the cell in the chunk
+    fn locate(&self, coord: Coord) -> Option<(usize, usize)> {
+        let (w, h) = self.size.size();
+        if coord.x >= w || coord.y >= h {
+            return None;
+        }
+
+        let (columns, _) = self.chunk_count();
+        let chunk = coord.x / self.chunk_size + coord.y / self.chunk_size * columns;
+        let rect = self.chunk_rect(chunk);
+
+        Some((chunk, coord.x - rect.x + (coord.y - rect.y) * rect.w))
+    }
+}
+
+impl ChunkedField<f32> {
+    /// Writes a chunk in memory to a file in a directory and frees its memory, empty and paged out chunks are left as they are.
+    /// The file is removed when the chunk is paged in again or the field is dropped
+    /// 
+    /// # Parameters
+    /// 
+    /// chunk: The index of the chunk
+    /// directory: The directory to write the file to, it must exist
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::Io: This will occur if the file cannot be written
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, chunk::ChunkedField};
+    /// 
+    /// let mut field = ChunkedField::new(Size::new(4, 4), 2, 0.0).unwrap();
+    /// field.set(Coord::new(3, 0), 1.5).unwrap();
+    /// field.page_out(1, &std::env::temp_dir()).unwrap();
+    /// 
+    /// assert_eq!(None, field.get(Coord::new(3, 0)));
+    /// assert_eq!(0, field.resident());
+    /// 
+    /// field.page_in(1).unwrap();
+    /// assert_eq!(Some(&1.5), field.get(Coord::new(3, 0)));
+    /// ```
+    pub fn page_out(&mut self, chunk: usize, directory: &Path) -> Result<(), ChunkError> {
+        let Some(Chunk::Resident(values)) = self.chunks.get(chunk) else {
+            return Ok(());
+        };
+
+        let path = directory.join(format!("chunk_{}_{:p}_{}.bin", std::process::id(), self, chunk));
+        let bytes: Vec<u8> = values.iter().flat_map(|value| value.to_le_bytes()).collect();
+        fs::write(&path, bytes).map_err(|error| ChunkError::Io { path: path.clone(), message: error.to_string() })?;
+        self.chunks[chunk] = Chunk::Paged(path);
+
+        Ok(())
+    }
+
+    /// Reads a paged out chunk back into memory and removes its file, other chunks are left as they are
+    /// 
+    /// # Parameters
+    /// 
+    /// chunk: The index of the chunk
+    /// 
+    /// # Errors
+    /// 
+    /// ChunkError::Io: This will occur if the file cannot be read
+    pub fn page_in(&mut self, chunk: usize) -> Result<(), ChunkError> {
+        let Some(Chunk::Paged(path)) = self.chunks.get(chunk) else {
+            return Ok(());
+        };
+
+        let io_error = |error: std::io::Error| ChunkError::Io { path: path.clone(), message: error.to_string() };
+        let bytes = fs::read(path).map_err(io_error)?;
+        if bytes.len() != self.chunk_rect(chunk).area() * 4 {
+            return Err(ChunkError::Io { path: path.clone(), message: "The file has the wrong length".to_string() });
+        }
+        let values = bytes.chunks_exact(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect();
+        fs::remove_file(path).map_err(io_error)?;
+        self.chunks[chunk] = Chunk::Resident(values);
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for ChunkedField<T> {
+    fn drop(&mut self) {
+        for chunk in &self.chunks {
+            if let Chunk::Paged(path) = chunk {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+impl<T> HeapSize for ChunkedField<T> {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.chunks) + self.chunks.iter().map(|chunk| match chunk {
+            Chunk::Resident(values) => vec_bytes(values),
+            Chunk::Paged(path) => path.as_os_str().len(),
+            Chunk::Empty => 0,
+        }).sum::<usize>()
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ChunkError {
+    #[error("The chunk size must be at least 1")]
+    ChunkSize,
+    #[error("The cell {coord:?} is outside the board")]
+    Outside {
+        coord: Coord,
+    },
+    #[error("The chunk {chunk} is paged out")]
+    Paged {
+        chunk: usize,
+    },
+    #[error("Unable to page the chunk file {path:?}: {message}")]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::Genome;
+    use crate::population::Plant;
+
+    #[test]
+    fn chunked_field_round_trip() {
+        let mut values = vec![0.0; 35];
+        values[3] = 1.0;
+        values[34] = 2.0;
+        let field = Field::from_vec("Water", Size::new(7, 5), values).unwrap();
+        let mut chunked = ChunkedField::from_field(&field, 3, 0.0).unwrap();
+
+        assert_eq!((3, 2), chunked.chunk_count());
+        assert_eq!(2, chunked.resident());
+        assert_eq!(field, chunked.to_field().unwrap());
+        let mut sparse = Field::filled(Size::new(64, 64), 0.0);
+        sparse[100] = 1.0;
+        assert!(ChunkedField::from_field(&sparse, 8, 0.0).unwrap().heap_bytes() * 4 < sparse.heap_bytes());
+
+        chunked.set(Coord::new(3, 0), 0.0).unwrap();
+        chunked.compact();
+        assert_eq!(1, chunked.resident());
+        assert_eq!(Err(ChunkError::Outside { coord: Coord::new(7, 0) }), chunked.set(Coord::new(7, 0), 1.0));
+        assert_eq!(Err(ChunkError::ChunkSize), ChunkedField::new(Size::new(2, 2), 0, 0.0));
+    }
+
+    #[test]
+    fn chunked_field_process_chunks() {
+        let mut chunked = ChunkedField::new(Size::new(5, 5), 2, 0).unwrap();
+        chunked.set(Coord::new(0, 0), 1).unwrap();
+        chunked.set(Coord::new(4, 4), 1).unwrap();
+
+        let mut rects = Vec::new();
+        chunked.for_each_chunk_mut(|rect, values| {
+            rects.push(rect);
+            values.iter_mut().for_each(|value| *value += 10);
+        });
+
+        assert_eq!(vec![Rect::new(0, 0, 2, 2), Rect::new(4, 4, 1, 1)], rects);
+        assert_eq!(Some(&11), chunked.get(Coord::new(0, 0)));
+        assert_eq!(Some(&10), chunked.get(Coord::new(1, 1)));
+        assert_eq!(Some(&0), chunked.get(Coord::new(2, 0)));
+    }
+
+    #[test]
+    fn chunked_field_paging() {
+        let directory = std::env::temp_dir();
+        let mut chunked = ChunkedField::new(Size::new(4, 3), 2, 0.5).unwrap();
+        chunked.set(Coord::new(1, 2), 3.0).unwrap();
+        chunked.page_out(2, &directory).unwrap();
+
+        assert!(chunked.is_paged(2));
+        assert_eq!(Err(ChunkError::Paged { chunk: 2 }), chunked.set(Coord::new(0, 2), 1.0));
+        assert_eq!(Err(ChunkError::Paged { chunk: 2 }), chunked.to_field());
+
+        chunked.page_in(2).unwrap();
+        assert_eq!(vec![0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 3.0, 0.5, 0.5], chunked.to_field().unwrap());
+    }
+
+    #[test]
+    fn chunked_plants() {
+        let mut plants = ChunkedField::new(Size::new(8, 8), 4, None).unwrap();
+        plants.set(Coord::new(5, 6), Some(Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()))).unwrap();
+
+        assert_eq!(1, plants.resident());
+        assert_eq!(Some(10), plants.get(Coord::new(5, 6)).unwrap().as_ref().map(|plant| plant.energy));
+        assert!(plants.get(Coord::new(1, 1)).unwrap().is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::board::Size;
+use crate::genome::{self, Genome};
+
+/// The settings for how temperature affects the growth of plants
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThermalConfig {
+    /// The optimum temperature of a plant with a thermal optimum gene of 0
+    pub min_optimum: f32,
+    /// The optimum temperature of a plant with a thermal optimum gene of 1
+    pub max_optimum: f32,
+    /// The distance from the optimum temperature a plant tolerates without a penalty when its thermal tolerance gene is 1
+    pub max_tolerance: f32,
+    /// The fraction of the light energy lost for every degree the temperature is outside the tolerated range
+    pub penalty: f32,
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            min_optimum: -10.0,
+            max_optimum: 40.0,
+            max_tolerance: 10.0,
+            penalty: 0.1,
+        }
+    }
+}
+
+impl ThermalConfig {
+    /// Finds the range of temperatures a genome tolerates without a penalty,
+    /// returns None if the genome does not have both thermal genes and therefore tolerates any temperature
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{climate::ThermalConfig, genome::Genome};
+    /// 
+    /// let config = ThermalConfig { min_optimum: 0.0, max_optimum: 20.0, max_tolerance: 4.0, penalty: 0.1 };
+    /// 
+    /// assert_eq!(Some((8.0, 12.0)), config.tolerated_range(&Genome::new(&[0.0, 0.0, 0.5, 0.5]).unwrap()));
+    /// assert_eq!(None, config.tolerated_range(&Genome::new(&[0.0, 0.0]).unwrap()));
+    /// ```
+    pub fn tolerated_range(&self, genome: &Genome) -> Option<(f32, f32)> {
+        let optimum = genome.get(genome::GENE_THERMAL_OPTIMUM)?;
+        let tolerance = genome.get(genome::GENE_THERMAL_TOLERANCE)?;
+
+        let optimum = self.min_optimum + (self.max_optimum - self.min_optimum) * optimum;
+        let tolerance = self.max_tolerance * tolerance;
+
+        Some((optimum - tolerance, optimum + tolerance))
+    }
+
+    /// Finds the fraction of the light energy a plant gains at a temperature, this is 1 inside the tolerated range
+    /// and falls linearly to 0 outside it
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// temperature: The temperature in the cell of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{climate::ThermalConfig, genome::Genome};
+    /// 
+    /// let config = ThermalConfig { min_optimum: 0.0, max_optimum: 20.0, max_tolerance: 4.0, penalty: 0.1 };
+    /// let genome = Genome::new(&[0.0, 0.0, 0.5, 0.5]).unwrap();
+    /// 
+    /// assert_eq!(1.0, config.growth_factor(&genome, 11.0));
+    /// assert_eq!(0.5, config.growth_factor(&genome, 17.0));
+    /// assert_eq!(0.0, config.growth_factor(&genome, -10.0));
+    /// ```
+    pub fn growth_factor(&self, genome: &Genome, temperature: f32) -> f32 {
+        let (low, high) = match self.tolerated_range(genome) {
+            Some(range) => range,
+            None => return 1.0,
+        };
+
+        let excess = (low - temperature).max(temperature - high).max(0.0);
+
+        (1.0 - excess * self.penalty).clamp(0.0, 1.0)
+    }
+}
+
+/// Creates a temperature field which changes linearly with latitude from the top row to the bottom row
+/// 
+/// # Parameters
+/// 
+/// size: The size of the board
+/// top: The temperature of the top row
+/// bottom: The temperature of the bottom row
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::Size, climate};
+/// 
+/// let temperature = climate::latitudinal_gradient(Size::new(2, 3), 0.0, 10.0);
+/// 
+/// assert_eq!(vec![0.0, 0.0, 5.0, 5.0, 10.0, 10.0], temperature);
+/// ```
+pub fn latitudinal_gradient(size: Size, top: f32, bottom: f32) -> Vec<f32> {
+    let (w, h) = size.size();
+
+    (0..h)
+        .flat_map(|y| {
+            let latitude = if h > 1 { y as f32 / (h - 1) as f32 } else { 0.0 };
+
+            std::iter::repeat_n(top + (bottom - top) * latitude, w)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ThermalConfig {
+        ThermalConfig { min_optimum: 0.0, max_optimum: 20.0, max_tolerance: 4.0, penalty: 0.25 }
+    }
+
+    #[test]
+    fn thermal_config_tolerated_range() {
+        assert_eq!(Some((-2.0, 2.0)), config().tolerated_range(&Genome::new(&[0.0, 0.0, 0.0, 0.5]).unwrap()));
+        assert_eq!(Some((20.0, 20.0)), config().tolerated_range(&Genome::new(&[0.0, 0.0, 1.0, 0.0]).unwrap()));
+        assert_eq!(None, config().tolerated_range(&Genome::new(&[0.0, 0.0, 1.0]).unwrap()));
+    }
+
+    #[test]
+    fn thermal_config_growth_factor() {
+        let genome = Genome::new(&[0.0, 0.0, 0.5, 0.5]).unwrap();
+
+        assert_eq!(1.0, config().growth_factor(&genome, 8.0));
+        assert_eq!(1.0, config().growth_factor(&genome, 12.0));
+        assert_eq!(0.5, config().growth_factor(&genome, 6.0));
+        assert_eq!(0.75, config().growth_factor(&genome, 13.0));
+        assert_eq!(0.0, config().growth_factor(&genome, 30.0));
+    }
+
+    #[test]
+    fn thermal_config_growth_factor_without_genes() {
+        assert_eq!(1.0, config().growth_factor(&Genome::new(&[0.0, 0.0]).unwrap(), 1000.0));
+    }
+
+    #[test]
+    fn latitudinal_gradient_rows() {
+        let temperature = latitudinal_gradient(Size::new(3, 2), 10.0, -10.0);
+
+        assert_eq!(vec![10.0, 10.0, 10.0, -10.0, -10.0, -10.0], temperature);
+    }
+
+    #[test]
+    fn latitudinal_gradient_single_row() {
+        assert_eq!(vec![5.0, 5.0], latitudinal_gradient(Size::new(2, 1), 5.0, 0.0));
+        assert!(latitudinal_gradient(Size::new(0, 3), 5.0, 0.0).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::genome::{self, Genome};
+use crate::population::Plant;
+
+/// The settings for trading the size of seeds against their number. A plant splits the energy it gives to its seeds
+/// evenly between a clutch of seeds whose size is set by its clutch gene, so it can produce many small seeds or a few
+/// large ones. Every seed must then establish itself in the cell it lands in, which is harder the more seeds land in
+/// the same cell and easier the more energy the seed has, so small seeds spread far but lose when crowded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClutchConfig {
+    /// The number of seeds a plant with a clutch gene of 1 produces at once, at least 1
+    pub max_seeds: usize,
+    /// The energy a seed alone in a cell needs to establish with a chance of one half,
+    /// this is multiplied by the number of seeds landing in the cell
+    pub establishment: f32,
+}
+
+impl Default for ClutchConfig {
+    fn default() -> Self {
+        Self {
+            max_seeds: 4,
+            establishment: 10.0,
+        }
+    }
+}
+
+impl ClutchConfig {
+    /// Finds the number of seeds a plant produces at once, plants without a clutch gene produce a single seed
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{clutch::ClutchConfig, genome::Genome};
+    /// 
+    /// let config = ClutchConfig { max_seeds: 5, ..Default::default() };
+    /// 
+    /// assert_eq!(1, config.seeds(&Genome::new(&[0.5, 0.5]).unwrap()));
+    /// assert_eq!(3, config.seeds(&Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+    /// assert_eq!(5, config.seeds(&Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+    /// ```
+    pub fn seeds(&self, genome: &Genome) -> usize {
+        let extra = self.max_seeds.max(1) - 1;
+
+        1 + (genome.get(genome::GENE_CLUTCH).unwrap_or(0.0) * extra as f32).round() as usize
+    }
+
+    /// Splits the energy of a clutch evenly between its seeds, the first seed gets what cannot be split evenly
+    /// 
+    /// # Parameters
+    /// 
+    /// energy: The energy given to the whole clutch
+    /// seeds: The number of seeds in the clutch, 0 is treated as 1
+    pub fn split(energy: u32, seeds: usize) -> Vec<u32> {
+        let seeds = seeds.max(1) as u32;
+        let share = energy / seeds;
+
+        let mut shares = vec![share; seeds as usize];
+        shares[0] += energy - share * seeds;
+        shares
+    }
+
+    /// Finds the chance of a seed establishing itself in a cell
+    /// 
+    /// # Parameters
+    /// 
+    /// energy: The energy of the seed
+    /// crowding: The number of seeds landing in the same cell, including this one
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::clutch::ClutchConfig;
+    /// 
+    /// let config = ClutchConfig { establishment: 10.0, ..Default::default() };
+    /// 
+    /// assert_eq!(0.5, config.establishment_chance(10, 1));
+    /// assert_eq!(0.25, config.establishment_chance(10, 3));
+    /// ```
+    pub fn establishment_chance(&self, energy: u32, crowding: usize) -> f32 {
+        let needed = self.establishment.max(0.0) * crowding.max(1) as f32;
+        if needed == 0.0 {
+            return 1.0;
+        }
+
+        energy as f32 / (energy as f32 + needed)
+    }
+
+    /// Lets every seed try to establish itself in the cell it landed in and returns the seeds which succeeded
+    /// in the order they were given, the rest die
+    /// 
+    /// # Parameters
+    /// 
+    /// seeds: The index of the cell every seed landed in together with the seed
+    /// rng: The random number generator to use
+    pub fn establish<R: Rng>(&self, seeds: Vec<(usize, Plant)>, rng: &mut R) -> Vec<(usize, Plant)> {
+        let mut crowding: BTreeMap<usize, usize> = BTreeMap::new();
+        for (target, _) in &seeds {
+            *crowding.entry(*target).or_default() += 1;
+        }
+
+        seeds.into_iter()
+            .filter(|(target, seed)| rng.gen::<f32>() < self.establishment_chance(seed.energy, crowding[target]))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn seed(energy: u32) -> Plant {
+        Plant::new(energy, Genome::new(&[0.5, 0.5]).unwrap())
+    }
+
+    #[test]
+    fn clutch_split() {
+        assert_eq!(vec![4, 3, 3], ClutchConfig::split(10, 3));
+        assert_eq!(vec![10], ClutchConfig::split(10, 0));
+        assert_eq!(vec![0, 0], ClutchConfig::split(0, 2));
+    }
+
+    #[test]
+    fn clutch_establishment_free() {
+        let config = ClutchConfig { establishment: 0.0, ..Default::default() };
+        let seeds: Vec<(usize, Plant)> = (0..10).map(|index| (index % 2, seed(0))).collect();
+
+        assert_eq!(1.0, config.establishment_chance(0, 5));
+        assert_eq!(10, config.establish(seeds, &mut ChaCha8Rng::seed_from_u64(0)).len());
+    }
+
+    #[test]
+    fn clutch_establishment_crowding() {
+        let config = ClutchConfig { establishment: 10.0, ..Default::default() };
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let mut established = [0; 2];
+
+        // Seeds of the same size alone in a cell and crowded together
+        for _ in 0..1000 {
+            let seeds = vec![(0, seed(5)), (1, seed(5)), (1, seed(5)), (1, seed(5)), (1, seed(5))];
+            for (target, _) in config.establish(seeds, &mut rng) {
+                established[target] += 1;
+            }
+        }
+
+        assert!(established[0] > 250 && established[0] < 420);
+        assert!(established[1] > 350 && established[1] < 600);
+        assert!(config.establishment_chance(50, 4) > config.establishment_chance(5, 1));
+    }
+}
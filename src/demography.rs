@@ -0,0 +1,288 @@
+use std::fmt::Write;
+
+use crate::memory::{vec_bytes, HeapSize};
+use crate::population::Population;
+
+/// A row of a life table for the plants of an age class
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LifeTableRow {
+    /// The first age of the class in steps
+    pub age: u64,
+    /// The number of plants which died at an age in the class, d_x
+    pub deaths: u64,
+    /// The fraction of the plants which reached the class, l_x
+    pub survivorship: f64,
+    /// The fraction of the plants reaching the class which died in it, q_x
+    pub mortality: f64,
+    /// The mean number of offspring born to a plant during the class, m_x
+    pub fecundity: f64,
+}
+
+/// The bookkeeping of the ages of the plants needed for the standard demographic outputs, the age distribution
+/// after every step and a life table with survivorship and fecundity for every age class.
+/// Survivorship is found from the ages the plants died at, so plants still alive are not part of it,
+/// and fecundity is the offspring born to the plants of a class for every step a plant spent in it times the width of the class
+#[derive(Clone, Debug, PartialEq)]
+pub struct Demography {
+    /// The number of ages in steps in each age class
+    bin_width: u64,
+    /// The number of steps spent by plants in every age class
+    exposure: Vec<u64>,
+    /// The number of plants which died in every age class
+    deaths: Vec<u64>,
+    /// The number of offspring born to plants in every age class
+    offspring: Vec<u64>,
+    /// The tick of every step recorded and the number of plants in every age class after it
+    histograms: Vec<(u64, Vec<usize>)>,
+}
+
+impl Demography {
+    /// Creates a new empty record
+    /// 
+    /// # Parameters
+    /// 
+    /// bin_width: The number of ages in steps in each age class, a width of 0 is treated as 1
+    pub fn new(bin_width: u64) -> Self {
+        Self { bin_width: bin_width.max(1), exposure: Vec::new(), deaths: Vec::new(), offspring: Vec::new(), histograms: Vec::new() }
+    }
+
+    /// Returns the number of ages in steps in each age class
+    pub fn bin_width(&self) -> u64 {
+        self.bin_width
+    }
+
+    /// Finds the number of plants in every age class, the last class is the one of the oldest plant
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to count
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, demography::Demography, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(3, 1));
+    /// for (x, age) in [(0, 0), (1, 3), (2, 12)] {
+    ///     let mut plant = Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap());
+    ///     plant.age = age;
+    ///     population.insert(Coord::new(x, 0), plant);
+    /// }
+    /// 
+    /// assert_eq!(vec![2, 0, 1], Demography::new(5).histogram(&population));
+    /// ```
+    pub fn histogram(&self, population: &Population) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for (_, plant) in population.iter() {
+            let bin = self.bin(plant.age);
+            if histogram.len() <= bin {
+                histogram.resize(bin + 1, 0);
+            }
+            histogram[bin] += 1;
+        }
+
+        histogram
+    }
+
+    /// Returns the tick of every step recorded and the number of plants in every age class after it
+    pub fn histograms(&self) -> &[(u64, Vec<usize>)] {
+        &self.histograms
+    }
+
+    /// Records the ages of the plants after a step
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the step
+    /// population: The plants living after the step
+    pub fn record_step(&mut self, tick: u64, population: &Population) {
+        let histogram = self.histogram(population);
+        grow(&mut self.exposure, histogram.len());
+        for (exposure, count) in self.exposure.iter_mut().zip(&histogram) {
+            *exposure += *count as u64;
+        }
+
+        self.histograms.push((tick, histogram));
+    }
+
+    /// Records the death of a plant
+    /// 
+    /// # Parameters
+    /// 
+    /// age: The age of the plant when it died
+    pub fn record_death(&mut self, age: u64) {
+        let bin = self.bin(age);
+        grow(&mut self.deaths, bin + 1);
+        self.deaths[bin] += 1;
+    }
+
+    /// Records the birth of an offspring
+    /// 
+    /// # Parameters
+    /// 
+    /// parent_age: The age of the parent when the offspring was born
+    pub fn record_birth(&mut self, parent_age: u64) {
+        let bin = self.bin(parent_age);
+        grow(&mut self.offspring, bin + 1);
+        self.offspring[bin] += 1;
+    }
+
+    /// Builds the life table with a row for every age class up to the oldest class seen.
+    /// If no plant has died yet the survivorship of every class is 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::demography::Demography;
+    /// 
+    /// let mut demography = Demography::new(10);
+    /// for age in [2, 5, 14, 31] {
+    ///     demography.record_death(age);
+    /// }
+    /// let table = demography.life_table();
+    /// 
+    /// assert_eq!(vec![0, 10, 20, 30], table.iter().map(|row| row.age).collect::<Vec<_>>());
+    /// assert_eq!(vec![1.0, 0.5, 0.25, 0.25], table.iter().map(|row| row.survivorship).collect::<Vec<_>>());
+    /// assert_eq!(0.5, table[0].mortality);
+    /// ```
+    pub fn life_table(&self) -> Vec<LifeTableRow> {
+        let classes = self.exposure.len().max(self.deaths.len()).max(self.offspring.len());
+        let total: u64 = self.deaths.iter().sum();
+        let mut remaining = total;
+
+        (0..classes).map(|class| {
+            let deaths = self.deaths.get(class).copied().unwrap_or(0);
+            let exposure = self.exposure.get(class).copied().unwrap_or(0);
+            let offspring = self.offspring.get(class).copied().unwrap_or(0);
+            let row = LifeTableRow {
+                age: class as u64 * self.bin_width,
+                deaths,
+                survivorship: if total == 0 { 1.0 } else { remaining as f64 / total as f64 },
+                mortality: if remaining == 0 { 0.0 } else { deaths as f64 / remaining as f64 },
+                fecundity: if exposure == 0 { 0.0 } else { (offspring * self.bin_width) as f64 / exposure as f64 },
+            };
+            remaining -= deaths;
+            row
+        }).collect()
+    }
+
+    /// Writes the life table as CSV with a header and a row for every age class
+    pub fn life_table_csv(&self) -> String {
+        let mut csv = String::from("age,deaths,lx,qx,mx\n");
+        for row in self.life_table() {
+            let _ = writeln!(csv, "{},{},{},{},{}", row.age, row.deaths, row.survivorship, row.mortality, row.fecundity);
+        }
+
+        csv
+    }
+
+    /// Writes the age distributions as CSV with a header, a row for every step recorded and a column for every age class
+    /// named by the first age of the class
+    pub fn histograms_csv(&self) -> String {
+        let classes = self.histograms.iter().map(|(_, histogram)| histogram.len()).max().unwrap_or(0);
+        let mut csv = String::from("tick");
+        for class in 0..classes {
+            let _ = write!(csv, ",{}", class as u64 * self.bin_width);
+        }
+        csv.push('\n');
+
+        for (tick, histogram) in &self.histograms {
+            let _ = write!(csv, "{}", tick);
+            for class in 0..classes {
+                let _ = write!(csv, ",{}", histogram.get(class).copied().unwrap_or(0));
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// Finds the age class of an age
+    fn bin(&self, age: u64) -> usize {
+        (age / self.bin_width) as usize
+    }
+}
+
+/// Makes a list of counts at least a length
+fn grow(counts: &mut Vec<u64>, len: usize) {
+    if counts.len() < len {
+        counts.resize(len, 0);
+    }
+}
+
+impl HeapSize for Demography {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.exposure) + vec_bytes(&self.deaths) + vec_bytes(&self.offspring) + vec_bytes(&self.histograms)
+            + self.histograms.iter().map(|(_, histogram)| vec_bytes(histogram)).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord, Rect, Size};
+    use crate::disturbance::{Disturbance, DisturbanceKind};
+    use crate::genome::Genome;
+    use crate::population::Plant;
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    fn population(ages: &[u64]) -> Population {
+        let mut population = Population::new(Size::new(ages.len(), 1));
+        for (x, age) in ages.iter().enumerate() {
+            let mut plant = Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap());
+            plant.age = *age;
+            population.insert(Coord::new(x, 0), plant);
+        }
+
+        population
+    }
+
+    #[test]
+    fn demography_fecundity() {
+        let mut demography = Demography::new(2);
+        demography.record_step(1, &population(&[0, 1, 2]));
+        demography.record_step(2, &population(&[1, 2, 3]));
+        for age in [1, 1, 3] {
+            demography.record_birth(age);
+        }
+        let table = demography.life_table();
+
+        // 3 plant steps in the first class and 3 in the second, each class is 2 steps wide
+        assert_eq!(vec![4.0 / 3.0, 2.0 / 3.0], table.iter().map(|row| row.fecundity).collect::<Vec<_>>());
+        assert_eq!(vec![1.0, 1.0], table.iter().map(|row| row.survivorship).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn demography_csv() {
+        let mut demography = Demography::new(5);
+        demography.record_step(1, &population(&[0, 7]));
+        demography.record_step(2, &population(&[1]));
+        demography.record_death(8);
+
+        assert_eq!("tick,0,5\n1,1,1\n2,1,0\n", demography.histograms_csv());
+        assert_eq!("age,deaths,lx,qx,mx\n0,0,1,0,0\n5,1,1,1,0\n", demography.life_table_csv());
+    }
+
+    #[test]
+    fn demography_simulation() {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+        simulation.track_demography(Some(4));
+        simulation.schedule_disturbance(30, Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 6, 3)));
+        for _ in 0..40 {
+            simulation.step();
+        }
+        let demography = simulation.demography().unwrap();
+        let table = demography.life_table();
+        let dead = simulation.phylogeny().iter().filter(|record| record.death.is_some()).count() as u64;
+        let born = simulation.phylogeny().iter().filter(|record| record.parent.is_some()).count() as f64;
+
+        assert_eq!(40, demography.histograms().len());
+        assert_eq!(simulation.population().count(), demography.histograms()[39].1.iter().sum::<usize>());
+        assert_eq!(dead, table.iter().map(|row| row.deaths).sum::<u64>());
+        assert!(table.windows(2).all(|rows| rows[0].survivorship >= rows[1].survivorship));
+        assert!(born > 0.0 && table.iter().any(|row| row.fecundity > 0.0));
+    }
+}
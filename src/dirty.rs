@@ -0,0 +1,212 @@
+use crate::board::{Rect, Size};
+use crate::memory::{vec_bytes, HeapSize};
+
+/// The cells of a board which may look different since they were last drawn, such that a renderer
+/// only has to draw and upload the changed parts of the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirtyCells {
+    /// The size of the board
+    size: Size,
+    /// True for every marked cell
+    marked: Vec<bool>,
+    /// The indices of the marked cells in the order they were marked
+    cells: Vec<usize>,
+    /// True if the entire board is marked
+    all: bool,
+}
+
+impl DirtyCells {
+    /// Creates a new set without any marked cells
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn new(size: Size) -> Self {
+        Self { size, marked: vec![false; size.len()], cells: Vec::new(), all: false }
+    }
+
+    /// Creates a new set with the entire board marked
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn all(size: Size) -> Self {
+        Self { all: true, ..Self::new(size) }
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Marks a single cell, indices outside the board are ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    pub fn mark(&mut self, index: usize) {
+        if self.all {
+            return;
+        }
+
+        if let Some(marked) = self.marked.get_mut(index) {
+            if !*marked {
+                *marked = true;
+                self.cells.push(index);
+            }
+        }
+    }
+
+    /// Marks the entire board
+    pub fn mark_all(&mut self) {
+        self.all = true;
+        self.marked.fill(false);
+        self.cells.clear();
+    }
+
+    /// Returns true if the entire board is marked
+    pub fn is_all(&self) -> bool {
+        self.all
+    }
+
+    /// Returns true if a cell is marked
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    pub fn is_dirty(&self, index: usize) -> bool {
+        index < self.size.len() && (self.all || self.marked[index])
+    }
+
+    /// Returns the number of marked cells
+    pub fn len(&self) -> usize {
+        if self.all { self.size.len() } else { self.cells.len() }
+    }
+
+    /// Returns true if no cells are marked
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the indices of all marked cells
+    pub fn cells(&self) -> impl Iterator<Item = usize> + '_ {
+        // The list of marked cells is empty while the entire board is marked
+        let all = if self.all { self.size.len() } else { 0 };
+
+        (0..all).chain(self.cells.iter().copied())
+    }
+
+    /// Finds the square tiles of the board containing marked cells, the tiles are ordered row by row
+    /// and tiles at the right and bottom edges are cut off at the edge of the board.
+    /// Larger tiles give fewer but larger regions to upload
+    /// 
+    /// # Parameters
+    /// 
+    /// tile: The width and height of a tile in cells, a value of 0 is treated as 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Rect, Size}, dirty::DirtyCells};
+    /// 
+    /// let size = Size::new(10, 10);
+    /// let mut dirty = DirtyCells::new(size);
+    /// dirty.mark(0);
+    /// dirty.mark(1);
+    /// dirty.mark(99);
+    /// 
+    /// assert_eq!(vec![Rect::new(0, 0, 4, 4), Rect::new(8, 8, 2, 2)], dirty.regions(4));
+    /// ```
+    pub fn regions(&self, tile: usize) -> Vec<Rect> {
+        let (w, h) = self.size.size();
+        if self.all {
+            return if w == 0 || h == 0 { Vec::new() } else { vec![Rect::new(0, 0, w, h)] };
+        }
+
+        let tile = tile.max(1);
+        let tiles_x = w.div_ceil(tile);
+        let mut tiles: Vec<usize> = self.cells.iter()
+            .map(|&index| {
+                let coord = self.size.coord_of(index).unwrap();
+                coord.x / tile + coord.y / tile * tiles_x
+            })
+            .collect();
+        tiles.sort_unstable();
+        tiles.dedup();
+
+        tiles.into_iter()
+            .map(|index| {
+                let (x, y) = (index % tiles_x * tile, index / tiles_x * tile);
+                Rect::new(x, y, tile.min(w - x), tile.min(h - y))
+            })
+            .collect()
+    }
+
+    /// Removes all marks
+    pub fn clear(&mut self) {
+        self.all = false;
+        for &index in &self.cells {
+            self.marked[index] = false;
+        }
+        self.cells.clear();
+    }
+}
+
+impl HeapSize for DirtyCells {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.marked) + vec_bytes(&self.cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dirty_cells_mark() {
+        let mut dirty = DirtyCells::new(Size::new(3, 3));
+        dirty.mark(4);
+        dirty.mark(4);
+        dirty.mark(20);
+
+        assert_eq!(1, dirty.len());
+        assert!(dirty.is_dirty(4));
+        assert!(!dirty.is_dirty(3));
+        assert_eq!(vec![4], dirty.cells().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn dirty_cells_all() {
+        let mut dirty = DirtyCells::new(Size::new(3, 2));
+        dirty.mark(1);
+        dirty.mark_all();
+        dirty.mark(2);
+
+        assert!(dirty.is_all());
+        assert_eq!(6, dirty.cells().count());
+        assert_eq!(vec![Rect::new(0, 0, 3, 2)], dirty.regions(1));
+    }
+
+    #[test]
+    fn dirty_cells_clear() {
+        let mut dirty = DirtyCells::all(Size::new(2, 2));
+        dirty.clear();
+        dirty.mark(3);
+        dirty.clear();
+
+        assert!(dirty.is_empty());
+        assert!(!dirty.is_dirty(3));
+        assert!(dirty.regions(2).is_empty());
+    }
+
+    #[test]
+    fn dirty_cells_regions_order() {
+        let size = Size::new(5, 5);
+        let mut dirty = DirtyCells::new(size);
+        dirty.mark(24);
+        dirty.mark(3);
+        dirty.mark(10);
+
+        assert_eq!(vec![Rect::new(2, 0, 2, 2), Rect::new(0, 2, 2, 2), Rect::new(4, 4, 1, 1)], dirty.regions(2));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::genome::Genome;
+use crate::population::PlantId;
+
+/// A way of measuring how different two genomes are, the distance of a genome to itself must be 0
+pub trait GenomeDistance {
+    /// Calculates the distance between two genomes
+    /// 
+    /// # Parameters
+    /// 
+    /// genome1: The first genome
+    /// genome2: The second genome
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32;
+}
+
+/// The mean absolute difference of the genes, genes only present in one of the genomes count as a difference of 1.
+/// This is the distance used by Genome::distance
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct MeanAbsolute;
+
+impl GenomeDistance for MeanAbsolute {
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32 {
+        genome1.distance(genome2)
+    }
+}
+
+/// The number of genes which differ by more than a tolerance, genes only present in one of the genomes always differ
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Hamming {
+    /// The largest difference between two genes for them to count as equal
+    pub tolerance: f32,
+}
+
+impl GenomeDistance for Hamming {
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{distance::{GenomeDistance, Hamming}, genome::Genome};
+    /// 
+    /// let genome1 = Genome::new(&[0.5, 0.25, 1.0]).unwrap();
+    /// let genome2 = Genome::new(&[0.5, 0.75]).unwrap();
+    /// 
+    /// assert_eq!(2.0, Hamming { tolerance: 0.1 }.distance(&genome1, &genome2));
+    /// ```
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32 {
+        let (genes1, genes2) = (genome1.genes(), genome2.genes());
+
+        let different = genes1.iter()
+            .zip(genes2.iter())
+            .filter(|(gene1, gene2)| (*gene1 - *gene2).abs() > self.tolerance)
+            .count();
+
+        (different + genes1.len().abs_diff(genes2.len())) as f32
+    }
+}
+
+/// The smallest number of genes which must be inserted, removed or changed to turn one genome into the other,
+/// two genes differing by at most the tolerance count as equal
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct EditDistance {
+    /// The largest difference between two genes for them to count as equal
+    pub tolerance: f32,
+}
+
+impl GenomeDistance for EditDistance {
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{distance::{EditDistance, GenomeDistance}, genome::Genome};
+    /// 
+    /// let genome1 = Genome::new(&[0.1, 0.5, 0.9]).unwrap();
+    /// let genome2 = Genome::new(&[0.5, 0.9]).unwrap();
+    /// 
+    /// assert_eq!(1.0, EditDistance { tolerance: 0.0 }.distance(&genome1, &genome2));
+    /// ```
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32 {
+        let (genes1, genes2) = (genome1.genes(), genome2.genes());
+
+        // Only keep the previous row of the table
+        let mut previous: Vec<usize> = (0..=genes2.len()).collect();
+        let mut current = vec![0; genes2.len() + 1];
+
+        for (i, gene1) in genes1.iter().enumerate() {
+            current[0] = i + 1;
+
+            for (j, gene2) in genes2.iter().enumerate() {
+                let change = if (gene1 - gene2).abs() <= self.tolerance { 0 } else { 1 };
+
+                current[j + 1] = (previous[j] + change)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1);
+            }
+
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        previous[genes2.len()] as f32
+    }
+}
+
+/// The euclidean distance between the genomes seen as points with a coordinate per gene,
+/// genes only present in one of the genomes count as a difference of 1
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Euclidean;
+
+impl GenomeDistance for Euclidean {
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{distance::{Euclidean, GenomeDistance}, genome::Genome};
+    /// 
+    /// let genome1 = Genome::new(&[0.0, 0.0]).unwrap();
+    /// let genome2 = Genome::new(&[0.3, 0.4]).unwrap();
+    /// 
+    /// assert!((0.5 - Euclidean.distance(&genome1, &genome2)).abs() < 1e-6);
+    /// ```
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32 {
+        let (genes1, genes2) = (genome1.genes(), genome2.genes());
+
+        let squared: f32 = genes1.iter()
+            .zip(genes2.iter())
+            .map(|(gene1, gene2)| (gene1 - gene2) * (gene1 - gene2))
+            .sum();
+
+        (squared + genes1.len().abs_diff(genes2.len()) as f32).sqrt()
+    }
+}
+
+/// A choice of distance which can be stored in the settings of a simulation
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Metric {
+    /// The mean absolute difference of the genes
+    #[default]
+    MeanAbsolute,
+    /// The number of genes which differ
+    Hamming(Hamming),
+    /// The number of insertions, removals and changes of genes
+    EditDistance(EditDistance),
+    /// The euclidean distance of the genes
+    Euclidean,
+}
+
+impl GenomeDistance for Metric {
+    fn distance(&self, genome1: &Genome, genome2: &Genome) -> f32 {
+        match self {
+            Self::MeanAbsolute => MeanAbsolute.distance(genome1, genome2),
+            Self::Hamming(hamming) => hamming.distance(genome1, genome2),
+            Self::EditDistance(edit) => edit.distance(genome1, genome2),
+            Self::Euclidean => Euclidean.distance(genome1, genome2),
+        }
+    }
+}
+
+/// The distances between every pair of a set of plants
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceMatrix {
+    /// The ids of the plants in the order of the rows and columns
+    ids: Vec<PlantId>,
+    /// The distances row by row
+    values: Vec<f32>,
+}
+
+impl DistanceMatrix {
+    /// Calculates the distances between every pair of genomes
+    pub(crate) fn new<'a, D: GenomeDistance + ?Sized, I: IntoIterator<Item = (PlantId, &'a Genome)>>(metric: &D, plants: I) -> Self {
+        let (ids, genomes): (Vec<_>, Vec<_>) = plants.into_iter().unzip();
+        let len = ids.len();
+        let mut values = vec![0.0; len * len];
+
+        for i in 0..len {
+            for j in i + 1..len {
+                let distance = metric.distance(genomes[i], genomes[j]);
+                values[i * len + j] = distance;
+                values[j * len + i] = distance;
+            }
+        }
+
+        Self { ids, values }
+    }
+
+    /// Returns the number of plants in the matrix
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns true if there are no plants in the matrix
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the ids of the plants in the order of the rows and columns
+    pub fn ids(&self) -> &[PlantId] {
+        &self.ids
+    }
+
+    /// Gets the distance between the plants of a row and a column
+    /// 
+    /// # Parameters
+    /// 
+    /// row: The index of the first plant
+    /// column: The index of the second plant
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the row or column is not less than the number of plants
+    pub fn get(&self, row: usize, column: usize) -> f32 {
+        assert!(row < self.len() && column < self.len(), "The row and column must be inside the matrix");
+
+        self.values[row * self.len() + column]
+    }
+
+    /// Calculates the mean distance over all pairs of different plants, returns 0 if there are fewer than two plants
+    pub fn mean(&self) -> f32 {
+        let len = self.len();
+        if len < 2 {
+            return 0.0;
+        }
+
+        self.values.iter().sum::<f32>() / (len * (len - 1)) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genome(genes: &[f32]) -> Genome {
+        Genome::new(genes).unwrap()
+    }
+
+    #[test]
+    fn mean_absolute_distance() {
+        assert_eq!(0.375, MeanAbsolute.distance(&genome(&[0.5, 0.25]), &genome(&[0.25, 0.75])));
+    }
+
+    #[test]
+    fn hamming_distance() {
+        let hamming = Hamming { tolerance: 0.25 };
+
+        assert_eq!(0.0, hamming.distance(&genome(&[0.5, 0.5]), &genome(&[0.75, 0.25])));
+        assert_eq!(1.0, hamming.distance(&genome(&[0.5, 0.5]), &genome(&[1.0, 0.5])));
+        assert_eq!(2.0, hamming.distance(&genome(&[0.5, 0.5]), &genome(&[0.5, 0.5, 0.0, 0.0])));
+    }
+
+    #[test]
+    fn edit_distance() {
+        let edit = EditDistance { tolerance: 0.0 };
+
+        assert_eq!(0.0, edit.distance(&genome(&[0.25, 0.5]), &genome(&[0.25, 0.5])));
+        assert_eq!(1.0, edit.distance(&genome(&[0.25, 0.5]), &genome(&[0.25, 0.75])));
+        assert_eq!(2.0, edit.distance(&genome(&[0.25, 0.5, 0.75, 1.0]), &genome(&[0.5, 0.75])));
+        assert_eq!(2.0, edit.distance(&genome(&[0.0, 1.0]), &genome(&[1.0, 0.0])));
+    }
+
+    #[test]
+    fn edit_distance_tolerance() {
+        let edit = EditDistance { tolerance: 0.1 };
+
+        assert_eq!(0.0, edit.distance(&genome(&[0.25, 0.5]), &genome(&[0.3, 0.45])));
+    }
+
+    #[test]
+    fn euclidean_distance() {
+        assert_eq!(0.0, Euclidean.distance(&genome(&[0.5, 0.5]), &genome(&[0.5, 0.5])));
+        assert_eq!(1.0, Euclidean.distance(&genome(&[0.0, 0.0]), &genome(&[0.0, 1.0])));
+        assert_eq!(2.0, Euclidean.distance(&genome(&[0.5, 0.5]), &genome(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0])));
+    }
+
+    #[test]
+    fn metric_distance() {
+        let (genome1, genome2) = (genome(&[0.0, 0.0]), genome(&[0.0, 1.0]));
+
+        assert_eq!(0.5, Metric::default().distance(&genome1, &genome2));
+        assert_eq!(1.0, Metric::Hamming(Hamming::default()).distance(&genome1, &genome2));
+        assert_eq!(1.0, Metric::EditDistance(EditDistance::default()).distance(&genome1, &genome2));
+        assert_eq!(1.0, Metric::Euclidean.distance(&genome1, &genome2));
+    }
+
+    #[test]
+    fn distance_matrix_new() {
+        let (genome1, genome2, genome3) = (genome(&[0.0, 0.0]), genome(&[0.0, 1.0]), genome(&[1.0, 1.0]));
+        let matrix = DistanceMatrix::new(&MeanAbsolute, [(PlantId(2), &genome1), (PlantId(5), &genome2), (PlantId(7), &genome3)]);
+
+        assert_eq!(3, matrix.len());
+        assert_eq!(&[PlantId(2), PlantId(5), PlantId(7)], matrix.ids());
+        assert_eq!(0.0, matrix.get(1, 1));
+        assert_eq!(0.5, matrix.get(0, 1));
+        assert_eq!(0.5, matrix.get(1, 0));
+        assert_eq!(1.0, matrix.get(2, 0));
+        assert_eq!(2.0 / 3.0, matrix.mean());
+    }
+
+    #[test]
+    fn distance_matrix_empty() {
+        let matrix = DistanceMatrix::new(&Euclidean, []);
+
+        assert!(matrix.is_empty());
+        assert_eq!(0.0, matrix.mean());
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::board::{Coord, Rect, Size, Terrain};
+
+/// A field of the board a drought can take away
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resource {
+    /// The light of the board
+    Light,
+    /// The water on the board
+    Water,
+}
+
+/// What a disturbance does to the cells it hits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisturbanceKind {
+    /// Burns down all plants
+    Fire,
+    /// Holds a resource at 0 for a number of steps, after which the resource gets back the value it had before.
+    /// The duration is at least 1 step
+    Drought {
+        resource: Resource,
+        duration: u64,
+    },
+    /// Kills all plants and permanently changes the terrain
+    Meteor {
+        terrain: Terrain,
+    },
+}
+
+/// A single disturbance hitting a region of the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Disturbance {
+    /// What the disturbance does
+    pub kind: DisturbanceKind,
+    /// The cells hit by the disturbance, it is clamped to the board
+    pub region: Rect,
+}
+
+impl Disturbance {
+    /// Creates a new disturbance
+    /// 
+    /// # Parameters
+    /// 
+    /// kind: What the disturbance does
+    /// region: The cells hit by the disturbance
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Rect, disturbance::{Disturbance, DisturbanceKind}};
+    /// 
+    /// let fire = Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 2, 2));
+    /// 
+    /// assert_eq!(4, fire.region.area());
+    /// ```
+    pub fn new(kind: DisturbanceKind, region: Rect) -> Self {
+        Self { kind, region }
+    }
+}
+
+/// A disturbance which can happen every step with a fixed chance at a random place on the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RandomDisturbance {
+    /// What the disturbance does
+    pub kind: DisturbanceKind,
+    /// The chance of the disturbance happening in a single step between 0 and 1
+    pub chance: f32,
+    /// The width of the region hit
+    pub w: usize,
+    /// The height of the region hit
+    pub h: usize,
+}
+
+impl RandomDisturbance {
+    /// Decides if the disturbance happens and where, the region is centered on a random cell
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// rng: The random number generator to use
+    pub(crate) fn roll<R: Rng>(&self, size: Size, rng: &mut R) -> Option<Disturbance> {
+        if size.is_empty() || rng.gen::<f32>() >= self.chance {
+            return None;
+        }
+
+        let (w, h) = size.size();
+        let center = Coord::new(rng.gen_range(0..w), rng.gen_range(0..h));
+        let region = Rect::new(center.x.saturating_sub(self.w / 2), center.y.saturating_sub(self.h / 2), self.w, self.h);
+
+        Some(Disturbance::new(self.kind, region))
+    }
+}
+
+/// A disturbance which has happened
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisturbanceRecord {
+    /// The tick the disturbance happened at
+    pub tick: u64,
+    /// The disturbance with its region clamped to the board
+    pub disturbance: Disturbance,
+    /// The number of plants killed
+    pub killed: usize,
+}
+
+/// A cell of a resource held at 0 by a drought or at a multiple of its value by fertilizing
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Held {
+    /// The value of the cell before it was held
+    original: f32,
+    /// The factor the original value is multiplied by while it is held, 0 for a drought
+    factor: f32,
+    /// The tick at which the cell gets its value back
+    until: u64,
+}
+
+/// The scheduled and random disturbances of a simulation and the droughts currently going on
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Disturbances {
+    /// The disturbances waiting to happen by the tick they happen at
+    scheduled: BTreeMap<u64, Vec<Disturbance>>,
+    /// The disturbances which may happen in every step
+    random: Vec<RandomDisturbance>,
+    /// The cells of the light held at 0
+    held_light: BTreeMap<usize, Held>,
+    /// The cells of the water held at 0
+    held_water: BTreeMap<usize, Held>,
+    /// All disturbances which have happened in order
+    log: Vec<DisturbanceRecord>,
+}
+
+impl Disturbances {
+    /// Schedules a disturbance to happen in the step reaching a tick
+    pub fn schedule(&mut self, tick: u64, disturbance: Disturbance) {
+        self.scheduled.entry(tick).or_default().push(disturbance);
+    }
+
+    /// Adds a disturbance which may happen in every step
+    pub fn add_random(&mut self, disturbance: RandomDisturbance) {
+        self.random.push(disturbance);
+    }
+
+    /// Returns all disturbances which have happened in order
+    pub fn log(&self) -> &[DisturbanceRecord] {
+        &self.log
+    }
+
+    /// Remembers a disturbance which has happened
+    pub fn record(&mut self, record: DisturbanceRecord) {
+        self.log.push(record);
+    }
+
+    /// Takes all disturbances which should happen in the step reaching a tick, first the scheduled ones
+    /// in the order they were scheduled and then the random ones which happened in the order they were added
+    pub fn due<R: Rng>(&mut self, tick: u64, size: Size, rng: &mut R) -> Vec<Disturbance> {
+        let later = self.scheduled.split_off(&(tick + 1));
+        let mut due: Vec<Disturbance> = std::mem::replace(&mut self.scheduled, later).into_values().flatten().collect();
+
+        due.extend(self.random.iter().filter_map(|random| random.roll(size, rng)));
+
+        due
+    }
+
+    /// Starts a drought in a set of cells, cells already in a drought keep their original value
+    /// and stay dry until the latest of the droughts ends
+    pub fn hold(&mut self, resource: Resource, indices: &[usize], until: u64, values: &mut [f32]) {
+        let cells: Vec<(usize, f32)> = indices.iter().map(|&index| (index, 0.0)).collect();
+        self.scale(resource, &cells, until, values);
+    }
+
+    /// Holds a set of cells at a multiple of the value they had before, cells which are already held keep their
+    /// original value and take the latest factor until the latest of the holds ends
+    pub fn scale(&mut self, resource: Resource, cells: &[(usize, f32)], until: u64, values: &mut [f32]) {
+        let held = self.held_mut(resource);
+
+        for &(index, factor) in cells {
+            let cell = held.entry(index).or_insert(Held { original: values[index], factor, until });
+            cell.until = cell.until.max(until);
+            cell.factor = factor.max(0.0);
+            values[index] = cell.original * cell.factor;
+        }
+    }
+
+    /// Gives back the value of all cells whose drought or fertilizing has ended and holds the rest,
+    /// returns true if any cells got their value back
+    pub fn release(&mut self, resource: Resource, tick: u64, values: &mut [f32]) -> bool {
+        let held = self.held_mut(resource);
+        let before = held.len();
+
+        held.retain(|&index, cell| {
+            if cell.until <= tick {
+                values[index] = cell.original;
+                false
+            } else {
+                values[index] = cell.original * cell.factor;
+                true
+            }
+        });
+
+        held.len() != before
+    }
+
+    /// Moves the droughts and the scheduled disturbances along when a rectangle of the board becomes the new board,
+    /// the cells outside the rectangle are no longer held and the scheduled regions are cut to the rectangle
+    pub fn reframe(&mut self, from: Size, rect: Rect) {
+        let to = Size::new(rect.w, rect.h);
+        let moved = |held: &BTreeMap<usize, Held>| -> BTreeMap<usize, Held> {
+            held.iter()
+                .filter_map(|(&index, &cell)| {
+                    let coord = from.coord_of(index).unwrap();
+                    let inside = coord.x.checked_sub(rect.x).zip(coord.y.checked_sub(rect.y));
+                    inside.and_then(|(x, y)| to.index_of(Coord::new(x, y))).map(|index| (index, cell))
+                })
+                .collect()
+        };
+        self.held_light = moved(&self.held_light);
+        self.held_water = moved(&self.held_water);
+
+        for disturbance in self.scheduled.values_mut().flatten() {
+            let region = disturbance.region;
+            let x = region.x.max(rect.x);
+            let y = region.y.max(rect.y);
+            let w = (region.x + region.w).min(rect.x + rect.w).saturating_sub(x);
+            let h = (region.y + region.h).min(rect.y + rect.h).saturating_sub(y);
+            disturbance.region = Rect::new(x - rect.x, y - rect.y, w, h);
+        }
+    }
+
+    /// Gets the held cells of a resource
+    fn held_mut(&mut self, resource: Resource) -> &mut BTreeMap<usize, Held> {
+        match resource {
+            Resource::Light => &mut self.held_light,
+            Resource::Water => &mut self.held_water,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn fire(x: usize) -> Disturbance {
+        Disturbance::new(DisturbanceKind::Fire, Rect::new(x, 0, 1, 1))
+    }
+
+    #[test]
+    fn random_disturbance_roll() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let never = RandomDisturbance { kind: DisturbanceKind::Fire, chance: 0.0, w: 3, h: 3 };
+        let always = RandomDisturbance { chance: 1.0, ..never };
+        let size = Size::new(10, 10);
+
+        assert_eq!(None, never.roll(size, &mut rng));
+        for _ in 0..20 {
+            let region = always.roll(size, &mut rng).unwrap().region;
+            assert!(region.w == 3 && region.h == 3 && region.x < 10 && region.y < 10);
+        }
+        assert_eq!(None, always.roll(Size::new(0, 0), &mut rng));
+    }
+
+    #[test]
+    fn disturbances_due() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut disturbances = Disturbances::default();
+        disturbances.schedule(5, fire(1));
+        disturbances.schedule(3, fire(0));
+        disturbances.schedule(5, fire(2));
+        disturbances.schedule(8, fire(3));
+
+        assert!(disturbances.due(2, Size::new(4, 1), &mut rng).is_empty());
+        assert_eq!(vec![fire(0), fire(1), fire(2)], disturbances.due(6, Size::new(4, 1), &mut rng));
+        assert!(disturbances.due(7, Size::new(4, 1), &mut rng).is_empty());
+        assert_eq!(vec![fire(3)], disturbances.due(8, Size::new(4, 1), &mut rng));
+    }
+
+    #[test]
+    fn disturbances_hold_release() {
+        let mut disturbances = Disturbances::default();
+        let mut values = vec![1.0, 2.0, 3.0];
+        disturbances.hold(Resource::Water, &[0, 1], 5, &mut values);
+        disturbances.hold(Resource::Water, &[1], 7, &mut values);
+
+        assert_eq!(vec![0.0, 0.0, 3.0], values);
+
+        // Something refills the cells while they are held
+        values = vec![0.5, 0.5, 3.0];
+
+        assert!(!disturbances.release(Resource::Water, 4, &mut values));
+        assert_eq!(vec![0.0, 0.0, 3.0], values);
+        assert!(disturbances.release(Resource::Water, 5, &mut values));
+        assert_eq!(vec![1.0, 0.0, 3.0], values);
+        assert!(disturbances.release(Resource::Water, 7, &mut values));
+        assert_eq!(vec![1.0, 2.0, 3.0], values);
+    }
+
+    #[test]
+    fn disturbances_scale_release() {
+        let mut disturbances = Disturbances::default();
+        let mut values = vec![1.0, 2.0];
+        disturbances.scale(Resource::Water, &[(0, 2.0), (1, 1.5)], 3, &mut values);
+
+        assert_eq!(vec![2.0, 3.0], values);
+
+        // A drought over a fertilized cell takes over but the cell gets its original value back
+        disturbances.hold(Resource::Water, &[1], 4, &mut values);
+        values = vec![0.5, 0.5];
+
+        assert!(disturbances.release(Resource::Water, 3, &mut values));
+        assert_eq!(vec![1.0, 0.0], values);
+        assert!(disturbances.release(Resource::Water, 4, &mut values));
+        assert_eq!(vec![1.0, 2.0], values);
+    }
+
+    #[test]
+    fn disturbances_hold_resources() {
+        let mut disturbances = Disturbances::default();
+        let mut light = vec![1.0];
+        let mut water = vec![2.0];
+        disturbances.hold(Resource::Light, &[0], 2, &mut light);
+
+        assert!(!disturbances.release(Resource::Water, 2, &mut water));
+        assert_eq!(vec![2.0], water);
+        assert!(disturbances.release(Resource::Light, 2, &mut light));
+        assert_eq!(vec![1.0], light);
+    }
+
+    #[test]
+    fn disturbances_reframe() {
+        let mut disturbances = Disturbances::default();
+        let mut values = vec![1.0, 2.0, 3.0, 4.0];
+        disturbances.hold(Resource::Water, &[0, 3], 5, &mut values);
+        disturbances.schedule(4, Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 2, 2)));
+        disturbances.reframe(Size::new(2, 2), Rect::new(1, 1, 2, 2));
+
+        let mut values = vec![0.0, 5.0, 5.0, 5.0];
+        assert!(disturbances.release(Resource::Water, 5, &mut values));
+        assert_eq!(vec![4.0, 5.0, 5.0, 5.0], values);
+        assert_eq!(Rect::new(0, 0, 1, 1), disturbances.due(4, Size::new(2, 2), &mut ChaCha8Rng::seed_from_u64(0))[0].region);
+    }
+}
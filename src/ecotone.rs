@@ -0,0 +1,118 @@
+use crate::board::{Coord, Size};
+
+/// A band along the edges of the board with harsher conditions, which keeps plants from favouring the edges
+/// where they have fewer neighbours to compete with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EdgeBand {
+    /// The width of the band in cells
+    pub width: usize,
+    /// The fraction of the light lost in the outermost cells, the loss falls linearly to 0 at the inner side of the band
+    pub light_loss: f32,
+    /// The fraction of the water lost every step in the outermost cells, the loss falls linearly to 0 at the inner
+    /// side of the band
+    pub water_loss: f32,
+}
+
+impl EdgeBand {
+    /// Creates a new edge band
+    /// 
+    /// # Parameters
+    /// 
+    /// width: The width of the band in cells
+    /// light_loss: The fraction of the light lost in the outermost cells
+    /// water_loss: The fraction of the water lost every step in the outermost cells
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::ecotone::EdgeBand;
+    /// 
+    /// let band = EdgeBand::new(4, 0.5, 0.1);
+    /// 
+    /// assert_eq!(4, band.width);
+    /// ```
+    pub fn new(width: usize, light_loss: f32, water_loss: f32) -> Self {
+        Self { width, light_loss, water_loss }
+    }
+
+    /// Finds how strongly the band affects a cell, this is 1 for the outermost cells, falls linearly towards
+    /// the inner side of the band and is 0 outside the band
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// coord: The cell to find the strength for
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, ecotone::EdgeBand};
+    /// 
+    /// let band = EdgeBand::new(2, 0.5, 0.1);
+    /// let size = Size::new(5, 5);
+    /// 
+    /// assert_eq!(1.0, band.strength(size, Coord::new(0, 3)));
+    /// assert_eq!(0.5, band.strength(size, Coord::new(1, 3)));
+    /// assert_eq!(0.0, band.strength(size, Coord::new(2, 2)));
+    /// ```
+    pub fn strength(&self, size: Size, coord: Coord) -> f32 {
+        let (w, h) = size.size();
+        let distance = coord.x
+            .min(coord.y)
+            .min(w.saturating_sub(coord.x + 1))
+            .min(h.saturating_sub(coord.y + 1));
+
+        if distance >= self.width {
+            0.0
+        } else {
+            (self.width - distance) as f32 / self.width as f32
+        }
+    }
+
+    /// Removes a fraction of the values in the band, scaled by the strength of the band in every cell
+    pub(crate) fn apply(&self, size: Size, values: &mut [f32], loss: f32) {
+        let loss = loss.clamp(0.0, 1.0);
+
+        for (index, value) in values.iter_mut().enumerate() {
+            *value *= 1.0 - loss * self.strength(size, size.coord_of(index).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_band_new() {
+        assert_eq!(EdgeBand { width: 3, light_loss: 0.5, water_loss: 0.25 }, EdgeBand::new(3, 0.5, 0.25));
+    }
+
+    #[test]
+    fn edge_band_strength() {
+        let band = EdgeBand::new(2, 1.0, 1.0);
+        let size = Size::new(6, 5);
+        let strengths: Vec<f32> = (0..6).map(|x| band.strength(size, Coord::new(x, 2))).collect();
+
+        assert_eq!(vec![1.0, 0.5, 0.0, 0.0, 0.5, 1.0], strengths);
+        assert_eq!(1.0, band.strength(size, Coord::new(3, 4)));
+        assert_eq!(0.5, band.strength(size, Coord::new(3, 1)));
+    }
+
+    #[test]
+    fn edge_band_strength_empty() {
+        let band = EdgeBand::new(0, 1.0, 1.0);
+
+        assert_eq!(0.0, band.strength(Size::new(3, 3), Coord::new(0, 0)));
+    }
+
+    #[test]
+    fn edge_band_apply() {
+        let band = EdgeBand::new(1, 0.5, 0.0);
+        let size = Size::new(3, 3);
+        let mut values = vec![2.0; 9];
+        band.apply(size, &mut values, band.light_loss);
+
+        assert_eq!(vec![1.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 1.0], values);
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::BTreeMap;
+
+use crate::board::{Coord, Size};
+use crate::genome::Genome;
+use crate::population::Plant;
+use crate::simulation::Simulation;
+
+/// The shape and strength of the brush used to edit the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Brush {
+    /// The largest distance in cells from the center of the brush to a cell it touches
+    pub radius: f32,
+    /// The amount added to a field at the center of the brush, the amount falls off linearly towards the edge,
+    /// negative strengths remove from the field
+    pub strength: f32,
+}
+
+impl Brush {
+    /// Creates a new brush
+    /// 
+    /// # Parameters
+    /// 
+    /// radius: The largest distance in cells from the center of the brush to a cell it touches
+    /// strength: The amount added to a field at the center of the brush
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::edit::Brush;
+    /// 
+    /// let brush = Brush::new(2.0, 0.1);
+    /// 
+    /// assert_eq!(2.0, brush.radius);
+    /// ```
+    pub fn new(radius: f32, strength: f32) -> Self {
+        Self { radius, strength }
+    }
+
+    /// Finds the index and weight of every cell the brush touches, the weight is 1 at the center
+    /// and falls off linearly towards the edge of the brush
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// center: The cell at the center of the brush
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, edit::Brush};
+    /// 
+    /// let brush = Brush::new(1.0, 0.1);
+    /// let cells = brush.cells(Size::new(3, 3), Coord::new(0, 0));
+    /// 
+    /// assert_eq!(vec![(0, 1.0), (1, 0.5), (3, 0.5)], cells);
+    /// ```
+    pub fn cells(&self, size: Size, center: Coord) -> Vec<(usize, f32)> {
+        let radius = self.radius.max(0.0);
+        let reach = radius.floor() as isize;
+        let mut cells = Vec::new();
+
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                if distance > radius {
+                    continue;
+                }
+
+                let (x, y) = (center.x as isize + dx, center.y as isize + dy);
+                if x < 0 || y < 0 {
+                    continue;
+                }
+
+                if let Some(index) = size.index_of(Coord::new(x as usize, y as usize)) {
+                    cells.push((index, 1.0 - distance / (radius + 1.0)));
+                }
+            }
+        }
+
+        cells.sort_by_key(|(index, _)| *index);
+
+        cells
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self { radius: 2.0, strength: 0.05 }
+    }
+}
+
+/// What the brush does to the cells it touches
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tool {
+    /// Adds light to the board, the light never goes below 0
+    Light,
+    /// Adds water to the board, the water never goes below 0
+    Water,
+    /// Plants copies of a plant in the empty cells plants can grow in
+    Plant { energy: u32, genome: Genome },
+    /// Removes all plants
+    Remove,
+}
+
+/// The state of the cells before a single stroke of the brush changed them
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Stroke {
+    /// The light of the board before the stroke
+    light: BTreeMap<usize, f32>,
+    /// The water before the stroke
+    water: BTreeMap<usize, f32>,
+    /// The plants before the stroke
+    plants: BTreeMap<usize, Option<Plant>>,
+}
+
+impl Stroke {
+    /// Returns true if the stroke did not change anything
+    fn is_empty(&self) -> bool {
+        self.light.is_empty() && self.water.is_empty() && self.plants.is_empty()
+    }
+}
+
+/// Applies edits to a running simulation and remembers them so they can be undone stroke by stroke
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditHistory {
+    /// The finished strokes with the latest last
+    strokes: Vec<Stroke>,
+    /// The stroke currently being painted
+    current: Option<Stroke>,
+    /// The largest number of strokes remembered, the oldest are forgotten first
+    limit: usize,
+}
+
+impl EditHistory {
+    /// Creates a new empty history
+    /// 
+    /// # Parameters
+    /// 
+    /// limit: The largest number of strokes which can be undone
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::edit::EditHistory;
+    /// 
+    /// let history = EditHistory::new(16);
+    /// 
+    /// assert!(history.is_empty());
+    /// ```
+    pub fn new(limit: usize) -> Self {
+        Self { strokes: Vec::new(), current: None, limit }
+    }
+
+    /// Returns the number of strokes which can be undone
+    pub fn len(&self) -> usize {
+        self.strokes.len()
+    }
+
+    /// Returns true if there are no strokes to undo
+    pub fn is_empty(&self) -> bool {
+        self.strokes.is_empty()
+    }
+
+    /// Applies the brush with a tool at a cell, this is part of the current stroke which is started if there is none
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to edit
+    /// tool: What the brush does
+    /// brush: The brush to paint with
+    /// center: The cell at the center of the brush
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, edit::{Brush, EditHistory, Tool}};
+    /// use evolution_plants::{population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(3, 3).light_uniform(0.5).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut history = EditHistory::new(16);
+    /// history.paint(&mut simulation, &Tool::Light, &Brush::new(0.0, 0.25), Coord::new(1, 1));
+    /// history.end();
+    /// 
+    /// assert_eq!(0.75, simulation.light()[4]);
+    /// 
+    /// history.undo(&mut simulation);
+    /// 
+    /// assert_eq!(0.5, simulation.light()[4]);
+    /// ```
+    pub fn paint(&mut self, simulation: &mut Simulation, tool: &Tool, brush: &Brush, center: Coord) {
+        let cells = brush.cells(simulation.board().fields.size, center);
+        let stroke = self.current.get_or_insert_with(Stroke::default);
+
+        match tool {
+            Tool::Light => {
+                let light = simulation.board_light_mut();
+                for (index, weight) in cells {
+                    stroke.light.entry(index).or_insert(light[index]);
+                    light[index] = (light[index] + brush.strength * weight).max(0.0);
+                }
+                simulation.refresh_light();
+            }
+            Tool::Water => {
+                let water = simulation.water_mut().values_mut();
+                for (index, weight) in cells {
+                    stroke.water.entry(index).or_insert(water[index]);
+                    water[index] = (water[index] + brush.strength * weight).max(0.0);
+                }
+            }
+            Tool::Plant { energy, genome } => {
+                for (index, _) in cells {
+                    if !simulation.population().is_occupied(index) && simulation.plant_founder(index, Plant::new(*energy, genome.clone())) {
+                        stroke.plants.entry(index).or_insert(None);
+                    }
+                }
+            }
+            Tool::Remove => {
+                for (index, _) in cells {
+                    if let Some(plant) = simulation.remove_plant(index) {
+                        stroke.plants.entry(index).or_insert(Some(plant));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finishes the current stroke so it can be undone, strokes which did not change anything are dropped
+    pub fn end(&mut self) {
+        if let Some(stroke) = self.current.take() {
+            if !stroke.is_empty() {
+                self.strokes.push(stroke);
+            }
+        }
+
+        if self.strokes.len() > self.limit {
+            let excess = self.strokes.len() - self.limit;
+            self.strokes.drain(..excess);
+        }
+    }
+
+    /// Undoes the latest stroke, finishing the current stroke first, returns false if there was nothing to undo.
+    /// The cells get back the state they had before the stroke, plants put back keep their ids
+    /// but the lineage tree keeps the record of the edit
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation which was edited
+    pub fn undo(&mut self, simulation: &mut Simulation) -> bool {
+        self.end();
+
+        let Some(stroke) = self.strokes.pop() else {
+            return false;
+        };
+
+        if !stroke.light.is_empty() {
+            let light = simulation.board_light_mut();
+            for (index, value) in stroke.light {
+                light[index] = value;
+            }
+            simulation.refresh_light();
+        }
+
+        let water = simulation.water_mut().values_mut();
+        for (index, value) in stroke.water {
+            water[index] = value;
+        }
+
+        for (index, plant) in stroke.plants {
+            simulation.restore_plant(index, plant);
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardBuilder;
+    use crate::population::Population;
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(0.5).water(&[1.0; 16]).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn brush_cells_falloff() {
+        let brush = Brush::new(1.5, 1.0);
+        let cells = brush.cells(Size::new(3, 3), Coord::new(1, 1));
+
+        assert_eq!(9, cells.len());
+        assert_eq!((4, 1.0), cells[4]);
+        assert_eq!((1, 0.6), cells[1]);
+        assert!(cells[0].1 > 0.0 && cells[0].1 < 0.6);
+    }
+
+    #[test]
+    fn brush_cells_edge() {
+        let brush = Brush::new(0.0, 1.0);
+
+        assert_eq!(vec![(15, 1.0)], brush.cells(Size::new(4, 4), Coord::new(3, 3)));
+        assert!(brush.cells(Size::new(4, 4), Coord::new(4, 0)).is_empty());
+    }
+
+    #[test]
+    fn edit_history_paint_water() {
+        let mut simulation = simulation();
+        let mut history = EditHistory::new(4);
+        history.paint(&mut simulation, &Tool::Water, &Brush::new(0.0, -2.0), Coord::new(0, 0));
+        history.paint(&mut simulation, &Tool::Water, &Brush::new(0.0, 0.5), Coord::new(1, 0));
+
+        assert_eq!(0.0, simulation.water().values()[0]);
+        assert_eq!(1.5, simulation.water().values()[1]);
+
+        assert!(history.undo(&mut simulation));
+        assert_eq!(&[1.0; 16], simulation.water().values());
+        assert!(!history.undo(&mut simulation));
+    }
+
+    #[test]
+    fn edit_history_paint_plants() {
+        let mut simulation = simulation();
+        let mut history = EditHistory::new(4);
+        let tool = Tool::Plant { energy: 50, genome: Genome::new(&[0.25, 0.25]).unwrap() };
+        let id = simulation.population().get(Coord::new(1, 1)).unwrap().id();
+
+        history.paint(&mut simulation, &tool, &Brush::new(1.0, 1.0), Coord::new(1, 1));
+        history.end();
+
+        assert_eq!(5, simulation.population().count());
+        assert_eq!(100, simulation.population().get(Coord::new(1, 1)).unwrap().energy);
+
+        history.paint(&mut simulation, &Tool::Remove, &Brush::new(0.0, 1.0), Coord::new(1, 1));
+        history.end();
+
+        assert_eq!(4, simulation.population().count());
+        assert_eq!(2, history.len());
+
+        history.undo(&mut simulation);
+
+        assert_eq!(Some(id), simulation.population().get(Coord::new(1, 1)).map(|plant| plant.id()));
+
+        history.undo(&mut simulation);
+
+        assert_eq!(1, simulation.population().count());
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn edit_history_limit() {
+        let mut simulation = simulation();
+        let mut history = EditHistory::new(2);
+        for x in 0..3 {
+            history.paint(&mut simulation, &Tool::Light, &Brush::new(0.0, 0.5), Coord::new(x, 0));
+            history.end();
+        }
+        // A stroke which changes nothing is not remembered
+        history.paint(&mut simulation, &Tool::Remove, &Brush::new(0.0, 1.0), Coord::new(3, 3));
+        history.end();
+
+        assert_eq!(2, history.len());
+
+        history.undo(&mut simulation);
+        history.undo(&mut simulation);
+
+        assert_eq!(&[1.0, 0.5, 0.5, 0.5], &simulation.light()[..4]);
+    }
+}
@@ -0,0 +1,140 @@
+use thiserror::Error;
+
+use crate::autosave::AutosaveError;
+use crate::board::{BoardConcatError, FieldCreateError, MultiplierError};
+use crate::checkpoint::CheckpointError;
+use crate::experiment::ExperimentError;
+use crate::genes::{GeneParseError, GeneRegistryError};
+use crate::genome::{GenomeCreateError, GenomeParseError, MutationConfigError};
+use crate::runarchive::RunArchiveError;
+use crate::simulation::{ConfigError, SimulationCreateError};
+use crate::sweep::SweepError;
+use crate::world::WorldError;
+
+/// A result with any error of the crate
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Any error of the crate, every error of the modules converts into it such that application code
+/// can use a single error type with the ? operator
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+/// use evolution_plants::simulation::{Simulation, SimulationConfig};
+/// 
+/// fn start() -> evolution_plants::Result<Simulation> {
+///     let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build()?;
+///     let mut population = Population::new(board.fields.size);
+///     population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5])?));
+/// 
+///     Ok(Simulation::new(board, population, SimulationConfig::default())?)
+/// }
+/// 
+/// assert!(start().is_ok());
+/// ```
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Field(#[from] FieldCreateError),
+    #[error(transparent)]
+    BoardConcat(#[from] BoardConcatError),
+    #[error(transparent)]
+    Multiplier(#[from] MultiplierError),
+    #[error(transparent)]
+    Genome(#[from] GenomeCreateError),
+    #[error(transparent)]
+    GenomeParse(#[from] GenomeParseError),
+    #[error(transparent)]
+    Mutation(#[from] MutationConfigError),
+    #[error(transparent)]
+    GeneRegistry(#[from] GeneRegistryError),
+    #[error(transparent)]
+    GeneParse(#[from] GeneParseError),
+    #[error(transparent)]
+    Simulation(#[from] SimulationCreateError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    World(#[from] WorldError),
+    #[error(transparent)]
+    Experiment(#[from] ExperimentError),
+    #[error(transparent)]
+    Sweep(#[from] SweepError),
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+    #[error(transparent)]
+    Autosave(#[from] AutosaveError),
+    #[error(transparent)]
+    RunArchive(#[from] RunArchiveError),
+    #[error("Unable to read or write: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(any(feature = "wasm", feature = "remote", feature = "cdylib"))]
+    #[error("Unable to read or write JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "image")]
+    #[error("Unable to read or write the image: {0}")]
+    Image(#[from] image::ImageError),
+    #[cfg(feature = "image")]
+    #[error(transparent)]
+    FieldImage(#[from] crate::fieldimage::FieldImageError),
+    #[cfg(feature = "image")]
+    #[error(transparent)]
+    Record(#[from] crate::recorder::RecordError),
+    #[cfg(feature = "netcdf")]
+    #[error(transparent)]
+    Netcdf(#[from] crate::netcdf::NetcdfError),
+    #[cfg(feature = "live-reload")]
+    #[error(transparent)]
+    Reload(#[from] crate::reload::ReloadError),
+    #[cfg(feature = "signals")]
+    #[error(transparent)]
+    Shutdown(#[from] crate::shutdown::ShutdownError),
+    #[cfg(feature = "tracing")]
+    #[error(transparent)]
+    Logging(#[from] crate::logging::LoggingError),
+    #[cfg(feature = "gui")]
+    #[error("Unable to open the window: {0}")]
+    Window(#[from] winit::error::OsError),
+    #[cfg(feature = "gui")]
+    #[error("Unable to draw the window: {0}")]
+    Draw(#[from] softbuffer::SoftBufferError),
+    #[cfg(feature = "gui")]
+    #[error("Unable to post to the window: {0}")]
+    Closed(#[from] winit::event_loop::EventLoopClosed<crate::interface::UserEvent>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use crate::genome::Genome;
+
+    fn genome() -> Result<Genome> {
+        Ok(Genome::new(&[])?)
+    }
+
+    #[test]
+    fn error_from() {
+        assert!(matches!(genome(), Err(Error::Genome(_))));
+
+        let error = Error::from(SimulationCreateError::Size { board: Size::new(1, 1), population: Size::new(2, 2) });
+
+        assert_eq!(SimulationCreateError::Size { board: Size::new(1, 1), population: Size::new(2, 2) }.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn error_io() {
+        let error: Error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing").into();
+
+        assert!(matches!(error, Error::Io(_)));
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn error_closed() {
+        let error: Error = winit::event_loop::EventLoopClosed(crate::interface::UserEvent::Shutdown).into();
+
+        assert!(matches!(error, Error::Closed(_)));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::adaptive::MutationAdjustment;
+use crate::alert::Alert;
+use crate::board::{Coord, Rect};
+use crate::disturbance::Disturbance;
+use crate::population::PlantId;
+use crate::simulation::ConfigUpdate;
+use crate::species::SpeciesId;
+
+/// Something which happened during a step of a simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimEvent {
+    /// A seed germinated and became a new plant
+    PlantBorn {
+        tick: u64,
+        id: PlantId,
+        coord: Coord,
+        parent: Option<PlantId>,
+        mate: Option<PlantId>,
+    },
+    /// A plant died because it could not pay its upkeep or was killed by a disturbance
+    PlantDied {
+        tick: u64,
+        id: PlantId,
+        coord: Coord,
+    },
+    /// The genome of a seed was mutated, the seed may still fail to germinate
+    MutationApplied {
+        tick: u64,
+        parent: PlantId,
+        genes: usize,
+    },
+    /// The mutation rate was changed by adaptive mutation
+    MutationRateChanged {
+        adjustment: MutationAdjustment,
+    },
+    /// A disturbance hit the board
+    Disturbed {
+        tick: u64,
+        disturbance: Disturbance,
+        killed: usize,
+    },
+    /// Part of the population was removed by a bottleneck, the plants removed are reported as PlantDied first
+    Culled {
+        tick: u64,
+        killed: usize,
+        survivors: usize,
+    },
+    /// Some of the settings were changed during the run
+    ConfigUpdated {
+        tick: u64,
+        update: ConfigUpdate,
+    },
+    /// An update of the scenario could not be used so the settings were kept, ConfigUpdate::validate tells why
+    ConfigRejected {
+        tick: u64,
+        update: ConfigUpdate,
+    },
+    /// A species was found for the first time when the plants were clustered, the founding genome is kept in the
+    /// events of the species tracker
+    SpeciesOriginated {
+        tick: u64,
+        species: SpeciesId,
+        origin: Rect,
+    },
+    /// A species had no members left when the plants were clustered
+    SpeciesExtinct {
+        tick: u64,
+        species: SpeciesId,
+        peak: usize,
+    },
+    /// An alert rule started to hold after the statistics of a step were calculated
+    Alert {
+        alert: Alert,
+    },
+    /// A step finished
+    TickCompleted {
+        tick: u64,
+        population: usize,
+    },
+}
+
+impl SimEvent {
+    /// Returns the tick the event happened at
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::events::SimEvent;
+    /// 
+    /// assert_eq!(4, SimEvent::TickCompleted { tick: 4, population: 10 }.tick());
+    /// ```
+    pub fn tick(&self) -> u64 {
+        match self {
+            SimEvent::PlantBorn { tick, .. }
+            | SimEvent::PlantDied { tick, .. }
+            | SimEvent::MutationApplied { tick, .. }
+            | SimEvent::Disturbed { tick, .. }
+            | SimEvent::Culled { tick, .. }
+            | SimEvent::ConfigUpdated { tick, .. }
+            | SimEvent::ConfigRejected { tick, .. }
+            | SimEvent::SpeciesOriginated { tick, .. }
+            | SimEvent::SpeciesExtinct { tick, .. }
+            | SimEvent::TickCompleted { tick, .. } => *tick,
+            SimEvent::MutationRateChanged { adjustment } => adjustment.tick,
+            SimEvent::Alert { alert } => alert.tick,
+        }
+    }
+}
+
+/// A function called for every event of a simulation
+pub type Hook = Box<dyn FnMut(&SimEvent) + Send>;
+
+/// All the hooks of a simulation, hooks are not copied when a simulation is cloned
+#[derive(Default)]
+pub(crate) struct Hooks {
+    /// The hooks in the order they were added
+    hooks: Vec<Hook>,
+}
+
+impl Hooks {
+    /// Adds a hook
+    pub fn push(&mut self, hook: Hook) {
+        self.hooks.push(hook);
+    }
+
+    /// Returns true if there are no hooks
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Returns true if the events should be recorded, which is when there are hooks or the tracing feature logs them
+    pub fn is_listening(&self) -> bool {
+        !self.is_empty() || cfg!(feature = "tracing")
+    }
+
+    /// Calls every hook with every event in order, with the tracing feature the events are logged first
+    pub fn emit(&mut self, events: &[SimEvent]) {
+        for event in events {
+            #[cfg(feature = "tracing")]
+            crate::logging::log_event(event);
+
+            for hook in self.hooks.iter_mut() {
+                hook(event);
+            }
+        }
+    }
+}
+
+impl Clone for Hooks {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hooks({})", self.hooks.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn sim_event_tick() {
+        let adjustment = MutationAdjustment { tick: 3, diversity: 0.0, old_rate: 0.1, new_rate: 0.2 };
+
+        assert_eq!(3, SimEvent::MutationRateChanged { adjustment }.tick());
+        assert_eq!(2, SimEvent::PlantDied { tick: 2, id: PlantId(0), coord: Coord::new(0, 0) }.tick());
+    }
+
+    #[test]
+    fn hooks_emit() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = Hooks::default();
+        for name in ["a", "b"] {
+            let seen = seen.clone();
+            hooks.push(Box::new(move |event| seen.lock().unwrap().push((name, event.tick()))));
+        }
+        hooks.emit(&[SimEvent::TickCompleted { tick: 1, population: 0 }, SimEvent::TickCompleted { tick: 2, population: 0 }]);
+
+        assert_eq!(vec![("a", 1), ("b", 1), ("a", 2), ("b", 2)], *seen.lock().unwrap());
+    }
+
+    #[test]
+    fn hooks_clone() {
+        let mut hooks = Hooks::default();
+        hooks.push(Box::new(|_| ()));
+
+        assert!(!hooks.is_empty());
+        assert!(hooks.clone().is_empty());
+    }
+}
@@ -0,0 +1,330 @@
+use std::path::PathBuf;
+use std::thread;
+
+use thiserror::Error;
+
+use crate::board::Board;
+use crate::population::Population;
+use crate::simulation::{Simulation, SimulationConfig, SimulationCreateError};
+use crate::stats::{self, TickStats};
+
+/// The starting state of a single run of an experiment
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    /// The board the run starts on
+    pub board: Board,
+    /// The plants the run starts with
+    pub population: Population,
+    /// The settings of the run
+    pub config: SimulationConfig,
+}
+
+/// A batch of isolated simulations run for the same number of steps on their own threads,
+/// the statistics of all runs are collected such that they can be compared and summarized
+#[derive(Clone, Debug, PartialEq)]
+pub struct Experiment {
+    /// The runs of the experiment
+    runs: Vec<Run>,
+    /// The number of steps every run is stepped
+    ticks: u64,
+    /// The largest number of runs stepped at the same time
+    threads: usize,
+}
+
+impl Experiment {
+    /// Creates a new experiment without any runs, the runs are spread over as many threads as the machine has cores
+    /// 
+    /// # Parameters
+    /// 
+    /// ticks: The number of steps every run is stepped
+    pub fn new(ticks: u64) -> Self {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        Self { runs: Vec::new(), ticks, threads }
+    }
+
+    /// Sets the largest number of runs stepped at the same time, a value of 0 is treated as 1
+    /// 
+    /// # Parameters
+    /// 
+    /// threads: The number of threads
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Adds a run
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board the run starts on
+    /// population: The plants the run starts with
+    /// config: The settings of the run
+    pub fn run(mut self, board: Board, population: Population, config: SimulationConfig) -> Self {
+        self.runs.push(Run { board, population, config });
+        self
+    }
+
+    /// Adds a run for every seed, the runs only differ in the seed of the config
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board the runs start on
+    /// population: The plants the runs start with
+    /// config: The settings of the runs
+    /// seeds: The seeds of the runs
+    pub fn replicate<I: IntoIterator<Item = u64>>(mut self, board: Board, population: Population, config: SimulationConfig, seeds: I) -> Self {
+        for seed in seeds {
+            self.runs.push(Run { board: board.clone(), population: population.clone(), config: SimulationConfig { seed, ..config.clone() } });
+        }
+        self
+    }
+
+    /// Returns the runs of the experiment
+    pub fn runs(&self) -> &[Run] {
+        &self.runs
+    }
+
+    /// Steps all runs and collects the statistics of every step of every run
+    /// 
+    /// # Errors
+    /// 
+    /// ExperimentError::Run: This will occur if one of the runs could not be created
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, experiment::Experiment, genome::Genome};
+    /// use evolution_plants::{population::{Plant, Population}, simulation::SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+    /// let report = Experiment::new(20).replicate(board, population, SimulationConfig::default(), 0..4).execute().unwrap();
+    /// let summary = report.final_summary(|stats| stats.population as f64);
+    /// 
+    /// assert_eq!(4, summary.count);
+    /// assert!(summary.low <= summary.mean && summary.mean <= summary.high);
+    /// ```
+    pub fn execute(self) -> Result<ExperimentReport, ExperimentError> {
+        let runs = self.step_runs()?.into_iter().map(|(records, _)| records).collect();
+
+        Ok(ExperimentReport { runs })
+    }
+
+    /// Returns the number of steps every run is stepped
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Steps all runs and returns the statistics of every step of every run together with the final simulation of the run
+    pub(crate) fn step_runs(&self) -> Result<Vec<(Vec<TickStats>, Simulation)>, ExperimentError> {
+        let ticks = self.ticks;
+        let mut runs = Vec::with_capacity(self.runs.len());
+
+        for (batch, chunk) in self.runs.chunks(self.threads).enumerate() {
+            let results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = chunk.iter()
+                    .map(|run| scope.spawn(move || step_run(run.clone(), ticks)))
+                    .collect();
+
+                handles.into_iter().map(|handle| handle.join().expect("A run panicked")).collect()
+            });
+
+            for (offset, result) in results.into_iter().enumerate() {
+                runs.push(result.map_err(|error| ExperimentError::Run { run: batch * self.threads + offset, error })?);
+            }
+        }
+
+        Ok(runs)
+    }
+}
+
+/// Steps a single run and collects the statistics of every step, returns the statistics and the final simulation
+pub(crate) fn step_run(run: Run, ticks: u64) -> Result<(Vec<TickStats>, Simulation), SimulationCreateError> {
+    let mut simulation = Simulation::new(run.board, run.population, run.config)?;
+    let mut subscription = stats::subscribe(&mut simulation, 1);
+    let mut records = Vec::with_capacity(ticks as usize);
+
+    for _ in 0..ticks {
+        simulation.step();
+        records.extend(subscription.try_next());
+    }
+
+    Ok((records, simulation))
+}
+
+/// The statistics collected by an experiment
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExperimentReport {
+    /// The statistics of every step of every run in the order the runs were added
+    pub runs: Vec<Vec<TickStats>>,
+}
+
+impl ExperimentReport {
+    /// Summarizes a value of the last step of every run
+    /// 
+    /// # Parameters
+    /// 
+    /// metric: Extracts the value from the statistics of a step
+    pub fn final_summary<F: Fn(&TickStats) -> f64>(&self, metric: F) -> Summary {
+        let values: Vec<f64> = self.runs.iter().filter_map(|run| run.last()).map(&metric).collect();
+
+        Summary::new(&values)
+    }
+
+    /// Summarizes a value across the runs at every step, steps are only summarized while every run has reached them
+    /// 
+    /// # Parameters
+    /// 
+    /// metric: Extracts the value from the statistics of a step
+    pub fn series<F: Fn(&TickStats) -> f64>(&self, metric: F) -> Vec<Summary> {
+        let steps = self.runs.iter().map(Vec::len).min().unwrap_or(0);
+
+        (0..steps)
+            .map(|step| Summary::new(&self.runs.iter().map(|run| metric(&run[step])).collect::<Vec<_>>()))
+            .collect()
+    }
+}
+
+/// The summary statistics of a value across the runs of an experiment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    /// The number of runs
+    pub count: usize,
+    /// The mean of the value
+    pub mean: f64,
+    /// The sample standard deviation of the value, 0 if there are fewer than two runs
+    pub std_dev: f64,
+    /// The lower end of the 95% confidence interval of the mean
+    pub low: f64,
+    /// The upper end of the 95% confidence interval of the mean
+    pub high: f64,
+}
+
+impl Summary {
+    /// Summarizes a set of values, the confidence interval uses the t-distribution and is only the mean
+    /// if there are fewer than two values
+    /// 
+    /// # Parameters
+    /// 
+    /// values: The value of every run
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::experiment::Summary;
+    /// 
+    /// let summary = Summary::new(&[1.0, 2.0, 3.0]);
+    /// 
+    /// assert_eq!(2.0, summary.mean);
+    /// assert_eq!(1.0, summary.std_dev);
+    /// assert!((summary.high - 2.0 - 4.303 / 3f64.sqrt()).abs() < 1e-9);
+    /// ```
+    pub fn new(values: &[f64]) -> Self {
+        let count = values.len();
+        if count == 0 {
+            return Self { count, mean: 0.0, std_dev: 0.0, low: 0.0, high: 0.0 };
+        }
+
+        let mean = values.iter().sum::<f64>() / count as f64;
+        if count == 1 {
+            return Self { count, mean, std_dev: 0.0, low: mean, high: mean };
+        }
+
+        let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (count - 1) as f64;
+        let std_dev = variance.sqrt();
+        let margin = t_critical(count - 1) * std_dev / (count as f64).sqrt();
+
+        Self { count, mean, std_dev, low: mean - margin, high: mean + margin }
+    }
+}
+
+/// Finds the two-sided 95% critical value of the t-distribution
+fn t_critical(degrees: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+        2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+
+    match degrees {
+        0 => f64::INFINITY,
+        1..=30 => TABLE[degrees - 1],
+        31..=60 => 2.000,
+        61..=120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// The errors which can occur when running an experiment
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ExperimentError {
+    #[error("Run {:?} could not be created: {}", run, error)]
+    Run {
+        run: usize,
+        error: SimulationCreateError,
+    },
+    #[error("Unable to write {:?}: {}", path, message)]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::Plant;
+
+    fn start() -> (Board, Population) {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+
+        (board, population)
+    }
+
+    #[test]
+    fn experiment_execute() {
+        let (board, population) = start();
+        let report = Experiment::new(10).threads(2).replicate(board, population, SimulationConfig::default(), 0..3).execute().unwrap();
+
+        assert_eq!(3, report.runs.len());
+        assert!(report.runs.iter().all(|run| run.len() == 10 && run[9].tick == 10));
+        assert_eq!(10, report.series(|stats| stats.population as f64).len());
+    }
+
+    #[test]
+    fn experiment_deterministic() {
+        // Runs give the same statistics no matter how they are spread over threads
+        let (board, population) = start();
+        let first = Experiment::new(15).threads(1).replicate(board.clone(), population.clone(), SimulationConfig::default(), 0..3).execute().unwrap();
+        let second = Experiment::new(15).threads(3).replicate(board, population, SimulationConfig::default(), 0..3).execute().unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn experiment_error() {
+        let (board, _) = start();
+        let population = Population::new(crate::board::Size::new(2, 2));
+        let result = Experiment::new(5).threads(1).run(board.clone(), Population::new(board.fields.size), SimulationConfig::default()).run(board, population, SimulationConfig::default()).execute();
+
+        assert!(matches!(result, Err(ExperimentError::Run { run: 1, .. })));
+    }
+
+    #[test]
+    fn summary_small() {
+        assert_eq!(0, Summary::new(&[]).count);
+        assert_eq!(Summary { count: 1, mean: 4.0, std_dev: 0.0, low: 4.0, high: 4.0 }, Summary::new(&[4.0]));
+    }
+
+    #[test]
+    fn t_critical_large() {
+        assert_eq!(1.960, t_critical(1000));
+        assert_eq!(2.042, t_critical(30));
+    }
+}
@@ -0,0 +1,310 @@
+use std::ffi::{c_char, CStr};
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::jsonconfig;
+use crate::simulation::Simulation;
+
+/// The light in every cell after the shadows, as read by sim_get_field
+pub const SIM_FIELD_LIGHT: u32 = 0;
+/// The water in every cell, as read by sim_get_field
+pub const SIM_FIELD_WATER: u32 = 1;
+/// The nutrients in every cell, as read by sim_get_field
+pub const SIM_FIELD_NUTRIENTS: u32 = 2;
+/// The toxin in every cell, as read by sim_get_field
+pub const SIM_FIELD_TOXIN: u32 = 3;
+/// The energy of the plant in every cell and 0 in empty cells, as read by sim_get_field
+pub const SIM_FIELD_ENERGY: u32 = 4;
+
+/// The step was run, as returned by sim_step
+pub const SIM_OK: i32 = 0;
+/// The simulation was NULL so nothing was run, as returned by sim_step
+pub const SIM_ERROR_NULL: i32 = 1;
+/// The step panicked and was stopped part way, the simulation must only be freed afterwards, as returned by sim_step
+pub const SIM_ERROR_PANIC: i32 = 2;
+
+/// The name and value of every field constant written to the header
+const FIELDS: [(&str, u32); 5] = [
+    ("SIM_FIELD_LIGHT", SIM_FIELD_LIGHT),
+    ("SIM_FIELD_WATER", SIM_FIELD_WATER),
+    ("SIM_FIELD_NUTRIENTS", SIM_FIELD_NUTRIENTS),
+    ("SIM_FIELD_TOXIN", SIM_FIELD_TOXIN),
+    ("SIM_FIELD_ENERGY", SIM_FIELD_ENERGY),
+];
+
+/// The name and value of every status constant written to the header
+const STATUSES: [(&str, i32); 3] = [
+    ("SIM_OK", SIM_OK),
+    ("SIM_ERROR_NULL", SIM_ERROR_NULL),
+    ("SIM_ERROR_PANIC", SIM_ERROR_PANIC),
+];
+
+/// The comment and the declaration of every function written to the header, in the order they are defined
+const FUNCTIONS: [(&str, &str); 7] = [
+    (
+        "Creates a simulation from its settings in JSON, returns NULL if the settings are invalid",
+        "Simulation *sim_new(const char *config_json);",
+    ),
+    (
+        "Runs a single step, returns SIM_OK, SIM_ERROR_NULL if sim is NULL or SIM_ERROR_PANIC if the step failed,\n   after which the simulation must only be freed",
+        "int32_t sim_step(Simulation *sim);",
+    ),
+    ("Returns the number of steps which have been run", "uint64_t sim_tick(const Simulation *sim);"),
+    ("Returns the width of the board in cells", "size_t sim_width(const Simulation *sim);"),
+    ("Returns the height of the board in cells", "size_t sim_height(const Simulation *sim);"),
+    (
+        "Copies a field with one value per cell with the rows in order into out, at most len values are copied.\n   Returns the number of cells of the board, or 0 if the field is unknown",
+        "size_t sim_get_field(const Simulation *sim, uint32_t field, float *out, size_t len);",
+    ),
+    ("Frees a simulation created by sim_new, NULL is ignored", "void sim_free(Simulation *sim);"),
+];
+
+/// Creates a new simulation from its settings in JSON, the simulation must be freed with sim_free.
+/// Returns NULL if the pointer is NULL, the text is not UTF-8, the settings cannot make a simulation or creating it panics
+/// 
+/// # Parameters
+/// 
+/// config_json: The settings in the format of jsonconfig::simulation_from_json as a NUL-terminated string
+/// 
+/// # Safety
+/// 
+/// config_json must be NULL or point to a NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn sim_new(config_json: *const c_char) -> *mut Simulation {
+    if config_json.is_null() {
+        return ptr::null_mut();
+    }
+
+    let Ok(text) = CStr::from_ptr(config_json).to_str() else {
+        return ptr::null_mut();
+    };
+
+    guard(ptr::null_mut(), || match jsonconfig::simulation_from_json(text) {
+        Ok(simulation) => Box::into_raw(Box::new(simulation)),
+        Err(_) => ptr::null_mut(),
+    })
+}
+
+/// Runs a single step and returns SIM_OK, SIM_ERROR_NULL if the simulation is NULL or SIM_ERROR_PANIC if the step panicked,
+/// in which case the simulation was stopped part way and must only be freed
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed
+#[no_mangle]
+pub unsafe extern "C" fn sim_step(sim: *mut Simulation) -> i32 {
+    let Some(simulation) = sim.as_mut() else {
+        return SIM_ERROR_NULL;
+    };
+
+    guard(SIM_ERROR_PANIC, || {
+        simulation.step();
+        SIM_OK
+    })
+}
+
+/// Returns the number of steps which have been run, 0 for NULL
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed
+#[no_mangle]
+pub unsafe extern "C" fn sim_tick(sim: *const Simulation) -> u64 {
+    guard(0, || sim.as_ref().map_or(0, |simulation| simulation.tick()))
+}
+
+/// Returns the width of the board in cells, 0 for NULL
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed
+#[no_mangle]
+pub unsafe extern "C" fn sim_width(sim: *const Simulation) -> usize {
+    guard(0, || sim.as_ref().map_or(0, |simulation| simulation.board().fields.size.size().0))
+}
+
+/// Returns the height of the board in cells, 0 for NULL
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed
+#[no_mangle]
+pub unsafe extern "C" fn sim_height(sim: *const Simulation) -> usize {
+    guard(0, || sim.as_ref().map_or(0, |simulation| simulation.board().fields.size.size().1))
+}
+
+/// Copies a field with one value for every cell with the rows in order into a buffer, at most len values are copied
+/// so the caller can ask for the number of cells first by passing NULL and 0. Returns the number of cells of the board,
+/// or 0 if the simulation is NULL, the field is unknown or reading it panicked
+/// 
+/// # Parameters
+/// 
+/// sim: The simulation to read
+/// field: The field to read, one of the SIM_FIELD constants
+/// out: The buffer to copy the values to, may be NULL if len is 0
+/// len: The number of values the buffer has room for
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed,
+/// and out must have room for len values unless len is 0
+#[no_mangle]
+pub unsafe extern "C" fn sim_get_field(sim: *const Simulation, field: u32, out: *mut f32, len: usize) -> usize {
+    let Some(simulation) = sim.as_ref() else {
+        return 0;
+    };
+
+    let Some(values) = guard(None, || field_values(simulation, field)) else {
+        return 0;
+    };
+
+    let count = values.len().min(len);
+    if count > 0 && !out.is_null() {
+        ptr::copy_nonoverlapping(values.as_ptr(), out, count);
+    }
+
+    values.len()
+}
+
+/// Frees a simulation created by sim_new, NULL is ignored
+/// 
+/// # Safety
+/// 
+/// sim must be NULL or a simulation from sim_new which has not been freed, it must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn sim_free(sim: *mut Simulation) {
+    if !sim.is_null() {
+        guard((), || drop(Box::from_raw(sim)));
+    }
+}
+
+/// Runs the body of an exported function and returns the fallback if it panics, a panic must never unwind into the caller
+/// 
+/// # Parameters
+/// 
+/// fallback: The value to return if the body panics
+/// body: The body of the function
+fn guard<T, F: FnOnce() -> T>(fallback: T, body: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(fallback)
+}
+
+/// Reads a field of a simulation, None if the field is unknown
+fn field_values(simulation: &Simulation, field: u32) -> Option<Vec<f32>> {
+    let values = match field {
+        SIM_FIELD_LIGHT => simulation.light().to_vec(),
+        SIM_FIELD_WATER => simulation.water().values().to_vec(),
+        SIM_FIELD_NUTRIENTS => simulation.nutrients().values().to_vec(),
+        SIM_FIELD_TOXIN => simulation.toxins().values().to_vec(),
+        SIM_FIELD_ENERGY => {
+            let population = simulation.population();
+            (0..population.size().len()).map(|index| population.plant(index).map_or(0.0, |plant| plant.energy as f32)).collect()
+        }
+        _ => return None,
+    };
+
+    Some(values)
+}
+
+/// Generates the C header declaring the functions and constants of the C API,
+/// the header shipped in the include directory is this text
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::ffi;
+/// 
+/// let header = ffi::header();
+/// 
+/// assert!(header.contains("Simulation *sim_new(const char *config_json);"));
+/// assert!(header.contains("#define SIM_FIELD_WATER 1"));
+/// ```
+pub fn header() -> String {
+    let mut header = String::new();
+    let _ = writeln!(header, "/* The C API of evolution_plants, generated by evolution_plants::ffi::header */");
+    let _ = writeln!(header, "#ifndef EVOLUTION_PLANTS_H\n#define EVOLUTION_PLANTS_H\n");
+    let _ = writeln!(header, "#include <stddef.h>\n#include <stdint.h>\n");
+    let _ = writeln!(header, "#ifdef __cplusplus\nextern \"C\" {{\n#endif\n");
+    let _ = writeln!(header, "/* A running simulation, only ever handled through a pointer */\ntypedef struct Simulation Simulation;\n");
+
+    let _ = writeln!(header, "/* The fields which can be read with sim_get_field */");
+    for (name, value) in FIELDS {
+        let _ = writeln!(header, "#define {} {}", name, value);
+    }
+
+    let _ = writeln!(header, "\n/* The statuses returned by sim_step */");
+    for (name, value) in STATUSES {
+        let _ = writeln!(header, "#define {} {}", name, value);
+    }
+
+    for (comment, declaration) in FUNCTIONS {
+        let _ = writeln!(header, "\n/* {} */\n{}", comment, declaration);
+    }
+
+    let _ = writeln!(header, "\n#ifdef __cplusplus\n}}\n#endif\n\n#endif");
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn ffi_lifecycle() {
+        let config = CString::new(r#"{"width": 4, "height": 2, "plants": [{"x": 1, "y": 1, "energy": 100, "genes": [0.5, 0.5]}]}"#).unwrap();
+
+        unsafe {
+            let sim = sim_new(config.as_ptr());
+            assert!(!sim.is_null());
+            assert_eq!((4, 2), (sim_width(sim), sim_height(sim)));
+
+            assert_eq!(SIM_OK, sim_step(sim));
+            assert_eq!(1, sim_tick(sim));
+
+            // The number of cells is found first and a short buffer only gets the first values
+            assert_eq!(8, sim_get_field(sim, SIM_FIELD_ENERGY, ptr::null_mut(), 0));
+            let mut energy = vec![-1.0; 8];
+            assert_eq!(8, sim_get_field(sim, SIM_FIELD_ENERGY, energy.as_mut_ptr(), 8));
+            assert!(energy[5] > 0.0);
+            assert_eq!(0.0, energy[0]);
+
+            let mut light = [-1.0; 3];
+            assert_eq!(8, sim_get_field(sim, SIM_FIELD_LIGHT, light.as_mut_ptr(), 2));
+            assert_eq!(-1.0, light[2]);
+            assert_eq!(0, sim_get_field(sim, 99, light.as_mut_ptr(), 3));
+
+            sim_free(sim);
+        }
+    }
+
+    #[test]
+    fn ffi_invalid() {
+        let config = CString::new("{").unwrap();
+        let mutation = CString::new(r#"{"mutationStrength": -0.1}"#).unwrap();
+        let seed_cost = CString::new(r#"{"seedCost": 4294967295}"#).unwrap();
+
+        unsafe {
+            assert!(sim_new(ptr::null()).is_null());
+            assert!(sim_new(config.as_ptr()).is_null());
+            assert!(sim_new(mutation.as_ptr()).is_null());
+            assert!(sim_new(seed_cost.as_ptr()).is_null());
+            assert_eq!(0, sim_tick(ptr::null()));
+            assert_eq!(SIM_ERROR_NULL, sim_step(ptr::null_mut()));
+            sim_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn ffi_guard() {
+        assert_eq!(SIM_ERROR_PANIC, guard(SIM_ERROR_PANIC, || -> i32 { panic!("the step failed") }));
+        assert_eq!(SIM_OK, guard(SIM_ERROR_PANIC, || SIM_OK));
+    }
+
+    #[test]
+    fn ffi_header_shipped() {
+        let shipped = include_str!("../include/evolution_plants.h");
+
+        assert_eq!(header(), shipped, "the shipped header is out of date, regenerate it from ffi::header");
+    }
+}
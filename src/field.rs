@@ -0,0 +1,423 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::board::{reframe_cells, FieldCreateError, Rect, Size};
+use crate::memory::{vec_bytes, HeapSize};
+use crate::view::{FieldView, FieldViewMut};
+
+/// How the edges of a field connect to the neighbouring cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    /// The cells at the edges have no neighbours past the edge
+    #[default]
+    Bounded,
+    /// The left edge is connected to the right edge and the top edge to the bottom edge
+    Torus,
+}
+
+/// A value for every cell of a board stored row by row, the shared type of all fields such that their size is checked
+/// in one place and they are indexed, mapped and diffused the same way. A field derefs to the slice of its values
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Field<T = f32> {
+    /// The size of the board
+    size: Size,
+    /// The value of every cell with the rows in order
+    data: Vec<T>,
+    /// How the edges connect
+    topology: Topology,
+}
+
+impl<T> Field<T> {
+    /// Creates a new bounded field from its values
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the field used in errors
+    /// size: The size of the board
+    /// data: The value of every cell with the rows in order
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if there is not exactly one value for every cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, field::Field};
+    /// 
+    /// let field = Field::from_vec("Light", Size::new(2, 1), vec![0.5, 1.0]).unwrap();
+    /// 
+    /// assert_eq!(1.0, field[1]);
+    /// assert!(Field::from_vec("Light", Size::new(2, 2), vec![0.5, 1.0]).is_err());
+    /// ```
+    pub fn from_vec(name: &str, size: Size, data: Vec<T>) -> Result<Self, FieldCreateError> {
+        if data.len() != size.len() {
+            return Err(FieldCreateError::Size { name: name.to_string(), len: data.len(), size });
+        }
+
+        Ok(Self { size, data, topology: Topology::Bounded })
+    }
+
+    /// Sets how the edges of the field connect
+    /// 
+    /// # Parameters
+    /// 
+    /// topology: How the edges connect
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the number of values between the starts of two rows
+    pub fn stride(&self) -> usize {
+        self.size.stride()
+    }
+
+    /// Returns how the edges of the field connect
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the value of every cell with the rows in order
+    pub fn values(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Returns the value of every cell with the rows in order for changing them
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Takes the values out of the field
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Finds the index of the cell an offset away from another cell, None if the offset leaves a bounded field
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell to start from
+    /// dx: The offset to the right
+    /// dy: The offset downwards
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, field::{Field, Topology}};
+    /// 
+    /// let bounded = Field::from_vec("Water", Size::new(3, 2), vec![0.0; 6]).unwrap();
+    /// let torus = bounded.clone().with_topology(Topology::Torus);
+    /// 
+    /// assert_eq!(Some(4), bounded.offset(0, 1, 1));
+    /// assert_eq!(None, bounded.offset(0, -1, 0));
+    /// assert_eq!(Some(2), torus.offset(0, -1, 0));
+    /// assert_eq!(Some(3), torus.offset(0, 0, 3));
+    /// ```
+    pub fn offset(&self, index: usize, dx: isize, dy: isize) -> Option<usize> {
+        let (w, h) = self.size.size();
+        if index >= self.data.len() {
+            return None;
+        }
+        let (x, y) = ((index % w) as isize + dx, (index / w) as isize + dy);
+
+        let (x, y) = match self.topology {
+            Topology::Bounded => {
+                if x < 0 || y < 0 || x >= w as isize || y >= h as isize {
+                    return None;
+                }
+                (x as usize, y as usize)
+            }
+            Topology::Torus => (x.rem_euclid(w as isize) as usize, y.rem_euclid(h as isize) as usize),
+        };
+
+        Some(x + y * w)
+    }
+
+    /// Finds the cells to the left, to the right, above and below a cell in that order, leaving out the cells
+    /// past the edges of a bounded field
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    pub fn neighbours(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].into_iter().filter_map(move |(dx, dy)| self.offset(index, dx, dy))
+    }
+
+    /// Borrows the field as a 2D view without copying it
+    pub fn view(&self) -> FieldView<'_, T> {
+        FieldView::new(&self.data, self.size)
+    }
+
+    /// Borrows the field as a mutable 2D view without copying it
+    pub fn view_mut(&mut self) -> FieldViewMut<'_, T> {
+        FieldViewMut::new(&mut self.data, self.size)
+    }
+
+    /// Creates a new field of the same size and topology by mapping every value
+    /// 
+    /// # Parameters
+    /// 
+    /// map: The function applied to every value
+    pub fn map<U, F: FnMut(&T) -> U>(&self, map: F) -> Field<U> {
+        Field { size: self.size, data: self.data.iter().map(map).collect(), topology: self.topology }
+    }
+
+    /// Creates a new field of the same size and topology by combining the values of two fields cell by cell
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The field to combine with
+    /// map: The function combining the values of a cell
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the fields do not have the same size
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, field::Field};
+    /// 
+    /// let light = Field::from_vec("Light", Size::new(2, 1), vec![0.5, 1.0]).unwrap();
+    /// let water = Field::from_vec("Water", Size::new(2, 1), vec![2.0, 0.0]).unwrap();
+    /// 
+    /// assert_eq!(vec![1.0, 0.0], light.zip_map(&water, |light, water| light * water));
+    /// ```
+    pub fn zip_map<U, V, F: FnMut(&T, &U) -> V>(&self, other: &Field<U>, mut map: F) -> Field<V> {
+        assert_eq!(self.size, other.size, "The fields must have the same size");
+
+        Field { size: self.size, data: self.data.iter().zip(&other.data).map(|(a, b)| map(a, b)).collect(), topology: self.topology }
+    }
+
+    /// Makes a rectangle of the board the new board, the cells which were not on the board get the value of the fill
+    pub(crate) fn reframe<F: FnMut() -> T>(&mut self, rect: Rect, fill: F) {
+        self.data = reframe_cells(std::mem::take(&mut self.data), self.size, rect, fill).0;
+        self.size = Size::new(rect.w, rect.h);
+    }
+}
+
+impl<T: Clone> Field<T> {
+    /// Creates a new bounded field with the same value in every cell
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// value: The value of every cell
+    pub fn filled(size: Size, value: T) -> Self {
+        Self { size, data: vec![value; size.len()], topology: Topology::Bounded }
+    }
+
+    /// Creates a new bounded field by copying its values
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the field used in errors
+    /// size: The size of the board
+    /// data: The value of every cell with the rows in order
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if there is not exactly one value for every cell
+    pub fn from_slice(name: &str, size: Size, data: &[T]) -> Result<Self, FieldCreateError> {
+        if data.len() != size.len() {
+            return Err(FieldCreateError::Size { name: name.to_string(), len: data.len(), size });
+        }
+
+        Ok(Self { size, data: data.to_vec(), topology: Topology::Bounded })
+    }
+}
+
+impl Field<f32> {
+    /// Makes sure every value is finite
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the field used in errors
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Value: This will occur if a value is infinite or NaN
+    pub fn check_finite(&self, name: &str) -> Result<(), FieldCreateError> {
+        self.check(name, |value| value.is_finite())
+    }
+
+    /// Makes sure every value is finite and not negative
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the field used in errors
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Value: This will occur if a value is negative, infinite or NaN
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{FieldCreateError, Size}, field::Field};
+    /// 
+    /// let field = Field::from_vec("Light", Size::new(2, 1), vec![0.5, -1.0]).unwrap();
+    /// 
+    /// assert_eq!(Err(FieldCreateError::Value { name: "Light".to_string(), index: 1, value: -1.0 }), field.check_non_negative("Light"));
+    /// ```
+    pub fn check_non_negative(&self, name: &str) -> Result<(), FieldCreateError> {
+        self.check(name, |value| value.is_finite() && value >= 0.0)
+    }
+
+    /// Finds the first value which is not valid
+    fn check<F: Fn(f32) -> bool>(&self, name: &str, valid: F) -> Result<(), FieldCreateError> {
+        match self.data.iter().enumerate().find(|(_, value)| !valid(**value)) {
+            Some((index, &value)) => Err(FieldCreateError::Value { name: name.to_string(), index, value }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the sum of all values
+    pub fn sum(&self) -> f32 {
+        self.data.iter().sum()
+    }
+
+    /// Lets every cell exchange a fraction of the difference to each of its neighbours and writes the result to another field,
+    /// the total is kept on a bounded field as well as on a torus
+    /// 
+    /// # Parameters
+    /// 
+    /// rate: The fraction of the difference exchanged with every neighbour, it should be at most 0.25 to stay stable
+    /// out: The field the result is written to
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the fields do not have the same size
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, field::{Field, Topology}};
+    /// 
+    /// let field = Field::from_vec("Water", Size::new(3, 1), vec![0.0, 1.0, 0.0]).unwrap();
+    /// let mut out = Field::filled(Size::new(3, 1), 0.0);
+    /// field.diffuse_into(0.25, &mut out);
+    /// 
+    /// assert_eq!(vec![0.25, 0.5, 0.25], out);
+    /// ```
+    pub fn diffuse_into(&self, rate: f32, out: &mut Field<f32>) {
+        assert_eq!(self.size, out.size, "The fields must have the same size");
+
+        for index in 0..self.data.len() {
+            let value = self.data[index];
+            let flow = self.neighbours(index).fold(0.0, |flow, neighbour| flow + (self.data[neighbour] - value));
+
+            out.data[index] = value + rate * flow;
+        }
+        out.topology = self.topology;
+    }
+}
+
+impl<T> Deref for Field<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T> DerefMut for Field<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Field<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Field<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter_mut()
+    }
+}
+
+impl<T: PartialEq> PartialEq<Vec<T>> for Field<T> {
+    fn eq(&self, other: &Vec<T>) -> bool {
+        &self.data == other
+    }
+}
+
+impl<T: PartialEq> PartialEq<Field<T>> for Vec<T> {
+    fn eq(&self, other: &Field<T>) -> bool {
+        self == &other.data
+    }
+}
+
+impl<T> HeapSize for Field<T> {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_from_vec_size() {
+        let size = Size::new(2, 2);
+
+        assert_eq!(Err(FieldCreateError::Size { name: "Water".to_string(), len: 3, size }), Field::from_vec("Water", size, vec![0.0; 3]));
+        assert_eq!(Ok(Field::filled(size, 1.0)), Field::from_slice("Water", size, &[1.0; 4]));
+    }
+
+    #[test]
+    fn field_neighbours_topology() {
+        let field = Field::filled(Size::new(3, 3), 0.0);
+        let torus = field.clone().with_topology(Topology::Torus);
+
+        assert_eq!(vec![1, 3], field.neighbours(0).collect::<Vec<_>>());
+        assert_eq!(vec![3, 5, 1, 7], field.neighbours(4).collect::<Vec<_>>());
+        assert_eq!(vec![2, 1, 6, 3], torus.neighbours(0).collect::<Vec<_>>());
+        assert_eq!(0, field.neighbours(9).count());
+    }
+
+    #[test]
+    fn field_diffuse_keeps_total() {
+        for topology in [Topology::Bounded, Topology::Torus] {
+            let field = Field::from_vec("Water", Size::new(4, 3), vec![5.0, 0.0, 1.0, 0.0, 2.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 1.0]).unwrap().with_topology(topology);
+            let mut out = Field::filled(field.size(), 0.0);
+            field.diffuse_into(0.2, &mut out);
+
+            assert!((field.sum() - out.sum()).abs() < 1e-5);
+            assert_eq!(topology, out.topology());
+        }
+    }
+
+    #[test]
+    fn field_check_values() {
+        let field = Field::from_vec("Elevation", Size::new(3, 1), vec![-1.0, f32::INFINITY, 0.0]).unwrap();
+
+        assert_eq!(Err(FieldCreateError::Value { name: "Elevation".to_string(), index: 1, value: f32::INFINITY }), field.check_finite("Elevation"));
+        assert!(matches!(field.check_non_negative("Elevation"), Err(FieldCreateError::Value { index: 0, .. })));
+        assert_eq!(vec![-2.0, f32::INFINITY, 0.0], field.map(|value| value * 2.0));
+    }
+
+    #[test]
+    fn field_reframe() {
+        let mut field = Field::from_vec("Light", Size::new(2, 2), vec![1, 2, 3, 4]).unwrap();
+        field.reframe(Rect::new(1, 0, 2, 2), || 0);
+
+        assert_eq!(Size::new(2, 2), field.size());
+        assert_eq!(vec![2, 0, 4, 0], field);
+    }
+}
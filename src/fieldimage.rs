@@ -0,0 +1,455 @@
+use std::path::Path;
+
+use image::{GrayImage, Luma};
+use thiserror::Error;
+
+use crate::board::{FieldCreateError, Fields, Size};
+use crate::field::Field;
+use crate::occupancy::{OccupancyLayer, OccupancyMap};
+
+/// A field of the board which can be read from and written to a grayscale image
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    /// The relative value of the light
+    Light,
+    /// The height of the terrain
+    Elevation,
+    /// The initial water
+    Water,
+    /// The temperature
+    Temperature,
+}
+
+impl FieldKind {
+    /// Returns the name of the field used in errors
+    fn name(&self) -> &'static str {
+        match self {
+            FieldKind::Light => "Light",
+            FieldKind::Elevation => "Elevation",
+            FieldKind::Water => "Water",
+            FieldKind::Temperature => "Temperature",
+        }
+    }
+}
+
+/// How the brightness of a pixel is spread over the range of values
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Curve {
+    /// The value grows evenly with the brightness
+    #[default]
+    Linear,
+    /// The brightness between 0 and 1 is raised to the power of the gamma before it is mapped to the range,
+    /// a gamma above 1 gives more room to the low values
+    Gamma(f32),
+}
+
+/// Maps the brightness of the pixels of a grayscale image to the values of a field and back,
+/// black is the minimum value and white is the maximum value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueMapping {
+    /// The value of a black pixel
+    pub min: f32,
+    /// The value of a white pixel
+    pub max: f32,
+    /// How the brightness is spread over the range
+    pub curve: Curve,
+}
+
+impl Default for ValueMapping {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 1.0,
+            curve: Curve::Linear,
+        }
+    }
+}
+
+impl ValueMapping {
+    /// Creates a new linear mapping
+    /// 
+    /// # Parameters
+    /// 
+    /// min: The value of a black pixel
+    /// max: The value of a white pixel
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::fieldimage::ValueMapping;
+    /// 
+    /// let mapping = ValueMapping::linear(0.0, 2.0);
+    /// 
+    /// assert_eq!(1.0, mapping.value(0.5));
+    /// ```
+    pub fn linear(min: f32, max: f32) -> Self {
+        Self { min, max, curve: Curve::Linear }
+    }
+
+    /// Creates a new mapping with a gamma curve
+    /// 
+    /// # Parameters
+    /// 
+    /// min: The value of a black pixel
+    /// max: The value of a white pixel
+    /// gamma: The power the brightness is raised to
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::fieldimage::ValueMapping;
+    /// 
+    /// let mapping = ValueMapping::gamma(0.0, 1.0, 2.0);
+    /// 
+    /// assert_eq!(0.25, mapping.value(0.5));
+    /// ```
+    pub fn gamma(min: f32, max: f32, gamma: f32) -> Self {
+        Self { min, max, curve: Curve::Gamma(gamma) }
+    }
+
+    /// Finds the value of a field from a brightness between 0 and 1
+    /// 
+    /// # Parameters
+    /// 
+    /// brightness: The brightness of the pixel, it is clamped between 0 and 1
+    pub fn value(&self, brightness: f32) -> f32 {
+        let brightness = brightness.clamp(0.0, 1.0);
+        let fraction = match self.curve {
+            Curve::Linear => brightness,
+            Curve::Gamma(gamma) => brightness.powf(gamma),
+        };
+
+        self.min + fraction * (self.max - self.min)
+    }
+
+    /// Finds the brightness between 0 and 1 of a value of a field, values outside the range are clamped to it
+    /// 
+    /// # Parameters
+    /// 
+    /// value: The value of the field
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::fieldimage::ValueMapping;
+    /// 
+    /// let mapping = ValueMapping::gamma(0.0, 1.0, 2.0);
+    /// 
+    /// assert_eq!(0.5, mapping.brightness(0.25));
+    /// assert_eq!(1.0, mapping.brightness(5.0));
+    /// ```
+    pub fn brightness(&self, value: f32) -> f32 {
+        let fraction = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0);
+
+        match self.curve {
+            Curve::Linear => fraction,
+            Curve::Gamma(gamma) => fraction.powf(1.0 / gamma),
+        }
+    }
+
+    /// Makes sure the mapping can be used in both directions
+    fn validate(&self) -> Result<(), FieldImageError> {
+        if !self.min.is_finite() || !self.max.is_finite() || self.min == self.max {
+            return Err(FieldImageError::Range { min: self.min, max: self.max });
+        }
+        if let Curve::Gamma(gamma) = self.curve {
+            if !gamma.is_finite() || gamma <= 0.0 {
+                return Err(FieldImageError::Gamma { gamma });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Fields {
+    /// Creates a new set of fields with the size of a grayscale image where one field is read from the image,
+    /// the other fields are 0 everywhere. Color images are converted to grayscale
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the image
+    /// kind: The field to read from the image
+    /// mapping: How the brightness of the pixels becomes the values of the field
+    /// 
+    /// # Errors
+    /// 
+    /// FieldImageError::Range: This will occur if the range of the mapping is empty or not finite
+    /// 
+    /// FieldImageError::Gamma: This will occur if the gamma of the mapping is not positive
+    /// 
+    /// FieldImageError::Image: This will occur if the image could not be read
+    /// 
+    /// FieldImageError::Field: This will occur if the mapping gives light which is negative
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::{board::Fields, fieldimage::{FieldKind, ValueMapping}};
+    /// 
+    /// let fields = Fields::from_image("light.png", FieldKind::Light, ValueMapping::default()).unwrap()
+    ///     .with_image("water.png", FieldKind::Water, ValueMapping::linear(0.0, 5.0)).unwrap();
+    /// ```
+    pub fn from_image<P: AsRef<Path>>(path: P, kind: FieldKind, mapping: ValueMapping) -> Result<Self, FieldImageError> {
+        mapping.validate()?;
+        let image = image::open(path)?.into_luma8();
+        let size = Size::new(image.width() as usize, image.height() as usize);
+
+        Fields::new(size, &vec![0.0; size.len()])?.with_gray(&image, kind, mapping)
+    }
+
+    /// Sets one field from a grayscale image with the same size as the fields, color images are converted to grayscale
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the image
+    /// kind: The field to read from the image
+    /// mapping: How the brightness of the pixels becomes the values of the field
+    /// 
+    /// # Errors
+    /// 
+    /// FieldImageError::Range: This will occur if the range of the mapping is empty or not finite
+    /// 
+    /// FieldImageError::Gamma: This will occur if the gamma of the mapping is not positive
+    /// 
+    /// FieldImageError::Image: This will occur if the image could not be read
+    /// 
+    /// FieldImageError::Field: This will occur if the image does not have the size of the fields or if the mapping gives light which is negative
+    pub fn with_image<P: AsRef<Path>>(self, path: P, kind: FieldKind, mapping: ValueMapping) -> Result<Self, FieldImageError> {
+        mapping.validate()?;
+        let image = image::open(path)?.into_luma8();
+
+        self.with_gray(&image, kind, mapping)
+    }
+
+    /// Writes one field to a grayscale png image with one pixel for every cell
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the image
+    /// kind: The field to write
+    /// mapping: How the values of the field become the brightness of the pixels, values outside the range are clamped to it
+    /// 
+    /// # Errors
+    /// 
+    /// FieldImageError::Range: This will occur if the range of the mapping is empty or not finite
+    /// 
+    /// FieldImageError::Gamma: This will occur if the gamma of the mapping is not positive
+    /// 
+    /// FieldImageError::Image: This will occur if the image could not be written
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::{board::{Fields, Size}, fieldimage::{FieldKind, ValueMapping}};
+    /// 
+    /// let fields = Fields::new(Size::new(2, 1), &[0.0, 1.0]).unwrap();
+    /// fields.to_image("light.png", FieldKind::Light, ValueMapping::default()).unwrap();
+    /// ```
+    pub fn to_image<P: AsRef<Path>>(&self, path: P, kind: FieldKind, mapping: ValueMapping) -> Result<(), FieldImageError> {
+        mapping.validate()?;
+        self.to_gray(kind, mapping).save_with_format(path, image::ImageFormat::Png)?;
+
+        Ok(())
+    }
+
+    /// Sets one field from the pixels of a grayscale image
+    fn with_gray(mut self, image: &GrayImage, kind: FieldKind, mapping: ValueMapping) -> Result<Self, FieldImageError> {
+        let (w, h) = self.size.size();
+        let len = image.width() as usize * image.height() as usize;
+        if (image.width() as usize, image.height() as usize) != (w, h) {
+            return Err(FieldCreateError::Size { name: kind.name().to_string(), len, size: self.size }.into());
+        }
+
+        let values = Field::from_vec(kind.name(), self.size, image.pixels().map(|Luma([pixel])| mapping.value(*pixel as f32 / 255.0)).collect())?;
+        if kind == FieldKind::Light {
+            values.check_non_negative(kind.name())?;
+        }
+
+        *self.field_mut(kind) = values;
+
+        Ok(self)
+    }
+
+    /// Draws one field as a grayscale image with one pixel for every cell
+    fn to_gray(&self, kind: FieldKind, mapping: ValueMapping) -> GrayImage {
+        let values = match kind {
+            FieldKind::Light => &self.light,
+            FieldKind::Elevation => &self.elevation,
+            FieldKind::Water => &self.water,
+            FieldKind::Temperature => &self.temperature,
+        };
+
+        gray(values, self.size, mapping)
+    }
+
+    /// Gets a field mutably
+    fn field_mut(&mut self, kind: FieldKind) -> &mut Field {
+        match kind {
+            FieldKind::Light => &mut self.light,
+            FieldKind::Elevation => &mut self.elevation,
+            FieldKind::Water => &mut self.water,
+            FieldKind::Temperature => &mut self.temperature,
+        }
+    }
+}
+
+impl OccupancyMap {
+    /// Writes a layer of the occupancy map to a grayscale PNG image with one pixel for every cell, such that the heat map
+    /// can be compared with the fields of the board
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the image
+    /// layer: The layer to write
+    /// mapping: How the values of the layer are mapped to the brightness of the pixels
+    /// 
+    /// # Errors
+    /// 
+    /// FieldImageError::Range: This will occur if the range of the mapping is empty or not finite
+    /// 
+    /// FieldImageError::Gamma: This will occur if the gamma of the mapping is not positive
+    /// 
+    /// FieldImageError::Image: This will occur if the image could not be written
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::{board::Size, fieldimage::ValueMapping, occupancy::{OccupancyLayer, OccupancyMap}};
+    /// 
+    /// let map = OccupancyMap::new(Size::new(2, 1));
+    /// map.to_image("mortality.png", OccupancyLayer::Mortality, ValueMapping::linear(0.0, 0.1)).unwrap();
+    /// ```
+    pub fn to_image<P: AsRef<Path>>(&self, path: P, layer: OccupancyLayer, mapping: ValueMapping) -> Result<(), FieldImageError> {
+        mapping.validate()?;
+        gray(&self.layer(layer), self.size(), mapping).save_with_format(path, image::ImageFormat::Png)?;
+
+        Ok(())
+    }
+}
+
+/// Draws values as a grayscale image with one pixel for every cell
+fn gray(values: &[f32], size: Size, mapping: ValueMapping) -> GrayImage {
+    let (w, h) = size.size();
+    let pixels = values.iter().map(|value| (mapping.brightness(*value) * 255.0).round() as u8).collect();
+
+    GrayImage::from_raw(w as u32, h as u32, pixels).unwrap()
+}
+
+#[derive(Error, Debug)]
+pub enum FieldImageError {
+    #[error("The range from {:?} to {:?} cannot be mapped to pixels", min, max)]
+    Range {
+        min: f32,
+        max: f32,
+    },
+    #[error("The gamma {:?} is not positive", gamma)]
+    Gamma {
+        gamma: f32,
+    },
+    #[error("Unable to read or write the image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error(transparent)]
+    Field(#[from] FieldCreateError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::board::Coord;
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("evolution_plants_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn value_mapping_linear() {
+        let mapping = ValueMapping::linear(-10.0, 30.0);
+
+        assert_eq!(-10.0, mapping.value(0.0));
+        assert_eq!(30.0, mapping.value(1.0));
+        assert_eq!(10.0, mapping.value(0.5));
+        assert_eq!(0.25, mapping.brightness(0.0));
+        assert_eq!(0.0, mapping.brightness(-20.0));
+    }
+
+    #[test]
+    fn value_mapping_gamma() {
+        let mapping = ValueMapping::gamma(0.0, 4.0, 0.5);
+
+        assert_eq!(2.0, mapping.value(0.25));
+        assert_eq!(0.25, mapping.brightness(2.0));
+    }
+
+    #[test]
+    fn value_mapping_validate() {
+        assert!(matches!(ValueMapping::linear(1.0, 1.0).validate(), Err(FieldImageError::Range { .. })));
+        assert!(matches!(ValueMapping::linear(0.0, f32::NAN).validate(), Err(FieldImageError::Range { .. })));
+        assert!(matches!(ValueMapping::gamma(0.0, 1.0, 0.0).validate(), Err(FieldImageError::Gamma { .. })));
+        assert!(ValueMapping::linear(1.0, 0.0).validate().is_ok());
+    }
+
+    #[test]
+    fn fields_gray_round_trip() {
+        let fields = Fields::new(Size::new(3, 1), &[0.0, 0.5, 1.0]).unwrap();
+        let mapping = ValueMapping::gamma(0.0, 1.0, 2.2);
+        let image = fields.to_gray(FieldKind::Light, mapping);
+        let read = Fields::new(Size::new(3, 1), &[0.0; 3]).unwrap().with_gray(&image, FieldKind::Water, mapping).unwrap();
+
+        assert_eq!(0, image.get_pixel(0, 0)[0]);
+        assert_eq!(255, image.get_pixel(2, 0)[0]);
+        for (expected, value) in fields.light.iter().zip(&read.water) {
+            assert!((expected - value).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn fields_with_gray_error() {
+        let image = GrayImage::new(2, 2);
+        let fields = Fields::new(Size::new(3, 1), &[0.0; 3]).unwrap();
+        let result = fields.clone().with_gray(&image, FieldKind::Elevation, ValueMapping::default());
+
+        assert!(matches!(result, Err(FieldImageError::Field(FieldCreateError::Size { len: 4, .. }))));
+
+        let image = GrayImage::new(3, 1);
+        let result = fields.with_gray(&image, FieldKind::Light, ValueMapping::linear(-1.0, 1.0));
+
+        assert!(matches!(result, Err(FieldImageError::Field(FieldCreateError::Value { index: 0, .. }))));
+    }
+
+    #[test]
+    fn fields_image_files() {
+        let path = temp_path("field.png");
+        let fields = Fields::new(Size::new(2, 2), &[1.0; 4]).unwrap().with_temperature(&[0.0, 10.0, 20.0, 40.0]).unwrap();
+        let mapping = ValueMapping::linear(0.0, 40.0);
+        fields.to_image(&path, FieldKind::Temperature, mapping).unwrap();
+        let read = Fields::from_image(&path, FieldKind::Temperature, mapping).unwrap()
+            .with_image(&path, FieldKind::Light, ValueMapping::linear(0.0, 40.0))
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(Size::new(2, 2), read.size);
+        assert_eq!(vec![0.0, 10.0, 20.0, 40.0], read.temperature.iter().map(|value| value.round()).collect::<Vec<_>>());
+        assert_eq!(read.temperature, read.light);
+        assert_eq!(vec![0.0; 4], read.water);
+    }
+
+    #[test]
+    fn occupancy_image_file() {
+        let path = temp_path("occupancy.png");
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+        let mut map = OccupancyMap::new(size);
+        map.record_step(&population);
+        map.to_image(&path, OccupancyLayer::Occupancy, ValueMapping::default()).unwrap();
+        let read = Fields::from_image(&path, FieldKind::Light, ValueMapping::default()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(vec![0.0, 1.0], read.light);
+        assert!(matches!(map.to_image(&path, OccupancyLayer::Mortality, ValueMapping::linear(0.0, 0.0)), Err(FieldImageError::Range { .. })));
+    }
+}
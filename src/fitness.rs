@@ -0,0 +1,212 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::memory::{deque_bytes, hash_map_bytes, HeapSize};
+use crate::population::PlantId;
+use crate::species::SpeciesId;
+
+/// The settings for counting the offspring born to every plant and species over the latest steps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitnessConfig {
+    /// The number of steps births are counted for
+    pub window: u64,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        Self {
+            window: 100,
+        }
+    }
+}
+
+/// A single birth counted by the fitness tracker
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Birth {
+    /// The tick the seed germinated
+    tick: u64,
+    /// The plant which produced the seed
+    parent: PlantId,
+    /// The species of the parent, None if species are not tracked or the parent has not been clustered yet
+    species: Option<SpeciesId>,
+}
+
+/// Counts the seeds which germinated over a sliding window of steps, attributed to the plant which produced them
+/// and to the species of that plant. Reproductive success is the fitness selection acts on, so the plants and species
+/// with the most recent offspring are the ones currently winning
+#[derive(Clone, Debug, PartialEq)]
+pub struct FitnessTracker {
+    /// The settings of the tracker
+    config: FitnessConfig,
+    /// All births inside the window, oldest first
+    births: VecDeque<Birth>,
+    /// The number of births inside the window for every plant with at least one
+    by_plant: HashMap<PlantId, usize>,
+    /// The number of births inside the window for every species with at least one
+    by_species: HashMap<SpeciesId, usize>,
+}
+
+impl FitnessTracker {
+    /// Creates a new tracker without any births
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the tracker
+    pub fn new(config: FitnessConfig) -> Self {
+        Self { config, births: VecDeque::new(), by_plant: HashMap::new(), by_species: HashMap::new() }
+    }
+
+    /// Returns the settings of the tracker
+    pub fn config(&self) -> FitnessConfig {
+        self.config
+    }
+
+    /// Returns the number of births inside the window
+    pub fn births(&self) -> usize {
+        self.births.len()
+    }
+
+    /// Returns the number of seeds produced by a plant which germinated inside the window
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn offspring(&self, id: PlantId) -> usize {
+        self.by_plant.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Returns the number of seeds produced by the plants of a species which germinated inside the window
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the species
+    pub fn species_offspring(&self, id: SpeciesId) -> usize {
+        self.by_species.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Finds the plants with offspring inside the window, most offspring first and ties by lowest id
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::fitness::{FitnessConfig, FitnessTracker};
+    /// use evolution_plants::population::PlantId;
+    /// 
+    /// let mut tracker = FitnessTracker::new(FitnessConfig { window: 10 });
+    /// tracker.record(1, PlantId(4), None);
+    /// tracker.record(2, PlantId(7), None);
+    /// tracker.record(3, PlantId(7), None);
+    /// 
+    /// assert_eq!(vec![(PlantId(7), 2), (PlantId(4), 1)], tracker.ranking());
+    /// 
+    /// // Once the window has moved past the first birth it no longer counts
+    /// tracker.advance(11);
+    /// 
+    /// assert_eq!(vec![(PlantId(7), 2)], tracker.ranking());
+    /// ```
+    pub fn ranking(&self) -> Vec<(PlantId, usize)> {
+        let mut ranking: Vec<(PlantId, usize)> = self.by_plant.iter().map(|(&id, &count)| (id, count)).collect();
+        ranking.sort_by_key(|&(id, count)| (std::cmp::Reverse(count), id));
+
+        ranking
+    }
+
+    /// Finds the species with offspring inside the window, most offspring first and ties by lowest id
+    pub fn species_ranking(&self) -> Vec<(SpeciesId, usize)> {
+        let mut ranking: Vec<(SpeciesId, usize)> = self.by_species.iter().map(|(&id, &count)| (id, count)).collect();
+        ranking.sort_by_key(|&(id, count)| (std::cmp::Reverse(count), id));
+
+        ranking
+    }
+
+    /// Returns the plant with the most offspring inside the window and the number of offspring,
+    /// None if there were no births inside the window
+    pub fn fittest(&self) -> Option<(PlantId, usize)> {
+        self.by_plant.iter()
+            .map(|(&id, &count)| (id, count))
+            .min_by_key(|&(id, count)| (std::cmp::Reverse(count), id))
+    }
+
+    /// Counts a seed which germinated
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick the seed germinated
+    /// parent: The plant which produced the seed
+    /// species: The species of the parent
+    pub fn record(&mut self, tick: u64, parent: PlantId, species: Option<SpeciesId>) {
+        self.births.push_back(Birth { tick, parent, species });
+        *self.by_plant.entry(parent).or_insert(0) += 1;
+        if let Some(species) = species {
+            *self.by_species.entry(species).or_insert(0) += 1;
+        }
+    }
+
+    /// Moves the window such that it ends at a tick, the births from before the window no longer count
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The latest tick inside the window
+    pub fn advance(&mut self, tick: u64) {
+        let start = (tick + 1).saturating_sub(self.config.window);
+
+        while let Some(birth) = self.births.front().filter(|birth| birth.tick < start).copied() {
+            self.births.pop_front();
+            decrement(&mut self.by_plant, birth.parent);
+            if let Some(species) = birth.species {
+                decrement(&mut self.by_species, species);
+            }
+        }
+    }
+}
+
+/// Lowers a count by one and removes it once it reaches 0
+fn decrement<K: std::hash::Hash + Eq>(counts: &mut HashMap<K, usize>, key: K) {
+    if let Some(count) = counts.get_mut(&key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&key);
+        }
+    }
+}
+
+impl HeapSize for FitnessTracker {
+    fn heap_bytes(&self) -> usize {
+        deque_bytes(&self.births) + hash_map_bytes(&self.by_plant) + hash_map_bytes(&self.by_species)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fitness_tracker_species() {
+        let mut tracker = FitnessTracker::new(FitnessConfig { window: 2 });
+        tracker.record(1, PlantId(0), Some(SpeciesId(3)));
+        tracker.record(2, PlantId(1), Some(SpeciesId(3)));
+        tracker.record(2, PlantId(2), Some(SpeciesId(5)));
+        tracker.record(2, PlantId(2), None);
+
+        assert_eq!(vec![(SpeciesId(3), 2), (SpeciesId(5), 1)], tracker.species_ranking());
+
+        tracker.advance(2);
+
+        assert_eq!(vec![(SpeciesId(3), 2), (SpeciesId(5), 1)], tracker.species_ranking());
+
+        tracker.advance(3);
+
+        assert_eq!(1, tracker.species_offspring(SpeciesId(3)));
+        assert_eq!(3, tracker.births());
+    }
+
+    #[test]
+    fn fitness_tracker_advance_empty() {
+        let mut tracker = FitnessTracker::new(FitnessConfig { window: 1 });
+        tracker.record(0, PlantId(9), None);
+        tracker.advance(1);
+
+        assert_eq!(0, tracker.offspring(PlantId(9)));
+        assert_eq!(None, tracker.fittest());
+        assert!(tracker.by_plant.is_empty());
+    }
+}
@@ -0,0 +1,479 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::genome::{self, Genome, GenomeCreateError};
+use crate::spectrum::CHANNELS;
+
+/// The prefix of the named text format of a genome, it names the version of the format
+pub const NAMED_PREFIX: &str = "n1:";
+/// The start of the name of a gene missing from the registry, followed by its index
+pub const UNNAMED_PREFIX: &str = "gene_";
+
+/// The description of a single gene
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneInfo {
+    /// The name of the gene used in the text format and shown in the interface, lower case with underscores
+    pub name: String,
+    /// The index of the gene in the genome
+    pub index: usize,
+    /// The lowest value the gene is allowed to have, at least 0
+    pub min: f32,
+    /// The highest value the gene is allowed to have, at most 1
+    pub max: f32,
+    /// A short description of what the gene does to the plant
+    pub effect: String,
+}
+
+impl GeneInfo {
+    /// Creates the description of a gene which may take any value between 0 and 1
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the gene
+    /// index: The index of the gene in the genome
+    /// effect: A short description of what the gene does
+    pub fn new(name: &str, index: usize, effect: &str) -> Self {
+        Self { name: name.to_string(), index, min: 0.0, max: 1.0, effect: effect.to_string() }
+    }
+}
+
+/// Describes the genes of the genomes by name such that genomes can be shown, written and bounded without knowing
+/// the index of every gene. Genes missing from the registry are named UNNAMED_PREFIX followed by their index
+/// and may take any value between 0 and 1. The default registry describes every gene of the genome module
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneRegistry {
+    /// The description of every registered gene ordered by index
+    genes: Vec<GeneInfo>,
+    /// The position in genes of every name
+    names: HashMap<String, usize>,
+}
+
+impl GeneRegistry {
+    /// Creates a registry without any genes
+    pub fn new() -> Self {
+        Self { genes: Vec::new(), names: HashMap::new() }
+    }
+
+    /// Adds a gene to the registry
+    /// 
+    /// # Parameters
+    /// 
+    /// info: The description of the gene
+    /// 
+    /// # Errors
+    /// 
+    /// GeneRegistryError::Name: This will occur if the name is empty, already used or starts with UNNAMED_PREFIX
+    /// 
+    /// GeneRegistryError::Index: This will occur if another gene has the same index
+    /// 
+    /// GeneRegistryError::Range: This will occur if the range is empty or not between 0 and 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genes::{GeneInfo, GeneRegistry};
+    /// 
+    /// let mut registry = GeneRegistry::new();
+    /// registry.register(GeneInfo::new("threshold", 0, "Energy stored before reproducing")).unwrap();
+    /// 
+    /// assert_eq!(Some(0), registry.index_of("threshold"));
+    /// assert!(registry.register(GeneInfo::new("other", 0, "")).is_err());
+    /// ```
+    pub fn register(&mut self, info: GeneInfo) -> Result<(), GeneRegistryError> {
+        if info.name.is_empty() || info.name.starts_with(UNNAMED_PREFIX) || self.names.contains_key(&info.name) {
+            return Err(GeneRegistryError::Name { name: info.name });
+        }
+        check_range(&info.name, info.min, info.max)?;
+        let position = match self.genes.binary_search_by_key(&info.index, |gene| gene.index) {
+            Ok(_) => return Err(GeneRegistryError::Index { index: info.index }),
+            Err(position) => position,
+        };
+
+        self.genes.insert(position, info);
+        self.names = self.genes.iter().enumerate().map(|(position, gene)| (gene.name.clone(), position)).collect();
+
+        Ok(())
+    }
+
+    /// Narrows the values a gene is allowed to have, typically from the settings of a run
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the gene
+    /// min: The lowest allowed value
+    /// max: The highest allowed value
+    /// 
+    /// # Errors
+    /// 
+    /// GeneRegistryError::Unknown: This will occur if no gene has the name
+    /// 
+    /// GeneRegistryError::Range: This will occur if the range is empty or not between 0 and 1
+    pub fn set_range(&mut self, name: &str, min: f32, max: f32) -> Result<(), GeneRegistryError> {
+        let &position = self.names.get(name).ok_or_else(|| GeneRegistryError::Unknown { name: name.to_string() })?;
+        check_range(name, min, max)?;
+
+        let gene = &mut self.genes[position];
+        gene.min = min;
+        gene.max = max;
+
+        Ok(())
+    }
+
+    /// Returns the description of every registered gene ordered by index
+    pub fn genes(&self) -> &[GeneInfo] {
+        &self.genes
+    }
+
+    /// Returns the description of the gene at an index, None if it is not registered
+    pub fn get(&self, index: usize) -> Option<&GeneInfo> {
+        self.genes.binary_search_by_key(&index, |gene| gene.index).ok().map(|position| &self.genes[position])
+    }
+
+    /// Finds the index of a gene from its name, names of unregistered genes made by name are also understood
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the gene
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        match self.names.get(name) {
+            Some(&position) => Some(self.genes[position].index),
+            None => name.strip_prefix(UNNAMED_PREFIX).and_then(|index| index.parse().ok()).filter(|&index| self.get(index).is_none()),
+        }
+    }
+
+    /// Finds the name of the gene at an index
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the gene
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genes::GeneRegistry;
+    /// use evolution_plants::genome::GENE_SEED_ENERGY;
+    /// 
+    /// let registry = GeneRegistry::default();
+    /// 
+    /// assert_eq!("seed_energy", registry.name(GENE_SEED_ENERGY));
+    /// assert_eq!("gene_40", registry.name(40));
+    /// ```
+    pub fn name(&self, index: usize) -> String {
+        match self.get(index) {
+            Some(gene) => gene.name.clone(),
+            None => format!("{}{}", UNNAMED_PREFIX, index),
+        }
+    }
+
+    /// Returns the allowed values of the gene at an index as the lowest and the highest value
+    pub fn range(&self, index: usize) -> (f32, f32) {
+        self.get(index).map_or((0.0, 1.0), |gene| (gene.min, gene.max))
+    }
+
+    /// Moves every gene of a genome into its allowed range, returns true if any gene was changed.
+    /// The genome is only copied if a gene is outside its range
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to bound
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genes::GeneRegistry;
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let mut registry = GeneRegistry::default();
+    /// registry.set_range("seed_energy", 0.25, 0.75).unwrap();
+    /// let mut genome = Genome::new(&[0.5, 0.9]).unwrap();
+    /// 
+    /// assert!(registry.clamp(&mut genome));
+    /// assert_eq!(&[0.5, 0.75], genome.genes());
+    /// assert!(!registry.clamp(&mut genome));
+    /// ```
+    pub fn clamp(&self, genome: &mut Genome) -> bool {
+        let outside = |(index, &gene): (usize, &f32)| {
+            let (min, max) = self.range(index);
+            gene < min || gene > max
+        };
+        if !genome.genes().iter().enumerate().any(outside) {
+            return false;
+        }
+
+        let genes: Vec<f32> = genome.genes().iter()
+            .enumerate()
+            .map(|(index, &gene)| {
+                let (min, max) = self.range(index);
+                gene.clamp(min, max)
+            })
+            .collect();
+        *genome = Genome::new(&genes).expect("The genes are kept inside ranges between 0 and 1 of a valid genome");
+
+        true
+    }
+
+    /// Writes a genome as text with every gene named, which can be read back with parse. The text is NAMED_PREFIX
+    /// followed by pairs of a name and a value separated by commas in the order of the genes
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to write
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genes::GeneRegistry;
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let registry = GeneRegistry::default();
+    /// let genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// 
+    /// assert_eq!("n1:reproduction_threshold=0.5,seed_energy=0.25", registry.to_named_string(&genome));
+    /// assert_eq!(genome, registry.parse(&registry.to_named_string(&genome)).unwrap());
+    /// ```
+    pub fn to_named_string(&self, genome: &Genome) -> String {
+        let genes: Vec<String> = genome.genes().iter()
+            .enumerate()
+            .map(|(index, gene)| format!("{}={}", self.name(index), gene))
+            .collect();
+
+        format!("{}{}", NAMED_PREFIX, genes.join(","))
+    }
+
+    /// Reads a genome written with to_named_string, the genes may be given in any order but every gene
+    /// up to the highest index given must be there. The unnamed text format of the genome module is also read.
+    /// Whitespace around the text, the names and the values is ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// text: The text to read
+    /// 
+    /// # Errors
+    /// 
+    /// GeneParseError::Genome: This will occur if the text is in the unnamed format and could not be read
+    /// 
+    /// GeneParseError::Prefix: This will occur if the text starts with neither NAMED_PREFIX nor genome::TEXT_PREFIX
+    /// 
+    /// GeneParseError::Pair: This will occur if a gene is not a name and a value separated by =
+    /// 
+    /// GeneParseError::Unknown: This will occur if no gene has a name
+    /// 
+    /// GeneParseError::Value: This will occur if a value is not a number
+    /// 
+    /// GeneParseError::Duplicate: This will occur if a gene is given more than once
+    /// 
+    /// GeneParseError::Missing: This will occur if a gene is left out
+    /// 
+    /// GeneParseError::Create: This will occur if there are too few genes or a gene is not between 0 and 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genes::{GeneParseError, GeneRegistry};
+    /// 
+    /// let registry = GeneRegistry::default();
+    /// 
+    /// assert_eq!(&[0.5, 0.25], registry.parse(" n1: seed_energy = 0.25, reproduction_threshold = 0.5 ").unwrap().genes());
+    /// assert_eq!(&[0.5, 0.25], registry.parse("g1:0.5,0.25").unwrap().genes());
+    /// assert_eq!(Err(GeneParseError::Missing { index: 1 }), registry.parse("n1:reproduction_threshold=0.5,gene_40=1"));
+    /// ```
+    pub fn parse(&self, text: &str) -> Result<Genome, GeneParseError> {
+        let text = text.trim();
+        if text.starts_with(genome::TEXT_PREFIX) {
+            return Ok(text.parse::<Genome>()?);
+        }
+        let Some(pairs) = text.strip_prefix(NAMED_PREFIX) else {
+            return Err(GeneParseError::Prefix { text: text.chars().take(NAMED_PREFIX.len()).collect() });
+        };
+
+        let mut genes: Vec<Option<f32>> = Vec::new();
+        for pair in pairs.split(',') {
+            let Some((name, value)) = pair.split_once('=') else {
+                return Err(GeneParseError::Pair { text: pair.trim().to_string() });
+            };
+            let (name, value) = (name.trim(), value.trim());
+            let index = self.index_of(name).ok_or_else(|| GeneParseError::Unknown { name: name.to_string() })?;
+            let value = value.parse::<f32>().map_err(|_| GeneParseError::Value { name: name.to_string(), text: value.to_string() })?;
+
+            if genes.len() <= index {
+                genes.resize(index + 1, None);
+            }
+            if genes[index].replace(value).is_some() {
+                return Err(GeneParseError::Duplicate { name: name.to_string() });
+            }
+        }
+
+        let genes = genes.into_iter()
+            .enumerate()
+            .map(|(index, gene)| gene.ok_or(GeneParseError::Missing { index }))
+            .collect::<Result<Vec<f32>, GeneParseError>>()?;
+
+        Ok(Genome::new(&genes)?)
+    }
+}
+
+impl Default for GeneRegistry {
+    fn default() -> Self {
+        let mut genes = vec![
+            GeneInfo::new("reproduction_threshold", genome::GENE_REPRODUCTION_THRESHOLD, "Energy stored before reproducing"),
+            GeneInfo::new("seed_energy", genome::GENE_SEED_ENERGY, "Fraction of the stored energy given to a seed"),
+            GeneInfo::new("thermal_optimum", genome::GENE_THERMAL_OPTIMUM, "Temperature the plant grows best at"),
+            GeneInfo::new("thermal_tolerance", genome::GENE_THERMAL_TOLERANCE, "Distance from the optimum temperature grown at without a penalty"),
+            GeneInfo::new("lifespan", genome::GENE_LIFESPAN, "Steps lived before ageing"),
+            GeneInfo::new("resistance", genome::GENE_RESISTANCE, "Resistance to the pathogen"),
+            GeneInfo::new("height", genome::GENE_HEIGHT, "Height grown above the ground"),
+            GeneInfo::new("roots", genome::GENE_ROOTS, "Investment in the roots and how far they reach"),
+            GeneInfo::new("flowering", genome::GENE_FLOWERING, "Investment in flowering to spread pollen"),
+            GeneInfo::new("clutch", genome::GENE_CLUTCH, "Number of seeds the seed energy is split between"),
+            GeneInfo::new("toxin", genome::GENE_TOXIN, "Toxin released into the neighbouring cells"),
+            GeneInfo::new("mycorrhiza", genome::GENE_MYCORRHIZA, "Energy shared through the underground network"),
+            GeneInfo::new("mutation_rate", genome::GENE_MUTATION_RATE, "Mutation rate when the rate can evolve"),
+        ];
+        genes.extend((0..CHANNELS).map(|channel| {
+            GeneInfo::new(&format!("absorption_{}", channel), genome::GENE_ABSORPTION + channel, "Pigment spent on a light channel")
+        }));
+        genes.push(GeneInfo::new("light_cue", genome::GENE_LIGHT_CUE, "Light a seed waits for before germinating"));
+        genes.push(GeneInfo::new("water_cue", genome::GENE_WATER_CUE, "Water a seed waits for before germinating"));
+
+        let mut registry = Self::new();
+        for gene in genes {
+            registry.register(gene).expect("The built in genes have distinct names and indices");
+        }
+
+        registry
+    }
+}
+
+/// Makes sure a range of gene values is inside 0 to 1 and not empty
+fn check_range(name: &str, min: f32, max: f32) -> Result<(), GeneRegistryError> {
+    if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) || min > max {
+        return Err(GeneRegistryError::Range { name: name.to_string(), min, max });
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum GeneRegistryError {
+    #[error("The gene name ({:?}) is empty, already used or reserved for unregistered genes", name)]
+    Name {
+        name: String,
+    },
+    #[error("A gene with the index {:?} is already registered", index)]
+    Index {
+        index: usize,
+    },
+    #[error("The range ({:?} to {:?}) of gene ({:?}) should be inside 0 to 1 and not empty", min, max, name)]
+    Range {
+        name: String,
+        min: f32,
+        max: f32,
+    },
+    #[error("There is no gene named ({:?})", name)]
+    Unknown {
+        name: String,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum GeneParseError {
+    #[error("Named genome text starts with ({:?}) but should start with ({:?})", text, NAMED_PREFIX)]
+    Prefix {
+        text: String,
+    },
+    #[error("Gene ({:?}) should be a name and a value separated by =", text)]
+    Pair {
+        text: String,
+    },
+    #[error("There is no gene named ({:?})", name)]
+    Unknown {
+        name: String,
+    },
+    #[error("Gene ({:?}) is ({:?}) which is not a number", name, text)]
+    Value {
+        name: String,
+        text: String,
+    },
+    #[error("Gene ({:?}) is given more than once", name)]
+    Duplicate {
+        name: String,
+    },
+    #[error("Gene {:?} is missing", index)]
+    Missing {
+        index: usize,
+    },
+    #[error(transparent)]
+    Genome(#[from] genome::GenomeParseError),
+    #[error(transparent)]
+    Create(#[from] GenomeCreateError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_default() {
+        let registry = GeneRegistry::default();
+
+        assert_eq!(genome::GENE_WATER_CUE + 1, registry.genes().len());
+        assert!(registry.genes().iter().enumerate().all(|(index, gene)| gene.index == index && !gene.effect.is_empty()));
+        assert_eq!(Some(genome::GENE_ABSORPTION + 2), registry.index_of("absorption_2"));
+        assert_eq!(Some(40), registry.index_of("gene_40"));
+        assert_eq!(None, registry.index_of("gene_1"));
+        assert_eq!(None, registry.index_of("leaves"));
+    }
+
+    #[test]
+    fn registry_register_errors() {
+        let mut registry = GeneRegistry::default();
+
+        assert_eq!(Err(GeneRegistryError::Name { name: "roots".to_string() }), registry.register(GeneInfo::new("roots", 30, "")));
+        assert_eq!(Err(GeneRegistryError::Name { name: "gene_30".to_string() }), registry.register(GeneInfo::new("gene_30", 30, "")));
+        assert_eq!(Err(GeneRegistryError::Index { index: 2 }), registry.register(GeneInfo::new("leaves", 2, "")));
+        assert_eq!(Err(GeneRegistryError::Range { name: "roots".to_string(), min: 0.5, max: 0.25 }), registry.set_range("roots", 0.5, 0.25));
+        assert_eq!(Err(GeneRegistryError::Unknown { name: "leaves".to_string() }), registry.set_range("leaves", 0.0, 1.0));
+
+        // A gene registered out of order is found by index and name
+        registry.register(GeneInfo::new("leaves", 30, "Leaf area")).unwrap();
+        registry.register(GeneInfo::new("bark", 20, "Bark thickness")).unwrap();
+        assert_eq!("leaves", registry.name(30));
+        assert_eq!(Some(20), registry.index_of("bark"));
+        assert_eq!(Some(30), registry.index_of("leaves"));
+    }
+
+    #[test]
+    fn registry_named_round_trip() {
+        let registry = GeneRegistry::default();
+        let genes: Vec<f32> = (0..20).map(|index| index as f32 / 19.0).collect();
+        let genome = Genome::new(&genes).unwrap();
+        let text = registry.to_named_string(&genome);
+
+        assert!(text.contains("mutation_rate=") && text.contains("gene_19="));
+        assert_eq!(genome, registry.parse(&text).unwrap());
+    }
+
+    #[test]
+    fn registry_parse_errors() {
+        let registry = GeneRegistry::default();
+
+        assert_eq!(Err(GeneParseError::Prefix { text: "x1:".to_string() }), registry.parse("x1:roots=1"));
+        assert_eq!(Err(GeneParseError::Pair { text: "seed_energy".to_string() }), registry.parse("n1:reproduction_threshold=0.5, seed_energy"));
+        assert_eq!(Err(GeneParseError::Unknown { name: "leaves".to_string() }), registry.parse("n1:leaves=0.5"));
+        assert_eq!(Err(GeneParseError::Value { name: "roots".to_string(), text: "x".to_string() }), registry.parse("n1:roots=x"));
+        assert_eq!(Err(GeneParseError::Duplicate { name: "seed_energy".to_string() }), registry.parse("n1:seed_energy=0.5,seed_energy=0.5"));
+        assert!(matches!(registry.parse("n1:reproduction_threshold=0.5,seed_energy=2"), Err(GeneParseError::Create(_))));
+        assert!(matches!(registry.parse("g1:0.5"), Err(GeneParseError::Genome(_))));
+    }
+
+    #[test]
+    fn registry_clamp_keeps_shared_genes() {
+        let registry = GeneRegistry::default();
+        let genome = Genome::new(&[0.5, 0.9]).unwrap();
+        let mut clamped = genome.clone();
+
+        assert!(!registry.clamp(&mut clamped));
+        assert!(std::ptr::eq(genome.genes(), clamped.genes()));
+    }
+}
@@ -0,0 +1,927 @@
+use std::collections::HashMap;
+use std::mem;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+
+use rand::Rng;
+use thiserror::Error;
+
+use crate::memory::{hash_map_bytes, vec_bytes, HeapSize};
+
+/// The index of the gene controlling how much energy a plant stores before reproducing
+pub const GENE_REPRODUCTION_THRESHOLD: usize = 0;
+/// The index of the gene controlling the fraction of the stored energy given to a seed
+pub const GENE_SEED_ENERGY: usize = 1;
+/// The index of the optional gene controlling the temperature a plant grows best at
+pub const GENE_THERMAL_OPTIMUM: usize = 2;
+/// The index of the optional gene controlling how far from its optimum temperature a plant grows without a penalty
+pub const GENE_THERMAL_TOLERANCE: usize = 3;
+/// The index of the optional gene controlling how many steps a plant lives before it starts to age
+pub const GENE_LIFESPAN: usize = 4;
+/// The index of the optional gene controlling how well a plant resists the pathogen
+pub const GENE_RESISTANCE: usize = 5;
+/// The index of the optional gene controlling how tall a plant grows above the ground
+pub const GENE_HEIGHT: usize = 6;
+/// The index of the optional gene controlling how much a plant invests in its roots and how far they reach
+pub const GENE_ROOTS: usize = 7;
+/// The index of the optional gene controlling how much a plant invests in flowering to spread its pollen
+pub const GENE_FLOWERING: usize = 8;
+/// The index of the optional gene controlling how many seeds a plant splits the energy of its seeds between
+pub const GENE_CLUTCH: usize = 9;
+/// The index of the optional gene controlling how much toxin a plant releases into the neighbouring cells
+pub const GENE_TOXIN: usize = 10;
+/// The index of the optional gene controlling how much of its energy a plant shares through the underground network
+pub const GENE_MYCORRHIZA: usize = 11;
+/// The index of the optional gene controlling the mutation rate of a genome when the rate can evolve
+pub const GENE_MUTATION_RATE: usize = 12;
+/// The index of the first of the optional genes controlling how a plant spreads its pigment over the light channels,
+/// there is a gene for every channel up to spectrum::CHANNELS
+pub const GENE_ABSORPTION: usize = 13;
+/// The index of the optional gene controlling how much light a seed waits for before it germinates,
+/// it follows the absorption genes
+pub const GENE_LIGHT_CUE: usize = GENE_ABSORPTION + crate::spectrum::CHANNELS;
+/// The index of the optional gene controlling how much water a seed waits for before it germinates
+pub const GENE_WATER_CUE: usize = GENE_LIGHT_CUE + 1;
+/// The minimum number of genes a genome must have
+pub const GENE_COUNT: usize = 2;
+/// The prefix of the text format of a genome, it names the version of the format
+pub const TEXT_PREFIX: &str = "g1:";
+
+/// The genetic material of a plant, every gene is a value between 0 and 1.
+/// Clones share the same genes until one of them is mutated, at which point the mutated genome gets its own copy
+#[derive(Clone, Debug)]
+pub struct Genome {
+    /// The values of all the genes
+    genes: Arc<[f32]>,
+}
+
+impl PartialEq for Genome {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.genes, &other.genes) || self.genes == other.genes
+    }
+}
+
+impl Genome {
+    /// Creates a new genome
+    /// 
+    /// # Parameters
+    /// 
+    /// genes: The values of the genes, there must be at least GENE_COUNT genes and they must all be between 0 and 1
+    /// 
+    /// # Errors
+    /// 
+    /// GenomeCreateError::Length: This will occur if there are fewer than GENE_COUNT genes
+    /// 
+    /// GenomeCreateError::Value: This will occur if any gene is not between 0 and 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// 
+    /// assert_eq!(&[0.5, 0.25], genome.genes());
+    /// ```
+    pub fn new(genes: &[f32]) -> Result<Self, GenomeCreateError> {
+        // Make sure there are enough genes
+        if genes.len() < GENE_COUNT {
+            return Err(GenomeCreateError::Length { len: genes.len() });
+        }
+
+        // Make sure all genes are valid
+        if let Some((index, &value)) = genes.iter().enumerate().find(|(_, value)| !(0.0..=1.0).contains(*value)) {
+            return Err(GenomeCreateError::Value { index, value });
+        }
+
+        let genes = Arc::from(genes);
+
+        Ok(Self { genes })
+    }
+
+    /// Returns the values of all the genes
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25, 1.0]).unwrap();
+    /// 
+    /// assert_eq!(&[0.5, 0.25, 1.0], genome.genes());
+    /// ```
+    pub fn genes(&self) -> &[f32] {
+        &self.genes
+    }
+
+    /// Returns the value of a single gene
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the gene
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the index is not smaller than the number of genes
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{self, Genome};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// 
+    /// assert_eq!(0.25, genome.gene(genome::GENE_SEED_ENERGY));
+    /// ```
+    pub fn gene(&self, index: usize) -> f32 {
+        self.genes[index]
+    }
+
+    /// Returns the value of a single gene, or None if the genome does not have the gene
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the gene
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{self, Genome};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// 
+    /// assert_eq!(Some(0.25), genome.get(genome::GENE_SEED_ENERGY));
+    /// assert_eq!(None, genome.get(genome::GENE_THERMAL_OPTIMUM));
+    /// ```
+    pub fn get(&self, index: usize) -> Option<f32> {
+        self.genes.get(index).copied()
+    }
+
+    /// Calculates the genetic distance to another genome as the mean absolute difference of the genes,
+    /// genes only present in one of the genomes count as a difference of 1
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The genome to compare with
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let genome1 = Genome::new(&[0.5, 0.25]).unwrap();
+    /// let genome2 = Genome::new(&[0.25, 0.75]).unwrap();
+    /// 
+    /// assert_eq!(0.375, genome1.distance(&genome2));
+    /// ```
+    pub fn distance(&self, other: &Genome) -> f32 {
+        let shared = self.genes.len().min(other.genes.len());
+        let total = self.genes.len().max(other.genes.len());
+
+        let difference: f32 = self.genes.iter()
+            .zip(other.genes.iter())
+            .map(|(gene1, gene2)| (gene1 - gene2).abs())
+            .sum();
+
+        (difference + (total - shared) as f32) / total as f32
+    }
+
+    /// Mutates the genome, every gene has a chance of being moved a random amount,
+    /// returns the number of genes which were mutated. The chance is the rate the genome had before the mutation
+    /// so a mutated mutation rate gene first changes the rate of the offspring
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings for the mutation
+    /// rng: The random number generator to use
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{Genome, MutationConfig};
+    /// use rand::SeedableRng;
+    /// 
+    /// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    /// let mut genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// genome.mutate(&MutationConfig::new(1.0, 0.1), &mut rng);
+    /// 
+    /// assert!((genome.gene(0) - 0.5).abs() <= 0.1);
+    /// ```
+    pub fn mutate<R: Rng>(&mut self, config: &MutationConfig, rng: &mut R) -> usize {
+        let rate = config.rate_of(self);
+        let mut count = 0;
+
+        for index in 0..self.genes.len() {
+            if rng.gen::<f32>() < rate {
+                let change = rng.gen_range(-config.strength..=config.strength);
+                let genes = Arc::make_mut(&mut self.genes);
+                genes[index] = (genes[index] + change).clamp(0.0, 1.0);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Mutates the genes in a range by adding normally distributed noise, which suits genes encoding the weights
+    /// of a network better than the even noise of mutate since most changes are small and a few are large.
+    /// Genes outside the genome are ignored and the genes are clamped between 0 and 1, returns the number of mutated genes
+    /// 
+    /// # Parameters
+    /// 
+    /// genes: The indices of the genes which may mutate
+    /// config: The mutation settings, the strength is the standard deviation of the noise
+    /// rng: The random number generator to use
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{Genome, MutationConfig};
+    /// use rand::SeedableRng;
+    /// 
+    /// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    /// let mut genome = Genome::new(&[0.5, 0.5, 0.5, 0.5]).unwrap();
+    /// 
+    /// assert_eq!(2, genome.mutate_gaussian(2..10, &MutationConfig::new(1.0, 0.1), &mut rng));
+    /// assert_eq!(&[0.5, 0.5], &genome.genes()[..2]);
+    /// ```
+    pub fn mutate_gaussian<R: Rng>(&mut self, genes: Range<usize>, config: &MutationConfig, rng: &mut R) -> usize {
+        let end = genes.end.min(self.genes.len());
+        let rate = config.rate_of(self);
+        let mut count = 0;
+
+        for index in genes.start.min(end)..end {
+            if rng.gen::<f32>() < rate {
+                // Box-Muller transform of two even samples into a normally distributed sample
+                let (u1, u2) = (1.0 - rng.gen::<f32>(), rng.gen::<f32>());
+                let noise = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+
+                let genes = Arc::make_mut(&mut self.genes);
+                genes[index] = (genes[index] + noise * config.strength).clamp(0.0, 1.0);
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Creates a new genome by combining this genome with another,
+    /// the new genome has the same number of genes as this genome
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The genome to combine with
+    /// crossover: The method used to combine the genomes
+    /// rng: The random number generator to use
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{Crossover, Genome};
+    /// use rand::SeedableRng;
+    /// 
+    /// let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(0);
+    /// let genome1 = Genome::new(&[0.0, 0.0, 0.0]).unwrap();
+    /// let genome2 = Genome::new(&[1.0, 1.0, 1.0]).unwrap();
+    /// let child = genome1.crossover(&genome2, Crossover::Uniform, &mut rng);
+    /// 
+    /// assert!(child.genes().iter().all(|&gene| gene == 0.0 || gene == 1.0));
+    /// ```
+    pub fn crossover<R: Rng>(&self, other: &Genome, crossover: Crossover, rng: &mut R) -> Genome {
+        let shared = self.genes.len().min(other.genes.len());
+
+        let genes: Vec<f32> = match crossover {
+            Crossover::SinglePoint => {
+                // Take the genes from this genome up until the point and from the other genome after
+                let point = rng.gen_range(0..=shared);
+
+                self.genes.iter()
+                    .enumerate()
+                    .map(|(index, &gene)| if index < point || index >= shared { gene } else { other.genes[index] })
+                    .collect()
+            }
+
+            Crossover::Uniform => {
+                // Pick every gene from a random genome
+                self.genes.iter()
+                    .enumerate()
+                    .map(|(index, &gene)| if index >= shared || rng.gen::<bool>() { gene } else { other.genes[index] })
+                    .collect()
+            }
+        };
+
+        Self { genes: Arc::from(genes) }
+    }
+
+    /// Returns true if this genome shares its genes with another genome instead of having its own copy
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The genome to compare with
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25]).unwrap();
+    /// 
+    /// assert!(genome.shares_genes(&genome.clone()));
+    /// assert!(!genome.shares_genes(&Genome::new(&[0.5, 0.25]).unwrap()));
+    /// ```
+    pub fn shares_genes(&self, other: &Genome) -> bool {
+        Arc::ptr_eq(&self.genes, &other.genes)
+    }
+
+    /// Writes the genome as text which can be read back with from_str. The text is TEXT_PREFIX followed by
+    /// the genes in order separated by commas, every gene is written with the fewest digits which read back
+    /// to exactly the same value, so reading the text gives a genome equal to this one
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.25, 1.0, 0.1]).unwrap();
+    /// 
+    /// assert_eq!("g1:0.5,0.25,1,0.1", genome.to_string_repr());
+    /// assert_eq!(genome, genome.to_string_repr().parse().unwrap());
+    /// ```
+    pub fn to_string_repr(&self) -> String {
+        let genes: Vec<String> = self.genes.iter().map(|gene| gene.to_string()).collect();
+
+        format!("{}{}", TEXT_PREFIX, genes.join(","))
+    }
+}
+
+impl FromStr for Genome {
+    type Err = GenomeParseError;
+
+    /// Reads a genome written with to_string_repr, whitespace around the text and around every gene is ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// text: The text to read
+    /// 
+    /// # Errors
+    /// 
+    /// GenomeParseError::Prefix: This will occur if the text does not start with TEXT_PREFIX
+    /// 
+    /// GenomeParseError::Gene: This will occur if a gene is not a number
+    /// 
+    /// GenomeParseError::Create: This will occur if there are too few genes or a gene is not between 0 and 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{Genome, GenomeParseError};
+    /// 
+    /// let genome: Genome = " g1: 0.5, 0.25 ".parse().unwrap();
+    /// 
+    /// assert_eq!(&[0.5, 0.25], genome.genes());
+    /// assert_eq!(Err(GenomeParseError::Gene { index: 1, text: "x".to_string() }), "g1:0.5,x".parse::<Genome>());
+    /// ```
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let Some(genes) = text.strip_prefix(TEXT_PREFIX) else {
+            return Err(GenomeParseError::Prefix { text: text.chars().take(TEXT_PREFIX.len()).collect() });
+        };
+
+        let genes = genes.split(',')
+            .enumerate()
+            .map(|(index, gene)| {
+                let gene = gene.trim();
+                gene.parse::<f32>().map_err(|_| GenomeParseError::Gene { index, text: gene.to_string() })
+            })
+            .collect::<Result<Vec<f32>, GenomeParseError>>()?;
+
+        Ok(Genome::new(&genes)?)
+    }
+}
+
+/// Keeps one copy of every distinct genome such that identical genomes created independently share their genes.
+/// Only weak references are kept, so a genome is dropped from memory once no plant has it
+#[derive(Clone, Debug, Default)]
+pub struct GenomeStore {
+    /// The genes of every stored genome by the bits of its genes
+    genomes: HashMap<Vec<u32>, Weak<[f32]>>,
+    /// The number of stored genomes after the latest pruning
+    pruned_len: usize,
+}
+
+impl GenomeStore {
+    /// Creates a new empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored genomes, some of which may no longer be in use
+    pub fn len(&self) -> usize {
+        self.genomes.len()
+    }
+
+    /// Returns true if no genomes are stored
+    pub fn is_empty(&self) -> bool {
+        self.genomes.is_empty()
+    }
+
+    /// Returns a genome sharing its genes with the stored identical genome, the genome is stored if there is none.
+    /// Genomes no longer in use are pruned once the store has doubled in size
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to look up
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{Genome, GenomeStore};
+    /// 
+    /// let mut store = GenomeStore::new();
+    /// let first = store.intern(&Genome::new(&[0.5, 0.25]).unwrap());
+    /// let second = store.intern(&Genome::new(&[0.5, 0.25]).unwrap());
+    /// 
+    /// assert!(first.shares_genes(&second));
+    /// assert_eq!(1, store.len());
+    /// ```
+    pub fn intern(&mut self, genome: &Genome) -> Genome {
+        let key: Vec<u32> = genome.genes.iter().map(|gene| gene.to_bits()).collect();
+
+        if let Some(genes) = self.genomes.get(&key).and_then(Weak::upgrade) {
+            return Genome { genes };
+        }
+
+        self.genomes.insert(key, Arc::downgrade(&genome.genes));
+        if self.genomes.len() > 2 * self.pruned_len.max(32) {
+            self.prune();
+        }
+
+        genome.clone()
+    }
+
+    /// Removes the genomes no longer in use
+    pub fn prune(&mut self) {
+        self.genomes.retain(|_, genes| genes.strong_count() > 0);
+        self.pruned_len = self.genomes.len();
+    }
+}
+
+/// The method used to combine two genomes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossover {
+    /// All genes before a random point are from the first genome and the rest is from the second
+    SinglePoint,
+    /// Every gene is picked from a random genome
+    Uniform,
+}
+
+/// The settings for mutating a genome
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MutationConfig {
+    /// The probability of mutating each gene
+    pub rate: f32,
+    /// The largest amount a gene can change in a single mutation
+    pub strength: f32,
+    /// The bounds of the mutation rate of genomes carrying a mutation rate gene, the rate is then read from the gene
+    /// and mutates along with the rest of the genome. Every genome uses the rate above if this is None
+    pub evolvable: Option<EvolvableRate>,
+}
+
+/// The bounds of a mutation rate encoded in the genome, a gene of 0 gives the lowest rate and a gene of 1 the highest
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvolvableRate {
+    /// The mutation rate of a genome with a mutation rate gene of 0
+    pub min: f32,
+    /// The mutation rate of a genome with a mutation rate gene of 1
+    pub max: f32,
+}
+
+impl Default for EvolvableRate {
+    fn default() -> Self {
+        Self {
+            min: 0.001,
+            max: 0.1,
+        }
+    }
+}
+
+impl MutationConfig {
+    /// Creates a new set of mutation settings
+    /// 
+    /// # Parameters
+    /// 
+    /// rate: The probability of mutating each gene
+    /// strength: The largest amount a gene can change in a single mutation
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::MutationConfig;
+    /// 
+    /// let config = MutationConfig::new(0.01, 0.1);
+    /// 
+    /// assert_eq!(0.01, config.rate);
+    /// assert_eq!(0.1, config.strength);
+    /// ```
+    pub fn new(rate: f32, strength: f32) -> Self {
+        Self { rate, strength, evolvable: None }
+    }
+
+    /// Finds the mutation rate of a genome, the rate of the settings unless the rate can evolve
+    /// and the genome has a mutation rate gene
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to mutate
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{EvolvableRate, Genome, MutationConfig, GENE_MUTATION_RATE};
+    /// 
+    /// let config = MutationConfig { evolvable: Some(EvolvableRate { min: 0.0, max: 0.2 }), ..MutationConfig::new(0.01, 0.1) };
+    /// let mut genes = vec![0.5; GENE_MUTATION_RATE + 1];
+    /// genes[GENE_MUTATION_RATE] = 0.25;
+    /// 
+    /// assert_eq!(0.05, config.rate_of(&Genome::new(&genes).unwrap()));
+    /// assert_eq!(0.01, config.rate_of(&Genome::new(&[0.5, 0.5]).unwrap()));
+    /// ```
+    pub fn rate_of(&self, genome: &Genome) -> f32 {
+        match (self.evolvable, genome.get(GENE_MUTATION_RATE)) {
+            (Some(bounds), Some(gene)) => bounds.min + (bounds.max - bounds.min) * gene,
+            _ => self.rate,
+        }
+    }
+
+    /// Checks that the settings can be used for mutating genomes
+    /// 
+    /// # Errors
+    /// 
+    /// MutationConfigError::Rate: This will occur if the rate or a bound of the evolvable rate is not between 0 and 1
+    /// 
+    /// MutationConfigError::Strength: This will occur if the strength is negative or not finite
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::{MutationConfig, MutationConfigError};
+    /// 
+    /// assert_eq!(Ok(()), MutationConfig::new(0.01, 0.1).validate());
+    /// assert_eq!(Err(MutationConfigError::Rate { rate: 1.5 }), MutationConfig::new(1.5, 0.1).validate());
+    /// assert_eq!(Err(MutationConfigError::Strength { strength: -0.1 }), MutationConfig::new(0.01, -0.1).validate());
+    /// ```
+    pub fn validate(&self) -> Result<(), MutationConfigError> {
+        let bounds = self.evolvable.map_or([self.rate; 2], |bounds| [bounds.min, bounds.max]);
+        if let Some(&rate) = [self.rate].iter().chain(bounds.iter()).find(|rate| !(0.0..=1.0).contains(*rate)) {
+            return Err(MutationConfigError::Rate { rate });
+        }
+        if !self.strength.is_finite() || self.strength < 0.0 {
+            return Err(MutationConfigError::Strength { strength: self.strength });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self::new(0.01, 0.1)
+    }
+}
+
+/// Calculates the mean value of every gene over a set of genomes, genes missing from some genomes are
+/// averaged over the genomes which have them
+pub(crate) fn mean_genes<'a, I: Iterator<Item = &'a Genome>>(genomes: I) -> Vec<f32> {
+    let mut sums: Vec<f32> = Vec::new();
+    let mut counts: Vec<usize> = Vec::new();
+
+    for genome in genomes {
+        if genome.genes.len() > sums.len() {
+            sums.resize(genome.genes.len(), 0.0);
+            counts.resize(genome.genes.len(), 0);
+        }
+
+        for (index, gene) in genome.genes.iter().enumerate() {
+            sums[index] += gene;
+            counts[index] += 1;
+        }
+    }
+
+    sums.iter()
+        .zip(counts.iter())
+        .map(|(&sum, &count)| (sum / count as f32).clamp(0.0, 1.0))
+        .collect()
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum GenomeCreateError {
+    #[error("Genome has too few genes ({:?}) should be at least ({:?})", len, GENE_COUNT)]
+    Length {
+        len: usize,
+    },
+    #[error("Gene {:?} has value ({:?}) outside of the range 0 to 1", index, value)]
+    Value {
+        index: usize,
+        value: f32,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum MutationConfigError {
+    #[error("Mutation rate is ({:?}) but should be between 0 and 1", rate)]
+    Rate {
+        rate: f32,
+    },
+    #[error("Mutation strength is ({:?}) but should be a finite number of at least 0", strength)]
+    Strength {
+        strength: f32,
+    },
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum GenomeParseError {
+    #[error("Genome text starts with ({:?}) but should start with ({:?})", text, TEXT_PREFIX)]
+    Prefix {
+        text: String,
+    },
+    #[error("Gene {:?} is ({:?}) which is not a number", index, text)]
+    Gene {
+        index: usize,
+        text: String,
+    },
+    #[error(transparent)]
+    Create(#[from] GenomeCreateError),
+}
+
+impl HeapSize for GenomeStore {
+    /// The genes of the genomes still in use are counted here once, no matter how many plants share them
+    fn heap_bytes(&self) -> usize {
+        let genes = self.genomes.iter()
+            .map(|(bits, genes)| vec_bytes(bits) + genes.upgrade().map_or(0, |genes| mem::size_of_val(&*genes) + 2 * mem::size_of::<usize>()))
+            .sum::<usize>();
+
+        hash_map_bytes(&self.genomes) + genes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn genome_new() -> Result<(), GenomeCreateError> {
+        let genome = Genome::new(&[0.5, 0.25, 1.0])?;
+
+        assert_eq!(vec![0.5, 0.25, 1.0], genome.genes.to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn genome_new_error_length() {
+        let genome = Genome::new(&[0.5]);
+
+        assert_eq!(GenomeCreateError::Length { len: 1 }, genome.unwrap_err());
+    }
+
+    #[test]
+    fn genome_new_error_value() {
+        let genome = Genome::new(&[0.5, 1.5]);
+
+        assert_eq!(GenomeCreateError::Value { index: 1, value: 1.5 }, genome.unwrap_err());
+    }
+
+    #[test]
+    fn genome_gene() {
+        let genome = Genome::new(&[0.5, 0.25]).unwrap();
+
+        assert_eq!(0.5, genome.gene(GENE_REPRODUCTION_THRESHOLD));
+        assert_eq!(0.25, genome.gene(GENE_SEED_ENERGY));
+    }
+
+    #[test]
+    fn genome_get() {
+        let genome = Genome::new(&[0.5, 0.25, 1.0]).unwrap();
+
+        assert_eq!(Some(1.0), genome.get(GENE_THERMAL_OPTIMUM));
+        assert_eq!(None, genome.get(GENE_THERMAL_TOLERANCE));
+    }
+
+    #[test]
+    fn genome_distance() {
+        let genome1 = Genome::new(&[0.5, 0.25]).unwrap();
+        let genome2 = Genome::new(&[0.25, 0.75]).unwrap();
+        let genome3 = Genome::new(&[0.5, 0.25, 0.0, 0.0]).unwrap();
+
+        assert_eq!(0.0, genome1.distance(&genome1));
+        assert_eq!(0.375, genome1.distance(&genome2));
+        assert_eq!(0.5, genome1.distance(&genome3));
+        assert_eq!(0.5, genome3.distance(&genome1));
+    }
+
+    #[test]
+    fn genome_mutate() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(&[0.5, 0.0, 1.0]).unwrap();
+
+        assert_eq!(3, genome.mutate(&MutationConfig::new(1.0, 0.1), &mut rng));
+        assert!((genome.gene(0) - 0.5).abs() <= 0.1);
+        assert!((0.0..=0.1).contains(&genome.gene(1)));
+        assert!((0.9..=1.0).contains(&genome.gene(2)));
+    }
+
+    #[test]
+    fn genome_mutate_rate_zero() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(&[0.5, 0.0, 1.0]).unwrap();
+
+        assert_eq!(0, genome.mutate(&MutationConfig::new(0.0, 0.1), &mut rng));
+        assert_eq!(vec![0.5, 0.0, 1.0], genome.genes.to_vec());
+    }
+
+    #[test]
+    fn genome_mutate_evolvable_rate() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let config = MutationConfig { evolvable: Some(EvolvableRate { min: 0.0, max: 2.0 }), ..MutationConfig::new(0.5, 0.1) };
+        let mut genes = vec![0.5; GENE_MUTATION_RATE + 1];
+        genes[GENE_MUTATION_RATE] = 0.0;
+        let mut stable = Genome::new(&genes).unwrap();
+        genes[GENE_MUTATION_RATE] = 0.75;
+        let mut unstable = Genome::new(&genes).unwrap();
+
+        // The rate is read from the gene instead of the settings
+        assert_eq!(0, stable.mutate(&config, &mut rng));
+        assert_eq!(GENE_MUTATION_RATE + 1, unstable.mutate(&config, &mut rng));
+
+        // The gene mutates like the rest so the rate of the offspring differs from the parent
+        assert_ne!(1.5, config.rate_of(&unstable));
+        assert_eq!(config.rate, MutationConfig::new(0.5, 0.1).rate_of(&unstable));
+    }
+
+    #[test]
+    fn genome_mutate_gaussian() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut genome = Genome::new(&[0.5; 200]).unwrap();
+
+        assert_eq!(100, genome.mutate_gaussian(100..300, &MutationConfig::new(1.0, 0.05), &mut rng));
+        assert!(genome.genes[..100].iter().all(|&gene| gene == 0.5));
+
+        // Most of the noise is within two standard deviations
+        let close = genome.genes[100..].iter().filter(|&&gene| (gene - 0.5).abs() <= 0.1).count();
+        assert!(close > 85);
+        assert_eq!(0, genome.mutate_gaussian(300..400, &MutationConfig::new(1.0, 0.05), &mut rng));
+    }
+
+    #[test]
+    fn genome_crossover_single_point() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let genome1 = Genome::new(&[0.0; 8]).unwrap();
+        let genome2 = Genome::new(&[1.0; 8]).unwrap();
+
+        for _ in 0..20 {
+            let child = genome1.crossover(&genome2, Crossover::SinglePoint, &mut rng);
+            let point = child.genes.iter().position(|&gene| gene == 1.0).unwrap_or(8);
+
+            assert_eq!(8, child.genes.len());
+            assert!(child.genes[..point].iter().all(|&gene| gene == 0.0));
+            assert!(child.genes[point..].iter().all(|&gene| gene == 1.0));
+        }
+    }
+
+    #[test]
+    fn genome_crossover_uniform() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let genome1 = Genome::new(&[0.0; 64]).unwrap();
+        let genome2 = Genome::new(&[1.0; 64]).unwrap();
+        let child = genome1.crossover(&genome2, Crossover::Uniform, &mut rng);
+
+        assert_eq!(64, child.genes.len());
+        assert!(child.genes.contains(&0.0));
+        assert!(child.genes.contains(&1.0));
+    }
+
+    #[test]
+    fn genome_crossover_length() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let genome1 = Genome::new(&[0.0; 4]).unwrap();
+        let genome2 = Genome::new(&[1.0; 2]).unwrap();
+
+        assert_eq!(2, genome2.crossover(&genome1, Crossover::Uniform, &mut rng).genes.len());
+        assert_eq!(2, genome2.crossover(&genome1, Crossover::SinglePoint, &mut rng).genes.len());
+
+        let child = genome1.crossover(&genome2, Crossover::Uniform, &mut rng);
+        assert_eq!(&[0.0, 0.0], &child.genes[2..]);
+    }
+
+    #[test]
+    fn mean_genes_lengths() {
+        let genomes = [Genome::new(&[0.0, 1.0]).unwrap(), Genome::new(&[1.0, 1.0, 0.5]).unwrap()];
+
+        assert_eq!(vec![0.5, 1.0, 0.5], mean_genes(genomes.iter()));
+        assert!(mean_genes([].iter()).is_empty());
+    }
+
+    #[test]
+    fn genome_mutate_copy() {
+        // Mutating a clone leaves the genes of the original untouched
+        let genome = Genome::new(&[0.5, 0.5, 0.5]).unwrap();
+        let mut clone = genome.clone();
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        clone.mutate(&MutationConfig::new(1.0, 0.1), &mut rng);
+
+        assert!(!clone.shares_genes(&genome));
+        assert_eq!(&[0.5, 0.5, 0.5], &genome.genes[..]);
+        assert_ne!(genome, clone);
+    }
+
+    #[test]
+    fn genome_store_intern() {
+        let mut store = GenomeStore::new();
+        let first = store.intern(&Genome::new(&[0.5, 0.25]).unwrap());
+        let other = store.intern(&Genome::new(&[0.25, 0.5]).unwrap());
+        let second = store.intern(&Genome::new(&[0.5, 0.25]).unwrap());
+
+        assert!(first.shares_genes(&second));
+        assert!(!first.shares_genes(&other));
+        assert_eq!(2, store.len());
+    }
+
+    #[test]
+    fn genome_store_prune() {
+        let mut store = GenomeStore::new();
+        let kept = store.intern(&Genome::new(&[0.5, 0.5]).unwrap());
+        store.intern(&Genome::new(&[0.25, 0.25]).unwrap());
+        store.prune();
+
+        assert_eq!(1, store.len());
+        assert!(kept.shares_genes(&store.intern(&Genome::new(&[0.5, 0.5]).unwrap())));
+    }
+
+    #[test]
+    fn mutation_config_new() {
+        let config = MutationConfig::new(0.01, 0.1);
+
+        assert_eq!(0.01, config.rate);
+        assert_eq!(0.1, config.strength);
+    }
+
+    #[test]
+    fn mutation_config_validate() {
+        let evolvable = MutationConfig { evolvable: Some(EvolvableRate { min: 0.0, max: 2.0 }), ..Default::default() };
+
+        assert_eq!(Ok(()), MutationConfig::new(0.0, 0.0).validate());
+        assert!(matches!(MutationConfig::new(f32::NAN, 0.1).validate(), Err(MutationConfigError::Rate { .. })));
+        assert!(matches!(MutationConfig::new(0.1, f32::INFINITY).validate(), Err(MutationConfigError::Strength { .. })));
+        assert!(matches!(MutationConfig::new(0.1, f32::NAN).validate(), Err(MutationConfigError::Strength { .. })));
+        assert_eq!(Err(MutationConfigError::Rate { rate: 2.0 }), evolvable.validate());
+    }
+
+    #[test]
+    fn genome_text_round_trip() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let len = rng.gen_range(GENE_COUNT..20);
+            let genes: Vec<f32> = (0..len)
+                .map(|_| match rng.gen_range(0..4) {
+                    0 => 0.0,
+                    1 => 1.0,
+                    2 => f32::from_bits(rng.gen_range(1..100)),
+                    _ => rng.gen(),
+                })
+                .collect();
+            let genome = Genome::new(&genes).unwrap();
+
+            assert_eq!(genome, genome.to_string_repr().parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn genome_text_fuzz() {
+        // Random text made of the characters of the format never panics and only gives valid genomes
+        let alphabet: Vec<char> = "g1:0.5,-e9 x".chars().collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(2);
+        for _ in 0..5000 {
+            let len = rng.gen_range(0..16);
+            let text: String = (0..len).map(|_| alphabet[rng.gen_range(0..alphabet.len())]).collect();
+
+            if let Ok(genome) = format!("{}{}", TEXT_PREFIX, text).parse::<Genome>() {
+                assert!(genome.genes().len() >= GENE_COUNT);
+                assert!(genome.genes().iter().all(|gene| (0.0..=1.0).contains(gene)));
+            }
+            let _ = text.parse::<Genome>();
+        }
+    }
+
+    #[test]
+    fn genome_from_str_errors() {
+        assert_eq!(Err(GenomeParseError::Prefix { text: "0.5".to_string() }), "0.5,0.5".parse::<Genome>());
+        assert_eq!(Err(GenomeParseError::Prefix { text: String::new() }), "".parse::<Genome>());
+        assert_eq!(Err(GenomeParseError::Gene { index: 0, text: String::new() }), "g1:".parse::<Genome>());
+        assert_eq!(Err(GenomeParseError::Create(GenomeCreateError::Length { len: 1 })), "g1:0.5".parse::<Genome>());
+        assert_eq!(Err(GenomeParseError::Create(GenomeCreateError::Value { index: 1, value: 2.0 })), "g1:0.5,2".parse::<Genome>());
+        assert!(matches!("g1:0.5,NaN".parse::<Genome>(), Err(GenomeParseError::Create(GenomeCreateError::Value { index: 1, .. }))));
+    }
+}
@@ -0,0 +1,130 @@
+use rand::Rng;
+
+use crate::genome::{self, Genome};
+use crate::seedbank::DormantSeed;
+
+/// The settings for letting seeds wait for the right conditions before they germinate. Every seed carries a light cue
+/// and a water cue in its genome, the cues are met when the light and the water of the cell the seed lies in reach the
+/// thresholds set by the genes. Once the cues have been met for enough steps in a row the seed germinates with a chance
+/// every step, seeds which do not germinate stay dormant in the seed bank or die if there is none
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GerminationConfig {
+    /// The light a seed with a light cue gene of 1 waits for, seeds without the gene do not wait for light
+    pub max_light: f32,
+    /// The water a seed with a water cue gene of 1 waits for, seeds without the gene do not wait for water
+    pub max_water: f32,
+    /// The chance of a seed germinating in a step where it is ready to, between 0 and 1
+    pub chance: f32,
+    /// The number of steps in a row the cues must be met before a seed is ready to germinate
+    pub persistence: u64,
+}
+
+impl Default for GerminationConfig {
+    fn default() -> Self {
+        Self {
+            max_light: 1.0,
+            max_water: 1.0,
+            chance: 0.5,
+            persistence: 1,
+        }
+    }
+}
+
+impl GerminationConfig {
+    /// Returns true if the light and the water of a cell reach the thresholds a seed waits for
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the seed
+    /// light: The light of the cell the seed lies in
+    /// water: The water of the cell the seed lies in
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::{Genome, GENE_LIGHT_CUE}, germination::GerminationConfig};
+    /// 
+    /// let config = GerminationConfig { max_light: 2.0, max_water: 1.0, ..Default::default() };
+    /// let mut genes = vec![0.0; GENE_LIGHT_CUE + 2];
+    /// genes[GENE_LIGHT_CUE] = 0.5;
+    /// genes[GENE_LIGHT_CUE + 1] = 0.25;
+    /// let genome = Genome::new(&genes).unwrap();
+    /// 
+    /// assert!(config.cues_met(&genome, 1.0, 0.25));
+    /// assert!(!config.cues_met(&genome, 0.9, 0.25));
+    /// assert!(!config.cues_met(&genome, 1.0, 0.2));
+    /// assert!(config.cues_met(&Genome::new(&[0.5, 0.5]).unwrap(), 0.0, 0.0));
+    /// ```
+    pub fn cues_met(&self, genome: &Genome, light: f32, water: f32) -> bool {
+        let light_cue = genome.get(genome::GENE_LIGHT_CUE).unwrap_or(0.0) * self.max_light.max(0.0);
+        let water_cue = genome.get(genome::GENE_WATER_CUE).unwrap_or(0.0) * self.max_water.max(0.0);
+
+        light >= light_cue && water >= water_cue
+    }
+
+    /// Senses the cues of the cell a dormant seed lies in, counting the steps in a row they have been met
+    /// 
+    /// # Parameters
+    /// 
+    /// dormant: The seed
+    /// light: The light of the cell the seed lies in
+    /// water: The water of the cell the seed lies in
+    pub(crate) fn sense(&self, dormant: &mut DormantSeed, light: f32, water: f32) {
+        dormant.cued = if self.cues_met(&dormant.seed.genome, light, water) { dormant.cued + 1 } else { 0 };
+    }
+
+    /// Decides if a seed which has met its cues for a number of steps in a row germinates in this step
+    /// 
+    /// # Parameters
+    /// 
+    /// cued: The number of steps in a row the cues of the seed have been met
+    /// rng: The random number generator to use
+    pub(crate) fn germinates<R: Rng>(&self, cued: u64, rng: &mut R) -> bool {
+        cued > 0 && cued >= self.persistence && rng.gen::<f32>() < self.chance.clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::population::Plant;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn dormant(light_cue: f32, water_cue: f32) -> DormantSeed {
+        let mut genes = vec![0.5; genome::GENE_WATER_CUE + 1];
+        genes[genome::GENE_LIGHT_CUE] = light_cue;
+        genes[genome::GENE_WATER_CUE] = water_cue;
+
+        DormantSeed { seed: Plant::new(10, Genome::new(&genes).unwrap()), since: 0, cued: 0 }
+    }
+
+    #[test]
+    fn germination_sense() {
+        let config = GerminationConfig::default();
+        let mut seed = dormant(0.5, 0.2);
+        config.sense(&mut seed, 0.6, 0.3);
+        config.sense(&mut seed, 0.5, 0.2);
+
+        assert_eq!(2, seed.cued);
+
+        config.sense(&mut seed, 0.4, 1.0);
+
+        assert_eq!(0, seed.cued);
+    }
+
+    #[test]
+    fn germination_germinates() {
+        let config = GerminationConfig { chance: 0.5, persistence: 3, ..Default::default() };
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert!((0..100).all(|_| !config.germinates(2, &mut rng)));
+
+        let germinated = (0..1000).filter(|_| config.germinates(3, &mut rng)).count();
+        assert!((400..600).contains(&germinated));
+
+        let config = GerminationConfig { chance: 1.0, persistence: 0, ..Default::default() };
+        assert!(!config.germinates(0, &mut rng));
+        assert!(config.germinates(1, &mut rng));
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::simulation::Simulation;
+use crate::stop::StopCondition;
+
+/// The largest number of ticks a governor asks for at once, if the simulation falls further behind the missed ticks are dropped
+/// such that a slow step does not cause a burst of steps trying to catch up
+pub const MAX_CATCH_UP: u64 = 1000;
+/// The shortest frame time a frame budget accepts, shorter frames are counted as this long
+pub const MIN_FRAME_TIME: Duration = Duration::from_millis(2);
+/// The longest frame time a frame budget accepts, longer frames such as those while a window is hidden are counted as this long
+pub const MAX_FRAME_TIME: Duration = Duration::from_millis(100);
+/// The weight of the latest frame in the smoothed frame time
+const FRAME_SMOOTHING: f64 = 0.1;
+
+/// How fast a simulation is run
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum RunMode {
+    /// Runs as many ticks as possible
+    #[default]
+    MaxSpeed,
+    /// Runs a fixed number of ticks every second, 0 or less never runs any ticks
+    FixedTicksPerSecond(f32),
+    /// Runs the simulated time at a multiple of the real time, the simulated time of a tick is set by the governor
+    RealTimeScaled(f32),
+}
+
+/// Keeps a simulation running at the rate of a run mode with a fixed timestep, the ticks are spread evenly over time
+/// and ticks which are late because a step was slow are run as soon as possible to catch up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Governor {
+    /// How fast the simulation is run
+    mode: RunMode,
+    /// The simulated time of a single tick used by RealTimeScaled
+    tick_duration: Duration,
+    /// The time the governor started counting ticks, None if it has not started yet
+    started: Option<Instant>,
+    /// The number of ticks handed out since it started
+    ticks: u64,
+}
+
+impl Governor {
+    /// Creates a new governor where every tick is a second of simulated time
+    /// 
+    /// # Parameters
+    /// 
+    /// mode: How fast the simulation is run
+    pub fn new(mode: RunMode) -> Self {
+        Self { mode, tick_duration: Duration::from_secs(1), started: None, ticks: 0 }
+    }
+
+    /// Sets the simulated time of a single tick used by RealTimeScaled
+    /// 
+    /// # Parameters
+    /// 
+    /// tick_duration: The simulated time of a tick
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use evolution_plants::governor::{Governor, RunMode};
+    /// 
+    /// // Every tick is a day and a day passes every second
+    /// let governor = Governor::new(RunMode::RealTimeScaled(86400.0)).with_tick_duration(Duration::from_secs(86400));
+    /// 
+    /// assert_eq!(Some(1.0), governor.ticks_per_second());
+    /// ```
+    pub fn with_tick_duration(mut self, tick_duration: Duration) -> Self {
+        self.tick_duration = tick_duration;
+
+        self
+    }
+
+    /// Returns how fast the simulation is run
+    pub fn mode(&self) -> RunMode {
+        self.mode
+    }
+
+    /// Changes how fast the simulation is run, the ticks are counted from the next call to due
+    /// 
+    /// # Parameters
+    /// 
+    /// mode: How fast the simulation is run
+    pub fn set_mode(&mut self, mode: RunMode) {
+        self.mode = mode;
+        self.reset();
+    }
+
+    /// Forgets the ticks handed out so far such that the time the simulation was stopped is not caught up
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.ticks = 0;
+    }
+
+    /// Finds the number of ticks to run every second, None if the simulation runs as fast as possible
+    pub fn ticks_per_second(&self) -> Option<f32> {
+        let rate = match self.mode {
+            RunMode::MaxSpeed => return None,
+            RunMode::FixedTicksPerSecond(rate) => rate,
+            RunMode::RealTimeScaled(scale) => scale / self.tick_duration.as_secs_f32(),
+        };
+
+        Some(if rate.is_finite() { rate.max(0.0) } else { 0.0 })
+    }
+
+    /// Finds the number of ticks which should be run now to keep up with the run mode and counts them as run,
+    /// None if the simulation runs as fast as possible. The first call starts the clock and asks for no ticks
+    /// 
+    /// # Parameters
+    /// 
+    /// now: The current time
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use evolution_plants::governor::{Governor, RunMode};
+    /// 
+    /// let mut governor = Governor::new(RunMode::FixedTicksPerSecond(10.0));
+    /// let start = Instant::now();
+    /// 
+    /// assert_eq!(Some(0), governor.due(start));
+    /// assert_eq!(Some(3), governor.due(start + Duration::from_millis(350)));
+    /// assert_eq!(Some(0), governor.due(start + Duration::from_millis(380)));
+    /// assert_eq!(None, Governor::new(RunMode::MaxSpeed).due(start));
+    /// ```
+    pub fn due(&mut self, now: Instant) -> Option<u64> {
+        let rate = self.ticks_per_second()?;
+        let started = *self.started.get_or_insert(now);
+
+        let target = (now.saturating_duration_since(started).as_secs_f64() * rate as f64) as u64;
+        let mut due = target.saturating_sub(self.ticks);
+        if due > MAX_CATCH_UP {
+            due = MAX_CATCH_UP;
+            self.ticks = target - MAX_CATCH_UP;
+        }
+        self.ticks += due;
+
+        Some(due)
+    }
+
+    /// Finds the time until the next tick is due, this is 0 if the simulation runs as fast as possible
+    /// or the governor has not started yet, and Duration::MAX if no ticks are ever run
+    /// 
+    /// # Parameters
+    /// 
+    /// now: The current time
+    pub fn wait(&self, now: Instant) -> Duration {
+        let (Some(rate), Some(started)) = (self.ticks_per_second(), self.started) else {
+            return Duration::ZERO;
+        };
+        if rate <= 0.0 {
+            return Duration::MAX;
+        }
+
+        let next = started + Duration::from_secs_f64((self.ticks + 1) as f64 / rate as f64);
+
+        next.saturating_duration_since(now)
+    }
+}
+
+/// Fits the steps run between two frames of a window into the time of a frame, such that the window stays smooth whether a step
+/// takes a microsecond or longer than a frame. As many steps as fit are run and only the latest state is drawn. The frame time is
+/// measured from the frames drawn and smoothed such that a single slow frame does not change the batches much
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FrameBudget {
+    /// The smoothed time between two frames
+    frame_time: Duration,
+    /// The share of the frame time the steps may take
+    share: f32,
+}
+
+impl FrameBudget {
+    /// Creates a new frame budget where the steps may take the whole frame
+    /// 
+    /// # Parameters
+    /// 
+    /// frame_time: The expected time between two frames, it is clamped between MIN_FRAME_TIME and MAX_FRAME_TIME
+    pub fn new(frame_time: Duration) -> Self {
+        Self { frame_time: frame_time.clamp(MIN_FRAME_TIME, MAX_FRAME_TIME), share: 1.0 }
+    }
+
+    /// Sets the share of the frame time the steps may take, such as when they run on the same thread as the drawing
+    /// 
+    /// # Parameters
+    /// 
+    /// share: The share of the frame time, it is clamped between 0 and 1
+    pub fn with_share(mut self, share: f32) -> Self {
+        self.share = if share.is_finite() { share.clamp(0.0, 1.0) } else { 1.0 };
+
+        self
+    }
+
+    /// Returns the smoothed time between two frames
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Returns the time the steps between two frames may take
+    pub fn budget(&self) -> Duration {
+        self.frame_time.mul_f32(self.share)
+    }
+
+    /// Adds the measured time between the latest two frames to the smoothed frame time
+    /// 
+    /// # Parameters
+    /// 
+    /// interval: The time since the previous frame, it is clamped between MIN_FRAME_TIME and MAX_FRAME_TIME
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use evolution_plants::governor::FrameBudget;
+    /// 
+    /// let mut budget = FrameBudget::new(Duration::from_millis(16)).with_share(0.5);
+    /// for _ in 0..100 {
+    ///     budget.record_frame(Duration::from_millis(40));
+    /// }
+    /// 
+    /// assert!(budget.budget() > Duration::from_millis(19) && budget.budget() <= Duration::from_millis(20));
+    /// ```
+    pub fn record_frame(&mut self, interval: Duration) {
+        let interval = interval.clamp(MIN_FRAME_TIME, MAX_FRAME_TIME).as_secs_f64();
+        let smoothed = self.frame_time.as_secs_f64() * (1.0 - FRAME_SMOOTHING) + interval * FRAME_SMOOTHING;
+
+        self.frame_time = Duration::from_secs_f64(smoothed);
+    }
+
+    /// Returns true if another step fits into the batch of the current frame. At least one step is run for every frame
+    /// while any are due, the steps due which do not fit are dropped such that the latest state is drawn on time
+    /// 
+    /// # Parameters
+    /// 
+    /// due: The number of steps due from a governor, None if the simulation runs as fast as possible
+    /// steps: The number of steps already run for the frame
+    /// elapsed: The time since the batch started
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use evolution_plants::governor::FrameBudget;
+    /// 
+    /// let budget = FrameBudget::new(Duration::from_millis(10));
+    /// 
+    /// assert!(budget.fits(None, 200, Duration::from_millis(9)));
+    /// assert!(!budget.fits(None, 200, Duration::from_millis(10)));
+    /// assert!(!budget.fits(Some(3), 3, Duration::ZERO));
+    /// assert!(budget.fits(Some(3), 0, Duration::from_millis(50)));
+    /// ```
+    pub fn fits(&self, due: Option<u64>, steps: u64, elapsed: Duration) -> bool {
+        if due.is_some_and(|due| steps >= due) {
+            return false;
+        }
+
+        steps == 0 || elapsed < self.budget()
+    }
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self::new(Duration::from_micros(16_667))
+    }
+}
+
+/// Measures the number of ticks a simulation runs every second over a sliding window of time, such that the rate
+/// actually reached can be shown next to the rate asked for
+#[derive(Clone, Debug, PartialEq)]
+pub struct TickRateMeter {
+    /// The length of time the rate is measured over
+    window: Duration,
+    /// The time and tick of every record in the window and the latest record before it, oldest first
+    records: VecDeque<(Instant, u64)>,
+}
+
+impl TickRateMeter {
+    /// Creates a new meter without any records
+    /// 
+    /// # Parameters
+    /// 
+    /// window: The length of time the rate is measured over
+    pub fn new(window: Duration) -> Self {
+        Self { window, records: VecDeque::new() }
+    }
+
+    /// Records the tick of the simulation at a time, the records are forgotten if the tick went backwards
+    /// such as when the simulation was rewound
+    /// 
+    /// # Parameters
+    /// 
+    /// now: The current time
+    /// tick: The tick of the simulation
+    pub fn record(&mut self, now: Instant, tick: u64) {
+        if self.records.back().is_some_and(|&(_, last)| tick < last) {
+            self.records.clear();
+        }
+        self.records.push_back((now, tick));
+
+        // Keep one record older than the window such that the rate covers the whole window
+        while self.records.len() > 2 && now.saturating_duration_since(self.records[1].0) >= self.window {
+            self.records.pop_front();
+        }
+    }
+
+    /// Returns the ticks per second between the oldest and the latest record, 0 before two records
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use evolution_plants::governor::TickRateMeter;
+    /// 
+    /// let mut meter = TickRateMeter::new(Duration::from_secs(1));
+    /// let start = Instant::now();
+    /// meter.record(start, 0);
+    /// meter.record(start + Duration::from_millis(500), 50);
+    /// 
+    /// assert_eq!(100.0, meter.ticks_per_second());
+    /// ```
+    pub fn ticks_per_second(&self) -> f32 {
+        let (Some(&(first, from)), Some(&(last, to))) = (self.records.front(), self.records.back()) else {
+            return 0.0;
+        };
+        let seconds = last.saturating_duration_since(first).as_secs_f64();
+        if seconds <= 0.0 {
+            return 0.0;
+        }
+
+        ((to - from) as f64 / seconds) as f32
+    }
+}
+
+impl Default for TickRateMeter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl Simulation {
+    /// Steps the simulation at the rate of a governor until a stop condition is met, the thread sleeps between the ticks.
+    /// Returns the number of steps run, this stops early if the governor never runs any ticks
+    /// 
+    /// # Parameters
+    /// 
+    /// governor: The governor setting how fast the simulation is run, it is reset before the first step
+    /// condition: The condition to stop at, it is checked before every step
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, stop::TickLimit};
+    /// use evolution_plants::governor::{Governor, RunMode};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let population = Population::new(board.fields.size);
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// 
+    /// assert_eq!(5, simulation.run_until_governed(&mut Governor::new(RunMode::FixedTicksPerSecond(500.0)), TickLimit(5)));
+    /// ```
+    pub fn run_until_governed<C: StopCondition>(&mut self, governor: &mut Governor, mut condition: C) -> u64 {
+        let start = self.tick();
+        governor.reset();
+
+        while !condition.should_stop(self) {
+            match governor.due(Instant::now()) {
+                None => self.step(),
+                Some(0) => match governor.wait(Instant::now()) {
+                    Duration::MAX => break,
+                    wait => thread::sleep(wait),
+                },
+                Some(due) => {
+                    for _ in 0..due {
+                        if condition.should_stop(self) {
+                            break;
+                        }
+                        self.step();
+                    }
+                }
+            }
+        }
+
+        self.tick() - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardBuilder;
+    use crate::population::Population;
+    use crate::stop::TickLimit;
+
+    #[test]
+    fn governor_fixed_rate() {
+        let mut governor = Governor::new(RunMode::FixedTicksPerSecond(4.0));
+        let start = Instant::now();
+
+        assert_eq!(Some(0), governor.due(start));
+        assert_eq!(Duration::from_millis(250), governor.wait(start));
+        assert_eq!(Some(1), governor.due(start + Duration::from_millis(300)));
+        assert_eq!(Duration::from_millis(200), governor.wait(start + Duration::from_millis(300)));
+        assert_eq!(Some(7), governor.due(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn governor_catch_up_limit() {
+        let mut governor = Governor::new(RunMode::FixedTicksPerSecond(1000.0));
+        let start = Instant::now();
+        governor.due(start);
+
+        assert_eq!(Some(MAX_CATCH_UP), governor.due(start + Duration::from_secs(10)));
+        assert_eq!(Some(500), governor.due(start + Duration::from_millis(10_500)));
+    }
+
+    #[test]
+    fn governor_real_time_scaled() {
+        let governor = Governor::new(RunMode::RealTimeScaled(2.0)).with_tick_duration(Duration::from_millis(500));
+
+        assert_eq!(Some(4.0), governor.ticks_per_second());
+        assert_eq!(Some(0.0), Governor::new(RunMode::FixedTicksPerSecond(f32::NAN)).ticks_per_second());
+        assert_eq!(None, Governor::new(RunMode::MaxSpeed).ticks_per_second());
+    }
+
+    #[test]
+    fn governor_reset() {
+        let mut governor = Governor::new(RunMode::FixedTicksPerSecond(10.0));
+        let start = Instant::now();
+        governor.due(start);
+        governor.set_mode(RunMode::FixedTicksPerSecond(20.0));
+
+        // The time before the reset is not caught up
+        assert_eq!(Some(0), governor.due(start + Duration::from_secs(1)));
+        assert_eq!(Some(2), governor.due(start + Duration::from_millis(1100)));
+    }
+
+    #[test]
+    fn governor_stopped() {
+        let mut governor = Governor::new(RunMode::FixedTicksPerSecond(0.0));
+        let start = Instant::now();
+
+        assert_eq!(Some(0), governor.due(start));
+        assert_eq!(Some(0), governor.due(start + Duration::from_secs(100)));
+        assert_eq!(Duration::MAX, governor.wait(start));
+    }
+
+    #[test]
+    fn frame_budget_smoothing() {
+        let mut budget = FrameBudget::new(Duration::from_millis(20));
+        budget.record_frame(Duration::from_millis(30));
+
+        assert_eq!(Duration::from_millis(21), budget.frame_time());
+
+        budget.record_frame(Duration::from_secs(10));
+        assert!(budget.frame_time() < Duration::from_millis(30));
+        assert_eq!(MIN_FRAME_TIME, FrameBudget::new(Duration::ZERO).frame_time());
+        assert_eq!(Duration::from_millis(5), FrameBudget::new(Duration::from_millis(10)).with_share(0.5).budget());
+        assert_eq!(Duration::ZERO, FrameBudget::new(Duration::from_millis(10)).with_share(-1.0).budget());
+    }
+
+    #[test]
+    fn frame_budget_fits() {
+        let budget = FrameBudget::new(Duration::from_millis(10)).with_share(0.0);
+
+        // A step is always run while any are due even without any time for it
+        assert!(budget.fits(None, 0, Duration::from_secs(1)));
+        assert!(!budget.fits(None, 1, Duration::ZERO));
+        assert!(!budget.fits(Some(0), 0, Duration::ZERO));
+    }
+
+    #[test]
+    fn tick_rate_meter_window() {
+        let mut meter = TickRateMeter::new(Duration::from_secs(1));
+        let start = Instant::now();
+        assert_eq!(0.0, meter.ticks_per_second());
+
+        for (millis, tick) in [(0, 0), (500, 100), (1000, 110), (1500, 120)] {
+            meter.record(start + Duration::from_millis(millis), tick);
+        }
+        // Only the second before the latest record counts
+        assert_eq!(20.0, meter.ticks_per_second());
+
+        meter.record(start + Duration::from_millis(1600), 5);
+        assert_eq!(0.0, meter.ticks_per_second());
+        meter.record(start + Duration::from_millis(1700), 15);
+        assert_eq!(100.0, meter.ticks_per_second());
+    }
+
+    #[test]
+    fn run_until_governed_rate() {
+        let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        let mut simulation = Simulation::new(board, Population::new(crate::board::Size::new(2, 2)), Default::default()).unwrap();
+        let start = Instant::now();
+
+        assert_eq!(10, simulation.run_until_governed(&mut Governor::new(RunMode::FixedTicksPerSecond(1000.0)), TickLimit(10)));
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert_eq!(3, simulation.run_until_governed(&mut Governor::new(RunMode::MaxSpeed), TickLimit(13)));
+        assert_eq!(0, simulation.run_until_governed(&mut Governor::new(RunMode::FixedTicksPerSecond(0.0)), TickLimit(20)));
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+
+use crate::memory::{deque_bytes, HeapSize};
+
+/// A ring buffer keeping the latest states of a simulation so it can go back in time,
+/// once it is full the oldest state is dropped for every new state
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct History<T> {
+    /// The largest number of states kept
+    capacity: usize,
+    /// The states from the oldest to the newest
+    states: VecDeque<T>,
+}
+
+impl<T> History<T> {
+    /// Creates a new empty history keeping at most a number of states
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity, states: VecDeque::with_capacity(capacity) }
+    }
+
+    /// Returns the largest number of states kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of states kept
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Changes the largest number of states kept, the oldest states are dropped if there are too many
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.states.len() > capacity {
+            self.states.pop_front();
+        }
+    }
+
+    /// Adds the newest state dropping the oldest if the history is full
+    pub fn push(&mut self, state: T) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.states.len() == self.capacity {
+            self.states.pop_front();
+        }
+
+        self.states.push_back(state);
+    }
+
+    /// Removes a number of the newest states and returns the oldest of them together with the number removed,
+    /// if there are fewer states than asked for all of them are removed. Returns None if nothing was removed
+    pub fn rewind(&mut self, steps: usize) -> Option<(T, usize)> {
+        let steps = steps.min(self.states.len());
+        if steps == 0 {
+            return None;
+        }
+
+        let state = self.states.drain(self.states.len() - steps..).next()?;
+
+        Some((state, steps))
+    }
+}
+
+impl<T: HeapSize> HeapSize for History<T> {
+    fn heap_bytes(&self) -> usize {
+        deque_bytes(&self.states) + self.states.iter().map(T::heap_bytes).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_push() {
+        let mut history = History::new(3);
+        for state in 0..5 {
+            history.push(state);
+        }
+
+        assert_eq!(3, history.len());
+        assert_eq!(vec![2, 3, 4], history.states.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn history_push_disabled() {
+        let mut history = History::new(0);
+        history.push(1);
+
+        assert_eq!(0, history.len());
+    }
+
+    #[test]
+    fn history_rewind() {
+        let mut history = History::new(5);
+        for state in 0..4 {
+            history.push(state);
+        }
+
+        assert_eq!(None, history.rewind(0));
+        assert_eq!(Some((2, 2)), history.rewind(2));
+        assert_eq!(Some((0, 2)), history.rewind(10));
+        assert_eq!(None, history.rewind(1));
+    }
+
+    #[test]
+    fn history_set_capacity() {
+        let mut history = History::new(4);
+        for state in 0..4 {
+            history.push(state);
+        }
+        history.set_capacity(2);
+
+        assert_eq!(2, history.capacity());
+        assert_eq!(vec![2, 3], history.states.iter().copied().collect::<Vec<_>>());
+    }
+}
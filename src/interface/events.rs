@@ -0,0 +1,314 @@
+use crate::board::{Coord, Rect};
+use crate::disturbance::Resource;
+use crate::edit::{Brush, EditHistory, Tool};
+use crate::genome::Genome;
+use crate::scenario::ScenarioAction;
+use crate::simulation::Simulation;
+
+/// The number of strokes which can be undone in the window
+const UNDO_LIMIT: usize = 64;
+/// The factor the fertilizing brush multiplies the resources by at its center
+const FERTILIZE_FACTOR: f32 = 2.0;
+/// The number of steps the fertilizing brush boosts the resources for
+const FERTILIZE_DURATION: u64 = 50;
+
+/// Tracks a rectangle of cells being selected by dragging the mouse
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub(crate) struct Selection {
+    /// The cell where the drag started
+    start: Option<Coord>,
+    /// The last cell on the board the mouse was dragged over
+    current: Option<Coord>,
+}
+
+impl Selection {
+    /// Starts a new selection, nothing is selected if the mouse is not over the board
+    pub fn press(&mut self, coord: Option<Coord>) {
+        self.start = coord;
+        self.current = coord;
+    }
+
+    /// Moves the corner of the selection to a new cell, positions outside the board are ignored
+    pub fn drag(&mut self, coord: Option<Coord>) {
+        if self.start.is_some() && coord.is_some() {
+            self.current = coord;
+        }
+    }
+
+    /// Returns the rectangle currently being selected
+    pub fn rect(&self) -> Option<Rect> {
+        match (self.start, self.current) {
+            (Some(start), Some(current)) => Some(Rect::from_corners(start, current)),
+            _ => None,
+        }
+    }
+
+    /// Finishes the selection and returns the selected rectangle
+    pub fn release(&mut self) -> Option<Rect> {
+        let rect = self.rect();
+        *self = Self::default();
+
+        rect
+    }
+}
+
+/// A brush intervening in the run instead of editing the board, interventions are recorded by the simulation
+/// so a replay carries them out as well, and they cannot be undone
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Intervention {
+    /// Spawns copies of a plant in the empty cells
+    Spawn { energy: u32, genome: Genome },
+    /// Kills the plants
+    Smite,
+    /// Boosts the water for a while, or the light while erasing
+    Fertilize,
+}
+
+impl Intervention {
+    /// Creates the action of the intervention with a brush at a cell
+    fn action(&self, brush: &Brush, center: Coord, erasing: bool) -> ScenarioAction {
+        let radius = brush.radius;
+
+        match self {
+            Intervention::Spawn { energy, genome } => ScenarioAction::Spawn { center, radius, energy: *energy, genome: genome.clone() },
+            Intervention::Smite => ScenarioAction::Smite { center, radius },
+            Intervention::Fertilize => {
+                let resource = if erasing { Resource::Light } else { Resource::Water };
+                ScenarioAction::Fertilize { center, radius, resource, factor: FERTILIZE_FACTOR, duration: FERTILIZE_DURATION }
+            }
+        }
+    }
+}
+
+/// The state of the edit mode where dragging the mouse paints onto the board
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Editor {
+    /// True if dragging paints instead of selecting
+    pub enabled: bool,
+    /// The tool used by the brush
+    pub tool: Tool,
+    /// The brush to paint with
+    pub brush: Brush,
+    /// The intervention made by the brush instead of using the tool, None if the tool is used
+    pub intervention: Option<Intervention>,
+    /// The strokes which can be undone
+    history: EditHistory,
+    /// True if the brush is removing instead of adding
+    erasing: Option<bool>,
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tool: Tool::Light,
+            brush: Brush::default(),
+            intervention: None,
+            history: EditHistory::new(UNDO_LIMIT),
+            erasing: None,
+        }
+    }
+}
+
+impl Editor {
+    /// Selects a tool by its number from 1, the numbers after the tools select the interventions and other numbers are ignored.
+    /// The spawning brush spawns the genome of the plant tool if it was picked last
+    pub fn select_tool(&mut self, number: usize) {
+        let (energy, genome) = match &self.tool {
+            Tool::Plant { energy, genome } => (*energy, genome.clone()),
+            _ => (100, Genome::new(&[0.5, 0.5]).expect("The default genome is valid")),
+        };
+
+        match number {
+            1 => self.tool = Tool::Light,
+            2 => self.tool = Tool::Water,
+            3 => self.tool = Tool::Plant { energy: 100, genome: Genome::new(&[0.5, 0.5]).expect("The default genome is valid") },
+            4 => self.tool = Tool::Remove,
+            5 => {
+                self.intervention = Some(Intervention::Spawn { energy, genome });
+                return;
+            }
+            6 => {
+                self.intervention = Some(Intervention::Smite);
+                return;
+            }
+            7 => {
+                self.intervention = Some(Intervention::Fertilize);
+                return;
+            }
+            _ => return,
+        }
+        self.intervention = None;
+    }
+
+    /// Turns on the edit mode with the plant tool planting copies of a genome
+    pub fn plant_copies(&mut self, energy: u32, genome: Genome) {
+        self.release();
+        self.tool = Tool::Plant { energy, genome };
+        self.intervention = None;
+        self.enabled = true;
+    }
+
+    /// Changes the radius of the brush by a number of cells, the radius never goes below 0
+    pub fn resize(&mut self, change: f32) {
+        self.brush.radius = (self.brush.radius + change).max(0.0);
+    }
+
+    /// Multiplies the strength of the brush
+    pub fn scale_strength(&mut self, factor: f32) {
+        self.brush.strength = (self.brush.strength * factor).clamp(0.001, 10.0);
+    }
+
+    /// Starts a stroke at a cell, if erasing the light and water tools remove instead of adding
+    pub fn press(&mut self, simulation: &mut Simulation, coord: Option<Coord>, erasing: bool) {
+        self.erasing = Some(erasing);
+        self.drag(simulation, coord);
+    }
+
+    /// Continues the stroke at a new cell, positions outside the board are ignored
+    pub fn drag(&mut self, simulation: &mut Simulation, coord: Option<Coord>) {
+        let (Some(erasing), Some(coord)) = (self.erasing, coord) else {
+            return;
+        };
+
+        if let Some(intervention) = &self.intervention {
+            simulation.intervene(intervention.action(&self.brush, coord, erasing)).expect("The tools never change the settings");
+            return;
+        }
+
+        let brush = if erasing { Brush { strength: -self.brush.strength, ..self.brush } } else { self.brush };
+        self.history.paint(simulation, &self.tool, &brush, coord);
+    }
+
+    /// Finishes the stroke
+    pub fn release(&mut self) {
+        self.erasing = None;
+        self.history.end();
+    }
+
+    /// Undoes the latest stroke
+    pub fn undo(&mut self, simulation: &mut Simulation) {
+        self.erasing = None;
+        self.history.undo(simulation);
+    }
+
+    /// Creates the line of text describing the editor
+    pub fn status(&self) -> String {
+        let tool = match (&self.intervention, &self.tool) {
+            (Some(Intervention::Spawn { .. }), _) => "SPAWN",
+            (Some(Intervention::Smite), _) => "SMITE",
+            (Some(Intervention::Fertilize), _) => "FERTILIZE",
+            (None, Tool::Light) => "LIGHT",
+            (None, Tool::Water) => "WATER",
+            (None, Tool::Plant { .. }) => "PLANT",
+            (None, Tool::Remove) => "REMOVE",
+        };
+
+        format!("EDIT {} R{:.0} S{:.3} UNDO {}", tool, self.brush.radius, self.brush.strength, self.history.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selection_drag() {
+        let mut selection = Selection::default();
+
+        assert_eq!(None, selection.rect());
+
+        selection.press(Some(Coord::new(3, 3)));
+        selection.drag(Some(Coord::new(1, 4)));
+
+        assert_eq!(Some(Rect::new(1, 3, 3, 2)), selection.rect());
+
+        selection.drag(None);
+
+        assert_eq!(Some(Rect::new(1, 3, 3, 2)), selection.rect());
+        assert_eq!(Some(Rect::new(1, 3, 3, 2)), selection.release());
+        assert_eq!(None, selection.rect());
+    }
+
+    #[test]
+    fn selection_press_outside() {
+        let mut selection = Selection::default();
+        selection.press(None);
+        selection.drag(Some(Coord::new(1, 4)));
+
+        assert_eq!(None, selection.release());
+    }
+
+    #[test]
+    fn editor_stroke() {
+        let board = crate::board::BoardBuilder::new().size(3, 3).light_uniform(0.5).build().unwrap();
+        let size = board.fields.size;
+        let mut simulation = Simulation::new(board, crate::population::Population::new(size), Default::default()).unwrap();
+        let mut editor = Editor { brush: Brush::new(0.0, 0.25), ..Default::default() };
+
+        // Dragging without pressing does nothing
+        editor.drag(&mut simulation, Some(Coord::new(0, 0)));
+        editor.press(&mut simulation, Some(Coord::new(1, 1)), true);
+        editor.drag(&mut simulation, Some(Coord::new(2, 1)));
+        editor.release();
+
+        assert_eq!(&[0.5, 0.5, 0.5, 0.5, 0.25, 0.25, 0.5, 0.5, 0.5], simulation.light());
+        assert_eq!("EDIT LIGHT R0 S0.250 UNDO 1", editor.status());
+
+        editor.undo(&mut simulation);
+
+        assert_eq!(&[0.5; 9], simulation.light());
+    }
+
+    #[test]
+    fn editor_plant_copies() {
+        let mut editor = Editor::default();
+        let genome = Genome::new(&[0.1, 0.9]).unwrap();
+        editor.plant_copies(30, genome.clone());
+
+        assert!(editor.enabled);
+        assert_eq!(Tool::Plant { energy: 30, genome }, editor.tool);
+    }
+
+    #[test]
+    fn editor_interventions() {
+        let board = crate::board::BoardBuilder::new().size(3, 3).light_uniform(0.5).build().unwrap();
+        let size = board.fields.size;
+        let mut simulation = Simulation::new(board, crate::population::Population::new(size), Default::default()).unwrap();
+        let mut editor = Editor { brush: Brush::new(1.0, 0.25), ..Default::default() };
+        editor.plant_copies(30, Genome::new(&[0.1, 0.9]).unwrap());
+        editor.select_tool(5);
+        editor.press(&mut simulation, Some(Coord::new(1, 1)), false);
+        editor.release();
+
+        assert_eq!(5, simulation.population().count());
+        assert_eq!("EDIT SPAWN R1 S0.250 UNDO 0", editor.status());
+
+        editor.select_tool(6);
+        editor.press(&mut simulation, Some(Coord::new(0, 1)), false);
+        editor.release();
+        editor.select_tool(7);
+        editor.press(&mut simulation, Some(Coord::new(2, 2)), true);
+        editor.release();
+
+        assert_eq!(3, simulation.population().count());
+        assert_eq!(1.0, simulation.board().fields.light[8]);
+        assert_eq!(3, simulation.interventions().events().len());
+
+        editor.select_tool(1);
+
+        assert_eq!(None, editor.intervention);
+    }
+
+    #[test]
+    fn editor_settings() {
+        let mut editor = Editor::default();
+        editor.select_tool(4);
+        editor.select_tool(9);
+        editor.resize(-5.0);
+        editor.scale_strength(2.0);
+
+        assert_eq!(Tool::Remove, editor.tool);
+        assert_eq!(Brush::new(0.0, 0.1), editor.brush);
+    }
+}
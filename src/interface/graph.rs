@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use crate::simulation::Simulation;
+
+use super::render::{Frame, PANEL, TEXT};
+
+/// The space in pixels around the graphs and between them
+const PADDING: usize = 4;
+/// The height in pixels of the label above every graph
+const LABEL_HEIGHT: usize = 14;
+/// The height in pixels of the plot of every graph
+const PLOT_HEIGHT: usize = 30;
+/// The color of the population graph
+const POPULATION: u32 = 0x60C060;
+/// The color of the mean energy graph
+const ENERGY: u32 = 0xE0C040;
+/// The color of the species graph
+const SPECIES: u32 = 0x60A0E0;
+/// The color of the baseline of a plot
+const AXIS: u32 = 0x404040;
+
+/// A graph given by its name, its color and the value it shows of every sample
+type Series = (&'static str, u32, fn(&Sample) -> f32);
+
+/// The values shown in the graphs at a single tick
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Sample {
+    /// The tick of the sample
+    pub tick: u64,
+    /// The number of living plants
+    pub population: usize,
+    /// The mean energy of the living plants
+    pub mean_energy: f32,
+    /// The number of living species, 0 if species are not tracked
+    pub species: usize,
+}
+
+impl Sample {
+    /// Takes a sample of the current state of a simulation
+    pub fn new(simulation: &Simulation) -> Self {
+        let population = simulation.population();
+        let count = population.count();
+        let mean_energy = if count == 0 {
+            0.0
+        } else {
+            population.iter().map(|(_, plant)| plant.energy as f64).sum::<f64>() as f32 / count as f32
+        };
+
+        Self {
+            tick: simulation.tick(),
+            population: count,
+            mean_energy,
+            species: simulation.species().map_or(0, |species| species.living_count()),
+        }
+    }
+}
+
+/// The latest samples of a simulation drawn as line graphs of the population, mean energy and species over time
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Graphs {
+    /// The largest number of samples kept, older samples are dropped
+    capacity: usize,
+    /// The samples in the order of their ticks
+    samples: VecDeque<Sample>,
+}
+
+impl Graphs {
+    /// Creates graphs without any samples keeping at most a number of samples, the capacity is at least 2
+    pub fn new(capacity: usize) -> Self {
+        Self { capacity: capacity.max(2), samples: VecDeque::new() }
+    }
+
+    /// Adds a sample of a simulation. Nothing is added if the tick has not changed and
+    /// if the simulation has gone back in time the samples after its tick are dropped
+    pub fn push(&mut self, sample: Sample) {
+        let tick = sample.tick;
+
+        while self.samples.back().is_some_and(|sample| sample.tick > tick) {
+            self.samples.pop_back();
+        }
+        if self.samples.back().is_some_and(|sample| sample.tick == tick) {
+            return;
+        }
+
+        self.samples.push_back(sample);
+        if self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Finds the size in pixels of the graphs when drawn with some width
+    pub fn size(width: usize) -> (usize, usize) {
+        (width, PADDING + 3 * (LABEL_HEIGHT + PLOT_HEIGHT + PADDING))
+    }
+
+    /// Draws the graphs on a dark panel with the top left corner at a position, every graph is scaled
+    /// from 0 to its largest value among the samples and the latest value is written above it
+    pub fn draw(&self, frame: &mut Frame, x: isize, y: isize, width: usize) {
+        let (w, h) = Self::size(width);
+        frame.fill_rect(x, y, w, h, PANEL);
+
+        let series: [Series; 3] = [
+            ("PLANTS", POPULATION, |sample| sample.population as f32),
+            ("ENERGY", ENERGY, |sample| sample.mean_energy),
+            ("SPECIES", SPECIES, |sample| sample.species as f32),
+        ];
+
+        let plot_w = w.saturating_sub(2 * PADDING);
+        for (index, (name, color, value)) in series.into_iter().enumerate() {
+            let top = y + (PADDING + index * (LABEL_HEIGHT + PLOT_HEIGHT + PADDING)) as isize;
+            let label = match self.samples.back() {
+                Some(latest) => format!("{} {:.0}", name, value(latest)),
+                None => name.to_string(),
+            };
+            frame.draw_text(x + PADDING as isize, top, &label, super::TEXT_SCALE, TEXT);
+
+            let plot_top = top + LABEL_HEIGHT as isize;
+            let bottom = plot_top + PLOT_HEIGHT as isize - 1;
+            frame.fill_rect(x + PADDING as isize, bottom, plot_w, 1, AXIS);
+
+            let max = self.samples.iter().map(value).fold(0.0, f32::max);
+            let points: Vec<(isize, isize)> = self.samples.iter()
+                .enumerate()
+                .map(|(position, sample)| {
+                    let px = x + PADDING as isize + (position * plot_w.saturating_sub(1) / (self.capacity - 1)) as isize;
+                    let fraction = if max > 0.0 { value(sample) / max } else { 0.0 };
+                    let py = bottom - (fraction * (PLOT_HEIGHT - 1) as f32).round() as isize;
+                    (px, py)
+                })
+                .collect();
+
+            for pair in points.windows(2) {
+                frame.draw_line(pair[0], pair[1], color);
+            }
+            if let [point] = points[..] {
+                frame.draw_line(point, point, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(3, 3).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(20, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(40, Genome::new(&[1.0, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn sample_new() {
+        let sample = Sample::new(&simulation());
+
+        assert_eq!(Sample { tick: 0, population: 2, mean_energy: 30.0, species: 0 }, sample);
+    }
+
+    #[test]
+    fn graphs_push() {
+        let mut simulation = simulation();
+        let mut graphs = Graphs::new(3);
+        graphs.push(Sample::new(&simulation));
+        graphs.push(Sample::new(&simulation));
+        for _ in 0..3 {
+            simulation.step();
+            graphs.push(Sample::new(&simulation));
+        }
+
+        assert_eq!(vec![1, 2, 3], graphs.samples.iter().map(|sample| sample.tick).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn graphs_push_rewind() {
+        let mut simulation = simulation();
+        let mut graphs = Graphs::new(10);
+        let start = simulation.clone();
+        for _ in 0..3 {
+            simulation.step();
+            graphs.push(Sample::new(&simulation));
+        }
+        graphs.push(Sample::new(&start));
+
+        assert_eq!(vec![0], graphs.samples.iter().map(|sample| sample.tick).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn graphs_draw() {
+        let mut simulation = simulation();
+        let mut graphs = Graphs::new(10);
+        for _ in 0..5 {
+            simulation.step();
+            graphs.push(Sample::new(&simulation));
+        }
+        let (w, h) = Graphs::size(100);
+        let mut frame = Frame::new(w, h);
+        graphs.draw(&mut frame, 0, 0, w);
+
+        assert_eq!(PANEL, frame.pixels()[0]);
+        assert!(frame.pixels().contains(&POPULATION));
+        assert!(frame.pixels().contains(&ENERGY));
+        assert!(frame.pixels().contains(&TEXT));
+    }
+}
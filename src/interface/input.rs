@@ -0,0 +1,416 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use winit::event::VirtualKeyCode;
+
+/// The number of actions which can be bound to keys
+pub const ACTIONS: usize = 32;
+
+/// Something the user can do in the window with a key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Selects the previous gene while the genome panel is open, or the plant above while the lineage panel is open
+    PreviousGene,
+    /// Selects the next gene while the genome panel is open, or the plant below while the lineage panel is open
+    NextGene,
+    /// Decreases the selected gene while the genome panel is open, or selects the parent while the lineage panel is open
+    DecreaseGene,
+    /// Increases the selected gene while the genome panel is open, or selects the first child while the lineage panel is open
+    IncreaseGene,
+    /// Plants copies of the genome being edited
+    PlantGenome,
+    /// Clears the selection and closes the genome panel and the lineage panel
+    Clear,
+    /// Copies the genome of the plant under the mouse into the genome panel
+    Inspect,
+    /// Opens the lineage panel on the plant under the mouse
+    Lineage,
+    /// Toggles the edit mode
+    Edit,
+    /// Toggles the graphs
+    Graphs,
+    /// Switches to the next render mode
+    RenderMode,
+    /// Switches to the next built-in palette
+    Palette,
+    /// Saves the window as a png, this requires the image feature
+    Screenshot,
+    /// Picks the light brush
+    LightTool,
+    /// Picks the water brush
+    WaterTool,
+    /// Picks the planting brush
+    PlantTool,
+    /// Picks the brush removing plants
+    RemoveTool,
+    /// Picks the brush spawning copies of the plant of the planting brush, spawns are recorded for replays
+    SpawnTool,
+    /// Picks the brush killing plants, kills are recorded for replays
+    SmiteTool,
+    /// Picks the brush boosting the water for a while, or the light while erasing, boosts are recorded for replays
+    FertilizeTool,
+    /// Makes the brush smaller
+    ShrinkBrush,
+    /// Makes the brush larger
+    GrowBrush,
+    /// Halves the strength of the brush
+    WeakenBrush,
+    /// Doubles the strength of the brush
+    StrengthenBrush,
+    /// Undoes the latest stroke while editing
+    Undo,
+    /// Removes most of the plants at random such that the survivors found the following generations
+    Bottleneck,
+    /// Restricts the simulation to the selected region, or simulates the whole board again if no region is selected
+    ActiveRegion,
+    /// Pauses or resumes the simulation, this requires the gui-panel feature
+    Pause,
+    /// Runs a single step, with the gui-panel feature the simulation is paused first
+    Step,
+    /// Pauses the simulation and undoes the latest step, this requires the gui-panel feature
+    StepBack,
+    /// Runs more steps every tick of the run mode, this requires the gui-panel feature
+    SpeedUp,
+    /// Runs fewer steps every tick of the run mode, this requires the gui-panel feature
+    SlowDown,
+}
+
+impl Action {
+    /// All actions in the order they are looked up, the actions of the genome panel come first such that
+    /// they win over other actions bound to the same keys while the panel is open
+    pub const ALL: [Action; ACTIONS] = [
+        Action::PreviousGene,
+        Action::NextGene,
+        Action::DecreaseGene,
+        Action::IncreaseGene,
+        Action::PlantGenome,
+        Action::Clear,
+        Action::Inspect,
+        Action::Lineage,
+        Action::Edit,
+        Action::Graphs,
+        Action::RenderMode,
+        Action::Palette,
+        Action::Screenshot,
+        Action::LightTool,
+        Action::WaterTool,
+        Action::PlantTool,
+        Action::RemoveTool,
+        Action::SpawnTool,
+        Action::SmiteTool,
+        Action::FertilizeTool,
+        Action::ShrinkBrush,
+        Action::GrowBrush,
+        Action::WeakenBrush,
+        Action::StrengthenBrush,
+        Action::Undo,
+        Action::Bottleneck,
+        Action::ActiveRegion,
+        Action::Pause,
+        Action::Step,
+        Action::StepBack,
+        Action::SpeedUp,
+        Action::SlowDown,
+    ];
+
+    /// Returns the name of the action used in input map files
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::interface::input::Action;
+    /// 
+    /// assert_eq!("render_mode", Action::RenderMode.name());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::PreviousGene => "previous_gene",
+            Action::NextGene => "next_gene",
+            Action::DecreaseGene => "decrease_gene",
+            Action::IncreaseGene => "increase_gene",
+            Action::PlantGenome => "plant_genome",
+            Action::Clear => "clear",
+            Action::Inspect => "inspect",
+            Action::Lineage => "lineage",
+            Action::Edit => "edit",
+            Action::Graphs => "graphs",
+            Action::RenderMode => "render_mode",
+            Action::Palette => "palette",
+            Action::Screenshot => "screenshot",
+            Action::LightTool => "light_tool",
+            Action::WaterTool => "water_tool",
+            Action::PlantTool => "plant_tool",
+            Action::RemoveTool => "remove_tool",
+            Action::SpawnTool => "spawn_tool",
+            Action::SmiteTool => "smite_tool",
+            Action::FertilizeTool => "fertilize_tool",
+            Action::ShrinkBrush => "shrink_brush",
+            Action::GrowBrush => "grow_brush",
+            Action::WeakenBrush => "weaken_brush",
+            Action::StrengthenBrush => "strengthen_brush",
+            Action::Undo => "undo",
+            Action::Bottleneck => "bottleneck",
+            Action::ActiveRegion => "active_region",
+            Action::Pause => "pause",
+            Action::Step => "step",
+            Action::StepBack => "step_back",
+            Action::SpeedUp => "speed_up",
+            Action::SlowDown => "slow_down",
+        }
+    }
+
+    /// Returns true if the action only applies while the genome panel or the lineage panel is open
+    pub fn needs_inspector(&self) -> bool {
+        matches!(self, Action::PreviousGene | Action::NextGene | Action::DecreaseGene | Action::IncreaseGene)
+    }
+
+    /// Returns the position of the action in ALL
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Finds the action with a name
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+/// The keys which can be named in input map files, the names are the names of the keys in winit
+const KEYS: [VirtualKeyCode; 70] = [
+    VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3, VirtualKeyCode::Key4, VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6, VirtualKeyCode::Key7, VirtualKeyCode::Key8, VirtualKeyCode::Key9, VirtualKeyCode::Key0,
+    VirtualKeyCode::A, VirtualKeyCode::B, VirtualKeyCode::C, VirtualKeyCode::D, VirtualKeyCode::E, VirtualKeyCode::F,
+    VirtualKeyCode::G, VirtualKeyCode::H, VirtualKeyCode::I, VirtualKeyCode::J, VirtualKeyCode::K, VirtualKeyCode::L,
+    VirtualKeyCode::M, VirtualKeyCode::N, VirtualKeyCode::O, VirtualKeyCode::P, VirtualKeyCode::Q, VirtualKeyCode::R,
+    VirtualKeyCode::S, VirtualKeyCode::T, VirtualKeyCode::U, VirtualKeyCode::V, VirtualKeyCode::W, VirtualKeyCode::X,
+    VirtualKeyCode::Y, VirtualKeyCode::Z,
+    VirtualKeyCode::F1, VirtualKeyCode::F2, VirtualKeyCode::F3, VirtualKeyCode::F4, VirtualKeyCode::F5, VirtualKeyCode::F6,
+    VirtualKeyCode::F7, VirtualKeyCode::F8, VirtualKeyCode::F9, VirtualKeyCode::F10, VirtualKeyCode::F11, VirtualKeyCode::F12,
+    VirtualKeyCode::Escape, VirtualKeyCode::Space, VirtualKeyCode::Return, VirtualKeyCode::Tab, VirtualKeyCode::Back,
+    VirtualKeyCode::Delete, VirtualKeyCode::Home, VirtualKeyCode::End, VirtualKeyCode::PageUp, VirtualKeyCode::PageDown,
+    VirtualKeyCode::Left, VirtualKeyCode::Up, VirtualKeyCode::Right, VirtualKeyCode::Down,
+    VirtualKeyCode::Minus, VirtualKeyCode::Equals, VirtualKeyCode::LBracket, VirtualKeyCode::RBracket,
+    VirtualKeyCode::Comma, VirtualKeyCode::Period, VirtualKeyCode::Slash, VirtualKeyCode::Semicolon,
+];
+
+/// Finds the key with a name, the case of the name does not matter
+fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+    KEYS.into_iter().find(|key| format!("{:?}", key).eq_ignore_ascii_case(name))
+}
+
+/// The keys bound to the actions of the window, every action has at most one key. Bindings are given
+/// in code with bind or read from a text file with a line "action = key" for every binding to change,
+/// empty lines and lines starting with # are skipped and the key "none" unbinds the action
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputMap {
+    /// The key of every action in the order of Action::ALL
+    keys: [Option<VirtualKeyCode>; ACTIONS],
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let mut map = Self { keys: [None; ACTIONS] };
+        map.bind(Action::PreviousGene, Some(VirtualKeyCode::Up))
+            .bind(Action::NextGene, Some(VirtualKeyCode::Down))
+            .bind(Action::DecreaseGene, Some(VirtualKeyCode::Left))
+            .bind(Action::IncreaseGene, Some(VirtualKeyCode::Right))
+            .bind(Action::PlantGenome, Some(VirtualKeyCode::Return))
+            .bind(Action::Clear, Some(VirtualKeyCode::Escape))
+            .bind(Action::Inspect, Some(VirtualKeyCode::I))
+            .bind(Action::Lineage, Some(VirtualKeyCode::L))
+            .bind(Action::Edit, Some(VirtualKeyCode::E))
+            .bind(Action::Graphs, Some(VirtualKeyCode::G))
+            .bind(Action::RenderMode, Some(VirtualKeyCode::V))
+            .bind(Action::Palette, Some(VirtualKeyCode::C))
+            .bind(Action::Screenshot, Some(VirtualKeyCode::F12))
+            .bind(Action::LightTool, Some(VirtualKeyCode::Key1))
+            .bind(Action::WaterTool, Some(VirtualKeyCode::Key2))
+            .bind(Action::PlantTool, Some(VirtualKeyCode::Key3))
+            .bind(Action::RemoveTool, Some(VirtualKeyCode::Key4))
+            .bind(Action::SpawnTool, Some(VirtualKeyCode::Key5))
+            .bind(Action::SmiteTool, Some(VirtualKeyCode::Key6))
+            .bind(Action::FertilizeTool, Some(VirtualKeyCode::Key7))
+            .bind(Action::ShrinkBrush, Some(VirtualKeyCode::LBracket))
+            .bind(Action::GrowBrush, Some(VirtualKeyCode::RBracket))
+            .bind(Action::WeakenBrush, Some(VirtualKeyCode::Minus))
+            .bind(Action::StrengthenBrush, Some(VirtualKeyCode::Equals))
+            .bind(Action::Undo, Some(VirtualKeyCode::Z))
+            .bind(Action::Bottleneck, Some(VirtualKeyCode::B))
+            .bind(Action::ActiveRegion, Some(VirtualKeyCode::F))
+            .bind(Action::Pause, Some(VirtualKeyCode::Space))
+            .bind(Action::Step, Some(VirtualKeyCode::Right))
+            .bind(Action::StepBack, Some(VirtualKeyCode::Left))
+            .bind(Action::SpeedUp, Some(VirtualKeyCode::PageUp))
+            .bind(Action::SlowDown, Some(VirtualKeyCode::PageDown));
+
+        map
+    }
+}
+
+impl InputMap {
+    /// Binds an action to a key, the key may be bound to other actions as well
+    /// 
+    /// # Parameters
+    /// 
+    /// action: The action to bind
+    /// key: The key of the action, None unbinds the action
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::interface::input::{Action, InputMap};
+    /// use winit::event::VirtualKeyCode;
+    /// 
+    /// let mut input = InputMap::default();
+    /// input.bind(Action::Pause, Some(VirtualKeyCode::P)).bind(Action::Graphs, None);
+    /// 
+    /// assert_eq!(Some(Action::Pause), input.action(VirtualKeyCode::P, false));
+    /// assert_eq!(None, input.key(Action::Graphs));
+    /// ```
+    pub fn bind(&mut self, action: Action, key: Option<VirtualKeyCode>) -> &mut Self {
+        self.keys[action.index()] = key;
+        self
+    }
+
+    /// Returns the key bound to an action, None if the action is not bound
+    /// 
+    /// # Parameters
+    /// 
+    /// action: The action
+    pub fn key(&self, action: Action) -> Option<VirtualKeyCode> {
+        self.keys[action.index()]
+    }
+
+    /// Finds the action of a key, the actions of the genome panel are skipped unless it or the lineage panel is open
+    /// 
+    /// # Parameters
+    /// 
+    /// key: The key which was pressed
+    /// inspecting: True if the genome panel or the lineage panel is open
+    pub fn action(&self, key: VirtualKeyCode, inspecting: bool) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| self.key(*action) == Some(key) && (inspecting || !action.needs_inspector()))
+    }
+
+    /// Changes the bindings given in the text of an input map file, the actions not named keep their keys
+    /// 
+    /// # Parameters
+    /// 
+    /// text: The lines of the file
+    /// 
+    /// # Errors
+    /// 
+    /// InputMapError::Line: This will occur if a line is not of the form "action = key"
+    /// 
+    /// InputMapError::Action: This will occur if there is no action with the name
+    /// 
+    /// InputMapError::Key: This will occur if there is no key with the name
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::interface::input::{Action, InputMap};
+    /// use winit::event::VirtualKeyCode;
+    /// 
+    /// let input = InputMap::default().parse("# Keys for a left handed layout\npause = P\nscreenshot = none\n").unwrap();
+    /// 
+    /// assert_eq!(Some(VirtualKeyCode::P), input.key(Action::Pause));
+    /// assert_eq!(None, input.key(Action::Screenshot));
+    /// assert_eq!(Some(VirtualKeyCode::V), input.key(Action::RenderMode));
+    /// ```
+    pub fn parse(mut self, text: &str) -> Result<Self, InputMapError> {
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (action, key) = line.split_once('=').ok_or_else(|| InputMapError::Line { line: number + 1, text: line.to_string() })?;
+            let (action, key) = (action.trim(), key.trim());
+            let action = Action::from_name(action).ok_or_else(|| InputMapError::Action { name: action.to_string() })?;
+            let key = match key.eq_ignore_ascii_case("none") {
+                true => None,
+                false => Some(key_from_name(key).ok_or_else(|| InputMapError::Key { name: key.to_string() })?),
+            };
+
+            self.bind(action, key);
+        }
+
+        Ok(self)
+    }
+
+    /// Reads an input map file on top of the default bindings
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the file
+    /// 
+    /// # Errors
+    /// 
+    /// InputMapError::Io: This will occur if the file could not be read
+    /// 
+    /// See parse for the other errors
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputMapError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| InputMapError::Io { path: path.to_path_buf(), message: error.to_string() })?;
+
+        Self::default().parse(&text)
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum InputMapError {
+    #[error("Unable to read {:?}: {}", path, message)]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("Line {:?} should be of the form \"action = key\" but is {:?}", line, text)]
+    Line {
+        line: usize,
+        text: String,
+    },
+    #[error("There is no action named {:?}", name)]
+    Action {
+        name: String,
+    },
+    #[error("There is no key named {:?}", name)]
+    Key {
+        name: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_names_unique() {
+        for (index, action) in Action::ALL.iter().enumerate() {
+            assert_eq!(index, action.index());
+            assert_eq!(Some(*action), Action::from_name(action.name()));
+        }
+    }
+
+    #[test]
+    fn input_map_inspector_first() {
+        let input = InputMap::default();
+
+        assert_eq!(Some(Action::DecreaseGene), input.action(VirtualKeyCode::Left, true));
+        assert_eq!(Some(Action::StepBack), input.action(VirtualKeyCode::Left, false));
+        assert_eq!(Some(Action::Step), input.action(VirtualKeyCode::Right, false));
+        assert_eq!(Some(Action::Lineage), input.action(VirtualKeyCode::L, false));
+        assert_eq!(None, input.action(VirtualKeyCode::Q, false));
+    }
+
+    #[test]
+    fn input_map_parse_errors() {
+        let parse = |text| InputMap::default().parse(text);
+
+        assert_eq!(Err(InputMapError::Line { line: 2, text: "pause P".to_string() }), parse("\npause P"));
+        assert_eq!(Err(InputMapError::Action { name: "jump".to_string() }), parse("jump = Space"));
+        assert_eq!(Err(InputMapError::Key { name: "Hyper".to_string() }), parse("pause = Hyper"));
+        assert_eq!(Ok(VirtualKeyCode::PageDown), parse("speed_up = pagedown").map(|input| input.key(Action::SpeedUp).unwrap()));
+        assert!(matches!(InputMap::load("missing_input_map.txt"), Err(InputMapError::Io { .. })));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::board::Coord;
+use crate::genes::GeneRegistry;
+use crate::genome::Genome;
+use crate::population::{PlantId, Population};
+
+/// The amount a gene changes by for every key press
+const GENE_STEP: f32 = 0.05;
+
+/// A copy of the genome of a plant picked on the board which can be edited gene by gene
+/// and planted again with the plant tool of the editor
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GenomeEditor {
+    /// The cell of the plant the genome was copied from
+    coord: Coord,
+    /// The id of the plant the genome was copied from
+    id: PlantId,
+    /// The energy of the plant the genome was copied from, the planted copies get the same energy
+    energy: u32,
+    /// The edited values of the genes
+    genes: Vec<f32>,
+    /// The index of the gene being edited
+    gene: usize,
+}
+
+impl GenomeEditor {
+    /// Copies the genome of the plant in a cell, returns None if the position is outside the board or the cell is empty
+    pub fn open(population: &Population, coord: Option<Coord>) -> Option<Self> {
+        let coord = coord?;
+        let plant = population.get(coord)?;
+
+        Some(Self { coord, id: plant.id(), energy: plant.energy, genes: plant.genome.genes().to_vec(), gene: 0 })
+    }
+
+    /// Returns the cell of the plant the genome was copied from
+    pub fn coord(&self) -> Coord {
+        self.coord
+    }
+
+    /// Returns the energy the planted copies get
+    pub fn energy(&self) -> u32 {
+        self.energy
+    }
+
+    /// Moves the gene being edited by a number of genes, wrapping around at the ends
+    pub fn select(&mut self, change: isize) {
+        let len = self.genes.len() as isize;
+
+        self.gene = (self.gene as isize + change).rem_euclid(len) as usize;
+    }
+
+    /// Changes the gene being edited by a number of steps, the gene stays between 0 and 1
+    pub fn adjust(&mut self, steps: f32) {
+        let gene = &mut self.genes[self.gene];
+
+        // Round to avoid drifting away from the values reachable with the steps
+        *gene = ((*gene + steps * GENE_STEP).clamp(0.0, 1.0) * 1000.0).round() / 1000.0;
+    }
+
+    /// Creates the edited genome
+    pub fn genome(&self) -> Genome {
+        Genome::new(&self.genes).expect("The genes are copied from a valid genome and kept between 0 and 1")
+    }
+
+    /// Creates the lines of text shown in the genome panel with the name of every gene, the gene being edited is marked
+    pub fn lines(&self, registry: &GeneRegistry) -> Vec<String> {
+        let mut lines = vec![format!("PLANT {} ENERGY {}", self.id.0, self.energy)];
+
+        lines.extend(self.genes.iter().enumerate().map(|(index, gene)| {
+            format!("{}{} {:.3}", if index == self.gene { ">" } else { " " }, registry.name(index).to_uppercase(), gene)
+        }));
+        lines.push("ENTER PLANT COPIES".to_string());
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use crate::population::Plant;
+
+    fn editor() -> GenomeEditor {
+        let mut population = Population::new(Size::new(3, 3));
+        population.insert(Coord::new(1, 2), Plant::new(40, Genome::new(&[0.5, 0.98, 0.1]).unwrap()));
+
+        GenomeEditor::open(&population, Some(Coord::new(1, 2))).unwrap()
+    }
+
+    #[test]
+    fn genome_editor_open() {
+        let population = Population::new(Size::new(3, 3));
+
+        assert_eq!(None, GenomeEditor::open(&population, Some(Coord::new(1, 2))));
+        assert_eq!(None, GenomeEditor::open(&population, None));
+        assert_eq!(Coord::new(1, 2), editor().coord());
+        assert_eq!(40, editor().energy());
+    }
+
+    #[test]
+    fn genome_editor_adjust() {
+        let registry = GeneRegistry::default();
+        let mut editor = editor();
+        editor.select(-2);
+        editor.adjust(2.0);
+        editor.select(4);
+        editor.adjust(-1.0);
+
+        assert_eq!(&[0.5, 1.0, 0.05], editor.genome().genes());
+        assert_eq!(vec!["PLANT 0 ENERGY 40", " REPRODUCTION_THRESHOLD 0.500", " SEED_ENERGY 1.000", ">THERMAL_OPTIMUM 0.050", "ENTER PLANT COPIES"], editor.lines(&registry));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::board::Coord;
+use crate::phylogeny::Phylogeny;
+use crate::population::{PlantId, Population};
+
+/// The largest number of ancestors shown above the plant the tree is opened on, the nearest ones are shown
+const MAX_ANCESTORS: usize = 8;
+/// The largest number of children shown below the plant the tree is opened on
+const MAX_CHILDREN: usize = 8;
+
+/// The plant a lineage tree is opened on and the plant picked in it, kept by the simulation thread
+/// such that the tree follows the phylogeny as the simulation runs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LineageFocus {
+    /// The plant the tree is opened on
+    pub focus: PlantId,
+    /// The plant picked in the tree, its living descendants are highlighted on the board
+    pub selected: PlantId,
+}
+
+impl LineageFocus {
+    /// Opens the tree on the plant in a cell, returns None if the position is outside the board or the cell is empty
+    pub fn open(population: &Population, coord: Option<Coord>) -> Option<Self> {
+        let id = population.get(coord?)?.id();
+
+        Some(Self { focus: id, selected: id })
+    }
+}
+
+/// A single plant shown in the lineage tree
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TreeNode {
+    /// The id of the plant
+    pub id: PlantId,
+    /// The number of generations below the oldest ancestor shown
+    pub depth: usize,
+    /// The tick the plant germinated at
+    pub birth: u64,
+    /// True if the plant is alive
+    pub alive: bool,
+    /// The number of living descendants of the plant
+    pub living: usize,
+}
+
+/// The ancestors of a plant and its children with living descendants, built from the phylogeny on the simulation thread
+/// and shown as a panel where every line is a plant which can be picked to highlight its living descendants
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LineageTree {
+    /// The plants of the tree from the oldest ancestor shown to the children
+    nodes: Vec<TreeNode>,
+    /// The position in nodes of the plant the tree is opened on
+    focus: usize,
+    /// The position in nodes of the picked plant
+    selected: usize,
+    /// The cells of the picked plant, if it is alive, and of its living descendants
+    highlighted: Vec<Coord>,
+}
+
+impl LineageTree {
+    /// Builds the tree of a plant, returns None if the plant is not in the phylogeny. The picked plant
+    /// falls back to the plant the tree is opened on if it is not in the tree
+    pub fn build(phylogeny: &Phylogeny, population: &Population, focus: LineageFocus) -> Option<Self> {
+        let node = |id: PlantId, depth: usize| {
+            phylogeny.get(id).map(|lineage| TreeNode { id, depth, birth: lineage.birth, alive: lineage.death.is_none(), living: phylogeny.living_descendants(id).len() })
+        };
+
+        let lineage = phylogeny.get(focus.focus)?;
+        let mut ancestors = phylogeny.ancestors(focus.focus);
+        ancestors.truncate(MAX_ANCESTORS);
+        let mut nodes: Vec<TreeNode> = ancestors.iter().rev().enumerate().filter_map(|(depth, &id)| node(id, depth)).collect();
+        let index = nodes.len();
+        nodes.extend(node(focus.focus, index));
+
+        // Only the children which are alive or have living descendants are relatives worth showing
+        nodes.extend(lineage.children.iter()
+            .filter_map(|&id| node(id, index + 1))
+            .filter(|child| child.alive || child.living > 0)
+            .take(MAX_CHILDREN));
+
+        let selected = nodes.iter().position(|node| node.id == focus.selected).unwrap_or(index);
+        let picked = nodes[selected].id;
+        let highlighted = std::iter::once(picked)
+            .chain(phylogeny.living_descendants(picked))
+            .filter_map(|id| population.coord_of(id))
+            .collect();
+
+        Some(Self { nodes, focus: index, selected, highlighted })
+    }
+
+    /// Returns the plant picked in the tree
+    pub fn selected(&self) -> PlantId {
+        self.nodes[self.selected].id
+    }
+
+    /// Returns the cells of the picked plant and its living descendants
+    pub fn highlighted(&self) -> &[Coord] {
+        &self.highlighted
+    }
+
+    /// Finds the plant a number of lines away from the picked plant, stopping at the ends
+    pub fn step(&self, change: isize) -> PlantId {
+        let position = (self.selected as isize + change).clamp(0, self.nodes.len() as isize - 1) as usize;
+
+        self.nodes[position].id
+    }
+
+    /// Finds the parent of the picked plant, the picked plant itself if its parent is not shown
+    pub fn parent(&self) -> PlantId {
+        let depth = self.nodes[self.selected].depth;
+
+        self.nodes[..self.selected].iter().rev().find(|node| node.depth + 1 == depth).map_or(self.selected(), |node| node.id)
+    }
+
+    /// Finds the first child of the picked plant, the picked plant itself if none of its children are shown
+    pub fn child(&self) -> PlantId {
+        let depth = self.nodes[self.selected].depth;
+
+        self.nodes.get(self.selected + 1).filter(|node| node.depth == depth + 1).map_or(self.selected(), |node| node.id)
+    }
+
+    /// Finds the plant shown on a line of the panel, None for the title
+    pub fn at_line(&self, line: usize) -> Option<PlantId> {
+        self.nodes.get(line.checked_sub(1)?).map(|node| node.id)
+    }
+
+    /// Creates the lines of text shown in the lineage panel, the plant the tree is opened on is marked with a star
+    /// and the picked plant with an arrow
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("LINEAGE OF {}", self.nodes[self.focus].id.0)];
+
+        lines.extend(self.nodes.iter().enumerate().map(|(index, node)| {
+            let marker = if index == self.selected { ">" } else if index == self.focus { "*" } else { " " };
+            let state = if node.alive { "ALIVE" } else { "DEAD" };
+            format!("{}{}{} B{} {} +{}", marker, " ".repeat(node.depth), node.id.0, node.birth, state, node.living)
+        }));
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use crate::genome::Genome;
+    use crate::population::Plant;
+
+    /// A founder 0 with the children 1 and 2 and the grandchildren 3 and 4 through 1, where 2 and 4 died
+    fn tree(focus: u64, selected: u64) -> Option<LineageTree> {
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+        phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 2, genome.clone());
+        phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 3, genome.clone());
+        phylogeny.record_birth(PlantId(3), Some(PlantId(1)), None, 5, genome.clone());
+        phylogeny.record_birth(PlantId(4), Some(PlantId(1)), None, 6, genome.clone());
+        phylogeny.record_death(PlantId(2), 4);
+        phylogeny.record_death(PlantId(4), 8);
+
+        let mut population = Population::new(Size::new(4, 4));
+        population.insert(Coord::new(0, 0), Plant::new(10, genome.clone()));
+        population.insert(Coord::new(1, 0), Plant::new(10, genome.clone()));
+        population.insert(Coord::new(2, 2), Plant::new(10, genome.clone()));
+        population.insert(Coord::new(3, 3), Plant::new(10, genome));
+        population.remove(Coord::new(2, 2));
+
+        LineageTree::build(&phylogeny, &population, LineageFocus { focus: PlantId(focus), selected: PlantId(selected) })
+    }
+
+    #[test]
+    fn lineage_tree_lines() {
+        let focus = tree(1, 1).unwrap();
+
+        // The dead grandchild is left out since it has no living descendants
+        assert_eq!(vec!["LINEAGE OF 1", " 0 B0 ALIVE +2", "> 1 B2 ALIVE +1", "   3 B5 ALIVE +0"], focus.lines());
+        assert_eq!(None, focus.at_line(0));
+        assert_eq!(Some(PlantId(3)), focus.at_line(3));
+        assert_eq!(None, focus.at_line(4));
+        assert!(tree(9, 9).is_none());
+    }
+
+    #[test]
+    fn lineage_tree_navigation() {
+        let founder = tree(1, 0).unwrap();
+
+        assert_eq!(PlantId(0), founder.selected());
+        assert_eq!(PlantId(0), founder.step(-1));
+        assert_eq!(PlantId(3), founder.step(5));
+        assert_eq!(PlantId(0), founder.parent());
+        assert_eq!(PlantId(1), founder.child());
+        assert_eq!(PlantId(1), tree(1, 3).unwrap().parent());
+        assert_eq!(PlantId(3), tree(1, 3).unwrap().child());
+        assert_eq!(PlantId(1), tree(1, 7).unwrap().selected());
+    }
+
+    #[test]
+    fn lineage_tree_highlighted() {
+        assert_eq!(&[Coord::new(0, 0), Coord::new(1, 0), Coord::new(3, 3)], tree(1, 0).unwrap().highlighted());
+        assert_eq!(&[Coord::new(1, 0), Coord::new(3, 3)], tree(1, 1).unwrap().highlighted());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::board::Size;
+use crate::render;
+
+use super::render::{Camera, Frame, PANEL, SELECTION};
+
+/// The largest width or height of the minimap in pixels
+const MINIMAP_SIDE: usize = 160;
+/// The number of steps between drawing the minimap again
+const REFRESH_STEPS: u64 = 10;
+/// The distance in pixels between the minimap and the corner of the window
+const MARGIN: usize = 4;
+
+/// An overview of the entire board at a low resolution shown in the bottom right corner while zoomed in,
+/// with a rectangle marking the part of the board shown in the window
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Minimap {
+    /// The size of the board the minimap was drawn from
+    board: Size,
+    /// The size of the minimap in pixels
+    size: Size,
+    /// The pixels of the minimap
+    pixels: Vec<u8>,
+    /// The number of cells in each direction for every pixel
+    factor: usize,
+    /// The tick the minimap was drawn at, None if it has never been drawn
+    tick: Option<u64>,
+}
+
+impl Minimap {
+    /// Draws the minimap again from the pixels of the board if enough steps have gone by since it was last drawn,
+    /// or if the size of the board has changed or the simulation was rewound
+    pub fn refresh(&mut self, tick: u64, rgba: &[u8], board: Size) {
+        let fresh = self.tick.is_some_and(|last| tick >= last && tick - last < REFRESH_STEPS);
+        if fresh && board == self.board {
+            return;
+        }
+
+        let (w, h) = board.size();
+        self.factor = w.max(h).div_ceil(MINIMAP_SIDE).max(1);
+        (self.size, self.pixels) = render::downsample_rgba(rgba, board, self.factor);
+        self.board = board;
+        self.tick = Some(tick);
+    }
+
+    /// Finds the position in the window of the top left corner of the minimap
+    fn position(&self, window: (usize, usize)) -> (isize, isize) {
+        let (w, h) = self.size.size();
+
+        (window.0 as isize - (w + MARGIN) as isize, window.1 as isize - (h + MARGIN) as isize)
+    }
+
+    /// Draws the minimap with the part of the board shown by the camera marked
+    pub fn draw(&self, frame: &mut Frame, window: (usize, usize), camera: &Camera) {
+        let (x, y) = self.position(window);
+        let (w, h) = self.size.size();
+        frame.draw_rect_outline(x - 1, y - 1, w + 2, h + 2, PANEL);
+        frame.draw_image(x, y, self.size, &self.pixels);
+
+        // Clamp the viewport to the minimap such that the marker stays inside it
+        let (view_x, view_y, view_w, view_h) = camera.viewport(window);
+        let factor = self.factor as f32;
+        let left = (view_x / factor).clamp(0.0, w as f32) as usize;
+        let top = (view_y / factor).clamp(0.0, h as f32) as usize;
+        let right = ((view_x + view_w) / factor).ceil().clamp(0.0, w as f32) as usize;
+        let bottom = ((view_y + view_h) / factor).ceil().clamp(0.0, h as f32) as usize;
+        frame.draw_rect_outline(x + left as isize, y + top as isize, right.saturating_sub(left), bottom.saturating_sub(top), SELECTION);
+    }
+
+    /// Finds the position on the board of a position in the window, returns None if it is outside the minimap
+    pub fn board_position(&self, window: (usize, usize), cursor: (f32, f32)) -> Option<(f32, f32)> {
+        let (x, y) = self.position(window);
+        let (w, h) = self.size.size();
+        let (dx, dy) = (cursor.0 - x as f32, cursor.1 - y as f32);
+
+        if self.tick.is_none() || dx < 0.0 || dy < 0.0 || dx >= w as f32 || dy >= h as f32 {
+            return None;
+        }
+
+        let (board_w, board_h) = self.board.size();
+
+        Some(((dx * self.factor as f32).min(board_w as f32), (dy * self.factor as f32).min(board_h as f32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimap(w: usize, h: usize) -> Minimap {
+        let board = Size::new(w, h);
+        let mut minimap = Minimap::default();
+        minimap.refresh(0, &vec![255; board.len() * 4], board);
+
+        minimap
+    }
+
+    #[test]
+    fn minimap_refresh() {
+        let mut minimap = minimap(400, 200);
+
+        assert_eq!(3, minimap.factor);
+        assert_eq!(Size::new(134, 67), minimap.size);
+
+        // The minimap is only drawn again once enough steps have gone by
+        minimap.refresh(5, &vec![0; 400 * 200 * 4], Size::new(400, 200));
+
+        assert_eq!(255, minimap.pixels[0]);
+
+        minimap.refresh(REFRESH_STEPS, &vec![0; 400 * 200 * 4], Size::new(400, 200));
+
+        assert_eq!(0, minimap.pixels[0]);
+    }
+
+    #[test]
+    fn minimap_board_position() {
+        let minimap = minimap(400, 200);
+        let window = (1000, 800);
+        let (x, y) = minimap.position(window);
+
+        assert_eq!(Some((30.0, 60.0)), minimap.board_position(window, (x as f32 + 10.0, y as f32 + 20.0)));
+        assert_eq!(None, minimap.board_position(window, (x as f32 - 1.0, y as f32)));
+        assert_eq!(None, Minimap::default().board_position(window, (999.0, 799.0)));
+    }
+}
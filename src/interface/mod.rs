@@ -1,27 +1,778 @@
+use std::num::NonZeroU32;
+use std::time::{Duration, Instant};
+
 use winit;
+use winit::event::{ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::{EventLoopClosed, EventLoopProxy};
+
+use crate::alert::AlertConfig;
+use crate::genes::GeneRegistry;
+use crate::governor::{Governor, RunMode};
+use input::{Action, InputMap};
+use crate::population::Cull;
+use crate::render::Palette;
+use crate::simulation::Simulation;
+use crate::stats::RegionStats;
+
+mod events;
+mod graph;
+pub mod input;
+mod inspector;
+mod lineage;
+mod minimap;
+#[cfg(feature = "gui-panel")]
+mod panel;
+mod render;
+mod worker;
+
+/// The number of pixels per pixel of the font
+const TEXT_SCALE: usize = 2;
+/// The largest number of times the board can be magnified
+const MAX_ZOOM: f32 = 64.0;
+/// The factor the zoom changes by for every step of the mouse wheel
+const ZOOM_STEP: f32 = 1.25;
+/// The number of steps shown in the graphs
+const GRAPH_SAMPLES: usize = 500;
+/// The largest width of the graphs in pixels
+const GRAPH_WIDTH: usize = 300;
+/// The share of the plants removed by the bottleneck action
+const BOTTLENECK_FRACTION: f32 = 0.9;
+/// How long a failure of the window, such as a screenshot which could not be saved, is shown below the status
+const FAILURE_NOTICE: Duration = Duration::from_secs(5);
+/// The refresh rate assumed for vsync when the monitor does not report its own in hertz
+const DEFAULT_REFRESH_RATE: f64 = 60.0;
+/// The number of steps which can be undone with the control panel if the simulation does not keep a history already
+#[cfg(feature = "gui-panel")]
+const HISTORY_STEPS: usize = 200;
+
+/// The settings of the window
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceConfig {
+    /// The largest number of snapshots of the simulation waiting to be drawn, the simulation keeps running
+    /// without taking snapshots while this many are waiting. At least 1 is used
+    pub max_snapshot_lag: usize,
+    /// How fast the simulation is run, with the gui-panel feature the speed slider multiplies the rate
+    pub run_mode: RunMode,
+    /// The keys bound to the actions of the window
+    pub input: InputMap,
+    /// The palette the board is drawn with when the window opens
+    pub palette: Palette,
+    /// The alert rules watched while the window is open, their latest alert is shown below the status for a while.
+    /// None keeps the alerts the simulation already watches
+    pub alerts: Option<AlertConfig>,
+}
+
+impl Default for InterfaceConfig {
+    fn default() -> Self {
+        Self {
+            max_snapshot_lag: 2,
+            run_mode: RunMode::FixedTicksPerSecond(60.0),
+            input: InputMap::default(),
+            palette: Palette::default(),
+            alerts: None,
+        }
+    }
+}
+
+/// The monitor a fullscreen window is shown on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Monitor {
+    /// The monitor the window is on when it opens
+    Current,
+    /// The primary monitor of the system
+    Primary,
+    /// A monitor by its position in the list given by WindowBuilder::monitors, the current monitor is used if there is no such monitor
+    Index(usize),
+}
+
+/// An event posted into the event loop of the window from another thread
+#[derive(Clone, Debug, PartialEq)]
+pub enum UserEvent {
+    /// Shows lines of text in a panel in the top left corner of the window, no lines hides the panel
+    UpdateStats(Vec<String>),
+    /// Draws the window again
+    RequestRedraw,
+    /// Closes the window
+    Shutdown,
+}
+
+/// A handle which can be sent to other threads to post events into the event loop of the window
+#[derive(Clone, Debug)]
+pub struct InterfaceProxy {
+    proxy: EventLoopProxy<UserEvent>,
+}
+
+impl InterfaceProxy {
+    /// Posts an event into the event loop, it is handled on the thread running the window
+    /// 
+    /// # Parameters
+    /// 
+    /// event: The event to post
+    /// 
+    /// # Errors
+    /// 
+    /// EventLoopClosed: This will occur if the window has been closed, the event is returned
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::interface::{UserEvent, WindowBuilder};
+    /// 
+    /// let window = WindowBuilder::new().build().unwrap();
+    /// let proxy = window.proxy();
+    /// 
+    /// std::thread::spawn(move || {
+    ///     proxy.send(UserEvent::UpdateStats(vec!["CONNECTED".to_string()])).unwrap();
+    /// });
+    /// ```
+    pub fn send(&self, event: UserEvent) -> Result<(), EventLoopClosed<UserEvent>> {
+        self.proxy.send_event(event)
+    }
+}
 
 pub struct Window {
     window: winit::window::Window,
-    event_loop: winit::event_loop::EventLoop<()>,
+    event_loop: winit::event_loop::EventLoop<UserEvent>,
+    config: InterfaceConfig,
+    vsync: bool,
 }
 
 impl Window {
+    /// Returns the width and height of the inside of the window in physical pixels
+    pub fn size(&self) -> (u32, u32) {
+        let size = self.window.inner_size();
+
+        (size.width, size.height)
+    }
+
+    /// Returns the number of physical pixels for every logical pixel of the monitor the window is on
+    pub fn scale_factor(&self) -> f64 {
+        self.window.scale_factor()
+    }
+
+    /// Creates a proxy for posting events into the event loop from other threads
+    pub fn proxy(&self) -> InterfaceProxy {
+        InterfaceProxy { proxy: self.event_loop.create_proxy() }
+    }
+
+    /// Runs the event loop of the window until it is closed, the simulation is stepped on its own thread at the rate
+    /// of the run mode and the latest snapshot of it is drawn in the window. The steps due between two frames are batched
+    /// into the measured time of a frame, the steps which do not fit are dropped and the steps actually run every second are
+    /// shown in the top left corner. Dragging with the left mouse button selects a region of the board and shows its statistics,
+    /// escape clears the selection.
+    /// 
+    /// G toggles graphs of the population, the mean energy and the number of species over the latest steps
+    /// in the bottom left corner. The mouse wheel zooms in and out, while zoomed in a minimap of the entire board
+    /// is shown in the bottom right corner and clicking it moves the view there. V cycles through the render modes showing the genomes, energy, age and species
+    /// of the plants, their relatedness to the plant picked in the lineage panel or the light and water of the board. With the image feature F12 saves the window as it is shown
+    /// to a png named after the time in the working directory.
+    /// 
+    /// E toggles the edit mode where dragging with the left mouse button paints onto the board while it runs
+    /// and the right mouse button removes light or water. 1 to 4 pick the tool between light, water, planting
+    /// and removing plants, [ and ] change the size of the brush, - and = change its strength and Z undoes the latest stroke
+    /// 
+    /// I copies the genome of the plant under the mouse into a panel on the left where up and down pick a gene
+    /// and left and right change it, enter turns on the edit mode with the plant tool planting the edited genome
+    /// and escape closes the panel
+    /// 
+    /// L opens a panel on the left with the lineage of the plant under the mouse, its ancestors and its children with
+    /// living descendants. Clicking a plant in the panel or moving with up and down picks it and outlines it and its
+    /// living descendants on the board, left and right move to the parent and the first child and enter opens the panel
+    /// on the picked plant. Escape closes the panel
+    /// 
+    /// Events posted through a proxy from other threads can show lines of text in the top left corner,
+    /// draw the window again or close it
+    /// 
+    /// With the gui-panel feature a control panel in the top right corner shows the tick and has sliders for the speed
+    /// and the mutation rate and buttons to pause, save a checkpoint and load it again. Space also pauses,
+    /// the left arrow pauses and steps backwards through the latest steps and page up and page down change the speed.
+    /// The right arrow runs a single step
+    /// 
+    /// The keys named here are the default bindings, they can be changed with the input map of the config
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to run and show
+    /// 
+    /// # Errors
+    /// 
+    /// softbuffer::SoftBufferError: This will occur if the window could not be drawn to
+    pub fn run(self, simulation: Simulation) -> Result<(), softbuffer::SoftBufferError> {
+        let Self { window, event_loop, config, vsync } = self;
 
+        // SAFETY: The window lives for as long as the event loop, which owns both the context and the surface
+        let context = unsafe { softbuffer::Context::new(&window) }?;
+        let mut surface = unsafe { softbuffer::Surface::new(&context, &window) }?;
+
+        #[cfg(feature = "gui-panel")]
+        let simulation = {
+            let mut simulation = simulation;
+            if simulation.history_capacity() == 0 {
+                simulation.enable_history(HISTORY_STEPS);
+            }
+            simulation
+        };
+        let mut model = worker::Model::new(simulation, Governor::new(config.run_mode));
+        model.style.set_palette(config.palette.clone());
+        if let Some(alerts) = &config.alerts {
+            model.simulation.watch_alerts(Some(alerts.clone()));
+        }
+        let (worker, mut latest) = worker::SimulationThread::spawn(model, config.max_snapshot_lag);
+
+        let mut cursor = (0.0, 0.0);
+        let mut selection = events::Selection::default();
+        let mut selected = None;
+        let mut editing = false;
+        let mut inspector: Option<inspector::GenomeEditor> = None;
+        let genes = GeneRegistry::default();
+        let mut lineage_at: Option<(isize, isize)> = None;
+        let mut graphs = graph::Graphs::new(GRAPH_SAMPLES);
+        latest.samples.drain(..).for_each(|sample| graphs.push(sample));
+        let mut show_graphs = false;
+        let mut canvas = crate::render::Canvas::new(&latest.board, &latest.population);
+        let mut zoom = 1.0;
+        let mut center = None;
+        let mut minimap = minimap::Minimap::default();
+        let mut posted: Vec<String> = Vec::new();
+        let mut failure: Option<(String, Instant)> = None;
+        #[cfg(feature = "image")]
+        let mut screenshot = false;
+        #[cfg(feature = "gui-panel")]
+        let mut panel_held = false;
+        let mut next_frame = Instant::now();
+        let mut last_frame: Option<Instant> = None;
+
+        event_loop.run(move |event, _, control_flow| {
+            control_flow.set_poll();
+
+            let size = latest.board.fields.size;
+            let window_size = window.inner_size();
+            let window_pixels = (window_size.width as usize, window_size.height as usize);
+            let (w, h) = size.size();
+            let (center_x, center_y) = center.unwrap_or((w as f32 / 2.0, h as f32 / 2.0));
+            let camera = render::Camera::zoomed(size, window_pixels, zoom, (center_x.clamp(0.0, w as f32), center_y.clamp(0.0, h as f32)));
+
+            match event {
+                Event::WindowEvent { event, window_id } if window_id == window.id() => match event {
+                    WindowEvent::CloseRequested => control_flow.set_exit(),
+                    WindowEvent::CursorMoved { position, .. } => {
+                        cursor = (position.x as f32, position.y as f32);
+                        #[cfg(feature = "gui-panel")]
+                        if panel_held {
+                            let (width, position) = (window_size.width as usize, cursor);
+                            worker.send(move |model| {
+                                model.panel.drag(&mut model.simulation, width, position);
+                            });
+                            return;
+                        }
+                        if editing {
+                            let coord = camera.screen_to_board(size, cursor);
+                            worker.send(move |model| model.editor.drag(&mut model.simulation, coord));
+                        } else {
+                            selection.drag(camera.screen_to_board(size, cursor));
+                        }
+                    }
+                    #[cfg(feature = "gui-panel")]
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if panel::covers(window_size.width as usize, cursor) => {
+                        panel_held = true;
+                        let (width, position) = (window_size.width as usize, cursor);
+                        worker.send(move |model| {
+                            model.panel.press(&mut model.simulation, width, position);
+                        });
+                    }
+                    #[cfg(feature = "gui-panel")]
+                    WindowEvent::MouseInput { state: ElementState::Released, button: MouseButton::Left, .. } if panel_held => {
+                        panel_held = false;
+                        worker.send(|model| {
+                            model.panel.release();
+                        });
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        let steps = match delta {
+                            MouseScrollDelta::LineDelta(_, lines) => lines,
+                            MouseScrollDelta::PixelDelta(position) => position.y as f32 / 40.0,
+                        };
+                        zoom = (zoom * ZOOM_STEP.powf(steps)).clamp(1.0, MAX_ZOOM);
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if zoom > 1.0 && minimap.board_position(window_pixels, cursor).is_some() => {
+                        center = minimap.board_position(window_pixels, cursor);
+                    }
+                    WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } if lineage_click(latest.lineage.as_ref(), lineage_at, cursor).is_some() => {
+                        pick_lineage(&worker, lineage_click(latest.lineage.as_ref(), lineage_at, cursor));
+                    }
+                    WindowEvent::MouseInput { state, button: button @ (MouseButton::Left | MouseButton::Right), .. } if editing => match state {
+                        ElementState::Pressed => {
+                            let coord = camera.screen_to_board(size, cursor);
+                            worker.send(move |model| model.editor.press(&mut model.simulation, coord, button == MouseButton::Right));
+                        }
+                        ElementState::Released => worker.send(|model| model.editor.release()),
+                    },
+                    WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => match state {
+                        ElementState::Pressed => selection.press(camera.screen_to_board(size, cursor)),
+                        ElementState::Released => selected = selection.release(),
+                    },
+                    WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => match input.virtual_keycode.and_then(|key| config.input.action(key, inspector.is_some() || latest.lineage.is_some())) {
+                        Some(Action::Clear) => {
+                            selection = events::Selection::default();
+                            selected = None;
+                            inspector = None;
+                            worker.send(|model| model.lineage = None);
+                        }
+                        Some(Action::Inspect) => inspector = inspector::GenomeEditor::open(&latest.population, camera.screen_to_board(size, cursor)),
+                        Some(Action::Lineage) => {
+                            let focus = lineage::LineageFocus::open(&latest.population, camera.screen_to_board(size, cursor));
+                            worker.send(move |model| model.lineage = focus);
+                        }
+                        // The genome panel takes the keys it shares with the lineage panel while both are open
+                        Some(Action::PreviousGene) if inspector.is_none() => pick_lineage(&worker, latest.lineage.as_ref().map(|tree| tree.step(-1))),
+                        Some(Action::NextGene) if inspector.is_none() => pick_lineage(&worker, latest.lineage.as_ref().map(|tree| tree.step(1))),
+                        Some(Action::DecreaseGene) if inspector.is_none() => pick_lineage(&worker, latest.lineage.as_ref().map(|tree| tree.parent())),
+                        Some(Action::IncreaseGene) if inspector.is_none() => pick_lineage(&worker, latest.lineage.as_ref().map(|tree| tree.child())),
+                        Some(Action::PlantGenome) if inspector.is_none() && latest.lineage.is_some() => worker.send(|model| {
+                            if let Some(focus) = &mut model.lineage {
+                                focus.focus = focus.selected;
+                            }
+                        }),
+                        Some(Action::PreviousGene) => inspector.iter_mut().for_each(|inspector| inspector.select(-1)),
+                        Some(Action::NextGene) => inspector.iter_mut().for_each(|inspector| inspector.select(1)),
+                        Some(Action::DecreaseGene) => inspector.iter_mut().for_each(|inspector| inspector.adjust(-1.0)),
+                        Some(Action::IncreaseGene) => inspector.iter_mut().for_each(|inspector| inspector.adjust(1.0)),
+                        Some(Action::PlantGenome) => {
+                            if let Some(inspector) = &inspector {
+                                let (energy, genome) = (inspector.energy(), inspector.genome());
+                                editing = true;
+                                selection = events::Selection::default();
+                                worker.send(move |model| model.editor.plant_copies(energy, genome));
+                            }
+                        }
+                        Some(Action::Edit) => {
+                            editing = !editing;
+                            selection = events::Selection::default();
+                            worker.send(move |model| {
+                                model.editor.release();
+                                model.editor.enabled = editing;
+                            });
+                        }
+                        Some(Action::Graphs) => show_graphs = !show_graphs,
+                        Some(Action::RenderMode) => worker.send(|model| model.style.mode = model.style.mode.next()),
+                        Some(Action::Palette) => worker.send(|model| model.style.set_palette(model.style.palette.next())),
+                        #[cfg(feature = "image")]
+                        Some(Action::Screenshot) => screenshot = true,
+                        Some(Action::LightTool) => worker.send(|model| model.editor.select_tool(1)),
+                        Some(Action::WaterTool) => worker.send(|model| model.editor.select_tool(2)),
+                        Some(Action::PlantTool) => worker.send(|model| model.editor.select_tool(3)),
+                        Some(Action::RemoveTool) => worker.send(|model| model.editor.select_tool(4)),
+                        Some(Action::SpawnTool) => worker.send(|model| model.editor.select_tool(5)),
+                        Some(Action::SmiteTool) => worker.send(|model| model.editor.select_tool(6)),
+                        Some(Action::FertilizeTool) => worker.send(|model| model.editor.select_tool(7)),
+                        Some(Action::ShrinkBrush) => worker.send(|model| model.editor.resize(-1.0)),
+                        Some(Action::GrowBrush) => worker.send(|model| model.editor.resize(1.0)),
+                        Some(Action::WeakenBrush) => worker.send(|model| model.editor.scale_strength(0.5)),
+                        Some(Action::StrengthenBrush) => worker.send(|model| model.editor.scale_strength(2.0)),
+                        Some(Action::Undo) if editing => worker.send(|model| model.editor.undo(&mut model.simulation)),
+                        Some(Action::Bottleneck) => worker.send(|model| {
+                            model.simulation.cull(Cull::Random { fraction: BOTTLENECK_FRACTION });
+                        }),
+                        Some(Action::ActiveRegion) => {
+                            let region = selected;
+                            worker.send(move |model| model.simulation.set_active_region(region));
+                        }
+                        Some(Action::Step) => worker.send(|model| {
+                            #[cfg(feature = "gui-panel")]
+                            {
+                                model.panel.paused = true;
+                            }
+                            model.simulation.step();
+                        }),
+                        #[cfg(feature = "gui-panel")]
+                        Some(Action::Pause) => worker.send(|model| model.panel.paused = !model.panel.paused),
+                        #[cfg(feature = "gui-panel")]
+                        Some(Action::StepBack) => worker.send(|model| {
+                            model.panel.paused = true;
+                            model.simulation.rewind(1);
+                        }),
+                        #[cfg(feature = "gui-panel")]
+                        Some(Action::SpeedUp) => worker.send(|model| model.panel.change_speed(1)),
+                        #[cfg(feature = "gui-panel")]
+                        Some(Action::SlowDown) => worker.send(|model| model.panel.change_speed(-1)),
+                        _ => (),
+                    },
+                    _ => (),
+                },
+                Event::UserEvent(event) => match event {
+                    UserEvent::UpdateStats(lines) => {
+                        posted = lines;
+                        window.request_redraw();
+                    }
+                    UserEvent::RequestRedraw => window.request_redraw(),
+                    UserEvent::Shutdown => control_flow.set_exit(),
+                },
+                Event::MainEventsCleared => {
+                    // Only the cells which changed since the previous snapshot are drawn again, the changes of
+                    // every snapshot are applied in order so none are missed
+                    for mut snapshot in worker.receive() {
+                        canvas.update(&snapshot.board, &snapshot.population, &snapshot.dirty, 1);
+                        snapshot.samples.drain(..).for_each(|sample| graphs.push(sample));
+                        latest = snapshot;
+                    }
+
+                    // With vsync the window is drawn at most once for every refresh of the monitor
+                    let now = Instant::now();
+                    if !vsync || now >= next_frame {
+                        if vsync {
+                            let refresh = window.current_monitor().and_then(|monitor| monitor.refresh_rate_millihertz());
+                            next_frame = now + frame_interval(refresh);
+                        }
+                        window.request_redraw();
+                    }
+                }
+                Event::RedrawRequested(window_id) if window_id == window.id() => {
+                    let (Some(width), Some(height)) = (NonZeroU32::new(window_size.width), NonZeroU32::new(window_size.height)) else {
+                        return;
+                    };
+
+                    // The simulation thread fits the steps between two snapshots into the time of a frame
+                    let now = Instant::now();
+                    if let Some(last) = last_frame.replace(now) {
+                        worker.record_frame(now - last);
+                    }
+
+                    let mut frame = render::Frame::new(width.get() as usize, height.get() as usize);
+                    let pixels = latest.styled.as_deref().unwrap_or(canvas.pixels());
+                    frame.draw_board(&camera, size, pixels);
+
+                    // Show where the window is on the board while zoomed in
+                    if zoom > 1.0 {
+                        minimap.refresh(latest.tick, pixels, size);
+                        minimap.draw(&mut frame, window_pixels, &camera);
+                    }
+
+                    // Show the region being selected or the last selected region
+                    if let Some(rect) = selection.rect().or(selected) {
+                        let (x, y) = camera.board_to_screen(crate::board::Coord::new(rect.x, rect.y));
+                        let w = (rect.w as f32 * camera.scale).round() as usize;
+                        let h = (rect.h as f32 * camera.scale).round() as usize;
+                        frame.draw_rect_outline(x as isize, y as isize, w, h, render::SELECTION);
+
+                        let lines = stats_lines(&RegionStats::new(&latest.board, &latest.population, rect));
+                        let (panel_w, _) = render::panel_size(&lines, TEXT_SCALE);
+                        let panel_x = if x as usize + w + panel_w + 4 <= width.get() as usize { x as isize + w as isize + 4 } else { 4 };
+                        frame.draw_panel(panel_x, y as isize, &lines, TEXT_SCALE);
+                    }
+
+                    // Show the steps actually run every second together with the status, the latest alert and the latest failure
+                    let mut panel_y = 4;
+                    failure = failure.take().filter(|(_, at)| at.elapsed() < FAILURE_NOTICE);
+                    let lines: Vec<String> = std::iter::once(format!("{:.0} TICKS/S", latest.tick_rate))
+                        .chain(latest.status.clone())
+                        .chain(latest.notice.clone())
+                        .chain(failure.as_ref().map(|(message, _)| message.clone()))
+                        .collect();
+                    frame.draw_panel(4, panel_y, &lines, TEXT_SCALE);
+                    panel_y += render::panel_size(&lines, TEXT_SCALE).1 as isize + 4;
+
+                    // Show the lines posted from other threads below the status
+                    if !posted.is_empty() {
+                        frame.draw_panel(4, panel_y, &posted, TEXT_SCALE);
+                        panel_y += render::panel_size(&posted, TEXT_SCALE).1 as isize + 4;
+                    }
+
+                    // Show the genome being edited below the other panels and mark the plant it was copied from
+                    if let Some(inspector) = &inspector {
+                        let (x, y) = camera.board_to_screen(inspector.coord());
+                        let cell = camera.scale.round().max(1.0) as usize;
+                        frame.draw_rect_outline(x as isize, y as isize, cell, cell, render::SELECTION);
+                        let lines = inspector.lines(&genes);
+                        frame.draw_panel(4, panel_y, &lines, TEXT_SCALE);
+                        panel_y += render::panel_size(&lines, TEXT_SCALE).1 as isize + 4;
+                    }
+
+                    // Show the lineage below the genome and outline the living descendants of the picked plant
+                    lineage_at = None;
+                    if let Some(tree) = &latest.lineage {
+                        let cell = camera.scale.round().max(1.0) as usize;
+                        for &coord in tree.highlighted() {
+                            let (x, y) = camera.board_to_screen(coord);
+                            frame.draw_rect_outline(x as isize, y as isize, cell, cell, render::LINEAGE);
+                        }
+                        frame.draw_panel(4, panel_y, &tree.lines(), TEXT_SCALE);
+                        lineage_at = Some((4, panel_y));
+                    }
+
+                    if show_graphs {
+                        let graph_w = (width.get() as usize).saturating_sub(8).min(GRAPH_WIDTH);
+                        let (_, graph_h) = graph::Graphs::size(graph_w);
+                        graphs.draw(&mut frame, 4, height.get() as isize - graph_h as isize - 4, graph_w);
+                    }
+
+                    #[cfg(feature = "gui-panel")]
+                    latest.panel.draw(&mut frame, latest.tick, latest.mutation_rate, width.get() as usize);
+
+                    // The screenshot is taken last so it includes everything drawn on top of the board
+                    #[cfg(feature = "image")]
+                    if std::mem::take(&mut screenshot) {
+                        let path = screenshot_path();
+                        if let Err(error) = frame.capture_frame().save(&path) {
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(?path, %error, "unable to save the screenshot");
+                            failure = Some((format!("UNABLE TO SAVE {}: {}", path.display(), error), Instant::now()));
+                            window.request_redraw();
+                        }
+                    }
+
+                    let result = surface.resize(width, height)
+                        .and_then(|_| surface.buffer_mut())
+                        .and_then(|mut buffer| {
+                            buffer.copy_from_slice(frame.pixels());
+                            buffer.present()
+                        });
+
+                    if result.is_err() {
+                        control_flow.set_exit();
+                    }
+                }
+                _ => (),
+            }
+        });
+    }
+}
+
+/// Picks a plant in the lineage panel such that its living descendants are outlined, nothing is done for None
+fn pick_lineage(worker: &worker::SimulationThread, id: Option<crate::population::PlantId>) {
+    if let Some(id) = id {
+        worker.send(move |model| {
+            if let Some(focus) = &mut model.lineage {
+                focus.selected = id;
+            }
+        });
+    }
+}
+
+/// Finds the plant in the lineage panel drawn at a position which a point is on
+fn lineage_click(tree: Option<&lineage::LineageTree>, at: Option<(isize, isize)>, point: (f32, f32)) -> Option<crate::population::PlantId> {
+    let (tree, (x, y)) = (tree?, at?);
+
+    tree.at_line(render::panel_line(x, y, &tree.lines(), TEXT_SCALE, point)?)
+}
+
+/// Creates the lines of text shown in the statistics panel of a region
+fn stats_lines(stats: &RegionStats) -> Vec<String> {
+    let mut lines = vec![
+        format!("REGION {}X{}", stats.rect.w, stats.rect.h),
+        format!("PLANTS {}", stats.population),
+        format!("ENERGY {:.1}", stats.mean_energy),
+        format!("LIGHT {:.3}", stats.mean_light),
+        format!("DIVERSITY {:.3}", stats.diversity),
+    ];
+
+    lines.extend(stats.mean_genes.iter().enumerate().map(|(index, gene)| format!("GENE {} {:.3}", index, gene)));
+
+    lines
+}
+
+/// Finds the time between two frames with vsync from the refresh rate of the monitor in millihertz
+fn frame_interval(refresh_millihertz: Option<u32>) -> Duration {
+    let hertz = refresh_millihertz
+        .filter(|&refresh| refresh > 0)
+        .map_or(DEFAULT_REFRESH_RATE, |refresh| refresh as f64 / 1000.0);
+
+    Duration::from_secs_f64(1.0 / hertz)
+}
+
+/// Creates the name of a screenshot from the current time such that screenshots do not overwrite each other
+#[cfg(feature = "image")]
+fn screenshot_path() -> std::path::PathBuf {
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    std::path::PathBuf::from(format!("screenshot-{}-{:03}.png", time.as_secs(), time.subsec_millis()))
 }
 
 pub struct WindowBuilder {
     window_builder: winit::window::WindowBuilder,
-    event_loop: winit::event_loop::EventLoop<()>,
+    event_loop: winit::event_loop::EventLoop<UserEvent>,
+    config: InterfaceConfig,
+    fullscreen: Option<Monitor>,
+    vsync: bool,
 }
 
 impl WindowBuilder {
     pub fn new() -> Self {
         // Create the event loop
-        let event_loop = winit::event_loop::EventLoop::new();
+        let event_loop = winit::event_loop::EventLoopBuilder::with_user_event().build();
 
         // Create the window builder
         let window_builder = winit::window::WindowBuilder::new();
 
-        Self {event_loop, window_builder}
+        Self {event_loop, window_builder, config: InterfaceConfig::default(), fullscreen: None, vsync: false}
+    }
+
+    /// Returns the names of the monitors of the system, the position of a monitor in the list is its index for Monitor::Index
+    pub fn monitors(&self) -> Vec<String> {
+        self.event_loop.available_monitors()
+            .enumerate()
+            .map(|(index, monitor)| monitor.name().unwrap_or_else(|| format!("Monitor {}", index)))
+            .collect()
+    }
+
+    /// Opens the window in borderless fullscreen on a monitor
+    /// 
+    /// # Parameters
+    /// 
+    /// monitor: The monitor to fill
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::interface::{Monitor, WindowBuilder};
+    /// 
+    /// let builder = WindowBuilder::new();
+    /// println!("{:?}", builder.monitors());
+    /// let window = builder.fullscreen(Monitor::Index(1)).vsync(true).build().unwrap();
+    /// 
+    /// println!("{:?} at scale {}", window.size(), window.scale_factor());
+    /// ```
+    pub fn fullscreen(mut self, monitor: Monitor) -> Self {
+        self.fullscreen = Some(monitor);
+        self
+    }
+
+    /// Sets whether the window is drawn at most once for every refresh of the monitor instead of as often as possible,
+    /// by default it is not
+    /// 
+    /// # Parameters
+    /// 
+    /// vsync: True if the drawing should follow the refresh rate of the monitor
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Sets whether the user can resize the window, by default they can
+    /// 
+    /// # Parameters
+    /// 
+    /// resizable: True if the window can be resized
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.window_builder = self.window_builder.with_resizable(resizable);
+        self
+    }
+
+    /// Sets the smallest size the window can be resized to
+    /// 
+    /// # Parameters
+    /// 
+    /// width: The smallest width of the inside of the window in logical pixels
+    /// height: The smallest height of the inside of the window in logical pixels
+    pub fn min_size(mut self, width: u32, height: u32) -> Self {
+        self.window_builder = self.window_builder.with_min_inner_size(winit::dpi::LogicalSize::new(width, height));
+        self
+    }
+
+    /// Sets the icon of the window
+    /// 
+    /// # Parameters
+    /// 
+    /// rgba: The red, green, blue and alpha values of the pixels of the icon with the rows in order
+    /// width: The width of the icon in pixels
+    /// height: The height of the icon in pixels
+    /// 
+    /// # Errors
+    /// 
+    /// winit::window::BadIcon: This will occur if there are not 4 values for every pixel
+    pub fn icon(mut self, rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, winit::window::BadIcon> {
+        let icon = winit::window::Icon::from_rgba(rgba, width, height)?;
+        self.window_builder = self.window_builder.with_window_icon(Some(icon));
+
+        Ok(self)
+    }
+
+    /// Sets the icon of the window from an image
+    /// 
+    /// # Parameters
+    /// 
+    /// image: The icon
+    #[cfg(feature = "image")]
+    pub fn icon_image(self, image: &image::RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+
+        self.icon(image.as_raw().clone(), width, height).expect("An image always has 4 values for every pixel")
+    }
+
+    /// Sets the settings of the window
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings to use
+    pub fn config(mut self, config: InterfaceConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Opens the window
+    /// 
+    /// # Errors
+    /// 
+    /// winit::error::OsError: This will occur if the operating system could not create the window
+    pub fn build(self) -> Result<Window, winit::error::OsError> {
+        let mut window_builder = self.window_builder;
+        if let Some(monitor) = self.fullscreen {
+            let handle = match monitor {
+                Monitor::Current => None,
+                Monitor::Primary => self.event_loop.primary_monitor(),
+                Monitor::Index(index) => self.event_loop.available_monitors().nth(index),
+            };
+            window_builder = window_builder.with_fullscreen(Some(winit::window::Fullscreen::Borderless(handle)));
+        }
+        let window = window_builder.build(&self.event_loop)?;
+
+        Ok(Window {window, event_loop: self.event_loop, config: self.config, vsync: self.vsync})
+    }
+}
+
+impl Default for WindowBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Rect;
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn screenshot_path_name() {
+        let path = screenshot_path();
+        let name = path.to_str().unwrap();
+
+        assert!(name.starts_with("screenshot-"));
+        assert!(name.ends_with(".png"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn frame_interval_refresh() {
+        assert_eq!(Duration::from_secs_f64(1.0 / 144.0), frame_interval(Some(144_000)));
+        assert_eq!(Duration::from_secs_f64(1.0 / 60.0), frame_interval(None));
+        assert_eq!(Duration::from_secs_f64(1.0 / 60.0), frame_interval(Some(0)));
+    }
+
+    #[test]
+    fn stats_lines_genes() {
+        let stats = RegionStats {
+            rect: Rect::new(0, 0, 2, 3),
+            population: 2,
+            mean_energy: 50.0,
+            mean_genes: vec![0.5, 0.25],
+            mean_light: 0.75,
+            diversity: 0.125,
+        };
+
+        assert_eq!(
+            vec!["REGION 2X3", "PLANTS 2", "ENERGY 50.0", "LIGHT 0.750", "DIVERSITY 0.125", "GENE 0 0.500", "GENE 1 0.250"],
+            stats_lines(&stats),
+        );
+    }
+}
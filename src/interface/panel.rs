@@ -0,0 +1,379 @@
+use crate::genome::MutationConfig;
+use crate::simulation::{ConfigUpdate, Simulation};
+
+use super::render::{Frame, PANEL, TEXT};
+
+/// The width of the control panel in pixels
+const PANEL_WIDTH: usize = 200;
+/// The space in pixels between the widgets and around the edge of the panel
+const PADDING: usize = 4;
+/// The height in pixels of a line of text
+const LINE_HEIGHT: usize = 14;
+/// The height in pixels of a slider
+const SLIDER_HEIGHT: usize = 10;
+/// The height in pixels of a button
+const BUTTON_HEIGHT: usize = 18;
+/// The largest number of steps run for every frame
+const MAX_SPEED: usize = 32;
+/// The largest mutation rate which can be set with the slider
+const MAX_MUTATION_RATE: f32 = 0.1;
+/// The color of the track of a slider and the background of a button
+const TRACK: u32 = 0x404040;
+
+/// A slider of the control panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slider {
+    /// The number of steps run for every frame
+    Speed,
+    /// The mutation rate of the simulation
+    Mutation,
+}
+
+/// Something in the control panel which can be clicked
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Widget {
+    /// A slider, clicking or dragging along it sets its value
+    Slider(Slider),
+    /// Pauses or resumes the simulation
+    Pause,
+    /// Saves a checkpoint of the simulation
+    Save,
+    /// Goes back to the saved checkpoint
+    Load,
+}
+
+/// A rectangle of pixels in the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Area {
+    /// The left edge
+    x: isize,
+    /// The top edge
+    y: isize,
+    /// The width
+    w: usize,
+    /// The height
+    h: usize,
+}
+
+impl Area {
+    /// Returns true if a position in the window is inside the area
+    fn contains(&self, position: (f32, f32)) -> bool {
+        position.0 >= self.x as f32 && position.0 < (self.x + self.w as isize) as f32
+            && position.1 >= self.y as f32 && position.1 < (self.y + self.h as isize) as f32
+    }
+
+    /// Finds the position along the area of a horizontal position between 0 and 1
+    fn fraction(&self, x: f32) -> f32 {
+        if self.w == 0 {
+            return 0.0;
+        }
+
+        ((x - self.x as f32) / self.w as f32).clamp(0.0, 1.0)
+    }
+}
+
+/// The control panel shown in the top right corner of the window with sliders for the speed and mutation rate,
+/// the tick counter and buttons to pause, save a checkpoint and load it again
+#[derive(Clone, Debug)]
+pub(crate) struct ControlPanel {
+    /// True if the simulation is not stepped
+    pub paused: bool,
+    /// The number of steps run for every frame
+    pub speed: usize,
+    /// The simulation as it was when it was last saved
+    checkpoint: Option<Simulation>,
+    /// The slider being dragged
+    dragging: Option<Slider>,
+}
+
+impl Default for ControlPanel {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1,
+            checkpoint: None,
+            dragging: None,
+        }
+    }
+}
+
+impl ControlPanel {
+    /// Returns the number of steps to run in this frame
+    pub fn steps(&self) -> usize {
+        if self.paused { 0 } else { self.speed }
+    }
+
+    /// Handles a click in the window, returns true if the click was in the panel and should not reach the board
+    pub fn press(&mut self, simulation: &mut Simulation, window_width: usize, position: (f32, f32)) -> bool {
+        let origin = origin(window_width);
+        if !panel_area(origin).contains(position) {
+            return false;
+        }
+
+        match widgets(origin).into_iter().find(|(_, area)| area.contains(position)).map(|(widget, _)| widget) {
+            Some(Widget::Slider(slider)) => {
+                self.dragging = Some(slider);
+                self.drag(simulation, window_width, position);
+            }
+            Some(Widget::Pause) => self.paused = !self.paused,
+            Some(Widget::Save) => self.checkpoint = Some(simulation.clone()),
+            Some(Widget::Load) => {
+                if let Some(checkpoint) = &self.checkpoint {
+                    *simulation = checkpoint.clone();
+                }
+            }
+            None => (),
+        }
+
+        true
+    }
+
+    /// Moves the slider being dragged, returns true if a slider is being dragged and the board should ignore the mouse
+    pub fn drag(&mut self, simulation: &mut Simulation, window_width: usize, position: (f32, f32)) -> bool {
+        let Some(slider) = self.dragging else {
+            return false;
+        };
+
+        let Some((_, area)) = widgets(origin(window_width)).into_iter().find(|(widget, _)| *widget == Widget::Slider(slider)) else {
+            return false;
+        };
+        let fraction = area.fraction(position.0);
+
+        match slider {
+            Slider::Speed => self.speed = 1 + (fraction * (MAX_SPEED - 1) as f32).round() as usize,
+            Slider::Mutation => {
+                let rate = (fraction * MAX_MUTATION_RATE * 1000.0).round() / 1000.0;
+                let mutation = simulation.config().mutation;
+
+                if rate != mutation.rate {
+                    simulation.update_config(ConfigUpdate { mutation: Some(MutationConfig { rate, ..mutation }), ..Default::default() })
+                        .expect("The slider only picks rates between 0 and 1 of valid settings");
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Changes the number of steps run every tick of the run mode, the speed stays between 1 and the largest speed
+    pub fn change_speed(&mut self, change: isize) {
+        self.speed = self.speed.saturating_add_signed(change).clamp(1, MAX_SPEED);
+    }
+
+    /// Copies the speed and whether the simulation is paused without the checkpoint, such that the panel
+    /// can be drawn without copying the simulation
+    pub fn view(&self) -> Self {
+        Self { checkpoint: None, dragging: None, ..*self }
+    }
+
+    /// Stops dragging, returns true if a slider was being dragged
+    pub fn release(&mut self) -> bool {
+        self.dragging.take().is_some()
+    }
+
+    /// Draws the panel in the top right corner of a frame showing the tick and mutation rate of the simulation
+    pub fn draw(&self, frame: &mut Frame, tick: u64, mutation_rate: f32, window_width: usize) {
+        let origin = origin(window_width);
+        let panel = panel_area(origin);
+        frame.fill_rect(panel.x, panel.y, panel.w, panel.h, PANEL);
+
+        let text_x = origin.0 + PADDING as isize;
+        frame.draw_text(text_x, origin.1 + PADDING as isize, &format!("TICK {}", tick), super::TEXT_SCALE, TEXT);
+
+        for (widget, area) in widgets(origin) {
+            match widget {
+                Widget::Slider(slider) => {
+                    let (label, fraction) = match slider {
+                        Slider::Speed => (format!("SPEED {}", self.speed), (self.speed - 1) as f32 / (MAX_SPEED - 1) as f32),
+                        Slider::Mutation => (format!("MUTATION {:.3}", mutation_rate), mutation_rate / MAX_MUTATION_RATE),
+                    };
+
+                    frame.draw_text(text_x, area.y - LINE_HEIGHT as isize, &label, super::TEXT_SCALE, TEXT);
+                    frame.fill_rect(area.x, area.y, area.w, area.h, TRACK);
+
+                    let handle = area.x + (fraction.clamp(0.0, 1.0) * area.w.saturating_sub(PADDING) as f32) as isize;
+                    frame.fill_rect(handle, area.y, PADDING, area.h, TEXT);
+                }
+                Widget::Pause | Widget::Save | Widget::Load => {
+                    let label = match widget {
+                        Widget::Pause if self.paused => "RUN",
+                        Widget::Pause => "PAUSE",
+                        Widget::Save => "SAVE",
+                        _ => "LOAD",
+                    };
+
+                    frame.fill_rect(area.x, area.y, area.w, area.h, TRACK);
+                    frame.draw_text(area.x + PADDING as isize, area.y + PADDING as isize, label, super::TEXT_SCALE, TEXT);
+                }
+            }
+        }
+    }
+}
+
+/// Returns true if a position in a window of some width is inside the panel
+pub(crate) fn covers(window_width: usize, position: (f32, f32)) -> bool {
+    panel_area(origin(window_width)).contains(position)
+}
+
+/// Finds the position of the top left corner of the panel in a window of some width
+fn origin(window_width: usize) -> (isize, isize) {
+    (window_width as isize - (PANEL_WIDTH + PADDING) as isize, PADDING as isize)
+}
+
+/// Finds the area covered by the panel
+fn panel_area(origin: (isize, isize)) -> Area {
+    Area { x: origin.0, y: origin.1, w: PANEL_WIDTH, h: 5 * PADDING + 3 * LINE_HEIGHT + 2 * SLIDER_HEIGHT + BUTTON_HEIGHT }
+}
+
+/// Finds the areas of all the widgets of the panel
+fn widgets(origin: (isize, isize)) -> Vec<(Widget, Area)> {
+    let x = origin.0 + PADDING as isize;
+    let w = PANEL_WIDTH - 2 * PADDING;
+
+    // The tick counter and the label of the speed take up the first two lines
+    let speed_y = origin.1 + (PADDING + 2 * LINE_HEIGHT) as isize;
+    let mutation_y = speed_y + (SLIDER_HEIGHT + PADDING + LINE_HEIGHT) as isize;
+    let buttons_y = mutation_y + (SLIDER_HEIGHT + 2 * PADDING) as isize;
+    let button_w = (w - 2 * PADDING) / 3;
+    let button = |index: usize| Area { x: x + (index * (button_w + PADDING)) as isize, y: buttons_y, w: button_w, h: BUTTON_HEIGHT };
+
+    vec![
+        (Widget::Slider(Slider::Speed), Area { x, y: speed_y, w, h: SLIDER_HEIGHT }),
+        (Widget::Slider(Slider::Mutation), Area { x, y: mutation_y, w, h: SLIDER_HEIGHT }),
+        (Widget::Pause, button(0)),
+        (Widget::Save, button(1)),
+        (Widget::Load, button(2)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardBuilder;
+    use crate::population::Population;
+    use crate::simulation::SimulationConfig;
+
+    /// The width of the window used in the tests
+    const WIDTH: usize = 400;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+        let size = board.fields.size;
+
+        Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap()
+    }
+
+    /// Finds the center of a widget
+    fn center(widget: Widget) -> (f32, f32) {
+        let (_, area) = widgets(origin(WIDTH)).into_iter().find(|(found, _)| *found == widget).unwrap();
+
+        (area.x as f32 + area.w as f32 / 2.0, area.y as f32 + area.h as f32 / 2.0)
+    }
+
+    #[test]
+    fn widgets_inside_panel() {
+        let panel = panel_area(origin(WIDTH));
+
+        for (_, area) in widgets(origin(WIDTH)) {
+            assert!(panel.contains((area.x as f32, area.y as f32)));
+            assert!(panel.contains(((area.x + area.w as isize) as f32 - 1.0, (area.y + area.h as isize) as f32 - 1.0)));
+        }
+    }
+
+    #[test]
+    fn control_panel_press_outside() {
+        let mut panel = ControlPanel::default();
+
+        assert!(!panel.press(&mut simulation(), WIDTH, (10.0, 10.0)));
+        assert!(!covers(WIDTH, (10.0, 10.0)));
+        assert_eq!(1, panel.steps());
+    }
+
+    #[test]
+    fn control_panel_view() {
+        let mut panel = ControlPanel::default();
+        let mut simulation = simulation();
+        panel.press(&mut simulation, WIDTH, center(Widget::Save));
+        panel.press(&mut simulation, WIDTH, center(Widget::Pause));
+        let view = panel.view();
+
+        assert!(covers(WIDTH, center(Widget::Save)));
+        assert!(view.paused);
+        assert_eq!(None, view.checkpoint.map(|checkpoint| checkpoint.tick()));
+    }
+
+    #[test]
+    fn control_panel_pause() {
+        let mut panel = ControlPanel::default();
+        let mut simulation = simulation();
+
+        assert!(panel.press(&mut simulation, WIDTH, center(Widget::Pause)));
+        assert_eq!(0, panel.steps());
+
+        panel.press(&mut simulation, WIDTH, center(Widget::Pause));
+
+        assert_eq!(1, panel.steps());
+    }
+
+    #[test]
+    fn control_panel_change_speed() {
+        let mut panel = ControlPanel::default();
+        panel.change_speed(-1);
+
+        assert_eq!(1, panel.steps());
+
+        panel.change_speed(3);
+
+        assert_eq!(4, panel.steps());
+
+        panel.change_speed(100);
+
+        assert_eq!(MAX_SPEED, panel.steps());
+    }
+
+    #[test]
+    fn control_panel_sliders() {
+        let mut panel = ControlPanel::default();
+        let mut simulation = simulation();
+        let (_, right) = center(Widget::Slider(Slider::Speed));
+        panel.press(&mut simulation, WIDTH, center(Widget::Slider(Slider::Mutation)));
+        panel.release();
+
+        assert_eq!(0.05, simulation.mutation_rate());
+
+        panel.press(&mut simulation, WIDTH, center(Widget::Slider(Slider::Speed)));
+        panel.drag(&mut simulation, WIDTH, (WIDTH as f32 + 50.0, right));
+
+        assert_eq!(MAX_SPEED, panel.steps());
+        assert!(panel.release());
+        assert!(!panel.drag(&mut simulation, WIDTH, (0.0, right)));
+    }
+
+    #[test]
+    fn control_panel_save_load() {
+        let mut panel = ControlPanel::default();
+        let mut simulation = simulation();
+
+        // Loading without a checkpoint does nothing
+        panel.press(&mut simulation, WIDTH, center(Widget::Load));
+        simulation.step();
+        panel.press(&mut simulation, WIDTH, center(Widget::Save));
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(3, simulation.tick());
+
+        panel.press(&mut simulation, WIDTH, center(Widget::Load));
+
+        assert_eq!(1, simulation.tick());
+    }
+
+    #[test]
+    fn control_panel_draw() {
+        let mut frame = Frame::new(WIDTH, 200);
+        ControlPanel::default().draw(&mut frame, 0, 0.01, WIDTH);
+        let panel = panel_area(origin(WIDTH));
+
+        assert_eq!(PANEL, frame.pixels()[panel.x as usize + panel.y as usize * WIDTH]);
+    }
+}
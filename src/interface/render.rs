@@ -0,0 +1,465 @@
+use crate::board::{Coord, Size};
+
+/// The width of a glyph in the font
+const GLYPH_WIDTH: usize = 3;
+/// The height of a glyph in the font
+const GLYPH_HEIGHT: usize = 5;
+
+/// A small bitmap font, every row of a glyph is 3 bits with the highest bit to the left
+const FONT: [(char, [u8; GLYPH_HEIGHT]); 50] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b001, 0b001, 0b001]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('A', [0b010, 0b101, 0b111, 0b101, 0b101]),
+    ('B', [0b110, 0b101, 0b110, 0b101, 0b110]),
+    ('C', [0b011, 0b100, 0b100, 0b100, 0b011]),
+    ('D', [0b110, 0b101, 0b101, 0b101, 0b110]),
+    ('E', [0b111, 0b100, 0b110, 0b100, 0b111]),
+    ('F', [0b111, 0b100, 0b110, 0b100, 0b100]),
+    ('G', [0b011, 0b100, 0b101, 0b101, 0b011]),
+    ('H', [0b101, 0b101, 0b111, 0b101, 0b101]),
+    ('I', [0b111, 0b010, 0b010, 0b010, 0b111]),
+    ('J', [0b001, 0b001, 0b001, 0b101, 0b010]),
+    ('K', [0b101, 0b101, 0b110, 0b101, 0b101]),
+    ('L', [0b100, 0b100, 0b100, 0b100, 0b111]),
+    ('M', [0b101, 0b111, 0b111, 0b101, 0b101]),
+    ('N', [0b110, 0b101, 0b101, 0b101, 0b101]),
+    ('O', [0b010, 0b101, 0b101, 0b101, 0b010]),
+    ('P', [0b110, 0b101, 0b110, 0b100, 0b100]),
+    ('Q', [0b010, 0b101, 0b101, 0b110, 0b011]),
+    ('R', [0b110, 0b101, 0b110, 0b101, 0b101]),
+    ('S', [0b011, 0b100, 0b010, 0b001, 0b110]),
+    ('T', [0b111, 0b010, 0b010, 0b010, 0b010]),
+    ('U', [0b101, 0b101, 0b101, 0b101, 0b111]),
+    ('V', [0b101, 0b101, 0b101, 0b101, 0b010]),
+    ('W', [0b101, 0b101, 0b111, 0b111, 0b101]),
+    ('X', [0b101, 0b101, 0b010, 0b101, 0b101]),
+    ('Y', [0b101, 0b101, 0b010, 0b010, 0b010]),
+    ('Z', [0b111, 0b001, 0b010, 0b100, 0b111]),
+    (' ', [0b000, 0b000, 0b000, 0b000, 0b000]),
+    ('.', [0b000, 0b000, 0b000, 0b000, 0b010]),
+    (',', [0b000, 0b000, 0b000, 0b010, 0b100]),
+    (':', [0b000, 0b010, 0b000, 0b010, 0b000]),
+    ('-', [0b000, 0b000, 0b111, 0b000, 0b000]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+    ('=', [0b000, 0b111, 0b000, 0b111, 0b000]),
+    ('_', [0b000, 0b000, 0b000, 0b000, 0b111]),
+    ('/', [0b001, 0b001, 0b010, 0b100, 0b100]),
+    ('%', [0b101, 0b001, 0b010, 0b100, 0b101]),
+    ('(', [0b001, 0b010, 0b010, 0b010, 0b001]),
+    (')', [0b100, 0b010, 0b010, 0b010, 0b100]),
+    ('?', [0b111, 0b001, 0b010, 0b000, 0b010]),
+    ('>', [0b100, 0b010, 0b001, 0b010, 0b100]),
+];
+
+/// The color of the window behind the board
+pub(crate) const BACKGROUND: u32 = 0x202020;
+/// The color of the background of panels
+pub(crate) const PANEL: u32 = 0x101010;
+/// The color of text
+pub(crate) const TEXT: u32 = 0xE0E0E0;
+/// The color of the selection rectangle
+pub(crate) const SELECTION: u32 = 0xFFFFFF;
+/// The color of the outlines of the plants highlighted in the lineage panel
+pub(crate) const LINEAGE: u32 = 0xFFC040;
+
+/// Decides where the board is drawn in the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Camera {
+    /// The number of pixels per cell
+    pub scale: f32,
+    /// The position in pixels of the top left corner of the board
+    pub offset: (f32, f32),
+}
+
+impl Camera {
+    /// Creates a camera showing the entire board centered in the window
+    pub fn fit(size: Size, window: (usize, usize)) -> Self {
+        let (w, h) = size.size();
+
+        if w == 0 || h == 0 {
+            return Self { scale: 1.0, offset: (0.0, 0.0) };
+        }
+
+        let scale = (window.0 as f32 / w as f32).min(window.1 as f32 / h as f32);
+        let offset = ((window.0 as f32 - w as f32 * scale) / 2.0, (window.1 as f32 - h as f32 * scale) / 2.0);
+
+        Self { scale, offset }
+    }
+
+    /// Creates a camera zoomed in from the one showing the entire board, with a position on the board
+    /// in the middle of the window. A zoom of 1 centered on the middle of the board shows the entire board
+    pub fn zoomed(size: Size, window: (usize, usize), zoom: f32, center: (f32, f32)) -> Self {
+        let scale = Self::fit(size, window).scale * zoom.max(1.0);
+        let offset = (window.0 as f32 / 2.0 - center.0 * scale, window.1 as f32 / 2.0 - center.1 * scale);
+
+        Self { scale, offset }
+    }
+
+    /// Finds the part of the board shown in the window as the position of its top left corner and its width
+    /// and height in cells, the part may reach outside the board
+    pub fn viewport(&self, window: (usize, usize)) -> (f32, f32, f32, f32) {
+        (-self.offset.0 / self.scale, -self.offset.1 / self.scale, window.0 as f32 / self.scale, window.1 as f32 / self.scale)
+    }
+
+    /// Finds the cell at a position in the window, returns None if the position is outside the board
+    pub fn screen_to_board(&self, size: Size, position: (f32, f32)) -> Option<Coord> {
+        let x = (position.0 - self.offset.0) / self.scale;
+        let y = (position.1 - self.offset.1) / self.scale;
+
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let coord = Coord::new(x as usize, y as usize);
+
+        size.index_of(coord).map(|_| coord)
+    }
+
+    /// Finds the position in the window of the top left corner of a cell
+    pub fn board_to_screen(&self, coord: Coord) -> (f32, f32) {
+        (self.offset.0 + coord.x as f32 * self.scale, self.offset.1 + coord.y as f32 * self.scale)
+    }
+}
+
+/// A buffer of pixels to be shown in the window, every pixel is stored as 0RGB
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Frame {
+    /// The width in pixels
+    width: usize,
+    /// The height in pixels
+    height: usize,
+    /// All the pixels row by row
+    pixels: Vec<u32>,
+}
+
+impl Frame {
+    /// Creates a new frame filled with the background color
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![BACKGROUND; width * height] }
+    }
+
+    /// Returns all the pixels row by row
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Captures the frame as it would be shown in the window including everything drawn on top of the board
+    #[cfg(feature = "image")]
+    pub fn capture_frame(&self) -> image::RgbaImage {
+        let rgba = self.pixels.iter()
+            .flat_map(|&pixel| {
+                let [_, r, g, b] = pixel.to_be_bytes();
+                [r, g, b, 255]
+            })
+            .collect();
+
+        // The buffer always has exactly 4 bytes for every pixel of the frame
+        image::RgbaImage::from_raw(self.width as u32, self.height as u32, rgba).unwrap()
+    }
+
+    /// Draws the board in the frame from rgba pixels with one pixel per cell
+    pub fn draw_board(&mut self, camera: &Camera, size: Size, rgba: &[u8]) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let Some(coord) = camera.screen_to_board(size, (x as f32 + 0.5, y as f32 + 0.5)) {
+                    let index = size.index_of(coord).unwrap() * 4;
+                    self.pixels[x + y * self.width] = u32::from_be_bytes([0, rgba[index], rgba[index + 1], rgba[index + 2]]);
+                }
+            }
+        }
+    }
+
+    /// Draws an image from rgba pixels with one pixel per pixel of the frame, the image is clipped to the frame
+    pub fn draw_image(&mut self, x: isize, y: isize, size: Size, rgba: &[u8]) {
+        let (w, h) = size.size();
+
+        for row in 0..h {
+            for column in 0..w {
+                let (px, py) = (x + column as isize, y + row as isize);
+                if px < 0 || py < 0 || px as usize >= self.width || py as usize >= self.height {
+                    continue;
+                }
+
+                let index = (column + row * w) * 4;
+                self.pixels[px as usize + py as usize * self.width] = u32::from_be_bytes([0, rgba[index], rgba[index + 1], rgba[index + 2]]);
+            }
+        }
+    }
+
+    /// Fills a rectangle of pixels with a color, the rectangle is clipped to the frame
+    pub fn fill_rect(&mut self, x: isize, y: isize, w: usize, h: usize, color: u32) {
+        let x0 = x.clamp(0, self.width as isize) as usize;
+        let y0 = y.clamp(0, self.height as isize) as usize;
+        let x1 = (x + w as isize).clamp(0, self.width as isize) as usize;
+        let y1 = (y + h as isize).clamp(0, self.height as isize) as usize;
+
+        for row in y0..y1 {
+            self.pixels[row * self.width + x0..row * self.width + x1].fill(color);
+        }
+    }
+
+    /// Draws the outline of a rectangle of pixels, the rectangle is clipped to the frame
+    pub fn draw_rect_outline(&mut self, x: isize, y: isize, w: usize, h: usize, color: u32) {
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        self.fill_rect(x, y, w, 1, color);
+        self.fill_rect(x, y + h as isize - 1, w, 1, color);
+        self.fill_rect(x, y, 1, h, color);
+        self.fill_rect(x + w as isize - 1, y, 1, h, color);
+    }
+
+    /// Draws a line one pixel wide between two pixels including both ends, the line is clipped to the frame
+    pub fn draw_line(&mut self, start: (isize, isize), end: (isize, isize), color: u32) {
+        let (dx, dy) = ((end.0 - start.0).abs(), -(end.1 - start.1).abs());
+        let (step_x, step_y) = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+        let (mut x, mut y) = start;
+        let mut error = dx + dy;
+
+        loop {
+            self.fill_rect(x, y, 1, 1, color);
+            if (x, y) == end {
+                break;
+            }
+
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Draws a line of text with the top left corner at a position, every pixel of the font is drawn as a square
+    /// of scale pixels. Letters are drawn as upper case and unknown characters are drawn as question marks
+    pub fn draw_text(&mut self, x: isize, y: isize, text: &str, scale: usize, color: u32) {
+        for (index, character) in text.chars().enumerate() {
+            let glyph = glyph(character);
+            let left = x + (index * (GLYPH_WIDTH + 1) * scale) as isize;
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0 {
+                        self.fill_rect(left + (column * scale) as isize, y + (row * scale) as isize, scale, scale, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws a panel with a dark background and a line of text for every entry at a position
+    pub fn draw_panel(&mut self, x: isize, y: isize, lines: &[String], scale: usize) {
+        let (w, h) = panel_size(lines, scale);
+        self.fill_rect(x, y, w, h, PANEL);
+
+        for (index, line) in lines.iter().enumerate() {
+            let line_y = y + ((2 + index * (GLYPH_HEIGHT + 2)) * scale) as isize;
+            self.draw_text(x + (2 * scale) as isize, line_y, line, scale, TEXT);
+        }
+    }
+}
+
+/// Finds the size in pixels of a panel with some lines of text
+pub(crate) fn panel_size(lines: &[String], scale: usize) -> (usize, usize) {
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+    ((longest * (GLYPH_WIDTH + 1) + 3) * scale, (lines.len() * (GLYPH_HEIGHT + 2) + 2) * scale)
+}
+
+/// Finds the line of a panel drawn at a position which a point is on, None if the point is outside the panel
+pub(crate) fn panel_line(x: isize, y: isize, lines: &[String], scale: usize, point: (f32, f32)) -> Option<usize> {
+    let (w, h) = panel_size(lines, scale);
+    let (px, py) = (point.0 as isize - x, point.1 as isize - y);
+    if px < 0 || py < 0 || px >= w as isize || py >= h as isize {
+        return None;
+    }
+
+    let line = (py as usize / scale).checked_sub(2)? / (GLYPH_HEIGHT + 2);
+
+    (line < lines.len()).then_some(line)
+}
+
+/// Finds the glyph of a character in the font
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT] {
+    let character = character.to_ascii_uppercase();
+
+    FONT.iter()
+        .find(|(glyph_character, _)| *glyph_character == character)
+        .or_else(|| FONT.iter().find(|(glyph_character, _)| *glyph_character == '?'))
+        .map(|(_, glyph)| *glyph)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_fit() {
+        let camera = Camera::fit(Size::new(10, 5), (200, 200));
+
+        assert_eq!(20.0, camera.scale);
+        assert_eq!((0.0, 50.0), camera.offset);
+    }
+
+    #[test]
+    fn camera_fit_empty() {
+        let camera = Camera::fit(Size::new(0, 5), (200, 200));
+
+        assert_eq!(1.0, camera.scale);
+    }
+
+    #[test]
+    fn camera_screen_to_board() {
+        let size = Size::new(10, 5);
+        let camera = Camera::fit(size, (200, 200));
+
+        assert_eq!(Some(Coord::new(0, 0)), camera.screen_to_board(size, (0.0, 50.0)));
+        assert_eq!(Some(Coord::new(2, 1)), camera.screen_to_board(size, (59.0, 89.0)));
+        assert_eq!(None, camera.screen_to_board(size, (59.0, 49.0)));
+        assert_eq!(None, camera.screen_to_board(size, (59.0, 150.0)));
+    }
+
+    #[test]
+    fn camera_board_to_screen() {
+        let camera = Camera::fit(Size::new(10, 5), (200, 200));
+
+        assert_eq!((40.0, 70.0), camera.board_to_screen(Coord::new(2, 1)));
+    }
+
+    #[test]
+    fn camera_zoomed() {
+        let size = Size::new(10, 5);
+
+        assert_eq!(Camera::fit(size, (200, 200)), Camera::zoomed(size, (200, 200), 1.0, (5.0, 2.5)));
+
+        let camera = Camera::zoomed(size, (200, 200), 2.0, (2.0, 1.0));
+
+        assert_eq!(40.0, camera.scale);
+        assert_eq!((-0.5, -1.5, 5.0, 5.0), camera.viewport((200, 200)));
+        assert_eq!(Some(Coord::new(2, 1)), camera.screen_to_board(size, (100.0, 100.0)));
+    }
+
+    #[test]
+    fn frame_draw_image() {
+        let mut frame = Frame::new(3, 2);
+        frame.draw_image(2, 0, Size::new(2, 1), &[255, 0, 0, 255, 0, 255, 0, 255]);
+        let b = BACKGROUND;
+
+        assert_eq!(vec![b, b, 0xFF0000, b, b, b], frame.pixels);
+    }
+
+    #[test]
+    fn frame_new() {
+        let frame = Frame::new(4, 3);
+
+        assert_eq!((4, 3), (frame.width, frame.height));
+        assert_eq!(vec![BACKGROUND; 12], frame.pixels);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn frame_capture_frame() {
+        let mut frame = Frame::new(3, 2);
+        frame.fill_rect(2, 1, 1, 1, 0xFF0000);
+        let image = frame.capture_frame();
+
+        assert_eq!((3, 2), image.dimensions());
+        assert_eq!(&image::Rgba([255, 0, 0, 255]), image.get_pixel(2, 1));
+    }
+
+    #[test]
+    fn frame_draw_board() {
+        let size = Size::new(2, 1);
+        let mut frame = Frame::new(4, 4);
+        let camera = Camera::fit(size, (4, 4));
+        frame.draw_board(&camera, size, &[255, 0, 0, 255, 0, 0, 255, 255]);
+
+        assert_eq!(&[BACKGROUND; 4], &frame.pixels[0..4]);
+        assert_eq!(&[0xFF0000, 0xFF0000, 0x0000FF, 0x0000FF], &frame.pixels[4..8]);
+        assert_eq!(&[0xFF0000, 0xFF0000, 0x0000FF, 0x0000FF], &frame.pixels[8..12]);
+        assert_eq!(&[BACKGROUND; 4], &frame.pixels[12..16]);
+    }
+
+    #[test]
+    fn frame_fill_rect() {
+        let mut frame = Frame::new(3, 3);
+        frame.fill_rect(-1, 1, 3, 5, 1);
+
+        assert_eq!(vec![BACKGROUND, BACKGROUND, BACKGROUND, 1, 1, BACKGROUND, 1, 1, BACKGROUND], frame.pixels);
+    }
+
+    #[test]
+    fn frame_draw_rect_outline() {
+        let mut frame = Frame::new(3, 3);
+        frame.draw_rect_outline(0, 0, 3, 3, 1);
+
+        assert_eq!(vec![1, 1, 1, 1, BACKGROUND, 1, 1, 1, 1], frame.pixels);
+    }
+
+    #[test]
+    fn frame_draw_line() {
+        let mut frame = Frame::new(3, 3);
+        frame.draw_line((0, 2), (2, 0), 1);
+        frame.draw_line((-5, 0), (0, 0), 2);
+        let b = BACKGROUND;
+
+        assert_eq!(vec![2, b, 1, b, 1, b, 1, b, b], frame.pixels);
+    }
+
+    #[test]
+    fn frame_draw_text() {
+        let mut frame = Frame::new(8, 5);
+        frame.draw_text(0, 0, "1-", 1, 1);
+        let b = BACKGROUND;
+
+        assert_eq!(&[b, 1, b, b, b, b, b, b], &frame.pixels[0..8]);
+        assert_eq!(&[1, 1, b, b, b, b, b, b], &frame.pixels[8..16]);
+        assert_eq!(&[b, 1, b, b, 1, 1, 1, b], &frame.pixels[16..24]);
+        assert_eq!(&[1, 1, 1, b, b, b, b, b], &frame.pixels[32..40]);
+    }
+
+    #[test]
+    fn frame_draw_panel() {
+        let lines = vec!["AB".to_string()];
+        let (w, h) = panel_size(&lines, 1);
+        let mut frame = Frame::new(w, h);
+        frame.draw_panel(0, 0, &lines, 1);
+
+        assert_eq!((11, 9), (w, h));
+        assert_eq!(PANEL, frame.pixels[0]);
+        assert!(frame.pixels.contains(&TEXT));
+        assert!(!frame.pixels.contains(&BACKGROUND));
+    }
+
+    #[test]
+    fn panel_line_lookup() {
+        let lines = vec!["A".to_string(), "B".to_string()];
+
+        assert_eq!(None, panel_line(4, 4, &lines, 2, (5.0, 5.0)));
+        assert_eq!(Some(0), panel_line(4, 4, &lines, 2, (5.0, 8.0)));
+        assert_eq!(Some(1), panel_line(4, 4, &lines, 2, (5.0, 22.0)));
+        assert_eq!(None, panel_line(4, 4, &lines, 2, (30.0, 8.0)));
+        assert_eq!(None, panel_line(4, 4, &lines, 2, (5.0, 2.0)));
+    }
+
+    #[test]
+    fn glyph_lookup() {
+        assert_eq!(glyph('A'), glyph('a'));
+        assert_eq!(glyph('?'), glyph('~'));
+        assert_eq!([0b111, 0b101, 0b101, 0b101, 0b111], glyph('0'));
+    }
+}
@@ -0,0 +1,408 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::dirty::DirtyCells;
+use crate::governor::{FrameBudget, Governor, TickRateMeter};
+use crate::population::Population;
+use crate::render::{Palette, RenderMode, RenderStyle};
+use crate::simulation::Simulation;
+
+use super::events::Editor;
+use super::graph::Sample;
+use super::lineage::{LineageFocus, LineageTree};
+#[cfg(feature = "gui-panel")]
+use super::panel::ControlPanel;
+
+/// The time the simulation thread waits for a command while no steps are due
+const PAUSED_WAIT: Duration = Duration::from_millis(10);
+
+/// The number of ticks the latest alert is shown for after it was raised
+const ALERT_NOTICE_TICKS: u64 = 500;
+
+/// A change to the state of the simulation thread sent from the window
+pub(crate) type Command = Box<dyn FnOnce(&mut Model) + Send>;
+
+/// Everything owned by the simulation thread, the window changes it by sending commands
+#[derive(Debug)]
+pub(crate) struct Model {
+    /// The simulation being run
+    pub simulation: Simulation,
+    /// The state of the edit mode
+    pub editor: Editor,
+    /// How the board is colored
+    pub style: RenderStyle,
+    /// The governor setting how many steps are run
+    pub governor: Governor,
+    /// The measured number of steps run every second
+    pub meter: TickRateMeter,
+    /// The plant the lineage panel is opened on, None if the panel is closed
+    pub lineage: Option<LineageFocus>,
+    /// The control panel setting the speed of the simulation
+    #[cfg(feature = "gui-panel")]
+    pub panel: ControlPanel,
+}
+
+impl Model {
+    /// Creates the state of a simulation which has not been edited, shown in the default render mode.
+    /// Occupancy is tracked from the start such that the heat map modes can be shown
+    pub fn new(mut simulation: Simulation, governor: Governor) -> Self {
+        if simulation.occupancy().is_none() {
+            simulation.track_occupancy(true);
+        }
+
+        Self {
+            simulation,
+            editor: Editor::default(),
+            style: RenderStyle::default(),
+            governor,
+            meter: TickRateMeter::default(),
+            lineage: None,
+            #[cfg(feature = "gui-panel")]
+            panel: ControlPanel::default(),
+        }
+    }
+
+    /// Returns the number of steps due now, None if the simulation runs as fast as possible.
+    /// While the control panel is paused no steps are due and the time is not caught up later
+    fn steps(&mut self, now: Instant) -> Option<usize> {
+        #[cfg(feature = "gui-panel")]
+        let speed = self.panel.steps();
+        #[cfg(not(feature = "gui-panel"))]
+        let speed = 1;
+
+        if speed == 0 {
+            self.governor.reset();
+            return Some(0);
+        }
+
+        self.governor.due(now).map(|due| due as usize * speed)
+    }
+
+    /// Takes a snapshot of everything the window shows
+    /// 
+    /// # Parameters
+    /// 
+    /// samples: The samples for the graphs taken since the last snapshot
+    fn snapshot(&self, samples: Vec<Sample>) -> Snapshot {
+        let styled = match self.style.mode {
+            // The canvas is only used while the whole board is simulated since it does not dim the frozen part,
+            // and it only knows the classic colors
+            RenderMode::GenomeColor if self.simulation.active_region().is_none() && self.style.palette == Palette::Classic => None,
+            // The relatives of the plant picked in the lineage panel are shown
+            RenderMode::Relatedness => {
+                let style = RenderStyle { related_to: self.lineage.map(|focus| focus.selected), ..self.style.clone() };
+                Some(crate::render::render_style(&self.simulation, &style))
+            }
+            _ => Some(crate::render::render_style(&self.simulation, &self.style)),
+        };
+        let status = if self.editor.enabled {
+            Some(self.editor.status())
+        } else if self.style.palette != Palette::Classic {
+            Some(format!("{} {}", self.style.mode.name(), self.style.palette.name()))
+        } else if self.style.mode != RenderMode::GenomeColor {
+            Some(self.style.mode.name().to_string())
+        } else {
+            None
+        };
+        let notice = self.simulation.alerts()
+            .and_then(|monitor| monitor.latest())
+            .filter(|alert| self.simulation.tick() < alert.tick + ALERT_NOTICE_TICKS)
+            .map(|alert| format!("ALERT AT {}: {}", alert.tick, alert.message()));
+
+        Snapshot {
+            tick: self.simulation.tick(),
+            tick_rate: self.meter.ticks_per_second(),
+            board: self.simulation.board().clone(),
+            population: self.simulation.population().clone(),
+            dirty: self.simulation.dirty().clone(),
+            styled,
+            samples,
+            status,
+            notice,
+            lineage: self.lineage.and_then(|focus| LineageTree::build(self.simulation.phylogeny(), self.simulation.population(), focus)),
+            #[cfg(feature = "gui-panel")]
+            panel: self.panel.view(),
+            #[cfg(feature = "gui-panel")]
+            mutation_rate: self.simulation.mutation_rate(),
+        }
+    }
+}
+
+/// The state of the simulation sent to the window to be drawn
+#[derive(Clone, Debug)]
+pub(crate) struct Snapshot {
+    /// The tick of the simulation
+    pub tick: u64,
+    /// The measured number of steps run every second
+    pub tick_rate: f32,
+    /// The board of the simulation
+    pub board: Board,
+    /// The plants of the simulation
+    pub population: Population,
+    /// The cells which changed since the previous snapshot
+    pub dirty: DirtyCells,
+    /// The board drawn in the render mode if it is not showing the genomes, these are drawn entirely every time
+    pub styled: Option<Vec<u8>>,
+    /// The samples for the graphs taken since the previous snapshot
+    pub samples: Vec<Sample>,
+    /// The line of text describing the edit mode or the render mode
+    pub status: Option<String>,
+    /// The line of text describing the latest alert if it was raised recently
+    pub notice: Option<String>,
+    /// The lineage tree of the plant the lineage panel is opened on, None if the panel is closed or the plant was pruned
+    pub lineage: Option<LineageTree>,
+    /// The control panel without its checkpoint
+    #[cfg(feature = "gui-panel")]
+    pub panel: ControlPanel,
+    /// The mutation rate of the simulation
+    #[cfg(feature = "gui-panel")]
+    pub mutation_rate: f32,
+}
+
+/// The thread running the simulation, it keeps stepping while the window draws the latest snapshot
+/// such that slow steps do not freeze the window and slow drawing does not slow down the simulation
+#[derive(Debug)]
+pub(crate) struct SimulationThread {
+    /// The channel the commands are sent through
+    commands: mpsc::Sender<Command>,
+    /// The channel the snapshots are received through
+    snapshots: Receiver<Snapshot>,
+    /// The time the steps between two snapshots may take, measured from the frames of the window
+    budget: Arc<Mutex<FrameBudget>>,
+}
+
+impl SimulationThread {
+    /// Starts running a simulation on its own thread and returns the thread together with a snapshot of its state
+    /// before the first step, the thread stops once it is dropped
+    /// 
+    /// # Parameters
+    /// 
+    /// model: The state to run
+    /// 
+    /// max_snapshot_lag: The largest number of snapshots waiting to be received, the simulation keeps running without
+    /// taking snapshots while this many are waiting. At least 1 is used
+    pub fn spawn(mut model: Model, max_snapshot_lag: usize) -> (Self, Snapshot) {
+        let first = model.snapshot(vec![Sample::new(&model.simulation)]);
+        model.simulation.clear_dirty();
+
+        let (commands, command_receiver) = mpsc::channel();
+        let (snapshot_sender, snapshots) = mpsc::sync_channel(max_snapshot_lag.max(1));
+        let budget = Arc::new(Mutex::new(FrameBudget::default()));
+        let thread_budget = Arc::clone(&budget);
+        thread::spawn(move || run(model, command_receiver, snapshot_sender, thread_budget));
+
+        (Self { commands, snapshots, budget }, first)
+    }
+
+    /// Sends a command which is run on the simulation thread before the next step
+    /// 
+    /// # Parameters
+    /// 
+    /// command: The change to make
+    pub fn send<F: FnOnce(&mut Model) + Send + 'static>(&self, command: F) {
+        // The thread only stops when this is dropped so the command cannot be lost
+        let _ = self.commands.send(Box::new(command));
+    }
+
+    /// Adds the time between the latest two frames of the window to the frame budget, the steps due
+    /// are batched such that they fit into the time of a frame
+    /// 
+    /// # Parameters
+    /// 
+    /// interval: The time since the previous frame
+    pub fn record_frame(&self, interval: Duration) {
+        self.budget.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).record_frame(interval);
+    }
+
+    /// Receives all snapshots waiting to be drawn, oldest first
+    pub fn receive(&self) -> mpsc::TryIter<'_, Snapshot> {
+        self.snapshots.try_iter()
+    }
+}
+
+/// Runs the simulation until the window stops listening
+/// 
+/// # Parameters
+/// 
+/// model: The state to run
+/// 
+/// commands: The channel the commands of the window arrive through
+/// 
+/// snapshots: The channel to send the snapshots through
+/// 
+/// budget: The frame budget the steps between two snapshots are fitted into
+fn run(mut model: Model, commands: Receiver<Command>, snapshots: SyncSender<Snapshot>, budget: Arc<Mutex<FrameBudget>>) {
+    let mut samples = Vec::new();
+    let mut changed = false;
+
+    loop {
+        let started = Instant::now();
+
+        loop {
+            match commands.try_recv() {
+                Ok(command) => {
+                    command(&mut model);
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        if changed {
+            samples.push(Sample::new(&model.simulation));
+        }
+
+        // As many of the steps due as fit into a frame are run and only the latest state is sent,
+        // running as fast as possible fills the whole frame with steps
+        let budget = *budget.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut steps = 0;
+        let due = model.steps(started);
+        while budget.fits(due.map(|due| due as u64), steps, started.elapsed()) {
+            model.simulation.step();
+            samples.push(Sample::new(&model.simulation));
+            steps += 1;
+        }
+        changed |= steps > 0;
+        model.meter.record(Instant::now(), model.simulation.tick());
+
+        // The graphs never show more samples than this so older ones are not worth sending
+        let excess = samples.len().saturating_sub(super::GRAPH_SAMPLES);
+        samples.drain(..excess);
+
+        // The changed cells keep adding up until a snapshot gets through
+        if changed {
+            match snapshots.try_send(model.snapshot(std::mem::take(&mut samples))) {
+                Ok(()) => {
+                    model.simulation.clear_dirty();
+                    changed = false;
+                }
+                Err(TrySendError::Full(snapshot)) => samples = snapshot.samples,
+                Err(TrySendError::Disconnected(_)) => return,
+            }
+        }
+
+        if steps == 0 {
+            match commands.recv_timeout(PAUSED_WAIT) {
+                Ok(command) => {
+                    command(&mut model);
+                    changed = true;
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        } else if let Some(rest) = budget.frame_time().checked_sub(started.elapsed()) {
+            thread::sleep(rest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::{AlertConfig, AlertRule};
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::governor::RunMode;
+    use crate::population::Plant;
+
+    fn model() -> Model {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+
+        Model::new(Simulation::new(board, population, Default::default()).unwrap(), Governor::new(RunMode::FixedTicksPerSecond(500.0)))
+    }
+
+    /// Receives snapshots until one fulfils a condition or a second has passed
+    fn wait_for<F: Fn(&Snapshot) -> bool>(thread: &SimulationThread, condition: F) -> Option<Snapshot> {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(1) {
+            if let Some(snapshot) = thread.receive().find(|snapshot| condition(snapshot)) {
+                return Some(snapshot);
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        None
+    }
+
+    #[test]
+    fn simulation_thread_first_snapshot() {
+        let (_, first) = SimulationThread::spawn(model(), 2);
+
+        assert_eq!(0, first.tick);
+        assert_eq!(1, first.population.count());
+        assert_eq!(vec![0], first.samples.iter().map(|sample| sample.tick).collect::<Vec<_>>());
+        assert!(first.styled.is_none());
+    }
+
+    #[test]
+    fn simulation_thread_steps() {
+        let (thread, _) = SimulationThread::spawn(model(), 2);
+        let snapshot = wait_for(&thread, |snapshot| snapshot.tick >= 3).unwrap();
+
+        assert_eq!(Some(&snapshot.tick), snapshot.samples.last().map(|sample| &sample.tick));
+    }
+
+    #[test]
+    fn simulation_thread_max_speed() {
+        let mut model = model();
+        model.governor.set_mode(RunMode::MaxSpeed);
+        let (thread, _) = SimulationThread::spawn(model, 2);
+
+        // A single step interval holds many steps
+        assert!(wait_for(&thread, |snapshot| snapshot.tick >= 100).is_some());
+    }
+
+    #[test]
+    fn simulation_thread_bounded() {
+        let (thread, _) = SimulationThread::spawn(model(), 1);
+        wait_for(&thread, |snapshot| snapshot.tick >= 1).unwrap();
+        thread::sleep(Duration::from_millis(20));
+
+        assert!(thread.receive().count() <= 1);
+    }
+
+    #[test]
+    fn simulation_thread_tick_rate() {
+        let (thread, first) = SimulationThread::spawn(model(), 2);
+        thread.record_frame(Duration::from_millis(5));
+        let snapshot = wait_for(&thread, |snapshot| snapshot.tick >= 50 && snapshot.tick_rate > 0.0).unwrap();
+
+        assert_eq!(0.0, first.tick_rate);
+        // The governor asks for 500 steps every second
+        assert!(snapshot.tick_rate > 100.0 && snapshot.tick_rate < 1000.0, "{}", snapshot.tick_rate);
+    }
+
+    #[test]
+    fn simulation_thread_send() {
+        let (thread, _) = SimulationThread::spawn(model(), 2);
+        thread.send(|model| model.style.mode = RenderMode::Energy);
+        let snapshot = wait_for(&thread, |snapshot| snapshot.styled.is_some()).unwrap();
+
+        assert_eq!(Some("ENERGY".to_string()), snapshot.status);
+        assert_eq!(4 * 4 * 4, snapshot.styled.unwrap().len());
+    }
+
+    #[test]
+    fn simulation_thread_palette() {
+        let (thread, _) = SimulationThread::spawn(model(), 2);
+        thread.send(|model| model.style.set_palette(Palette::Viridis));
+        let snapshot = wait_for(&thread, |snapshot| snapshot.styled.is_some()).unwrap();
+
+        assert_eq!(Some("GENOME VIRIDIS".to_string()), snapshot.status);
+    }
+
+    #[test]
+    fn simulation_thread_alert_notice() {
+        let (thread, first) = SimulationThread::spawn(model(), 2);
+        thread.send(|model| model.simulation.watch_alerts(Some(AlertConfig { rules: vec![AlertRule::PopulationBelow(100)], ..Default::default() })));
+        let snapshot = wait_for(&thread, |snapshot| snapshot.notice.is_some()).unwrap();
+
+        assert_eq!(None, first.notice);
+        assert!(snapshot.notice.unwrap().ends_with(" BELOW 100"));
+    }
+}
@@ -0,0 +1,261 @@
+use thiserror::Error;
+
+use crate::audit::PhaseLedger;
+use crate::board::{Coord, Size};
+use crate::population::PlantId;
+use crate::simulation::Simulation;
+
+/// The energy stored on the board before and after a step and the energy the plants collected in between.
+/// Energy only enters the board through the plants collecting it, so a step can never end with more energy
+/// than it started with plus what was collected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnergyBalance {
+    /// The energy of the plants and the dormant seeds before the step
+    pub before: u64,
+    /// The energy collected by the plants during the step
+    pub intake: u64,
+    /// The energy of the plants and the dormant seeds after the step
+    pub after: u64,
+}
+
+impl EnergyBalance {
+    /// Returns true if no energy was created during the step
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::invariants::EnergyBalance;
+    /// 
+    /// assert!(EnergyBalance { before: 100, intake: 20, after: 90 }.is_conserved());
+    /// assert!(!EnergyBalance { before: 100, intake: 20, after: 121 }.is_conserved());
+    /// ```
+    pub fn is_conserved(&self) -> bool {
+        self.after <= self.before.saturating_add(self.intake)
+    }
+}
+
+impl Simulation {
+    /// Checks the invariants the simulation relies on: every plant lives in exactly one cell inside the board,
+    /// the cells, the ids and the spatial index of the population agree, the population and the fields have the size
+    /// of the board, the latest step did not create energy and, if the energy is audited, the books of its phases balance
+    /// 
+    /// # Errors
+    /// 
+    /// The first invariant which does not hold
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.2, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// for _ in 0..20 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(Ok(()), simulation.check_invariants());
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let size = self.board().fields.size;
+
+        if self.population().size() != size {
+            return Err(InvariantViolation::Size { expected: size, found: self.population().size() });
+        }
+        for (field, found) in [("light", self.light().len()), ("water", self.water().values().len()), ("nutrients", self.nutrients().values().len())] {
+            if found != size.len() {
+                return Err(InvariantViolation::Length { field, expected: size.len(), found });
+            }
+        }
+
+        self.population().check_invariants()?;
+
+        if let Some(balance) = self.energy_balance() {
+            if !balance.is_conserved() {
+                return Err(InvariantViolation::Energy { balance });
+            }
+        }
+        if let Some(violation) = self.audit_violation() {
+            return Err(violation);
+        }
+
+        Ok(())
+    }
+
+    /// Panics if any invariant does not hold, does nothing in release builds such that long runs are not slowed down.
+    /// This is called at the end of every step in debug builds
+    /// 
+    /// # Panics
+    /// 
+    /// If an invariant does not hold in a debug build
+    pub fn debug_assert_invariants(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(violation) = self.check_invariants() {
+                panic!("Invariant broken at tick {}: {}", self.tick(), violation);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum InvariantViolation {
+    #[error("The population has size {:?} but the board has size {:?}", found, expected)]
+    Size {
+        expected: Size,
+        found: Size,
+    },
+    #[error("The {} has {:?} values but the board has {:?} cells", field, found, expected)]
+    Length {
+        field: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error("The population holds {:?} plants, {:?} occupied cells and {:?} ids", plants, cells, ids)]
+    Bookkeeping {
+        plants: usize,
+        cells: usize,
+        ids: usize,
+    },
+    #[error("Plant {:?} is in cell {:?} outside the board", id, index)]
+    OutOfBounds {
+        id: PlantId,
+        index: usize,
+    },
+    #[error("More than one plant is in cell {:?}", coord)]
+    SharedCell {
+        coord: Coord,
+    },
+    #[error("More than one plant has id {:?}", id)]
+    DuplicateId {
+        id: PlantId,
+    },
+    #[error("Plant {:?} has an id which has not been given out yet, the next id is {:?}", id, next)]
+    FutureId {
+        id: PlantId,
+        next: u64,
+    },
+    #[error("The spatial index holds {:?} cells but there are {:?} plants", found, expected)]
+    SpatialIndex {
+        expected: usize,
+        found: usize,
+    },
+    #[error("The latest step created energy: {:?}", balance)]
+    Energy {
+        balance: EnergyBalance,
+    },
+    #[error("The energy of the {} phase at tick {:?} is off by {:?}: {:?}", ledger.phase.name(), tick, ledger.discrepancy(), ledger)]
+    Audit {
+        tick: u64,
+        ledger: PhaseLedger,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use crate::aging::AgingConfig;
+    use crate::board::{Board, Fields, Multipliers};
+    use crate::genome::{Genome, MutationConfig};
+    use crate::nutrient::NutrientConfig;
+    use crate::pathogen::PathogenConfig;
+    use crate::population::{Plant, Population};
+    use crate::roots::RootConfig;
+    use crate::seedbank::SeedBankConfig;
+    use crate::shadow::{CanopyConfig, Sun};
+    use crate::simulation::{Competition, ReproductionMode, SimulationConfig};
+    use crate::water::WaterConfig;
+
+    /// Builds a simulation with a random board, a random population and random settings
+    fn random_simulation(rng: &mut ChaCha8Rng) -> Simulation {
+        let size = Size::new(rng.gen_range(1..12), rng.gen_range(1..12));
+        let light: Vec<f32> = (0..size.len()).map(|_| rng.gen()).collect();
+        let water: Vec<f32> = (0..size.len()).map(|_| rng.gen::<f32>() * 2.0).collect();
+        let fields = Fields::new(size, &light).unwrap().with_water(&water).unwrap();
+        let board = Board::new(Multipliers::new(rng.gen_range(1..200)).unwrap(), fields);
+
+        let mut population = Population::new(size);
+        for index in 0..size.len() {
+            if rng.gen_bool(0.3) {
+                let genes: Vec<f32> = (0..rng.gen_range(2..10)).map(|_| rng.gen()).collect();
+                population.insert(size.coord_of(index).unwrap(), Plant::new(rng.gen_range(0..200), Genome::new(&genes).unwrap()));
+            }
+        }
+
+        let mut config = SimulationConfig {
+            seed: rng.gen(),
+            upkeep: rng.gen_range(0..30),
+            seed_cost: rng.gen_range(0..50),
+            max_threshold: rng.gen_range(0..300),
+            mutation: MutationConfig::new(rng.gen(), rng.gen()),
+            competition: [Competition::FirstWins, Competition::HighestEnergyWins, Competition::Lottery][rng.gen_range(0..3)],
+            ..Default::default()
+        };
+        if rng.gen_bool(0.5) {
+            config.reproduction.mode = ReproductionMode::Sexual;
+            config.reproduction.pollen_range = rng.gen_range(1..4);
+            config.reproduction.compatibility = rng.gen();
+            config.reproduction.self_fertilize = rng.gen_bool(0.5);
+        }
+        if rng.gen_bool(0.5) {
+            config.water = Some(WaterConfig::default());
+        }
+        if rng.gen_bool(0.5) {
+            config.nutrients = Some(NutrientConfig::default());
+        }
+        if rng.gen_bool(0.5) {
+            config.roots = Some(RootConfig::default());
+        }
+        if rng.gen_bool(0.5) {
+            config.sun = Some(Sun::new(rng.gen::<f32>() * 6.0, rng.gen::<f32>() * 1.5, rng.gen()));
+            config.canopy = rng.gen_bool(0.5).then(CanopyConfig::default);
+        }
+        if rng.gen_bool(0.3) {
+            config.pathogen = Some(PathogenConfig::default());
+        }
+        if rng.gen_bool(0.3) {
+            config.aging = Some(AgingConfig::default());
+        }
+        if rng.gen_bool(0.3) {
+            config.seed_bank = Some(SeedBankConfig::default());
+        }
+
+        Simulation::new(board, population, config).unwrap()
+    }
+
+    #[test]
+    fn invariants_random_runs() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        for _ in 0..40 {
+            let mut simulation = random_simulation(&mut rng);
+            assert_eq!(Ok(()), simulation.check_invariants());
+
+            for _ in 0..60 {
+                simulation.step();
+
+                assert_eq!(Ok(()), simulation.check_invariants(), "{:?}", simulation.config());
+            }
+        }
+    }
+
+    #[test]
+    fn invariants_energy_balance() {
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        let board = Board::new(Multipliers::new(100).unwrap(), Fields::new(size, &[0.5, 0.5]).unwrap());
+        let config = SimulationConfig { upkeep: 10, max_threshold: 1000, ..Default::default() };
+        let mut simulation = Simulation::new(board, population, config).unwrap();
+
+        assert_eq!(None, simulation.energy_balance());
+
+        simulation.step();
+
+        assert_eq!(Some(EnergyBalance { before: 50, intake: 50, after: 90 }), simulation.energy_balance());
+    }
+}
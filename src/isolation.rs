@@ -0,0 +1,277 @@
+use crate::genome::Genome;
+use crate::phylogeny::{Lineage, Phylogeny};
+use crate::population::Population;
+
+/// One of the two species of a species pair
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Species {
+    /// The species of the first reference genome
+    A,
+    /// The species of the second reference genome
+    B,
+}
+
+/// Two species defined by reference genomes, a plant belongs to the species whose reference genome it is closest to
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeciesPair {
+    /// The reference genome of the first species
+    pub a: Genome,
+    /// The reference genome of the second species
+    pub b: Genome,
+    /// The largest genetic distance to a reference genome for a plant to belong to that species
+    pub max_distance: f32,
+}
+
+impl SpeciesPair {
+    /// Creates a new species pair
+    /// 
+    /// # Parameters
+    /// 
+    /// a: The reference genome of the first species
+    /// b: The reference genome of the second species
+    /// max_distance: The largest genetic distance to a reference genome for a plant to belong to that species
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, isolation::SpeciesPair};
+    /// 
+    /// let a = Genome::new(&[0.0, 0.0]).unwrap();
+    /// let b = Genome::new(&[1.0, 1.0]).unwrap();
+    /// let pair = SpeciesPair::new(a.clone(), b.clone(), 0.2);
+    /// 
+    /// assert_eq!(a, pair.a);
+    /// assert_eq!(b, pair.b);
+    /// ```
+    pub fn new(a: Genome, b: Genome, max_distance: f32) -> Self {
+        Self { a, b, max_distance }
+    }
+
+    /// Finds the species of a genome, returns None if it is too far from both reference genomes
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to classify
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, isolation::{Species, SpeciesPair}};
+    /// 
+    /// let pair = SpeciesPair::new(Genome::new(&[0.0, 0.0]).unwrap(), Genome::new(&[1.0, 1.0]).unwrap(), 0.2);
+    /// 
+    /// assert_eq!(Some(Species::A), pair.classify(&Genome::new(&[0.1, 0.0]).unwrap()));
+    /// assert_eq!(Some(Species::B), pair.classify(&Genome::new(&[0.9, 0.9]).unwrap()));
+    /// assert_eq!(None, pair.classify(&Genome::new(&[0.5, 0.5]).unwrap()));
+    /// ```
+    pub fn classify(&self, genome: &Genome) -> Option<Species> {
+        let distance_a = genome.distance(&self.a);
+        let distance_b = genome.distance(&self.b);
+
+        if distance_a <= distance_b && distance_a <= self.max_distance {
+            Some(Species::A)
+        } else if distance_b < distance_a && distance_b <= self.max_distance {
+            Some(Species::B)
+        } else {
+            None
+        }
+    }
+}
+
+/// Metrics of the reproductive isolation between two species
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IsolationReport {
+    /// The fraction of matings between plants of the two species which were between different species,
+    /// None if there were no matings between plants of the species
+    pub hybridization_rate: Option<f32>,
+    /// The mean number of children of hybrids relative to the mean number of children of plants with parents of
+    /// the same species, None if there are no hybrids or no plants with parents of the same species
+    pub hybrid_fitness: Option<f32>,
+    /// The width in cells of the transition from one species to the other along the x axis in the living population,
+    /// calculated as one over the steepest change in the frequency of the first species between columns.
+    /// None if there is no change in the frequency
+    pub cline_width: Option<f32>,
+}
+
+impl IsolationReport {
+    /// Calculates the reproductive isolation between two species from the lineage tree and the living population,
+    /// plants in the lineage tree are classified by the genome they were born with
+    /// 
+    /// # Parameters
+    /// 
+    /// phylogeny: The lineage tree with the recorded matings
+    /// population: The living population
+    /// pair: The two species to compare
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, isolation::{IsolationReport, SpeciesPair}};
+    /// use evolution_plants::{phylogeny::Phylogeny, population::{Plant, PlantId, Population}};
+    /// 
+    /// let a = Genome::new(&[0.0, 0.0]).unwrap();
+    /// let b = Genome::new(&[1.0, 1.0]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, a.clone());
+    /// phylogeny.record_birth(PlantId(1), None, None, 0, b.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(0)), Some(PlantId(1)), 1, a.clone());
+    /// phylogeny.record_birth(PlantId(3), Some(PlantId(0)), Some(PlantId(0)), 1, a.clone());
+    /// let mut population = Population::new(Size::new(2, 1));
+    /// population.insert(Coord::new(0, 0), Plant::new(0, a.clone()));
+    /// population.insert(Coord::new(1, 0), Plant::new(0, b.clone()));
+    /// let report = IsolationReport::new(&phylogeny, &population, &SpeciesPair::new(a, b, 0.1));
+    /// 
+    /// assert_eq!(Some(0.5), report.hybridization_rate);
+    /// assert_eq!(Some(1.0), report.cline_width);
+    /// ```
+    pub fn new(phylogeny: &Phylogeny, population: &Population, pair: &SpeciesPair) -> Self {
+        let species = |id| phylogeny.get(id).and_then(|node: &Lineage| pair.classify(&node.genome));
+
+        // Count the children of hybrids and of plants with parents of the same species
+        let mut hybrids = (0, 0);
+        let mut pure = (0, 0);
+
+        for node in phylogeny.iter() {
+            let parents = match (node.parent.and_then(species), node.mate.and_then(species)) {
+                (Some(parent), Some(mate)) => (parent, mate),
+                _ => continue,
+            };
+
+            let counts = if parents.0 == parents.1 { &mut pure } else { &mut hybrids };
+            counts.0 += 1;
+            counts.1 += node.children.len();
+        }
+
+        let hybridization_rate = if hybrids.0 + pure.0 > 0 {
+            Some(hybrids.0 as f32 / (hybrids.0 + pure.0) as f32)
+        } else {
+            None
+        };
+
+        let hybrid_fitness = if hybrids.0 > 0 && pure.0 > 0 && pure.1 > 0 {
+            Some((hybrids.1 as f32 / hybrids.0 as f32) / (pure.1 as f32 / pure.0 as f32))
+        } else {
+            None
+        };
+
+        let cline_width = cline_width(population, pair);
+
+        Self { hybridization_rate, hybrid_fitness, cline_width }
+    }
+}
+
+/// Calculates the width of the cline of the first species along the x axis
+fn cline_width(population: &Population, pair: &SpeciesPair) -> Option<f32> {
+    let (w, _) = population.size().size();
+    let mut counts = vec![(0, 0); w];
+
+    for (coord, plant) in population.iter() {
+        match pair.classify(&plant.genome) {
+            Some(Species::A) => counts[coord.x].0 += 1,
+            Some(Species::B) => counts[coord.x].1 += 1,
+            None => (),
+        }
+    }
+
+    // Find the frequency of the first species in every column with plants of either species
+    let frequencies: Vec<(usize, f32)> = counts.iter()
+        .enumerate()
+        .filter(|(_, (a, b))| a + b > 0)
+        .map(|(x, (a, b))| (x, *a as f32 / (a + b) as f32))
+        .collect();
+
+    let slope = frequencies.windows(2)
+        .map(|pair| ((pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0) as f32).abs())
+        .fold(0.0, f32::max);
+
+    if slope > 0.0 {
+        Some(1.0 / slope)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Size};
+    use crate::population::{Plant, PlantId};
+
+    fn pair() -> SpeciesPair {
+        SpeciesPair::new(Genome::new(&[0.0, 0.0]).unwrap(), Genome::new(&[1.0, 1.0]).unwrap(), 0.2)
+    }
+
+    #[test]
+    fn species_pair_new() {
+        let pair = SpeciesPair::new(Genome::new(&[0.0, 0.0]).unwrap(), Genome::new(&[1.0, 1.0]).unwrap(), 0.2);
+
+        assert_eq!(Genome::new(&[0.0, 0.0]).unwrap(), pair.a);
+        assert_eq!(Genome::new(&[1.0, 1.0]).unwrap(), pair.b);
+        assert_eq!(0.2, pair.max_distance);
+    }
+
+    #[test]
+    fn species_pair_classify() {
+        let pair = pair();
+
+        assert_eq!(Some(Species::A), pair.classify(&Genome::new(&[0.2, 0.2]).unwrap()));
+        assert_eq!(Some(Species::B), pair.classify(&Genome::new(&[0.8, 0.8]).unwrap()));
+        assert_eq!(None, pair.classify(&Genome::new(&[0.3, 0.3]).unwrap()));
+    }
+
+    #[test]
+    fn isolation_report_new() {
+        let a = pair().a;
+        let b = pair().b;
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(0), None, None, 0, a.clone());
+        phylogeny.record_birth(PlantId(1), None, None, 0, b.clone());
+        // Two hybrids with one child in total
+        phylogeny.record_birth(PlantId(2), Some(PlantId(0)), Some(PlantId(1)), 1, a.clone());
+        phylogeny.record_birth(PlantId(3), Some(PlantId(1)), Some(PlantId(0)), 1, b.clone());
+        // Two pure plants with four children in total
+        phylogeny.record_birth(PlantId(4), Some(PlantId(0)), Some(PlantId(0)), 1, a.clone());
+        phylogeny.record_birth(PlantId(5), Some(PlantId(1)), Some(PlantId(1)), 1, b.clone());
+        phylogeny.record_birth(PlantId(6), Some(PlantId(2)), None, 2, a.clone());
+        for id in 7..11 {
+            phylogeny.record_birth(PlantId(id), Some(PlantId(4)), None, 2, a.clone());
+        }
+        let report = IsolationReport::new(&phylogeny, &Population::new(Size::new(2, 2)), &pair());
+
+        assert_eq!(Some(0.5), report.hybridization_rate);
+        assert_eq!(Some(0.25), report.hybrid_fitness);
+        assert_eq!(None, report.cline_width);
+    }
+
+    #[test]
+    fn isolation_report_new_empty() {
+        let report = IsolationReport::new(&Phylogeny::new(), &Population::new(Size::new(2, 2)), &pair());
+
+        assert_eq!(IsolationReport { hybridization_rate: None, hybrid_fitness: None, cline_width: None }, report);
+    }
+
+    #[test]
+    fn cline_width_gradient() {
+        let a = pair().a;
+        let b = pair().b;
+        let mut population = Population::new(Size::new(5, 2));
+        population.insert(Coord::new(0, 0), Plant::new(0, a.clone()));
+        population.insert(Coord::new(0, 1), Plant::new(0, a.clone()));
+        population.insert(Coord::new(2, 0), Plant::new(0, a.clone()));
+        population.insert(Coord::new(2, 1), Plant::new(0, b.clone()));
+        population.insert(Coord::new(4, 0), Plant::new(0, b.clone()));
+        population.insert(Coord::new(4, 1), Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap()));
+
+        // The frequency changes by 0.5 over every two columns
+        assert_eq!(Some(4.0), cline_width(&population, &pair()));
+    }
+
+    #[test]
+    fn cline_width_uniform() {
+        let mut population = Population::new(Size::new(3, 1));
+        population.insert(Coord::new(0, 0), Plant::new(0, pair().a));
+        population.insert(Coord::new(2, 0), Plant::new(0, pair().a));
+
+        assert_eq!(None, cline_width(&population, &pair()));
+    }
+}
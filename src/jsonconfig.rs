@@ -0,0 +1,165 @@
+use serde::Deserialize;
+
+use crate::board::{BoardBuilder, Coord};
+use crate::genome::{Genome, MutationConfig};
+use crate::population::{Plant, Population};
+use crate::simulation::{Simulation, SimulationConfig, SimulationCreateError};
+
+/// The settings of a simulation as given in JSON by the bindings for other languages, every field is optional
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JsonConfig {
+    /// The width of the board in cells
+    width: usize,
+    /// The height of the board in cells
+    height: usize,
+    /// The light in every cell, the light goes from top to bottom between these two values
+    light: (f32, f32),
+    /// The energy a plant gains from full light
+    light_multiplier: u32,
+    /// The seed for the random number generator
+    seed: u64,
+    /// The energy every plant must pay each step to survive
+    upkeep: u32,
+    /// The energy it costs to produce a seed
+    seed_cost: u32,
+    /// The energy required to reproduce on top of the seed cost
+    max_threshold: u32,
+    /// The probability of mutating each gene
+    mutation_rate: f32,
+    /// The largest amount a gene can change in a single mutation
+    mutation_strength: f32,
+    /// The plants to start with
+    plants: Vec<JsonPlant>,
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        let defaults = SimulationConfig::default();
+
+        Self {
+            width: 128,
+            height: 96,
+            light: (1.0, 0.2),
+            light_multiplier: 100,
+            seed: defaults.seed,
+            upkeep: defaults.upkeep,
+            seed_cost: defaults.seed_cost,
+            max_threshold: defaults.max_threshold,
+            mutation_rate: defaults.mutation.rate,
+            mutation_strength: defaults.mutation.strength,
+            plants: vec![JsonPlant { x: 64, y: 48, energy: 100, genes: vec![0.5, 0.5] }],
+        }
+    }
+}
+
+/// A plant to start a simulation with as given in JSON
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+struct JsonPlant {
+    /// The column of the plant
+    x: usize,
+    /// The row of the plant
+    y: usize,
+    /// The energy of the plant
+    energy: u32,
+    /// The genes of the plant
+    genes: Vec<f32>,
+}
+
+/// Creates a new simulation from its settings in JSON. The settings are an object with the fields width, height,
+/// light as a pair of the light at the top and the bottom, lightMultiplier, seed, upkeep, seedCost, maxThreshold,
+/// mutationRate, mutationStrength and plants as a list of objects with the fields x, y, energy and genes
+/// 
+/// # Parameters
+/// 
+/// config_json: The settings, any missing setting gets its default value
+/// 
+/// # Errors
+/// 
+/// Error::Json: This will occur if the JSON is invalid
+/// 
+/// Error::Field: This will occur if the board cannot be built from the settings
+/// 
+/// Error::Genome: This will occur if the genes of a plant are invalid
+/// 
+/// Error::Mutation: This will occur if the mutation rate is not between 0 and 1 or the mutation strength is negative or not finite
+/// 
+/// Error::Simulation: This will occur if a plant is outside the board or on blocked ground, or if the upkeep, the seed cost
+/// or the maximum threshold is larger than SimulationConfig::MAX_ENERGY
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::jsonconfig;
+/// 
+/// let simulation = jsonconfig::simulation_from_json(r#"{"width": 8, "height": 4, "plants": [{"x": 2, "y": 1, "energy": 100, "genes": [0.5, 0.5]}]}"#).unwrap();
+/// 
+/// assert_eq!((8, 4), simulation.board().fields.size.size());
+/// assert_eq!(1, simulation.population().count());
+/// assert!(jsonconfig::simulation_from_json("{").is_err());
+/// ```
+pub fn simulation_from_json(config_json: &str) -> crate::Result<Simulation> {
+    let config: JsonConfig = serde_json::from_str(config_json)?;
+
+    let (top, bottom) = config.light;
+    let height = config.height;
+    let board = BoardBuilder::new()
+        .size(config.width, config.height)
+        .light_generator(move |coord| {
+            let t = if height <= 1 { 0.0 } else { coord.y as f32 / (height - 1) as f32 };
+            top + (bottom - top) * t
+        })
+        .multiplier_light(config.light_multiplier)
+        .build()?;
+
+    // Every setting is checked here or by Simulation::new, settings which would make a step fail are never accepted
+    let mutation = MutationConfig::new(config.mutation_rate, config.mutation_strength);
+    mutation.validate()?;
+
+    let mut population = Population::new(board.fields.size);
+    for plant in config.plants {
+        let coord = Coord::new(plant.x, plant.y);
+        if board.fields.size.index_of(coord).is_none() {
+            return Err(SimulationCreateError::Outside { coord }.into());
+        }
+        population.insert(coord, Plant::new(plant.energy, Genome::new(&plant.genes)?));
+    }
+
+    let simulation_config = SimulationConfig {
+        seed: config.seed,
+        upkeep: config.upkeep,
+        seed_cost: config.seed_cost,
+        max_threshold: config.max_threshold,
+        mutation,
+        ..Default::default()
+    };
+
+    Ok(Simulation::new(board, population, simulation_config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn json_config_defaults() {
+        let simulation = simulation_from_json("{}").unwrap();
+
+        assert_eq!((128, 96), simulation.board().fields.size.size());
+        assert_eq!(SimulationConfig::default().upkeep, simulation.config().upkeep);
+        assert!(simulation.population().get(Coord::new(64, 48)).is_some());
+    }
+
+    #[test]
+    fn json_config_errors() {
+        assert!(matches!(simulation_from_json(r#"{"width": "wide"}"#), Err(Error::Json(_))));
+        assert!(matches!(simulation_from_json(r#"{"plants": [{"x": 0, "y": 0, "energy": 1, "genes": [2.0, 0.5]}]}"#), Err(Error::Genome(_))));
+        assert!(matches!(simulation_from_json(r#"{"mutationStrength": -0.1}"#), Err(Error::Mutation(_))));
+        assert!(matches!(simulation_from_json(r#"{"mutationRate": 1.5}"#), Err(Error::Mutation(_))));
+        assert!(matches!(simulation_from_json(r#"{"seedCost": 4294967295}"#), Err(Error::Simulation(SimulationCreateError::Config(_)))));
+        assert!(matches!(simulation_from_json(r#"{"light": [1.0, -0.5]}"#), Err(Error::Field(_))));
+        assert!(matches!(simulation_from_json(r#"{"lightMultiplier": 0}"#), Err(Error::Field(_))));
+        assert!(matches!(simulation_from_json(r#"{"plants": [{"x": 200, "y": 0, "energy": 1, "genes": [0.5, 0.5]}]}"#), Err(Error::Simulation(SimulationCreateError::Outside { .. }))));
+    }
+}
@@ -1,2 +1,98 @@
+pub mod adaptive;
+pub mod aggregate;
+pub mod aging;
+pub mod alert;
+pub mod allelopathy;
+pub mod analysis;
+pub mod archive;
+pub mod audit;
+pub mod autosave;
 pub mod board;
-pub mod interface;
\ No newline at end of file
+pub mod checkpoint;
+pub mod chunk;
+pub mod climate;
+pub mod clutch;
+pub mod demography;
+pub mod dirty;
+pub mod disturbance;
+pub mod distance;
+pub mod ecotone;
+pub mod edit;
+pub mod error;
+pub mod events;
+pub mod experiment;
+#[cfg(feature = "cdylib")]
+pub mod ffi;
+pub mod field;
+#[cfg(feature = "image")]
+pub mod fieldimage;
+pub mod fitness;
+pub mod genes;
+pub mod genome;
+pub mod germination;
+pub mod governor;
+pub mod history;
+#[cfg(feature = "gui")]
+pub mod interface;
+pub mod invariants;
+pub mod isolation;
+#[cfg(any(feature = "wasm", feature = "cdylib"))]
+pub mod jsonconfig;
+#[cfg(feature = "tracing")]
+pub mod logging;
+pub mod memory;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod migrate;
+#[cfg(feature = "netcdf")]
+pub mod netcdf;
+pub mod mycorrhiza;
+pub mod neural;
+pub mod nutrient;
+pub mod occupancy;
+pub mod organism;
+pub mod pathogen;
+pub mod phenotype;
+pub mod phylogeny;
+pub mod plot;
+pub mod pollination;
+pub mod population;
+pub mod profile;
+#[cfg(feature = "pyo3")]
+pub mod python;
+#[cfg(feature = "image")]
+pub mod recorder;
+#[cfg(feature = "live-reload")]
+pub mod reload;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod render;
+pub mod report;
+pub mod roots;
+pub mod runarchive;
+pub mod scenario;
+pub mod schedule;
+pub mod seedbank;
+pub mod shadow;
+#[cfg(feature = "signals")]
+pub mod shutdown;
+pub mod simulation;
+pub mod snapshot;
+pub mod spatial;
+pub mod species;
+pub mod spectrum;
+pub mod stats;
+pub mod stop;
+pub mod streams;
+pub mod sweep;
+pub mod trace;
+pub mod tuning;
+pub mod view;
+pub mod visual;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod water;
+pub mod wind;
+pub mod world;
+
+pub use error::{Error, Result};
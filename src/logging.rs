@@ -0,0 +1,74 @@
+use thiserror::Error;
+use tracing::{debug, trace, warn};
+
+use crate::events::SimEvent;
+
+/// Starts printing the logs of the simulations to standard error, the spans of every step and the events
+/// of the simulations are logged through the log crate so that the filter selects what is printed.
+/// Births, deaths and mutations are logged at the trace level, disturbances and changes to the settings at the debug level,
+/// extinctions at the info level and alerts at the warn level
+/// 
+/// # Parameters
+/// 
+/// filter: The filter in the format of env_logger such as "info" or "evolution_plants::simulation=trace"
+/// 
+/// # Errors
+/// 
+/// LoggingError::AlreadyInitialized: This will occur if a logger has already been set
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::logging::{self, LoggingError};
+/// 
+/// assert_eq!(Ok(()), logging::init_logging("evolution_plants=debug"));
+/// assert_eq!(Err(LoggingError::AlreadyInitialized), logging::init_logging("info"));
+/// ```
+pub fn init_logging(filter: &str) -> Result<(), LoggingError> {
+    env_logger::Builder::new()
+        .parse_filters(filter)
+        .try_init()
+        .map_err(|_| LoggingError::AlreadyInitialized)
+}
+
+/// Logs an event of a simulation
+/// 
+/// # Parameters
+/// 
+/// event: The event to log
+pub(crate) fn log_event(event: &SimEvent) {
+    match event {
+        SimEvent::PlantBorn { tick, id, coord, parent, mate } => trace!(tick, ?id, ?coord, ?parent, ?mate, "plant born"),
+        SimEvent::PlantDied { tick, id, coord } => trace!(tick, ?id, ?coord, "plant died"),
+        SimEvent::MutationApplied { tick, parent, genes } => trace!(tick, ?parent, genes, "mutation applied"),
+        SimEvent::MutationRateChanged { adjustment } => debug!(tick = adjustment.tick, ?adjustment, "mutation rate changed"),
+        SimEvent::Disturbed { tick, disturbance, killed } => debug!(tick, ?disturbance, killed, "disturbance"),
+        SimEvent::Culled { tick, killed, survivors } => debug!(tick, killed, survivors, "population culled"),
+        SimEvent::ConfigUpdated { tick, update } => debug!(tick, ?update, "settings updated"),
+        SimEvent::ConfigRejected { tick, update } => warn!(tick, ?update, "settings update rejected"),
+        SimEvent::SpeciesOriginated { tick, species, origin } => debug!(tick, ?species, ?origin, "species originated"),
+        SimEvent::SpeciesExtinct { tick, species, peak } => debug!(tick, ?species, peak, "species went extinct"),
+        SimEvent::Alert { alert } => warn!(tick = alert.tick, rule = ?alert.rule, value = alert.value, "{}", alert.message()),
+        SimEvent::TickCompleted { tick, population } => trace!(tick, population, "tick completed"),
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum LoggingError {
+    #[error("A logger has already been set")]
+    AlreadyInitialized,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Coord;
+    use crate::population::PlantId;
+
+    #[test]
+    fn log_event_all() {
+        // Logging works without a logger
+        log_event(&SimEvent::PlantDied { tick: 1, id: PlantId(0), coord: Coord::new(0, 0) });
+        log_event(&SimEvent::TickCompleted { tick: 1, population: 0 });
+    }
+}
@@ -1,3 +1,25 @@
-fn main() {
-    println!("Hello, world!");
+use evolution_plants::{board, genome::Genome, interface, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+
+fn main() -> evolution_plants::Result<()> {
+    env_logger::init();
+
+    // Create a board with the light increasing from left to right
+    let (w, h) = (128, 96);
+    let board = board::BoardBuilder::new()
+        .size(w, h)
+        .light_generator(move |coord| coord.x as f32 / (w - 1) as f32)
+        .multiplier_light(30)
+        .build()?;
+    let size = board.fields.size;
+
+    // Start with a single plant in the middle
+    let mut population = Population::new(size);
+    population.insert(board::Coord::new(w / 2, h / 2), Plant::new(100, Genome::new(&[0.1, 0.5])?));
+
+    let simulation = Simulation::new(board, population, SimulationConfig::default())?;
+
+    let window = interface::WindowBuilder::new().build()?;
+    window.run(simulation)?;
+
+    Ok(())
 }
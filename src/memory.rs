@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::mem;
+
+/// An estimate of the memory a part of the simulation uses outside of its own struct
+pub(crate) trait HeapSize {
+    /// Estimates the number of bytes allocated on the heap, the genes of genomes are left out
+    /// since they are shared through the genome store and counted there
+    fn heap_bytes(&self) -> usize;
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_bytes(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_bytes)
+    }
+}
+
+/// Estimates the bytes allocated by a vector of values without heap memory of their own
+pub(crate) fn vec_bytes<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * mem::size_of::<T>()
+}
+
+/// Estimates the bytes allocated by a double ended queue of values without heap memory of their own
+pub(crate) fn deque_bytes<T>(deque: &VecDeque<T>) -> usize {
+    deque.capacity() * mem::size_of::<T>()
+}
+
+/// Estimates the bytes allocated by a hash map of keys and values without heap memory of their own,
+/// every slot of the table also has a control byte
+pub(crate) fn hash_map_bytes<K, V>(map: &HashMap<K, V>) -> usize {
+    map.capacity() * (mem::size_of::<(K, V)>() + 1)
+}
+
+/// Estimates the bytes allocated by a B-tree map of keys and values without heap memory of their own,
+/// the nodes of the tree are assumed to be two thirds full
+pub(crate) fn btree_map_bytes<K, V>(map: &BTreeMap<K, V>) -> usize {
+    map.len() * mem::size_of::<(K, V)>() * 3 / 2
+}
+
+/// The estimated memory used by the parts of a simulation in bytes, only the large parts which grow with the board,
+/// the population or the length of the run are counted. The numbers are estimates from the capacity of the containers
+/// and do not include the overhead of the allocator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct MemoryReport {
+    /// The fields of the board, the regions, the dormant seeds, the light after shadows, the water, the nutrients and the toxin
+    pub fields: usize,
+    /// The plants and the grid, ids and spatial index used to find them
+    pub population: usize,
+    /// The genes of every distinct genome, shared by all plants, seeds and records with the same genes
+    pub genomes: usize,
+    /// The saved states used to go back in time
+    pub history: usize,
+    /// The lineage tree of every plant
+    pub phylogeny: usize,
+    /// The species, the fitness counts, the hall of fame, the log of settings changes, the emigrating seeds and the changed cells
+    pub stats: usize,
+}
+
+impl MemoryReport {
+    /// Returns the total number of bytes of all parts
+    pub fn total(&self) -> usize {
+        self.fields + self.population + self.genomes + self.history + self.phylogeny + self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    #[test]
+    fn memory_report_total() {
+        let report = MemoryReport { fields: 1, population: 2, genomes: 3, history: 4, phylogeny: 5, stats: 6 };
+
+        assert_eq!(21, report.total());
+    }
+
+    #[test]
+    fn memory_report_grows() {
+        let board = BoardBuilder::new().size(16, 16).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(8, 8), Plant::new(100, Genome::new(&[0.2, 0.5, 0.1]).unwrap()));
+        let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+        let before = simulation.memory_report();
+
+        for _ in 0..50 {
+            simulation.step();
+        }
+        let after = simulation.memory_report();
+
+        assert!(before.genomes > 0);
+        assert!(after.population > before.population);
+        assert!(after.phylogeny > before.phylogeny);
+        assert!(after.genomes > before.genomes);
+        assert_eq!(0, after.history);
+    }
+
+    #[test]
+    fn heap_bytes_containers() {
+        let vec: Vec<u32> = Vec::with_capacity(10);
+        let map: HashMap<u64, u64> = HashMap::new();
+
+        assert_eq!(40, vec_bytes(&vec));
+        assert_eq!(0, hash_map_bytes(&map));
+        assert_eq!(0, Option::<Population>::None.heap_bytes());
+    }
+}
@@ -0,0 +1,443 @@
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write as _};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::memory::MemoryReport;
+use crate::stats::{Subscription, TickStats};
+
+/// The prefix of the name of every metric
+const PREFIX: &str = "evolution_plants";
+
+/// The content type of the Prometheus text format
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The latest values of the metrics of a simulation, kept up to date by recording the statistics of its steps
+/// from a subscription and written in the Prometheus text format. Stalls are found from the time of the last step and extinctions
+/// from the population, such that alerts can be set up for both
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+    /// The tick of the last record and when it was recorded, None before the first record
+    last: Option<(u64, Instant)>,
+    /// The number of steps run
+    tick: u64,
+    /// The steps per second between the last two records with different ticks
+    tick_rate: f64,
+    /// The seconds since the unix epoch when the tick last changed
+    stepped_at: f64,
+    /// The number of living plants
+    population: usize,
+    /// The mean energy of the living plants
+    mean_energy: f64,
+    /// The number of living species, 0 if species are not tracked
+    species: usize,
+    /// The name and bytes of every part of the memory used
+    memory: Vec<(&'static str, usize)>,
+    /// The number of checkpoints timed
+    checkpoints: u64,
+    /// The total seconds spent writing checkpoints
+    checkpoint_seconds: f64,
+    /// The seconds spent writing the last checkpoint
+    checkpoint_last: f64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            last: None,
+            tick: 0,
+            tick_rate: 0.0,
+            stepped_at: 0.0,
+            population: 0,
+            mean_energy: 0.0,
+            species: 0,
+            memory: Vec::new(),
+            checkpoints: 0,
+            checkpoint_seconds: 0.0,
+            checkpoint_last: 0.0,
+        }
+    }
+}
+
+impl Metrics {
+    /// Creates new metrics without any records
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the statistics of a step, such as received from stats::subscribe. The tick rate is found from the records
+    /// which have different ticks, so recording the same tick several times does not change it
+    /// 
+    /// # Parameters
+    /// 
+    /// stats: The statistics of the step
+    pub fn record(&mut self, stats: &TickStats) {
+        self.record_at(stats, Instant::now());
+    }
+
+    /// Records the memory used by a simulation, see Simulation::memory_report
+    /// 
+    /// # Parameters
+    /// 
+    /// memory: The memory used
+    pub fn record_memory(&mut self, memory: &MemoryReport) {
+        self.memory = vec![
+            ("fields", memory.fields),
+            ("population", memory.population),
+            ("genomes", memory.genomes),
+            ("history", memory.history),
+            ("phylogeny", memory.phylogeny),
+            ("stats", memory.stats),
+        ];
+    }
+
+    /// Records the time it took to write a checkpoint
+    /// 
+    /// # Parameters
+    /// 
+    /// duration: The time it took
+    pub fn record_checkpoint(&mut self, duration: Duration) {
+        self.checkpoints += 1;
+        self.checkpoint_seconds += duration.as_secs_f64();
+        self.checkpoint_last = duration.as_secs_f64();
+    }
+
+    /// Runs something which writes a checkpoint and records the time it took, returns what it returned
+    /// 
+    /// # Parameters
+    /// 
+    /// checkpoint: Writes the checkpoint
+    pub fn time_checkpoint<T, F: FnOnce() -> T>(&mut self, checkpoint: F) -> T {
+        let start = Instant::now();
+        let result = checkpoint();
+        self.record_checkpoint(start.elapsed());
+
+        result
+    }
+
+    /// Returns the tick of the last record, 0 before the first record
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the steps per second between the last two records with different ticks, 0 before that
+    pub fn tick_rate(&self) -> f64 {
+        self.tick_rate
+    }
+
+    /// Writes the metrics in the Prometheus text format
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, metrics::Metrics, population::Population, simulation::{Simulation, SimulationConfig}, stats};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut subscription = stats::subscribe(&mut simulation, 16);
+    /// let mut metrics = Metrics::new();
+    /// simulation.step();
+    /// for stats in std::iter::from_fn(|| subscription.try_next()) {
+    ///     metrics.record(&stats);
+    /// }
+    /// let text = metrics.exposition();
+    /// 
+    /// assert!(text.contains("# TYPE evolution_plants_ticks_total counter\nevolution_plants_ticks_total 1\n"));
+    /// assert!(text.contains("evolution_plants_extinct 1\n"));
+    /// ```
+    pub fn exposition(&self) -> String {
+        let mut text = String::new();
+        metric(&mut text, "ticks_total", "counter", "The number of steps run", self.tick);
+        metric(&mut text, "tick_rate", "gauge", "The steps per second between the last two records", self.tick_rate);
+        metric(&mut text, "last_step_timestamp_seconds", "gauge", "The unix time of the last step, for finding stalled runs", self.stepped_at);
+        metric(&mut text, "population", "gauge", "The number of living plants", self.population);
+        metric(&mut text, "extinct", "gauge", "1 if no plants are alive", u8::from(self.population == 0));
+        metric(&mut text, "mean_energy", "gauge", "The mean energy of the living plants", self.mean_energy);
+        metric(&mut text, "species", "gauge", "The number of living species, 0 if species are not tracked", self.species);
+
+        let name = format!("{}_memory_bytes", PREFIX);
+        let _ = writeln!(text, "# HELP {} The bytes of memory used on the heap by every part of the simulation", name);
+        let _ = writeln!(text, "# TYPE {} gauge", name);
+        for (part, bytes) in &self.memory {
+            let _ = writeln!(text, "{}{{part=\"{}\"}} {}", name, part, bytes);
+        }
+
+        let name = format!("{}_checkpoint_seconds", PREFIX);
+        let _ = writeln!(text, "# HELP {} The time spent writing checkpoints", name);
+        let _ = writeln!(text, "# TYPE {} summary", name);
+        let _ = writeln!(text, "{}_sum {}", name, self.checkpoint_seconds);
+        let _ = writeln!(text, "{}_count {}", name, self.checkpoints);
+        metric(&mut text, "checkpoint_last_seconds", "gauge", "The time spent writing the last checkpoint", self.checkpoint_last);
+
+        text
+    }
+
+    /// Records the statistics of a step at a moment
+    fn record_at(&mut self, stats: &TickStats, now: Instant) {
+        let tick = stats.tick;
+        if self.last.is_none_or(|(last_tick, _)| last_tick != tick) {
+            if let Some((last_tick, last_time)) = self.last {
+                let seconds = now.saturating_duration_since(last_time).as_secs_f64();
+                self.tick_rate = if seconds > 0.0 { tick.abs_diff(last_tick) as f64 / seconds } else { 0.0 };
+            }
+            self.last = Some((tick, now));
+            self.stepped_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |time| time.as_secs_f64());
+        }
+
+        self.tick = tick;
+        self.population = stats.population;
+        self.mean_energy = stats.mean_energy as f64;
+        self.species = stats.species;
+    }
+}
+
+/// Writes a single metric without labels with its help and type
+fn metric<T: std::fmt::Display>(text: &mut String, name: &str, kind: &str, help: &str, value: T) {
+    let _ = writeln!(text, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(text, "# TYPE {}_{} {}", PREFIX, name, kind);
+    let _ = writeln!(text, "{}_{} {}", PREFIX, name, value);
+}
+
+/// An HTTP endpoint serving the metrics of a simulation to Prometheus at /metrics. The connections are handled
+/// on their own threads and are served the metrics as they were at the last record, so scraping never waits for a step
+#[derive(Debug)]
+pub struct MetricsServer {
+    /// The address the server listens on
+    address: SocketAddr,
+    /// The latest values of the metrics shared with the connections and the threads following subscriptions
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl MetricsServer {
+    /// Starts listening for scrapes, the connections are accepted on a background thread
+    /// 
+    /// # Parameters
+    /// 
+    /// address: The address to listen on, use port 0 to get any free port
+    /// 
+    /// # Errors
+    /// 
+    /// This will fail if the address cannot be listened on
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::io::{Read, Write};
+    /// use std::net::TcpStream;
+    /// use evolution_plants::{board::BoardBuilder, metrics::MetricsServer, population::Population, simulation::{Simulation, SimulationConfig}, stats};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let server = MetricsServer::bind("127.0.0.1:0").unwrap();
+    /// server.follow(stats::subscribe(&mut simulation, 16));
+    /// simulation.step();
+    /// while server.metrics().tick() < 1 {
+    ///     std::thread::yield_now();
+    /// }
+    /// 
+    /// let mut client = TcpStream::connect(server.local_addr()).unwrap();
+    /// client.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+    /// let mut response = String::new();
+    /// client.read_to_string(&mut response).unwrap();
+    /// 
+    /// assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    /// assert!(response.contains("evolution_plants_ticks_total 1\n"));
+    /// ```
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let metrics = Arc::new(Mutex::new(Metrics::new()));
+
+        let shared = Arc::clone(&metrics);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || serve_connection(stream, &shared));
+            }
+        });
+
+        Ok(Self { address, metrics })
+    }
+
+    /// Returns the address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the latest values of the metrics
+    pub fn metrics(&self) -> Metrics {
+        lock(&self.metrics).clone()
+    }
+
+    /// Records the statistics of every step received by a subscription on a background thread,
+    /// the thread stops once the simulation has been dropped. The simulation never waits for the server,
+    /// records which do not fit in the buffer of the subscription are dropped
+    /// 
+    /// # Parameters
+    /// 
+    /// subscription: The subscription to the simulation to serve the metrics of
+    pub fn follow(&self, subscription: Subscription) {
+        let shared = Arc::clone(&self.metrics);
+        thread::spawn(move || {
+            for stats in subscription {
+                lock(&shared).record(&stats);
+            }
+        });
+    }
+
+    /// Records the statistics of a step and serves them to the next scrapes, see Metrics::record
+    /// 
+    /// # Parameters
+    /// 
+    /// stats: The statistics of the step
+    pub fn record(&self, stats: &TickStats) {
+        lock(&self.metrics).record(stats);
+    }
+
+    /// Records the memory used by a simulation and serves it to the next scrapes
+    /// 
+    /// # Parameters
+    /// 
+    /// memory: The memory used
+    pub fn record_memory(&self, memory: &MemoryReport) {
+        lock(&self.metrics).record_memory(memory);
+    }
+
+    /// Records the time it took to write a checkpoint and serves it to the next scrapes
+    /// 
+    /// # Parameters
+    /// 
+    /// duration: The time it took
+    pub fn record_checkpoint(&self, duration: Duration) {
+        lock(&self.metrics).record_checkpoint(duration);
+    }
+
+    /// Runs something which writes a checkpoint and records the time it took, returns what it returned
+    /// 
+    /// # Parameters
+    /// 
+    /// checkpoint: Writes the checkpoint
+    pub fn time_checkpoint<T, F: FnOnce() -> T>(&self, checkpoint: F) -> T {
+        let start = Instant::now();
+        let result = checkpoint();
+        self.record_checkpoint(start.elapsed());
+
+        result
+    }
+}
+
+/// Locks the shared metrics, the metrics are still used if a thread panicked while holding the lock
+fn lock(metrics: &Mutex<Metrics>) -> MutexGuard<'_, Metrics> {
+    metrics.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Answers a single HTTP request and closes the connection
+fn serve_connection(stream: TcpStream, metrics: &Mutex<Metrics>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut first = String::new();
+    reader.read_line(&mut first)?;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let path = first.split_whitespace().nth(1).unwrap_or("");
+    let (status, content_type, body) = if first.starts_with("GET ") && (path == "/metrics" || path == "/") {
+        let body = lock(metrics).exposition();
+        ("200 OK", CONTENT_TYPE, body)
+    } else {
+        ("404 Not Found", "text/plain; charset=utf-8", String::from("Not found\n"))
+    };
+
+    write!(writer, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, body.len(), body)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::{Simulation, SimulationConfig};
+    use crate::stats;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.5, 0.25]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn metrics_tick_rate() {
+        let simulation = simulation();
+        let stats = |tick| TickStats::new(tick, simulation.population(), 0, 0, 0.0, 0, None);
+        let mut metrics = Metrics::new();
+        let start = Instant::now();
+        metrics.record_at(&stats(1), start);
+        metrics.record_at(&stats(5), start + Duration::from_secs(2));
+        assert_eq!(2.0, metrics.tick_rate());
+
+        // Recording the same tick again keeps the rate
+        metrics.record_at(&stats(5), start + Duration::from_secs(10));
+        assert_eq!(2.0, metrics.tick_rate());
+    }
+
+    #[test]
+    fn metrics_exposition() {
+        let simulation = simulation();
+        let mut metrics = Metrics::new();
+        metrics.record(&TickStats::new(0, simulation.population(), 0, 0, 0.0, 0, None));
+        metrics.record_memory(&simulation.memory_report());
+        metrics.record_checkpoint(Duration::from_millis(500));
+        metrics.time_checkpoint(|| ());
+        let text = metrics.exposition();
+
+        assert!(text.contains("evolution_plants_population 1\n"));
+        assert!(text.contains("evolution_plants_extinct 0\n"));
+        assert!(text.contains("evolution_plants_mean_energy 50\n"));
+        assert!(text.contains(&format!("evolution_plants_memory_bytes{{part=\"population\"}} {}\n", simulation.memory_report().population)));
+        assert!(text.contains("evolution_plants_checkpoint_seconds_count 2\n"));
+        assert!(text.lines().filter(|line| !line.starts_with('#')).all(|line| line.split(' ').count() == 2));
+    }
+
+    #[test]
+    fn metrics_server_follow() {
+        let mut simulation = simulation();
+        let server = MetricsServer::bind("127.0.0.1:0").unwrap();
+        server.follow(stats::subscribe(&mut simulation, 16));
+        for _ in 0..3 {
+            simulation.step();
+        }
+        drop(simulation);
+        while server.metrics().tick() < 3 {
+            thread::yield_now();
+        }
+
+        assert!(server.metrics().exposition().contains("evolution_plants_ticks_total 3\n"));
+    }
+
+    #[test]
+    fn metrics_server_not_found() {
+        let server = MetricsServer::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client.write_all(b"GET /other HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
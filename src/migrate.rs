@@ -0,0 +1,187 @@
+use crate::checkpoint::{self, CheckpointError, Reader, KIND_DELTA, KIND_FULL, MAGIC, VERSION};
+use crate::tuning::Parallelism;
+
+/// The oldest version of the checkpoint format which can still be upgraded and loaded
+pub const OLDEST_VERSION: u8 = 1;
+
+/// Changes the uncompressed bytes of a single checkpoint of a given kind from one version of the format to the next
+type Migration = fn(u8, Vec<u8>) -> Result<Vec<u8>, CheckpointError>;
+
+/// Returns the version of the format a checkpoint chain was saved with
+/// 
+/// # Parameters
+/// 
+/// bytes: The saved chain
+/// 
+/// # Errors
+/// 
+/// CheckpointError::Format: This will occur if the bytes are not a checkpoint chain
+pub fn version(bytes: &[u8]) -> Result<u8, CheckpointError> {
+    if bytes.len() < MAGIC.len() + 1 || bytes[..MAGIC.len()] != MAGIC {
+        return Err(CheckpointError::Format);
+    }
+
+    Ok(bytes[MAGIC.len()])
+}
+
+/// Upgrades a checkpoint chain saved with an older version of the format to the current version one version at a time.
+/// The values added to the format since the chain was saved get the value a new simulation starts with,
+/// and every checkpoint keeps its compression. A chain which already has the current version is returned unchanged
+/// 
+/// # Parameters
+/// 
+/// bytes: The saved chain
+/// 
+/// # Errors
+/// 
+/// CheckpointError::Format: This will occur if the bytes are not a checkpoint chain
+/// 
+/// CheckpointError::Version: This will occur if the chain is older than OLDEST_VERSION or newer than the current version
+/// 
+/// CheckpointError::Corrupt: This will occur if a checkpoint ends early or holds invalid values
+pub fn upgrade(bytes: &[u8]) -> Result<Vec<u8>, CheckpointError> {
+    let mut version = version(bytes)?;
+    if !(OLDEST_VERSION..=VERSION).contains(&version) {
+        return Err(CheckpointError::Version { found: version });
+    }
+
+    let mut bytes = bytes.to_vec();
+    while version < VERSION {
+        let migration: Migration = match version {
+            1 => add_nutrients,
+            2 => add_parallelism,
+            _ => unreachable!("Every version before the current one has a migration"),
+        };
+
+        version += 1;
+        bytes = rewrite(&bytes, version, migration)?;
+    }
+
+    Ok(bytes)
+}
+
+/// Runs a migration on every checkpoint of a chain and marks the chain with the version it was migrated to
+fn rewrite(bytes: &[u8], version: u8, migration: Migration) -> Result<Vec<u8>, CheckpointError> {
+    let mut out = Vec::with_capacity(bytes.len());
+    out.extend_from_slice(&MAGIC);
+    out.push(version);
+
+    for (kind, compression, raw) in checkpoint::entries(&bytes[MAGIC.len() + 1..])? {
+        checkpoint::put_entry(&mut out, kind, compression, &migration(kind, raw)?);
+    }
+
+    Ok(out)
+}
+
+/// Version 2 added the nutrients of every cell after the water, the soil of older chains has no nutrients
+fn add_nutrients(kind: u8, mut raw: Vec<u8>) -> Result<Vec<u8>, CheckpointError> {
+    match kind {
+        KIND_FULL => {
+            let mut reader = Reader::new(&raw);
+            reader.u64()?;
+            let cells = reader.len()?.checked_mul(reader.len()?).ok_or(CheckpointError::Corrupt)?;
+
+            // Every cell of version 1 takes at least a byte for the plant and 4 for the water
+            if cells.checked_mul(5).is_none_or(|len| len > reader.remaining()) {
+                return Err(CheckpointError::Corrupt);
+            }
+
+            raw.extend(std::iter::repeat_n(0.0f32.to_le_bytes(), cells).flatten());
+        }
+
+        KIND_DELTA => raw.extend_from_slice(&0u64.to_le_bytes()),
+
+        _ => return Err(CheckpointError::Corrupt),
+    }
+
+    Ok(raw)
+}
+
+/// Version 3 added the parallel setting at the end of every checkpoint, older chains ran on a single thread
+fn add_parallelism(kind: u8, mut raw: Vec<u8>) -> Result<Vec<u8>, CheckpointError> {
+    if kind != KIND_FULL && kind != KIND_DELTA {
+        return Err(CheckpointError::Corrupt);
+    }
+    checkpoint::put_parallelism(&mut raw, Parallelism::default());
+
+    Ok(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use crate::checkpoint::{Checkpoint, CheckpointChain, CheckpointConfig, Compression};
+
+    /// A chain of a board with a single cell saved with version 1, a full checkpoint followed by a delta changing the water
+    fn version_1(compression: Compression) -> Vec<u8> {
+        let mut full = Vec::new();
+        full.extend_from_slice(&3u64.to_le_bytes());
+        full.extend_from_slice(&1u64.to_le_bytes());
+        full.extend_from_slice(&1u64.to_le_bytes());
+        full.extend_from_slice(&11u128.to_le_bytes());
+        full.push(0);
+        full.extend_from_slice(&0.25f32.to_le_bytes());
+
+        let mut delta = Vec::new();
+        delta.extend_from_slice(&3u64.to_le_bytes());
+        delta.extend_from_slice(&4u64.to_le_bytes());
+        delta.extend_from_slice(&12u128.to_le_bytes());
+        delta.extend_from_slice(&0u64.to_le_bytes());
+        delta.extend_from_slice(&1u64.to_le_bytes());
+        delta.extend_from_slice(&0u64.to_le_bytes());
+        delta.extend_from_slice(&0.5f32.to_le_bytes());
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(1);
+        checkpoint::put_entry(&mut bytes, KIND_FULL, compression, &full);
+        checkpoint::put_entry(&mut bytes, KIND_DELTA, compression, &delta);
+
+        bytes
+    }
+
+    #[test]
+    fn upgrade_version_1() {
+        for compression in [Compression::None, Compression::RunLength] {
+            let bytes = version_1(compression);
+            let upgraded = upgrade(&bytes).unwrap();
+
+            assert_eq!(Ok(VERSION), version(&upgraded));
+
+            let chain = CheckpointChain::from_bytes(&bytes, CheckpointConfig::default()).unwrap();
+            let first = match &chain.checkpoints()[0] {
+                Checkpoint::Full(snapshot) => snapshot.clone(),
+                Checkpoint::Delta(_) => panic!("Expected a full checkpoint"),
+            };
+
+            assert_eq!(Size::new(1, 1), first.size);
+            assert_eq!(vec![0.0], first.nutrients);
+            assert_eq!(vec![0.25], first.water);
+
+            let latest = chain.latest().unwrap();
+
+            assert_eq!(4, latest.tick);
+            assert_eq!(vec![0.5], latest.water);
+            assert_eq!(vec![0.0], latest.nutrients);
+            assert_eq!(Parallelism::default(), latest.parallelism);
+        }
+    }
+
+    #[test]
+    fn upgrade_current_unchanged() {
+        let mut chain = CheckpointChain::new(CheckpointConfig::default());
+        chain.push(&CheckpointChain::from_bytes(&version_1(Compression::None), CheckpointConfig::default()).unwrap().latest().unwrap());
+        let bytes = chain.to_bytes();
+
+        assert_eq!(bytes, upgrade(&bytes).unwrap());
+    }
+
+    #[test]
+    fn upgrade_unknown_version() {
+        let mut bytes = version_1(Compression::None);
+        bytes[MAGIC.len()] = 0;
+
+        assert_eq!(Err(CheckpointError::Version { found: 0 }), upgrade(&bytes));
+        assert_eq!(Err(CheckpointError::Format), upgrade(b"EPC"));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::genome::{self, Genome};
+use crate::population::Population;
+
+/// The settings for a mycorrhizal network. Plants with a mycorrhiza gene join an underground network with every other
+/// member within reach, and networks chain through their members so a network is every member reachable by hopping
+/// between members. Every step each member pays a part of its energy set by its mycorrhiza gene into its network,
+/// and the network shares the pooled energy evenly between its members after a loss. Members paying less than
+/// the rest gain from the network at the expense of the others, so cooperation and defection compete
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MycorrhizaConfig {
+    /// The largest distance in cells in each direction between two members which are connected
+    pub reach: usize,
+    /// The fraction of its energy a plant with a mycorrhiza gene of 1 pays into its network every step
+    pub share: f32,
+    /// The fraction of the energy paid into a network which is lost before it is shared
+    pub loss: f32,
+}
+
+impl Default for MycorrhizaConfig {
+    fn default() -> Self {
+        Self {
+            reach: 1,
+            share: 0.1,
+            loss: 0.1,
+        }
+    }
+}
+
+impl MycorrhizaConfig {
+    /// Finds how much a plant invests in the network from 0 to 1, plants without a mycorrhiza gene are not members
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    pub fn investment(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_MYCORRHIZA).unwrap_or(0.0)
+    }
+
+    /// Finds the networks of the population, a network is the indices of the cells of its members in order
+    /// and the networks are sorted by their first cell. Plants investing nothing are not members of any network
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to connect
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, mycorrhiza::MycorrhizaConfig, population::{Plant, Population}};
+    /// 
+    /// let member = Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap();
+    /// let mut population = Population::new(Size::new(5, 1));
+    /// for x in [0, 1, 4] {
+    ///     population.insert(Coord::new(x, 0), Plant::new(10, member.clone()));
+    /// }
+    /// population.insert(Coord::new(3, 0), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// 
+    /// assert_eq!(vec![vec![0, 1], vec![4]], MycorrhizaConfig::default().networks(&population));
+    /// ```
+    pub fn networks(&self, population: &Population) -> Vec<Vec<usize>> {
+        let size = population.size();
+        let members: Vec<usize> = (0..size.len())
+            .filter(|index| population.plant(*index).is_some_and(|plant| self.investment(&plant.genome) > 0.0))
+            .collect();
+
+        // Join every member with the members within reach
+        let mut roots: Vec<usize> = (0..size.len()).collect();
+        for &index in &members {
+            for neighbour in population.spatial().neighbors_within(size.coord_of(index).unwrap(), self.reach) {
+                let neighbour = size.index_of(neighbour).unwrap();
+                if members.binary_search(&neighbour).is_ok() {
+                    let (a, b) = (find_root(&mut roots, index), find_root(&mut roots, neighbour));
+                    roots[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut networks: Vec<Vec<usize>> = Vec::new();
+        let mut positions = vec![usize::MAX; size.len()];
+        for &index in &members {
+            let root = find_root(&mut roots, index);
+            if positions[root] == usize::MAX {
+                positions[root] = networks.len();
+                networks.push(Vec::new());
+            }
+            networks[positions[root]].push(index);
+        }
+
+        networks
+    }
+
+    /// Lets every member pay into its network and shares the pooled energy of every network evenly between its members,
+    /// the energy which cannot be shared evenly is lost. Returns the energy lost
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants sharing energy
+    pub(crate) fn share(&self, population: &mut Population) -> u64 {
+        let share = self.share.clamp(0.0, 1.0);
+        let kept = 1.0 - self.loss.clamp(0.0, 1.0);
+        let mut lost = 0;
+
+        for network in self.networks(population) {
+            if network.len() < 2 {
+                continue;
+            }
+
+            let mut pool: u64 = 0;
+            for &index in &network {
+                let plant = population.plant_mut(index).unwrap();
+                let payment = (plant.energy as f32 * self.investment(&plant.genome) * share) as u32;
+                plant.energy -= payment;
+                pool += payment as u64;
+            }
+
+            let portion = ((pool as f32 * kept) as u64 / network.len() as u64).min(u32::MAX as u64) as u32;
+            for &index in &network {
+                let plant = population.plant_mut(index).unwrap();
+                plant.energy = plant.energy.saturating_add(portion);
+            }
+            lost += pool - portion as u64 * network.len() as u64;
+        }
+
+        lost
+    }
+}
+
+/// Finds the root of the network of a cell and points the cells on the way straight at it
+fn find_root(roots: &mut [usize], index: usize) -> usize {
+    let mut root = index;
+    while roots[root] != root {
+        root = roots[root];
+    }
+
+    let mut index = index;
+    while roots[index] != root {
+        let next = roots[index];
+        roots[index] = root;
+        index = next;
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Size};
+    use crate::population::Plant;
+
+    fn genome(investment: f32) -> Genome {
+        let mut genes = vec![0.5; genome::GENE_MYCORRHIZA + 1];
+        genes[genome::GENE_MYCORRHIZA] = investment;
+
+        Genome::new(&genes).unwrap()
+    }
+
+    #[test]
+    fn mycorrhiza_networks_chain() {
+        let config = MycorrhizaConfig { reach: 2, ..Default::default() };
+        let mut population = Population::new(Size::new(8, 3));
+        for coord in [Coord::new(0, 0), Coord::new(2, 1), Coord::new(4, 2), Coord::new(7, 0)] {
+            population.insert(coord, Plant::new(10, genome(0.5)));
+        }
+        population.insert(Coord::new(6, 1), Plant::new(10, genome(0.0)));
+
+        assert_eq!(vec![vec![0, 10, 20], vec![7]], config.networks(&population));
+    }
+
+    #[test]
+    fn mycorrhiza_share() {
+        let config = MycorrhizaConfig { reach: 1, share: 0.5, loss: 0.1 };
+        let mut population = Population::new(Size::new(3, 1));
+        population.insert(Coord::new(0, 0), Plant::new(100, genome(1.0)));
+        population.insert(Coord::new(1, 0), Plant::new(100, genome(0.2)));
+        population.insert(Coord::new(2, 0), Plant::new(40, genome(0.0)));
+
+        // The cooperator pays 50 and the defector 10, the 54 left after the loss are shared evenly
+        assert_eq!(6, config.share(&mut population));
+        assert_eq!(77, population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(117, population.get(Coord::new(1, 0)).unwrap().energy);
+        assert_eq!(40, population.get(Coord::new(2, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn mycorrhiza_share_alone() {
+        let config = MycorrhizaConfig::default();
+        let mut population = Population::new(Size::new(3, 1));
+        population.insert(Coord::new(0, 0), Plant::new(100, genome(1.0)));
+        population.insert(Coord::new(2, 0), Plant::new(100, genome(1.0)));
+
+        assert_eq!(0, config.share(&mut population));
+        assert_eq!(100, population.get(Coord::new(0, 0)).unwrap().energy);
+    }
+}
@@ -0,0 +1,416 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::board::Size;
+use crate::simulation::Simulation;
+
+/// The tag of a list of dimensions in the header
+const NC_DIMENSION: u32 = 0x0A;
+/// The tag of a list of variables in the header
+const NC_VARIABLE: u32 = 0x0B;
+/// The tag of a list of attributes in the header
+const NC_ATTRIBUTE: u32 = 0x0C;
+/// The offset of the number of records in the file
+const NUMRECS_OFFSET: u64 = 4;
+
+/// The type of the values of a variable
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum NcType {
+    /// Signed 8 bit integers
+    Byte = 1,
+    /// Characters of text
+    Char = 2,
+    /// Signed 32 bit integers
+    Int = 4,
+    /// 32 bit floats
+    Float = 5,
+    /// 64 bit floats
+    Double = 6,
+}
+
+impl NcType {
+    /// Returns the number of bytes of a single value
+    fn width(&self) -> usize {
+        match self {
+            NcType::Byte | NcType::Char => 1,
+            NcType::Int | NcType::Float => 4,
+            NcType::Double => 8,
+        }
+    }
+}
+
+/// A variable stored for every tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Variable {
+    /// The name of the variable
+    name: &'static str,
+    /// The description of the variable
+    long_name: &'static str,
+    /// The type of the values
+    nc_type: NcType,
+    /// True if the variable has a value for every cell, otherwise it has a single value
+    spatial: bool,
+}
+
+/// All variables in the order they are stored in every record
+const VARIABLES: [Variable; 6] = [
+    Variable { name: "time", long_name: "tick of the simulation", nc_type: NcType::Double, spatial: false },
+    Variable { name: "light", long_name: "light after shadows and edge band", nc_type: NcType::Float, spatial: true },
+    Variable { name: "water", long_name: "water on the board", nc_type: NcType::Float, spatial: true },
+    Variable { name: "temperature", long_name: "temperature", nc_type: NcType::Float, spatial: true },
+    Variable { name: "occupied", long_name: "1 if a plant lives in the cell", nc_type: NcType::Byte, spatial: true },
+    Variable { name: "energy", long_name: "energy of the plant in the cell, 0 if empty", nc_type: NcType::Int, spatial: true },
+];
+
+/// Streams the fields and the population of a simulation into a NetCDF file (the classic format with 64 bit offsets)
+/// with one record for every written tick. Every spatial variable has the dimensions (time, y, x)
+/// so the file can be opened directly as a spatial time series by xarray, netCDF4 or ncview.
+/// The number of records in the file is updated after every tick, so the file can be read while it is written.
+/// The format is written directly without the netcdf or hdf5 libraries, so it is not a NetCDF-4 file:
+/// the variables are neither compressed nor chunked and a single field of the board must fit in 4 GiB
+#[derive(Debug)]
+pub struct NetcdfWriter<W: Write + Seek> {
+    /// The file being written
+    out: W,
+    /// The size of the board
+    size: Size,
+    /// The number of records written
+    records: u32,
+    /// The offset of the end of the last record
+    end: u64,
+}
+
+impl NetcdfWriter<BufWriter<File>> {
+    /// Creates a new file and writes the header
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the file
+    /// size: The size of the board
+    /// 
+    /// # Errors
+    /// 
+    /// NetcdfError::Io: This will occur if the file could not be created
+    /// 
+    /// NetcdfError::TooLarge: This will occur if a single field of the board does not fit in a record
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::{board::BoardBuilder, netcdf::NetcdfWriter, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(20, 10).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut writer = NetcdfWriter::create("history.nc", size).unwrap();
+    /// for _ in 0..100 {
+    ///     simulation.step();
+    ///     writer.write(&simulation).unwrap();
+    /// }
+    /// writer.finish().unwrap();
+    /// ```
+    pub fn create<P: AsRef<Path>>(path: P, size: Size) -> Result<Self, NetcdfError> {
+        Self::new(BufWriter::new(File::create(path)?), size)
+    }
+}
+
+impl<W: Write + Seek> NetcdfWriter<W> {
+    /// Creates a new writer writing the header to the start of a stream
+    /// 
+    /// # Parameters
+    /// 
+    /// out: The stream to write to
+    /// size: The size of the board
+    /// 
+    /// # Errors
+    /// 
+    /// NetcdfError::Io: This will occur if the header could not be written
+    /// 
+    /// NetcdfError::TooLarge: This will occur if a single field of the board does not fit in a record
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::io::Cursor;
+    /// use evolution_plants::{board::Size, netcdf::NetcdfWriter};
+    /// 
+    /// let writer = NetcdfWriter::new(Cursor::new(Vec::new()), Size::new(4, 3)).unwrap();
+    /// let bytes = writer.finish().unwrap().into_inner();
+    /// 
+    /// assert_eq!(b"CDF\x02", &bytes[..4]);
+    /// ```
+    pub fn new(mut out: W, size: Size) -> Result<Self, NetcdfError> {
+        if VARIABLES.iter().any(|variable| variable_size(variable, size) > u32::MAX as usize) {
+            return Err(NetcdfError::TooLarge { size });
+        }
+
+        // The header does not change length with the offsets, so it is built once to find its length
+        let len = header(size, 0).len() as u64;
+        out.seek(SeekFrom::Start(0))?;
+        out.write_all(&header(size, len))?;
+
+        Ok(Self { out, size, records: 0, end: len })
+    }
+
+    /// Returns the number of ticks written
+    pub fn records(&self) -> u32 {
+        self.records
+    }
+
+    /// Writes the current tick of a simulation as a new record and updates the number of records
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to write
+    /// 
+    /// # Errors
+    /// 
+    /// NetcdfError::Size: This will occur if the board of the simulation does not have the size of the file
+    /// 
+    /// NetcdfError::Full: This will occur if the file already has the largest number of records
+    /// 
+    /// NetcdfError::Io: This will occur if the record could not be written
+    pub fn write(&mut self, simulation: &Simulation) -> Result<(), NetcdfError> {
+        let size = simulation.board().fields.size;
+        if size != self.size {
+            return Err(NetcdfError::Size { expected: self.size, found: size });
+        }
+        if self.records == u32::MAX - 1 {
+            return Err(NetcdfError::Full);
+        }
+
+        let mut record = Vec::with_capacity(record_size(size));
+        let population = simulation.population();
+
+        for variable in VARIABLES.iter() {
+            let start = record.len();
+            match variable.name {
+                "time" => record.extend_from_slice(&(simulation.tick() as f64).to_be_bytes()),
+                "light" => put_floats(&mut record, simulation.light()),
+                "water" => put_floats(&mut record, simulation.water().values()),
+                "temperature" => put_floats(&mut record, &simulation.board().fields.temperature),
+                "occupied" => record.extend(population.cells().map(|cell| cell.is_some() as u8)),
+                "energy" => {
+                    for cell in population.cells() {
+                        let energy = cell.map_or(0, |plant| plant.energy.min(i32::MAX as u32) as i32);
+                        record.extend_from_slice(&energy.to_be_bytes());
+                    }
+                }
+                _ => unreachable!("Every variable is written"),
+            }
+            record.resize(start + variable_size(variable, size), 0);
+        }
+
+        self.out.seek(SeekFrom::Start(self.end))?;
+        self.out.write_all(&record)?;
+        self.end += record.len() as u64;
+        self.records += 1;
+
+        self.out.seek(SeekFrom::Start(NUMRECS_OFFSET))?;
+        self.out.write_all(&self.records.to_be_bytes())?;
+        self.out.flush()?;
+
+        Ok(())
+    }
+
+    /// Flushes everything written and returns the stream
+    /// 
+    /// # Errors
+    /// 
+    /// NetcdfError::Io: This will occur if the stream could not be flushed
+    pub fn finish(mut self) -> Result<W, NetcdfError> {
+        self.out.seek(SeekFrom::Start(self.end))?;
+        self.out.flush()?;
+
+        Ok(self.out)
+    }
+}
+
+/// Finds the number of bytes a variable takes up in every record, padded to a multiple of 4
+fn variable_size(variable: &Variable, size: Size) -> usize {
+    let count = if variable.spatial { size.len() } else { 1 };
+
+    padded(count * variable.nc_type.width())
+}
+
+/// Finds the number of bytes of a full record
+fn record_size(size: Size) -> usize {
+    VARIABLES.iter().map(|variable| variable_size(variable, size)).sum()
+}
+
+/// Rounds a number of bytes up to a multiple of 4
+fn padded(len: usize) -> usize {
+    len.div_ceil(4) * 4
+}
+
+/// Adds 32 bit floats in big endian
+fn put_floats(out: &mut Vec<u8>, values: &[f32]) {
+    for value in values {
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Adds a 32 bit integer in big endian
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Adds a name given by its length and its characters padded with zeros to a multiple of 4
+fn put_name(out: &mut Vec<u8>, name: &str) {
+    put_u32(out, name.len() as u32);
+    out.extend_from_slice(name.as_bytes());
+    out.resize(out.len() + padded(name.len()) - name.len(), 0);
+}
+
+/// Adds a list of text attributes
+fn put_text_attributes(out: &mut Vec<u8>, attributes: &[(&str, &str)]) {
+    put_u32(out, NC_ATTRIBUTE);
+    put_u32(out, attributes.len() as u32);
+    for (name, value) in attributes {
+        put_name(out, name);
+        put_u32(out, NcType::Char as u32);
+        put_name(out, value);
+    }
+}
+
+/// Builds the header of the file where the records start at some offset
+fn header(size: Size, begin: u64) -> Vec<u8> {
+    let (w, h) = size.size();
+    let mut out = b"CDF\x02".to_vec();
+    put_u32(&mut out, 0);
+
+    // The time is unlimited and the board is indexed as (y, x)
+    put_u32(&mut out, NC_DIMENSION);
+    put_u32(&mut out, 3);
+    for (name, len) in [("time", 0), ("y", h), ("x", w)] {
+        put_name(&mut out, name);
+        put_u32(&mut out, len as u32);
+    }
+
+    put_text_attributes(&mut out, &[("title", "evolution_plants field history")]);
+
+    put_u32(&mut out, NC_VARIABLE);
+    put_u32(&mut out, VARIABLES.len() as u32);
+    let mut offset = begin;
+    for variable in VARIABLES.iter() {
+        put_name(&mut out, variable.name);
+        let dimensions: &[u32] = if variable.spatial { &[0, 1, 2] } else { &[0] };
+        put_u32(&mut out, dimensions.len() as u32);
+        for dimension in dimensions {
+            put_u32(&mut out, *dimension);
+        }
+        put_text_attributes(&mut out, &[("long_name", variable.long_name)]);
+        put_u32(&mut out, variable.nc_type as u32);
+        put_u32(&mut out, variable_size(variable, size) as u32);
+        out.extend_from_slice(&offset.to_be_bytes());
+        offset += variable_size(variable, size) as u64;
+    }
+
+    out
+}
+
+#[derive(Error, Debug)]
+pub enum NetcdfError {
+    #[error("Unable to write the file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("The board has size {:?} but the file was created for size {:?}", found, expected)]
+    Size {
+        expected: Size,
+        found: Size,
+    },
+    #[error("A field of a board with size {:?} is too large for a record", size)]
+    TooLarge {
+        size: Size,
+    },
+    #[error("The file has the largest number of records")]
+    Full,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(3, 2).light_from_slice(&[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(2, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn padded_multiple_of_4() {
+        assert_eq!(0, padded(0));
+        assert_eq!(4, padded(1));
+        assert_eq!(8, padded(8));
+    }
+
+    #[test]
+    fn header_offsets() {
+        let size = Size::new(3, 2);
+        let begin = header(size, 0).len();
+        let bytes = header(size, begin as u64);
+
+        assert_eq!(begin, bytes.len());
+        // The last variable is the energy which starts after all the others
+        let energy_begin = u64::from_be_bytes(bytes[bytes.len() - 8..].try_into().unwrap()) as usize;
+        assert_eq!(begin + 8 + 3 * 24 + 8, energy_begin);
+        assert_eq!(8 + 3 * 24 + 8 + 24, record_size(size));
+    }
+
+    #[test]
+    fn netcdf_writer_write() {
+        let mut simulation = simulation();
+        let mut writer = NetcdfWriter::new(Cursor::new(Vec::new()), Size::new(3, 2)).unwrap();
+        writer.write(&simulation).unwrap();
+        simulation.step();
+        writer.write(&simulation).unwrap();
+
+        assert_eq!(2, writer.records());
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let begin = header(Size::new(3, 2), 0).len();
+        let second = begin + record_size(Size::new(3, 2));
+
+        assert_eq!(2, u32_at(&bytes, 4));
+        assert_eq!(second + record_size(Size::new(3, 2)), bytes.len());
+        assert_eq!(1.0, f64::from_be_bytes(bytes[second..second + 8].try_into().unwrap()));
+        assert_eq!(0.1, f32::from_be_bytes(bytes[begin + 12..begin + 16].try_into().unwrap()));
+        // The plant is in the last cell of the occupied variable
+        assert_eq!(&[0, 0, 0, 0, 0, 1, 0, 0], &bytes[begin + 80..begin + 88]);
+        assert_eq!(100, u32_at(&bytes, begin + 88 + 20));
+    }
+
+    #[test]
+    fn netcdf_writer_size_error() {
+        let mut writer = NetcdfWriter::new(Cursor::new(Vec::new()), Size::new(2, 2)).unwrap();
+
+        assert!(matches!(writer.write(&simulation()), Err(NetcdfError::Size { .. })));
+        assert_eq!(0, writer.records());
+    }
+
+    #[test]
+    fn netcdf_writer_create() {
+        let path = std::env::temp_dir().join(format!("evolution_plants_{}_history.nc", std::process::id()));
+        let mut writer = NetcdfWriter::create(&path, Size::new(3, 2)).unwrap();
+        writer.write(&simulation()).unwrap();
+        writer.finish().unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(1, u32_at(&bytes, 4));
+        assert_eq!(header(Size::new(3, 2), 0).len() + record_size(Size::new(3, 2)), bytes.len());
+    }
+}
@@ -0,0 +1,263 @@
+use std::ops::Range;
+
+use crate::genome::{Genome, MutationConfig};
+use crate::population::Plant;
+
+/// The number of sensor inputs of the network
+pub const INPUTS: usize = 4;
+/// The number of decisions made by the network
+pub const OUTPUTS: usize = 3;
+
+/// The settings for letting plants decide how to use their energy with a small feed-forward network.
+/// The weights of the network are stored in the genome after an offset, every gene between 0 and 1 is mapped
+/// to a weight between -weight_range and weight_range. Plants whose genome is too short to hold all the weights
+/// live as if the network was disabled
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NeuralConfig {
+    /// The index of the first gene of the weights
+    pub offset: usize,
+    /// The number of neurons in the hidden layer
+    pub hidden: usize,
+    /// The largest absolute value of a weight
+    pub weight_range: f32,
+    /// The number of steps of a full season cycle, the season input is always 0 if this is 0
+    pub season_length: u64,
+    /// The increase of the growth of a plant for every unit of energy invested in growing
+    pub growth_rate: f32,
+    /// The largest growth of a plant, a plant with a growth of 1 collects twice the light
+    pub max_growth: f32,
+    /// The extra normally distributed mutation of the weights of every seed on top of the normal mutation,
+    /// the weights only mutate like the other genes if this is None
+    pub weight_mutation: Option<MutationConfig>,
+}
+
+impl Default for NeuralConfig {
+    fn default() -> Self {
+        Self {
+            offset: 9,
+            hidden: 4,
+            weight_range: 4.0,
+            season_length: 0,
+            growth_rate: 0.01,
+            max_growth: 1.0,
+            weight_mutation: None,
+        }
+    }
+}
+
+/// What a plant senses about its cell, every value is roughly between 0 and 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sensors {
+    /// The relative light reaching the cell
+    pub light: f32,
+    /// The water in the cell
+    pub water: f32,
+    /// The fraction of the neighbouring cells with a plant
+    pub density: f32,
+    /// The point in the season cycle, 0 in the middle of winter and 1 in the middle of summer
+    pub season: f32,
+}
+
+/// How a plant splits the energy it collects in a step, the fractions add up to 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Allocation {
+    /// The fraction invested in growing, which makes the plant collect more light in the following steps
+    pub grow: f32,
+    /// The fraction stored as energy
+    pub store: f32,
+    /// The fraction stored as energy while letting the plant reproduce in this step
+    pub reproduce: f32,
+}
+
+impl Allocation {
+    /// Returns true if reproducing is the strongest decision, only then the plant reproduces in the step
+    pub fn reproduces(&self) -> bool {
+        self.reproduce >= self.grow && self.reproduce >= self.store
+    }
+}
+
+impl NeuralConfig {
+    /// Returns the number of genes holding the weights and biases of the network
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::neural::NeuralConfig;
+    /// 
+    /// let config = NeuralConfig { hidden: 2, ..Default::default() };
+    /// 
+    /// assert_eq!(2 * 5 + 3 * 3, config.weight_count());
+    /// ```
+    pub fn weight_count(&self) -> usize {
+        self.hidden * (INPUTS + 1) + OUTPUTS * (self.hidden + 1)
+    }
+
+    /// Returns the indices of the genes holding the weights
+    pub fn weight_genes(&self) -> Range<usize> {
+        self.offset..self.offset + self.weight_count()
+    }
+
+    /// Finds the season input at a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the simulation
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::neural::NeuralConfig;
+    /// 
+    /// let config = NeuralConfig { season_length: 100, ..Default::default() };
+    /// 
+    /// assert!((config.season(25) - 1.0).abs() < 1e-6);
+    /// assert!(config.season(75).abs() < 1e-6);
+    /// ```
+    pub fn season(&self, tick: u64) -> f32 {
+        if self.season_length == 0 {
+            return 0.0;
+        }
+
+        let phase = (tick % self.season_length) as f32 / self.season_length as f32;
+
+        0.5 + 0.5 * (std::f32::consts::TAU * phase).sin()
+    }
+
+    /// Runs the network of a genome on what the plant senses, returns None if the genome is too short to hold the network
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome holding the weights
+    /// sensors: What the plant senses about its cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, neural::{NeuralConfig, Sensors}};
+    /// 
+    /// let config = NeuralConfig { offset: 2, hidden: 1, ..Default::default() };
+    /// let genome = Genome::new(&vec![0.5; 2 + config.weight_count()]).unwrap();
+    /// let sensors = Sensors { light: 1.0, water: 0.0, density: 0.5, season: 0.0 };
+    /// let allocation = config.evaluate(&genome, &sensors).unwrap();
+    /// 
+    /// // All weights are 0, so every decision is as strong
+    /// assert!((allocation.grow - 1.0 / 3.0).abs() < 1e-6);
+    /// assert_eq!(None, config.evaluate(&Genome::new(&[0.5; 4]).unwrap(), &sensors));
+    /// ```
+    pub fn evaluate(&self, genome: &Genome, sensors: &Sensors) -> Option<Allocation> {
+        let genes = genome.genes().get(self.weight_genes())?;
+        let weight = |index: usize| (2.0 * genes[index] - 1.0) * self.weight_range;
+        let inputs = [sensors.light, sensors.water, sensors.density, sensors.season];
+
+        // Every hidden neuron has a weight for every input followed by its bias
+        let hidden: Vec<f32> = (0..self.hidden)
+            .map(|neuron| {
+                let start = neuron * (INPUTS + 1);
+                let sum: f32 = inputs.iter().enumerate().map(|(input, value)| weight(start + input) * value).sum();
+                (sum + weight(start + INPUTS)).tanh()
+            })
+            .collect();
+
+        // Every output has a weight for every hidden neuron followed by its bias
+        let first = self.hidden * (INPUTS + 1);
+        let mut outputs = [0.0; OUTPUTS];
+        for (output, value) in outputs.iter_mut().enumerate() {
+            let start = first + output * (self.hidden + 1);
+            let sum: f32 = hidden.iter().enumerate().map(|(neuron, activation)| weight(start + neuron) * activation).sum();
+            *value = sum + weight(start + self.hidden);
+        }
+
+        // Turn the outputs into fractions with a softmax
+        let max = outputs.iter().copied().fold(f32::MIN, f32::max);
+        let exp = outputs.map(|value| (value - max).exp());
+        let total: f32 = exp.iter().sum();
+
+        Some(Allocation { grow: exp[0] / total, store: exp[1] / total, reproduce: exp[2] / total })
+    }
+
+    /// Splits the energy collected by a plant in a step, the part invested in growing raises the growth of the plant.
+    /// Returns the energy stored
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant collecting the energy
+    /// intake: The energy collected
+    /// allocation: How the plant splits the energy
+    pub fn allocate(&self, plant: &mut Plant, intake: u32, allocation: &Allocation) -> u32 {
+        let invested = intake as f32 * allocation.grow;
+        plant.growth = (plant.growth + invested * self.growth_rate.max(0.0)).min(self.max_growth.max(0.0));
+
+        intake - (invested as u32).min(intake)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> NeuralConfig {
+        NeuralConfig { offset: 2, hidden: 1, ..Default::default() }
+    }
+
+    /// Creates a genome with all weights at 0 except the bias of one output
+    fn biased(output: usize) -> Genome {
+        let config = config();
+        let mut genes = vec![0.5; 2 + config.weight_count()];
+        genes[2 + (INPUTS + 1) + output * 2 + 1] = 1.0;
+
+        Genome::new(&genes).unwrap()
+    }
+
+    fn sensors() -> Sensors {
+        Sensors { light: 0.5, water: 0.5, density: 0.5, season: 0.5 }
+    }
+
+    #[test]
+    fn neural_config_weight_genes() {
+        assert_eq!(2..2 + 5 + 6, config().weight_genes());
+    }
+
+    #[test]
+    fn neural_config_season_disabled() {
+        assert_eq!(0.0, NeuralConfig::default().season(123));
+    }
+
+    #[test]
+    fn neural_config_evaluate_bias() {
+        let grow = config().evaluate(&biased(0), &sensors()).unwrap();
+        let reproduce = config().evaluate(&biased(2), &sensors()).unwrap();
+
+        assert!(grow.grow > 0.9 && !grow.reproduces());
+        assert!(reproduce.reproduce > 0.9 && reproduce.reproduces());
+        assert!((grow.grow + grow.store + grow.reproduce - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn neural_config_evaluate_input() {
+        // The only hidden neuron follows the light and drives the store output
+        let config = config();
+        let mut genes = vec![0.5; 2 + config.weight_count()];
+        genes[2] = 1.0;
+        genes[2 + (INPUTS + 1) + 2] = 1.0;
+        let genome = Genome::new(&genes).unwrap();
+        let dark = config.evaluate(&genome, &Sensors { light: 0.0, ..sensors() }).unwrap();
+        let bright = config.evaluate(&genome, &Sensors { light: 1.0, ..sensors() }).unwrap();
+
+        assert!(bright.store > dark.store);
+    }
+
+    #[test]
+    fn neural_config_allocate() {
+        let config = NeuralConfig { growth_rate: 0.01, max_growth: 0.5, ..config() };
+        let mut plant = Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap());
+        let allocation = Allocation { grow: 0.25, store: 0.5, reproduce: 0.25 };
+
+        assert_eq!(75, config.allocate(&mut plant, 100, &allocation));
+        assert_eq!(0.25, plant.growth);
+
+        config.allocate(&mut plant, 100, &allocation);
+        config.allocate(&mut plant, 100, &allocation);
+
+        assert_eq!(0.5, plant.growth);
+    }
+}
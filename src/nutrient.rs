@@ -0,0 +1,195 @@
+use crate::board::{Rect, Size};
+use crate::field::Field;
+use crate::memory::HeapSize;
+
+/// The settings for the soil nutrients. The energy left in a plant when it dies becomes litter in its cell,
+/// the litter slowly decomposes into nutrients and the nutrients let the plants in the cell collect more light
+/// until they have taken them up, so dead plants leave patches of fertile soil behind
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NutrientConfig {
+    /// The fraction of the litter in a cell which decomposes every step
+    pub decay_rate: f32,
+    /// The nutrients released by decomposing a unit of energy
+    pub yield_per_energy: f32,
+    /// The extra fraction of light a plant collects for every unit of nutrients in its cell
+    pub growth: f32,
+    /// The largest amount of nutrients which counts towards growth
+    pub saturation: f32,
+    /// The fraction of the nutrients in its cell a plant takes up every step
+    pub uptake: f32,
+}
+
+impl Default for NutrientConfig {
+    fn default() -> Self {
+        Self {
+            decay_rate: 0.1,
+            yield_per_energy: 0.01,
+            growth: 0.5,
+            saturation: 2.0,
+            uptake: 0.05,
+        }
+    }
+}
+
+impl NutrientConfig {
+    /// Finds the factor the light collected by a plant is multiplied by for the nutrients in its cell
+    /// 
+    /// # Parameters
+    /// 
+    /// nutrients: The nutrients in the cell of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::nutrient::NutrientConfig;
+    /// 
+    /// let config = NutrientConfig { growth: 0.5, saturation: 2.0, ..Default::default() };
+    /// 
+    /// assert_eq!(1.0, config.growth_factor(0.0));
+    /// assert_eq!(1.5, config.growth_factor(1.0));
+    /// assert_eq!(2.0, config.growth_factor(5.0));
+    /// ```
+    pub fn growth_factor(&self, nutrients: f32) -> f32 {
+        1.0 + self.growth.max(0.0) * nutrients.clamp(0.0, self.saturation.max(0.0))
+    }
+}
+
+/// The litter of dead plants and the nutrients in the soil of every cell, nutrients stay in the cell they were released in
+#[derive(Clone, Debug, PartialEq)]
+pub struct NutrientField {
+    /// The size of the board
+    size: Size,
+    /// The energy of dead plants which has not decomposed yet in every cell
+    litter: Field,
+    /// The nutrients in every cell
+    nutrients: Field,
+}
+
+impl NutrientField {
+    /// Creates a new field without any litter or nutrients
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn new(size: Size) -> Self {
+        Self { size, litter: Field::filled(size, 0.0), nutrients: Field::filled(size, 0.0) }
+    }
+
+    /// Returns the nutrients in every cell
+    pub fn values(&self) -> &[f32] {
+        &self.nutrients
+    }
+
+    /// Returns the nutrients in every cell for changing it
+    pub(crate) fn values_mut(&mut self) -> &mut [f32] {
+        &mut self.nutrients
+    }
+
+    /// Returns the energy of dead plants which has not decomposed yet in every cell
+    pub fn litter(&self) -> &[f32] {
+        &self.litter
+    }
+
+    /// Returns the total amount of nutrients on the board
+    pub fn total(&self) -> f32 {
+        self.nutrients.sum()
+    }
+
+    /// Leaves the energy of a dead plant as litter in its cell
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    /// energy: The energy left in the plant
+    pub(crate) fn deposit(&mut self, index: usize, energy: u32) {
+        self.litter[index] += energy as f32;
+    }
+
+    /// Lets a step of the litter decompose into nutrients
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings for the nutrients
+    pub fn decompose(&mut self, config: &NutrientConfig) {
+        let decay = config.decay_rate.clamp(0.0, 1.0);
+        let yield_per_energy = config.yield_per_energy.max(0.0);
+
+        for (litter, nutrients) in self.litter.iter_mut().zip(self.nutrients.iter_mut()) {
+            let decomposed = *litter * decay;
+            *litter -= decomposed;
+            *nutrients += decomposed * yield_per_energy;
+        }
+    }
+
+    /// Lets a plant take up its part of the nutrients in its cell
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell of the plant
+    /// config: The settings for the nutrients
+    pub(crate) fn take_up(&mut self, index: usize, config: &NutrientConfig) {
+        self.nutrients[index] *= 1.0 - config.uptake.clamp(0.0, 1.0);
+    }
+
+    /// Puts the litter and nutrients of some cells back to an earlier state
+    pub(crate) fn restore(&mut self, earlier: &Self, cells: &[usize]) {
+        for &index in cells {
+            self.litter[index] = earlier.litter[index];
+            self.nutrients[index] = earlier.nutrients[index];
+        }
+    }
+
+    /// Makes a rectangle of the board the new board, the cells outside the board have no litter or nutrients
+    pub(crate) fn reframe(&mut self, rect: Rect) {
+        self.litter.reframe(rect, || 0.0);
+        self.nutrients.reframe(rect, || 0.0);
+        self.size = Size::new(rect.w, rect.h);
+    }
+}
+
+impl HeapSize for NutrientField {
+    fn heap_bytes(&self) -> usize {
+        self.litter.heap_bytes() + self.nutrients.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nutrient_field_decompose() {
+        let mut field = NutrientField::new(Size::new(2, 1));
+        field.deposit(1, 100);
+        let config = NutrientConfig { decay_rate: 0.5, yield_per_energy: 0.1, ..Default::default() };
+        field.decompose(&config);
+
+        assert_eq!(&[0.0, 50.0], field.litter());
+        assert_eq!(&[0.0, 5.0], field.values());
+
+        field.decompose(&config);
+
+        assert_eq!(&[0.0, 25.0], field.litter());
+        assert_eq!(7.5, field.total());
+    }
+
+    #[test]
+    fn nutrient_field_take_up() {
+        let mut field = NutrientField::new(Size::new(1, 1));
+        field.deposit(0, 10);
+        field.decompose(&NutrientConfig { decay_rate: 1.0, yield_per_energy: 1.0, ..Default::default() });
+        field.take_up(0, &NutrientConfig { uptake: 0.25, ..Default::default() });
+
+        assert_eq!(&[7.5], field.values());
+    }
+
+    #[test]
+    fn nutrient_field_reframe() {
+        let mut field = NutrientField::new(Size::new(2, 2));
+        field.deposit(3, 4);
+        field.reframe(Rect::new(1, 1, 2, 1));
+
+        assert_eq!(&[4.0, 0.0], field.litter());
+        assert_eq!(2, field.values().len());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::board::{reframe_cells, Rect, Size};
+use crate::memory::{vec_bytes, HeapSize};
+use crate::population::Population;
+use crate::view::FieldView;
+
+/// A value of the occupancy map for every cell which can be drawn or exported as a field
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OccupancyLayer {
+    /// The fraction of the counted steps the cell held a plant, between 0 and 1
+    Occupancy,
+    /// The number of plants which died in the cell for every counted step
+    Mortality,
+}
+
+/// Counts for every cell how many steps it has held a plant and how many plants have died in it since counting started.
+/// Where plants persistently die shows the selective landscape better than the plants alive at a single tick
+#[derive(Clone, Debug, PartialEq)]
+pub struct OccupancyMap {
+    /// The size of the board
+    size: Size,
+    /// The number of steps counted
+    ticks: u64,
+    /// The number of counted steps every cell ended with a plant in it
+    occupied: Vec<u64>,
+    /// The number of plants which died in every cell
+    deaths: Vec<u64>,
+}
+
+impl OccupancyMap {
+    /// Creates a new map where nothing has been counted
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn new(size: Size) -> Self {
+        Self { size, ticks: 0, occupied: vec![0; size.len()], deaths: vec![0; size.len()] }
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the number of steps counted
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Returns the number of counted steps every cell ended with a plant in it
+    pub fn occupied(&self) -> FieldView<'_, u64> {
+        FieldView::new(&self.occupied, self.size)
+    }
+
+    /// Returns the number of plants which died in every cell
+    pub fn deaths(&self) -> FieldView<'_, u64> {
+        FieldView::new(&self.deaths, self.size)
+    }
+
+    /// Finds the value of a layer for every cell with the rows in order, all values are 0 before any steps are counted
+    /// 
+    /// # Parameters
+    /// 
+    /// layer: The layer to find
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::occupancy::{OccupancyLayer, OccupancyMap};
+    /// 
+    /// let mut population = Population::new(Size::new(2, 1));
+    /// population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut map = OccupancyMap::new(Size::new(2, 1));
+    /// map.record_step(&population);
+    /// map.record_death(0);
+    /// map.record_step(&Population::new(Size::new(2, 1)));
+    /// 
+    /// assert_eq!(vec![0.5, 0.0], map.layer(OccupancyLayer::Occupancy));
+    /// assert_eq!(vec![0.5, 0.0], map.layer(OccupancyLayer::Mortality));
+    /// ```
+    pub fn layer(&self, layer: OccupancyLayer) -> Vec<f32> {
+        let values = match layer {
+            OccupancyLayer::Occupancy => &self.occupied,
+            OccupancyLayer::Mortality => &self.deaths,
+        };
+        let ticks = self.ticks.max(1) as f64;
+
+        values.iter().map(|&value| (value as f64 / ticks) as f32).collect()
+    }
+
+    /// Counts a step, every cell with a plant in it counts as occupied
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants at the end of the step
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the population does not have the size of the map
+    pub fn record_step(&mut self, population: &Population) {
+        assert_eq!(self.size, population.size(), "The population must have the size of the occupancy map");
+
+        self.ticks += 1;
+        for (occupied, cell) in self.occupied.iter_mut().zip(population.cells()) {
+            *occupied += cell.is_some() as u64;
+        }
+    }
+
+    /// Counts a plant dying in a cell
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    pub fn record_death(&mut self, index: usize) {
+        if let Some(deaths) = self.deaths.get_mut(index) {
+            *deaths += 1;
+        }
+    }
+
+    /// Makes a rectangle of the board the new board, the cells which were not on the board have counted nothing
+    pub(crate) fn reframe(&mut self, rect: Rect) {
+        self.occupied = reframe_cells(std::mem::take(&mut self.occupied), self.size, rect, || 0).0;
+        self.deaths = reframe_cells(std::mem::take(&mut self.deaths), self.size, rect, || 0).0;
+        self.size = Size::new(rect.w, rect.h);
+    }
+}
+
+impl HeapSize for OccupancyMap {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.occupied) + vec_bytes(&self.deaths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::disturbance::{Disturbance, DisturbanceKind};
+    use crate::genome::Genome;
+    use crate::population::Plant;
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    #[test]
+    fn occupancy_record() {
+        let size = Size::new(3, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+        let mut map = OccupancyMap::new(size);
+
+        assert_eq!(vec![0.0; 3], map.layer(OccupancyLayer::Occupancy));
+
+        for _ in 0..4 {
+            map.record_step(&population);
+        }
+        map.record_death(2);
+        map.record_death(7);
+
+        assert_eq!(4, map.ticks());
+        assert_eq!(Some(&4), map.occupied().get(1, 0));
+        assert_eq!(vec![0, 0, 1], map.deaths().iter().copied().collect::<Vec<_>>());
+        assert_eq!(vec![0.0, 1.0, 0.0], map.layer(OccupancyLayer::Occupancy));
+        assert_eq!(vec![0.0, 0.0, 0.25], map.layer(OccupancyLayer::Mortality));
+    }
+
+    #[test]
+    fn occupancy_reframe() {
+        let size = Size::new(2, 2);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+        let mut map = OccupancyMap::new(size);
+        map.record_step(&population);
+        map.reframe(Rect::new(1, 1, 2, 1));
+
+        assert_eq!(Size::new(2, 1), map.size());
+        assert_eq!(vec![1, 0], map.occupied().iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn occupancy_simulation() {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+        simulation.track_occupancy(true);
+        simulation.schedule_disturbance(30, Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 6, 3)));
+        for _ in 0..40 {
+            simulation.step();
+        }
+        let map = simulation.occupancy().unwrap();
+        let deaths: u64 = map.deaths().iter().sum();
+        let dead = simulation.phylogeny().iter().filter(|record| record.death.is_some()).count() as u64;
+
+        assert_eq!(40, map.ticks());
+        assert!(deaths > 0);
+        assert_eq!(dead, deaths);
+        assert_eq!(Some(&40), map.occupied().get(3, 3));
+        assert_eq!(0, map.deaths().iter().skip(18).sum::<u64>());
+    }
+}
@@ -0,0 +1,480 @@
+use rand::{Rng, RngCore};
+
+use crate::genome::{Genome, MutationConfig};
+use crate::population::{Plant, PlantId};
+use crate::simulation::{self, SimulationConfig};
+use crate::spectrum::Spectrum;
+
+/// What an organism can sense about the cell it lives in during a step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surroundings {
+    /// The light energy reaching the cell
+    pub light: u32,
+    /// The temperature of the cell
+    pub temperature: f32,
+    /// The water in the cell
+    pub water: f32,
+    /// The nutrients in the soil of the cell
+    pub nutrients: f32,
+    /// The toxin released into the cell by the neighbouring plants
+    pub toxin: f32,
+    /// The water the roots of the organism took up this step
+    pub root_water: f32,
+    /// The light reaching the cell split into channels, None if the light is a single channel
+    pub spectrum: Option<Spectrum>,
+}
+
+/// The life cycle of anything living on the board. Every step an organism perceives its surroundings, acts on what it found,
+/// dies if it cannot pay its upkeep and finally reproduces if it is fertile. The step of a simulation runs these phases for
+/// its plants through this trait and the resource and reproduction models, while the subsystems which only exist for plants,
+/// such as the neural allocations, finding mates, clutches and the phenotype, use the plants directly
+pub trait Organism: Clone {
+    /// Returns the unique id of the organism
+    fn id(&self) -> PlantId;
+
+    /// Gives the organism its unique id, this is done by the population when the organism is placed in it
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The new id of the organism
+    fn set_id(&mut self, id: PlantId);
+
+    /// Returns the energy stored in the organism
+    fn energy(&self) -> u32;
+
+    /// Returns the genetic material of the organism
+    fn genome(&self) -> &Genome;
+
+    /// Finds the energy the organism can gain from its surroundings this step
+    /// 
+    /// # Parameters
+    /// 
+    /// surroundings: What the organism can sense about its cell
+    /// config: The settings of the simulation
+    fn perceive(&self, surroundings: &Surroundings, config: &SimulationConfig) -> u32;
+
+    /// Gains the energy found while perceiving
+    /// 
+    /// # Parameters
+    /// 
+    /// intake: The energy found while perceiving
+    fn act(&mut self, intake: u32);
+
+    /// Pays the upkeep of a step and grows a step older, returns true if the organism could not pay and dies
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the simulation
+    fn die(&mut self, config: &SimulationConfig) -> bool;
+
+    /// Returns true if the organism has enough energy to reproduce
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the simulation
+    fn fertile(&self, config: &SimulationConfig) -> bool;
+
+    /// Produces an offspring, possibly combined with a mate, and pays for it.
+    /// Returns the offspring and the number of genes which were mutated, None if the organism cannot pay for it,
+    /// in which case nothing is paid. This may be asked of organisms which are not fertile by a reproduction model
+    /// 
+    /// # Parameters
+    /// 
+    /// mate: The organism to combine genomes with, None to produce a mutated clone
+    /// config: The settings of the simulation
+    /// mutation: The settings for mutating the genome of the offspring
+    /// rng: The random number generator to use
+    fn reproduce<R: Rng>(&mut self, mate: Option<&Self>, config: &SimulationConfig, mutation: &MutationConfig, rng: &mut R) -> Option<(Self, usize)>;
+
+    /// Returns the largest distance in cells in each direction the offspring can land from the organism,
+    /// by default the offspring land in a neighbouring cell
+    fn dispersal(&self) -> usize {
+        1
+    }
+}
+
+/// The resources of a cell which organisms can use up, handed to a resource model after the organism in the cell has fed
+#[derive(Debug, PartialEq)]
+pub struct CellStock<'a> {
+    /// The water in the cell
+    pub water: &'a mut f32,
+    /// The nutrients in the soil of the cell
+    pub nutrients: &'a mut f32,
+}
+
+/// Decides what the organisms gain from the cells they live in, implement this to feed the plants on something else
+/// than the light, for example on the water of their cell. The simulation finds the surroundings
+/// of every cell from the fields of the board, the model turns them into energy on any thread and then uses up
+/// the resources of the cells in the order of the cells. A simulation only hosts plants and only runs models for plants,
+/// a model for another organism can be called directly but no simulation steps it
+pub trait ResourceModel<O: Organism = Plant>: std::fmt::Debug + Send + Sync {
+    /// Finds the energy the organism in a cell gains this step, by default the energy the organism perceives
+    /// 
+    /// # Parameters
+    /// 
+    /// organism: The organism in the cell
+    /// surroundings: What the organism can sense about its cell
+    /// config: The settings of the simulation
+    fn intake(&self, organism: &O, surroundings: &Surroundings, config: &SimulationConfig) -> u32 {
+        organism.perceive(surroundings, config)
+    }
+
+    /// Uses up the resources of a cell after the organism in it gained energy, by default nothing is used up
+    /// as the light reaches the cell again in the next step
+    /// 
+    /// # Parameters
+    /// 
+    /// intake: The energy the organism gained from the cell
+    /// stock: The resources of the cell
+    fn deplete(&self, intake: u32, stock: CellStock<'_>) {
+        let _ = (intake, stock);
+    }
+}
+
+/// The resource model used unless another is chosen, the plants live off the light and nothing is used up
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightResources;
+
+impl<O: Organism> ResourceModel<O> for LightResources {}
+
+/// Decides when the organisms reproduce and where their offspring land, implement this to change how the plants spread,
+/// for example sending the seeds further than the dispersal distance of the plants.
+/// A simulation only hosts plants and only runs models for plants, a model for another organism can be called directly
+/// but no simulation steps it
+pub trait ReproductionModel<O: Organism = Plant>: std::fmt::Debug + Send + Sync {
+    /// Returns true if an organism reproduces this step, by default when the organism is fertile
+    /// 
+    /// # Parameters
+    /// 
+    /// organism: The organism
+    /// config: The settings of the simulation
+    fn fertile(&self, organism: &O, config: &SimulationConfig) -> bool {
+        organism.fertile(config)
+    }
+
+    /// Picks where an offspring lands relative to its parent, it must not land in the cell of the parent.
+    /// By default it lands in a random cell at most the dispersal distance of the parent away in each direction
+    /// 
+    /// # Parameters
+    /// 
+    /// parent: The organism which produced the offspring
+    /// rng: The random number generator to use
+    fn disperse(&self, parent: &O, rng: &mut dyn RngCore) -> (isize, isize) {
+        simulation::disperse(parent.dispersal(), rng)
+    }
+}
+
+/// The reproduction model used unless another is chosen, fertile plants reproduce and the seeds land within their dispersal distance
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SeedDispersal;
+
+impl<O: Organism> ReproductionModel<O> for SeedDispersal {}
+
+impl Organism for Plant {
+    fn id(&self) -> PlantId {
+        self.id()
+    }
+
+    fn set_id(&mut self, id: PlantId) {
+        self.id = id;
+    }
+
+    fn energy(&self) -> u32 {
+        self.energy
+    }
+
+    fn genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    /// Plants collect the light in their cell scaled by their photosynthesis, by how well they tolerate the temperature,
+    /// by the nutrients in the soil, by the toxin of their neighbours and by how well their pigments match the channels of the light,
+    /// on top of this they gain energy from the water taken up by their roots
+    fn perceive(&self, surroundings: &Surroundings, config: &SimulationConfig) -> u32 {
+        let thermal = config.thermal.map_or(1.0, |thermal| thermal.growth_factor(&self.genome, surroundings.temperature));
+        let fertility = config.nutrients.map_or(1.0, |nutrients| nutrients.growth_factor(surroundings.nutrients));
+        let poisoning = config.allelopathy.map_or(1.0, |allelopathy| allelopathy.growth_factor(surroundings.toxin));
+        let pigments = surroundings.spectrum.map_or(1.0, |spectrum| spectrum.light_factor(&self.genome));
+        let factor = self.phenotype.photosynthesis * thermal * fertility * poisoning * pigments * (1.0 + self.growth);
+        let water = config.roots.map_or(0, |roots| (surroundings.root_water * roots.energy_per_water.max(0.0)) as u32);
+
+        let light = if factor == 1.0 {
+            surroundings.light
+        } else {
+            (surroundings.light as f32 * factor) as u32
+        };
+
+        light.saturating_add(water)
+    }
+
+    fn act(&mut self, intake: u32) {
+        self.energy = self.energy.saturating_add(intake);
+        self.gathered = self.gathered.saturating_add(intake as u64);
+    }
+
+    /// Plants pay extra upkeep once they have outlived the lifespan of their phenotype, while the pathogen costs them energy
+    /// and for growing tall, growing roots, flowering and releasing toxin
+    fn die(&mut self, config: &SimulationConfig) -> bool {
+        let respiration = config.aging.map_or(0, |aging| aging.respiration_for(self.phenotype.lifespan, self.age));
+        let disease = config.pathogen.map_or(0, |pathogen| pathogen.upkeep(self));
+        let height = config.canopy.map_or(0, |canopy| canopy.upkeep(self));
+        let roots = config.roots.map_or(0, |roots| roots.upkeep(self));
+        let flowering = config.pollination.map_or(0, |pollination| pollination.upkeep(self));
+        let toxin = config.allelopathy.map_or(0, |allelopathy| allelopathy.upkeep(self));
+        let upkeep = config.upkeep.saturating_add(respiration).saturating_add(disease).saturating_add(height).saturating_add(roots)
+            .saturating_add(flowering).saturating_add(toxin);
+
+        if self.energy < upkeep {
+            return true;
+        }
+
+        self.energy -= upkeep;
+        self.age += 1;
+
+        false
+    }
+
+    /// Plants are fertile once they have the seed cost plus the part of the largest threshold given by their phenotype
+    fn fertile(&self, config: &SimulationConfig) -> bool {
+        self.energy >= config.seed_cost.saturating_add((self.phenotype.reproduction_threshold * config.max_threshold as f32) as u32)
+    }
+
+    /// Plants give a part of their remaining energy after the seed cost to the seed, set by their phenotype.
+    /// Plants with less energy than the seed cost produce nothing
+    fn reproduce<R: Rng>(&mut self, mate: Option<&Self>, config: &SimulationConfig, mutation: &MutationConfig, rng: &mut R) -> Option<(Self, usize)> {
+        let remaining = self.energy.checked_sub(config.seed_cost)?;
+        let provision = ((remaining as f32 * self.phenotype.seed_size) as u32).min(remaining);
+        self.energy = remaining - provision;
+
+        Some(self.sibling(mate, provision, config, mutation, rng))
+    }
+
+    fn dispersal(&self) -> usize {
+        self.phenotype.dispersal.max(1)
+    }
+}
+
+impl Plant {
+    /// Produces another seed of the same clutch as a seed from reproduce with its own crossover and mutations,
+    /// the energy of the seed has already been paid for. Returns the seed and the number of genes which were mutated
+    /// 
+    /// # Parameters
+    /// 
+    /// mate: The plant to combine genomes with, None to produce a mutated clone
+    /// energy: The energy given to the seed
+    /// config: The settings of the simulation
+    /// mutation: The settings for mutating the genome of the seed
+    /// rng: The random number generator to use
+    pub(crate) fn sibling<R: Rng>(&self, mate: Option<&Self>, energy: u32, config: &SimulationConfig, mutation: &MutationConfig, rng: &mut R) -> (Self, usize) {
+        let mut genome = match mate {
+            Some(mate) => self.genome.crossover(&mate.genome, config.reproduction.crossover, rng),
+            None => self.genome.clone(),
+        };
+        let mutations = genome.mutate(mutation, rng);
+
+        (Plant::seed(self.id(), mate.map(|mate| mate.id()), energy, genome), mutations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aging::AgingConfig;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn plant(energy: u32) -> Plant {
+        Plant::new(energy, Genome::new(&[0.5, 0.5]).unwrap())
+    }
+
+    /// An organism grazing on the water of its cell, giving birth to a young with half its energy
+    #[derive(Clone, Debug, PartialEq)]
+    struct Grazer {
+        id: PlantId,
+        energy: u32,
+        genome: Genome,
+    }
+
+    impl Organism for Grazer {
+        fn id(&self) -> PlantId {
+            self.id
+        }
+
+        fn set_id(&mut self, id: PlantId) {
+            self.id = id;
+        }
+
+        fn energy(&self) -> u32 {
+            self.energy
+        }
+
+        fn genome(&self) -> &Genome {
+            &self.genome
+        }
+
+        fn perceive(&self, surroundings: &Surroundings, _: &SimulationConfig) -> u32 {
+            (surroundings.water * 10.0) as u32
+        }
+
+        fn act(&mut self, intake: u32) {
+            self.energy += intake;
+        }
+
+        fn die(&mut self, config: &SimulationConfig) -> bool {
+            let dies = self.energy < config.upkeep;
+            self.energy = self.energy.saturating_sub(config.upkeep);
+
+            dies
+        }
+
+        fn fertile(&self, config: &SimulationConfig) -> bool {
+            self.energy >= 2 * config.seed_cost
+        }
+
+        fn reproduce<R: Rng>(&mut self, _: Option<&Self>, config: &SimulationConfig, _: &MutationConfig, _: &mut R) -> Option<(Self, usize)> {
+            self.energy = self.energy.checked_sub(config.seed_cost)?;
+            let young = self.energy / 2;
+            self.energy -= young;
+
+            Some((Grazer { id: self.id, energy: young, genome: self.genome.clone() }, 0))
+        }
+
+        fn dispersal(&self) -> usize {
+            3
+        }
+    }
+
+    /// Grazers only find forage in cells with plenty of water
+    #[derive(Debug)]
+    struct Wet;
+
+    impl ResourceModel<Grazer> for Wet {
+        fn intake(&self, organism: &Grazer, surroundings: &Surroundings, config: &SimulationConfig) -> u32 {
+            if surroundings.water < 1.0 { 0 } else { organism.perceive(surroundings, config) }
+        }
+
+        fn deplete(&self, intake: u32, stock: CellStock<'_>) {
+            *stock.water -= intake as f32 / 10.0;
+        }
+    }
+
+    fn grazer(energy: u32) -> Grazer {
+        Grazer { id: PlantId(0), energy, genome: Genome::new(&[0.5, 0.5]).unwrap() }
+    }
+
+    #[test]
+    fn plant_die_upkeep() {
+        let config = SimulationConfig { upkeep: 10, ..Default::default() };
+        let mut alive = plant(15);
+        let mut dead = plant(5);
+
+        assert!(!alive.die(&config));
+        assert_eq!(5, alive.energy);
+        assert_eq!(1, alive.age);
+        assert!(dead.die(&config));
+    }
+
+    #[test]
+    fn plant_die_senescence() {
+        let config = SimulationConfig { upkeep: 10, aging: Some(AgingConfig { max_lifespan: 10, respiration: 5.0 }), ..Default::default() };
+        let mut plant = Plant::new(100, Genome::new(&[0.5, 0.5, 0.5, 0.5, 0.5]).unwrap());
+        plant.age = 7;
+
+        assert!(!plant.die(&config));
+        assert_eq!(80, plant.energy);
+        assert!(!plant.die(&config));
+        assert_eq!(55, plant.energy);
+        assert_eq!(9, plant.age);
+    }
+
+    #[test]
+    fn plant_perceive_act() {
+        let mut plant = plant(u32::MAX - 5);
+        let surroundings = Surroundings { light: 100, temperature: 10.0, water: 0.0, nutrients: 0.0, toxin: 0.0, root_water: 0.0, spectrum: None };
+        let intake = plant.perceive(&surroundings, &SimulationConfig::default());
+        plant.act(intake);
+
+        assert_eq!(100, intake);
+        assert_eq!(u32::MAX, plant.energy);
+    }
+
+    #[test]
+    fn plant_perceive_photosynthesis() {
+        let mut plant = plant(0);
+        plant.phenotype.photosynthesis = 0.5;
+        let surroundings = Surroundings { light: 100, temperature: 10.0, water: 0.0, nutrients: 0.0, toxin: 0.0, root_water: 0.0, spectrum: None };
+
+        assert_eq!(50, plant.perceive(&surroundings, &SimulationConfig::default()));
+    }
+
+    #[test]
+    fn plant_phenotype_reproduction() {
+        let config = SimulationConfig { seed_cost: 10, max_threshold: 100, ..Default::default() };
+        let mut plant = plant(40);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert!(!plant.fertile(&config));
+
+        plant.phenotype.reproduction_threshold = 0.2;
+        plant.phenotype.seed_size = 0.1;
+
+        assert!(plant.fertile(&config));
+        assert_eq!(3, plant.reproduce(None, &config, &MutationConfig::new(0.0, 0.0), &mut rng).unwrap().0.energy);
+        assert_eq!(1, plant.dispersal());
+    }
+
+    #[test]
+    fn plant_reproduce_cost() {
+        let config = SimulationConfig { seed_cost: 50, max_threshold: 100, ..Default::default() };
+        let mut plant = plant(150);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert!(plant.fertile(&config));
+
+        let (seed, mutations) = plant.reproduce(None, &config, &MutationConfig::new(0.0, 0.0), &mut rng).unwrap();
+
+        assert_eq!(0, mutations);
+        assert_eq!(50, seed.energy);
+        assert_eq!(50, plant.energy);
+        assert_eq!(Some(plant.id()), seed.parent());
+        assert!(!plant.fertile(&config));
+    }
+
+    #[test]
+    fn plant_reproduce_unpaid() {
+        let config = SimulationConfig { seed_cost: 50, max_threshold: 100, ..Default::default() };
+        let mut plant = plant(30);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert!(plant.reproduce(None, &config, &MutationConfig::new(0.0, 0.0), &mut rng).is_none());
+        assert_eq!(30, plant.energy);
+    }
+
+    #[test]
+    fn models_for_other_organisms() {
+        let config = SimulationConfig { seed_cost: 10, ..Default::default() };
+        let surroundings = |water| Surroundings { light: 100, temperature: 10.0, water, nutrients: 0.0, toxin: 0.0, root_water: 0.0, spectrum: None };
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        assert_eq!(0, Wet.intake(&grazer(0), &surroundings(0.5), &config));
+        assert_eq!(20, Wet.intake(&grazer(0), &surroundings(2.0), &config));
+        assert_eq!(20, LightResources.intake(&grazer(0), &surroundings(2.0), &config));
+        assert!(!SeedDispersal.fertile(&grazer(15), &config));
+        assert!(SeedDispersal.fertile(&grazer(20), &config));
+
+        let (dx, dy) = SeedDispersal.disperse(&grazer(20), &mut rng);
+        assert!((dx, dy) != (0, 0) && dx.abs() <= 3 && dy.abs() <= 3);
+        assert!(grazer(5).reproduce(None, &config, &MutationConfig::new(0.0, 0.0), &mut rng).is_none());
+    }
+
+    #[test]
+    fn light_resources_default() {
+        let plant = plant(0);
+        let surroundings = Surroundings { light: 100, temperature: 10.0, water: 2.0, nutrients: 3.0, toxin: 0.0, root_water: 0.0, spectrum: None };
+        let (mut water, mut nutrients) = (2.0, 3.0);
+        ResourceModel::<Plant>::deplete(&LightResources, 100, CellStock { water: &mut water, nutrients: &mut nutrients });
+
+        assert_eq!(100, LightResources.intake(&plant, &surroundings, &SimulationConfig::default()));
+        assert_eq!((2.0, 3.0), (water, nutrients));
+    }
+}
@@ -0,0 +1,207 @@
+use rand::Rng;
+
+use crate::board::Size;
+use crate::genome::{self, Genome};
+use crate::population::{Plant, Population};
+
+/// The settings for a pathogen spreading between neighbouring plants. Infected plants pay extra upkeep until they recover,
+/// and resistance is inherited through the resistance gene but costs upkeep of its own, so resistant plants only
+/// win while the pathogen is common
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathogenConfig {
+    /// The chance every step that an infected neighbour infects a plant without any resistance
+    pub transmission: f32,
+    /// The chance every step that a healthy plant is infected without any infected neighbours
+    pub outbreak: f32,
+    /// The number of steps a plant stays infected
+    pub duration: u32,
+    /// The extra energy an infected plant pays every step
+    pub damage: u32,
+    /// The extra energy a plant with a resistance of 1 pays every step, this scales with the resistance
+    pub resistance_cost: f32,
+}
+
+impl Default for PathogenConfig {
+    fn default() -> Self {
+        Self {
+            transmission: 0.2,
+            outbreak: 0.0005,
+            duration: 20,
+            damage: 5,
+            resistance_cost: 2.0,
+        }
+    }
+}
+
+impl PathogenConfig {
+    /// Finds how well a plant resists the pathogen from 0 to 1, plants without a resistance gene have no resistance
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    pub fn resistance(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_RESISTANCE).unwrap_or(0.0)
+    }
+
+    /// Finds the chance that a healthy plant is infected in a step
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// infected_neighbours: The number of neighbouring plants which are infected
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, pathogen::PathogenConfig};
+    /// 
+    /// let config = PathogenConfig { transmission: 0.5, outbreak: 0.0, ..Default::default() };
+    /// let weak = Genome::new(&[0.0, 0.0]).unwrap();
+    /// let resistant = Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap();
+    /// 
+    /// assert_eq!(0.75, config.infection_chance(&weak, 2));
+    /// assert_eq!(0.0, config.infection_chance(&resistant, 2));
+    /// ```
+    pub fn infection_chance(&self, genome: &Genome, infected_neighbours: usize) -> f32 {
+        let transmission = self.transmission.clamp(0.0, 1.0) * (1.0 - self.resistance(genome));
+        let escape = (1.0 - transmission).powi(infected_neighbours as i32) * (1.0 - self.outbreak.clamp(0.0, 1.0));
+
+        1.0 - escape
+    }
+
+    /// Finds the extra upkeep a plant pays in a step for being infected and for its resistance
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant paying the upkeep
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, pathogen::PathogenConfig, population::Plant};
+    /// 
+    /// let config = PathogenConfig { damage: 5, resistance_cost: 4.0, ..Default::default() };
+    /// let mut plant = Plant::new(100, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap());
+    /// 
+    /// assert_eq!(2, config.upkeep(&plant));
+    /// 
+    /// plant.infection = 3;
+    /// 
+    /// assert_eq!(7, config.upkeep(&plant));
+    /// ```
+    pub fn upkeep(&self, plant: &Plant) -> u32 {
+        let damage = if plant.infection > 0 { self.damage } else { 0 };
+        let resistance = (self.resistance(&plant.genome) * self.resistance_cost.max(0.0)) as u32;
+
+        damage.saturating_add(resistance)
+    }
+
+    /// Lets the infected plants recover a step and spreads the pathogen to their neighbours,
+    /// only plants which were infected at the start of the step spread it. Returns the number of new infections
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants on the board
+    /// rng: The random number generator used to pick the infected plants
+    pub(crate) fn spread<R: Rng>(&self, population: &mut Population, rng: &mut R) -> usize {
+        let size = population.size();
+        let infected: Vec<bool> = population.cells().map(|cell| cell.is_some_and(|plant| plant.infection > 0)).collect();
+        let mut infections = 0;
+
+        for index in 0..size.len() {
+            let plant = match population.plant_mut(index) {
+                Some(plant) => plant,
+                None => continue,
+            };
+
+            if plant.infection > 0 {
+                plant.infection -= 1;
+                continue;
+            }
+
+            let neighbours = neighbours(size, index).filter(|&neighbour| infected[neighbour]).count();
+            if rng.gen::<f32>() < self.infection_chance(&plant.genome, neighbours) {
+                plant.infection = self.duration;
+                infections += 1;
+            }
+        }
+
+        infections
+    }
+}
+
+/// Finds the indices of the cells next to a cell including diagonals
+fn neighbours(size: Size, index: usize) -> impl Iterator<Item = usize> {
+    let coord = size.coord_of(index).unwrap();
+
+    (-1..=1isize)
+        .flat_map(|dy| (-1..=1isize).map(move |dx| (dx, dy)))
+        .filter(|&offset| offset != (0, 0))
+        .filter_map(move |(dx, dy)| size.index_of(coord.offset(dx, dy)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+    use crate::board::Coord;
+
+    fn population(resistances: &[f32]) -> Population {
+        let mut population = Population::new(Size::new(resistances.len(), 1));
+        for (x, &resistance) in resistances.iter().enumerate() {
+            population.insert(Coord::new(x, 0), Plant::new(100, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, resistance]).unwrap()));
+        }
+
+        population
+    }
+
+    fn infections(population: &Population) -> Vec<u32> {
+        population.iter().map(|(_, plant)| plant.infection).collect()
+    }
+
+    #[test]
+    fn pathogen_config_infection_chance_outbreak() {
+        let config = PathogenConfig { transmission: 0.0, outbreak: 0.25, ..Default::default() };
+
+        assert_eq!(0.25, config.infection_chance(&Genome::new(&[0.0, 0.0]).unwrap(), 3));
+    }
+
+    #[test]
+    fn pathogen_config_spread() {
+        let config = PathogenConfig { transmission: 1.0, outbreak: 0.0, duration: 2, ..Default::default() };
+        let mut population = population(&[0.0, 0.0, 1.0, 0.0]);
+        population.plant_mut(0).unwrap().infection = 2;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+
+        assert_eq!(1, config.spread(&mut population, &mut rng));
+        assert_eq!(vec![1, 2, 0, 0], infections(&population));
+
+        // The resistant plant stops the pathogen from reaching the last plant
+        assert_eq!(0, config.spread(&mut population, &mut rng));
+        assert_eq!(vec![0, 1, 0, 0], infections(&population));
+    }
+
+    #[test]
+    fn pathogen_config_spread_reinfect() {
+        // A plant which recovers is infected again by a neighbour which is still infected
+        let config = PathogenConfig { transmission: 1.0, outbreak: 0.0, duration: 3, ..Default::default() };
+        let mut population = population(&[0.0, 0.0]);
+        population.plant_mut(0).unwrap().infection = 1;
+        population.plant_mut(1).unwrap().infection = 3;
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        config.spread(&mut population, &mut rng);
+        config.spread(&mut population, &mut rng);
+
+        assert_eq!(vec![3, 1], infections(&population));
+    }
+
+    #[test]
+    fn neighbours_corner() {
+        let mut found: Vec<_> = neighbours(Size::new(3, 3), 0).collect();
+        found.sort();
+
+        assert_eq!(vec![1, 3, 4], found);
+    }
+}
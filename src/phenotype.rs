@@ -0,0 +1,89 @@
+use crate::genome::{self, Genome};
+
+/// The traits of a plant which the simulation acts on, developed from its genome.
+/// Mutation and crossover only ever change the genome, so how the genes turn into traits can be swapped
+/// without touching the mechanics of the simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Phenotype {
+    /// The fraction of the light reaching the cell which the plant turns into energy
+    pub photosynthesis: f32,
+    /// The fraction of the energy left after paying the seed cost which is given to a seed
+    pub seed_size: f32,
+    /// The fraction of the largest threshold the plant must store on top of the seed cost before it reproduces
+    pub reproduction_threshold: f32,
+    /// The largest distance in cells in each direction a seed can land from the plant, at least 1
+    pub dispersal: usize,
+    /// The fraction of the largest lifespan the plant lives before it starts to age, None if it never ages
+    pub lifespan: Option<f32>,
+}
+
+/// Turns a genome into the traits of a plant, implement this to change how genes are expressed,
+/// for example through a neural network or a grammar growing the plant
+pub trait Development: std::fmt::Debug + Send + Sync {
+    /// Develops the traits of a plant from its genome
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    fn develop(&self, genome: &Genome) -> Phenotype;
+}
+
+/// The development used unless another is chosen, every trait is read directly from its gene.
+/// All plants photosynthesize the full light and disperse their seeds to the neighbouring cells
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DirectDevelopment;
+
+impl Development for DirectDevelopment {
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phenotype::{Development, DirectDevelopment}};
+    /// 
+    /// let phenotype = DirectDevelopment.develop(&Genome::new(&[0.25, 0.75]).unwrap());
+    /// 
+    /// assert_eq!(0.25, phenotype.reproduction_threshold);
+    /// assert_eq!(0.75, phenotype.seed_size);
+    /// assert_eq!(None, phenotype.lifespan);
+    /// ```
+    fn develop(&self, genome: &Genome) -> Phenotype {
+        Phenotype {
+            photosynthesis: 1.0,
+            seed_size: genome.gene(genome::GENE_SEED_ENERGY),
+            reproduction_threshold: genome.gene(genome::GENE_REPRODUCTION_THRESHOLD),
+            dispersal: 1,
+            lifespan: genome.get(genome::GENE_LIFESPAN),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A development where the first gene sets how far seeds travel
+    #[derive(Debug)]
+    struct Spreading;
+
+    impl Development for Spreading {
+        fn develop(&self, genome: &Genome) -> Phenotype {
+            Phenotype { dispersal: 1 + (genome.gene(0) * 4.0) as usize, ..DirectDevelopment.develop(genome) }
+        }
+    }
+
+    #[test]
+    fn direct_development_lifespan() {
+        let phenotype = DirectDevelopment.develop(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.5]).unwrap());
+
+        assert_eq!(Some(0.5), phenotype.lifespan);
+        assert_eq!(1.0, phenotype.photosynthesis);
+        assert_eq!(1, phenotype.dispersal);
+    }
+
+    #[test]
+    fn development_trait_object() {
+        let developments: [&dyn Development; 2] = [&DirectDevelopment, &Spreading];
+        let genome = Genome::new(&[1.0, 0.5]).unwrap();
+
+        assert_eq!(vec![1, 5], developments.iter().map(|development| development.develop(&genome).dispersal).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,618 @@
+use std::collections::BTreeMap;
+
+use crate::genome::Genome;
+use crate::memory::{btree_map_bytes, vec_bytes, HeapSize};
+use crate::population::PlantId;
+
+/// A single plant in the lineage tree
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lineage {
+    /// The id of the plant
+    pub id: PlantId,
+    /// The id of the parent, None if the plant is a founder or the parent is not in the tree
+    pub parent: Option<PlantId>,
+    /// The id of the plant which pollinated the parent, None if the plant was produced asexually
+    pub mate: Option<PlantId>,
+    /// The tick at which the plant germinated
+    pub birth: u64,
+    /// The tick at which the plant died, None if it is still alive
+    pub death: Option<u64>,
+    /// The genome the plant was born with
+    pub genome: Genome,
+    /// The ids of all the children of the plant which are in the tree
+    pub children: Vec<PlantId>,
+}
+
+/// The lineage tree of all plants which have lived in a simulation
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Phylogeny {
+    /// All the plants in the tree
+    nodes: BTreeMap<PlantId, Lineage>,
+}
+
+impl Phylogeny {
+    /// Creates a new empty lineage tree
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::phylogeny::Phylogeny;
+    /// 
+    /// let phylogeny = Phylogeny::new();
+    /// 
+    /// assert_eq!(0, phylogeny.len());
+    /// ```
+    pub fn new() -> Self {
+        Self { nodes: BTreeMap::new() }
+    }
+
+    /// Returns the number of plants in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns true if there are no plants in the tree
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Gets the lineage of a plant, returns None if the plant is not in the tree
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, Genome::new(&[0.5, 0.5]).unwrap());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, Genome::new(&[0.5, 0.5]).unwrap());
+    /// 
+    /// assert_eq!(Some(PlantId(0)), phylogeny.get(PlantId(1)).unwrap().parent);
+    /// assert!(phylogeny.get(PlantId(2)).is_none());
+    /// ```
+    pub fn get(&self, id: PlantId) -> Option<&Lineage> {
+        self.nodes.get(&id)
+    }
+
+    /// Iterates over all plants in the tree ordered by id
+    pub fn iter(&self) -> impl Iterator<Item = &Lineage> {
+        self.nodes.values()
+    }
+
+    /// Iterates over all plants in the tree without a parent in the tree ordered by id
+    pub fn roots(&self) -> impl Iterator<Item = &Lineage> {
+        self.nodes.values().filter(|node| node.parent.is_none())
+    }
+
+    /// Finds all ancestors of a plant in the tree starting with the parent and ending with the founder
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(1)), None, 9, genome);
+    /// 
+    /// assert_eq!(vec![PlantId(1), PlantId(0)], phylogeny.ancestors(PlantId(2)));
+    /// ```
+    pub fn ancestors(&self, id: PlantId) -> Vec<PlantId> {
+        let mut ancestors = Vec::new();
+        let mut current = self.nodes.get(&id).and_then(|node| node.parent);
+
+        while let Some(parent) = current {
+            ancestors.push(parent);
+            current = self.nodes.get(&parent).and_then(|node| node.parent);
+        }
+
+        ancestors
+    }
+
+    /// Counts the descendants of a plant in the tree which germinated at or after a tick,
+    /// a tick of 0 counts all descendants. Plants removed by pruning are not counted
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// tick: The first tick to count the births of
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(1)), None, 9, genome.clone());
+    /// phylogeny.record_birth(PlantId(3), Some(PlantId(0)), None, 12, genome);
+    /// 
+    /// assert_eq!(3, phylogeny.descendants_since(PlantId(0), 0));
+    /// assert_eq!(2, phylogeny.descendants_since(PlantId(0), 9));
+    /// assert_eq!(0, phylogeny.descendants_since(PlantId(2), 0));
+    /// ```
+    pub fn descendants_since(&self, id: PlantId, tick: u64) -> usize {
+        let mut count = 0;
+        let mut stack: Vec<PlantId> = self.nodes.get(&id).map_or(Vec::new(), |node| node.children.clone());
+
+        // Children may be born before the tick while their own children are born after it, so the whole tree is visited
+        while let Some(child) = stack.pop() {
+            if let Some(node) = self.nodes.get(&child) {
+                if node.birth >= tick {
+                    count += 1;
+                }
+                stack.extend_from_slice(&node.children);
+            }
+        }
+
+        count
+    }
+
+    /// Finds the descendants of a plant in the tree which are still alive ordered by id
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(1)), None, 9, genome);
+    /// phylogeny.record_death(PlantId(1), 12);
+    /// 
+    /// assert_eq!(vec![PlantId(2)], phylogeny.living_descendants(PlantId(0)));
+    /// ```
+    pub fn living_descendants(&self, id: PlantId) -> Vec<PlantId> {
+        let mut living = Vec::new();
+        let mut stack: Vec<PlantId> = self.nodes.get(&id).map_or(Vec::new(), |node| node.children.clone());
+
+        while let Some(child) = stack.pop() {
+            if let Some(node) = self.nodes.get(&child) {
+                if node.death.is_none() {
+                    living.push(child);
+                }
+                stack.extend_from_slice(&node.children);
+            }
+        }
+        living.sort();
+
+        living
+    }
+
+    /// Finds every plant in the tree sharing an ancestor with a plant within a number of generations, together with
+    /// the number of generations between the nearest shared ancestor and the further of the two plants. The plant
+    /// itself is at 0 generations, its parent, children and siblings at 1 and its grandparent and the children of its siblings at 2.
+    /// Only the subtrees of the ancestors within the generations are visited, down to the generations,
+    /// so the query is fast for small generations no matter how large the tree is. Nothing is found for a plant
+    /// which is not in the tree, such as one removed by pruning
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// generations: The largest number of generations to the shared ancestor
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 6, genome.clone());
+    /// phylogeny.record_birth(PlantId(3), Some(PlantId(2)), None, 9, genome);
+    /// let relatives = phylogeny.relatives(PlantId(1), 1);
+    /// 
+    /// assert_eq!(Some(&0), relatives.get(&PlantId(1)));
+    /// assert_eq!(Some(&1), relatives.get(&PlantId(2)));
+    /// assert_eq!(None, relatives.get(&PlantId(3)));
+    /// assert_eq!(Some(&2), phylogeny.relatives(PlantId(1), 2).get(&PlantId(3)));
+    /// ```
+    pub fn relatives(&self, id: PlantId, generations: usize) -> BTreeMap<PlantId, usize> {
+        let mut relatives = BTreeMap::new();
+        if !self.nodes.contains_key(&id) {
+            return relatives;
+        }
+
+        // The nearer ancestors are visited first, so the subtree of the previous ancestor is skipped as it is done
+        let mut previous = None;
+        let mut ancestor = Some(id);
+        for up in 0..=generations {
+            let Some(current) = ancestor else {
+                break;
+            };
+
+            let mut stack = vec![(current, 0)];
+            while let Some((node, down)) = stack.pop() {
+                relatives.insert(node, up.max(down));
+                if down < generations {
+                    let children = self.nodes.get(&node).map_or(&[][..], |node| &node.children);
+                    stack.extend(children.iter().filter(|&&child| Some(child) != previous).map(|&child| (child, down + 1)));
+                }
+            }
+
+            previous = Some(current);
+            ancestor = self.nodes.get(&current).and_then(|node| node.parent);
+        }
+
+        relatives
+    }
+
+    /// Adds a newly germinated plant to the tree, the parent is ignored if it is not in the tree
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// parent: The id of the parent of the plant
+    /// mate: The id of the plant which pollinated the parent
+    /// tick: The tick at which the plant germinated
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, Genome::new(&[0.5, 0.5]).unwrap());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, Genome::new(&[0.5, 0.5]).unwrap());
+    /// 
+    /// assert_eq!(vec![PlantId(1)], phylogeny.get(PlantId(0)).unwrap().children);
+    /// ```
+    pub fn record_birth(&mut self, id: PlantId, parent: Option<PlantId>, mate: Option<PlantId>, tick: u64, genome: Genome) {
+        let parent = parent.filter(|parent| self.nodes.contains_key(parent));
+
+        if let Some(parent) = parent {
+            self.nodes.get_mut(&parent).unwrap().children.push(id);
+        }
+
+        self.nodes.insert(id, Lineage { id, parent, mate, birth: tick, death: None, genome, children: Vec::new() });
+    }
+
+    /// Marks a plant in the tree as dead
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// tick: The tick at which the plant died
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, Genome::new(&[0.5, 0.5]).unwrap());
+    /// phylogeny.record_death(PlantId(0), 7);
+    /// 
+    /// assert_eq!(Some(7), phylogeny.get(PlantId(0)).unwrap().death);
+    /// ```
+    pub fn record_death(&mut self, id: PlantId, tick: u64) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.death = Some(tick);
+        }
+    }
+
+    /// Removes all dead plants without any living descendants so the tree only contains the ancestry of the living population
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 6, genome);
+    /// phylogeny.record_death(PlantId(0), 8);
+    /// phylogeny.record_death(PlantId(1), 9);
+    /// phylogeny.prune();
+    /// 
+    /// assert_eq!(2, phylogeny.len());
+    /// assert!(phylogeny.get(PlantId(1)).is_none());
+    /// ```
+    pub fn prune(&mut self) {
+        // Start with all the dead leaves and work up towards the roots
+        let mut stack: Vec<PlantId> = self.nodes.values()
+            .filter(|node| node.death.is_some() && node.children.is_empty())
+            .map(|node| node.id)
+            .collect();
+
+        while let Some(id) = stack.pop() {
+            let node = self.nodes.remove(&id).unwrap();
+
+            if let Some(parent) = node.parent {
+                let parent = self.nodes.get_mut(&parent).unwrap();
+                parent.children.retain(|&child| child != id);
+
+                if parent.death.is_some() && parent.children.is_empty() {
+                    stack.push(parent.id);
+                }
+            }
+        }
+    }
+
+    /// Exports the tree in the Newick format with the plant ids as labels and the number of ticks between the
+    /// germination of a parent and its child as branch lengths, every root gets its own tree on a separate line
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, phylogeny::Phylogeny, population::PlantId};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let mut phylogeny = Phylogeny::new();
+    /// phylogeny.record_birth(PlantId(0), None, None, 0, genome.clone());
+    /// phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 5, genome.clone());
+    /// phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 6, genome);
+    /// 
+    /// assert_eq!("(1:5,2:6)0;\n", phylogeny.to_newick());
+    /// ```
+    pub fn to_newick(&self) -> String {
+        let mut newick = String::new();
+
+        for root in self.roots() {
+            self.write_newick(root.id, &mut newick);
+            newick.push_str(";\n");
+        }
+
+        newick
+    }
+
+    /// Writes the subtree of a single plant in the Newick format, this is done without recursion to support long lineages
+    fn write_newick(&self, root: PlantId, newick: &mut String) {
+        enum Visit {
+            Enter(PlantId),
+            Exit(PlantId),
+            Separator,
+        }
+
+        let mut stack = vec![Visit::Enter(root)];
+
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(id) => {
+                    let node = &self.nodes[&id];
+
+                    if node.children.is_empty() {
+                        self.write_label(node, newick);
+                        continue;
+                    }
+
+                    // Visit all the children before writing the label
+                    newick.push('(');
+                    stack.push(Visit::Exit(id));
+
+                    for (index, &child) in node.children.iter().enumerate().rev() {
+                        stack.push(Visit::Enter(child));
+
+                        if index > 0 {
+                            stack.push(Visit::Separator);
+                        }
+                    }
+                }
+
+                Visit::Exit(id) => {
+                    newick.push(')');
+                    self.write_label(&self.nodes[&id], newick);
+                }
+
+                Visit::Separator => newick.push(','),
+            }
+        }
+    }
+
+    /// Writes the label and branch length of a single plant in the Newick format
+    fn write_label(&self, node: &Lineage, newick: &mut String) {
+        newick.push_str(&node.id.0.to_string());
+
+        if let Some(parent) = node.parent {
+            newick.push_str(&format!(":{}", node.birth - self.nodes[&parent].birth));
+        }
+    }
+}
+
+impl HeapSize for Phylogeny {
+    fn heap_bytes(&self) -> usize {
+        btree_map_bytes(&self.nodes) + self.nodes.values().map(|node| vec_bytes(&node.children)).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genome() -> Genome {
+        Genome::new(&[0.5, 0.5]).unwrap()
+    }
+
+    fn phylogeny() -> Phylogeny {
+        // 0 -> 1 -> 3
+        //   -> 2
+        // 4
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(0), None, None, 0, genome());
+        phylogeny.record_birth(PlantId(1), Some(PlantId(0)), None, 2, genome());
+        phylogeny.record_birth(PlantId(2), Some(PlantId(0)), None, 3, genome());
+        phylogeny.record_birth(PlantId(3), Some(PlantId(1)), None, 7, genome());
+        phylogeny.record_birth(PlantId(4), None, None, 0, genome());
+
+        phylogeny
+    }
+
+    #[test]
+    fn phylogeny_new() {
+        let phylogeny = Phylogeny::new();
+
+        assert!(phylogeny.nodes.is_empty());
+    }
+
+    #[test]
+    fn phylogeny_len() {
+        assert_eq!(5, phylogeny().len());
+        assert!(!phylogeny().is_empty());
+        assert!(Phylogeny::new().is_empty());
+    }
+
+    #[test]
+    fn phylogeny_get() {
+        let phylogeny = phylogeny();
+
+        assert_eq!(Some(&Lineage { id: PlantId(1), parent: Some(PlantId(0)), mate: None, birth: 2, death: None, genome: genome(), children: vec![PlantId(3)] }), phylogeny.get(PlantId(1)));
+        assert_eq!(None, phylogeny.get(PlantId(5)));
+    }
+
+    #[test]
+    fn phylogeny_roots() {
+        let roots: Vec<PlantId> = phylogeny().roots().map(|node| node.id).collect();
+
+        assert_eq!(vec![PlantId(0), PlantId(4)], roots);
+    }
+
+    #[test]
+    fn phylogeny_ancestors() {
+        let phylogeny = phylogeny();
+
+        assert_eq!(vec![PlantId(1), PlantId(0)], phylogeny.ancestors(PlantId(3)));
+        assert!(phylogeny.ancestors(PlantId(4)).is_empty());
+        assert!(phylogeny.ancestors(PlantId(5)).is_empty());
+    }
+
+    #[test]
+    fn phylogeny_living_descendants() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_death(PlantId(1), 8);
+
+        assert_eq!(vec![PlantId(2), PlantId(3)], phylogeny.living_descendants(PlantId(0)));
+        assert_eq!(vec![PlantId(3)], phylogeny.living_descendants(PlantId(1)));
+        assert!(phylogeny.living_descendants(PlantId(4)).is_empty());
+        assert!(phylogeny.living_descendants(PlantId(9)).is_empty());
+    }
+
+    #[test]
+    fn phylogeny_relatives() {
+        let phylogeny = phylogeny();
+        let relatives = |id, generations| phylogeny.relatives(PlantId(id), generations).into_iter().map(|(id, distance)| (id.0, distance)).collect::<Vec<_>>();
+
+        assert_eq!(vec![(0, 2), (1, 1), (2, 2), (3, 0)], relatives(3, 2));
+        assert_eq!(vec![(1, 1), (3, 0)], relatives(3, 1));
+        assert_eq!(vec![(3, 0)], relatives(3, 0));
+        assert_eq!(vec![(0, 1), (1, 1), (2, 0), (3, 2)], relatives(2, 2));
+        assert_eq!(vec![(4, 0)], relatives(4, 5));
+        assert!(relatives(9, 5).is_empty());
+    }
+
+    #[test]
+    fn phylogeny_record_birth() {
+        let phylogeny = phylogeny();
+
+        assert_eq!(vec![PlantId(1), PlantId(2)], phylogeny.nodes[&PlantId(0)].children);
+        assert_eq!(Some(PlantId(0)), phylogeny.nodes[&PlantId(2)].parent);
+        assert_eq!(3, phylogeny.nodes[&PlantId(2)].birth);
+    }
+
+    #[test]
+    fn phylogeny_record_birth_unknown_parent() {
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(3), Some(PlantId(1)), None, 7, genome());
+
+        assert_eq!(None, phylogeny.nodes[&PlantId(3)].parent);
+    }
+
+    #[test]
+    fn phylogeny_record_birth_mate() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_birth(PlantId(5), Some(PlantId(3)), Some(PlantId(2)), 9, genome());
+
+        assert_eq!(Some(PlantId(2)), phylogeny.nodes[&PlantId(5)].mate);
+        assert_eq!(vec![PlantId(5)], phylogeny.nodes[&PlantId(3)].children);
+        assert!(phylogeny.nodes[&PlantId(2)].children.is_empty());
+    }
+
+    #[test]
+    fn phylogeny_record_death() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_death(PlantId(2), 9);
+        phylogeny.record_death(PlantId(7), 9);
+
+        assert_eq!(Some(9), phylogeny.nodes[&PlantId(2)].death);
+        assert_eq!(5, phylogeny.nodes.len());
+    }
+
+    #[test]
+    fn phylogeny_prune() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_death(PlantId(0), 8);
+        phylogeny.record_death(PlantId(2), 9);
+        phylogeny.record_death(PlantId(4), 10);
+        phylogeny.prune();
+        let ids: Vec<PlantId> = phylogeny.nodes.keys().copied().collect();
+
+        assert_eq!(vec![PlantId(0), PlantId(1), PlantId(3)], ids);
+        assert_eq!(vec![PlantId(1)], phylogeny.nodes[&PlantId(0)].children);
+    }
+
+    #[test]
+    fn phylogeny_prune_lineage() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_death(PlantId(0), 8);
+        phylogeny.record_death(PlantId(1), 8);
+        phylogeny.record_death(PlantId(2), 9);
+        phylogeny.record_death(PlantId(3), 10);
+        phylogeny.prune();
+        let ids: Vec<PlantId> = phylogeny.nodes.keys().copied().collect();
+
+        assert_eq!(vec![PlantId(4)], ids);
+    }
+
+    #[test]
+    fn phylogeny_relatives_pruned() {
+        let mut phylogeny = phylogeny();
+        phylogeny.record_death(PlantId(3), 10);
+        phylogeny.prune();
+
+        assert!(phylogeny.relatives(PlantId(3), 2).is_empty());
+        assert!(phylogeny.relatives(PlantId(99), 2).is_empty());
+        assert_eq!(vec![PlantId(0), PlantId(1), PlantId(2)], phylogeny.relatives(PlantId(1), 1).into_keys().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn phylogeny_to_newick() {
+        assert_eq!("((3:5)1:2,2:3)0;\n4;\n", phylogeny().to_newick());
+    }
+
+    #[test]
+    fn phylogeny_to_newick_deep() {
+        let mut phylogeny = Phylogeny::new();
+        phylogeny.record_birth(PlantId(0), None, None, 0, genome());
+
+        for id in 1..100_000 {
+            phylogeny.record_birth(PlantId(id), Some(PlantId(id - 1)), None, id, genome());
+        }
+
+        let newick = phylogeny.to_newick();
+
+        assert!(newick.starts_with(&"(".repeat(99_999)));
+        assert!(newick.ends_with(")0;\n"));
+    }
+}
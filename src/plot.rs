@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use crate::genome::Genome;
+use crate::population::Population;
+
+/// The width in pixels of the column of every gene
+pub const COLUMN_WIDTH: usize = 24;
+/// The height in pixels of the area a gene value of 1 reaches
+pub const PLOT_HEIGHT: usize = 120;
+/// The space in pixels around the plot holding the axis and the labels
+pub const MARGIN: usize = 20;
+
+impl Genome {
+    /// Draws the genome as an SVG bar chart with one bar for every gene, the height of a bar is the value of the gene
+    /// and every bar is labelled with the index of its gene
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::genome::Genome;
+    /// 
+    /// let svg = Genome::new(&[0.5, 0.25, 1.0]).unwrap().plot_svg();
+    /// 
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(3, svg.matches("<rect class=\"gene\"").count());
+    /// ```
+    pub fn plot_svg(&self) -> String {
+        let genes = self.genes();
+        let mut svg = open_plot(genes.len());
+
+        for (index, &gene) in genes.iter().enumerate() {
+            let height = gene * PLOT_HEIGHT as f32;
+            let _ = writeln!(
+                svg,
+                "<rect class=\"gene\" x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"seagreen\"><title>gene {}: {}</title></rect>",
+                column_x(index) + 2, (MARGIN + PLOT_HEIGHT) as f32 - height, COLUMN_WIDTH - 4, height, index, gene,
+            );
+        }
+
+        close_plot(svg, genes.len())
+    }
+}
+
+impl Population {
+    /// Draws how the values of every gene are spread over the population as an SVG plot. Every gene gets a column split
+    /// into a number of bins from 0 at the bottom to 1 at the top, a bin is darker the more plants have a value inside it,
+    /// and a line marks the mean value of the gene. Plants with fewer genes do not count towards the missing genes
+    /// 
+    /// # Parameters
+    /// 
+    /// bins: The number of bins every column is split into, 0 is treated as 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+    /// population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[0.9, 0.5, 0.3]).unwrap()));
+    /// let svg = population.diversity_plot_svg(10);
+    /// 
+    /// assert_eq!(3, svg.matches("<line class=\"mean\"").count());
+    /// ```
+    pub fn diversity_plot_svg(&self, bins: usize) -> String {
+        let bins = bins.max(1);
+        let genes = self.plants().iter().map(|plant| plant.genome.genes().len()).max().unwrap_or(0);
+
+        // Count the plants with a value in every bin of every gene
+        let mut counts = vec![vec![0usize; bins]; genes];
+        let mut sums = vec![(0.0f32, 0usize); genes];
+        for plant in self.plants() {
+            for (index, &gene) in plant.genome.genes().iter().enumerate() {
+                counts[index][((gene * bins as f32) as usize).min(bins - 1)] += 1;
+                sums[index].0 += gene;
+                sums[index].1 += 1;
+            }
+        }
+        let most = counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+        let mut svg = open_plot(genes);
+        let bin_height = PLOT_HEIGHT as f32 / bins as f32;
+        for (index, gene_counts) in counts.iter().enumerate() {
+            for (bin, &count) in gene_counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+                let _ = writeln!(
+                    svg,
+                    "<rect class=\"bin\" x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"seagreen\" fill-opacity=\"{:.3}\"><title>gene {}: {}</title></rect>",
+                    column_x(index) + 2, (MARGIN + PLOT_HEIGHT) as f32 - (bin + 1) as f32 * bin_height, COLUMN_WIDTH - 4, bin_height,
+                    count as f32 / most as f32, index, count,
+                );
+            }
+
+            let (sum, plants) = sums[index];
+            let y = (MARGIN + PLOT_HEIGHT) as f32 - sum / plants as f32 * PLOT_HEIGHT as f32;
+            let _ = writeln!(
+                svg,
+                "<line class=\"mean\" x1=\"{}\" y1=\"{:.1}\" x2=\"{}\" y2=\"{:.1}\" stroke=\"black\" stroke-width=\"2\"/>",
+                column_x(index), y, column_x(index) + COLUMN_WIDTH, y,
+            );
+        }
+
+        close_plot(svg, genes)
+    }
+}
+
+/// Finds the x position of the left side of the column of a gene
+fn column_x(index: usize) -> usize {
+    MARGIN + index * COLUMN_WIDTH
+}
+
+/// Starts an SVG plot with room for a number of gene columns and draws the axes
+fn open_plot(columns: usize) -> String {
+    let width = 2 * MARGIN + columns * COLUMN_WIDTH;
+    let height = 2 * MARGIN + PLOT_HEIGHT;
+    let bottom = MARGIN + PLOT_HEIGHT;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height);
+    let _ = writeln!(svg, "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>", width, height);
+    let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>", MARGIN, MARGIN, MARGIN, bottom);
+    let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>", MARGIN, bottom, width - MARGIN, bottom);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">1</text>", MARGIN - 4, MARGIN + 4);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">0</text>", MARGIN - 4, bottom);
+
+    svg
+}
+
+/// Labels the gene columns and ends the SVG plot
+fn close_plot(mut svg: String, columns: usize) -> String {
+    for index in 0..columns {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\">{}</text>",
+            column_x(index) + COLUMN_WIDTH / 2, MARGIN + PLOT_HEIGHT + 14, index,
+        );
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Size};
+    use crate::population::Plant;
+
+    #[test]
+    fn plot_genome_bars() {
+        let svg = Genome::new(&[0.0, 0.5, 1.0]).unwrap().plot_svg();
+
+        assert!(svg.contains(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\"", 2 * MARGIN + 3 * COLUMN_WIDTH)));
+        assert!(svg.contains(&format!("y=\"{:.1}\" width=\"{}\" height=\"{:.1}\"", MARGIN as f32, COLUMN_WIDTH - 4, PLOT_HEIGHT as f32)));
+        assert!(svg.contains(&format!("height=\"{:.1}\"", PLOT_HEIGHT as f32 / 2.0)));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn plot_diversity_bins() {
+        let mut population = Population::new(Size::new(3, 1));
+        for (x, genes) in [(0, [0.05, 0.5]), (1, [0.05, 0.5]), (2, [0.95, 0.5])] {
+            population.insert(Coord::new(x, 0), Plant::new(10, Genome::new(&genes).unwrap()));
+        }
+        let svg = population.diversity_plot_svg(10);
+
+        // The first gene fills two bins and the second one bin
+        assert_eq!(3, svg.matches("<rect class=\"bin\"").count());
+        assert!(svg.contains("fill-opacity=\"0.667\"><title>gene 0: 2</title>"));
+        assert!(svg.contains("<title>gene 1: 3</title>"));
+    }
+
+    #[test]
+    fn plot_diversity_empty() {
+        let svg = Population::new(Size::new(2, 2)).diversity_plot_svg(0);
+
+        assert!(!svg.contains("class=\"bin\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,181 @@
+use rand::Rng;
+
+use crate::board::Coord;
+use crate::genome::{self, Genome};
+use crate::population::{Plant, Population};
+
+/// The settings for carrying pollen between plants with the wind in sexual reproduction. The pollen of a plant
+/// is blown along the wind and spreads out around where the wind carries it, so a plant is mostly pollinated by
+/// the plants upwind of it. How much pollen a plant releases is set by how much it invests in flowering,
+/// and flowering costs upkeep, so plants trade energy for the chance of fathering seeds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PollinationConfig {
+    /// The average number of cells the pollen is carried along x and y
+    pub wind: (f32, f32),
+    /// How far in cells the pollen spreads out around where the wind carries it
+    pub spread: f32,
+    /// The extra energy a plant with a flowering gene of 1 pays every step, this scales with the flowering gene
+    pub cost: f32,
+}
+
+impl Default for PollinationConfig {
+    fn default() -> Self {
+        Self {
+            wind: (0.0, 0.0),
+            spread: 1.0,
+            cost: 2.0,
+        }
+    }
+}
+
+impl PollinationConfig {
+    /// Finds how much a plant invests in flowering from 0 to 1, plants without a flowering gene release no pollen
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    pub fn investment(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_FLOWERING).unwrap_or(0.0)
+    }
+
+    /// Finds the extra upkeep a plant pays in a step for flowering
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant paying the upkeep
+    pub fn upkeep(&self, plant: &Plant) -> u32 {
+        (self.investment(&plant.genome) * self.cost.max(0.0)) as u32
+    }
+
+    /// Finds the part of the pollen which is carried a distance by the wind, 1 for the distance the wind carries it
+    /// 
+    /// # Parameters
+    /// 
+    /// dx: The distance along x from the plant releasing the pollen to the plant receiving it
+    /// dy: The distance along y from the plant releasing the pollen to the plant receiving it
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::pollination::PollinationConfig;
+    /// 
+    /// let config = PollinationConfig { wind: (2.0, 0.0), spread: 1.0, ..Default::default() };
+    /// 
+    /// assert_eq!(1.0, config.kernel(2.0, 0.0));
+    /// assert!(config.kernel(1.0, 0.0) > config.kernel(-1.0, 0.0));
+    /// ```
+    pub fn kernel(&self, dx: f32, dy: f32) -> f32 {
+        let spread = self.spread.max(0.01);
+        let (ex, ey) = (dx - self.wind.0, dy - self.wind.1);
+
+        (-(ex * ex + ey * ey) / (2.0 * spread * spread)).exp()
+    }
+
+    /// Finds how much pollen a plant receives from another plant
+    /// 
+    /// # Parameters
+    /// 
+    /// donor: The position of the plant releasing the pollen
+    /// genome: The genome of the plant releasing the pollen
+    /// receiver: The position of the plant receiving the pollen
+    pub fn pollen(&self, donor: Coord, genome: &Genome, receiver: Coord) -> f32 {
+        let dx = receiver.x as f32 - donor.x as f32;
+        let dy = receiver.y as f32 - donor.y as f32;
+
+        self.investment(genome) * self.kernel(dx, dy)
+    }
+
+    /// Picks the mate of a plant at random with a chance proportional to the pollen it receives from every candidate,
+    /// None if it receives no pollen
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The population the candidates live in
+    /// receiver: The position of the plant receiving the pollen
+    /// candidates: The indices of the compatible plants within pollen range
+    /// rng: The random number generator to use
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, pollination::PollinationConfig, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(3, 1));
+    /// population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+    /// population.insert(Coord::new(2, 0), Plant::new(10, Genome::new(&[0.0, 0.0]).unwrap()));
+    /// let config = PollinationConfig::default();
+    /// 
+    /// // Only the flowering plant releases any pollen
+    /// assert_eq!(Some(0), config.choose_mate(&population, Coord::new(1, 0), &[0, 2], &mut rand::thread_rng()));
+    /// assert_eq!(None, config.choose_mate(&population, Coord::new(1, 0), &[2], &mut rand::thread_rng()));
+    /// ```
+    pub fn choose_mate<R: Rng>(&self, population: &Population, receiver: Coord, candidates: &[usize], rng: &mut R) -> Option<usize> {
+        let size = population.size();
+        let weights: Vec<f32> = candidates.iter()
+            .map(|&index| population.plant(index).map_or(0.0, |plant| self.pollen(size.coord_of(index).unwrap(), &plant.genome, receiver)))
+            .collect();
+
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut pick = rng.gen::<f32>() * total;
+        for (&index, &weight) in candidates.iter().zip(weights.iter()) {
+            if weight > 0.0 && pick < weight {
+                return Some(index);
+            }
+            pick -= weight;
+        }
+
+        // Rounding may leave a bit of the pick, it goes to the last candidate with any pollen
+        candidates.iter().zip(weights.iter()).rev().find(|(_, &weight)| weight > 0.0).map(|(&index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn flowering(investment: f32) -> Genome {
+        Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, investment]).unwrap()
+    }
+
+    #[test]
+    fn pollination_upkeep() {
+        let config = PollinationConfig { cost: 4.0, ..Default::default() };
+
+        assert_eq!(2, config.upkeep(&Plant::new(10, flowering(0.5))));
+        assert_eq!(0, config.upkeep(&Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap())));
+    }
+
+    #[test]
+    fn pollination_choose_mate_wind() {
+        let mut population = Population::new(Size::new(5, 1));
+        population.insert(Coord::new(0, 0), Plant::new(10, flowering(1.0)));
+        population.insert(Coord::new(4, 0), Plant::new(10, flowering(1.0)));
+        let config = PollinationConfig { wind: (2.0, 0.0), spread: 0.5, ..Default::default() };
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+
+        // The wind blows towards positive x, so the plant upwind fathers nearly every seed
+        let upwind = (0..200).filter(|_| config.choose_mate(&population, Coord::new(2, 0), &[0, 4], &mut rng) == Some(0)).count();
+
+        assert!(upwind > 190, "{upwind}");
+    }
+
+    #[test]
+    fn pollination_choose_mate_investment() {
+        let mut population = Population::new(Size::new(3, 1));
+        population.insert(Coord::new(0, 0), Plant::new(10, flowering(0.9)));
+        population.insert(Coord::new(2, 0), Plant::new(10, flowering(0.1)));
+        let config = PollinationConfig::default();
+        let mut rng = ChaCha8Rng::seed_from_u64(5);
+
+        let generous = (0..1000).filter(|_| config.choose_mate(&population, Coord::new(1, 0), &[0, 2], &mut rng) == Some(0)).count();
+
+        assert!((850..950).contains(&generous), "{generous}");
+    }
+}
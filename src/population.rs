@@ -0,0 +1,1187 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::board::{reframe_cells, Coord, Rect, Size};
+use crate::distance::{DistanceMatrix, GenomeDistance, MeanAbsolute};
+use crate::genome::{self, Genome};
+use crate::invariants::InvariantViolation;
+use crate::memory::{hash_map_bytes, vec_bytes, HeapSize};
+use crate::organism::Organism;
+use crate::phenotype::{Development, DirectDevelopment, Phenotype};
+use crate::spatial::{self, SpatialIndex};
+use crate::species::{SpeciesId, SpeciesTracker};
+
+/// The unique id of a plant
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PlantId(pub u64);
+
+/// A single plant living on the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plant {
+    /// The unique id of the plant, this is assigned when the plant is placed in a population
+    pub(crate) id: PlantId,
+    /// The id of the parent of the plant, None if the plant was not produced by another plant
+    parent: Option<PlantId>,
+    /// The id of the plant which pollinated the parent, None if the plant was produced asexually
+    mate: Option<PlantId>,
+    /// The energy stored in the plant
+    pub energy: u32,
+    /// The genetic material of the plant
+    pub genome: Genome,
+    /// The number of steps the plant has survived
+    pub age: u64,
+    /// The traits developed from the genome, this is developed again when the plant is placed in a simulation
+    pub phenotype: Phenotype,
+    /// How much the plant has grown by investing energy, a plant collects the light times one plus its growth
+    pub growth: f32,
+    /// The number of steps the plant stays infected by the pathogen, 0 if it is healthy
+    pub infection: u32,
+    /// The total energy the plant has collected during its life
+    pub gathered: u64,
+}
+
+impl Plant {
+    /// Creates a new plant without a parent, its traits are developed directly from the genes
+    /// 
+    /// # Parameters
+    /// 
+    /// energy: The initial energy of the plant
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, population::Plant};
+    /// 
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let plant = Plant::new(100, genome.clone());
+    /// 
+    /// assert_eq!(100, plant.energy);
+    /// assert_eq!(genome, plant.genome);
+    /// ```
+    pub fn new(energy: u32, genome: Genome) -> Self {
+        let phenotype = DirectDevelopment.develop(&genome);
+
+        Self { id: PlantId(0), parent: None, mate: None, energy, genome, age: 0, phenotype, growth: 0.0, infection: 0, gathered: 0 }
+    }
+
+    /// Creates a new seed produced by another plant, possibly pollinated by a mate
+    pub(crate) fn seed(parent: PlantId, mate: Option<PlantId>, energy: u32, genome: Genome) -> Self {
+        let phenotype = DirectDevelopment.develop(&genome);
+
+        Self { id: PlantId(0), parent: Some(parent), mate, energy, genome, age: 0, phenotype, growth: 0.0, infection: 0, gathered: 0 }
+    }
+
+    /// Gives the plant the id and ancestry it had when it was saved
+    pub(crate) fn with_lineage(self, id: PlantId, parent: Option<PlantId>, mate: Option<PlantId>) -> Self {
+        Self { id, parent, mate, ..self }
+    }
+
+    /// Returns the unique id of the plant
+    pub fn id(&self) -> PlantId {
+        self.id
+    }
+
+    /// Returns the id of the parent of the plant, None if the plant was not produced by another plant
+    pub fn parent(&self) -> Option<PlantId> {
+        self.parent
+    }
+
+    /// Returns the id of the plant which pollinated the parent, None if the plant was produced asexually
+    pub fn mate(&self) -> Option<PlantId> {
+        self.mate
+    }
+}
+
+/// A bottleneck removing part of a population, such that founder effects can be studied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cull {
+    /// Removes a share of the plants picked at random, the fraction is clamped between 0 and 1
+    Random {
+        fraction: f32,
+    },
+    /// Removes every plant inside a rectangle
+    Region(Rect),
+    /// Removes every plant outside a rectangle, the plants inside become the founders
+    KeepRegion(Rect),
+}
+
+/// All the plants on the board, there can be at most one plant in every cell. A population can hold another organism
+/// through the Organism trait, but a simulation only steps a population of plants.
+/// The plants are packed together without gaps such that loops over the plants do not visit empty cells,
+/// removing a plant moves the last plant into its slot. Every cell knows the slot of its plant
+/// and every plant can be found from its id. A spatial index of the occupied cells is kept up to date for neighbourhood queries
+#[derive(Clone, Debug)]
+pub struct Population<O = Plant> {
+    /// The size of the board the population lives on
+    size: Size,
+    /// The slot of the plant in every cell of the board
+    grid: Vec<Option<usize>>,
+    /// The living plants in no particular order
+    plants: Vec<O>,
+    /// The cell of every plant in the same order as the plants
+    cells: Vec<usize>,
+    /// The slot of every living plant by its id
+    slots: HashMap<PlantId, usize>,
+    /// The id to give the next plant placed in the population
+    next_id: u64,
+    /// The index of the occupied cells for finding the plants near a position
+    spatial: SpatialIndex,
+}
+
+impl<O: Organism + PartialEq> PartialEq for Population<O> {
+    /// Two populations are the same if they have the same plants in the same cells, the order they are stored in does not matter
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.next_id == other.next_id
+            && self.plants.len() == other.plants.len()
+            && (0..self.grid.len()).all(|index| self.plant(index) == other.plant(index))
+    }
+}
+
+impl Population {
+    /// Creates a new empty population of plants
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board the population lives on
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, population::Population};
+    /// 
+    /// let population = Population::new(Size::new(4, 4));
+    /// 
+    /// assert_eq!(0, population.count());
+    /// ```
+    pub fn new(size: Size) -> Self {
+        Self::empty(size)
+    }
+}
+
+impl<O: Organism> Population<O> {
+    /// Creates a new empty population of any kind of organism, for plants this is the same as new
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board the population lives on
+    pub fn empty(size: Size) -> Self {
+        let grid = vec![None; size.len()];
+
+        Self { size, grid, plants: Vec::new(), cells: Vec::new(), slots: HashMap::new(), next_id: 0, spatial: SpatialIndex::new(size, spatial::BUCKET_SIZE) }
+    }
+
+    /// Returns the size of the board the population lives on
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, population::Population};
+    /// 
+    /// let population = Population::new(Size::new(4, 2));
+    /// 
+    /// assert_eq!(Size::new(4, 2), population.size());
+    /// ```
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the index of the occupied cells for finding the plants near a position
+    pub fn spatial(&self) -> &SpatialIndex {
+        &self.spatial
+    }
+
+    /// Returns the number of living plants
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(1, 2), Plant::new(100, genome));
+    /// 
+    /// assert_eq!(1, population.count());
+    /// ```
+    pub fn count(&self) -> usize {
+        self.plants.len()
+    }
+
+    /// Gets the plant at a position, returns None if there is no plant or the position is outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(1, 2), Plant::new(100, genome));
+    /// 
+    /// assert_eq!(100, population.get(Coord::new(1, 2)).unwrap().energy);
+    /// assert!(population.get(Coord::new(2, 1)).is_none());
+    /// ```
+    pub fn get(&self, coord: Coord) -> Option<&O> {
+        self.size.index_of(coord).and_then(|index| self.plant(index))
+    }
+
+    /// Gets the plant at a position mutably, returns None if there is no plant or the position is outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(1, 2), Plant::new(100, genome));
+    /// population.get_mut(Coord::new(1, 2)).unwrap().energy = 50;
+    /// 
+    /// assert_eq!(50, population.get(Coord::new(1, 2)).unwrap().energy);
+    /// ```
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut O> {
+        self.size.index_of(coord).and_then(|index| self.plant_mut(index))
+    }
+
+    /// Places a plant on the board and returns the plant which was there before,
+    /// nothing happens if the position is outside the board and the plant is returned.
+    /// The plant is given a new unique id
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position of the plant
+    /// plant: The plant to place
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// 
+    /// assert!(population.insert(Coord::new(1, 2), Plant::new(100, genome.clone())).is_none());
+    /// assert_eq!(100, population.insert(Coord::new(1, 2), Plant::new(50, genome)).unwrap().energy);
+    /// ```
+    pub fn insert(&mut self, coord: Coord, plant: O) -> Option<O> {
+        match self.size.index_of(coord) {
+            Some(index) => {
+                let replaced = self.take(index);
+                self.place(index, plant);
+
+                replaced
+            }
+            None => Some(plant),
+        }
+    }
+
+    /// Removes the plant at a position and returns it
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(1, 2), Plant::new(100, genome));
+    /// 
+    /// assert_eq!(100, population.remove(Coord::new(1, 2)).unwrap().energy);
+    /// assert_eq!(0, population.count());
+    /// ```
+    pub fn remove(&mut self, coord: Coord) -> Option<O> {
+        self.size.index_of(coord).and_then(|index| self.take(index))
+    }
+
+    /// Changes the size of the board the population lives on keeping the top left corner in place,
+    /// the plants which no longer fit on the board are removed and returned in the order of their cells
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The new size of the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(3, 3));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(0, 1), Plant::new(100, genome.clone()));
+    /// population.insert(Coord::new(2, 2), Plant::new(50, genome));
+    /// let culled = population.resize(Size::new(2, 4));
+    /// 
+    /// assert_eq!(50, culled[0].energy);
+    /// assert_eq!(100, population.get(Coord::new(0, 1)).unwrap().energy);
+    /// ```
+    pub fn resize(&mut self, size: Size) -> Vec<O> {
+        let (w, h) = size.size();
+
+        self.reframe(Rect::new(0, 0, w, h)).into_iter().map(|(_, plant)| plant).collect()
+    }
+
+    /// Cuts out a rectangle of the board which becomes the new board the population lives on, the rectangle is clamped
+    /// to the board. The plants inside the rectangle move along with it and keep their ids,
+    /// the plants outside are removed and returned in the order of their cells
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x coordinate of the left edge
+    /// y: The y coordinate of the top edge
+    /// w: The width
+    /// h: The height
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(3, 3));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(0, 0), Plant::new(100, genome.clone()));
+    /// population.insert(Coord::new(2, 2), Plant::new(50, genome));
+    /// let culled = population.crop(1, 1, 2, 2);
+    /// 
+    /// assert_eq!(100, culled[0].energy);
+    /// assert_eq!(50, population.get(Coord::new(1, 1)).unwrap().energy);
+    /// ```
+    pub fn crop(&mut self, x: usize, y: usize, w: usize, h: usize) -> Vec<O> {
+        let rect = Rect::new(x, y, w, h).clamp(self.size);
+
+        self.reframe(rect).into_iter().map(|(_, plant)| plant).collect()
+    }
+
+    /// Removes a share of the plants picked at random, such that a population bottleneck can be studied. The number removed is
+    /// the fraction of the plants rounded to the nearest whole number. Returns the removed plants together with their coordinates
+    /// in the order of the cells
+    /// 
+    /// # Parameters
+    /// 
+    /// fraction: The share of the plants to remove, it is clamped between 0 and 1
+    /// rng: The random number generator picking the plants
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// use rand::SeedableRng;
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// for x in 0..4 {
+    ///     population.insert(Coord::new(x, 0), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// }
+    /// let culled = population.cull_random(0.75, &mut rand_chacha::ChaCha8Rng::seed_from_u64(1));
+    /// 
+    /// assert_eq!(3, culled.len());
+    /// assert_eq!(1, population.count());
+    /// ```
+    pub fn cull_random<R: Rng>(&mut self, fraction: f32, rng: &mut R) -> Vec<(Coord, O)> {
+        // The cells are picked from in their order on the board such that the slots the plants happen to be in do not matter
+        let mut occupied = self.cells.clone();
+        occupied.sort_unstable();
+        let count = (occupied.len() as f32 * fraction.clamp(0.0, 1.0)).round() as usize;
+        let mut picked: Vec<usize> = rand::seq::index::sample(rng, occupied.len(), count).into_iter().map(|slot| occupied[slot]).collect();
+        picked.sort_unstable();
+
+        self.remove_cells(picked)
+    }
+
+    /// Removes every plant inside a rectangle and returns them together with their coordinates row by row.
+    /// The parts of the rectangle outside the board are ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to empty
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Rect, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// population.insert(Coord::new(3, 3), Plant::new(50, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let culled = population.cull_region(Rect::new(2, 2, 5, 5));
+    /// 
+    /// assert_eq!(vec![Coord::new(3, 3)], culled.iter().map(|(coord, _)| *coord).collect::<Vec<_>>());
+    /// assert_eq!(1, population.count());
+    /// ```
+    pub fn cull_region(&mut self, rect: Rect) -> Vec<(Coord, O)> {
+        let picked: Vec<usize> = rect.clamp(self.size)
+            .coords()
+            .map(|coord| self.size.index_of(coord).unwrap())
+            .filter(|&index| self.is_occupied(index))
+            .collect();
+
+        self.remove_cells(picked)
+    }
+
+    /// Keeps only the plants passing a filter and removes the rest, such that the survivors become the founders of the
+    /// following generations. Returns the removed plants together with their coordinates in the order of the cells
+    /// 
+    /// # Parameters
+    /// 
+    /// filter: Returns true for the plants to keep given their coordinate
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+    /// population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[0.9, 0.5]).unwrap()));
+    /// let culled = population.keep_only(|_, plant| plant.genome.genes()[0] > 0.5);
+    /// 
+    /// assert_eq!(Coord::new(0, 0), culled[0].0);
+    /// assert!(population.get(Coord::new(1, 0)).is_some());
+    /// ```
+    pub fn keep_only<F: FnMut(Coord, &O) -> bool>(&mut self, mut filter: F) -> Vec<(Coord, O)> {
+        let picked: Vec<usize> = (0..self.grid.len())
+            .filter(|&index| self.plant(index).is_some_and(|plant| !filter(self.size.coord_of(index).unwrap(), plant)))
+            .collect();
+
+        self.remove_cells(picked)
+    }
+
+    /// Removes the plants in cells given in order and returns them together with their coordinates
+    fn remove_cells(&mut self, indices: Vec<usize>) -> Vec<(Coord, O)> {
+        indices.into_iter()
+            .filter_map(|index| self.take(index).map(|plant| (self.size.coord_of(index).unwrap(), plant)))
+            .collect()
+    }
+
+    /// Iterates over all living plants and their positions in the order of the cells
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// population.insert(Coord::new(1, 2), Plant::new(100, genome.clone()));
+    /// population.insert(Coord::new(3, 0), Plant::new(50, genome));
+    /// let coords: Vec<Coord> = population.iter().map(|(coord, _)| coord).collect();
+    /// 
+    /// assert_eq!(vec![Coord::new(3, 0), Coord::new(1, 2)], coords);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &O)> {
+        self.grid.iter()
+            .enumerate()
+            .filter_map(|(index, slot)| slot.map(|slot| (self.size.coord_of(index).unwrap(), &self.plants[slot])))
+    }
+
+    /// Iterates over all plants inside a rectangle together with their positions, the plants are visited row by row.
+    /// The parts of the rectangle outside the board are ignored
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to look inside
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Rect, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// let genome = Genome::new(&[0.5, 0.5]).unwrap();
+    /// for coord in [Coord::new(0, 0), Coord::new(2, 1), Coord::new(3, 3)] {
+    ///     population.insert(coord, Plant::new(100, genome.clone()));
+    /// }
+    /// let coords: Vec<Coord> = population.iter_in_region(Rect::new(1, 1, 5, 5)).map(|(coord, _)| coord).collect();
+    /// 
+    /// assert_eq!(vec![Coord::new(2, 1), Coord::new(3, 3)], coords);
+    /// ```
+    pub fn iter_in_region(&self, rect: Rect) -> impl Iterator<Item = (Coord, &O)> {
+        rect.clamp(self.size)
+            .coords()
+            .filter_map(|coord| self.get(coord).map(|plant| (coord, plant)))
+    }
+
+    /// Iterates over all plants which belonged to a species at the latest clustering together with their positions,
+    /// in the same order as iter. Plants born after the latest clustering do not belong to any species yet
+    /// 
+    /// # Parameters
+    /// 
+    /// tracker: The tracker which clustered the population
+    /// id: The id of the species
+    pub fn iter_species<'a>(&'a self, tracker: &'a SpeciesTracker, id: SpeciesId) -> impl Iterator<Item = (Coord, &'a O)> {
+        self.iter().filter(move |(_, plant)| tracker.species_of(plant.id()) == Some(id))
+    }
+
+    /// Calculates the diversity of the population as the mean genetic distance of the genomes to the mean genome,
+    /// returns 0 if there are no plants
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(1, 2), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+    /// population.insert(Coord::new(3, 0), Plant::new(100, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// 
+    /// assert_eq!(0.25, population.diversity());
+    /// ```
+    pub fn diversity(&self) -> f32 {
+        self.diversity_with(&MeanAbsolute)
+    }
+
+    /// Calculates the diversity of the population as the mean distance of the genomes to the mean genome
+    /// measured with any distance, returns 0 if there are no plants
+    /// 
+    /// # Parameters
+    /// 
+    /// metric: The distance between two genomes
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, distance::Hamming, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(1, 2), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+    /// population.insert(Coord::new(3, 0), Plant::new(100, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// 
+    /// assert_eq!(1.0, population.diversity_with(&Hamming { tolerance: 0.1 }));
+    /// ```
+    pub fn diversity_with<D: GenomeDistance + ?Sized>(&self, metric: &D) -> f32 {
+        // Find the mean genome
+        let mean = genome::mean_genes(self.iter().map(|(_, plant)| plant.genome()));
+
+        if mean.is_empty() {
+            return 0.0;
+        }
+
+        let mean = Genome::new(&mean).expect("The mean of valid genomes is a valid genome");
+
+        // Find the mean distance to the mean genome
+        let total: f32 = self.iter()
+            .map(|(_, plant)| metric.distance(plant.genome(), &mean))
+            .sum();
+
+        total / self.count() as f32
+    }
+
+    /// Calculates the distance between every pair of plants ordered by id.
+    /// Large populations are sampled down by picking plants evenly spread over the ids,
+    /// so the matrix of the same population is always the same
+    /// 
+    /// # Parameters
+    /// 
+    /// metric: The distance between two genomes
+    /// max_plants: The largest number of plants to include in the matrix
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, distance::MeanAbsolute, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// for x in 0..4 {
+    ///     population.insert(Coord::new(x, 0), Plant::new(100, Genome::new(&[x as f32 / 4.0, 0.5]).unwrap()));
+    /// }
+    /// let matrix = population.pairwise_distance_matrix(&MeanAbsolute, 2);
+    /// 
+    /// assert_eq!(2, matrix.len());
+    /// assert_eq!(0.25, matrix.get(0, 1));
+    /// ```
+    pub fn pairwise_distance_matrix<D: GenomeDistance + ?Sized>(&self, metric: &D, max_plants: usize) -> DistanceMatrix {
+        let mut plants: Vec<_> = self.iter().map(|(_, plant)| plant).collect();
+        plants.sort_by_key(|plant| plant.id());
+
+        let count = plants.len();
+        let sample = count.min(max_plants);
+        let sampled = (0..sample).map(|index| plants[index * count / sample]);
+
+        DistanceMatrix::new(metric, sampled.map(|plant| (plant.id(), plant.genome())))
+    }
+
+    /// Gets the plant with an id, returns None if there is no living plant with the id
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, PlantId, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(1, 2), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// 
+    /// assert_eq!(100, population.get_by_id(PlantId(0)).unwrap().energy);
+    /// assert!(population.get_by_id(PlantId(1)).is_none());
+    /// ```
+    pub fn get_by_id(&self, id: PlantId) -> Option<&O> {
+        self.slots.get(&id).map(|&slot| &self.plants[slot])
+    }
+
+    /// Gets the plant with an id mutably, returns None if there is no living plant with the id
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn get_by_id_mut(&mut self, id: PlantId) -> Option<&mut O> {
+        self.slots.get(&id).map(|&slot| &mut self.plants[slot])
+    }
+
+    /// Finds the position of the plant with an id, returns None if there is no living plant with the id
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, PlantId, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(4, 4));
+    /// population.insert(Coord::new(1, 2), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// 
+    /// assert_eq!(Some(Coord::new(1, 2)), population.coord_of(PlantId(0)));
+    /// ```
+    pub fn coord_of(&self, id: PlantId) -> Option<Coord> {
+        self.slots.get(&id).map(|&slot| self.size.coord_of(self.cells[slot]).unwrap())
+    }
+
+    /// Returns all living plants in the order they are stored, this is faster than iterating over the cells
+    /// but the order changes when plants are removed
+    pub fn plants(&self) -> &[O] {
+        &self.plants
+    }
+
+    /// Returns all living plants mutably in the order they are stored
+    pub fn plants_mut(&mut self) -> &mut [O] {
+        &mut self.plants
+    }
+
+    /// Places a plant in a cell giving it a new unique id and returns the id
+    pub(crate) fn place(&mut self, index: usize, mut plant: O) -> PlantId {
+        let id = PlantId(self.next_id);
+        plant.set_id(id);
+        self.next_id += 1;
+
+        self.put(index, Some(plant));
+
+        id
+    }
+
+    /// Makes a rectangle of the board the new board, the rectangle may reach outside the board in which case
+    /// the cells outside are empty. Returns the removed plants together with their coordinates on the old board
+    pub(crate) fn reframe(&mut self, rect: Rect) -> Vec<(Coord, O)> {
+        let from = self.size;
+        let (cells, removed) = reframe_cells(self.take_cells(), from, rect, || None);
+        self.size = Size::new(rect.w, rect.h);
+        self.grid = vec![None; self.size.len()];
+        self.spatial = SpatialIndex::new(self.size, spatial::BUCKET_SIZE);
+        for (index, plant) in cells.into_iter().enumerate() {
+            self.put(index, plant);
+        }
+
+        removed.into_iter()
+            .filter_map(|(index, cell)| cell.map(|plant| (from.coord_of(index).unwrap(), plant)))
+            .collect()
+    }
+
+    /// Gets the plant in a cell
+    pub(crate) fn plant(&self, index: usize) -> Option<&O> {
+        self.grid[index].map(|slot| &self.plants[slot])
+    }
+
+    /// Gets the plant in a cell mutably
+    pub(crate) fn plant_mut(&mut self, index: usize) -> Option<&mut O> {
+        self.grid[index].map(|slot| &mut self.plants[slot])
+    }
+
+    /// Returns true if there is a plant in a cell
+    pub(crate) fn is_occupied(&self, index: usize) -> bool {
+        self.grid[index].is_some()
+    }
+
+    /// Removes the plant in a cell, the last plant is moved into its slot
+    pub(crate) fn take(&mut self, index: usize) -> Option<O> {
+        let slot = self.grid[index].take()?;
+        self.spatial.remove(index);
+        let plant = self.plants.swap_remove(slot);
+        self.cells.swap_remove(slot);
+        if self.slots.get(&plant.id()) == Some(&slot) {
+            self.slots.remove(&plant.id());
+        }
+
+        // Point the cell and id of the moved plant to its new slot
+        if slot < self.plants.len() {
+            self.grid[self.cells[slot]] = Some(slot);
+            self.slots.insert(self.plants[slot].id(), slot);
+        }
+
+        Some(plant)
+    }
+
+    /// Puts a plant in a cell keeping its id, the plant which was there before is dropped
+    pub(crate) fn put(&mut self, index: usize, plant: Option<O>) {
+        self.take(index);
+
+        if let Some(plant) = plant {
+            let slot = self.plants.len();
+            self.slots.insert(plant.id(), slot);
+            self.plants.push(plant);
+            self.cells.push(index);
+            self.grid[index] = Some(slot);
+            self.spatial.insert(index);
+        }
+    }
+
+    /// Checks that every plant is in exactly one cell on the board, that every cell, id and the spatial index
+    /// point to the right plant and that no plant has an id which will be given out again
+    pub(crate) fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        if self.plants.len() != self.cells.len() || self.plants.len() != self.slots.len() || self.grid.len() != self.size.len() {
+            return Err(InvariantViolation::Bookkeeping { plants: self.plants.len(), cells: self.cells.len(), ids: self.slots.len() });
+        }
+
+        for (slot, (plant, &index)) in self.plants.iter().zip(self.cells.iter()).enumerate() {
+            if index >= self.grid.len() {
+                return Err(InvariantViolation::OutOfBounds { id: plant.id(), index });
+            }
+            if self.grid[index] != Some(slot) {
+                return Err(InvariantViolation::SharedCell { coord: self.size.coord_of(index).unwrap() });
+            }
+            if self.slots.get(&plant.id()) != Some(&slot) {
+                return Err(InvariantViolation::DuplicateId { id: plant.id() });
+            }
+            if plant.id().0 >= self.next_id {
+                return Err(InvariantViolation::FutureId { id: plant.id(), next: self.next_id });
+            }
+        }
+
+        let occupied = self.grid.iter().flatten().count();
+        if occupied != self.plants.len() {
+            return Err(InvariantViolation::Bookkeeping { plants: self.plants.len(), cells: occupied, ids: self.slots.len() });
+        }
+        if self.spatial.len() != self.plants.len() {
+            return Err(InvariantViolation::SpatialIndex { expected: self.plants.len(), found: self.spatial.len() });
+        }
+
+        Ok(())
+    }
+
+    /// Iterates over the plant in every cell in the order of the cells
+    pub(crate) fn cells(&self) -> impl Iterator<Item = Option<&O>> + '_ {
+        self.grid.iter().map(|slot| slot.map(|slot| &self.plants[slot]))
+    }
+
+    /// Creates a population from the plant in every cell, the plants keep their ids and new plants get ids after the largest one
+    pub(crate) fn from_cells(size: Size, cells: Vec<Option<O>>) -> Self {
+        let mut population = Self::empty(size);
+        for (index, plant) in cells.into_iter().enumerate().take(size.len()) {
+            if let Some(plant) = plant {
+                population.next_id = population.next_id.max(plant.id().0 + 1);
+                population.put(index, Some(plant));
+            }
+        }
+
+        population
+    }
+
+    /// Removes all plants and returns the plant in every cell
+    fn take_cells(&mut self) -> Vec<Option<O>> {
+        let mut cells: Vec<Option<O>> = vec![None; self.grid.len()];
+        for (plant, index) in self.plants.drain(..).zip(self.cells.drain(..)) {
+            cells[index] = Some(plant);
+        }
+        self.grid.fill(None);
+        self.slots.clear();
+        self.spatial = SpatialIndex::new(self.size, spatial::BUCKET_SIZE);
+
+        cells
+    }
+}
+
+impl<O> HeapSize for Population<O> {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.grid) + vec_bytes(&self.plants) + vec_bytes(&self.cells) + hash_map_bytes(&self.slots) + self.spatial.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::species::SpeciesConfig;
+
+    fn genome() -> Genome {
+        Genome::new(&[0.5, 0.5]).unwrap()
+    }
+
+    #[test]
+    fn plant_new() {
+        let plant = Plant::new(100, genome());
+
+        assert_eq!(None, plant.parent);
+        assert_eq!(None, plant.mate);
+        assert_eq!(100, plant.energy);
+        assert_eq!(genome(), plant.genome);
+        assert_eq!(0, plant.age);
+    }
+
+    #[test]
+    fn plant_seed() {
+        let plant = Plant::seed(PlantId(5), Some(PlantId(3)), 100, genome());
+
+        assert_eq!(Some(PlantId(5)), plant.parent);
+        assert_eq!(Some(PlantId(3)), plant.mate);
+        assert_eq!(100, plant.energy);
+        assert_eq!(genome(), plant.genome);
+    }
+
+    #[test]
+    fn population_new() {
+        let size = Size::new(4, 3);
+        let population = Population::new(size);
+
+        assert_eq!(size, population.size);
+        assert_eq!(12, population.grid.len());
+        assert!(population.cells().all(|cell| cell.is_none()));
+        assert_eq!(0, population.next_id);
+    }
+
+    #[test]
+    fn population_size() {
+        let size = Size::new(4, 3);
+        let population = Population::new(size);
+
+        assert_eq!(size, population.size());
+    }
+
+    #[test]
+    fn population_count() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(2, Some(Plant::new(100, genome())));
+        population.put(5, Some(Plant::new(100, genome())));
+
+        assert_eq!(2, population.count());
+    }
+
+    #[test]
+    fn population_get() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(6, Some(Plant::new(100, genome())));
+
+        assert_eq!(Some(&Plant::new(100, genome())), population.get(Coord::new(2, 1)));
+        assert_eq!(None, population.get(Coord::new(1, 2)));
+        assert_eq!(None, population.get(Coord::new(4, 1)));
+    }
+
+    #[test]
+    fn population_get_mut() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(6, Some(Plant::new(100, genome())));
+        population.get_mut(Coord::new(2, 1)).unwrap().energy = 50;
+
+        assert_eq!(50, population.plant(6).unwrap().energy);
+        assert_eq!(None, population.get_mut(Coord::new(2, 3)));
+    }
+
+    #[test]
+    fn population_insert() {
+        let mut population = Population::new(Size::new(4, 3));
+
+        assert_eq!(None, population.insert(Coord::new(2, 1), Plant::new(100, genome())));
+        assert_eq!(Some(Plant::new(100, genome())), population.insert(Coord::new(2, 1), Plant::new(50, genome())));
+        assert_eq!(50, population.plant(6).unwrap().energy);
+        assert_eq!(PlantId(1), population.plant(6).unwrap().id);
+        assert_eq!(Some(Plant::new(20, genome())), population.insert(Coord::new(2, 3), Plant::new(20, genome())));
+    }
+
+    #[test]
+    fn population_place() {
+        let mut population = Population::new(Size::new(4, 3));
+
+        assert_eq!(PlantId(0), population.place(6, Plant::new(100, genome())));
+        assert_eq!(PlantId(1), population.place(2, Plant::seed(PlantId(0), None, 100, genome())));
+        assert_eq!(PlantId(1), population.plant(2).unwrap().id);
+        assert_eq!(Some(PlantId(0)), population.plant(2).unwrap().parent);
+        assert_eq!(2, population.next_id);
+    }
+
+    #[test]
+    fn population_remove() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(6, Some(Plant::new(100, genome())));
+
+        assert_eq!(Some(Plant::new(100, genome())), population.remove(Coord::new(2, 1)));
+        assert_eq!(None, population.remove(Coord::new(2, 1)));
+        assert_eq!(None, population.plant(6));
+    }
+
+    #[test]
+    fn population_diversity() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(1, Some(Plant::new(50, Genome::new(&[0.0, 0.0]).unwrap())));
+        population.put(2, Some(Plant::new(50, Genome::new(&[0.0, 1.0]).unwrap())));
+        population.put(3, Some(Plant::new(50, Genome::new(&[0.0, 1.0, 1.0]).unwrap())));
+
+        // The mean genome is [0, 2/3, 1]
+        let expected = ((2.0 / 3.0 + 1.0) + (1.0 / 3.0 + 1.0) + 1.0 / 3.0) / 9.0;
+        assert!((expected - population.diversity()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn population_diversity_uniform() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(1, Some(Plant::new(50, genome())));
+        population.put(2, Some(Plant::new(50, genome())));
+
+        assert_eq!(0.0, population.diversity());
+    }
+
+    #[test]
+    fn population_diversity_empty() {
+        let population = Population::new(Size::new(4, 3));
+
+        assert_eq!(0.0, population.diversity());
+    }
+
+    #[test]
+    fn population_diversity_with() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(1, Some(Plant::new(50, Genome::new(&[0.0, 0.0]).unwrap())));
+        population.put(2, Some(Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap())));
+
+        assert_eq!(population.diversity(), population.diversity_with(&MeanAbsolute));
+        assert_eq!(0.5, population.diversity_with(&crate::distance::Euclidean));
+    }
+
+    #[test]
+    fn population_pairwise_distance_matrix() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.place(5, Plant::new(50, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.place(1, Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.place(3, Plant::new(50, Genome::new(&[1.0, 1.0]).unwrap()));
+        let matrix = population.pairwise_distance_matrix(&MeanAbsolute, 10);
+
+        assert_eq!(&[PlantId(0), PlantId(1), PlantId(2)], matrix.ids());
+        assert_eq!(0.5, matrix.get(0, 1));
+        assert_eq!(1.0, matrix.get(0, 2));
+    }
+
+    #[test]
+    fn population_pairwise_distance_matrix_sampled() {
+        let mut population = Population::new(Size::new(4, 3));
+        for index in 0..12 {
+            population.place(index, Plant::new(50, genome()));
+        }
+        let matrix = population.pairwise_distance_matrix(&MeanAbsolute, 4);
+
+        assert_eq!(&[PlantId(0), PlantId(3), PlantId(6), PlantId(9)], matrix.ids());
+        assert_eq!(0.0, matrix.mean());
+        assert!(population.pairwise_distance_matrix(&MeanAbsolute, 0).is_empty());
+    }
+
+    #[test]
+    fn population_iter() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.put(6, Some(Plant::new(100, genome())));
+        population.put(1, Some(Plant::new(50, genome())));
+        let plants: Vec<(Coord, u32)> = population.iter().map(|(coord, plant)| (coord, plant.energy)).collect();
+
+        assert_eq!(vec![(Coord::new(1, 0), 50), (Coord::new(2, 1), 100)], plants);
+    }
+
+    #[test]
+    fn population_iter_in_region() {
+        let mut population = Population::new(Size::new(4, 3));
+        for index in [0, 5, 6, 11] {
+            population.place(index, Plant::new(index as u32, genome()));
+        }
+        let energies: Vec<u32> = population.iter_in_region(Rect::new(1, 1, 2, 5)).map(|(_, plant)| plant.energy).collect();
+
+        assert_eq!(vec![5, 6], energies);
+        assert_eq!(0, population.iter_in_region(Rect::new(4, 0, 2, 2)).count());
+    }
+
+    #[test]
+    fn population_iter_species() {
+        let mut population = Population::new(Size::new(4, 3));
+        population.place(2, Plant::new(10, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.place(7, Plant::new(20, Genome::new(&[1.0, 1.0]).unwrap()));
+        population.place(9, Plant::new(30, Genome::new(&[0.0, 0.1]).unwrap()));
+        let mut tracker = SpeciesTracker::new(SpeciesConfig { interval: 1, threshold: 0.2, ..Default::default() });
+        tracker.update(0, &population);
+        let species = tracker.species_of(PlantId(0)).unwrap();
+
+        let energies: Vec<u32> = population.iter_species(&tracker, species).map(|(_, plant)| plant.energy).collect();
+
+        assert_eq!(vec![10, 30], energies);
+    }
+
+    #[test]
+    fn population_swap_remove() {
+        let mut population = Population::new(Size::new(4, 3));
+        for index in [3, 7, 9] {
+            population.place(index, Plant::new(index as u32, genome()));
+        }
+        population.remove(Coord::new(3, 0));
+
+        // The last plant was moved into the slot of the removed plant and can still be found by cell and id
+        assert_eq!(2, population.plants().len());
+        assert_eq!(9, population.plant(9).unwrap().energy);
+        assert_eq!(Some(Coord::new(1, 2)), population.coord_of(PlantId(2)));
+        assert_eq!(7, population.get_by_id(PlantId(1)).unwrap().energy);
+        assert!(population.get_by_id(PlantId(0)).is_none());
+    }
+
+    #[test]
+    fn population_get_by_id_mut() {
+        let mut population = Population::new(Size::new(4, 3));
+        let id = population.place(5, Plant::new(10, genome()));
+        population.get_by_id_mut(id).unwrap().energy = 30;
+
+        assert_eq!(30, population.plant(5).unwrap().energy);
+    }
+
+    #[test]
+    fn population_eq_storage_order() {
+        let mut first = Population::new(Size::new(2, 2));
+        let mut second = Population::new(Size::new(2, 2));
+        first.place(0, Plant::new(10, genome()));
+        first.place(3, Plant::new(20, genome()));
+        let (a, b) = (first.plant(0).cloned(), first.plant(3).cloned());
+        second.next_id = 2;
+        second.put(3, b);
+        second.put(0, a);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn population_resize() {
+        let mut population = Population::new(Size::new(2, 2));
+        population.insert(Coord::new(1, 0), Plant::new(10, genome()));
+        population.insert(Coord::new(0, 1), Plant::new(20, genome()));
+        let culled = population.resize(Size::new(3, 1));
+
+        assert_eq!(Size::new(3, 1), population.size());
+        assert_eq!(3, population.grid.len());
+        assert_eq!(vec![20], culled.iter().map(|plant| plant.energy).collect::<Vec<_>>());
+        assert_eq!(10, population.get(Coord::new(1, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn population_cull_random() {
+        use rand::SeedableRng;
+
+        let size = Size::new(5, 4);
+        let mut population = Population::new(size);
+        for coord in size.coords() {
+            population.insert(coord, Plant::new(100, genome()));
+        }
+        let mut same = population.clone();
+        // Moving plants between slots does not change which cells are picked
+        let plant = same.remove(Coord::new(0, 0)).unwrap();
+        same.insert(Coord::new(0, 0), plant);
+
+        let culled = population.cull_random(0.5, &mut rand_chacha::ChaCha8Rng::seed_from_u64(3));
+        let again = same.cull_random(0.5, &mut rand_chacha::ChaCha8Rng::seed_from_u64(3));
+
+        assert_eq!(10, culled.len());
+        assert_eq!(10, population.count());
+        assert!(culled.windows(2).all(|pair| size.index_of(pair[0].0) < size.index_of(pair[1].0)));
+        assert!(culled.iter().all(|(coord, _)| population.get(*coord).is_none()));
+        assert_eq!(culled.iter().map(|(coord, _)| *coord).collect::<Vec<_>>(), again.iter().map(|(coord, _)| *coord).collect::<Vec<_>>());
+        assert!(population.check_invariants().is_ok());
+
+        assert!(population.cull_random(0.0, &mut rand_chacha::ChaCha8Rng::seed_from_u64(3)).is_empty());
+        assert_eq!(10, population.cull_random(2.0, &mut rand_chacha::ChaCha8Rng::seed_from_u64(3)).len());
+        assert_eq!(0, population.count());
+    }
+
+    #[test]
+    fn population_cull_region_keep_only() {
+        let size = Size::new(4, 4);
+        let mut population = Population::new(size);
+        for coord in size.coords() {
+            population.insert(coord, Plant::new(coord.x as u32, genome()));
+        }
+
+        assert_eq!(4, population.cull_region(Rect::new(3, 0, 3, 10)).len());
+        let culled = population.keep_only(|coord, plant| coord.y == 0 || plant.energy == 2);
+
+        assert_eq!(6, culled.len());
+        assert_eq!(vec![Coord::new(0, 0), Coord::new(1, 0), Coord::new(2, 0), Coord::new(2, 1), Coord::new(2, 2), Coord::new(2, 3)], population.iter().map(|(coord, _)| coord).collect::<Vec<_>>());
+        assert!(population.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn population_crop() {
+        let mut population = Population::new(Size::new(3, 2));
+        population.insert(Coord::new(0, 0), Plant::new(10, genome()));
+        population.insert(Coord::new(2, 1), Plant::new(20, genome()));
+        let id = population.get(Coord::new(2, 1)).unwrap().id();
+        let culled = population.crop(1, 1, 4, 4);
+
+        assert_eq!(Size::new(2, 1), population.size());
+        assert_eq!(1, culled.len());
+        assert_eq!(id, population.get(Coord::new(1, 0)).unwrap().id());
+
+        // New plants keep getting unique ids
+        population.insert(Coord::new(0, 0), Plant::new(5, genome()));
+        assert_eq!(PlantId(2), population.get(Coord::new(0, 0)).unwrap().id());
+    }
+
+    #[test]
+    fn population_reframe() {
+        let mut population = Population::new(Size::new(2, 2));
+        population.insert(Coord::new(1, 1), Plant::new(10, genome()));
+        let removed = population.reframe(Rect::new(0, 0, 1, 3));
+
+        assert_eq!(vec![Coord::new(1, 1)], removed.iter().map(|(coord, _)| *coord).collect::<Vec<_>>());
+        assert_eq!(Size::new(1, 3), population.size());
+        assert_eq!(0, population.count());
+    }
+
+    #[test]
+    fn population_check_invariants() {
+        let mut population = Population::new(Size::new(3, 3));
+        population.insert(Coord::new(0, 0), Plant::new(10, genome()));
+        population.insert(Coord::new(1, 0), Plant::new(10, genome()));
+
+        assert_eq!(Ok(()), population.check_invariants());
+
+        population.put(5, Some(Plant::new(10, genome())));
+
+        assert_eq!(Err(InvariantViolation::Bookkeeping { plants: 3, cells: 3, ids: 2 }), population.check_invariants());
+    }
+
+    #[test]
+    fn population_spatial_sync() {
+        let mut population = Population::new(Size::new(20, 20));
+        for coord in [Coord::new(1, 1), Coord::new(2, 2), Coord::new(12, 12), Coord::new(19, 0)] {
+            population.insert(coord, Plant::new(10, genome()));
+        }
+        population.remove(Coord::new(2, 2));
+        population.insert(Coord::new(12, 12), Plant::new(20, genome()));
+        population.crop(1, 1, 15, 15);
+
+        assert_eq!(2, population.spatial().len());
+        assert_eq!(vec![Coord::new(11, 11)], population.spatial().nearest(Coord::new(0, 0), 1));
+        assert_eq!(vec![Coord::new(0, 0), Coord::new(11, 11)], population.spatial().nearest(Coord::new(5, 5), 3));
+    }
+}
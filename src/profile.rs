@@ -0,0 +1,242 @@
+use std::time::{Duration, Instant};
+
+/// The number of phases of a step
+pub const PHASES: usize = 7;
+
+/// A part of a step which is timed when profiling
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// The disturbances hitting the board, the light being recalculated and the water moving
+    Fields,
+    /// The pathogen spreading, the networks allocating energy, the plants collecting energy and sharing it underground
+    Energy,
+    /// The plants paying upkeep and the plants which cannot pay dying
+    Death,
+    /// The plants producing seeds and picking where they land
+    Reproduction,
+    /// The seeds competing for the cells they landed in, germinating and going dormant in the seed bank
+    Dispersal,
+    /// The toxin released by the plants decaying and spreading into the neighbouring cells
+    Allelopathy,
+    /// Saving the history, clustering species, publishing statistics, adjusting the mutation rate and emitting events
+    Bookkeeping,
+}
+
+impl Phase {
+    /// All phases in the order they run in a step
+    pub const ALL: [Phase; PHASES] = [Phase::Fields, Phase::Energy, Phase::Death, Phase::Reproduction, Phase::Dispersal, Phase::Allelopathy, Phase::Bookkeeping];
+
+    /// Returns the name of the phase
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::profile::Phase;
+    /// 
+    /// assert_eq!("dispersal", Phase::Dispersal.name());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Fields => "fields",
+            Phase::Energy => "energy",
+            Phase::Death => "death",
+            Phase::Reproduction => "reproduction",
+            Phase::Dispersal => "dispersal",
+            Phase::Allelopathy => "allelopathy",
+            Phase::Bookkeeping => "bookkeeping",
+        }
+    }
+
+    /// Returns the position of the phase in ALL
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// The time spent in every phase of the steps run while profiling
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepProfile {
+    /// The number of steps which have been timed
+    steps: u64,
+    /// The time spent in every phase across all timed steps
+    totals: [Duration; PHASES],
+    /// The time spent in every phase in the latest timed step
+    latest: [Duration; PHASES],
+}
+
+impl StepProfile {
+    /// Creates a new profile without any timed steps
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of steps which have been timed
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Returns the time spent in a phase across all timed steps
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase to look up
+    pub fn total(&self, phase: Phase) -> Duration {
+        self.totals[phase.index()]
+    }
+
+    /// Returns the mean time spent in a phase per step, 0 if no steps have been timed
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase to look up
+    pub fn mean(&self, phase: Phase) -> Duration {
+        if self.steps == 0 {
+            return Duration::ZERO;
+        }
+
+        self.total(phase).div_f64(self.steps as f64)
+    }
+
+    /// Returns the time spent in a phase in the latest timed step
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase to look up
+    pub fn latest(&self, phase: Phase) -> Duration {
+        self.latest[phase.index()]
+    }
+
+    /// Finds the timing of every phase in the order they run in a step
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use evolution_plants::profile::{Phase, StepProfile};
+    /// 
+    /// let report = StepProfile::new().report();
+    /// 
+    /// assert_eq!(Phase::ALL.len(), report.len());
+    /// assert_eq!(Duration::ZERO, report[0].mean);
+    /// assert_eq!(0.0, report[0].share);
+    /// ```
+    pub fn report(&self) -> Vec<PhaseTiming> {
+        let total: Duration = self.totals.iter().sum();
+
+        Phase::ALL.iter()
+            .map(|&phase| {
+                let share = if total.is_zero() { 0.0 } else { self.total(phase).as_secs_f64() / total.as_secs_f64() };
+
+                PhaseTiming { phase, total: self.total(phase), mean: self.mean(phase), share }
+            })
+            .collect()
+    }
+
+    /// Starts timing a step
+    pub(crate) fn begin_step(&mut self) {
+        self.steps += 1;
+        self.latest = [Duration::ZERO; PHASES];
+    }
+
+    /// Adds time spent in a phase of the current step
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase the time was spent in
+    /// elapsed: The time spent
+    pub(crate) fn add(&mut self, phase: Phase, elapsed: Duration) {
+        self.totals[phase.index()] += elapsed;
+        self.latest[phase.index()] += elapsed;
+    }
+}
+
+/// The time spent in a single phase across the timed steps
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseTiming {
+    /// The phase
+    pub phase: Phase,
+    /// The time spent in the phase across all timed steps
+    pub total: Duration,
+    /// The mean time spent in the phase per step
+    pub mean: Duration,
+    /// The fraction of the time of all phases spent in this phase
+    pub share: f64,
+}
+
+/// Times a phase of a step from when it is started until it is stopped, with the tracing feature
+/// the phase is also a tracing span
+#[derive(Debug)]
+pub(crate) struct Stopwatch {
+    /// The phase being timed
+    phase: Phase,
+    /// The time the phase started
+    start: Instant,
+    /// The span of the phase which is exited when the stopwatch is dropped
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl Stopwatch {
+    /// Starts timing a phase
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase to time
+    pub fn start(phase: Phase) -> Self {
+        Self {
+            phase,
+            #[cfg(feature = "tracing")]
+            _span: tracing::trace_span!("phase", name = phase.name()).entered(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Stops timing and adds the elapsed time to the phase in a profile
+    /// 
+    /// # Parameters
+    /// 
+    /// profile: The profile to add the time to
+    pub fn stop(self, profile: &mut StepProfile) {
+        profile.add(self.phase, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_all_order() {
+        for (index, phase) in Phase::ALL.iter().enumerate() {
+            assert_eq!(index, phase.index());
+        }
+    }
+
+    #[test]
+    fn step_profile_add() {
+        let mut profile = StepProfile::new();
+        profile.begin_step();
+        profile.add(Phase::Energy, Duration::from_millis(3));
+        profile.add(Phase::Death, Duration::from_millis(1));
+        profile.begin_step();
+        profile.add(Phase::Energy, Duration::from_millis(1));
+
+        assert_eq!(2, profile.steps());
+        assert_eq!(Duration::from_millis(4), profile.total(Phase::Energy));
+        assert_eq!(Duration::from_millis(2), profile.mean(Phase::Energy));
+        assert_eq!(Duration::from_millis(1), profile.latest(Phase::Energy));
+        assert_eq!(Duration::ZERO, profile.latest(Phase::Death));
+        assert_eq!(0.8, profile.report()[Phase::Energy.index()].share);
+    }
+
+    #[test]
+    fn stopwatch_stop() {
+        let mut profile = StepProfile::new();
+        profile.begin_step();
+        let stopwatch = Stopwatch::start(Phase::Fields);
+        std::thread::sleep(Duration::from_millis(2));
+        stopwatch.stop(&mut profile);
+
+        assert!(profile.latest(Phase::Fields) >= Duration::from_millis(2));
+    }
+}
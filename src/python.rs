@@ -0,0 +1,240 @@
+use numpy::{PyArray, PyArray2, PyArray3, PyArrayMethods, PyReadonlyArray2, PyUntypedArrayMethods};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::board::{BoardBuilder, Coord};
+use crate::genome::{Genome, MutationConfig};
+use crate::population::{Plant, Population};
+use crate::simulation::{ConfigUpdate, ReproductionMode, Simulation, SimulationConfig};
+use crate::stats::{self, Subscription, TickStats};
+
+/// The settings of a simulation as seen from Python
+#[pyclass(name = "SimulationConfig", module = "evolution_plants")]
+#[derive(Clone, Debug)]
+struct PyConfig {
+    /// The settings being wrapped
+    inner: SimulationConfig,
+}
+
+#[pymethods]
+impl PyConfig {
+    #[new]
+    #[pyo3(signature = (seed = 0, upkeep = 10, seed_cost = 50, max_threshold = 1000, mutation_rate = None, mutation_strength = None, sexual = false))]
+    fn new(seed: u64, upkeep: u32, seed_cost: u32, max_threshold: u32, mutation_rate: Option<f32>, mutation_strength: Option<f32>, sexual: bool) -> Self {
+        let defaults = SimulationConfig::default();
+        let mutation = MutationConfig::new(
+            mutation_rate.unwrap_or(defaults.mutation.rate),
+            mutation_strength.unwrap_or(defaults.mutation.strength),
+        );
+        let mut reproduction = defaults.reproduction;
+        reproduction.mode = if sexual { ReproductionMode::Sexual } else { ReproductionMode::Asexual };
+
+        Self { inner: SimulationConfig { seed, upkeep, seed_cost, max_threshold, mutation, reproduction, ..defaults } }
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.inner.seed
+    }
+
+    #[setter]
+    fn set_seed(&mut self, seed: u64) {
+        self.inner.seed = seed;
+    }
+
+    #[getter]
+    fn upkeep(&self) -> u32 {
+        self.inner.upkeep
+    }
+
+    #[setter]
+    fn set_upkeep(&mut self, upkeep: u32) {
+        self.inner.upkeep = upkeep;
+    }
+
+    #[getter]
+    fn seed_cost(&self) -> u32 {
+        self.inner.seed_cost
+    }
+
+    #[setter]
+    fn set_seed_cost(&mut self, seed_cost: u32) {
+        self.inner.seed_cost = seed_cost;
+    }
+
+    #[getter]
+    fn max_threshold(&self) -> u32 {
+        self.inner.max_threshold
+    }
+
+    #[setter]
+    fn set_max_threshold(&mut self, max_threshold: u32) {
+        self.inner.max_threshold = max_threshold;
+    }
+
+    #[getter]
+    fn mutation_rate(&self) -> f32 {
+        self.inner.mutation.rate
+    }
+
+    #[setter]
+    fn set_mutation_rate(&mut self, rate: f32) {
+        self.inner.mutation.rate = rate;
+    }
+
+    #[getter]
+    fn mutation_strength(&self) -> f32 {
+        self.inner.mutation.strength
+    }
+
+    #[setter]
+    fn set_mutation_strength(&mut self, strength: f32) {
+        self.inner.mutation.strength = strength;
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
+
+/// A simulation as seen from Python, the fields are returned as numpy arrays indexed by row and then column
+#[pyclass(name = "Simulation", module = "evolution_plants", unsendable)]
+struct PySimulation {
+    /// The simulation being wrapped
+    inner: Simulation,
+    /// The statistics of every step, drained after each step
+    subscription: Subscription,
+    /// The statistics of the latest step
+    latest: Option<TickStats>,
+}
+
+#[pymethods]
+impl PySimulation {
+    /// Creates a simulation from the light of every cell as a 2d array and a list of plants given as
+    /// (x, y, energy, genes)
+    #[new]
+    #[pyo3(signature = (light, plants, config = None, light_multiplier = 1))]
+    fn new(light: PyReadonlyArray2<f32>, plants: Vec<(usize, usize, u32, Vec<f32>)>, config: Option<PyConfig>, light_multiplier: u32) -> PyResult<Self> {
+        let (h, w) = (light.shape()[0], light.shape()[1]);
+        let light: Vec<f32> = light.as_array().iter().copied().collect();
+
+        let board = BoardBuilder::new()
+            .size(w, h)
+            .light_from_slice(&light)
+            .multiplier_light(light_multiplier)
+            .build()
+            .map_err(|error| PyValueError::new_err(error.to_string()))?;
+
+        let mut population = Population::new(board.fields.size);
+        for (x, y, energy, genes) in plants {
+            let genome = Genome::new(&genes).map_err(|error| PyValueError::new_err(error.to_string()))?;
+            population.insert(Coord::new(x, y), Plant::new(energy, genome));
+        }
+
+        let config = config.map(|config| config.inner).unwrap_or_default();
+        let mut inner = Simulation::new(board, population, config).map_err(|error| PyValueError::new_err(error.to_string()))?;
+        let subscription = stats::subscribe(&mut inner, 1);
+
+        Ok(Self { inner, subscription, latest: None })
+    }
+
+    /// Runs a number of steps
+    #[pyo3(signature = (steps = 1))]
+    fn step(&mut self, steps: u64) {
+        for _ in 0..steps {
+            self.inner.step();
+            self.latest = self.subscription.try_next();
+        }
+    }
+
+    /// Changes some of the settings while the simulation is running, settings left as None are kept
+    #[pyo3(signature = (upkeep = None, seed_cost = None, max_threshold = None, mutation_rate = None, mutation_strength = None, light_multiplier = None))]
+    fn update_config(&mut self, upkeep: Option<u32>, seed_cost: Option<u32>, max_threshold: Option<u32>, mutation_rate: Option<f32>, mutation_strength: Option<f32>, light_multiplier: Option<u32>) -> PyResult<()> {
+        let mutation = (mutation_rate.is_some() || mutation_strength.is_some()).then(|| {
+            let current = self.inner.config().mutation;
+            MutationConfig::new(mutation_rate.unwrap_or(current.rate), mutation_strength.unwrap_or(current.strength))
+        });
+
+        self.inner.update_config(ConfigUpdate { mutation, upkeep, seed_cost, max_threshold, light_multiplier, ..Default::default() })
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    #[getter]
+    fn tick(&self) -> u64 {
+        self.inner.tick()
+    }
+
+    /// Retrieves the statistics of the latest step as a dict, None if no steps have been run
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(latest) = self.latest else {
+            return Ok(None);
+        };
+
+        let dict = PyDict::new(py);
+        dict.set_item("tick", latest.tick)?;
+        dict.set_item("population", latest.population)?;
+        dict.set_item("births", latest.births)?;
+        dict.set_item("deaths", latest.deaths)?;
+        dict.set_item("mean_energy", latest.mean_energy)?;
+        dict.set_item("diversity", latest.diversity)?;
+        dict.set_item("mutation_rate", latest.mutation_rate)?;
+        dict.set_item("species", latest.species)?;
+
+        Ok(Some(dict))
+    }
+
+    /// Retrieves the light reaching every cell
+    fn light<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        self.grid(py, self.inner.light().to_vec())
+    }
+
+    /// Retrieves the water in every cell
+    fn water<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        self.grid(py, self.inner.water().values().to_vec())
+    }
+
+    /// Retrieves whether every cell holds a plant
+    fn occupied<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<bool>>> {
+        let cells = self.inner.population().cells().map(|cell| cell.is_some()).collect();
+        self.grid(py, cells)
+    }
+
+    /// Retrieves the energy of the plant in every cell, 0 for empty cells
+    fn energy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<u32>>> {
+        let cells = self.inner.population().cells().map(|cell| cell.map_or(0, |plant| plant.energy)).collect();
+        self.grid(py, cells)
+    }
+
+    /// Retrieves the genes of the plant in every cell as a 3d array indexed by row, column and gene,
+    /// empty cells and genes missing from shorter genomes are NaN
+    fn genes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray3<f32>>> {
+        let (w, h) = self.inner.board().fields.size.size();
+        let population = self.inner.population();
+        let count = population.plants().iter().map(|plant| plant.genome.genes().len()).max().unwrap_or(0);
+
+        let genes = population.cells()
+            .flat_map(|cell| (0..count).map(move |index| cell.and_then(|plant| plant.genome.get(index)).unwrap_or(f32::NAN)))
+            .collect();
+
+        PyArray::from_vec(py, genes).reshape([h, w, count])
+    }
+}
+
+impl PySimulation {
+    /// Shapes the values of every cell into a 2d array
+    fn grid<'py, T: numpy::Element>(&self, py: Python<'py>, values: Vec<T>) -> PyResult<Bound<'py, PyArray2<T>>> {
+        let (w, h) = self.inner.board().fields.size.size();
+
+        PyArray::from_vec(py, values).reshape([h, w])
+    }
+}
+
+/// The evolution_plants Python module
+#[pymodule]
+fn evolution_plants(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyConfig>()?;
+    module.add_class::<PySimulation>()?;
+
+    Ok(())
+}
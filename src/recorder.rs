@@ -0,0 +1,357 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use thiserror::Error;
+
+use crate::simulation::Simulation;
+
+/// The shortest delay between frames of a gif which is shown correctly by most viewers
+const MIN_GIF_DELAY: Duration = Duration::from_millis(20);
+
+/// Captures frames of a running simulation to be saved as a timelapse
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recorder {
+    /// The number of ticks between every captured frame
+    interval: u64,
+    /// The number of pixels in each direction for every cell
+    scale: u32,
+    /// All captured frames in order
+    frames: Vec<RgbaImage>,
+}
+
+impl Recorder {
+    /// Creates a new recorder without any frames
+    /// 
+    /// # Parameters
+    /// 
+    /// interval: The number of ticks between every captured frame, an interval of 0 is treated as 1
+    /// scale: The number of pixels in each direction for every cell, a scale of 0 is treated as 1
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::recorder::Recorder;
+    /// 
+    /// let recorder = Recorder::new(10, 4);
+    /// 
+    /// assert_eq!(10, recorder.interval());
+    /// assert!(recorder.is_empty());
+    /// ```
+    pub fn new(interval: u64, scale: u32) -> Self {
+        Self { interval: interval.max(1), scale: scale.max(1), frames: Vec::new() }
+    }
+
+    /// Returns the number of ticks between every captured frame
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Returns the number of pixels in each direction for every cell
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns all captured frames in order
+    pub fn frames(&self) -> &[RgbaImage] {
+        &self.frames
+    }
+
+    /// Returns the number of captured frames
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns true if no frames have been captured
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Removes all captured frames
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    /// Captures a frame of the simulation if the current tick is a multiple of the interval,
+    /// returns true if a frame was captured
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to capture
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, population::Population, recorder::Recorder, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), board::Fields::new(size, &[0.0; 4]).unwrap());
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut recorder = Recorder::new(2, 1);
+    /// 
+    /// assert!(recorder.capture(&simulation));
+    /// simulation.step();
+    /// assert!(!recorder.capture(&simulation));
+    /// assert_eq!(1, recorder.len());
+    /// ```
+    pub fn capture(&mut self, simulation: &Simulation) -> bool {
+        if !simulation.tick().is_multiple_of(self.interval) {
+            return false;
+        }
+
+        let image = simulation.board().render_to_image(simulation.population());
+        let image = if self.scale > 1 {
+            image::imageops::resize(&image, image.width() * self.scale, image.height() * self.scale, image::imageops::FilterType::Nearest)
+        } else {
+            image
+        };
+
+        self.frames.push(image);
+
+        true
+    }
+
+    /// Finds the delay between frames needed to show all captured frames in a given duration,
+    /// the delay is never shorter than what gif viewers can show
+    /// 
+    /// # Parameters
+    /// 
+    /// duration: The total length of the timelapse
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::time::Duration;
+    /// use evolution_plants::{board, population::Population, recorder::Recorder, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), board::Fields::new(size, &[0.0; 4]).unwrap());
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut recorder = Recorder::new(1, 1);
+    /// for _ in 0..10 {
+    ///     recorder.capture(&simulation);
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(Duration::from_millis(100), recorder.timelapse_delay(Duration::from_secs(1)));
+    /// ```
+    pub fn timelapse_delay(&self, duration: Duration) -> Duration {
+        let delay = duration / self.frames.len().max(1) as u32;
+
+        delay.max(MIN_GIF_DELAY)
+    }
+
+    /// Saves all captured frames as an animated gif which repeats forever
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the gif file
+    /// delay: The time every frame is shown
+    /// 
+    /// # Errors
+    /// 
+    /// RecordError::Empty: This will occur if no frames have been captured
+    /// 
+    /// RecordError::Io: This will occur if the file could not be created
+    /// 
+    /// RecordError::Image: This will occur if the frames could not be encoded
+    pub fn save_gif<P: AsRef<Path>>(&self, path: P, delay: Duration) -> Result<(), RecordError> {
+        if self.frames.is_empty() {
+            return Err(RecordError::Empty);
+        }
+
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = GifEncoder::new(file);
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        let delay = Delay::from_saturating_duration(delay);
+        encoder.encode_frames(self.frames.iter().map(|frame| Frame::from_parts(frame.clone(), 0, 0, delay)))?;
+
+        Ok(())
+    }
+
+    /// Saves all captured frames as numbered png files named frame_00000.png, frame_00001.png and so on,
+    /// returns the paths of the files in order
+    /// 
+    /// # Parameters
+    /// 
+    /// dir: The directory to save the files in, it is created if it does not exist
+    /// 
+    /// # Errors
+    /// 
+    /// RecordError::Io: This will occur if the directory could not be created
+    /// 
+    /// RecordError::Image: This will occur if a frame could not be encoded or written to its file
+    pub fn save_png_sequence<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<PathBuf>, RecordError> {
+        std::fs::create_dir_all(dir.as_ref())?;
+
+        self.frames.iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                let path = dir.as_ref().join(format!("frame_{:05}.png", index));
+                frame.save_with_format(&path, image::ImageFormat::Png)?;
+
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// Encodes all captured frames as a video by piping them to ffmpeg, which must be installed.
+    /// The format of the video is decided by ffmpeg from the extension of the path
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the video file, it is overwritten if it exists
+    /// fps: The number of frames shown every second
+    /// 
+    /// # Errors
+    /// 
+    /// RecordError::Empty: This will occur if no frames have been captured
+    /// 
+    /// RecordError::Io: This will occur if ffmpeg could not be started or the frames could not be sent to it
+    /// 
+    /// RecordError::Ffmpeg: This will occur if ffmpeg failed to encode the video
+    pub fn pipe_to_ffmpeg<P: AsRef<Path>>(&self, path: P, fps: u32) -> Result<(), RecordError> {
+        let first = self.frames.first().ok_or(RecordError::Empty)?;
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{}x{}", first.width(), first.height())])
+            .args(["-r", &fps.max(1).to_string(), "-i", "-"])
+            // Most encoders need an even size
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2", "-pix_fmt", "yuv420p"])
+            .arg(path.as_ref())
+            .stdin(Stdio::piped())
+            .spawn()?;
+
+        {
+            let mut stdin = child.stdin.take().expect("The input of ffmpeg is piped");
+            for frame in self.frames.iter() {
+                stdin.write_all(frame.as_raw())?;
+            }
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(RecordError::Ffmpeg { status: status.code() });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum RecordError {
+    #[error("No frames have been captured")]
+    Empty,
+    #[error("Unable to write the frames: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unable to encode the frames: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("ffmpeg exited with status {:?}", status)]
+    Ffmpeg {
+        status: Option<i32>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, Coord, Fields, Multipliers, Size};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let size = Size::new(3, 2);
+        let board = Board::new(Multipliers::new(100).unwrap(), Fields::new(size, &[1.0; 6]).unwrap());
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    fn recording(count: usize) -> Recorder {
+        let mut simulation = simulation();
+        let mut recorder = Recorder::new(1, 2);
+        for _ in 0..count {
+            recorder.capture(&simulation);
+            simulation.step();
+        }
+
+        recorder
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("evolution_plants_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn recorder_new() {
+        let recorder = Recorder::new(0, 0);
+
+        assert_eq!(1, recorder.interval);
+        assert_eq!(1, recorder.scale);
+        assert!(recorder.frames.is_empty());
+    }
+
+    #[test]
+    fn recorder_capture() {
+        let mut simulation = simulation();
+        let mut recorder = Recorder::new(3, 2);
+        let captured: Vec<bool> = (0..7).map(|_| {
+            let captured = recorder.capture(&simulation);
+            simulation.step();
+            captured
+        }).collect();
+
+        assert_eq!(vec![true, false, false, true, false, false, true], captured);
+        assert_eq!(3, recorder.len());
+        assert_eq!((6, 4), recorder.frames[0].dimensions());
+        assert_eq!(recorder.frames[0].get_pixel(2, 2), recorder.frames[0].get_pixel(3, 3));
+    }
+
+    #[test]
+    fn recorder_timelapse_delay() {
+        let recorder = recording(3);
+
+        assert_eq!(Duration::from_secs(10), recorder.timelapse_delay(Duration::from_secs(30)));
+        assert_eq!(MIN_GIF_DELAY, recorder.timelapse_delay(Duration::from_millis(30)));
+        assert_eq!(Duration::from_secs(30), Recorder::new(1, 1).timelapse_delay(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn recorder_save_gif() {
+        let recorder = recording(3);
+        let path = temp_path("timelapse.gif");
+        recorder.save_gif(&path, Duration::from_millis(50)).unwrap();
+        let decoder = image::codecs::gif::GifDecoder::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let frames = image::AnimationDecoder::into_frames(decoder).collect_frames().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(3, frames.len());
+        assert_eq!((6, 4), frames[0].buffer().dimensions());
+    }
+
+    #[test]
+    fn recorder_save_gif_empty() {
+        let result = Recorder::new(1, 1).save_gif(temp_path("empty.gif"), Duration::from_millis(50));
+
+        assert!(matches!(result, Err(RecordError::Empty)));
+    }
+
+    #[test]
+    fn recorder_save_png_sequence() {
+        let recorder = recording(2);
+        let dir = temp_path("frames");
+        let paths = recorder.save_png_sequence(&dir).unwrap();
+        let first = image::open(&paths[0]).unwrap().to_rgba8();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(vec![dir.join("frame_00000.png"), dir.join("frame_00001.png")], paths);
+        assert_eq!(recorder.frames[0], first);
+    }
+}
@@ -0,0 +1,329 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::genome::MutationConfig;
+use crate::scenario::{Scenario, ScenarioAction};
+use crate::simulation::{ConfigUpdate, Simulation};
+
+/// The settings of a running simulation which can be changed in a TOML file, every setting is optional
+/// and settings which are left out are not changed
+#[derive(Clone, Copy, Debug, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TunableConfig {
+    /// The probability of mutating each gene
+    pub mutation_rate: Option<f32>,
+    /// The largest amount a gene can change in a single mutation
+    pub mutation_strength: Option<f32>,
+    /// The energy every plant must pay each step to survive
+    pub upkeep: Option<u32>,
+    /// The energy it costs to produce a seed
+    pub seed_cost: Option<u32>,
+    /// The energy required to reproduce when the reproduction threshold gene is 1
+    pub max_threshold: Option<u32>,
+    /// The multiplier of the light field
+    pub light_multiplier: Option<u32>,
+}
+
+impl TunableConfig {
+    /// Reads the settings from the text of a TOML file
+    /// 
+    /// # Parameters
+    /// 
+    /// text: The text of the file
+    /// 
+    /// # Errors
+    /// 
+    /// ReloadError::Parse: This will occur if the text is not valid TOML or has a setting which cannot be changed
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::reload::TunableConfig;
+    /// 
+    /// let config = TunableConfig::parse("mutation_rate = 0.05\nupkeep = 3\n").unwrap();
+    /// 
+    /// assert_eq!(Some(0.05), config.mutation_rate);
+    /// assert_eq!(Some(3), config.upkeep);
+    /// assert_eq!(None, config.seed_cost);
+    /// assert!(TunableConfig::parse("width = 10").is_err());
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, ReloadError> {
+        toml::from_str(text).map_err(|error| ReloadError::Parse { message: error.message().to_string() })
+    }
+
+    /// Finds the settings which are different from an earlier version of the file, settings which were removed are not changes.
+    /// Returns None if nothing was changed
+    /// 
+    /// # Parameters
+    /// 
+    /// previous: The earlier version of the file
+    /// mutation: The current settings for mutation which a new rate or strength is applied to
+    pub fn changes(&self, previous: &Self, mutation: MutationConfig) -> Option<ConfigUpdate> {
+        fn changed<T: PartialEq + Copy>(new: Option<T>, old: Option<T>) -> Option<T> {
+            new.filter(|new| old != Some(*new))
+        }
+
+        let rate = changed(self.mutation_rate, previous.mutation_rate);
+        let strength = changed(self.mutation_strength, previous.mutation_strength);
+        let update = ConfigUpdate {
+            mutation: (rate.is_some() || strength.is_some()).then(|| MutationConfig {
+                rate: rate.unwrap_or(mutation.rate),
+                strength: strength.unwrap_or(mutation.strength),
+                ..mutation
+            }),
+            upkeep: changed(self.upkeep, previous.upkeep),
+            seed_cost: changed(self.seed_cost, previous.seed_cost),
+            max_threshold: changed(self.max_threshold, previous.max_threshold),
+            light_multiplier: changed(self.light_multiplier, previous.light_multiplier),
+            ..Default::default()
+        };
+
+        (update != ConfigUpdate::default()).then_some(update)
+    }
+}
+
+/// Watches a TOML file with the tunable settings of a running simulation and applies the settings which change
+/// while the simulation runs. Every change goes through Simulation::update_config so it is in the config log
+/// and sent to the hooks, and it is also added to a scenario so a replay can carry out the same changes
+pub struct ConfigWatcher {
+    /// The file being watched
+    path: PathBuf,
+    /// The watcher of the directory of the file, it stops watching when dropped
+    _watcher: RecommendedWatcher,
+    /// The events of the directory of the file
+    events: Receiver<notify::Result<Event>>,
+    /// The settings in the file when it was last read
+    current: TunableConfig,
+    /// The changes applied so far at the ticks of the steps they took effect in
+    scenario: Scenario,
+}
+
+impl ConfigWatcher {
+    /// Starts watching a file, the settings in it now are taken to be the settings of the simulation
+    /// and only later changes are applied. The directory of the file is watched so that editors which replace
+    /// the file when saving are noticed
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The TOML file to watch
+    /// 
+    /// # Errors
+    /// 
+    /// ReloadError::Io: This will occur if the file cannot be read
+    /// 
+    /// ReloadError::Parse: This will occur if the file is not valid
+    /// 
+    /// ReloadError::Watch: This will occur if the directory of the file cannot be watched
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ReloadError> {
+        let path = path.as_ref().to_path_buf();
+        let current = read(&path)?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        }).map_err(watch_error)?;
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        watcher.watch(directory, RecursiveMode::NonRecursive).map_err(watch_error)?;
+
+        Ok(Self { path, _watcher: watcher, events, current, scenario: Scenario::new() })
+    }
+
+    /// Returns the settings in the file when it was last read
+    pub fn current(&self) -> &TunableConfig {
+        &self.current
+    }
+
+    /// Returns the changes applied so far, each at the tick of the step it took effect in
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// Checks if the file has been written since the last call and applies the changed settings if it has,
+    /// this does not wait so it can be called every step. Returns the changes applied
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to change
+    /// 
+    /// # Errors
+    /// 
+    /// See ConfigWatcher::reload, the settings are left unchanged on errors so a half written file is picked up
+    /// again when it is saved
+    pub fn poll(&mut self, simulation: &mut Simulation) -> Result<Option<ConfigUpdate>, ReloadError> {
+        let mut written = false;
+        loop {
+            match self.events.try_recv() {
+                Ok(Ok(event)) => {
+                    written |= matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                        && event.paths.iter().any(|path| path.file_name() == self.path.file_name());
+                }
+                Ok(Err(error)) => return Err(watch_error(error)),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+
+        if written {
+            self.reload(simulation)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads the file and applies the settings which changed since it was last read, returns the changes applied
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to change
+    /// 
+    /// # Errors
+    /// 
+    /// ReloadError::Io: This will occur if the file cannot be read
+    /// 
+    /// ReloadError::Parse: This will occur if the file is not valid
+    /// 
+    /// ReloadError::Invalid: This will occur if the simulation cannot use the new settings, the file is read again on the next change
+    pub fn reload(&mut self, simulation: &mut Simulation) -> Result<Option<ConfigUpdate>, ReloadError> {
+        let config = read(&self.path)?;
+        let update = config.changes(&self.current, simulation.config().mutation);
+
+        if let Some(update) = update {
+            simulation.update_config(update).map_err(|error| ReloadError::Invalid { message: error.to_string() })?;
+            // The simulation has already accepted the update
+            self.scenario = std::mem::take(&mut self.scenario).insert(simulation.tick() + 1, ScenarioAction::Update(update));
+        }
+        self.current = config;
+
+        Ok(update)
+    }
+}
+
+/// Reads the settings from a file
+fn read(path: &Path) -> Result<TunableConfig, ReloadError> {
+    let text = fs::read_to_string(path).map_err(|error| ReloadError::Io { path: path.to_path_buf(), message: error.to_string() })?;
+
+    TunableConfig::parse(&text)
+}
+
+/// Turns an error of the watcher into a reload error
+fn watch_error(error: notify::Error) -> ReloadError {
+    ReloadError::Watch { message: error.to_string() }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ReloadError {
+    #[error("Unable to read the settings file {path:?}: {message}")]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("Unable to parse the settings file: {message}")]
+    Parse {
+        message: String,
+    },
+    #[error("The settings file has settings the simulation cannot use: {message}")]
+    Invalid {
+        message: String,
+    },
+    #[error("Unable to watch the settings file: {message}")]
+    Watch {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::board::BoardBuilder;
+    use crate::population::Population;
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let size = board.fields.size;
+
+        Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap()
+    }
+
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("evolution_plants_reload_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn tunable_changes() {
+        let previous = TunableConfig { mutation_rate: Some(0.1), upkeep: Some(2), seed_cost: Some(5), ..Default::default() };
+        let config = TunableConfig { mutation_strength: Some(0.3), upkeep: Some(2), light_multiplier: Some(50), ..previous };
+        let mutation = MutationConfig::new(0.2, 0.1);
+
+        let update = config.changes(&previous, mutation).unwrap();
+        assert_eq!(Some(MutationConfig::new(0.2, 0.3)), update.mutation);
+        assert_eq!((None, None, Some(50)), (update.upkeep, update.seed_cost, update.light_multiplier));
+
+        // Removing a setting keeps its value
+        assert_eq!(None, TunableConfig::default().changes(&previous, mutation));
+    }
+
+    #[test]
+    fn config_watcher_reload() {
+        let directory = directory("reload");
+        let path = directory.join("settings.toml");
+        fs::write(&path, "upkeep = 2\nmutation_rate = 0.1\n").unwrap();
+        let mut simulation = simulation();
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+        simulation.step();
+
+        fs::write(&path, "upkeep = 2\nmutation_rate = 0.05\nseed_cost = 7\n").unwrap();
+        let update = watcher.reload(&mut simulation).unwrap().unwrap();
+        assert_eq!((None, Some(7)), (update.upkeep, update.seed_cost));
+        assert_eq!(0.05, simulation.mutation_rate());
+        assert_eq!(7, simulation.config().seed_cost);
+        assert_eq!(1, simulation.config_log().len());
+        assert_eq!(vec![2], watcher.scenario().events().iter().map(|event| event.tick).collect::<Vec<_>>());
+
+        // A broken file leaves everything unchanged
+        fs::write(&path, "seed_cost = \"many\"").unwrap();
+        assert!(matches!(watcher.reload(&mut simulation), Err(ReloadError::Parse { .. })));
+        assert_eq!(Some(7), watcher.current().seed_cost);
+        fs::write(&path, "upkeep = 2\nmutation_rate = 0.05\nseed_cost = 7\n").unwrap();
+        assert_eq!(Ok(None), watcher.reload(&mut simulation));
+
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(matches!(watcher.reload(&mut simulation), Err(ReloadError::Io { .. })));
+    }
+
+    #[test]
+    fn config_watcher_poll() {
+        let directory = directory("poll");
+        let path = directory.join("settings.toml");
+        fs::write(&path, "upkeep = 2\n").unwrap();
+        let mut simulation = simulation();
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+
+        assert_eq!(Ok(None), watcher.poll(&mut simulation));
+
+        fs::write(&path, "upkeep = 9\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut update = None;
+        while update.is_none() && Instant::now() < deadline {
+            update = watcher.poll(&mut simulation).unwrap();
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(Some(9), update.and_then(|update| update.upkeep));
+        assert_eq!(9, simulation.config().upkeep);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
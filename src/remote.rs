@@ -0,0 +1,701 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::genome::MutationConfig;
+use crate::simulation::{ConfigUpdate, Simulation};
+use crate::stats::{self, Subscription, TickStats};
+
+/// The magic string appended to the key of a client to accept a WebSocket connection
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The largest message accepted from a client in bytes
+const MAX_MESSAGE: u64 = 1 << 20;
+
+/// The characters used for base64
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The opcode of a frame continuing a fragmented message
+const OPCODE_CONTINUATION: u8 = 0x0;
+/// The opcode of a text frame
+const OPCODE_TEXT: u8 = 0x1;
+/// The opcode of a binary frame
+const OPCODE_BINARY: u8 = 0x2;
+/// The opcode of a frame closing the connection
+const OPCODE_CLOSE: u8 = 0x8;
+/// The opcode of a ping frame
+const OPCODE_PING: u8 = 0x9;
+/// The opcode of a pong frame
+const OPCODE_PONG: u8 = 0xA;
+
+/// A command sent by a client as a JSON object whose "cmd" field is the name of the command
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// Gets the statistics of the current tick, with the births and deaths of the step if the server has received its record
+    Stats,
+    /// Stops stepping the simulation
+    Pause,
+    /// Starts stepping the simulation again
+    Resume,
+    /// Changes some of the settings of the simulation, settings which are left out are kept.
+    /// Nothing is changed if any of the settings cannot be used, such as a negative mutation strength
+    Set {
+        #[serde(default)]
+        upkeep: Option<u32>,
+        #[serde(default)]
+        seed_cost: Option<u32>,
+        #[serde(default)]
+        max_threshold: Option<u32>,
+        #[serde(default)]
+        mutation_rate: Option<f32>,
+        #[serde(default)]
+        mutation_strength: Option<f32>,
+        #[serde(default)]
+        light_multiplier: Option<u32>,
+    },
+    /// Gets all living plants on the board
+    Snapshot,
+}
+
+/// A request waiting to be handled by the server together with the channel to send the response to
+struct Pending {
+    /// The JSON text of the request
+    text: String,
+    /// The channel the JSON text of the response is sent to
+    reply: mpsc::Sender<String>,
+}
+
+/// A server letting clients control a simulation remotely with a small JSON protocol.
+/// Clients either connect with a WebSocket and send every request as a text message,
+/// or connect with plain TCP and send every request as a single line. Every request gets a single
+/// response, an object with "ok" set to true and the result or with "ok" false and an "error" message.
+/// 
+/// The connections are handled on their own threads while the requests are handled on the thread stepping the simulation
+#[derive(Debug)]
+pub struct RemoteServer {
+    /// The address the server listens on
+    address: SocketAddr,
+    /// The requests sent by all clients
+    requests: mpsc::Receiver<Pending>,
+    /// True if the simulation should not be stepped
+    paused: bool,
+    /// The statistics of the steps of the simulation, None until the first poll
+    subscription: Option<Subscription>,
+    /// The latest statistics received from the subscription
+    latest: Option<TickStats>,
+}
+
+impl RemoteServer {
+    /// Starts listening for clients, the clients are accepted on a background thread
+    /// 
+    /// # Parameters
+    /// 
+    /// address: The address to listen on, use port 0 to get any free port
+    /// 
+    /// # Errors
+    /// 
+    /// This will fail if the address cannot be listened on
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::io::{BufRead, BufReader, Write};
+    /// use std::net::TcpStream;
+    /// use evolution_plants::{board::BoardBuilder, population::Population, remote::RemoteServer, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let mut server = RemoteServer::bind("127.0.0.1:0").unwrap();
+    /// 
+    /// let mut client = TcpStream::connect(server.local_addr()).unwrap();
+    /// client.write_all(b"{\"cmd\": \"pause\"}\n").unwrap();
+    /// while server.poll(&mut simulation) == 0 {}
+    /// let mut response = String::new();
+    /// BufReader::new(client).read_line(&mut response).unwrap();
+    /// 
+    /// assert!(server.paused());
+    /// assert!(!server.step(&mut simulation));
+    /// assert_eq!(0, simulation.tick());
+    /// ```
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (sender, requests) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+
+                let sender = sender.clone();
+                thread::spawn(move || serve_connection(stream, sender));
+            }
+        });
+
+        Ok(Self { address, requests, paused: false, subscription: None, latest: None })
+    }
+
+    /// Returns the address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns true if a client has paused the simulation
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Handles all requests which have arrived without waiting for more, returns the number of requests handled
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation controlled by the clients
+    pub fn poll(&mut self, simulation: &mut Simulation) -> usize {
+        let subscription = self.subscription.get_or_insert_with(|| stats::subscribe(simulation, 1));
+        while let Some(stats) = subscription.try_next() {
+            self.latest = Some(stats);
+        }
+
+        let mut handled = 0;
+
+        while let Ok(pending) = self.requests.try_recv() {
+            let response = self.handle(&pending.text, simulation);
+
+            // The client may have disconnected while waiting
+            let _ = pending.reply.send(response.to_string());
+            handled += 1;
+        }
+
+        handled
+    }
+
+    /// Handles all requests which have arrived and runs a single step of the simulation unless it is paused,
+    /// returns true if the simulation was stepped
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation controlled by the clients
+    pub fn step(&mut self, simulation: &mut Simulation) -> bool {
+        self.poll(simulation);
+
+        if self.paused {
+            return false;
+        }
+
+        simulation.step();
+
+        true
+    }
+
+    /// Handles a single request given as JSON text and returns the response
+    fn handle(&mut self, text: &str, simulation: &mut Simulation) -> Value {
+        let request: Request = match serde_json::from_str(text) {
+            Ok(request) => request,
+            Err(error) => return json!({ "ok": false, "error": error.to_string() }),
+        };
+
+        match request {
+            Request::Stats => {
+                // The record of the latest step is missing before the first step, when the statistics are not due
+                // and when the simulation was stepped without polling, then the current plants are summarized instead
+                let stats = match self.latest {
+                    Some(stats) if stats.tick == simulation.tick() => stats,
+                    _ => {
+                        let species = simulation.species().map_or(0, |species| species.living_count());
+                        TickStats::new(simulation.tick(), simulation.population(), 0, 0, simulation.mutation_rate(), species, None)
+                    }
+                };
+
+                json!({
+                    "ok": true,
+                    "tick": stats.tick,
+                    "paused": self.paused,
+                    "population": stats.population,
+                    "births": stats.births,
+                    "deaths": stats.deaths,
+                    "mean_energy": stats.mean_energy,
+                    "diversity": stats.diversity,
+                    "mutation_rate": stats.mutation_rate,
+                    "species": stats.species,
+                })
+            }
+            Request::Pause | Request::Resume => {
+                self.paused = request == Request::Pause;
+
+                json!({ "ok": true, "paused": self.paused })
+            }
+            Request::Set { upkeep, seed_cost, max_threshold, mutation_rate, mutation_strength, light_multiplier } => {
+                let mutation = (mutation_rate.is_some() || mutation_strength.is_some()).then(|| {
+                    let current = simulation.config().mutation;
+                    MutationConfig::new(mutation_rate.unwrap_or(current.rate), mutation_strength.unwrap_or(current.strength))
+                });
+
+                // Settings sent by a client must never make the next step fail
+                let update = ConfigUpdate { mutation, upkeep, seed_cost, max_threshold, light_multiplier, ..Default::default() };
+                match update.validate().and_then(|()| simulation.update_config(update)) {
+                    Ok(()) => json!({ "ok": true, "tick": simulation.tick() }),
+                    Err(error) => json!({ "ok": false, "error": error.to_string() }),
+                }
+            }
+            Request::Snapshot => {
+                let (w, h) = simulation.board().fields.size.size();
+                let plants: Vec<Value> = simulation.population()
+                    .iter()
+                    .map(|(coord, plant)| json!({ "x": coord.x, "y": coord.y, "id": plant.id().0, "energy": plant.energy, "genes": plant.genome.genes() }))
+                    .collect();
+
+                json!({ "ok": true, "tick": simulation.tick(), "width": w, "height": h, "plants": plants })
+            }
+        }
+    }
+}
+
+/// Reads the requests of a single client until it disconnects, the protocol is chosen from the first line
+fn serve_connection(stream: TcpStream, requests: mpsc::Sender<Pending>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut first = String::new();
+    if reader.read_line(&mut first)? == 0 {
+        return Ok(());
+    }
+
+    // WebSocket clients start with an http upgrade request
+    if first.starts_with("GET ") {
+        let key = read_websocket_key(&mut reader)?;
+        write!(writer, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n", websocket_accept(&key))?;
+
+        while let Some(text) = read_message(&mut reader, &mut writer)? {
+            let Some(response) = request(&requests, text) else {
+                return Ok(());
+            };
+            write_frame(&mut writer, OPCODE_TEXT, response.as_bytes())?;
+        }
+
+        return Ok(());
+    }
+
+    // Plain TCP clients send a request on every line
+    let mut line = first;
+    loop {
+        if !line.trim().is_empty() {
+            let Some(response) = request(&requests, line.trim().to_string()) else {
+                return Ok(());
+            };
+            writeln!(writer, "{}", response)?;
+        }
+
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Sends a request to the server and waits for the response, returns None if the server has been dropped
+fn request(requests: &mpsc::Sender<Pending>, text: String) -> Option<String> {
+    let (reply, response) = mpsc::channel();
+    requests.send(Pending { text, reply }).ok()?;
+
+    response.recv().ok()
+}
+
+/// Reads the headers of a WebSocket upgrade request and returns the key of the client
+fn read_websocket_key<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut key = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Sec-WebSocket-Key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    key.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "The upgrade request has no WebSocket key"))
+}
+
+/// Finds the accept key the server must answer the key of a WebSocket client with
+fn websocket_accept(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()))
+}
+
+/// Reads WebSocket frames until a full text or binary message has arrived, answering pings on the way.
+/// Returns None once the client has closed the connection
+fn read_message<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<Option<String>> {
+    let mut message = Vec::new();
+
+    loop {
+        let (fin, opcode, payload) = read_frame(reader)?;
+
+        match opcode {
+            OPCODE_CONTINUATION | OPCODE_TEXT | OPCODE_BINARY => {
+                if message.len() as u64 + payload.len() as u64 > MAX_MESSAGE {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "The message is too large"));
+                }
+
+                message.extend_from_slice(&payload);
+                if fin {
+                    return Ok(Some(String::from_utf8_lossy(&message).into_owned()));
+                }
+            }
+            OPCODE_PING => write_frame(writer, OPCODE_PONG, &payload)?,
+            OPCODE_CLOSE => {
+                write_frame(writer, OPCODE_CLOSE, &payload)?;
+                return Ok(None);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Reads a single WebSocket frame and returns if it is the final frame, its opcode and its unmasked payload
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut header = [0; 2];
+    reader.read_exact(&mut header)?;
+
+    let fin = header[0] & 0x80 != 0;
+    let opcode = header[0] & 0x0F;
+    let masked = header[1] & 0x80 != 0;
+
+    let len = match header[1] & 0x7F {
+        126 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+
+    if len > MAX_MESSAGE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The frame is too large"));
+    }
+
+    let mut mask = [0; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    for (index, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+
+    Ok((fin, opcode, payload))
+}
+
+/// Writes a single unmasked WebSocket frame holding a full message
+fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Encodes bytes as base64 with padding
+fn base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let value = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+        for position in 0..4 {
+            if position <= chunk.len() {
+                encoded.push(BASE64[(value >> (18 - 6 * position)) as usize & 0x3F] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Calculates the SHA-1 hash of some bytes, only used for the WebSocket handshake
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad the message to a whole number of blocks ending with its length in bits
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for index in 16..80 {
+            words[index] = (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut hash = [0; 20];
+    for (bytes, value) in hash.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(3, 2).light_uniform(1.0).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.5, 0.25]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    fn server() -> RemoteServer {
+        let (_, requests) = mpsc::channel();
+
+        RemoteServer { address: "127.0.0.1:0".parse().unwrap(), requests, paused: false, subscription: None, latest: None }
+    }
+
+    #[test]
+    fn sha1_known() {
+        assert_eq!("a9993e364706816aba3e25717850c26c9cd0d89d", sha1(b"abc").iter().map(|byte| format!("{:02x}", byte)).collect::<String>());
+        assert_eq!("da39a3ee5e6b4b0d3255bfef95601890afd80709", sha1(b"").iter().map(|byte| format!("{:02x}", byte)).collect::<String>());
+    }
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYg==", base64(b"foob"));
+    }
+
+    #[test]
+    fn websocket_accept_key() {
+        // The example from the WebSocket specification
+        assert_eq!("s3pPLMBiTxaQ9kYGzzhZRbK+xOo=", websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="));
+    }
+
+    #[test]
+    fn read_websocket_key_headers() {
+        let mut headers = io::Cursor::new("Host: localhost\r\nsec-websocket-key: abc==\r\n\r\n");
+
+        assert_eq!("abc==", read_websocket_key(&mut headers).unwrap());
+        assert!(read_websocket_key(&mut io::Cursor::new("Host: localhost\r\n\r\n")).is_err());
+    }
+
+    #[test]
+    fn frame_roundtrip() {
+        for len in [5, 200, 70000] {
+            let payload = vec![7; len];
+            let mut frame = Vec::new();
+            write_frame(&mut frame, OPCODE_TEXT, &payload).unwrap();
+
+            assert_eq!((true, OPCODE_TEXT, payload), read_frame(&mut io::Cursor::new(frame)).unwrap());
+        }
+    }
+
+    #[test]
+    fn read_message_masked_fragments() {
+        // A masked text frame split into two fragments with a ping in between
+        let mask = [1, 2, 3, 4];
+        let masked = |text: &[u8]| text.iter().enumerate().map(|(index, byte)| byte ^ mask[index % 4]).collect::<Vec<u8>>();
+        let mut input = vec![OPCODE_TEXT, 0x80 | 3];
+        input.extend_from_slice(&mask);
+        input.extend(masked(b"hel"));
+        input.extend_from_slice(&[0x80 | OPCODE_PING, 0]);
+        input.extend_from_slice(&[0x80 | OPCODE_CONTINUATION, 0x80 | 2]);
+        input.extend_from_slice(&mask);
+        input.extend(masked(b"lo"));
+        input.extend_from_slice(&[0x80 | OPCODE_CLOSE, 0]);
+        let mut reader = io::Cursor::new(input);
+        let mut output = Vec::new();
+
+        assert_eq!(Some("hello".to_string()), read_message(&mut reader, &mut output).unwrap());
+        assert_eq!(None, read_message(&mut reader, &mut output).unwrap());
+        assert_eq!(vec![0x80 | OPCODE_PONG, 0, 0x80 | OPCODE_CLOSE, 0], output);
+    }
+
+    #[test]
+    fn remote_server_handle_stats() {
+        let mut simulation = simulation();
+        let response = server().handle("{\"cmd\": \"stats\"}", &mut simulation);
+
+        assert_eq!(json!(true), response["ok"]);
+        assert_eq!(json!(1), response["population"]);
+        assert_eq!(json!(50.0), response["mean_energy"]);
+        assert_eq!(json!(false), response["paused"]);
+    }
+
+    #[test]
+    fn remote_server_handle_stats_subscribed() {
+        let board = BoardBuilder::new().size(3, 2).light_uniform(0.0).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[0.5, 0.25]).unwrap()));
+        let mut simulation = Simulation::new(board, population, SimulationConfig { upkeep: 10, ..Default::default() }).unwrap();
+        let mut server = server();
+        assert!(server.step(&mut simulation));
+        server.poll(&mut simulation);
+        let response = server.handle("{\"cmd\": \"stats\"}", &mut simulation);
+
+        // The plant in the dark dies in the first step which only the record of the step knows about
+        assert_eq!(json!(1), response["tick"]);
+        assert_eq!(json!(0), response["population"]);
+        assert_eq!(json!(1), response["deaths"]);
+    }
+
+    #[test]
+    fn remote_server_handle_pause() {
+        let mut simulation = simulation();
+        let mut server = server();
+        server.handle("{\"cmd\": \"pause\"}", &mut simulation);
+
+        assert!(server.paused());
+        assert!(!server.step(&mut simulation));
+
+        server.handle("{\"cmd\": \"resume\"}", &mut simulation);
+
+        assert!(server.step(&mut simulation));
+        assert_eq!(1, simulation.tick());
+    }
+
+    #[test]
+    fn remote_server_handle_set() {
+        let mut simulation = simulation();
+        let response = server().handle("{\"cmd\": \"set\", \"upkeep\": 5, \"mutation_rate\": 0.5}", &mut simulation);
+
+        assert_eq!(json!(true), response["ok"]);
+        assert_eq!(5, simulation.config().upkeep);
+        assert_eq!(MutationConfig::new(0.5, 0.1), simulation.config().mutation);
+        assert_eq!(1, simulation.config_log().len());
+    }
+
+    #[test]
+    fn remote_server_handle_set_invalid() {
+        let mut simulation = simulation();
+        for request in [
+            "{\"cmd\": \"set\", \"upkeep\": 5, \"mutation_strength\": -0.1}",
+            "{\"cmd\": \"set\", \"mutation_rate\": 1.5}",
+            "{\"cmd\": \"set\", \"mutation_strength\": 1e39}",
+            "{\"cmd\": \"set\", \"seed_cost\": 4294967295}",
+        ] {
+            let response = server().handle(request, &mut simulation);
+
+            assert_eq!(json!(false), response["ok"], "{}", request);
+            assert!(response["error"].is_string());
+        }
+        simulation.step();
+
+        assert_eq!(SimulationConfig::default().upkeep, simulation.config().upkeep);
+        assert_eq!(MutationConfig::default(), simulation.config().mutation);
+        assert!(simulation.config_log().is_empty());
+    }
+
+    #[test]
+    fn remote_server_handle_snapshot() {
+        let mut simulation = simulation();
+        let response = server().handle("{\"cmd\": \"snapshot\"}", &mut simulation);
+
+        assert_eq!(json!(3), response["width"]);
+        assert_eq!(json!([{ "x": 1, "y": 1, "id": 0, "energy": 50, "genes": [0.5, 0.25] }]), response["plants"]);
+    }
+
+    #[test]
+    fn remote_server_handle_error() {
+        let mut simulation = simulation();
+
+        assert_eq!(json!(false), server().handle("{\"cmd\": \"explode\"}", &mut simulation)["ok"]);
+        assert_eq!(json!(false), server().handle("not json", &mut simulation)["ok"]);
+    }
+
+    #[test]
+    fn remote_server_websocket() {
+        let mut simulation = simulation();
+        let mut server = RemoteServer::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr()).unwrap();
+        client.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n").unwrap();
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let mut handshake = String::new();
+        while !handshake.ends_with("\r\n\r\n") {
+            reader.read_line(&mut handshake).unwrap();
+        }
+
+        assert!(handshake.starts_with("HTTP/1.1 101"));
+        assert!(handshake.contains("Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+
+        // Clients must mask their frames, a zero mask keeps the payload readable
+        let text = b"{\"cmd\": \"stats\"}";
+        let mut frame = vec![0x80 | OPCODE_TEXT, 0x80 | text.len() as u8, 0, 0, 0, 0];
+        frame.extend_from_slice(text);
+        client.write_all(&frame).unwrap();
+        while server.poll(&mut simulation) == 0 {
+            thread::yield_now();
+        }
+        let (_, opcode, payload) = read_frame(&mut reader).unwrap();
+        let response: Value = serde_json::from_slice(&payload).unwrap();
+
+        assert_eq!(OPCODE_TEXT, opcode);
+        assert_eq!(json!(1), response["population"]);
+    }
+}
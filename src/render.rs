@@ -0,0 +1,1627 @@
+use crate::board::{Board, Modifier, Rect, Size, Terrain};
+use crate::dirty::DirtyCells;
+use crate::field::Field;
+use crate::genome::Genome;
+use crate::occupancy::OccupancyLayer;
+use crate::population::{Plant, PlantId, Population};
+use crate::simulation::Simulation;
+use crate::visual::{self, GeneHue, GenomeColoring};
+
+/// The color of a cell without any light
+const DARK: [u8; 3] = [16, 12, 8];
+/// The color of a cell with full light
+const BRIGHT: [u8; 3] = [232, 220, 170];
+/// The color of a cell of rock
+const ROCK: [u8; 4] = [96, 96, 104, 255];
+/// The color of a cell of open water
+const WATER: [u8; 4] = [40, 80, 160, 255];
+/// The color of a plant without a species
+const UNKNOWN_SPECIES: [u8; 4] = [128, 128, 128, 255];
+/// The color of a plant not related to the plant picked in the relatedness mode
+const UNRELATED: [u8; 4] = [56, 56, 56, 255];
+/// The brightness left of the cells outside the active region of the simulation
+const INACTIVE_DIM: f32 = 0.4;
+/// The golden ratio conjugate, multiplying by it spreads consecutive species ids evenly around the color wheel
+const GOLDEN: f32 = 0.618034;
+
+/// Finds the background color of a cell from the light in it, the light is clamped between 0 and 1
+/// 
+/// # Parameters
+/// 
+/// light: The light in the cell
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::render;
+/// 
+/// assert_eq!(render::light_color(1.0), render::light_color(2.0));
+/// assert_ne!(render::light_color(0.0), render::light_color(1.0));
+/// ```
+pub fn light_color(light: f32) -> [u8; 4] {
+    let light = if light.is_nan() { 0.0 } else { light.clamp(0.0, 1.0) };
+    let mix = |dark: u8, bright: u8| (dark as f32 + (bright as f32 - dark as f32) * light).round() as u8;
+
+    [mix(DARK[0], BRIGHT[0]), mix(DARK[1], BRIGHT[1]), mix(DARK[2], BRIGHT[2]), 255]
+}
+
+/// Finds the color of a plant from its genome such that similar genomes get similar colors
+/// 
+/// # Parameters
+/// 
+/// genome: The genome of the plant
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{genome::Genome, render};
+/// 
+/// let genome1 = Genome::new(&[0.5, 0.5]).unwrap();
+/// let genome2 = Genome::new(&[0.1, 0.9]).unwrap();
+/// 
+/// assert_ne!(render::plant_color(&genome1), render::plant_color(&genome2));
+/// ```
+pub fn plant_color(genome: &Genome) -> [u8; 4] {
+    let [r, g, b] = visual::genome_color(genome);
+
+    [r, g, b, 255]
+}
+
+/// Finds the color of a cell where plants cannot grow, returns None for open ground
+/// 
+/// # Parameters
+/// 
+/// terrain: The kind of ground in the cell
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::Terrain, render};
+/// 
+/// assert_eq!(None, render::terrain_color(Terrain::Open));
+/// assert_ne!(render::terrain_color(Terrain::Rock), render::terrain_color(Terrain::Water));
+/// ```
+pub fn terrain_color(terrain: Terrain) -> Option<[u8; 4]> {
+    match terrain {
+        Terrain::Open => None,
+        Terrain::Rock => Some(ROCK),
+        Terrain::Water => Some(WATER),
+    }
+}
+
+/// Renders the board with a population on top as rgba pixels, one pixel per cell with the rows in order,
+/// empty cells are colored by their light, blocked cells by their terrain and plants are colored by their genome
+/// 
+/// # Parameters
+/// 
+/// board: The board to draw
+/// population: The plants to draw on the board
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, population::Population, render};
+/// 
+/// let size = board::Size::new(2, 2);
+/// let fields = board::Fields::new(size, &[0.0, 0.5, 0.5, 1.0]).unwrap();
+/// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+/// let pixels = render::render_rgba(&board, &Population::new(size));
+/// 
+/// assert_eq!(4 * 4, pixels.len());
+/// assert_eq!(render::light_color(1.0), pixels[12..16]);
+/// ```
+pub fn render_rgba(board: &Board, population: &Population) -> Vec<u8> {
+    render_rgba_with(board, population, &visual::genome_color)
+}
+
+/// Renders the board with a population on top as rgba pixels like render_rgba, but with the plants colored by
+/// a custom mapping from their genome
+/// 
+/// # Parameters
+/// 
+/// board: The board to draw
+/// population: The plants to draw on the board
+/// coloring: Finds the color of a plant from its genome
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, render};
+/// 
+/// let board = board::BoardBuilder::new().size(1, 1).light_uniform(1.0).build().unwrap();
+/// let mut population = Population::new(board.fields.size);
+/// population.insert(board::Coord::new(0, 0), Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap()));
+/// let pixels = render::render_rgba_with(&board, &population, &|_: &Genome| [1, 2, 3]);
+/// 
+/// assert_eq!(&[1, 2, 3, 255], &pixels[..]);
+/// ```
+pub fn render_rgba_with<C: GenomeColoring + ?Sized>(board: &Board, population: &Population, coloring: &C) -> Vec<u8> {
+    board.fields.light.iter()
+        .zip(board.fields.terrain.iter())
+        .zip(population.cells())
+        .flat_map(|((&light, &terrain), cell)| cell_color(light, terrain, cell, coloring))
+        .collect()
+}
+
+/// Finds the color of a single cell as drawn by render_rgba_with
+fn cell_color<C: GenomeColoring + ?Sized>(light: f32, terrain: Terrain, cell: Option<&Plant>, coloring: &C) -> [u8; 4] {
+    match cell {
+        Some(plant) => {
+            let [r, g, b] = coloring.color(&plant.genome);
+            [r, g, b, 255]
+        }
+        None => terrain_color(terrain).unwrap_or_else(|| light_color(light)),
+    }
+}
+
+/// The rgba pixels of a board kept between frames such that only the cells which changed have to be drawn again,
+/// the pixels are the same as the ones from render_rgba
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+    /// The size of the board which was drawn
+    size: Size,
+    /// The pixels of every cell with the rows in order
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    /// Creates a new canvas with the entire board drawn
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board to draw
+    /// population: The plants to draw on the board
+    pub fn new(board: &Board, population: &Population) -> Self {
+        Self { size: board.fields.size, pixels: render_rgba(board, population) }
+    }
+
+    /// Returns the size of the board which was drawn
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the pixels of every cell with the rows in order
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Draws the dirty cells again and returns the tiles which changed such that only those have to be uploaded,
+    /// if the size of the board has changed the entire board is drawn and returned as a single region
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board to draw
+    /// population: The plants to draw on the board
+    /// dirty: The cells which may have changed since the last update
+    /// tile: The width and height of the returned tiles in cells
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Rect}, dirty::DirtyCells, genome::Genome, population::{Plant, Population}, render::{self, Canvas}};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// let mut canvas = Canvas::new(&board, &population);
+    /// population.insert(Coord::new(5, 6), Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut dirty = DirtyCells::new(board.fields.size);
+    /// dirty.mark(53);
+    /// 
+    /// assert_eq!(vec![Rect::new(4, 4, 4, 4)], canvas.update(&board, &population, &dirty, 4));
+    /// assert_eq!(render::render_rgba(&board, &population), canvas.pixels());
+    /// ```
+    pub fn update(&mut self, board: &Board, population: &Population, dirty: &DirtyCells, tile: usize) -> Vec<Rect> {
+        let size = board.fields.size;
+        if size != self.size || dirty.size() != size || dirty.is_all() {
+            *self = Self::new(board, population);
+            let (w, h) = size.size();
+
+            return if size.is_empty() { Vec::new() } else { vec![Rect::new(0, 0, w, h)] };
+        }
+
+        for index in dirty.cells() {
+            let color = cell_color(board.fields.light[index], board.fields.terrain[index], population.plant(index), &visual::genome_color);
+            self.pixels[index * 4..index * 4 + 4].copy_from_slice(&color);
+        }
+
+        dirty.regions(tile)
+    }
+
+    /// Copies the pixels of a rectangle of the board with the rows in order, such as a tile to upload,
+    /// the rectangle is clamped to the board
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle to copy
+    pub fn region(&self, rect: Rect) -> Vec<u8> {
+        let rect = rect.clamp(self.size);
+        let (w, _) = self.size.size();
+
+        (rect.y..rect.y + rect.h)
+            .flat_map(|y| {
+                let start = (rect.x + y * w) * 4;
+                self.pixels[start..start + rect.w * 4].iter().copied()
+            })
+            .collect()
+    }
+}
+
+/// What the renderer shows in every cell, blocked terrain is drawn with its own colors in every mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RenderMode {
+    /// Plants colored by their genome on top of the light
+    #[default]
+    GenomeColor,
+    /// Plants colored by their energy on top of the light
+    Energy,
+    /// Plants colored by their age on top of the light
+    Age,
+    /// Plants colored by their species on top of the light, plants without a species are gray
+    SpeciesId,
+    /// Plants sharing an ancestor with the picked plant within the generations of the style colored brighter the closer
+    /// they are related, other plants are dark gray
+    Relatedness,
+    /// The light after shadows for every cell, plants are not drawn
+    LightField,
+    /// Plants colored by their genome on top of the light, darkened by the shadows falling on every cell
+    Shadows,
+    /// The water in every cell, plants are not drawn
+    WaterField,
+    /// The toxin released by the plants in every cell, plants are not drawn
+    ToxinField,
+    /// The fraction of the tracked steps every cell held a plant, plants are not drawn and every cell has the lowest color
+    /// if occupancy is not tracked
+    Occupancy,
+    /// The plants which died in every cell for every tracked step, plants are not drawn and every cell has the lowest color
+    /// if occupancy is not tracked
+    Mortality,
+}
+
+impl RenderMode {
+    /// All modes in the order they are cycled through
+    pub const ALL: [RenderMode; 11] = [
+        RenderMode::GenomeColor, RenderMode::Energy, RenderMode::Age, RenderMode::SpeciesId, RenderMode::Relatedness,
+        RenderMode::LightField, RenderMode::Shadows, RenderMode::WaterField, RenderMode::ToxinField, RenderMode::Occupancy, RenderMode::Mortality,
+    ];
+
+    /// Returns the mode after this one, the last mode is followed by the first
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::RenderMode;
+    /// 
+    /// assert_eq!(RenderMode::Energy, RenderMode::GenomeColor.next());
+    /// assert_eq!(RenderMode::GenomeColor, RenderMode::Mortality.next());
+    /// ```
+    pub fn next(&self) -> Self {
+        let position = Self::ALL.iter().position(|mode| mode == self).unwrap_or(0);
+
+        Self::ALL[(position + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the name of the mode in capital letters
+    pub fn name(&self) -> &'static str {
+        match self {
+            RenderMode::GenomeColor => "GENOME",
+            RenderMode::Energy => "ENERGY",
+            RenderMode::Age => "AGE",
+            RenderMode::SpeciesId => "SPECIES",
+            RenderMode::Relatedness => "RELATEDNESS",
+            RenderMode::LightField => "LIGHT",
+            RenderMode::Shadows => "SHADOWS",
+            RenderMode::WaterField => "WATER",
+            RenderMode::ToxinField => "TOXIN",
+            RenderMode::Occupancy => "OCCUPANCY",
+            RenderMode::Mortality => "MORTALITY",
+        }
+    }
+}
+
+/// Maps values to colors by blending evenly spaced colors between a low and a high value
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorRamp {
+    /// The value mapped to the first color, lower values get the first color as well
+    pub low: f32,
+    /// The value mapped to the last color, higher values get the last color as well
+    pub high: f32,
+    /// The rgb colors spread evenly from low to high
+    pub stops: Vec<[u8; 3]>,
+}
+
+impl ColorRamp {
+    /// Creates a new color ramp
+    /// 
+    /// # Parameters
+    /// 
+    /// low: The value mapped to the first color
+    /// high: The value mapped to the last color
+    /// stops: The rgb colors spread evenly from low to high
+    pub fn new(low: f32, high: f32, stops: &[[u8; 3]]) -> Self {
+        Self { low, high, stops: stops.to_vec() }
+    }
+
+    /// Finds the color of a value, a ramp without colors gives black and NaN gives the first color
+    /// 
+    /// # Parameters
+    /// 
+    /// value: The value to color
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::ColorRamp;
+    /// 
+    /// let ramp = ColorRamp::new(0.0, 10.0, &[[0, 0, 0], [200, 100, 0], [200, 200, 200]]);
+    /// 
+    /// assert_eq!([100, 50, 0, 255], ramp.color(2.5));
+    /// assert_eq!([200, 200, 200, 255], ramp.color(20.0));
+    /// ```
+    pub fn color(&self, value: f32) -> [u8; 4] {
+        let (first, last) = match (self.stops.first(), self.stops.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return [0, 0, 0, 255],
+        };
+
+        let range = self.high - self.low;
+        let t = if range > 0.0 { (value - self.low) / range } else { 0.0 };
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        if t >= 1.0 {
+            return [last[0], last[1], last[2], 255];
+        }
+
+        let position = t * (self.stops.len() - 1) as f32;
+        let segment = position as usize;
+        let (from, to) = (self.stops[segment], self.stops.get(segment + 1).copied().unwrap_or(first));
+        let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * position.fract()).round() as u8;
+
+        [mix(from[0], to[0]), mix(from[1], to[1]), mix(from[2], to[2]), 255]
+    }
+}
+
+/// The colors of the viridis ramp from dark blue over green to yellow, evenly spaced
+const VIRIDIS: [[u8; 3]; 10] = [
+    [68, 1, 84], [72, 40, 120], [62, 73, 137], [49, 104, 142], [38, 130, 142],
+    [31, 158, 137], [53, 183, 121], [110, 206, 88], [181, 222, 43], [253, 231, 37],
+];
+/// The colors of the cividis ramp from dark blue over grey to yellow, evenly spaced
+const CIVIDIS: [[u8; 3]; 10] = [
+    [0, 32, 77], [0, 51, 111], [57, 72, 107], [87, 93, 109], [112, 113, 115],
+    [138, 135, 121], [166, 157, 117], [196, 181, 108], [228, 207, 91], [255, 234, 70],
+];
+/// The eight colors of Okabe and Ito, black is left out as it cannot be told apart from the dark cells
+const OKABE_ITO: [[u8; 3]; 7] = [
+    [230, 159, 0], [86, 180, 233], [0, 158, 115], [240, 228, 66], [0, 114, 178], [213, 94, 0], [204, 121, 167],
+];
+/// The colors of Okabe and Ito ordered from dark to bright such that they form a ramp for the modes showing values
+const OKABE_ITO_RAMP: [[u8; 3]; 5] = [[0, 114, 178], [86, 180, 233], [0, 158, 115], [240, 228, 66], [230, 159, 0]];
+
+/// The colors the modes showing values and the plants are drawn with. The built-in palettes besides the classic one
+/// stay readable with color blindness and in grayscale prints
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Palette {
+    /// Every mode has its own ramp and the plants are colored by the hue of their genome
+    #[default]
+    Classic,
+    /// A ramp from dark blue over green to yellow which is perceived evenly
+    Viridis,
+    /// A ramp from dark blue over grey to yellow which is perceived the same with red-green color blindness
+    Cividis,
+    /// The colors of Okabe and Ito which stay distinct for every kind of color blindness, the plants and species
+    /// get one of the colors each instead of a blend
+    OkabeIto,
+    /// A ramp through custom colors spread evenly
+    Custom(Vec<[u8; 3]>),
+}
+
+impl Palette {
+    /// All built-in palettes in the order they are switched through
+    pub const ALL: [Palette; 4] = [Palette::Classic, Palette::Viridis, Palette::Cividis, Palette::OkabeIto];
+
+    /// Creates a palette blending through custom colors
+    /// 
+    /// # Parameters
+    /// 
+    /// stops: The rgb colors spread evenly from the lowest to the highest value
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::Palette;
+    /// 
+    /// let palette = Palette::from_stops(&[[0, 0, 0], [255, 255, 255]]);
+    /// 
+    /// assert_eq!([128, 128, 128, 255], palette.ramp(0.0, 1.0).unwrap().color(0.5));
+    /// ```
+    pub fn from_stops(stops: &[[u8; 3]]) -> Self {
+        Palette::Custom(stops.to_vec())
+    }
+
+    /// Returns the colors of the ramp of the palette, None for the classic palette where every mode has its own ramp
+    pub fn stops(&self) -> Option<&[[u8; 3]]> {
+        match self {
+            Palette::Classic => None,
+            Palette::Viridis => Some(&VIRIDIS),
+            Palette::Cividis => Some(&CIVIDIS),
+            Palette::OkabeIto => Some(&OKABE_ITO_RAMP),
+            Palette::Custom(stops) => Some(stops),
+        }
+    }
+
+    /// Creates a color ramp of the palette between two values, None for the classic palette
+    /// 
+    /// # Parameters
+    /// 
+    /// low: The value mapped to the first color
+    /// high: The value mapped to the last color
+    pub fn ramp(&self, low: f32, high: f32) -> Option<ColorRamp> {
+        self.stops().map(|stops| ColorRamp::new(low, high, stops))
+    }
+
+    /// Finds the color of a category such as a species, consecutive categories get colors far apart.
+    /// Returns None for the classic palette
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The number of the category
+    pub fn category(&self, index: u64) -> Option<[u8; 4]> {
+        match self {
+            Palette::OkabeIto => {
+                let [r, g, b] = OKABE_ITO[(index % OKABE_ITO.len() as u64) as usize];
+                Some([r, g, b, 255])
+            }
+            _ => self.ramp(0.0, 1.0).map(|ramp| ramp.color((index as f32 * GOLDEN).fract())),
+        }
+    }
+
+    /// Returns the palette after this one in ALL, custom palettes go back to the classic palette
+    pub fn next(&self) -> Self {
+        let position = Self::ALL.iter().position(|palette| palette == self).unwrap_or(Self::ALL.len() - 1);
+
+        Self::ALL[(position + 1) % Self::ALL.len()].clone()
+    }
+
+    /// Returns the name of the palette in capital letters
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Classic => "CLASSIC",
+            Palette::Viridis => "VIRIDIS",
+            Palette::Cividis => "CIVIDIS",
+            Palette::OkabeIto => "OKABE-ITO",
+            Palette::Custom(_) => "CUSTOM",
+        }
+    }
+}
+
+impl GenomeColoring for Palette {
+    /// Colors the genomes like genome_color for the classic palette, otherwise the hue of the genome picks
+    /// a place on the ramp, or one of the colors of Okabe and Ito
+    fn color(&self, genome: &Genome) -> [u8; 3] {
+        let hue = GeneHue::default().hue(genome);
+        let color = match self {
+            Palette::Classic => return visual::genome_color(genome),
+            Palette::OkabeIto => self.category((hue * OKABE_ITO.len() as f32) as u64),
+            _ => self.ramp(0.0, 1.0).map(|ramp| ramp.color(hue)),
+        };
+        let [r, g, b, _] = color.unwrap_or([0, 0, 0, 255]);
+
+        [r, g, b]
+    }
+}
+
+/// The render mode and the color ramps used by the modes showing values
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderStyle {
+    /// What is shown in every cell
+    pub mode: RenderMode,
+    /// The colors of the energy of plants
+    pub energy: ColorRamp,
+    /// The colors of the age of plants
+    pub age: ColorRamp,
+    /// The colors of the light after shadows
+    pub light: ColorRamp,
+    /// The colors of the water
+    pub water: ColorRamp,
+    /// The colors of the toxin
+    pub toxin: ColorRamp,
+    /// The colors of the fraction of the time cells held a plant
+    pub occupancy: ColorRamp,
+    /// The colors of the deaths in cells for every step
+    pub mortality: ColorRamp,
+    /// The plant whose relatives are shown in the relatedness mode, every plant is unrelated if this is None
+    pub related_to: Option<PlantId>,
+    /// The largest number of generations to an ancestor shared with the picked plant in the relatedness mode
+    pub generations: usize,
+    /// The colors of the relatedness to the picked plant from the most distant relatives at 0 to the plant itself at 1
+    pub relatedness: ColorRamp,
+    /// The colors of the plants and the species, change it with set_palette to recolor the ramps as well
+    pub palette: Palette,
+}
+
+impl Default for RenderStyle {
+    fn default() -> Self {
+        Self {
+            mode: RenderMode::default(),
+            energy: ColorRamp::new(0.0, 1000.0, &[[40, 0, 60], [200, 40, 40], [250, 220, 60]]),
+            age: ColorRamp::new(0.0, 500.0, &[[20, 40, 120], [60, 180, 160], [240, 240, 240]]),
+            light: ColorRamp::new(0.0, 1.0, &[DARK, BRIGHT]),
+            water: ColorRamp::new(0.0, 1.0, &[[230, 220, 200], [60, 140, 220], [10, 30, 120]]),
+            toxin: ColorRamp::new(0.0, 2.0, &[[20, 30, 20], [120, 60, 140], [230, 80, 200]]),
+            occupancy: ColorRamp::new(0.0, 1.0, &[[10, 10, 30], [40, 120, 90], [240, 250, 140]]),
+            mortality: ColorRamp::new(0.0, 0.1, &[[10, 10, 30], [160, 30, 40], [250, 200, 60]]),
+            related_to: None,
+            generations: 4,
+            relatedness: ColorRamp::new(0.0, 1.0, &[[70, 40, 90], [220, 90, 60], [255, 240, 160]]),
+            palette: Palette::Classic,
+        }
+    }
+}
+
+impl RenderStyle {
+    /// Changes the palette and gives every color ramp the colors of the palette, the values the ramps go between are kept.
+    /// The classic palette gives the ramps their default colors back
+    /// 
+    /// # Parameters
+    /// 
+    /// palette: The new palette
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::{Palette, RenderStyle};
+    /// 
+    /// let mut style = RenderStyle::default();
+    /// style.set_palette(Palette::Viridis);
+    /// 
+    /// assert_eq!(Palette::Viridis.ramp(0.0, 1000.0), Some(style.energy.clone()));
+    /// 
+    /// style.set_palette(Palette::Classic);
+    /// 
+    /// assert_eq!(RenderStyle::default(), style);
+    /// ```
+    pub fn set_palette(&mut self, palette: Palette) {
+        let defaults = Self::default();
+        let ramps = [
+            (&mut self.energy, defaults.energy),
+            (&mut self.age, defaults.age),
+            (&mut self.light, defaults.light),
+            (&mut self.water, defaults.water),
+            (&mut self.toxin, defaults.toxin),
+            (&mut self.occupancy, defaults.occupancy),
+            (&mut self.mortality, defaults.mortality),
+            (&mut self.relatedness, defaults.relatedness),
+        ];
+        for (ramp, default) in ramps {
+            ramp.stops = palette.stops().map_or(default.stops, |stops| stops.to_vec());
+        }
+
+        self.palette = palette;
+    }
+}
+
+/// Renders a simulation as rgba pixels in a render mode, one pixel per cell with the rows in order.
+/// In the genome mode the pixels are the same as the ones from render_rgba
+/// 
+/// # Parameters
+/// 
+/// simulation: The simulation to draw
+/// style: The render mode and the color ramps of the modes
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+/// use evolution_plants::render::{self, RenderMode, RenderStyle};
+/// 
+/// let board = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+/// let mut population = Population::new(board.fields.size);
+/// population.insert(Coord::new(0, 0), Plant::new(250, Genome::new(&[0.5, 0.5]).unwrap()));
+/// let simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+/// let style = RenderStyle { mode: RenderMode::Energy, ..Default::default() };
+/// let pixels = render::render_style(&simulation, &style);
+/// 
+/// assert_eq!(style.energy.color(250.0), pixels[0..4]);
+/// assert_eq!(render::light_color(1.0), pixels[4..8]);
+/// ```
+pub fn render_style(simulation: &Simulation, style: &RenderStyle) -> Vec<u8> {
+    let board = simulation.board();
+    let population = simulation.population();
+
+    // The relatives are found once for the whole board, the closest relatives get the highest values
+    let relatives = match (style.mode, style.related_to) {
+        (RenderMode::Relatedness, Some(id)) => simulation.phylogeny().relatives(id, style.generations),
+        _ => Default::default(),
+    };
+
+    let color_plant = |plant: &Plant| -> [u8; 4] {
+        match style.mode {
+            RenderMode::Energy => style.energy.color(plant.energy as f32),
+            RenderMode::Age => style.age.color(plant.age as f32),
+            RenderMode::SpeciesId => match simulation.species().and_then(|tracker| tracker.species_of(plant.id())) {
+                Some(species) => style.palette.category(species.0).unwrap_or_else(|| {
+                    let [r, g, b] = visual::hsv_to_rgb((species.0 as f32 * GOLDEN).fract(), 0.8, 0.9);
+                    [r, g, b, 255]
+                }),
+                None => UNKNOWN_SPECIES,
+            },
+            RenderMode::Relatedness => match relatives.get(&plant.id()) {
+                Some(&distance) => style.relatedness.color(1.0 - distance as f32 / (style.generations + 1) as f32),
+                None => UNRELATED,
+            },
+            _ => palette_color(&style.palette, &plant.genome),
+        }
+    };
+
+    let heat = match style.mode {
+        RenderMode::Occupancy => simulation.occupancy().map(|occupancy| occupancy.layer(OccupancyLayer::Occupancy)),
+        RenderMode::Mortality => simulation.occupancy().map(|occupancy| occupancy.layer(OccupancyLayer::Mortality)),
+        _ => None,
+    };
+    let heat_value = |index: usize| heat.as_ref().map_or(0.0, |heat| heat[index]);
+
+    let mut pixels: Vec<u8> = board.fields.terrain.iter()
+        .zip(population.cells())
+        .enumerate()
+        .flat_map(|(index, (&terrain, cell))| {
+            if let Some(color) = terrain_color(terrain) {
+                return color;
+            }
+
+            match (style.mode, cell) {
+                (RenderMode::LightField, _) => style.light.color(simulation.light()[index]),
+                (RenderMode::WaterField, _) => style.water.color(simulation.water().values()[index]),
+                (RenderMode::ToxinField, _) => style.toxin.color(simulation.toxins().values()[index]),
+                (RenderMode::Occupancy, _) => style.occupancy.color(heat_value(index)),
+                (RenderMode::Mortality, _) => style.mortality.color(heat_value(index)),
+                (RenderMode::Shadows, cell) => {
+                    let color = cell.map_or_else(|| light_color(board.fields.light[index]), |plant| palette_color(&style.palette, &plant.genome));
+                    let light = board.fields.light[index];
+                    let remaining = if light > 0.0 { (simulation.light()[index] / light).clamp(0.0, 1.0) } else { 1.0 };
+
+                    [0, 1, 2, 3].map(|channel| if channel == 3 { color[3] } else { (color[channel] as f32 * remaining).round() as u8 })
+                }
+                (_, Some(plant)) => color_plant(plant),
+                (_, None) => light_color(board.fields.light[index]),
+            }
+        })
+        .collect();
+
+    // The frozen part of the board is dimmed such that the active region stands out
+    if let Some(region) = simulation.active_region() {
+        dim_outside(&mut pixels, board.fields.size, region, INACTIVE_DIM);
+    }
+
+    pixels
+}
+
+/// Finds the rgba color of a plant from its genome in a palette
+fn palette_color(palette: &Palette, genome: &Genome) -> [u8; 4] {
+    let [r, g, b] = palette.color(genome);
+
+    [r, g, b, 255]
+}
+
+/// Darkens the rgba pixels of the cells outside a rectangle, the alpha is kept
+/// 
+/// # Parameters
+/// 
+/// pixels: The pixels of every cell with the rows in order
+/// size: The size of the board
+/// region: The rectangle which is not darkened
+/// brightness: The fraction of the brightness left of the cells outside the rectangle
+/// 
+/// # Panics
+/// 
+/// If there are fewer than 4 values for every cell of the board
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{Rect, Size}, render};
+/// 
+/// let mut pixels = vec![200, 100, 50, 255, 200, 100, 50, 255];
+/// render::dim_outside(&mut pixels, Size::new(2, 1), Rect::new(1, 0, 1, 1), 0.5);
+/// 
+/// assert_eq!(vec![100, 50, 25, 255, 200, 100, 50, 255], pixels);
+/// ```
+pub fn dim_outside(pixels: &mut [u8], size: Size, region: Rect, brightness: f32) {
+    let brightness = brightness.clamp(0.0, 1.0);
+
+    for index in 0..size.len() {
+        if region.contains(size.coord_of(index).unwrap()) {
+            continue;
+        }
+
+        for value in &mut pixels[index * 4..index * 4 + 3] {
+            *value = (*value as f32 * brightness).round() as u8;
+        }
+    }
+}
+
+/// Shrinks rgba pixels with one pixel per cell by averaging blocks of cells into single pixels, such as for an overview
+/// of a large board. Returns the size of the shrunk image and its pixels, blocks at the right and bottom edges
+/// which reach past the board are averaged over the cells they cover
+/// 
+/// # Parameters
+/// 
+/// pixels: The pixels of every cell with the rows in order
+/// size: The size of the board
+/// factor: The width and height of the blocks in cells, a factor of 0 is treated as 1
+/// 
+/// # Panics
+/// 
+/// If there are fewer than 4 values for every cell of the board
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::Size, render};
+/// 
+/// let pixels = [0, 0, 0, 255, 100, 50, 0, 255, 20, 20, 20, 255];
+/// let (size, shrunk) = render::downsample_rgba(&pixels, Size::new(3, 1), 2);
+/// 
+/// assert_eq!(Size::new(2, 1), size);
+/// assert_eq!(vec![50, 25, 0, 255, 20, 20, 20, 255], shrunk);
+/// ```
+pub fn downsample_rgba(pixels: &[u8], size: Size, factor: usize) -> (Size, Vec<u8>) {
+    let factor = factor.max(1);
+    let (w, h) = size.size();
+    let shrunk = Size::new(w.div_ceil(factor), h.div_ceil(factor));
+    let mut result = Vec::with_capacity(shrunk.len() * 4);
+
+    for coord in shrunk.coords() {
+        let block = Rect::new(coord.x * factor, coord.y * factor, factor, factor).clamp(size);
+        let mut sums = [0u32; 4];
+        for cell in block.coords() {
+            let index = size.index_of(cell).unwrap() * 4;
+            for (sum, &value) in sums.iter_mut().zip(&pixels[index..index + 4]) {
+                *sum += value as u32;
+            }
+        }
+
+        let count = block.area() as u32;
+        result.extend(sums.map(|sum| ((sum + count / 2) / count) as u8));
+    }
+
+    (shrunk, result)
+}
+
+/// The settings for drawing plants as overlapping translucent canopies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanopyStyle {
+    /// The number of pixels in each direction for every cell
+    pub scale: usize,
+    /// The radius in cells of the canopy of a plant with full energy
+    pub max_radius: f32,
+    /// The energy at which a plant has its full canopy
+    pub full_energy: u32,
+    /// The opacity of a canopy between 0 and 1
+    pub alpha: f32,
+}
+
+impl Default for CanopyStyle {
+    fn default() -> Self {
+        Self {
+            scale: 8,
+            max_radius: 1.5,
+            full_energy: 1000,
+            alpha: 0.6,
+        }
+    }
+}
+
+impl CanopyStyle {
+    /// Finds the radius in cells of the canopy of a plant, plants with more energy are taller and wider
+    /// 
+    /// # Parameters
+    /// 
+    /// energy: The energy of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::CanopyStyle;
+    /// 
+    /// let style = CanopyStyle { scale: 8, max_radius: 1.5, full_energy: 100, alpha: 0.5 };
+    /// 
+    /// assert_eq!(0.5, style.radius(0));
+    /// assert_eq!(1.5, style.radius(200));
+    /// ```
+    pub fn radius(&self, energy: u32) -> f32 {
+        let growth = if self.full_energy == 0 { 1.0 } else { (energy as f32 / self.full_energy as f32).min(1.0) };
+
+        0.5 + (self.max_radius - 0.5).max(0.0) * growth
+    }
+}
+
+/// Renders the board with the plants drawn as round translucent canopies which can spread over neighbouring cells,
+/// returns the width and height in pixels and the rgba pixels row by row.
+/// Canopies are drawn from the lowest to the highest plant, where plants with more energy are higher,
+/// so the tallest plants end up on top and the lower layers show through them
+/// 
+/// # Parameters
+/// 
+/// board: The board to draw
+/// population: The plants to draw on the board
+/// style: The settings for the canopies
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, population::Population, render::{self, CanopyStyle}};
+/// 
+/// let board = board::BoardBuilder::new().size(3, 2).light_uniform(1.0).build().unwrap();
+/// let style = CanopyStyle { scale: 4, ..Default::default() };
+/// let (w, h, pixels) = render::render_canopy(&board, &Population::new(board.fields.size), &style);
+/// 
+/// assert_eq!((12, 8), (w, h));
+/// assert_eq!(12 * 8 * 4, pixels.len());
+/// ```
+pub fn render_canopy(board: &Board, population: &Population, style: &CanopyStyle) -> (usize, usize, Vec<u8>) {
+    let (w, h) = board.fields.size.size();
+    let scale = style.scale.max(1);
+    let (width, height) = (w * scale, h * scale);
+    let background = render_ground(board);
+
+    // Start with the ground scaled up
+    let mut pixels = vec![0; width * height * 4];
+    for y in 0..height {
+        for x in 0..width {
+            let cell = (x / scale + y / scale * w) * 4;
+            let pixel = (x + y * width) * 4;
+            pixels[pixel..pixel + 4].copy_from_slice(&background[cell..cell + 4]);
+        }
+    }
+
+    // Draw the lowest canopies first
+    let mut plants: Vec<_> = population.iter().collect();
+    plants.sort_by_key(|(_, plant)| (plant.energy, plant.id()));
+
+    let alpha = style.alpha.clamp(0.0, 1.0);
+
+    for (coord, plant) in plants {
+        let color = plant_color(&plant.genome);
+        let radius = style.radius(plant.energy) * scale as f32;
+        let center = ((coord.x as f32 + 0.5) * scale as f32, (coord.y as f32 + 0.5) * scale as f32);
+
+        let x0 = (center.0 - radius).floor().max(0.0) as usize;
+        let y0 = (center.1 - radius).floor().max(0.0) as usize;
+        let x1 = ((center.0 + radius).ceil() as usize).min(width);
+        let y1 = ((center.1 + radius).ceil() as usize).min(height);
+
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+
+                let pixel = (x + y * width) * 4;
+                for channel in 0..3 {
+                    let below = pixels[pixel + channel] as f32;
+                    pixels[pixel + channel] = (below + (color[channel] as f32 - below) * alpha).round() as u8;
+                }
+            }
+        }
+    }
+
+    (width, height, pixels)
+}
+
+/// A layer blended over recent ticks with exponential decay so that changes leave fading trails,
+/// such as the light behind a moving weather front
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trail {
+    /// The fraction of the blended value which is kept every tick, between 0 and 1
+    decay: f32,
+    /// The blended value of every cell
+    values: Vec<f32>,
+}
+
+impl Trail {
+    /// Creates a new empty trail, the first update fills it with the layer
+    /// 
+    /// # Parameters
+    /// 
+    /// decay: The fraction of the blended value which is kept every tick, it is clamped between 0 and 1,
+    /// 0 shows only the latest tick and values close to 1 give long trails
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::Trail;
+    /// 
+    /// let trail = Trail::new(0.9);
+    /// 
+    /// assert!(trail.values().is_empty());
+    /// ```
+    pub fn new(decay: f32) -> Self {
+        let decay = if decay.is_nan() { 0.0 } else { decay.clamp(0.0, 1.0) };
+
+        Self { decay, values: Vec::new() }
+    }
+
+    /// Retrieves the blended value of every cell
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Blends the layer of the latest tick into the trail, if the layer has a different number of cells
+    /// than the trail the trail starts over from the layer
+    /// 
+    /// # Parameters
+    /// 
+    /// layer: The value of every cell in the latest tick
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::Trail;
+    /// 
+    /// let mut trail = Trail::new(0.5);
+    /// trail.update(&[1.0, 0.0]);
+    /// trail.update(&[0.0, 0.0]);
+    /// 
+    /// assert_eq!(&[0.5, 0.0], trail.values());
+    /// ```
+    pub fn update(&mut self, layer: &[f32]) {
+        if self.values.len() != layer.len() {
+            self.values = layer.to_vec();
+            return;
+        }
+
+        for (value, &latest) in self.values.iter_mut().zip(layer.iter()) {
+            *value = latest + (*value - latest) * self.decay;
+        }
+    }
+
+    /// Renders the trail as rgba pixels, one pixel per cell with the rows in order
+    /// 
+    /// # Parameters
+    /// 
+    /// color: Finds the color of a cell from its blended value
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::render::{self, Trail};
+    /// 
+    /// let mut trail = Trail::new(0.5);
+    /// trail.update(&[0.0, 1.0]);
+    /// 
+    /// assert_eq!(render::light_color(1.0), trail.render_rgba(render::light_color)[4..8]);
+    /// ```
+    pub fn render_rgba<F: Fn(f32) -> [u8; 4]>(&self, color: F) -> Vec<u8> {
+        self.values.iter()
+            .flat_map(|&value| color(value))
+            .collect()
+    }
+}
+
+/// Draws the wind as arrows on top of an rgba image of the board, such as one from render_canopy. An arrow starts
+/// in the middle of every cell a spacing apart and points downwind with a length in proportion to the speed of the wind,
+/// the arrow of the fastest wind is as long as the spacing
+/// 
+/// # Parameters
+/// 
+/// pixels: The rgba pixels of the image with the rows in order
+/// width: The width of the image in pixels
+/// height: The height of the image in pixels
+/// wind: The wind in every cell, such as from Simulation::wind
+/// spacing: The number of cells between the arrows in each direction, a spacing of 0 is treated as 1
+/// color: The color of the arrows
+/// 
+/// # Panics
+/// 
+/// This will panic if there are not 4 values for every pixel of the image
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::Size, render, wind::WindConfig};
+/// 
+/// let wind = WindConfig::new(0.0, 1.0).field(Size::new(4, 4), 0);
+/// let mut pixels = vec![0; 16 * 16 * 4];
+/// render::draw_wind(&mut pixels, 16, 16, &wind, 4, [255, 255, 255, 255]);
+/// 
+/// // A single arrow pointing along x from the middle of the first cell
+/// let drawn = |x: usize, y: usize| pixels[(x + y * 16) * 4] == 255;
+/// assert!(drawn(2, 2) && drawn(10, 2) && drawn(14, 2));
+/// assert!(!drawn(2, 10));
+/// ```
+pub fn draw_wind(pixels: &mut [u8], width: usize, height: usize, wind: &Field<(f32, f32)>, spacing: usize, color: [u8; 4]) {
+    assert_eq!(width * height * 4, pixels.len(), "There must be 4 values for every pixel");
+
+    let (w, h) = wind.size().size();
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    let spacing = spacing.max(1);
+    let scale = (width as f32 / w as f32, height as f32 / h as f32);
+    let fastest = wind.iter().map(|(x, y)| (x * x + y * y).sqrt()).fold(0.0, f32::max);
+    if fastest <= 0.0 {
+        return;
+    }
+    let length = spacing as f32 * scale.0.min(scale.1) / fastest;
+
+    let mut plot = |from: (f32, f32), to: (f32, f32)| {
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).ceil().max(1.0) as usize;
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let (x, y) = (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t);
+            if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+                continue;
+            }
+
+            let pixel = (x as usize + y as usize * width) * 4;
+            pixels[pixel..pixel + 4].copy_from_slice(&color);
+        }
+    };
+
+    for y in (0..h).step_by(spacing) {
+        for x in (0..w).step_by(spacing) {
+            let (dx, dy) = wind[x + y * w];
+            let start = ((x as f32 + 0.5) * scale.0, (y as f32 + 0.5) * scale.1);
+            let tip = (start.0 + dx * length, start.1 + dy * length);
+            plot(start, tip);
+
+            // The head is two strokes back from the tip at either side
+            let (back_x, back_y) = ((start.0 - tip.0) * 0.3, (start.1 - tip.1) * 0.3);
+            for side in [-1.0, 1.0] {
+                plot(tip, (tip.0 + back_x - back_y * side * 0.6, tip.1 + back_y + back_x * side * 0.6));
+            }
+        }
+    }
+}
+
+/// Tints the cells with a modifier on top of an image of a board, cells gaining light are tinted towards one color
+/// and cells losing light towards another. The tint grows with the change of the light of a fully lit cell
+/// and is strongest for a change of 1 or more
+/// 
+/// # Parameters
+/// 
+/// pixels: The rgba pixels of the image with the rows in order
+/// width: The width of the image in pixels
+/// height: The height of the image in pixels
+/// modifiers: The modifier of every cell, such as Board::modifiers
+/// bonus: The color of cells gaining light
+/// penalty: The color of cells losing light
+/// alpha: The opacity of the strongest tint between 0 and 1
+/// 
+/// # Panics
+/// 
+/// This will panic if there are not 4 values for every pixel of the image
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{BoardBuilder, Coord, Modifier}, render};
+/// 
+/// let mut board = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+/// board.set_modifier(Coord::new(1, 0), Modifier::new(1.0, 1.0));
+/// let mut pixels = vec![0; 4 * 2 * 4];
+/// render::draw_modifiers(&mut pixels, 4, 2, &board.modifiers, [0, 200, 0, 255], [200, 0, 0, 255], 0.5);
+/// 
+/// assert_eq!([0, 0, 0, 0], pixels[0..4]);
+/// assert_eq!([0, 100, 0, 128], pixels[8..12]);
+/// ```
+pub fn draw_modifiers(pixels: &mut [u8], width: usize, height: usize, modifiers: &Field<Modifier>, bonus: [u8; 4], penalty: [u8; 4], alpha: f32) {
+    assert_eq!(width * height * 4, pixels.len(), "There must be 4 values for every pixel");
+
+    let (w, h) = modifiers.size().size();
+    if w == 0 || h == 0 {
+        return;
+    }
+
+    for (index, modifier) in modifiers.iter().enumerate() {
+        let change = modifier.apply(1.0) - 1.0;
+        if change == 0.0 {
+            continue;
+        }
+        let color = if change > 0.0 { bonus } else { penalty };
+        let strength = change.abs().min(1.0) * alpha.clamp(0.0, 1.0);
+
+        let (x, y) = (index % w, index / w);
+        for py in y * height / h..(y + 1) * height / h {
+            for px in x * width / w..(x + 1) * width / w {
+                let pixel = (px + py * width) * 4;
+                for (value, &tint) in pixels[pixel..pixel + 4].iter_mut().zip(&color) {
+                    *value = (*value as f32 + (tint as f32 - *value as f32) * strength).round() as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Renders the board without plants as rgba pixels, one pixel per cell
+fn render_ground(board: &Board) -> Vec<u8> {
+    board.fields.light.iter()
+        .zip(board.fields.terrain.iter())
+        .flat_map(|(&light, &terrain)| terrain_color(terrain).unwrap_or_else(|| light_color(light)))
+        .collect()
+}
+
+#[cfg(feature = "image")]
+impl Board {
+    /// Renders the board with a population on top as an image with one pixel per cell,
+    /// empty cells are colored by their light and plants are colored by their genome
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to draw on the board
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the population does not have the same size as the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, population::Population};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[0.0, 0.5, 0.5, 1.0]).unwrap();
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+    /// let image = board.render_to_image(&Population::new(size));
+    /// 
+    /// assert_eq!((2, 2), image.dimensions());
+    /// ```
+    pub fn render_to_image(&self, population: &Population) -> image::RgbaImage {
+        self.render_to_image_with(population, &visual::genome_color)
+    }
+
+    /// Renders the board with a population on top as an image like render_to_image, but with the plants colored by
+    /// a custom mapping from their genome
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to draw on the board
+    /// coloring: Finds the color of a plant from its genome
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the population does not have the same size as the board
+    pub fn render_to_image_with<C: GenomeColoring + ?Sized>(&self, population: &Population, coloring: &C) -> image::RgbaImage {
+        assert_eq!(self.fields.size, population.size(), "The population must have the same size as the board");
+
+        let (w, h) = self.fields.size.size();
+
+        image::RgbaImage::from_raw(w as u32, h as u32, render_rgba_with(self, population, coloring))
+            .expect("The rendered buffer has one pixel per cell")
+    }
+
+    /// Renders the board with a population on top and saves it as a png image
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to draw on the board
+    /// path: The path of the image file
+    /// 
+    /// # Errors
+    /// 
+    /// image::ImageError: This will occur if the image could not be encoded or written to the file
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the population does not have the same size as the board
+    pub fn save_png<P: AsRef<std::path::Path>>(&self, population: &Population, path: P) -> Result<(), image::ImageError> {
+        self.render_to_image(population).save_with_format(path, image::ImageFormat::Png)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Fields, Multipliers, Size};
+    use crate::population::Plant;
+
+    fn board() -> Board {
+        let size = Size::new(2, 2);
+        let fields = Fields::new(size, &[0.0, 0.5, 1.0, 2.0]).unwrap();
+
+        Board::new(Multipliers::new(1024).unwrap(), fields)
+    }
+
+    #[test]
+    fn trail_update_decay() {
+        let mut trail = Trail::new(0.75);
+        trail.update(&[1.0, 0.0]);
+        trail.update(&[0.0, 1.0]);
+        trail.update(&[0.0, 1.0]);
+
+        assert_eq!(&[0.5625, 0.4375], trail.values());
+    }
+
+    #[test]
+    fn trail_update_no_decay() {
+        let mut trail = Trail::new(-1.0);
+        trail.update(&[1.0, 0.0]);
+        trail.update(&[0.0, 0.5]);
+
+        assert_eq!(&[0.0, 0.5], trail.values());
+    }
+
+    #[test]
+    fn trail_update_resize() {
+        let mut trail = Trail::new(0.5);
+        trail.update(&[1.0, 1.0]);
+        trail.update(&[0.0, 0.0, 0.25]);
+
+        assert_eq!(&[0.0, 0.0, 0.25], trail.values());
+    }
+
+    #[test]
+    fn light_color_range() {
+        assert_eq!([DARK[0], DARK[1], DARK[2], 255], light_color(0.0));
+        assert_eq!([BRIGHT[0], BRIGHT[1], BRIGHT[2], 255], light_color(1.0));
+        assert_eq!(light_color(1.0), light_color(3.5));
+        assert_eq!(light_color(0.0), light_color(-1.0));
+        assert_eq!(light_color(0.0), light_color(f32::NAN));
+    }
+
+    #[test]
+    fn plant_color_similar() {
+        let genome1 = Genome::new(&[0.5, 0.5]).unwrap();
+        let genome2 = Genome::new(&[0.5, 0.5]).unwrap();
+        let genome3 = Genome::new(&[0.1, 0.9]).unwrap();
+
+        assert_eq!(plant_color(&genome1), plant_color(&genome2));
+        assert_ne!(plant_color(&genome1), plant_color(&genome3));
+    }
+
+    #[test]
+    fn render_rgba_with_coloring() {
+        let board = board();
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut population = Population::new(Size::new(2, 2));
+        population.insert(Coord::new(0, 1), Plant::new(100, genome));
+        let coloring = visual::GeneHue { saturation: 0.0, value: 1.0, ..Default::default() };
+        let pixels = render_rgba_with(&board, &population, &coloring);
+
+        assert_eq!([255, 255, 255, 255], pixels[8..12]);
+        assert_eq!(light_color(0.0), pixels[0..4]);
+    }
+
+    #[test]
+    fn render_rgba_cells() {
+        let board = board();
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut population = Population::new(Size::new(2, 2));
+        population.insert(Coord::new(1, 0), Plant::new(100, genome.clone()));
+        let pixels = render_rgba(&board, &population);
+
+        assert_eq!(16, pixels.len());
+        assert_eq!(light_color(0.0), pixels[0..4]);
+        assert_eq!(plant_color(&genome), pixels[4..8]);
+        assert_eq!(light_color(1.0), pixels[8..12]);
+        assert_eq!(light_color(2.0), pixels[12..16]);
+    }
+
+    #[test]
+    fn render_rgba_terrain() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[1.0; 3]).unwrap().with_terrain(&[Terrain::Rock, Terrain::Water, Terrain::Open]).unwrap();
+        let pixels = render_rgba(&Board::new(Multipliers::new(1024).unwrap(), fields), &Population::new(size));
+
+        assert_eq!(ROCK, pixels[0..4]);
+        assert_eq!(WATER, pixels[4..8]);
+        assert_eq!(light_color(1.0), pixels[8..12]);
+    }
+
+    #[test]
+    fn canvas_update_cells() {
+        let board = board();
+        let mut population = Population::new(board.fields.size);
+        let mut canvas = Canvas::new(&board, &population);
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap()));
+        let mut dirty = DirtyCells::new(board.fields.size);
+
+        // Cells which are not marked keep their old pixels
+        assert!(canvas.update(&board, &population, &dirty, 1).is_empty());
+        assert_eq!(light_color(0.5), canvas.pixels()[4..8]);
+
+        dirty.mark(1);
+
+        assert_eq!(vec![Rect::new(1, 0, 1, 1)], canvas.update(&board, &population, &dirty, 1));
+        assert_eq!(render_rgba(&board, &population), canvas.pixels());
+    }
+
+    #[test]
+    fn canvas_update_resize() {
+        let board = board();
+        let mut canvas = Canvas::new(&board, &Population::new(board.fields.size));
+        let size = Size::new(3, 1);
+        let other = Board::new(Multipliers::new(1024).unwrap(), Fields::new(size, &[1.0; 3]).unwrap());
+
+        assert_eq!(vec![Rect::new(0, 0, 3, 1)], canvas.update(&other, &Population::new(size), &DirtyCells::new(size), 4));
+        assert_eq!(size, canvas.size());
+        assert_eq!(12, canvas.pixels().len());
+    }
+
+    #[test]
+    fn canvas_region() {
+        let board = board();
+        let canvas = Canvas::new(&board, &Population::new(board.fields.size));
+
+        assert_eq!(&canvas.pixels()[8..16], &canvas.region(Rect::new(0, 1, 2, 1))[..]);
+        assert_eq!(&canvas.pixels()[12..16], &canvas.region(Rect::new(1, 1, 5, 5))[..]);
+    }
+
+    fn simulation() -> Simulation {
+        let size = Size::new(2, 1);
+        let fields = Fields::new(size, &[0.5, 1.0]).unwrap().with_water(&[0.0, 1.0]).unwrap();
+        let mut population = Population::new(size);
+        let mut plant = Plant::new(1000, Genome::new(&[0.5, 0.5]).unwrap());
+        plant.age = 50;
+        population.insert(Coord::new(0, 0), plant);
+
+        Simulation::new(Board::new(Multipliers::new(1024).unwrap(), fields), population, Default::default()).unwrap()
+    }
+
+    #[test]
+    fn render_style_genome() {
+        let simulation = simulation();
+
+        assert_eq!(render_rgba(simulation.board(), simulation.population()), render_style(&simulation, &RenderStyle::default()));
+    }
+
+    #[test]
+    fn render_style_plants() {
+        let simulation = simulation();
+        let mut style = RenderStyle { mode: RenderMode::Age, ..Default::default() };
+
+        assert_eq!(style.age.color(50.0), render_style(&simulation, &style)[0..4]);
+
+        style.mode = RenderMode::Energy;
+
+        assert_eq!(style.energy.color(1000.0), render_style(&simulation, &style)[0..4]);
+
+        // Species tracking is disabled so the plant has no species
+        style.mode = RenderMode::SpeciesId;
+
+        assert_eq!(UNKNOWN_SPECIES, render_style(&simulation, &style)[0..4]);
+    }
+
+    #[test]
+    fn render_style_relatedness() {
+        let size = Size::new(5, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(1000, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(4, 0), Plant::new(100, Genome::new(&[1.0, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(1024).unwrap(), Fields::new(size, &[1.0; 5]).unwrap()), population, Default::default()).unwrap();
+        while !simulation.population().iter().any(|(_, plant)| plant.parent() == Some(PlantId(0))) {
+            simulation.step();
+        }
+        let mut style = RenderStyle { mode: RenderMode::Relatedness, ..Default::default() };
+
+        // Nothing is picked so every plant is unrelated
+        assert_eq!(UNRELATED, render_style(&simulation, &style)[4..8]);
+
+        // The seed of the first founder is its child and the second founder is unrelated
+        style.related_to = Some(PlantId(0));
+        style.generations = 1;
+        let pixels = render_style(&simulation, &style);
+        let (seed, _) = simulation.population().iter().find(|(_, plant)| plant.parent() == Some(PlantId(0))).unwrap();
+
+        assert_eq!(style.relatedness.color(1.0), pixels[4..8]);
+        assert_eq!(style.relatedness.color(0.5), pixels[seed.x * 4..seed.x * 4 + 4]);
+        assert_eq!(UNRELATED, pixels[16..20]);
+    }
+
+    #[test]
+    fn render_style_active_region() {
+        let mut simulation = simulation();
+        let style = RenderStyle::default();
+        let full = render_style(&simulation, &style);
+        simulation.set_active_region(Some(Rect::new(1, 0, 1, 1)));
+        let dimmed = render_style(&simulation, &style);
+
+        assert_eq!(full[4..8], dimmed[4..8]);
+        assert!(full.chunks(4).zip(dimmed.chunks(4)).enumerate().all(|(index, (full, dimmed))| index == 1 || (dimmed[0] < full[0] && dimmed[3] == full[3])));
+    }
+
+    #[test]
+    fn render_style_fields() {
+        let simulation = simulation();
+        let mut style = RenderStyle { mode: RenderMode::WaterField, ..Default::default() };
+        let pixels = render_style(&simulation, &style);
+
+        assert_eq!(style.water.color(0.0), pixels[0..4]);
+        assert_eq!(style.water.color(1.0), pixels[4..8]);
+
+        style.mode = RenderMode::LightField;
+
+        assert_eq!(style.light.color(0.5), render_style(&simulation, &style)[0..4]);
+
+        // The plants release no toxin without allelopathy
+        style.mode = RenderMode::ToxinField;
+
+        assert_eq!(style.toxin.color(0.0), render_style(&simulation, &style)[0..4]);
+    }
+
+    #[test]
+    fn render_style_occupancy() {
+        let mut simulation = simulation();
+        let mut style = RenderStyle { mode: RenderMode::Occupancy, ..Default::default() };
+
+        // Nothing is counted while occupancy is not tracked
+        assert_eq!(style.occupancy.color(0.0), render_style(&simulation, &style)[0..4]);
+
+        simulation.track_occupancy(true);
+        simulation.step();
+        let pixels = render_style(&simulation, &style);
+
+        assert_eq!(style.occupancy.color(1.0), pixels[0..4]);
+        assert_eq!(style.occupancy.color(0.0), pixels[4..8]);
+
+        style.mode = RenderMode::Mortality;
+
+        assert_eq!(style.mortality.color(0.0), render_style(&simulation, &style)[0..4]);
+    }
+
+    #[test]
+    fn render_style_shadows() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[1.0; 3]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(1000, Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(1000, Genome::new(&[0.5, 0.5]).unwrap()));
+        let config = crate::simulation::SimulationConfig {
+            sun: Some(crate::shadow::Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.5)),
+            canopy: Some(crate::shadow::CanopyConfig { max_height: 1.5, ..Default::default() }),
+            ..Default::default()
+        };
+        let simulation = Simulation::new(Board::new(Multipliers::new(1024).unwrap(), fields), population, config).unwrap();
+        let pixels = render_style(&simulation, &RenderStyle { mode: RenderMode::Shadows, ..Default::default() });
+        let color = plant_color(&Genome::new(&[0.5, 0.5]).unwrap());
+
+        // The tall plant shades the short plant next to it but its shadow does not reach the empty cell
+        assert_eq!(plant_color(&Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()), pixels[0..4]);
+        assert_eq!([0, 1, 2].map(|channel| (color[channel] as f32 * 0.5).round() as u8), pixels[4..7]);
+        assert_eq!(light_color(1.0), pixels[8..12]);
+    }
+
+    #[test]
+    fn render_mode_next() {
+        let mut mode = RenderMode::GenomeColor;
+        for _ in 0..RenderMode::ALL.len() {
+            mode = mode.next();
+        }
+
+        assert_eq!(RenderMode::GenomeColor, mode);
+    }
+
+    #[test]
+    fn color_ramp_edges() {
+        let ramp = ColorRamp::new(1.0, 1.0, &[[10, 10, 10], [20, 20, 20]]);
+
+        assert_eq!([10, 10, 10, 255], ramp.color(f32::NAN));
+        assert_eq!([0, 0, 0, 255], ColorRamp::new(0.0, 1.0, &[]).color(0.5));
+        assert_eq!([20, 20, 20, 255], ColorRamp::new(0.0, 1.0, &[[20, 20, 20]]).color(0.5));
+    }
+
+    #[test]
+    fn palette_next() {
+        let mut palette = Palette::Classic;
+        for _ in 0..Palette::ALL.len() {
+            palette = palette.next();
+        }
+
+        assert_eq!(Palette::Classic, palette);
+        assert_eq!(Palette::Classic, Palette::from_stops(&[[1, 2, 3]]).next());
+    }
+
+    #[test]
+    fn palette_colors() {
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let [r, g, b, _] = plant_color(&genome);
+
+        assert_eq!([r, g, b], Palette::Classic.color(&genome));
+        assert_eq!(None, Palette::Classic.category(3));
+        assert!(OKABE_ITO.contains(&Palette::OkabeIto.color(&genome)));
+        assert_eq!(Palette::OkabeIto.category(1), Palette::OkabeIto.category(1 + OKABE_ITO.len() as u64));
+        assert_eq!([9, 9, 9], Palette::from_stops(&[[9, 9, 9]]).color(&genome));
+        assert_ne!(Palette::Viridis.category(0), Palette::Viridis.category(1));
+    }
+
+    #[test]
+    fn render_style_palette() {
+        let simulation = simulation();
+        let mut style = RenderStyle { mode: RenderMode::Age, ..Default::default() };
+        style.set_palette(Palette::Cividis);
+
+        assert_eq!(Palette::Cividis.ramp(0.0, 500.0).unwrap().color(50.0), render_style(&simulation, &style)[0..4]);
+
+        style.mode = RenderMode::GenomeColor;
+        let [r, g, b] = Palette::Cividis.color(&simulation.population().get(Coord::new(0, 0)).unwrap().genome);
+
+        assert_eq!([r, g, b, 255], render_style(&simulation, &style)[0..4]);
+    }
+
+    #[test]
+    fn downsample_rgba_blocks() {
+        let size = Size::new(3, 3);
+        let pixels: Vec<u8> = (0..9u8).flat_map(|value| [value * 10, 0, 0, 255]).collect();
+        let (shrunk, result) = downsample_rgba(&pixels, size, 2);
+
+        assert_eq!(Size::new(2, 2), shrunk);
+        assert_eq!(vec![20, 0, 0, 255], result[0..4]);
+        assert_eq!(vec![80, 0, 0, 255], result[12..16]);
+        assert_eq!((size, pixels.clone()), downsample_rgba(&pixels, size, 0));
+    }
+
+    #[test]
+    fn canopy_style_radius() {
+        let style = CanopyStyle { scale: 4, max_radius: 2.5, full_energy: 100, alpha: 1.0 };
+
+        assert_eq!(0.5, style.radius(0));
+        assert_eq!(1.5, style.radius(50));
+        assert_eq!(2.5, style.radius(1000));
+        assert_eq!(2.5, CanopyStyle { full_energy: 0, ..style }.radius(0));
+    }
+
+    #[test]
+    fn render_canopy_blend() {
+        let size = Size::new(1, 1);
+        let board = Board::new(Multipliers::new(1).unwrap(), Fields::new(size, &[0.0]).unwrap());
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, genome.clone()));
+        let style = CanopyStyle { scale: 2, max_radius: 0.5, full_energy: 1, alpha: 0.5 };
+        let (_, _, pixels) = render_canopy(&board, &population, &style);
+        let color = plant_color(&genome);
+
+        for channel in 0..3 {
+            let expected = (DARK[channel] as f32 + (color[channel] as f32 - DARK[channel] as f32) * 0.5).round() as u8;
+            assert_eq!(expected, pixels[channel]);
+        }
+    }
+
+    #[test]
+    fn render_canopy_order() {
+        // The canopy of the plant with the most energy covers the centre of the other cell
+        let size = Size::new(2, 1);
+        let board = Board::new(Multipliers::new(1).unwrap(), Fields::new(size, &[0.0, 0.0]).unwrap());
+        let low = Genome::new(&[0.0, 0.0]).unwrap();
+        let high = Genome::new(&[1.0, 0.3]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, low.clone()));
+        population.insert(Coord::new(1, 0), Plant::new(100, high.clone()));
+        let style = CanopyStyle { scale: 4, max_radius: 1.0, full_energy: 100, alpha: 1.0 };
+        let (w, h, pixels) = render_canopy(&board, &population, &style);
+
+        assert_eq!((8, 4), (w, h));
+        assert_eq!(plant_color(&high), pixels[(2 + 2 * 8) * 4..(2 + 2 * 8) * 4 + 4]);
+        // The corner of the first cell is outside both canopies
+        assert_eq!(light_color(0.0), pixels[0..4]);
+    }
+
+    #[test]
+    fn draw_modifiers_tint() {
+        let size = Size::new(3, 1);
+        let mut modifiers = Field::filled(size, Modifier::default());
+        modifiers[0] = Modifier::new(-0.25, 1.0);
+        modifiers[1] = Modifier::new(0.0, 4.0);
+        let mut pixels = vec![100; 3 * 4];
+        draw_modifiers(&mut pixels, 3, 1, &modifiers, [200, 200, 200, 255], [0, 0, 0, 255], 1.0);
+
+        assert_eq!(vec![75, 75, 75, 139], pixels[0..4]);
+        assert_eq!(vec![200, 200, 200, 255], pixels[4..8]);
+        assert_eq!(vec![100; 4], pixels[8..12]);
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn board_render_to_image() {
+        let board = board();
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let mut population = Population::new(Size::new(2, 2));
+        population.insert(Coord::new(1, 0), Plant::new(100, genome.clone()));
+        let image = board.render_to_image(&population);
+
+        assert_eq!((2, 2), image.dimensions());
+        assert_eq!(plant_color(&genome), image.get_pixel(1, 0).0);
+        assert_eq!(light_color(1.0), image.get_pixel(0, 1).0);
+    }
+}
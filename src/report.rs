@@ -0,0 +1,276 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::board::Size;
+use crate::experiment::{Experiment, ExperimentError, ExperimentReport, Run, Summary};
+use crate::render;
+use crate::simulation::Simulation;
+use crate::stats::TickStats;
+
+/// The name of the report file written to the report directory
+pub const REPORT_FILE: &str = "report.html";
+/// The largest number of pixels along each side of the image of a board, larger boards are downsampled
+pub const BOARD_PIXELS: usize = 128;
+/// The largest number of hall of fame genomes shown for every run
+pub const FAME_ENTRIES: usize = 5;
+/// The width in pixels of the statistics plots
+const SERIES_WIDTH: usize = 480;
+/// The height in pixels of the statistics plots
+const SERIES_HEIGHT: usize = 160;
+/// The space in pixels around the statistics plots holding the axes and the labels
+const SERIES_MARGIN: usize = 40;
+
+/// A statistic shown in the report, the name and how to read it from the statistics of a step
+type Metric = (&'static str, fn(&TickStats) -> f64);
+
+/// The statistics shown in the summary table and plotted over time
+const METRICS: [Metric; 6] = [
+    ("Population", |stats| stats.population as f64),
+    ("Mean energy", |stats| stats.mean_energy as f64),
+    ("Diversity", |stats| stats.diversity as f64),
+    ("Species", |stats| stats.species as f64),
+    ("Births", |stats| stats.births as f64),
+    ("Deaths", |stats| stats.deaths as f64),
+];
+
+impl Experiment {
+    /// Steps all runs and writes a self-contained HTML report of the experiment to REPORT_FILE in a directory,
+    /// the directory is created if it does not exist. The report holds a summary of the final statistics across the runs,
+    /// plots of the statistics over time, and for every run an image of the final board, the best genomes of the hall
+    /// of fame and the settings. Returns the statistics of the runs
+    /// 
+    /// # Parameters
+    /// 
+    /// dir: The directory to write the report to
+    /// 
+    /// # Errors
+    /// 
+    /// ExperimentError::Run: This will occur if one of the runs could not be created
+    /// 
+    /// ExperimentError::Io: This will occur if the directory could not be created or the report could not be written
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, experiment::Experiment, genome::Genome};
+    /// use evolution_plants::{population::{Plant, Population}, simulation::SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+    /// let dir = std::env::temp_dir().join("evolution_plants_report_doc");
+    /// let report = Experiment::new(20).replicate(board, population, SimulationConfig::default(), 0..2).write_report(&dir).unwrap();
+    /// 
+    /// assert_eq!(2, report.runs.len());
+    /// assert!(std::fs::read_to_string(dir.join("report.html")).unwrap().contains("<h2>Summary</h2>"));
+    /// # std::fs::remove_dir_all(dir).unwrap();
+    /// ```
+    pub fn write_report<P: AsRef<Path>>(self, dir: P) -> Result<ExperimentReport, ExperimentError> {
+        let dir = dir.as_ref();
+        let (runs, simulations): (Vec<Vec<TickStats>>, Vec<Simulation>) = self.step_runs()?.into_iter().unzip();
+        let report = ExperimentReport { runs };
+
+        let html = report_html(self.ticks(), self.runs(), &report, &simulations);
+        fs::create_dir_all(dir).map_err(|error| io_error(dir, error))?;
+        let path = dir.join(REPORT_FILE);
+        fs::write(&path, html).map_err(|error| io_error(&path, error))?;
+
+        Ok(report)
+    }
+}
+
+/// Creates the error of a failed file operation
+fn io_error(path: &Path, error: std::io::Error) -> ExperimentError {
+    ExperimentError::Io { path: PathBuf::from(path), message: error.to_string() }
+}
+
+/// Writes the HTML of the report of an experiment
+/// 
+/// # Parameters
+/// 
+/// ticks: The number of steps every run was stepped
+/// runs: The starting state of every run
+/// report: The statistics of every step of every run
+/// simulations: The final simulation of every run
+fn report_html(ticks: u64, runs: &[Run], report: &ExperimentReport, simulations: &[Simulation]) -> String {
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Experiment report</title>");
+    let _ = writeln!(html, "<style>body {{ font-family: sans-serif; margin: 2em; }} table {{ border-collapse: collapse; }} td, th {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }} pre {{ background: #f4f4f4; padding: 1em; }}</style>");
+    let _ = writeln!(html, "</head>\n<body>\n<h1>Experiment report</h1>");
+    let _ = writeln!(html, "<p>{} runs of {} steps</p>", runs.len(), ticks);
+
+    // The final statistics across the runs
+    let _ = writeln!(html, "<h2>Summary</h2>\n<table>\n<tr><th>Statistic</th><th>Mean</th><th>Standard deviation</th><th>95% interval</th></tr>");
+    for (name, metric) in METRICS {
+        let summary = report.final_summary(metric);
+        let _ = writeln!(
+            html,
+            "<tr><th>{}</th><td>{:.3}</td><td>{:.3}</td><td>{:.3} to {:.3}</td></tr>",
+            name, summary.mean, summary.std_dev, summary.low, summary.high,
+        );
+    }
+    let _ = writeln!(html, "</table>");
+
+    // The statistics over time
+    let _ = writeln!(html, "<h2>Statistics</h2>");
+    for (name, metric) in METRICS {
+        let _ = writeln!(html, "<h3>{}</h3>\n{}", name, series_svg(&report.series(metric)));
+    }
+
+    // The final state and settings of every run
+    let _ = writeln!(html, "<h2>Runs</h2>");
+    for (index, (run, simulation)) in runs.iter().zip(simulations).enumerate() {
+        let _ = writeln!(html, "<h3>Run {} (seed {})</h3>", index, run.config.seed);
+        let _ = writeln!(html, "<p>{} plants after {} steps</p>\n{}", simulation.population().count(), simulation.tick(), board_svg(simulation));
+
+        match simulation.hall_of_fame() {
+            Some(fame) if !fame.is_empty() => {
+                let _ = writeln!(html, "<h4>Hall of fame</h4>\n<table>\n<tr><th>Plant</th><th>Score</th><th>Death</th><th>Genes</th><th>Genome</th></tr>");
+                for entry in fame.entries().iter().take(FAME_ENTRIES) {
+                    let _ = writeln!(
+                        html,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        entry.id.0, entry.score, entry.death, escape(&entry.genome.to_string_repr()), entry.genome.plot_svg(),
+                    );
+                }
+                let _ = writeln!(html, "</table>");
+            }
+            Some(_) => {
+                let _ = writeln!(html, "<p>No plants have died</p>");
+            }
+            None => {
+                let _ = writeln!(html, "<p>No hall of fame was kept</p>");
+            }
+        }
+
+        let _ = writeln!(html, "<details>\n<summary>Settings</summary>\n<pre>{}</pre>\n</details>", escape(&format!("{:#?}", run.config)));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Draws a statistic over time as an SVG plot, the mean across the runs is a line inside a band covering the 95% interval
+/// 
+/// # Parameters
+/// 
+/// series: The summary of the statistic at every step
+fn series_svg(series: &[Summary]) -> String {
+    let width = SERIES_WIDTH + 2 * SERIES_MARGIN;
+    let height = SERIES_HEIGHT + 2 * SERIES_MARGIN;
+    let bottom = SERIES_MARGIN + SERIES_HEIGHT;
+
+    let finite = |value: f64| if value.is_finite() { value } else { 0.0 };
+    let low = series.iter().map(|summary| finite(summary.low)).fold(0.0, f64::min);
+    let high = series.iter().map(|summary| finite(summary.high)).fold(low, f64::max);
+    let range = if high > low { high - low } else { 1.0 };
+    let x = |step: usize| SERIES_MARGIN as f64 + step as f64 * SERIES_WIDTH as f64 / (series.len().max(2) - 1) as f64;
+    let y = |value: f64| bottom as f64 - (finite(value) - low) / range * SERIES_HEIGHT as f64;
+
+    let mut svg = String::new();
+    let _ = writeln!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height, width, height);
+    let _ = writeln!(svg, "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>", width, height);
+    let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>", SERIES_MARGIN, SERIES_MARGIN, SERIES_MARGIN, bottom);
+    let _ = writeln!(svg, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>", SERIES_MARGIN, bottom, width - SERIES_MARGIN, bottom);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{:.4}</text>", SERIES_MARGIN - 4, SERIES_MARGIN + 4, high);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{:.4}</text>", SERIES_MARGIN - 4, bottom, low);
+    let _ = writeln!(svg, "<text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"end\">{}</text>", width - SERIES_MARGIN, bottom + 14, series.len());
+
+    if !series.is_empty() {
+        let upper = series.iter().enumerate().map(|(step, summary)| format!("{:.1},{:.1}", x(step), y(summary.high)));
+        let lower = series.iter().enumerate().rev().map(|(step, summary)| format!("{:.1},{:.1}", x(step), y(summary.low)));
+        let band: Vec<String> = upper.chain(lower).collect();
+        let _ = writeln!(svg, "<polygon class=\"interval\" points=\"{}\" fill=\"seagreen\" fill-opacity=\"0.25\"/>", band.join(" "));
+
+        let mean: Vec<String> = series.iter().enumerate().map(|(step, summary)| format!("{:.1},{:.1}", x(step), y(summary.mean))).collect();
+        let _ = writeln!(svg, "<polyline class=\"mean\" points=\"{}\" fill=\"none\" stroke=\"seagreen\" stroke-width=\"2\"/>", mean.join(" "));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draws the board of a simulation as an SVG image with one square for every pixel, boards wider or taller
+/// than BOARD_PIXELS are downsampled first
+fn board_svg(simulation: &Simulation) -> String {
+    let size = simulation.board().fields.size;
+    let pixels = render::render_rgba(simulation.board(), simulation.population());
+    let (w, h) = size.size();
+    let factor = w.max(h).div_ceil(BOARD_PIXELS).max(1);
+    let (size, pixels): (Size, Vec<u8>) = render::downsample_rgba(&pixels, size, factor);
+    let (w, h) = size.size();
+    let scale = (BOARD_PIXELS * 3 / w.max(h).max(1)).max(1);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg class=\"board\" xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+        w * scale, h * scale, w, h,
+    );
+    for (index, pixel) in pixels.chunks(4).enumerate() {
+        let _ = writeln!(svg, "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"#{:02x}{:02x}{:02x}\"/>", index % w, index / w, pixel[0], pixel[1], pixel[2]);
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Escapes the characters of text which have a meaning in HTML
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aging::AgingConfig;
+    use crate::archive::{ArchiveConfig, Criterion};
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn experiment(config: SimulationConfig) -> Experiment {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.1, 0.5, 0.0, 0.0, 0.0]).unwrap()));
+
+        Experiment::new(30).threads(1).replicate(board, population, config, 0..2)
+    }
+
+    #[test]
+    fn report_contents() {
+        let dir = std::env::temp_dir().join(format!("evolution_plants_{}_report", std::process::id()));
+        // The plants age quickly so some have died and entered the hall of fame by the end
+        let archive = Some(ArchiveConfig { capacity: 3, criterion: Criterion::Longevity });
+        let config = SimulationConfig { archive, aging: Some(AgingConfig { max_lifespan: 10, respiration: 20.0 }), ..Default::default() };
+        let report = experiment(config).write_report(&dir).unwrap();
+        let html = fs::read_to_string(dir.join(REPORT_FILE)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(2, report.runs.len());
+        assert_eq!(METRICS.len(), html.matches("<polyline class=\"mean\"").count());
+        assert_eq!(2, html.matches("<svg class=\"board\"").count());
+        assert_eq!(2, html.matches("<h4>Hall of fame</h4>").count());
+        assert_eq!(2, html.matches("<summary>Settings</summary>").count());
+        assert!(html.contains("Run 1 (seed 1)"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn report_error() {
+        let file = std::env::temp_dir().join(format!("evolution_plants_{}_report_file", std::process::id()));
+        fs::write(&file, "").unwrap();
+        let result = experiment(SimulationConfig::default()).write_report(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert!(matches!(result, Err(ExperimentError::Io { .. })));
+    }
+
+    #[test]
+    fn report_escape() {
+        assert_eq!("a &lt;b&gt; &amp; &quot;c&quot;", escape("a <b> & \"c\""));
+        assert!(!series_svg(&[]).contains("polyline"));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::board::{Coord, Size};
+use crate::genome::{self, Genome};
+use crate::population::{Plant, Population};
+
+/// The settings for letting plants compete for water with their roots. The roots of a plant reach every cell
+/// within its root radius, and the water taken up from a cell every step is split between all plants reaching it
+/// in proportion to how much they invest in their roots. Roots cost upkeep, so plants only grow long roots
+/// where water is worth fighting for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RootConfig {
+    /// The root radius in cells of a plant with a root gene of 1
+    pub max_radius: usize,
+    /// The fraction of the water in a cell which is taken up every step if any roots reach it
+    pub uptake: f32,
+    /// The energy a plant gains for every unit of water taken up
+    pub energy_per_water: f32,
+    /// The extra energy a plant with a root gene of 1 pays every step, this scales with the root gene
+    pub cost: f32,
+}
+
+impl Default for RootConfig {
+    fn default() -> Self {
+        Self {
+            max_radius: 2,
+            uptake: 0.1,
+            energy_per_water: 50.0,
+            cost: 2.0,
+        }
+    }
+}
+
+impl RootConfig {
+    /// Finds how much a plant invests in its roots from 0 to 1, plants without a root gene have no roots
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    pub fn investment(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_ROOTS).unwrap_or(0.0)
+    }
+
+    /// Finds how far the roots of a plant reach in cells, the roots always reach the cell of the plant
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, roots::RootConfig};
+    /// 
+    /// let config = RootConfig { max_radius: 4, ..Default::default() };
+    /// 
+    /// assert_eq!(2, config.radius(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+    /// assert_eq!(0, config.radius(&Genome::new(&[0.0, 0.0]).unwrap()));
+    /// ```
+    pub fn radius(&self, genome: &Genome) -> usize {
+        (self.investment(genome) * self.max_radius as f32).round() as usize
+    }
+
+    /// Finds the extra upkeep a plant pays in a step for its roots
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant paying the upkeep
+    pub fn upkeep(&self, plant: &Plant) -> u32 {
+        (self.investment(&plant.genome) * self.cost.max(0.0)) as u32
+    }
+
+    /// Lets the roots of all plants take up water and removes it from the board. The plants first claim every cell
+    /// their roots reach, then every plant gathers its share of the water of the cells it claimed.
+    /// Returns the water taken up by the plant in every cell, 0 for empty cells
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants competing for the water
+    /// water: The water in every cell
+    /// 
+    /// # Panics
+    /// 
+    /// If there is less water than cells on the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}, roots::RootConfig};
+    /// 
+    /// let mut population = Population::new(Size::new(3, 1));
+    /// population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+    /// population.insert(Coord::new(2, 0), Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+    /// let config = RootConfig { max_radius: 1, uptake: 0.5, ..Default::default() };
+    /// let mut water = [1.0, 3.0, 1.0];
+    /// let taken = config.absorb(&population, &mut water);
+    /// 
+    /// // The middle cell is split 2 to 1 in favour of the plant investing more in its roots
+    /// assert_eq!(vec![0.5 + 1.0, 0.0, 0.5 + 0.5], taken);
+    /// assert_eq!([0.5, 1.5, 0.5], water);
+    /// ```
+    pub fn absorb(&self, population: &Population, water: &mut [f32]) -> Vec<f32> {
+        let size = population.size();
+        assert!(water.len() >= size.len(), "There must be water for every cell");
+
+        let uptake = self.uptake.clamp(0.0, 1.0);
+        let roots: Vec<(usize, f32, usize)> = population.iter()
+            .map(|(coord, plant)| (size.index_of(coord).unwrap(), self.investment(&plant.genome), self.radius(&plant.genome)))
+            .filter(|&(_, investment, _)| investment > 0.0)
+            .collect();
+
+        // Scatter the investments of the plants onto the cells their roots reach
+        let mut claims = vec![0.0; size.len()];
+        for &(index, investment, radius) in &roots {
+            for cell in reach(size, size.coord_of(index).unwrap(), radius) {
+                claims[cell] += investment;
+            }
+        }
+
+        // Gather the share of every plant from the cells its roots reach
+        let mut taken = vec![0.0; size.len()];
+        for &(index, investment, radius) in &roots {
+            taken[index] = reach(size, size.coord_of(index).unwrap(), radius)
+                .map(|cell| water[cell] * uptake * investment / claims[cell])
+                .sum();
+        }
+
+        for (water, &claim) in water.iter_mut().zip(&claims) {
+            if claim > 0.0 {
+                *water *= 1.0 - uptake;
+            }
+        }
+
+        taken
+    }
+}
+
+/// Finds the indices of the cells within a radius of a cell which are on the board
+fn reach(size: Size, center: Coord, radius: usize) -> impl Iterator<Item = usize> {
+    let (w, h) = size.size();
+
+    (center.y.saturating_sub(radius)..(center.y + radius + 1).min(h))
+        .flat_map(move |y| (center.x.saturating_sub(radius)..(center.x + radius + 1).min(w)).map(move |x| (x, y)))
+        .filter(move |&(x, y)| {
+            let (dx, dy) = (x.abs_diff(center.x), y.abs_diff(center.y));
+
+            dx * dx + dy * dy <= radius * radius
+        })
+        .map(move |(x, y)| x + y * w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plant(roots: f32) -> Plant {
+        Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, roots]).unwrap())
+    }
+
+    #[test]
+    fn reach_circle() {
+        let mut cells: Vec<usize> = reach(Size::new(5, 5), Coord::new(2, 2), 1).collect();
+        cells.sort();
+
+        assert_eq!(vec![7, 11, 12, 13, 17], cells);
+        assert_eq!(vec![0], reach(Size::new(5, 5), Coord::new(0, 0), 0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn root_config_absorb_conserved() {
+        let size = Size::new(4, 4);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), plant(1.0));
+        population.insert(Coord::new(1, 2), plant(0.3));
+        population.insert(Coord::new(3, 3), plant(0.7));
+        population.insert(Coord::new(3, 0), Plant::new(10, Genome::new(&[0.0, 0.0]).unwrap()));
+        let config = RootConfig { max_radius: 3, uptake: 0.4, ..Default::default() };
+        let mut water: Vec<f32> = (0..size.len()).map(|index| index as f32).collect();
+        let before: f32 = water.iter().sum();
+        let taken = config.absorb(&population, &mut water);
+
+        // All water taken from the board ends up in a plant and the plant without roots gets nothing
+        assert!((before - water.iter().sum::<f32>() - taken.iter().sum::<f32>()).abs() < 1e-3);
+        assert_eq!(0.0, taken[3]);
+    }
+
+    #[test]
+    fn root_config_upkeep() {
+        let config = RootConfig { cost: 4.0, ..Default::default() };
+
+        assert_eq!(2, config.upkeep(&plant(0.5)));
+        assert_eq!(0, config.upkeep(&Plant::new(10, Genome::new(&[0.0, 0.0]).unwrap())));
+    }
+}
@@ -0,0 +1,1240 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use std::str::SplitWhitespace;
+
+use crate::adaptive::AdaptiveMutationConfig;
+use crate::aging::AgingConfig;
+use crate::allelopathy::AllelopathyConfig;
+use crate::archive::{ArchiveConfig, Criterion};
+use crate::board::{Board, Coord, Fields, Modifier, Multipliers, Rect, Region, Shape, Size, Terrain};
+use crate::checkpoint::{self, CheckpointChain, CheckpointConfig, CheckpointError, Compression, Reader};
+use crate::climate::ThermalConfig;
+use crate::clutch::ClutchConfig;
+use crate::distance::{EditDistance, Hamming, Metric};
+use crate::disturbance::{Disturbance, DisturbanceKind, Resource};
+use crate::ecotone::EdgeBand;
+use crate::experiment::Run;
+use crate::field::Field;
+use crate::fitness::FitnessConfig;
+use crate::genes::{GeneInfo, GeneRegistry};
+use crate::genome::{Crossover, EvolvableRate, Genome, GenomeParseError, MutationConfig};
+use crate::germination::GerminationConfig;
+use crate::mycorrhiza::MycorrhizaConfig;
+use crate::neural::NeuralConfig;
+use crate::nutrient::NutrientConfig;
+use crate::pathogen::PathogenConfig;
+use crate::pollination::PollinationConfig;
+use crate::population::{Cull, Population};
+use crate::roots::RootConfig;
+use crate::scenario::{ScenarioAction, ScenarioEvent};
+use crate::schedule::{Scheduler, Subsystem};
+use crate::seedbank::{DormantSeed, SeedBank, SeedBankConfig};
+use crate::shadow::{CanopyConfig, Sun};
+use crate::simulation::{Competition, ConfigError, ConfigUpdate, ReproductionConfig, ReproductionMode, SimulationConfig};
+use crate::snapshot::StateSnapshot;
+use crate::species::SpeciesConfig;
+use crate::spectrum::CHANNELS;
+use crate::tuning::Parallelism;
+use crate::water::{Rainfall, WaterConfig};
+use crate::wind::WindConfig;
+
+/// The entry with the settings of the run as lines of key = value
+const SETTINGS: &str = "settings.txt";
+/// The entry with the fields and multipliers of the board
+const BOARD: &str = "board.bin";
+/// The entry with the plants the run starts with saved as a checkpoint chain with a single checkpoint
+const POPULATION: &str = "population.bin";
+/// The entry with the checkpoints saved during the run
+const CHECKPOINTS: &str = "checkpoints.bin";
+/// The entry with a genome of the hall of fame on every line
+const HALL_OF_FAME: &str = "hall_of_fame.txt";
+/// The folder of the statistics tables
+const STATS: &str = "stats/";
+
+/// The signature of a local file header of a zip file
+const LOCAL_SIGNATURE: u32 = 0x0403_4b50;
+/// The signature of a central directory header of a zip file
+const CENTRAL_SIGNATURE: u32 = 0x0201_4b50;
+/// The signature of the end of the central directory of a zip file
+const END_SIGNATURE: u32 = 0x0605_4b50;
+/// The zip version needed to read stored entries
+const ZIP_VERSION: u16 = 10;
+/// The flag telling the names of the entries are UTF-8
+const ZIP_UTF8: u16 = 1 << 11;
+/// The date written for every entry, the first of January 1980 as the archive does not depend on when it was written
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+/// What a run produced which is kept in its archive together with the starting state of the run
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct RunRecord {
+    /// The checkpoints saved during the run
+    pub checkpoints: Option<CheckpointChain>,
+    /// The statistics tables of the run as CSV text together with their file names
+    pub stats: Vec<(String, String)>,
+    /// The genomes of the hall of fame with the best first
+    pub hall_of_fame: Vec<Genome>,
+}
+
+impl Run {
+    /// Saves the run and what it produced as a single zip file such that it can be moved to another machine
+    /// and picked up with import_archive. The entries are stored without compression so any zip tool can open them,
+    /// the settings and the hall of fame are text and the statistics are kept as the CSV files they were.
+    /// Every setting is saved, and the board is saved with its regions and its dormant seeds
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The file to write
+    /// record: The checkpoints, statistics and hall of fame of the run
+    /// 
+    /// # Errors
+    /// 
+    /// RunArchiveError::TooLarge: This will occur if the archive would be larger than 4 GiB or have too many entries
+    /// 
+    /// RunArchiveError::Io: This will occur if the file cannot be written
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::{experiment::Run, runarchive::RunRecord, simulation::SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(3, 3), Plant::new(50, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let run = Run { board, population, config: SimulationConfig { seed: 4, ..Default::default() } };
+    /// let record = RunRecord { stats: vec![("stats.csv".to_string(), "tick,population\n1,1\n".to_string())], ..Default::default() };
+    /// 
+    /// let path = std::env::temp_dir().join("evolution_plants_run.zip");
+    /// run.export_archive(&path, &record).unwrap();
+    /// 
+    /// assert_eq!((run, record), Run::import_archive(&path).unwrap());
+    /// ```
+    pub fn export_archive<P: AsRef<Path>>(&self, path: P, record: &RunRecord) -> Result<(), RunArchiveError> {
+        let path = path.as_ref();
+        let bytes = write_zip(&archive_entries(self, record))?;
+
+        fs::write(path, bytes).map_err(|error| RunArchiveError::Io { path: path.to_path_buf(), message: error.to_string() })
+    }
+
+    /// Reads a run and what it produced from a zip file written with export_archive.
+    /// The settings which are not in the archive get their default values
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The file to read
+    /// 
+    /// # Errors
+    /// 
+    /// RunArchiveError::Io: This will occur if the file cannot be read
+    /// 
+    /// RunArchiveError::Format: This will occur if the file is not a zip file
+    /// 
+    /// RunArchiveError::Compressed: This will occur if an entry has been compressed, such as by repacking the archive
+    /// 
+    /// RunArchiveError::Checksum: This will occur if an entry does not match its checksum
+    /// 
+    /// RunArchiveError::Missing: This will occur if the settings, the board or the plants are not in the archive
+    /// 
+    /// RunArchiveError::Setting: This will occur if a line of the settings is unknown or has an invalid value, such as a mutation rate outside 0 to 1
+    /// 
+    /// RunArchiveError::Config: This will occur if the settings cannot be used to run a simulation
+    /// 
+    /// RunArchiveError::Corrupt: This will occur if the board or the plants hold invalid values, such as an empty board
+    /// 
+    /// RunArchiveError::Checkpoint: This will occur if the checkpoints cannot be read
+    /// 
+    /// RunArchiveError::Genome: This will occur if a genome of the hall of fame cannot be read
+    pub fn import_archive<P: AsRef<Path>>(path: P) -> Result<(Run, RunRecord), RunArchiveError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|error| RunArchiveError::Io { path: path.to_path_buf(), message: error.to_string() })?;
+        let entries = read_zip(&bytes)?;
+        let entry = |name: &str| {
+            entries.iter()
+                .find(|(entry, _)| entry == name)
+                .map(|(_, data)| data.as_slice())
+        };
+        let required = |name: &str| entry(name).ok_or_else(|| RunArchiveError::Missing { name: name.to_string() });
+
+        let (config, checkpoint_config) = parse_settings(&String::from_utf8_lossy(required(SETTINGS)?))?;
+        config.validate()?;
+        let board = decode_board(required(BOARD)?).map_err(|_| RunArchiveError::Corrupt { name: BOARD.to_string() })?;
+        let population = decode_population(required(POPULATION)?, board.fields.size)
+            .ok_or_else(|| RunArchiveError::Corrupt { name: POPULATION.to_string() })?;
+
+        let checkpoints = entry(CHECKPOINTS).map(|bytes| CheckpointChain::from_bytes(bytes, checkpoint_config)).transpose()?;
+        let stats = entries.iter()
+            .filter_map(|(name, data)| Some((name.strip_prefix(STATS)?.to_string(), String::from_utf8_lossy(data).into_owned())))
+            .collect();
+        let hall_of_fame = String::from_utf8_lossy(entry(HALL_OF_FAME).unwrap_or_default())
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok((Run { board, population, config }, RunRecord { checkpoints, stats, hall_of_fame }))
+    }
+}
+
+/// Creates the entries of the archive of a run
+fn archive_entries(run: &Run, record: &RunRecord) -> Vec<(String, Vec<u8>)> {
+    let mut entries = vec![
+        (SETTINGS.to_string(), settings(&run.config, record.checkpoints.as_ref().map(CheckpointChain::config)).into_bytes()),
+        (BOARD.to_string(), encode_board(&run.board)),
+        (POPULATION.to_string(), encode_population(run)),
+    ];
+    if let Some(checkpoints) = &record.checkpoints {
+        entries.push((CHECKPOINTS.to_string(), checkpoints.to_bytes()));
+    }
+    entries.extend(record.stats.iter().map(|(name, csv)| (format!("{}{}", STATS, name), csv.clone().into_bytes())));
+    if !record.hall_of_fame.is_empty() {
+        let genomes: String = record.hall_of_fame.iter().map(|genome| format!("{}\n", genome.to_string_repr())).collect();
+        entries.push((HALL_OF_FAME.to_string(), genomes.into_bytes()));
+    }
+
+    entries
+}
+
+/// Writes the settings of a run as lines of key = value, settings with several fields are written as their fields
+/// in order separated by spaces and every action of the scenario is a line of its own
+fn settings(config: &SimulationConfig, checkpoints: Option<CheckpointConfig>) -> String {
+    let competition = match config.competition {
+        Competition::FirstWins => "first_wins",
+        Competition::HighestEnergyWins => "highest_energy_wins",
+        Competition::Lottery => "lottery",
+    };
+
+    let mut lines = vec![
+        format!("seed = {}", config.seed),
+        format!("upkeep = {}", config.upkeep),
+        format!("seed_cost = {}", config.seed_cost),
+        format!("max_threshold = {}", config.max_threshold),
+        format!("mutation_rate = {}", config.mutation.rate),
+        format!("mutation_strength = {}", config.mutation.strength),
+        format!("mutation_evolvable = {}", words(&config.mutation.evolvable)),
+        format!("competition = {}", competition),
+        format!("reproduction = {}", words(&config.reproduction)),
+        format!("adaptive_mutation = {}", words(&config.adaptive_mutation)),
+        format!("sun = {}", words(&config.sun)),
+        format!("canopy = {}", words(&config.canopy)),
+        format!("water = {}", words(&config.water)),
+        format!("nutrients = {}", words(&config.nutrients)),
+        format!("roots = {}", words(&config.roots)),
+        format!("pollination = {}", words(&config.pollination)),
+        format!("clutch = {}", words(&config.clutch)),
+        format!("allelopathy = {}", words(&config.allelopathy)),
+        format!("mycorrhiza = {}", words(&config.mycorrhiza)),
+        format!("wind = {}", words(&config.wind)),
+        format!("thermal = {}", words(&config.thermal)),
+        format!("edge_band = {}", words(&config.edge_band)),
+        format!("species = {}", words(&config.species)),
+        format!("aging = {}", words(&config.aging)),
+        format!("seed_bank = {}", words(&config.seed_bank)),
+        format!("germination = {}", words(&config.germination)),
+        format!("neural = {}", words(&config.neural)),
+        format!("pathogen = {}", words(&config.pathogen)),
+        format!("archive = {}", words(&config.archive)),
+        format!("fitness = {}", words(&config.fitness)),
+        format!("gene_bounds = {}", words(&config.gene_bounds)),
+        format!("schedule = {}", words(&config.schedule)),
+    ];
+    // Every action of the scenario is a line of its own, they are read back in order so actions at the same tick keep their order
+    lines.extend(config.scenario.events().iter().map(|event| format!("scenario = {}", words(event))));
+    if let Some(checkpoints) = checkpoints {
+        let compression = match checkpoints.compression {
+            Compression::None => "none",
+            Compression::RunLength => "run_length",
+        };
+        lines.push(format!("checkpoint_interval = {}", checkpoints.full_interval));
+        lines.push(format!("checkpoint_compression = {}", compression));
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Reads the settings written by settings, the settings which are left out get their default values.
+/// A mutation rate outside 0 to 1 or a negative or infinite mutation strength is an invalid value,
+/// the other ranges are left to SimulationConfig::validate
+fn parse_settings(text: &str) -> Result<(SimulationConfig, CheckpointConfig), RunArchiveError> {
+    let mut config = SimulationConfig::default();
+    let mut checkpoints = CheckpointConfig::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || RunArchiveError::Setting { line: index + 1, text: line.to_string() };
+        let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+        let value = value.trim();
+        let parsed = match key.trim() {
+            "seed" => value.parse().map(|seed| config.seed = seed).ok(),
+            "upkeep" => value.parse().map(|upkeep| config.upkeep = upkeep).ok(),
+            "seed_cost" => value.parse().map(|seed_cost| config.seed_cost = seed_cost).ok(),
+            "max_threshold" => value.parse().map(|max_threshold| config.max_threshold = max_threshold).ok(),
+            "mutation_rate" => value.parse().ok()
+                .filter(|rate: &f32| (0.0..=1.0).contains(rate))
+                .map(|rate| config.mutation.rate = rate),
+            "mutation_strength" => value.parse().ok()
+                .filter(|strength: &f32| strength.is_finite() && *strength >= 0.0)
+                .map(|strength| config.mutation.strength = strength),
+            "mutation_evolvable" => read(value).map(|evolvable| config.mutation.evolvable = evolvable),
+            "competition" => match value {
+                "first_wins" => Some(Competition::FirstWins),
+                "highest_energy_wins" => Some(Competition::HighestEnergyWins),
+                "lottery" => Some(Competition::Lottery),
+                _ => None,
+            }.map(|competition| config.competition = competition),
+            "reproduction" => read(value).map(|reproduction| config.reproduction = reproduction),
+            "adaptive_mutation" => read(value).map(|adaptive| config.adaptive_mutation = adaptive),
+            "sun" => read(value).map(|sun| config.sun = sun),
+            "canopy" => read(value).map(|canopy| config.canopy = canopy),
+            "water" => read(value).map(|water| config.water = water),
+            "nutrients" => read(value).map(|nutrients| config.nutrients = nutrients),
+            "roots" => read(value).map(|roots| config.roots = roots),
+            "pollination" => read(value).map(|pollination| config.pollination = pollination),
+            "clutch" => read(value).map(|clutch| config.clutch = clutch),
+            "allelopathy" => read(value).map(|allelopathy| config.allelopathy = allelopathy),
+            "mycorrhiza" => read(value).map(|mycorrhiza| config.mycorrhiza = mycorrhiza),
+            "wind" => read(value).map(|wind| config.wind = wind),
+            "thermal" => read(value).map(|thermal| config.thermal = thermal),
+            "edge_band" => read(value).map(|edge_band| config.edge_band = edge_band),
+            "species" => read(value).map(|species| config.species = species),
+            "aging" => read(value).map(|aging| config.aging = aging),
+            "seed_bank" => read(value).map(|seed_bank| config.seed_bank = seed_bank),
+            "germination" => read(value).map(|germination| config.germination = germination),
+            "neural" => read(value).map(|neural| config.neural = neural),
+            "pathogen" => read(value).map(|pathogen| config.pathogen = pathogen),
+            "archive" => read(value).map(|archive| config.archive = archive),
+            "fitness" => read(value).map(|fitness| config.fitness = fitness),
+            "gene_bounds" => read(value).map(|gene_bounds| config.gene_bounds = gene_bounds),
+            "schedule" => read(value).map(|schedule| config.schedule = schedule),
+            "scenario" => read(value).map(|event: ScenarioEvent| {
+                // The updates are checked together with the other settings once the archive is read
+                config.scenario = std::mem::take(&mut config.scenario).insert(event.tick, event.action);
+            }),
+            "checkpoint_interval" => value.parse().map(|interval| checkpoints.full_interval = interval).ok(),
+            "checkpoint_compression" => match value {
+                "none" => Some(Compression::None),
+                "run_length" => Some(Compression::RunLength),
+                _ => None,
+            }.map(|compression| checkpoints.compression = compression),
+            _ => None,
+        };
+        parsed.ok_or_else(invalid)?;
+    }
+
+    Ok((config, checkpoints))
+}
+
+/// Writes a setting as words separated by spaces
+fn words<T: Setting>(setting: &T) -> String {
+    let mut out = Vec::new();
+    setting.put(&mut out);
+
+    out.join(" ")
+}
+
+/// Reads a setting written by words, None if the words are not a valid setting or there are words left over
+fn read<T: Setting>(value: &str) -> Option<T> {
+    let mut words = value.split_whitespace();
+    let setting = T::take(&mut words)?;
+
+    words.next().is_none().then_some(setting)
+}
+
+/// A setting which is written in the settings of an archive as words separated by spaces
+trait Setting: Sized {
+    /// Writes the setting as words
+    fn put(&self, out: &mut Vec<String>);
+
+    /// Reads a setting written by put from the next words, None if they are not a valid setting
+    fn take(words: &mut SplitWhitespace) -> Option<Self>;
+}
+
+/// Implements Setting for numbers and flags which are written as a single word
+macro_rules! single_word {
+    ($($kind:ty),*) => {
+        $(
+            impl Setting for $kind {
+                fn put(&self, out: &mut Vec<String>) {
+                    out.push(self.to_string());
+                }
+
+                fn take(words: &mut SplitWhitespace) -> Option<Self> {
+                    words.next()?.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+/// Implements Setting for structs which are written as their fields in order
+macro_rules! fields {
+    ($($kind:ident { $($field:ident),* })*) => {
+        $(
+            impl Setting for $kind {
+                fn put(&self, out: &mut Vec<String>) {
+                    $(self.$field.put(out);)*
+                }
+
+                fn take(words: &mut SplitWhitespace) -> Option<Self> {
+                    Some(Self { $($field: Setting::take(words)?),* })
+                }
+            }
+        )*
+    };
+}
+
+single_word!(bool, u32, u64, usize, f32, f64);
+
+fields! {
+    EvolvableRate { min, max }
+    MutationConfig { rate, strength, evolvable }
+    ReproductionConfig { mode, crossover, pollen_range, compatibility, self_fertilize }
+    AdaptiveMutationConfig { interval, low_diversity, high_diversity, factor, min_rate, max_rate }
+    Sun { azimuth, altitude, shadow_strength, slope_strength }
+    CanopyConfig { max_height, height_cost }
+    Rainfall { chance, amount, radius }
+    WaterConfig { diffusion, evaporation, runoff, rainfall }
+    NutrientConfig { decay_rate, yield_per_energy, growth, saturation, uptake }
+    RootConfig { max_radius, uptake, energy_per_water, cost }
+    PollinationConfig { wind, spread, cost }
+    ClutchConfig { max_seeds, establishment }
+    AllelopathyConfig { emission, cost, decay_rate, inhibition }
+    MycorrhizaConfig { reach, share, loss }
+    WindConfig { azimuth, speed, meander, swing, period }
+    ThermalConfig { min_optimum, max_optimum, max_tolerance, penalty }
+    EdgeBand { width, light_loss, water_loss }
+    Hamming { tolerance }
+    EditDistance { tolerance }
+    SpeciesConfig { interval, threshold, metric }
+    AgingConfig { max_lifespan, respiration }
+    SeedBankConfig { dormancy, lifetime, capacity, min_light }
+    GerminationConfig { max_light, max_water, chance, persistence }
+    NeuralConfig { offset, hidden, weight_range, season_length, growth_rate, max_growth, weight_mutation }
+    PathogenConfig { transmission, outbreak, duration, damage, resistance_cost }
+    ArchiveConfig { capacity, criterion }
+    FitnessConfig { window }
+    GeneInfo { name, index, min, max, effect }
+    ConfigUpdate { mutation, upkeep, seed_cost, max_threshold, light_multiplier, water, sun }
+    Coord { x, y }
+    Rect { x, y, w, h }
+    Disturbance { kind, region }
+    ScenarioEvent { tick, action }
+}
+
+/// Optional settings are written as none, or as some followed by the setting
+impl<T: Setting> Setting for Option<T> {
+    fn put(&self, out: &mut Vec<String>) {
+        match self {
+            Some(setting) => {
+                out.push("some".to_string());
+                setting.put(out);
+            }
+            None => out.push("none".to_string()),
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        match words.next()? {
+            "some" => Some(Some(T::take(words)?)),
+            "none" => Some(None),
+            _ => None,
+        }
+    }
+}
+
+/// Lists are written as their length followed by every setting
+impl<T: Setting> Setting for Vec<T> {
+    fn put(&self, out: &mut Vec<String>) {
+        out.push(self.len().to_string());
+        for setting in self {
+            setting.put(out);
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        let len: usize = words.next()?.parse().ok()?;
+
+        (0..len).map(|_| T::take(words)).collect()
+    }
+}
+
+impl Setting for (f32, f32) {
+    fn put(&self, out: &mut Vec<String>) {
+        self.0.put(out);
+        self.1.put(out);
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        Some((f32::take(words)?, f32::take(words)?))
+    }
+}
+
+/// Text is written between quotes with every space, quote and percent sign written as percent signs followed by its bytes in hexadecimal
+impl Setting for String {
+    fn put(&self, out: &mut Vec<String>) {
+        let mut text = String::from("\"");
+        for character in self.chars() {
+            if character.is_whitespace() || character == '"' || character == '%' {
+                let mut bytes = [0; 4];
+                for byte in character.encode_utf8(&mut bytes).bytes() {
+                    text.push_str(&format!("%{:02X}", byte));
+                }
+            } else {
+                text.push(character);
+            }
+        }
+        text.push('"');
+
+        out.push(text);
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        let text = words.next()?.strip_prefix('"')?.strip_suffix('"')?;
+        let mut bytes = Vec::new();
+        let mut rest = text.as_bytes();
+        while let Some((&byte, tail)) = rest.split_first() {
+            if byte == b'%' {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            } else {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl Setting for Genome {
+    fn put(&self, out: &mut Vec<String>) {
+        out.push(self.to_string_repr());
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        words.next()?.parse().ok()
+    }
+}
+
+/// The registry is written as its genes, the names are checked again when the genes are registered
+impl Setting for GeneRegistry {
+    fn put(&self, out: &mut Vec<String>) {
+        self.genes().to_vec().put(out);
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        let mut registry = GeneRegistry::new();
+        for gene in Vec::<GeneInfo>::take(words)? {
+            registry.register(gene).ok()?;
+        }
+
+        Some(registry)
+    }
+}
+
+/// The schedule is written as the period of every subsystem in the order of Subsystem::ALL
+impl Setting for Scheduler {
+    fn put(&self, out: &mut Vec<String>) {
+        for subsystem in Subsystem::ALL {
+            self.period(subsystem).put(out);
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        let mut scheduler = Scheduler::new();
+        for subsystem in Subsystem::ALL {
+            scheduler.set_period(subsystem, u64::take(words)?);
+        }
+
+        Some(scheduler)
+    }
+}
+
+/// Implements Setting for enums without fields which are written as the name of their variant
+macro_rules! named {
+    ($($kind:ident { $($variant:ident => $name:literal),* })*) => {
+        $(
+            impl Setting for $kind {
+                fn put(&self, out: &mut Vec<String>) {
+                    out.push(match self {
+                        $($kind::$variant => $name),*
+                    }.to_string());
+                }
+
+                fn take(words: &mut SplitWhitespace) -> Option<Self> {
+                    match words.next()? {
+                        $($name => Some($kind::$variant),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+named! {
+    ReproductionMode { Asexual => "asexual", Sexual => "sexual" }
+    Crossover { SinglePoint => "single_point", Uniform => "uniform" }
+    Criterion { Longevity => "longevity", Descendants => "descendants", EnergyGathered => "energy_gathered" }
+    Resource { Light => "light", Water => "water" }
+    Terrain { Open => "open", Rock => "rock", Water => "water" }
+}
+
+impl Setting for Metric {
+    fn put(&self, out: &mut Vec<String>) {
+        match self {
+            Metric::MeanAbsolute => out.push("mean_absolute".to_string()),
+            Metric::Hamming(hamming) => {
+                out.push("hamming".to_string());
+                hamming.put(out);
+            }
+            Metric::EditDistance(edit) => {
+                out.push("edit_distance".to_string());
+                edit.put(out);
+            }
+            Metric::Euclidean => out.push("euclidean".to_string()),
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        match words.next()? {
+            "mean_absolute" => Some(Metric::MeanAbsolute),
+            "hamming" => Some(Metric::Hamming(Hamming::take(words)?)),
+            "edit_distance" => Some(Metric::EditDistance(EditDistance::take(words)?)),
+            "euclidean" => Some(Metric::Euclidean),
+            _ => None,
+        }
+    }
+}
+
+impl Setting for DisturbanceKind {
+    fn put(&self, out: &mut Vec<String>) {
+        match self {
+            DisturbanceKind::Fire => out.push("fire".to_string()),
+            DisturbanceKind::Drought { resource, duration } => {
+                out.push("drought".to_string());
+                resource.put(out);
+                duration.put(out);
+            }
+            DisturbanceKind::Meteor { terrain } => {
+                out.push("meteor".to_string());
+                terrain.put(out);
+            }
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        match words.next()? {
+            "fire" => Some(DisturbanceKind::Fire),
+            "drought" => Some(DisturbanceKind::Drought { resource: Resource::take(words)?, duration: u64::take(words)? }),
+            "meteor" => Some(DisturbanceKind::Meteor { terrain: Terrain::take(words)? }),
+            _ => None,
+        }
+    }
+}
+
+impl Setting for Cull {
+    fn put(&self, out: &mut Vec<String>) {
+        match self {
+            Cull::Random { fraction } => {
+                out.push("random".to_string());
+                fraction.put(out);
+            }
+            Cull::Region(rect) => {
+                out.push("region".to_string());
+                rect.put(out);
+            }
+            Cull::KeepRegion(rect) => {
+                out.push("keep_region".to_string());
+                rect.put(out);
+            }
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        match words.next()? {
+            "random" => Some(Cull::Random { fraction: f32::take(words)? }),
+            "region" => Some(Cull::Region(Rect::take(words)?)),
+            "keep_region" => Some(Cull::KeepRegion(Rect::take(words)?)),
+            _ => None,
+        }
+    }
+}
+
+impl Setting for ScenarioAction {
+    fn put(&self, out: &mut Vec<String>) {
+        match self {
+            ScenarioAction::Update(update) => {
+                out.push("update".to_string());
+                update.put(out);
+            }
+            ScenarioAction::ScaleLight(factor) => {
+                out.push("scale_light".to_string());
+                factor.put(out);
+            }
+            ScenarioAction::Disturb(disturbance) => {
+                out.push("disturb".to_string());
+                disturbance.put(out);
+            }
+            ScenarioAction::DisturbRegion { region, kind } => {
+                out.push("disturb_region".to_string());
+                region.put(out);
+                kind.put(out);
+            }
+            ScenarioAction::Cull(cull) => {
+                out.push("cull".to_string());
+                cull.put(out);
+            }
+            ScenarioAction::Spawn { center, radius, energy, genome } => {
+                out.push("spawn".to_string());
+                center.put(out);
+                radius.put(out);
+                energy.put(out);
+                genome.put(out);
+            }
+            ScenarioAction::Smite { center, radius } => {
+                out.push("smite".to_string());
+                center.put(out);
+                radius.put(out);
+            }
+            ScenarioAction::Fertilize { center, radius, resource, factor, duration } => {
+                out.push("fertilize".to_string());
+                center.put(out);
+                radius.put(out);
+                resource.put(out);
+                factor.put(out);
+                duration.put(out);
+            }
+        }
+    }
+
+    fn take(words: &mut SplitWhitespace) -> Option<Self> {
+        match words.next()? {
+            "update" => Some(ScenarioAction::Update(ConfigUpdate::take(words)?)),
+            "scale_light" => Some(ScenarioAction::ScaleLight(f32::take(words)?)),
+            "disturb" => Some(ScenarioAction::Disturb(Disturbance::take(words)?)),
+            "disturb_region" => Some(ScenarioAction::DisturbRegion { region: String::take(words)?, kind: DisturbanceKind::take(words)? }),
+            "cull" => Some(ScenarioAction::Cull(Cull::take(words)?)),
+            "spawn" => Some(ScenarioAction::Spawn {
+                center: Coord::take(words)?,
+                radius: f32::take(words)?,
+                energy: u32::take(words)?,
+                genome: Genome::take(words)?,
+            }),
+            "smite" => Some(ScenarioAction::Smite { center: Coord::take(words)?, radius: f32::take(words)? }),
+            "fertilize" => Some(ScenarioAction::Fertilize {
+                center: Coord::take(words)?,
+                radius: f32::take(words)?,
+                resource: Resource::take(words)?,
+                factor: f32::take(words)?,
+                duration: u64::take(words)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the multipliers, the fields, the regions and the dormant seeds of a board, the fields are written as their values one after another
+fn encode_board(board: &Board) -> Vec<u8> {
+    let fields = &board.fields;
+    let (width, height) = fields.size.size();
+    let mut out = Vec::new();
+    out.extend_from_slice(&(width as u64).to_le_bytes());
+    out.extend_from_slice(&(height as u64).to_le_bytes());
+    out.extend_from_slice(&board.multipliers.light.to_le_bytes());
+    for channel in board.multipliers.channels {
+        out.extend_from_slice(&channel.to_le_bytes());
+    }
+
+    let values = [&fields.light, &fields.elevation, &fields.water, &fields.temperature].into_iter()
+        .chain(&fields.spectrum)
+        .flat_map(|field| field.iter().copied())
+        .chain(board.modifiers.iter().flat_map(|modifier| [modifier.add, modifier.multiply]));
+    out.extend_from_slice(&(fields.spectrum.len() as u64).to_le_bytes());
+    out.extend(fields.terrain.iter().map(|terrain| match terrain {
+        Terrain::Open => 0,
+        Terrain::Rock => 1,
+        Terrain::Water => 2,
+    }));
+    for value in values {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+
+    out.extend_from_slice(&(board.regions.len() as u64).to_le_bytes());
+    for region in &board.regions {
+        out.extend_from_slice(&(region.name.len() as u64).to_le_bytes());
+        out.extend_from_slice(region.name.as_bytes());
+        match &region.shape {
+            Shape::Rect(rect) => {
+                out.push(0);
+                for value in [rect.x, rect.y, rect.w, rect.h] {
+                    out.extend_from_slice(&(value as u64).to_le_bytes());
+                }
+            }
+            Shape::Polygon(points) => {
+                out.push(1);
+                out.extend_from_slice(&(points.len() as u64).to_le_bytes());
+                for (x, y) in points {
+                    out.extend_from_slice(&x.to_le_bytes());
+                    out.extend_from_slice(&y.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    // The dormant seeds are saved like the plants of the checkpoints together with when they entered the ground
+    for seeds in board.seed_bank.cells() {
+        out.extend_from_slice(&(seeds.len() as u64).to_le_bytes());
+        for dormant in seeds {
+            checkpoint::put_cell(&mut out, Some(&dormant.seed));
+            out.extend_from_slice(&dormant.since.to_le_bytes());
+            out.extend_from_slice(&dormant.cued.to_le_bytes());
+        }
+    }
+
+    out
+}
+
+/// Reads a board written by encode_board
+fn decode_board(bytes: &[u8]) -> Result<Board, CheckpointError> {
+    let mut reader = Reader::new(bytes);
+    let (width, height) = (reader.len()?, reader.len()?);
+    let light = reader.u32()?;
+    let mut channels = [0.0; CHANNELS];
+    for channel in &mut channels {
+        *channel = reader.f32()?;
+    }
+    let spectrum = reader.len()?;
+    if width == 0 || height == 0 {
+        return Err(CheckpointError::Corrupt);
+    }
+
+    // Every cell takes a byte for the terrain, 4 for every field and 8 for the number of dormant seeds,
+    // so a corrupt size is caught before anything is allocated for it
+    let per_cell = spectrum.checked_add(8).and_then(|fields| fields.checked_mul(4)).and_then(|len| len.checked_add(1));
+    if per_cell.and_then(|per_cell| width.checked_mul(height)?.checked_mul(per_cell)).is_none_or(|len| len > reader.remaining()) {
+        return Err(CheckpointError::Corrupt);
+    }
+    let size = Size::new(width, height);
+
+    let terrain = (0..size.len()).map(|_| match reader.u8()? {
+        0 => Ok(Terrain::Open),
+        1 => Ok(Terrain::Rock),
+        2 => Ok(Terrain::Water),
+        _ => Err(CheckpointError::Corrupt),
+    }).collect::<Result<Vec<_>, _>>()?;
+    let mut field = || (0..size.len()).map(|_| reader.f32()).collect::<Result<Vec<_>, _>>();
+    let (light_field, elevation, water, temperature) = (field()?, field()?, field()?, field()?);
+    let shares = (0..spectrum).map(|_| field()).collect::<Result<Vec<_>, _>>()?;
+    let modifiers = (0..size.len()).map(|_| Ok(Modifier { add: reader.f32()?, multiply: reader.f32()? })).collect::<Result<Vec<_>, _>>()?;
+
+    let mut regions = Vec::new();
+    for _ in 0..reader.len()? {
+        let len = reader.len()?;
+        let name = String::from_utf8(reader.take(len)?.to_vec()).map_err(|_| CheckpointError::Corrupt)?;
+        let shape = match reader.u8()? {
+            0 => Shape::Rect(Rect::new(reader.len()?, reader.len()?, reader.len()?, reader.len()?)),
+            1 => {
+                let mut points = Vec::new();
+                for _ in 0..reader.len()? {
+                    points.push((reader.f32()?, reader.f32()?));
+                }
+                Shape::Polygon(points)
+            }
+            _ => return Err(CheckpointError::Corrupt),
+        };
+        regions.push(Region { name, shape });
+    }
+
+    let mut seeds = Vec::with_capacity(size.len());
+    for _ in 0..size.len() {
+        let mut cell = Vec::new();
+        for _ in 0..reader.len()? {
+            let seed = reader.cell()?.ok_or(CheckpointError::Corrupt)?;
+            cell.push(DormantSeed { seed, since: reader.u64()?, cued: reader.u64()? });
+        }
+        seeds.push(cell);
+    }
+    if !reader.is_done() {
+        return Err(CheckpointError::Corrupt);
+    }
+
+    let mut fields = Fields::new(size, &light_field)
+        .and_then(|fields| fields.with_elevation(&elevation))
+        .and_then(|fields| fields.with_water(&water))
+        .and_then(|fields| fields.with_temperature(&temperature))
+        .and_then(|fields| fields.with_terrain(&terrain))
+        .map_err(|_| CheckpointError::Corrupt)?;
+    if !shares.is_empty() {
+        fields = fields.with_spectrum(&shares.iter().map(Vec::as_slice).collect::<Vec<_>>()).map_err(|_| CheckpointError::Corrupt)?;
+    }
+    let multipliers = Multipliers::new(light).map_err(|_| CheckpointError::Corrupt)?.with_channels(channels);
+
+    let mut board = Board::new(multipliers, fields);
+    board.modifiers = Field::from_slice("Modifiers", size, &modifiers).map_err(|_| CheckpointError::Corrupt)?;
+    board.regions = regions;
+    board.seed_bank = SeedBank::from_cells(size, seeds);
+
+    Ok(board)
+}
+
+/// Writes the plants of a run as a checkpoint chain with a single full checkpoint, so the plants are saved
+/// the same way as in the checkpoints and keep their ids and lineage
+fn encode_population(run: &Run) -> Vec<u8> {
+    let size = run.board.fields.size;
+    let mut chain = CheckpointChain::new(CheckpointConfig { full_interval: 0, compression: Compression::RunLength });
+    chain.push(&StateSnapshot {
+        tick: 0,
+        size,
+        cells: run.population.cells().map(|plant| plant.cloned()).collect(),
+        water: run.board.fields.water.to_vec(),
+        nutrients: vec![0.0; size.len()],
+        rng_position: 0,
+        parallelism: Parallelism::default(),
+    });
+
+    chain.to_bytes()
+}
+
+/// Reads the plants written by encode_population, returns None if they cannot be read or are not on a board of the size
+fn decode_population(bytes: &[u8], size: Size) -> Option<Population> {
+    let snapshot = CheckpointChain::from_bytes(bytes, CheckpointConfig::default()).ok()?.latest()?;
+
+    (snapshot.size == size).then(|| Population::from_cells(size, snapshot.cells))
+}
+
+/// Finds the CRC-32 checksum zip files keep for every entry
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 })
+    })
+}
+
+/// Writes the part of a header which is the same in the local header and the central directory
+fn put_header(out: &mut Vec<u8>, name: &str, crc: u32, size: u32) {
+    out.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+    out.extend_from_slice(&ZIP_UTF8.to_le_bytes());
+    // The entry is stored without compression and the time of day is midnight
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&ZIP_DATE.to_le_bytes());
+    out.extend_from_slice(&crc.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+}
+
+/// Writes entries as a zip file where every entry is stored without compression
+fn write_zip(entries: &[(String, Vec<u8>)]) -> Result<Vec<u8>, RunArchiveError> {
+    let count = u16::try_from(entries.len()).map_err(|_| RunArchiveError::TooLarge)?;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+
+    for (name, data) in entries {
+        let offset = u32::try_from(out.len()).map_err(|_| RunArchiveError::TooLarge)?;
+        let size = u32::try_from(data.len()).map_err(|_| RunArchiveError::TooLarge)?;
+        if u16::try_from(name.len()).is_err() {
+            return Err(RunArchiveError::TooLarge);
+        }
+        let crc = crc32(data);
+
+        out.extend_from_slice(&LOCAL_SIGNATURE.to_le_bytes());
+        put_header(&mut out, name, crc, size);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        central.extend_from_slice(&CENTRAL_SIGNATURE.to_le_bytes());
+        central.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        put_header(&mut central, name, crc, size);
+        // No comment, the first disk and no attributes
+        central.extend_from_slice(&[0; 10]);
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let offset = u32::try_from(out.len()).map_err(|_| RunArchiveError::TooLarge)?;
+    let central_size = u32::try_from(central.len()).map_err(|_| RunArchiveError::TooLarge)?;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&END_SIGNATURE.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&count.to_le_bytes());
+    out.extend_from_slice(&central_size.to_le_bytes());
+    out.extend_from_slice(&offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    u32::try_from(out.len()).map_err(|_| RunArchiveError::TooLarge)?;
+
+    Ok(out)
+}
+
+/// Reads a little endian number of 16 bits at a position of a zip file
+fn u16_at(bytes: &[u8], at: usize) -> Result<u16, RunArchiveError> {
+    bytes.get(at..at + 2).map(|value| u16::from_le_bytes([value[0], value[1]])).ok_or(RunArchiveError::Format)
+}
+
+/// Reads a little endian number of 32 bits at a position of a zip file
+fn u32_at(bytes: &[u8], at: usize) -> Result<u32, RunArchiveError> {
+    bytes.get(at..at + 4).map(|value| u32::from_le_bytes([value[0], value[1], value[2], value[3]])).ok_or(RunArchiveError::Format)
+}
+
+/// Reads the entries of a zip file with their names in the order of the central directory,
+/// only entries stored without compression can be read
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, Vec<u8>)>, RunArchiveError> {
+    // The end of the central directory is the last 22 bytes unless the zip file has a comment
+    let end = (0..bytes.len().saturating_sub(21)).rev()
+        .find(|&at| u32_at(bytes, at) == Ok(END_SIGNATURE))
+        .ok_or(RunArchiveError::Format)?;
+    let count = u16_at(bytes, end + 10)?;
+    let mut at = u32_at(bytes, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        if u32_at(bytes, at)? != CENTRAL_SIGNATURE {
+            return Err(RunArchiveError::Format);
+        }
+        let method = u16_at(bytes, at + 10)?;
+        let crc = u32_at(bytes, at + 16)?;
+        let size = u32_at(bytes, at + 20)? as usize;
+        let name_len = u16_at(bytes, at + 28)? as usize;
+        let extra_len = u16_at(bytes, at + 30)? as usize + u16_at(bytes, at + 32)? as usize;
+        let offset = u32_at(bytes, at + 42)? as usize;
+        let name = bytes.get(at + 46..at + 46 + name_len).ok_or(RunArchiveError::Format)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        at += 46 + name_len + extra_len;
+
+        if method != 0 {
+            return Err(RunArchiveError::Compressed { name });
+        }
+        if u32_at(bytes, offset)? != LOCAL_SIGNATURE {
+            return Err(RunArchiveError::Format);
+        }
+        let start = offset + 30 + u16_at(bytes, offset + 26)? as usize + u16_at(bytes, offset + 28)? as usize;
+        let data = bytes.get(start..start + size).ok_or(RunArchiveError::Format)?;
+        if crc32(data) != crc {
+            return Err(RunArchiveError::Checksum { name });
+        }
+
+        entries.push((name, data.to_vec()));
+    }
+
+    Ok(entries)
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum RunArchiveError {
+    #[error("Unable to read or write {:?}: {}", path, message)]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("The archive would be too large for a zip file")]
+    TooLarge,
+    #[error("The file is not a zip file")]
+    Format,
+    #[error("The entry {:?} is compressed but only stored entries can be read", name)]
+    Compressed {
+        name: String,
+    },
+    #[error("The entry {:?} does not match its checksum", name)]
+    Checksum {
+        name: String,
+    },
+    #[error("The archive has no entry {:?}", name)]
+    Missing {
+        name: String,
+    },
+    #[error("Line {:?} of the settings is not a known setting with a valid value: {:?}", line, text)]
+    Setting {
+        line: usize,
+        text: String,
+    },
+    #[error("The entry {:?} holds invalid values", name)]
+    Corrupt {
+        name: String,
+    },
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+    #[error(transparent)]
+    Genome(#[from] GenomeParseError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardBuilder;
+    use crate::population::{Plant, PlantId};
+    use crate::scenario::Scenario;
+    use crate::simulation::Simulation;
+
+    fn run() -> Run {
+        let mut board = BoardBuilder::new().size(6, 5).light_uniform(0.75).multiplier_light(120).build().unwrap();
+        board.fields.elevation[7] = 2.5;
+        board.fields.terrain[3] = Terrain::Rock;
+        board.modifiers[4] = Modifier::new(0.25, 2.0);
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(80, Genome::new(&[0.1, 0.5, 0.3]).unwrap()));
+        population.insert(Coord::new(4, 2), Plant::new(60, Genome::new(&[0.2, 0.25]).unwrap()));
+        let config = SimulationConfig {
+            seed: 9,
+            upkeep: 3,
+            mutation: MutationConfig { rate: 0.125, ..Default::default() },
+            competition: Competition::Lottery,
+            ..Default::default()
+        };
+
+        Run { board, population, config }
+    }
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("evolution_plants_archive_{}_{}.zip", std::process::id(), name))
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0, crc32(&[]));
+    }
+
+    #[test]
+    fn run_archive_round_trip() {
+        let run = run();
+        let mut simulation = Simulation::new(run.board.clone(), run.population.clone(), run.config.clone()).unwrap();
+        let mut checkpoints = CheckpointChain::new(CheckpointConfig { full_interval: 3, compression: Compression::None });
+        for _ in 0..5 {
+            checkpoints.push(&simulation.snapshot());
+            simulation.step();
+        }
+        let record = RunRecord {
+            checkpoints: Some(checkpoints),
+            stats: vec![("stats.csv".to_string(), "tick,population\n1,2\n".to_string()), ("life_table.csv".to_string(), "age,alive\n".to_string())],
+            hall_of_fame: vec![Genome::new(&[0.5, 0.125]).unwrap(), Genome::new(&[1.0, 0.0, 0.3]).unwrap()],
+        };
+
+        let path = path("round_trip");
+        run.export_archive(&path, &record).unwrap();
+        let (imported, imported_record) = Run::import_archive(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(run, imported);
+        assert_eq!(record, imported_record);
+        assert_eq!(vec![0, 1], imported.population.iter().map(|(_, plant)| plant.id().0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn run_archive_every_setting() {
+        let mut run = run();
+        run.board.add_region("north east", Rect::new(0, 0, 4, 2));
+        run.board.add_region("\"odd\" 100%", Shape::Polygon(vec![(0.5, 0.5), (4.0, 1.5), (2.0, 4.25)]));
+        run.board.seed_bank.bury(8, Plant::new(12, Genome::new(&[0.3, 0.6]).unwrap()).with_lineage(PlantId(7), Some(PlantId(2)), None), 3, 4);
+        run.board.seed_bank.bury(8, Plant::new(5, Genome::new(&[0.9, 0.1]).unwrap()), 4, 4);
+        let mut gene_bounds = GeneRegistry::default();
+        gene_bounds.set_range(&gene_bounds.name(0), 0.25, 0.75).unwrap();
+        let update = ConfigUpdate { upkeep: Some(4), water: Some(None), sun: Some(Some(Sun::new(1.0, 0.5, 0.25))), ..Default::default() };
+        run.config = SimulationConfig {
+            mutation: MutationConfig { rate: 0.25, strength: 0.5, evolvable: Some(EvolvableRate { min: 0.01, max: 0.5 }) },
+            reproduction: ReproductionConfig { mode: ReproductionMode::Sexual, crossover: Crossover::Uniform, pollen_range: 3, compatibility: 0.2, self_fertilize: true },
+            adaptive_mutation: Some(AdaptiveMutationConfig::default()),
+            sun: Some(Sun::new(0.3, 0.8, 0.6)),
+            canopy: Some(CanopyConfig::default()),
+            water: Some(WaterConfig { rainfall: Some(Rainfall { chance: 0.125, amount: 2.0, radius: 3 }), ..Default::default() }),
+            nutrients: Some(NutrientConfig::default()),
+            roots: Some(RootConfig::default()),
+            pollination: Some(PollinationConfig::default()),
+            clutch: Some(ClutchConfig::default()),
+            allelopathy: Some(AllelopathyConfig::default()),
+            mycorrhiza: Some(MycorrhizaConfig::default()),
+            wind: Some(WindConfig::default()),
+            thermal: Some(ThermalConfig::default()),
+            edge_band: Some(EdgeBand::new(1, 0.5, 0.25)),
+            species: Some(SpeciesConfig { metric: Metric::Hamming(Hamming { tolerance: 0.1 }), ..Default::default() }),
+            aging: Some(AgingConfig::default()),
+            seed_bank: Some(SeedBankConfig::default()),
+            germination: Some(GerminationConfig::default()),
+            neural: Some(NeuralConfig { weight_mutation: Some(MutationConfig::new(0.5, 0.125)), ..Default::default() }),
+            pathogen: Some(PathogenConfig::default()),
+            archive: Some(ArchiveConfig { capacity: 4, criterion: Criterion::EnergyGathered }),
+            fitness: Some(FitnessConfig::default()),
+            gene_bounds: Some(gene_bounds),
+            schedule: Scheduler::new().every(Subsystem::Water, 5),
+            scenario: Scenario::new()
+                .at(4, ScenarioAction::Update(update)).unwrap()
+                .at(2, ScenarioAction::DisturbRegion { region: "north east".to_string(), kind: DisturbanceKind::Drought { resource: Resource::Water, duration: 3 } }).unwrap()
+                .at(2, ScenarioAction::Cull(Cull::KeepRegion(Rect::new(1, 1, 2, 2)))).unwrap()
+                .at(6, ScenarioAction::Spawn { center: Coord::new(2, 2), radius: 1.5, energy: 40, genome: Genome::new(&[0.5, 0.5]).unwrap() }).unwrap()
+                .at(7, ScenarioAction::Fertilize { center: Coord::new(1, 3), radius: 2.0, resource: Resource::Light, factor: 1.5, duration: 4 }).unwrap(),
+            ..run.config
+        };
+
+        let path = path("every_setting");
+        run.export_archive(&path, &RunRecord::default()).unwrap();
+        let (imported, _) = Run::import_archive(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(run, imported);
+        assert_eq!(2, imported.board.seed_bank.count());
+    }
+
+    #[test]
+    fn run_archive_setting_words() {
+        for text in ["", "plain", "two words", "100% \"quoted\"\ttab", "naïve café"] {
+            let text = text.to_string();
+
+            assert_eq!(Some(text.clone()), read(&words(&text)), "{:?}", text);
+        }
+        assert_eq!(None, read::<Option<u32>>("some 3 4"));
+        assert_eq!(None, read::<Option<u32>>("maybe 3"));
+    }
+
+    #[test]
+    fn run_archive_invalid() {
+        let entries = vec![(SETTINGS.to_string(), b"seed = 3\nwidth = 4\n".to_vec()), (BOARD.to_string(), vec![1, 2, 3]), (POPULATION.to_string(), Vec::new())];
+        let mut bytes = write_zip(&entries).unwrap();
+        let path = path("invalid");
+
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(Err(RunArchiveError::Setting { line: 2, text: "width = 4".to_string() }), Run::import_archive(&path));
+
+        // Changing the first byte of the settings, right after the 30 bytes of the local header and the name, breaks the checksum
+        bytes[30 + SETTINGS.len()] = b't';
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(Err(RunArchiveError::Checksum { name: SETTINGS.to_string() }), Run::import_archive(&path));
+
+        std::fs::write(&path, &bytes[..bytes.len() - 4]).unwrap();
+        assert_eq!(Err(RunArchiveError::Format), Run::import_archive(&path));
+
+        let settings = (SETTINGS.to_string(), b"seed = 3\n".to_vec());
+        std::fs::write(&path, write_zip(&[settings, entries[1].clone()]).unwrap()).unwrap();
+        assert_eq!(Err(RunArchiveError::Corrupt { name: BOARD.to_string() }), Run::import_archive(&path));
+
+        for text in ["mutation_rate = 1.5", "mutation_rate = NaN", "mutation_strength = -0.1", "mutation_strength = inf"] {
+            let settings = (SETTINGS.to_string(), format!("seed = 3\n{}\n", text).into_bytes());
+            std::fs::write(&path, write_zip(&[settings, entries[1].clone()]).unwrap()).unwrap();
+            assert_eq!(Err(RunArchiveError::Setting { line: 2, text: text.to_string() }), Run::import_archive(&path));
+        }
+
+        let mut empty = vec![0; 16];
+        empty.extend_from_slice(&1u32.to_le_bytes());
+        empty.extend_from_slice(&[0; 4 * CHANNELS + 8]);
+        let board = (BOARD.to_string(), empty);
+        std::fs::write(&path, write_zip(&[(SETTINGS.to_string(), b"seed = 3\n".to_vec()), board]).unwrap()).unwrap();
+        assert_eq!(Err(RunArchiveError::Corrupt { name: BOARD.to_string() }), Run::import_archive(&path));
+
+        let settings = (SETTINGS.to_string(), b"seed_cost = 4294967295\n".to_vec());
+        std::fs::write(&path, write_zip(&[settings, entries[1].clone()]).unwrap()).unwrap();
+        assert_eq!(Err(RunArchiveError::Config(ConfigError::Energy { name: "seed_cost", value: u32::MAX })), Run::import_archive(&path));
+
+        std::fs::write(&path, write_zip(&entries[1..]).unwrap()).unwrap();
+        assert_eq!(Err(RunArchiveError::Missing { name: SETTINGS.to_string() }), Run::import_archive(&path));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+use std::collections::HashSet;
+
+use crate::board::{Board, Coord, Multipliers};
+use crate::disturbance::{Disturbance, DisturbanceKind, Resource};
+use crate::edit::Brush;
+use crate::genome::Genome;
+use crate::population::{Cull, Plant};
+use crate::events::SimEvent;
+use crate::simulation::{ConfigError, ConfigUpdate, Simulation};
+
+/// Something a scenario does to a running simulation
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioAction {
+    /// Changes some of the settings, the change is remembered in the config log
+    Update(ConfigUpdate),
+    /// Multiplies the multiplier of the light field by a factor, the result is clamped between 1 and Multipliers::MAX
+    ScaleLight(f32),
+    /// Lets a disturbance hit a rectangle of the board
+    Disturb(Disturbance),
+    /// Lets a disturbance hit the smallest rectangle around a named region of the board
+    DisturbRegion {
+        region: String,
+        kind: DisturbanceKind,
+    },
+    /// Removes part of the population such that the survivors found the following generations
+    Cull(Cull),
+    /// Plants copies of a plant without a parent in the empty cells plants can grow in within a radius of a cell
+    Spawn {
+        center: Coord,
+        radius: f32,
+        energy: u32,
+        genome: Genome,
+    },
+    /// Kills the plants within a radius of a cell, the deaths are recorded like the deaths of a cull
+    Smite {
+        center: Coord,
+        radius: f32,
+    },
+    /// Multiplies a resource within a radius of a cell for a number of steps, after which the cells get back the value
+    /// they had before. The factor falls off towards the edge like the strength of a brush and the duration is at least 1 step
+    Fertilize {
+        center: Coord,
+        radius: f32,
+        resource: Resource,
+        factor: f32,
+        duration: u64,
+    },
+}
+
+impl ScenarioAction {
+    /// Checks that the action can be carried out, only the settings of an update can be invalid
+    /// 
+    /// # Errors
+    /// 
+    /// ConfigError::Mutation: This will occur if the update has mutation settings which cannot be used
+    /// 
+    /// ConfigError::Energy: This will occur if the update has an energy setting larger than SimulationConfig::MAX_ENERGY
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            ScenarioAction::Update(update) => update.validate(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An action of a scenario together with the tick of the step it happens in
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScenarioEvent {
+    /// The tick of the step the action happens in, actions at tick 0 never happen since the first step reaches tick 1
+    pub tick: u64,
+    /// What happens
+    pub action: ScenarioAction,
+}
+
+/// A list of timed actions which the simulation carries out by itself such that an experimental protocol is part of the
+/// settings and is carried out the same way in every replay. The actions of a step happen at the start of the step
+/// in the order they were added, before the disturbances of the step
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Scenario {
+    /// The actions sorted by their tick
+    events: Vec<ScenarioEvent>,
+}
+
+impl Scenario {
+    /// Creates a new scenario without any actions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an action happening in the step reaching a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the step the action happens in
+    /// action: What happens
+    /// 
+    /// # Errors
+    /// 
+    /// See ScenarioAction::validate, nothing is added if the action cannot be carried out
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Rect}, disturbance::{DisturbanceKind, Resource}, population::Population};
+    /// use evolution_plants::{scenario::{Scenario, ScenarioAction}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let mut board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// board.add_region("north", Rect::new(0, 0, 4, 2));
+    /// let scenario = Scenario::new()
+    ///     .at(3, ScenarioAction::ScaleLight(0.5)).unwrap()
+    ///     .at(5, ScenarioAction::DisturbRegion { region: "north".to_string(), kind: DisturbanceKind::Drought { resource: Resource::Light, duration: 10 } }).unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig { scenario, ..Default::default() }).unwrap();
+    /// for _ in 0..5 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(50, simulation.board().multipliers.light);
+    /// assert_eq!(&[0.0, 1.0], &simulation.light()[7..9]);
+    /// ```
+    pub fn at(self, tick: u64, action: ScenarioAction) -> Result<Self, ConfigError> {
+        action.validate()?;
+
+        Ok(self.insert(tick, action))
+    }
+
+    /// Adds an action which has already been checked, after the actions added before at the same tick
+    pub(crate) fn insert(mut self, tick: u64, action: ScenarioAction) -> Self {
+        let position = self.events.partition_point(|event| event.tick <= tick);
+        self.events.insert(position, ScenarioEvent { tick, action });
+        self
+    }
+
+    /// Returns all actions sorted by their tick
+    pub fn events(&self) -> &[ScenarioEvent] {
+        &self.events
+    }
+
+    /// Returns true if the scenario has no actions
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns the actions happening in the step reaching a tick in the order they were added
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the step
+    pub fn due(&self, tick: u64) -> impl Iterator<Item = &ScenarioAction> {
+        let start = self.events.partition_point(|event| event.tick < tick);
+        let end = self.events.partition_point(|event| event.tick <= tick);
+
+        self.events[start..end].iter().map(|event| &event.action)
+    }
+
+    /// Forgets the actions happening after a tick
+    pub(crate) fn forget_after(&mut self, tick: u64) {
+        let end = self.events.partition_point(|event| event.tick <= tick);
+        self.events.truncate(end);
+    }
+
+    /// Finds the first region used by an action which the board does not have
+    pub(crate) fn missing_region<'a>(&'a self, board: &Board) -> Option<&'a str> {
+        self.events.iter().find_map(|event| match &event.action {
+            ScenarioAction::DisturbRegion { region, .. } if board.region(region).is_none() => Some(region.as_str()),
+            _ => None,
+        })
+    }
+}
+
+impl Simulation {
+    /// Carries out the actions of the scenario happening in the step reaching a tick
+    pub(crate) fn run_scenario(&mut self, tick: u64) {
+        let actions: Vec<ScenarioAction> = self.config().scenario.due(tick).cloned().collect();
+
+        for action in actions {
+            self.run_action(tick, action);
+        }
+    }
+
+    /// Carries out a single action of a scenario as part of the step reaching a tick
+    pub(crate) fn run_action(&mut self, tick: u64, action: ScenarioAction) {
+        let size = self.board().fields.size;
+
+        match action {
+            ScenarioAction::Update(update) => {
+                // Updates are checked when they are added to a scenario, an update which still fails is reported
+                // to the hooks instead of stopping the step
+                if self.update_config(update).is_err() {
+                    self.emit(&[SimEvent::ConfigRejected { tick, update }]);
+                }
+            }
+            ScenarioAction::ScaleLight(factor) => {
+                let light = (self.board().multipliers.light as f32 * factor.max(0.0)).round() as u32;
+                self.update_config(ConfigUpdate { light_multiplier: Some(light.clamp(1, Multipliers::MAX)), ..Default::default() })
+                    .expect("Changing the light multiplier is always valid");
+            }
+            ScenarioAction::Disturb(disturbance) => self.schedule_disturbance(tick, disturbance),
+            ScenarioAction::Cull(cull) => {
+                self.cull(cull);
+            }
+            ScenarioAction::DisturbRegion { region, kind } => {
+                // Regions removed during the run are skipped
+                if let Some(region) = self.board().region(&region) {
+                    let disturbance = Disturbance::new(kind, region.shape.bounds());
+                    self.schedule_disturbance(tick, disturbance);
+                }
+            }
+            ScenarioAction::Spawn { center, radius, energy, genome } => {
+                for (index, _) in Brush::new(radius, 0.0).cells(size, center) {
+                    self.plant_founder(index, Plant::new(energy, genome.clone()));
+                }
+                // The new plants shade their neighbours
+                self.refresh_light();
+            }
+            ScenarioAction::Smite { center, radius } => {
+                let cells: HashSet<Coord> = Brush::new(radius, 0.0).cells(size, center).into_iter().map(|(index, _)| size.coord_of(index).unwrap()).collect();
+                self.keep_only(|coord, _| !cells.contains(&coord));
+            }
+            ScenarioAction::Fertilize { center, radius, resource, factor, duration } => {
+                let cells: Vec<(usize, f32)> = Brush::new(radius, 0.0).cells(size, center).into_iter()
+                    .map(|(index, weight)| (index, 1.0 + (factor - 1.0) * weight))
+                    .collect();
+                self.fertilize(resource, &cells, tick + duration.max(1));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Rect};
+    use crate::genome::MutationConfig;
+    use crate::population::Population;
+    use crate::simulation::{SimulationConfig, SimulationCreateError};
+
+    fn simulation(scenario: Scenario) -> Result<Simulation, SimulationCreateError> {
+        let mut board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        board.add_region("west", Rect::new(0, 0, 3, 6));
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+        population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig { scenario, ..Default::default() })
+    }
+
+    #[test]
+    fn scenario_order() {
+        let scenario = Scenario::new()
+            .at(5, ScenarioAction::ScaleLight(2.0)).unwrap()
+            .at(2, ScenarioAction::ScaleLight(0.5)).unwrap()
+            .at(5, ScenarioAction::ScaleLight(3.0)).unwrap();
+
+        assert_eq!(vec![2, 5, 5], scenario.events().iter().map(|event| event.tick).collect::<Vec<_>>());
+        assert_eq!(vec![&ScenarioAction::ScaleLight(2.0), &ScenarioAction::ScaleLight(3.0)], scenario.due(5).collect::<Vec<_>>());
+        assert_eq!(0, scenario.due(4).count());
+    }
+
+    #[test]
+    fn scenario_at_invalid() {
+        let update = ConfigUpdate { mutation: Some(MutationConfig::new(0.1, -1.0)), ..Default::default() };
+        let error = Scenario::new().at(3, ScenarioAction::Update(update));
+
+        assert!(matches!(error, Err(ConfigError::Mutation(_))));
+
+        let mut simulation = simulation(Scenario::new()).unwrap();
+
+        assert!(simulation.intervene(ScenarioAction::Update(update)).is_err());
+        assert!(simulation.interventions().is_empty());
+    }
+
+    #[test]
+    fn scenario_update_rejected() {
+        let mut simulation = simulation(Scenario::new()).unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = std::sync::Arc::clone(&events);
+        simulation.on_event(move |event| recorded.lock().unwrap().push(*event));
+        let update = ConfigUpdate { upkeep: Some(u32::MAX), ..Default::default() };
+        simulation.run_action(2, ScenarioAction::Update(update));
+
+        // An update which slipped past the checks is reported instead of being dropped
+        assert_eq!(vec![SimEvent::ConfigRejected { tick: 2, update }], *events.lock().unwrap());
+        assert!(simulation.config_log().is_empty());
+    }
+
+    #[test]
+    fn scenario_missing_region() {
+        let scenario = Scenario::new().at(3, ScenarioAction::DisturbRegion { region: "east".to_string(), kind: DisturbanceKind::Fire }).unwrap();
+
+        assert_eq!(Some(SimulationCreateError::Region { name: "east".to_string() }), simulation(scenario).err());
+    }
+
+    #[test]
+    fn scenario_run() {
+        let update = ConfigUpdate { mutation: Some(MutationConfig::new(0.3, 0.1)), ..Default::default() };
+        let scenario = Scenario::new()
+            .at(4, ScenarioAction::DisturbRegion { region: "west".to_string(), kind: DisturbanceKind::Fire }).unwrap()
+            .at(6, ScenarioAction::Update(update)).unwrap()
+            .at(6, ScenarioAction::ScaleLight(0.25)).unwrap();
+        let mut simulation = simulation(scenario).unwrap();
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        assert_eq!(1, simulation.disturbance_log().len());
+        assert_eq!(4, simulation.disturbance_log()[0].tick);
+        assert!(simulation.population().iter().all(|(coord, _)| coord.x >= 3));
+        assert_eq!(25, simulation.board().multipliers.light);
+        assert_eq!(vec![5, 5], simulation.config_log().iter().map(|change| change.tick).collect::<Vec<_>>());
+        assert_eq!(0.3, simulation.mutation_rate());
+
+        // A replay from the same settings carries out the same actions
+        let mut replay = self::simulation(simulation.config().scenario.clone()).unwrap();
+        for _ in 0..10 {
+            replay.step();
+        }
+        assert_eq!(simulation.snapshot(), replay.snapshot());
+    }
+
+    #[test]
+    fn scenario_cull() {
+        let scenario = Scenario::new()
+            .at(2, ScenarioAction::Cull(Cull::KeepRegion(Rect::new(0, 0, 3, 6)))).unwrap()
+            .at(8, ScenarioAction::Cull(Cull::Random { fraction: 1.0 })).unwrap();
+        let mut simulation = simulation(scenario).unwrap();
+        simulation.step();
+        simulation.step();
+
+        assert!(simulation.population().iter().all(|(coord, _)| coord.x < 3));
+        assert!(simulation.population().count() > 0);
+
+        for _ in 0..6 {
+            simulation.step();
+        }
+        // The cull happens at the start of the step so only the seeds of the step can be alive
+        assert!(simulation.population().iter().all(|(_, plant)| plant.age <= 1));
+    }
+
+    #[test]
+    fn scenario_god_tools() {
+        let scenario = Scenario::new()
+            .at(2, ScenarioAction::Smite { center: Coord::new(1, 1), radius: 1.0 }).unwrap()
+            .at(2, ScenarioAction::Fertilize { center: Coord::new(4, 1), radius: 1.0, resource: Resource::Water, factor: 3.0, duration: 3 }).unwrap()
+            .at(3, ScenarioAction::Spawn { center: Coord::new(0, 5), radius: 0.0, energy: 50, genome: Genome::new(&[0.9, 0.5]).unwrap() }).unwrap();
+        let mut simulation = simulation(scenario).unwrap();
+        simulation.water_mut().values_mut().fill(1.0);
+        simulation.step();
+        simulation.step();
+
+        assert!(simulation.population().iter().all(|(coord, _)| coord.x >= 3));
+        assert_eq!(&[1.0, 2.0, 3.0, 2.0], &simulation.water().values()[8..12]);
+
+        simulation.step();
+
+        assert_eq!(Some(&Genome::new(&[0.9, 0.5]).unwrap()), simulation.population().get(Coord::new(0, 5)).map(|plant| &plant.genome));
+
+        for _ in 0..2 {
+            simulation.step();
+        }
+        // The water is no longer held after the fertilizing ends
+        assert!(simulation.water().values()[10] < 3.0);
+    }
+
+    #[test]
+    fn simulation_intervene_rewind() {
+        let mut simulation = simulation(Scenario::new()).unwrap();
+        simulation.enable_history(10);
+        simulation.step();
+        simulation.intervene(ScenarioAction::Smite { center: Coord::new(4, 4), radius: 0.0 }).unwrap();
+        simulation.step();
+        simulation.intervene(ScenarioAction::Smite { center: Coord::new(1, 1), radius: 0.0 }).unwrap();
+
+        assert_eq!(vec![2, 3], simulation.interventions().events().iter().map(|event| event.tick).collect::<Vec<_>>());
+
+        simulation.rewind(1);
+
+        assert_eq!(vec![2], simulation.interventions().events().iter().map(|event| event.tick).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,168 @@
+/// The number of subsystems which can be scheduled
+pub const SUBSYSTEMS: usize = 5;
+
+/// A part of a step which changes slowly enough that it does not need to run every step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    /// The water flowing, evaporating and raining
+    Water,
+    /// The litter of dead plants decomposing into nutrients
+    Nutrients,
+    /// The shadows of the plants being recalculated, the shadows are always recalculated when the light of the board changes
+    Shadows,
+    /// The statistics being sent to the subscribers
+    Statistics,
+    /// The dead plants without living descendants being pruned from the lineage tree, by default every Scheduler::PRUNE_PERIOD steps
+    Phylogeny,
+}
+
+impl Subsystem {
+    /// All subsystems which can be scheduled
+    pub const ALL: [Subsystem; SUBSYSTEMS] = [Subsystem::Water, Subsystem::Nutrients, Subsystem::Shadows, Subsystem::Statistics, Subsystem::Phylogeny];
+
+    /// Returns the name of the subsystem
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::schedule::Subsystem;
+    /// 
+    /// assert_eq!("shadows", Subsystem::Shadows.name());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Subsystem::Water => "water",
+            Subsystem::Nutrients => "nutrients",
+            Subsystem::Shadows => "shadows",
+            Subsystem::Statistics => "statistics",
+            Subsystem::Phylogeny => "phylogeny",
+        }
+    }
+
+    /// Returns the position of the subsystem in ALL
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// How often the slowly changing subsystems of the simulation run. A subsystem with a period of n runs in every
+/// step whose tick is a multiple of n and is skipped in the other steps, a period of 0 means it never runs.
+/// The rates of a subsystem apply per run, so a subsystem running every 10 steps changes 10 times slower.
+/// Species are clustered on the interval of their own settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scheduler {
+    /// The number of steps between every run of every subsystem, in the order of Subsystem::ALL
+    periods: [u64; SUBSYSTEMS],
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        // The lineage tree grows with every birth, pruning it less often keeps the dead ancestors around for longer
+        let mut periods = [1; SUBSYSTEMS];
+        periods[Subsystem::Phylogeny.index()] = Self::PRUNE_PERIOD;
+
+        Self { periods }
+    }
+}
+
+impl Scheduler {
+    /// The default number of steps between every pruning of the lineage tree
+    pub const PRUNE_PERIOD: u64 = 100;
+
+    /// Creates a new scheduler running every subsystem every step, except for the pruning of the lineage tree
+    /// which runs every PRUNE_PERIOD steps
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of steps between every run of a subsystem
+    /// 
+    /// # Parameters
+    /// 
+    /// subsystem: The subsystem to schedule
+    /// period: The number of steps between every run, 0 if it should never run
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::schedule::{Scheduler, Subsystem};
+    /// 
+    /// let scheduler = Scheduler::new().every(Subsystem::Nutrients, 10).every(Subsystem::Statistics, 0);
+    /// 
+    /// assert!(scheduler.is_due(Subsystem::Water, 7));
+    /// assert!(!scheduler.is_due(Subsystem::Nutrients, 7));
+    /// assert!(scheduler.is_due(Subsystem::Nutrients, 20));
+    /// assert!(!scheduler.is_due(Subsystem::Statistics, 20));
+    /// ```
+    pub fn every(mut self, subsystem: Subsystem, period: u64) -> Self {
+        self.set_period(subsystem, period);
+
+        self
+    }
+
+    /// Returns the number of steps between every run of a subsystem, 0 if it never runs
+    /// 
+    /// # Parameters
+    /// 
+    /// subsystem: The subsystem to look up
+    pub fn period(&self, subsystem: Subsystem) -> u64 {
+        self.periods[subsystem.index()]
+    }
+
+    /// Sets the number of steps between every run of a subsystem
+    /// 
+    /// # Parameters
+    /// 
+    /// subsystem: The subsystem to schedule
+    /// period: The number of steps between every run, 0 if it should never run
+    pub fn set_period(&mut self, subsystem: Subsystem, period: u64) {
+        self.periods[subsystem.index()] = period;
+    }
+
+    /// Returns true if a subsystem runs in the step with a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// subsystem: The subsystem to check
+    /// tick: The tick of the step
+    pub fn is_due(&self, subsystem: Subsystem, tick: u64) -> bool {
+        let period = self.period(subsystem);
+
+        period != 0 && tick.is_multiple_of(period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduler_default_every_step() {
+        let scheduler = Scheduler::default();
+
+        for subsystem in Subsystem::ALL.into_iter().filter(|&subsystem| subsystem != Subsystem::Phylogeny) {
+            assert_eq!(1, scheduler.period(subsystem));
+            assert!((0..5).all(|tick| scheduler.is_due(subsystem, tick)));
+        }
+        assert_eq!(Scheduler::PRUNE_PERIOD, scheduler.period(Subsystem::Phylogeny));
+        assert!(scheduler.is_due(Subsystem::Phylogeny, 200) && !scheduler.is_due(Subsystem::Phylogeny, 150));
+    }
+
+    #[test]
+    fn scheduler_period() {
+        let mut scheduler = Scheduler::new();
+        scheduler.set_period(Subsystem::Water, 3);
+
+        let due: Vec<u64> = (1..10).filter(|&tick| scheduler.is_due(Subsystem::Water, tick)).collect();
+
+        assert_eq!(vec![3, 6, 9], due);
+        assert_eq!(1, scheduler.period(Subsystem::Shadows));
+    }
+
+    #[test]
+    fn subsystem_all_in_order() {
+        for (index, subsystem) in Subsystem::ALL.iter().enumerate() {
+            assert_eq!(index, subsystem.index());
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use rand::Rng;
+
+use crate::board::{concat_cells, flip_cells, reframe_cells, Coord, Rect, Size};
+use crate::germination::GerminationConfig;
+use crate::memory::{vec_bytes, HeapSize};
+use crate::population::Plant;
+
+/// The settings for keeping seeds which could not germinate dormant in the ground
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeedBankConfig {
+    /// The number of steps a seed stays dormant before it can germinate
+    pub dormancy: u64,
+    /// The number of steps a seed survives in the ground, older seeds die
+    pub lifetime: u64,
+    /// The largest number of seeds in a single cell, seeds arriving at a full cell die
+    pub capacity: usize,
+    /// The least light a cell must get before its seeds germinate
+    pub min_light: f32,
+}
+
+impl Default for SeedBankConfig {
+    fn default() -> Self {
+        Self {
+            dormancy: 1,
+            lifetime: 50,
+            capacity: 4,
+            min_light: 0.0,
+        }
+    }
+}
+
+/// A seed waiting in the ground
+#[derive(Clone, Debug, PartialEq)]
+pub struct DormantSeed {
+    /// The seed
+    pub seed: Plant,
+    /// The tick the seed entered the ground
+    pub since: u64,
+    /// The number of steps in a row the germination cues of the seed have been met
+    pub cued: u64,
+}
+
+/// The dormant seeds in every cell of the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedBank {
+    /// The size of the board
+    size: Size,
+    /// The seeds of every cell in the order they entered the ground
+    cells: Vec<Vec<DormantSeed>>,
+}
+
+impl SeedBank {
+    /// Creates a new seed bank without any seeds
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, seedbank::SeedBank};
+    /// 
+    /// let bank = SeedBank::new(Size::new(3, 2));
+    /// 
+    /// assert_eq!(0, bank.count());
+    /// ```
+    pub fn new(size: Size) -> Self {
+        Self { size, cells: vec![Vec::new(); size.len()] }
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the total number of dormant seeds
+    pub fn count(&self) -> usize {
+        self.cells.iter().map(Vec::len).sum()
+    }
+
+    /// Returns the total energy stored in the dormant seeds
+    pub fn energy(&self) -> u64 {
+        self.cells.iter().flatten().map(|dormant| dormant.seed.energy as u64).sum()
+    }
+
+    /// Gets the dormant seeds in a cell in the order they entered the ground, the slice is empty outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate of the cell
+    pub fn seeds(&self, coord: Coord) -> &[DormantSeed] {
+        match self.size.index_of(coord) {
+            Some(index) => &self.cells[index],
+            None => &[],
+        }
+    }
+
+    /// Removes all dormant seeds
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            cell.clear();
+        }
+    }
+
+    /// Creates a seed bank from the dormant seeds of every cell, the cells are in index order
+    pub(crate) fn from_cells(size: Size, cells: Vec<Vec<DormantSeed>>) -> Self {
+        Self { size, cells }
+    }
+
+    /// Returns the dormant seeds of every cell in index order
+    pub(crate) fn cells(&self) -> &[Vec<DormantSeed>] {
+        &self.cells
+    }
+
+    /// Makes a rectangle of the board the new board, the cells outside the board start without seeds
+    /// and the seeds of the cells outside the rectangle die
+    pub(crate) fn reframe(&mut self, rect: Rect) {
+        self.cells = reframe_cells(std::mem::take(&mut self.cells), self.size, rect, Vec::new).0;
+        self.size = Size::new(rect.w, rect.h);
+    }
+
+    /// Joins the seeds of two boards into the seed bank of a single board,
+    /// either with the second board to the right of the first or below it
+    pub(crate) fn concat(first: &SeedBank, second: &SeedBank, size: Size, horizontal: bool) -> Self {
+        Self { size, cells: concat_cells(&first.cells, first.size, &second.cells, second.size, horizontal) }
+    }
+
+    /// Mirrors the seeds in place, either left to right or top to bottom
+    pub(crate) fn flip(&mut self, horizontal: bool) {
+        flip_cells(&mut self.cells, self.size, horizontal);
+    }
+
+    /// Puts a seed in the ground of a cell, returns false if the cell is full and the seed died
+    pub(crate) fn bury(&mut self, index: usize, seed: Plant, tick: u64, capacity: usize) -> bool {
+        let cell = &mut self.cells[index];
+        if cell.len() >= capacity {
+            return false;
+        }
+
+        cell.push(DormantSeed { seed, since: tick, cued: 0 });
+
+        true
+    }
+
+    /// Removes all seeds which have been in the ground for longer than their lifetime, returns the number removed
+    pub(crate) fn expire(&mut self, tick: u64, lifetime: u64) -> usize {
+        let before = self.count();
+
+        for cell in self.cells.iter_mut() {
+            cell.retain(|dormant| tick.saturating_sub(dormant.since) <= lifetime);
+        }
+
+        before - self.count()
+    }
+
+    /// Takes the seed which germinates from a cell among the seeds which have been dormant long enough,
+    /// the seed with the most energy is taken and ties are won by the seed whose parent has the lowest id
+    pub(crate) fn take_germinating(&mut self, index: usize, tick: u64, dormancy: u64) -> Option<Plant> {
+        let cell = &mut self.cells[index];
+
+        let (position, _) = cell.iter()
+            .enumerate()
+            .filter(|(_, dormant)| tick.saturating_sub(dormant.since) >= dormancy)
+            .max_by_key(|(_, dormant)| (dormant.seed.energy, std::cmp::Reverse(dormant.seed.parent())))?;
+
+        Some(cell.remove(position).seed)
+    }
+
+    /// Lets the seeds of a cell sense the light and water of the cell and takes the seed which germinates among the seeds
+    /// which have been dormant long enough and are ready to germinate, every ready seed draws its chance of germinating.
+    /// The seed with the most energy is taken and ties are won by the seed whose parent has the lowest id
+    pub(crate) fn take_cued<R: Rng>(&mut self, index: usize, tick: u64, dormancy: u64, cues: Cues, germination: &GerminationConfig, rng: &mut R) -> Option<Plant> {
+        let cell = &mut self.cells[index];
+        for dormant in cell.iter_mut() {
+            germination.sense(dormant, cues.light, cues.water);
+        }
+
+        let (position, _) = cell.iter()
+            .enumerate()
+            .filter(|(_, dormant)| tick.saturating_sub(dormant.since) >= dormancy && germination.germinates(dormant.cued, rng))
+            .max_by_key(|(_, dormant)| (dormant.seed.energy, std::cmp::Reverse(dormant.seed.parent())))?;
+
+        Some(cell.remove(position).seed)
+    }
+}
+
+/// The conditions of a cell the dormant seeds in it sense
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Cues {
+    /// The light of the cell
+    pub light: f32,
+    /// The water of the cell
+    pub water: f32,
+}
+
+impl HeapSize for SeedBank {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.cells) + self.cells.iter().map(vec_bytes).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genome::Genome;
+    use crate::population::PlantId;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn seed(parent: u64, energy: u32) -> Plant {
+        Plant::seed(PlantId(parent), None, energy, Genome::new(&[0.5, 0.5]).unwrap())
+    }
+
+    #[test]
+    fn seed_bank_bury() {
+        let mut bank = SeedBank::new(Size::new(2, 2));
+
+        assert!(bank.bury(3, seed(0, 10), 1, 2));
+        assert!(bank.bury(3, seed(1, 20), 2, 2));
+        assert!(!bank.bury(3, seed(2, 30), 2, 2));
+        assert_eq!(2, bank.count());
+        assert_eq!(vec![1, 2], bank.seeds(Coord::new(1, 1)).iter().map(|dormant| dormant.since).collect::<Vec<_>>());
+        assert!(bank.seeds(Coord::new(2, 0)).is_empty());
+    }
+
+    #[test]
+    fn seed_bank_expire() {
+        let mut bank = SeedBank::new(Size::new(2, 1));
+        bank.bury(0, seed(0, 10), 1, 4);
+        bank.bury(0, seed(1, 10), 5, 4);
+        bank.bury(1, seed(2, 10), 3, 4);
+
+        assert_eq!(0, bank.expire(6, 5));
+        assert_eq!(1, bank.expire(7, 5));
+        assert_eq!(1, bank.seeds(Coord::new(0, 0)).len());
+        assert_eq!(1, bank.expire(9, 5));
+        assert_eq!(1, bank.count());
+    }
+
+    #[test]
+    fn seed_bank_take_germinating() {
+        let mut bank = SeedBank::new(Size::new(1, 1));
+        bank.bury(0, seed(4, 10), 1, 4);
+        bank.bury(0, seed(2, 30), 2, 4);
+        bank.bury(0, seed(1, 30), 3, 4);
+        bank.bury(0, seed(0, 50), 4, 4);
+
+        // The seed with the most energy is still dormant and the tie is won by the lowest parent
+        assert_eq!(Some(PlantId(1)), bank.take_germinating(0, 5, 2).unwrap().parent());
+        assert_eq!(Some(PlantId(2)), bank.take_germinating(0, 5, 2).unwrap().parent());
+        assert_eq!(Some(PlantId(4)), bank.take_germinating(0, 5, 2).unwrap().parent());
+        assert_eq!(None, bank.take_germinating(0, 5, 2));
+        assert_eq!(1, bank.count());
+    }
+
+    #[test]
+    fn seed_bank_take_cued() {
+        let mut bank = SeedBank::new(Size::new(1, 1));
+        bank.bury(0, seed(0, 50), 4, 4);
+        bank.bury(0, seed(1, 10), 1, 4);
+        bank.bury(0, seed(2, 30), 2, 4);
+        let germination = GerminationConfig { chance: 1.0, persistence: 2, ..Default::default() };
+        let cues = Cues { light: 1.0, water: 1.0 };
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        // Every seed senses the cues but none has met them for long enough
+        assert_eq!(None, bank.take_cued(0, 5, 2, cues, &germination, &mut rng));
+        assert!(bank.seeds(Coord::new(0, 0)).iter().all(|dormant| dormant.cued == 1));
+
+        // The seed with the most energy is still dormant
+        assert_eq!(Some(PlantId(2)), bank.take_cued(0, 5, 2, cues, &germination, &mut rng).unwrap().parent());
+        assert_eq!(2, bank.count());
+
+        let never = GerminationConfig { chance: 0.0, ..germination };
+        assert_eq!(None, bank.take_cued(0, 9, 2, cues, &never, &mut rng));
+    }
+
+    #[test]
+    fn seed_bank_reframe() {
+        let mut bank = SeedBank::new(Size::new(2, 2));
+        bank.bury(0, seed(0, 10), 1, 4);
+        bank.bury(3, seed(1, 10), 1, 4);
+        bank.reframe(Rect::new(1, 1, 2, 1));
+
+        assert_eq!(Size::new(2, 1), bank.size());
+        assert_eq!(1, bank.count());
+        assert_eq!(Some(PlantId(1)), bank.seeds(Coord::new(0, 0))[0].seed.parent());
+    }
+
+    #[test]
+    fn seed_bank_clear() {
+        let mut bank = SeedBank::new(Size::new(2, 1));
+        bank.bury(0, seed(0, 10), 1, 4);
+        bank.clear();
+
+        assert_eq!(0, bank.count());
+    }
+}
@@ -0,0 +1,426 @@
+use crate::board::{Fields, Size};
+use crate::genome::{self, Genome};
+use crate::population::{Plant, Population};
+
+/// The position of the sun which decides the shadows cast by the terrain
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sun {
+    /// The direction towards the sun along the board in radians, measured from the positive x axis towards
+    /// the positive y axis
+    pub azimuth: f32,
+    /// The angle of the sun above the horizon in radians, there are no shadows when the sun is straight above
+    pub altitude: f32,
+    /// The fraction of the light which is blocked in a shaded cell
+    pub shadow_strength: f32,
+    /// How much the slopes facing towards or away from the sun change the light, at 0 all ground gets the light of flat ground
+    /// and at 1 the light follows the angle between the ground and the sun fully
+    pub slope_strength: f32,
+}
+
+impl Sun {
+    /// Creates a new sun lighting slopes like flat ground
+    /// 
+    /// # Parameters
+    /// 
+    /// azimuth: The direction towards the sun along the board in radians, measured from the positive x axis towards
+    /// the positive y axis
+    /// altitude: The angle of the sun above the horizon in radians
+    /// shadow_strength: The fraction of the light which is blocked in a shaded cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::shadow::Sun;
+    /// 
+    /// let sun = Sun::new(std::f32::consts::PI, 0.5, 0.8);
+    /// 
+    /// assert_eq!(0.5, sun.altitude);
+    /// ```
+    pub fn new(azimuth: f32, altitude: f32, shadow_strength: f32) -> Self {
+        Self { azimuth, altitude, shadow_strength, slope_strength: 0.0 }
+    }
+
+    /// Finds how much the slope of the terrain in every cell changes the light compared to flat ground, slopes facing
+    /// the sun get more light and slopes facing away get less down to none. The slope is found from the neighbouring cells
+    /// on either side, and the sun does not change the light if it is below the horizon
+    /// 
+    /// # Parameters
+    /// 
+    /// fields: The fields with the elevation of the terrain
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, shadow::Sun};
+    /// 
+    /// let size = board::Size::new(3, 1);
+    /// let fields = board::Fields::new(size, &[1.0; 3]).unwrap().with_elevation(&[2.0, 1.0, 0.0]).unwrap();
+    /// let sun = Sun { slope_strength: 1.0, ..Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.0) };
+    /// let factors = sun.slope_factors(&fields);
+    /// 
+    /// // The ground rises towards the sun, so it faces away from it and gets no light
+    /// assert!(factors.iter().all(|&factor| factor.abs() < 1e-6));
+    /// ```
+    pub fn slope_factors(&self, fields: &Fields) -> Vec<f32> {
+        let (w, h) = fields.size.size();
+        let strength = self.slope_strength.clamp(0.0, 1.0);
+        let height = self.altitude.sin();
+        if strength == 0.0 || height <= 0.0 {
+            return vec![1.0; fields.size.len()];
+        }
+
+        let toward = (self.altitude.cos() * self.azimuth.cos(), self.altitude.cos() * self.azimuth.sin());
+        let elevation = |x: usize, y: usize| fields.elevation[x + y * w];
+
+        fields.size.coords().map(|coord| {
+            let (x, y) = (coord.x, coord.y);
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(w - 1));
+            let (top, bottom) = (y.saturating_sub(1), (y + 1).min(h - 1));
+            let dx = if right > left { (elevation(right, y) - elevation(left, y)) / (right - left) as f32 } else { 0.0 };
+            let dy = if bottom > top { (elevation(x, bottom) - elevation(x, top)) / (bottom - top) as f32 } else { 0.0 };
+
+            // The light on ground with the normal (-dx, -dy, 1) compared to flat ground
+            let facing = (height - dx * toward.0 - dy * toward.1) / (1.0 + dx * dx + dy * dy).sqrt();
+            let factor = facing.max(0.0) / height;
+
+            1.0 + (factor - 1.0) * strength
+        }).collect()
+    }
+}
+
+/// The settings for letting plants grow tall and cast shadows on their neighbours under the sun. A plant stands
+/// on the terrain of its cell and collects light at its top, so taller plants escape the shadows of their neighbours
+/// but pay upkeep for their height
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanopyConfig {
+    /// The height above the ground of a plant with a height gene of 1, in the units of the elevation
+    pub max_height: f32,
+    /// The extra energy a plant with a height gene of 1 pays every step, this scales with the height gene
+    pub height_cost: f32,
+}
+
+impl Default for CanopyConfig {
+    fn default() -> Self {
+        Self {
+            max_height: 3.0,
+            height_cost: 2.0,
+        }
+    }
+}
+
+impl CanopyConfig {
+    /// Finds how tall a plant grows above the ground, plants without a height gene lie flat on the ground
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, shadow::CanopyConfig};
+    /// 
+    /// let config = CanopyConfig { max_height: 4.0, ..Default::default() };
+    /// 
+    /// assert_eq!(2.0, config.height(&Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+    /// assert_eq!(0.0, config.height(&Genome::new(&[0.0, 0.0]).unwrap()));
+    /// ```
+    pub fn height(&self, genome: &Genome) -> f32 {
+        genome.get(genome::GENE_HEIGHT).unwrap_or(0.0) * self.max_height.max(0.0)
+    }
+
+    /// Finds the extra upkeep a plant pays in a step for its height
+    /// 
+    /// # Parameters
+    /// 
+    /// plant: The plant paying the upkeep
+    pub fn upkeep(&self, plant: &Plant) -> u32 {
+        (plant.genome.get(genome::GENE_HEIGHT).unwrap_or(0.0) * self.height_cost.max(0.0)) as u32
+    }
+
+    /// Finds the height of the top of every cell, the elevation of the terrain plus the height of the plant in it
+    /// 
+    /// # Parameters
+    /// 
+    /// fields: The fields with the elevation of the terrain
+    /// population: The plants standing on the terrain
+    pub fn surface(&self, fields: &Fields, population: &Population) -> Vec<f32> {
+        fields.elevation.iter()
+            .zip(population.cells())
+            .map(|(&elevation, cell)| elevation + cell.map_or(0.0, |plant| self.height(&plant.genome)))
+            .collect()
+    }
+}
+
+/// Finds the cells which are shaded by the terrain, a cell is shaded if the terrain along the ray towards the sun
+/// rises above the ray. The ray is sampled once for every cell of distance
+/// 
+/// # Parameters
+/// 
+/// fields: The fields with the elevation of the terrain
+/// sun: The position of the sun
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, shadow::{self, Sun}};
+/// 
+/// let size = board::Size::new(3, 1);
+/// let fields = board::Fields::new(size, &[1.0; 3]).unwrap().with_elevation(&[2.5, 0.0, 0.0]).unwrap();
+/// let sun = Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 1.0);
+/// 
+/// assert_eq!(vec![false, true, true], shadow::shadow_mask(&fields, &sun));
+/// ```
+pub fn shadow_mask(fields: &Fields, sun: &Sun) -> Vec<bool> {
+    surface_shadow_mask(fields.size, &fields.elevation, sun)
+}
+
+/// Finds the cells which are shaded by a surface of any height such as the terrain with the plants on top,
+/// a cell is shaded if the surface along the ray towards the sun rises above the ray.
+/// The ray is sampled once for every cell of distance
+/// 
+/// # Parameters
+/// 
+/// size: The size of the board
+/// surface: The height of the top of every cell
+/// sun: The position of the sun
+/// 
+/// # Panics
+/// 
+/// If there are fewer heights than cells on the board
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::Size, shadow::{self, Sun}};
+/// 
+/// // A tree of height 2.5 on flat ground shades the two cells behind it
+/// let sun = Sun::new(0.0, std::f32::consts::FRAC_PI_4, 1.0);
+/// 
+/// assert_eq!(vec![true, true, false, false], shadow::surface_shadow_mask(Size::new(4, 1), &[0.0, 0.0, 2.5, 0.0], &sun));
+/// ```
+pub fn surface_shadow_mask(size: Size, surface: &[f32], sun: &Sun) -> Vec<bool> {
+    assert!(surface.len() >= size.len(), "There must be a height for every cell");
+
+    if sun.altitude >= std::f32::consts::FRAC_PI_2 {
+        return vec![false; size.len()];
+    }
+
+    let (w, h) = size.size();
+    let (dx, dy) = (sun.azimuth.cos(), sun.azimuth.sin());
+    let rise = sun.altitude.max(0.0).tan();
+    let highest = surface.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    (0..size.len())
+        .map(|index| {
+            let coord = size.coord_of(index).unwrap();
+            let start = surface[index];
+
+            for step in 1.. {
+                let distance = step as f32;
+                let ray = start + distance * rise;
+
+                // Nothing can block the ray once it is above the highest point
+                if ray >= highest {
+                    return false;
+                }
+
+                let x = coord.x as f32 + 0.5 + distance * dx;
+                let y = coord.y as f32 + 0.5 + distance * dy;
+
+                if x < 0.0 || y < 0.0 || x >= w as f32 || y >= h as f32 {
+                    return false;
+                }
+
+                if surface[x as usize + y as usize * w] > ray {
+                    return true;
+                }
+            }
+
+            false
+        })
+        .collect()
+}
+
+/// Calculates the light in every cell after the terrain has cast its shadows
+/// 
+/// # Parameters
+/// 
+/// fields: The fields with the light and the elevation of the terrain
+/// sun: The position of the sun
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, shadow::{self, Sun}};
+/// 
+/// let size = board::Size::new(3, 1);
+/// let fields = board::Fields::new(size, &[1.0; 3]).unwrap().with_elevation(&[1.5, 0.0, 0.0]).unwrap();
+/// let sun = Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.75);
+/// 
+/// assert_eq!(vec![1.0, 0.25, 1.0], shadow::shaded_light(&fields, &sun));
+/// ```
+pub fn shaded_light(fields: &Fields, sun: &Sun) -> Vec<f32> {
+    shade(fields, shadow_mask(fields, sun), sun)
+}
+
+/// Calculates the light in every cell after the terrain and the plants have cast their shadows,
+/// the light is collected at the top of the plants
+/// 
+/// # Parameters
+/// 
+/// fields: The fields with the light and the elevation of the terrain
+/// population: The plants casting shadows
+/// sun: The position of the sun
+/// canopy: The settings for how tall the plants grow
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, shadow::{self, CanopyConfig, Sun}};
+/// 
+/// let size = board::Size::new(3, 1);
+/// let fields = board::Fields::new(size, &[1.0; 3]).unwrap();
+/// let mut population = Population::new(size);
+/// population.insert(board::Coord::new(0, 0), Plant::new(100, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+/// let sun = Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.5);
+/// let canopy = CanopyConfig { max_height: 4.0, ..Default::default() };
+/// 
+/// assert_eq!(vec![1.0, 0.5, 1.0], shadow::canopy_light(&fields, &population, &sun, &canopy));
+/// ```
+pub fn canopy_light(fields: &Fields, population: &Population, sun: &Sun, canopy: &CanopyConfig) -> Vec<f32> {
+    let surface = canopy.surface(fields, population);
+
+    shade(fields, surface_shadow_mask(fields.size, &surface, sun), sun)
+}
+
+/// Removes the part of the light blocked by the sun's shadows in the shaded cells and lights the slopes by how they face the sun
+fn shade(fields: &Fields, mask: Vec<bool>, sun: &Sun) -> Vec<f32> {
+    let strength = sun.shadow_strength.clamp(0.0, 1.0);
+
+    fields.light.iter()
+        .zip(mask)
+        .zip(sun.slope_factors(fields))
+        .map(|((&light, shaded), slope)| if shaded { light * (1.0 - strength) * slope } else { light * slope })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Size;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    fn fields(w: usize, h: usize, elevation: &[f32]) -> Fields {
+        let size = Size::new(w, h);
+
+        Fields::new(size, &vec![1.0; size.len()]).unwrap().with_elevation(elevation).unwrap()
+    }
+
+    #[test]
+    fn sun_new() {
+        let sun = Sun::new(1.0, 0.5, 0.25);
+
+        assert_eq!(Sun { azimuth: 1.0, altitude: 0.5, shadow_strength: 0.25, slope_strength: 0.0 }, sun);
+    }
+
+    #[test]
+    fn shadow_mask_flat() {
+        let fields = fields(3, 3, &[1.0; 9]);
+
+        assert_eq!(vec![false; 9], shadow_mask(&fields, &Sun::new(0.3, 0.1, 1.0)));
+    }
+
+    #[test]
+    fn shadow_mask_wall() {
+        // The shadow of a wall of height 2.5 under a sun at 45 degrees reaches 2.5 cells
+        let fields = fields(6, 1, &[2.5, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(vec![false, true, true, false, false, false], shadow_mask(&fields, &Sun::new(PI, FRAC_PI_4, 1.0)));
+    }
+
+    #[test]
+    fn shadow_mask_length() {
+        // The shadow of a wall of height 2.4 under a sun rising 1 cell every 2 cells reaches 4.8 cells
+        let fields = fields(7, 1, &[2.4, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let sun = Sun::new(PI, 0.5f32.atan(), 1.0);
+
+        assert_eq!(vec![false, true, true, true, true, false, false], shadow_mask(&fields, &sun));
+    }
+
+    #[test]
+    fn shadow_mask_direction() {
+        // Only the side away from the sun is shaded
+        let fields = fields(5, 1, &[0.0, 0.0, 2.5, 0.0, 0.0]);
+
+        assert_eq!(vec![true, true, false, false, false], shadow_mask(&fields, &Sun::new(0.0, FRAC_PI_4, 1.0)));
+    }
+
+    #[test]
+    fn shadow_mask_vertical() {
+        let fields = fields(1, 4, &[0.0, 0.0, 0.0, 2.5]);
+
+        assert_eq!(vec![false, true, true, false], shadow_mask(&fields, &Sun::new(FRAC_PI_2, FRAC_PI_4, 1.0)));
+    }
+
+    #[test]
+    fn shadow_mask_hill() {
+        // A cell high enough is not shaded by the wall in front of it but casts its own shadow
+        let fields = fields(4, 1, &[2.5, 0.0, 2.0, 0.0]);
+
+        assert_eq!(vec![false, true, false, true], shadow_mask(&fields, &Sun::new(PI, FRAC_PI_4, 1.0)));
+    }
+
+    #[test]
+    fn shadow_mask_zenith() {
+        let fields = fields(3, 1, &[5.0, 0.0, 0.0]);
+
+        assert_eq!(vec![false; 3], shadow_mask(&fields, &Sun::new(PI, FRAC_PI_2, 1.0)));
+    }
+
+    #[test]
+    fn canopy_config_upkeep() {
+        let config = CanopyConfig { height_cost: 3.0, ..Default::default() };
+
+        assert_eq!(1, config.upkeep(&Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap())));
+        assert_eq!(0, config.upkeep(&Plant::new(10, Genome::new(&[0.0, 0.0]).unwrap())));
+    }
+
+    #[test]
+    fn canopy_light_tall_neighbour() {
+        // The taller plant is not shaded by the shorter one in front of it but shades the cell behind it
+        let size = Size::new(4, 1);
+        let fields = Fields::new(size, &[1.0; 4]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(crate::board::Coord::new(0, 0), Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+        population.insert(crate::board::Coord::new(1, 0), Plant::new(10, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        let canopy = CanopyConfig { max_height: 2.0, ..Default::default() };
+
+        assert_eq!(vec![1.0, 1.0, 0.0, 1.0], canopy_light(&fields, &population, &Sun::new(PI, FRAC_PI_4, 1.0), &canopy));
+    }
+
+    #[test]
+    fn shaded_light_strength() {
+        let fields = fields(3, 1, &[1.5, 0.0, 0.0]);
+
+        assert_eq!(vec![1.0, 0.5, 1.0], shaded_light(&fields, &Sun::new(PI, FRAC_PI_4, 0.5)));
+        assert_eq!(vec![1.0, 0.0, 1.0], shaded_light(&fields, &Sun::new(PI, FRAC_PI_4, 2.0)));
+    }
+
+    #[test]
+    fn sun_slope_factors() {
+        // A ridge along y, the sun is low in the east so the eastern slope gets more light than flat ground
+        let fields = fields(5, 2, &[0.0, 0.5, 1.0, 0.5, 0.0, 0.0, 0.5, 1.0, 0.5, 0.0]);
+        let sun = Sun { slope_strength: 1.0, ..Sun::new(0.0, FRAC_PI_4, 0.0) };
+        let factors = sun.slope_factors(&fields);
+
+        assert!(factors[3] > 1.0 && factors[1] < 1.0);
+        assert!((factors[2] - 1.0).abs() < 1e-6);
+        assert_eq!(factors[..5], factors[5..]);
+
+        // Half the strength goes halfway to flat ground
+        let half = Sun { slope_strength: 0.5, ..sun }.slope_factors(&fields);
+        assert!((half[3] - (1.0 + factors[3]) / 2.0).abs() < 1e-6);
+        assert_eq!(vec![1.0; 10], Sun::new(0.0, FRAC_PI_4, 0.0).slope_factors(&fields));
+    }
+}
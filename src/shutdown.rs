@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::simulation::Simulation;
+use crate::stop::StopCondition;
+
+/// The exit code of a run stopped by a second signal without finishing its step, 128 plus the number of SIGINT
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// A request to stop a run shared between the thread stepping the simulation and whoever asks it to stop,
+/// such as the handler of Ctrl-C and SIGTERM. The run checks it between steps so the current step always finishes.
+/// As a stop condition it stops a run once it has been requested
+#[derive(Clone, Debug, Default)]
+pub struct Shutdown {
+    /// True once a stop has been requested
+    requested: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    /// Creates a new shutdown which is only requested by calling request
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new shutdown which is requested by Ctrl-C and SIGTERM. A second signal while the run is stopping exits
+    /// the process at once with FORCED_EXIT_CODE, the autosave files already written are still complete.
+    /// The handler can only be installed once for every process
+    /// 
+    /// # Errors
+    /// 
+    /// ShutdownError::Install: This will occur if the handler could not be installed
+    pub fn install() -> Result<Self, ShutdownError> {
+        let shutdown = Self::new();
+        let requested = Arc::clone(&shutdown.requested);
+        ctrlc::set_handler(move || {
+            if requested.swap(true, Ordering::SeqCst) {
+                std::process::exit(FORCED_EXIT_CODE);
+            }
+        }).map_err(|error| ShutdownError::Install { message: error.to_string() })?;
+
+        Ok(shutdown)
+    }
+
+    /// Requests the run to stop after the current step
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if a stop has been requested
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
+
+impl StopCondition for Shutdown {
+    fn should_stop(&mut self, _simulation: &Simulation) -> bool {
+        self.is_requested()
+    }
+}
+
+/// The errors which can occur when handling signals
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ShutdownError {
+    #[error("Unable to handle the signals: {message}")]
+    Install {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+    use crate::stop::TickLimit;
+
+    fn simulation() -> Simulation {
+        let board = BoardBuilder::new().size(5, 5).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(2, 2), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+
+        Simulation::new(board, population, SimulationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn shutdown_stops_run() {
+        let mut simulation = simulation();
+        let shutdown = Shutdown::new();
+        let mut steps = 0;
+        let ticks = simulation.run_until(shutdown.clone().or(|_: &Simulation| {
+            steps += 1;
+            if steps == 4 {
+                shutdown.request();
+            }
+            false
+        }));
+
+        // Both conditions are checked before every step, the request is seen at the next check
+        assert_eq!(4, ticks);
+        assert!(shutdown.is_requested());
+        assert_eq!(0, simulation.run_until(shutdown.clone().or(TickLimit(100))));
+    }
+
+    #[test]
+    fn shutdown_across_threads() {
+        let shutdown = Shutdown::new();
+        let remote = shutdown.clone();
+        thread::spawn(move || remote.request()).join().unwrap();
+
+        assert!(shutdown.is_requested());
+    }
+}
@@ -0,0 +1,4696 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use thiserror::Error;
+
+use crate::adaptive::{AdaptiveMutationConfig, AdaptiveMutationError, MutationAdjustment, MutationController};
+use crate::aggregate::Aggregates;
+use crate::aging::AgingConfig;
+use crate::alert::{AlertConfig, AlertMonitor};
+use crate::allelopathy::{AllelopathyConfig, ToxinField};
+use crate::archive::{ArchiveConfig, HallOfFame};
+use crate::audit::{AuditReport, EnergyAudit, Flows};
+use crate::autosave::{Autosave, AutosaveError};
+use crate::board::{Board, Coord, FieldCreateError, Fill, Modifier, Multipliers, Rect, Size};
+use crate::climate::ThermalConfig;
+use crate::clutch::ClutchConfig;
+use crate::demography::Demography;
+use crate::dirty::DirtyCells;
+use crate::disturbance::{Disturbance, DisturbanceKind, DisturbanceRecord, Disturbances, RandomDisturbance, Resource};
+use crate::ecotone::EdgeBand;
+use crate::events::{Hooks, SimEvent};
+use crate::field::Field;
+use crate::fitness::{FitnessConfig, FitnessTracker};
+use crate::genes::GeneRegistry;
+use crate::genome::{Crossover, Genome, GenomeStore, MutationConfig, MutationConfigError};
+use crate::germination::GerminationConfig;
+use crate::history::History;
+use crate::invariants::{EnergyBalance, InvariantViolation};
+use crate::memory::{btree_map_bytes, vec_bytes, HeapSize, MemoryReport};
+use crate::mycorrhiza::MycorrhizaConfig;
+use crate::neural::{NeuralConfig, Sensors};
+use crate::nutrient::{NutrientConfig, NutrientField};
+use crate::occupancy::OccupancyMap;
+use crate::organism::{CellStock, LightResources, Organism, ReproductionModel, ResourceModel, SeedDispersal, Surroundings};
+use crate::pathogen::PathogenConfig;
+use crate::phenotype::{Development, DirectDevelopment};
+use crate::phylogeny::Phylogeny;
+use crate::pollination::PollinationConfig;
+use crate::population::{Cull, Plant, PlantId, Population};
+use crate::profile::{Phase, StepProfile, Stopwatch};
+use crate::roots::RootConfig;
+use crate::scenario::{Scenario, ScenarioAction};
+use crate::schedule::{Scheduler, Subsystem};
+use crate::seedbank::{Cues, SeedBankConfig};
+use crate::shadow::{self, CanopyConfig, Sun};
+use crate::species::{SpeciesConfig, SpeciesEvent, SpeciesTracker};
+use crate::stats::{Publisher, TickStats};
+use crate::trace::{DeathCause, PlantTrace, ReproductionDecision, TraceEvent};
+use crate::tuning::Parallelism;
+use crate::water::{WaterConfig, WaterField};
+use crate::wind::{self, WindConfig};
+use crate::world::Emigrant;
+
+/// The offsets to all the neighbouring cells a seed can land in
+const NEIGHBOURS: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+/// Runs the evolution of a population of plants on a board. The plants go through the life cycle of the Organism trait
+/// together with the other subsystems
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// The board the plants live on
+    board: Board,
+    /// All the living plants
+    population: Population,
+    /// The settings of the simulation
+    config: SimulationConfig,
+    /// The random number generator used for all random choices
+    rng: ChaCha8Rng,
+    /// The number of steps which have been run
+    tick: u64,
+    /// The controller adjusting the mutation rate if adaptive mutation is enabled
+    mutation_controller: Option<MutationController>,
+    /// The lineage tree of all plants in the simulation
+    phylogeny: Phylogeny,
+    /// The light in every cell after the terrain and the plants have cast their shadows and the edge band has been applied
+    light: Vec<f32>,
+    /// The water on the board
+    water: WaterField,
+    /// The litter of dead plants and the nutrients in the soil
+    nutrients: NutrientField,
+    /// The toxin released by the plants
+    toxins: ToxinField,
+    /// The tracker clustering the plants into species if species tracking is enabled
+    species: Option<SpeciesTracker>,
+    /// The disturbances waiting to happen and the droughts going on
+    disturbances: Disturbances,
+    /// The genomes waiting to be introduced and where to place them by the tick they are introduced at
+    introductions: BTreeMap<u64, Vec<(Vec<Genome>, Placement)>>,
+    /// The actions carried out by hand during the run, each at the tick of the step it counts towards
+    interventions: Scenario,
+    /// The subscriptions the statistics of every step are sent to
+    subscribers: Vec<Publisher>,
+    /// The functions called for every event
+    hooks: Hooks,
+    /// All changes made to the settings during the run
+    config_log: Vec<ConfigChange>,
+    /// The states before the latest steps if the history is enabled
+    history: Option<History<SavedState>>,
+    /// Turns the genomes of the plants into their traits
+    development: Arc<dyn Development>,
+    /// What the plants gain from the cells they live in
+    resource_model: Arc<dyn ResourceModel>,
+    /// When the plants reproduce and where their seeds land
+    reproduction_model: Arc<dyn ReproductionModel>,
+    /// The seeds which left the board during the latest steps, None if seeds leaving the board are lost
+    emigrants: Option<Vec<Emigrant>>,
+    /// One copy of every distinct genome on the board such that identical genomes share their genes
+    genomes: GenomeStore,
+    /// The cells which may look different since they were last cleared
+    dirty: DirtyCells,
+    /// The time spent in every phase of the steps if profiling is enabled
+    profile: Option<StepProfile>,
+    /// The best genomes of the plants which have died if the hall of fame is enabled
+    archive: Option<HallOfFame>,
+    /// The offspring born to every plant and species over the latest steps if fitness is tracked
+    fitness: Option<FitnessTracker>,
+    /// The energy on the board before and after the latest step, None before the first step and after going back in time
+    balance: Option<EnergyBalance>,
+    /// The books of the energy flowing through every phase of the steps if the energy is audited
+    audit: Option<EnergyAudit>,
+    /// The steps every cell held a plant and the plants which died in it if occupancy is tracked
+    occupancy: Option<OccupancyMap>,
+    /// The ages of the plants and the ages they died and reproduced at if demography is tracked
+    demography: Option<Demography>,
+    /// The events of the traced plants and their descendants if any plants are traced
+    trace: Option<PlantTrace>,
+    /// The rules checked against the statistics of every step and the alerts they raised if alerts are watched
+    alerts: Option<AlertMonitor>,
+    /// The background thread saving checkpoints to a directory if autosaving is enabled
+    autosave: Autosave,
+    /// The part of the board the steps are restricted to, None if the whole board is simulated
+    active: Option<Rect>,
+    /// The part of the board outside the active region while a step runs
+    frozen: Option<Frozen>,
+    /// How the work on the cells of a step is split over threads
+    parallelism: Parallelism,
+}
+
+/// The part of the board outside the active region, its plants are taken off the board during a step
+/// and its fields are put back afterwards such that nothing outside the region changes
+#[derive(Clone, Debug)]
+struct Frozen {
+    /// The cells outside the active region
+    cells: Vec<usize>,
+    /// The plants outside the active region together with their cells
+    plants: Vec<(usize, Plant)>,
+    /// The water before the step
+    water: WaterField,
+    /// The nutrients before the step
+    nutrients: NutrientField,
+    /// The toxin before the step
+    toxins: ToxinField,
+}
+
+/// The state of a simulation before a step, kept in the history so the step can be undone
+#[derive(Clone, Debug)]
+struct SavedState {
+    /// The board the plants live on
+    board: Board,
+    /// All the living plants
+    population: Population,
+    /// The settings of the simulation
+    config: SimulationConfig,
+    /// The random number generator
+    rng: ChaCha8Rng,
+    /// The number of steps which had been run
+    tick: u64,
+    /// The controller adjusting the mutation rate
+    mutation_controller: Option<MutationController>,
+    /// The lineage tree of all plants
+    phylogeny: Phylogeny,
+    /// The light in every cell
+    light: Vec<f32>,
+    /// The water on the board
+    water: WaterField,
+    /// The litter of dead plants and the nutrients in the soil
+    nutrients: NutrientField,
+    /// The toxin released by the plants
+    toxins: ToxinField,
+    /// The tracker clustering the plants into species
+    species: Option<SpeciesTracker>,
+    /// The disturbances waiting to happen and the droughts going on
+    disturbances: Disturbances,
+    /// The genomes waiting to be introduced
+    introductions: BTreeMap<u64, Vec<(Vec<Genome>, Placement)>>,
+    /// All changes made to the settings
+    config_log: Vec<ConfigChange>,
+    /// The best genomes of the plants which had died
+    archive: Option<HallOfFame>,
+    /// The offspring born over the latest steps
+    fitness: Option<FitnessTracker>,
+    /// The occupancy and deaths of every cell
+    occupancy: Option<OccupancyMap>,
+    /// The ages of the plants and the ages they died and reproduced at
+    demography: Option<Demography>,
+}
+
+impl HeapSize for SavedState {
+    fn heap_bytes(&self) -> usize {
+        self.board.heap_bytes() + self.population.heap_bytes() + self.phylogeny.heap_bytes() + vec_bytes(&self.light)
+            + self.water.heap_bytes() + self.nutrients.heap_bytes() + self.toxins.heap_bytes() + self.species.heap_bytes() + vec_bytes(&self.config_log)
+            + self.archive.heap_bytes() + self.fitness.heap_bytes() + btree_map_bytes(&self.introductions)
+            + self.occupancy.heap_bytes() + self.demography.heap_bytes()
+    }
+}
+
+impl Simulation {
+    /// Creates a new simulation
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board the plants live on
+    /// population: The initial plants
+    /// config: The settings of the simulation
+    /// 
+    /// # Errors
+    /// 
+    /// SimulationCreateError::Size: This will occur if the population does not have the same size as the board
+    /// 
+    /// SimulationCreateError::Blocked: This will occur if a plant is placed on terrain where plants cannot grow
+    /// 
+    /// SimulationCreateError::Config: This will occur if the settings cannot be used
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[0.0, 0.5, 0.5, 1.0]).unwrap();
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+    /// let simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// 
+    /// assert_eq!(0, simulation.tick());
+    /// ```
+    pub fn new(board: Board, mut population: Population, config: SimulationConfig) -> Result<Self, SimulationCreateError> {
+        // Make sure the population fits on the board
+        if board.fields.size != population.size() {
+            return Err(SimulationCreateError::Size { board: board.fields.size, population: population.size() });
+        }
+
+        // Make sure no plants start on blocked ground
+        if let Some((coord, _)) = population.iter().find(|(coord, _)| board.fields.is_blocked(board.fields.size.index_of(*coord).unwrap())) {
+            return Err(SimulationCreateError::Blocked { coord });
+        }
+
+        // Make sure the scenario only uses regions of the board
+        if let Some(name) = config.scenario.missing_region(&board) {
+            return Err(SimulationCreateError::Region { name: name.to_string() });
+        }
+
+        config.validate()?;
+
+        let rng = ChaCha8Rng::seed_from_u64(config.seed);
+        let mutation_controller = config.adaptive_mutation
+            .map(|adaptive| MutationController::new(adaptive, config.mutation.rate))
+            .transpose()
+            .map_err(ConfigError::from)?;
+
+        // Identical founders share their genes like the seeds born later
+        let mut genomes = GenomeStore::new();
+        for plant in population.plants_mut() {
+            plant.genome = genomes.intern(&plant.genome);
+        }
+
+        // The initial plants are the founders of the lineage tree
+        let mut phylogeny = Phylogeny::new();
+        for (_, plant) in population.iter() {
+            phylogeny.record_birth(plant.id(), None, None, 0, plant.genome.clone());
+        }
+
+        let light = derived_light(&board, &population, &config);
+        let water = WaterField::new(board.fields.size, &board.fields.water);
+        let nutrients = NutrientField::new(board.fields.size);
+        let toxins = ToxinField::new(board.fields.size);
+        let dirty = DirtyCells::all(board.fields.size);
+        let archive = config.archive.map(HallOfFame::new);
+        let fitness = config.fitness.map(FitnessTracker::new);
+
+        // The initial plants are clustered into the founding species
+        let species = config.species.map(|config| {
+            let mut tracker = SpeciesTracker::new(config);
+            tracker.cluster(0, &population);
+            tracker
+        });
+
+        Ok(Self { board, population, config, rng, tick: 0, mutation_controller, phylogeny, light, water, nutrients, toxins, species, disturbances: Disturbances::default(), introductions: BTreeMap::new(), interventions: Scenario::new(), subscribers: Vec::new(), hooks: Hooks::default(), config_log: Vec::new(), history: None, development: Arc::new(DirectDevelopment), resource_model: Arc::new(LightResources), reproduction_model: Arc::new(SeedDispersal), emigrants: None, genomes, dirty, profile: None, archive, fitness, balance: None, audit: None, occupancy: None, demography: None, trace: None, alerts: None, autosave: Autosave::default(), active: None, frozen: None, parallelism: Parallelism::default() })
+    }
+
+    /// Returns the board the plants live on
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns all the living plants
+    pub fn population(&self) -> &Population {
+        &self.population
+    }
+
+    /// Returns the settings of the simulation
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    /// Returns the number of steps which have been run
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the light in every cell after the terrain and the plants have cast their shadows and the edge band has been applied
+    pub fn light(&self) -> &[f32] {
+        &self.light
+    }
+
+    /// Returns the water on the board
+    pub fn water(&self) -> &WaterField {
+        &self.water
+    }
+
+    /// Returns the litter of dead plants and the nutrients in the soil
+    pub fn nutrients(&self) -> &NutrientField {
+        &self.nutrients
+    }
+
+    /// Returns the toxin released by the plants
+    pub fn toxins(&self) -> &ToxinField {
+        &self.toxins
+    }
+
+    /// Generates the wind carrying the seeds in every cell for the next step, None if there is no wind
+    pub fn wind(&self) -> Option<Field<(f32, f32)>> {
+        self.config.wind.map(|wind| wind.field(self.board.fields.size, self.tick + 1))
+    }
+
+    /// Builds the tables answering the totals of the light after the shadows, the plants and their energy in any rectangle
+    /// in constant time, for queries on many regions of the current state
+    pub fn aggregates(&self) -> Aggregates {
+        Aggregates::new(&self.light, &self.population)
+    }
+
+    /// Returns the species the plants have been clustered into, None if species tracking is disabled
+    pub fn species(&self) -> Option<&SpeciesTracker> {
+        self.species.as_ref()
+    }
+
+    /// Returns the lineage tree of all plants in the simulation
+    pub fn phylogeny(&self) -> &Phylogeny {
+        &self.phylogeny
+    }
+
+    /// Returns the best genomes of the plants which have died, None if the hall of fame is not enabled
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{archive::ArchiveConfig, board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(0.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(25, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let config = SimulationConfig { upkeep: 10, archive: Some(ArchiveConfig::default()), ..Default::default() };
+    /// let mut simulation = Simulation::new(board, population, config).unwrap();
+    /// for _ in 0..3 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// // The plant survived two steps before it ran out of energy
+    /// let hall = simulation.hall_of_fame().unwrap();
+    /// assert_eq!(1, hall.len());
+    /// assert_eq!(2, hall.entries()[0].score);
+    /// ```
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame> {
+        self.archive.as_ref()
+    }
+
+    /// Returns the offspring born to every plant and species within the fitness window, None if fitness is not tracked
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, fitness::FitnessConfig, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(3, 3).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+    /// let config = SimulationConfig { fitness: Some(FitnessConfig { window: 10 }), ..Default::default() };
+    /// let mut simulation = Simulation::new(board, population, config).unwrap();
+    /// simulation.step();
+    /// 
+    /// let founder = simulation.phylogeny().roots().next().unwrap().id;
+    /// assert_eq!(1, simulation.fitness().unwrap().offspring(founder));
+    /// assert_eq!(1, simulation.phylogeny().descendants_since(founder, 0));
+    /// ```
+    pub fn fitness(&self) -> Option<&FitnessTracker> {
+        self.fitness.as_ref()
+    }
+
+    /// Returns the energy on the board before and after the latest step and the energy collected during it,
+    /// None before the first step and after going back in time
+    pub fn energy_balance(&self) -> Option<EnergyBalance> {
+        self.balance
+    }
+
+    /// Removes all plants from the lineage tree which have no living descendants, this is also done
+    /// in the steps the phylogeny subsystem is scheduled for
+    pub fn prune_phylogeny(&mut self) {
+        self.phylogeny.prune();
+    }
+
+    /// Returns the mutation rate currently used, this differs from the configured rate if adaptive mutation is enabled
+    pub fn mutation_rate(&self) -> f32 {
+        match &self.mutation_controller {
+            Some(controller) => controller.rate(),
+            None => self.config.mutation.rate,
+        }
+    }
+
+    /// Returns all adjustments made to the mutation rate by adaptive mutation
+    pub fn mutation_log(&self) -> &[MutationAdjustment] {
+        match &self.mutation_controller {
+            Some(controller) => controller.log(),
+            None => &[],
+        }
+    }
+
+    /// Changes the temperature field during the run
+    /// 
+    /// # Parameters
+    /// 
+    /// temperature: The new temperature in every cell
+    /// 
+    /// # Errors
+    /// 
+    /// FieldCreateError::Size: This will occur if the temperature field is not the correct size for the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, climate, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), board::Fields::new(size, &[1.0; 4]).unwrap());
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.set_temperature(&climate::latitudinal_gradient(size, 5.0, 15.0)).unwrap();
+    /// 
+    /// assert_eq!(vec![5.0, 5.0, 15.0, 15.0], simulation.board().fields.temperature);
+    /// ```
+    pub fn set_temperature(&mut self, temperature: &[f32]) -> Result<(), FieldCreateError> {
+        self.board.fields.temperature = Field::from_slice("Temperature", self.board.fields.size, temperature)?;
+
+        Ok(())
+    }
+
+    /// Sets the adjustment of the light energy collected in a cell from the following step, returns false if the cell is outside the board
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The coordinate of the cell
+    /// modifier: The adjustment of the cell, Modifier::default() removes the adjustment
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Modifier}, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.set_modifier(Coord::new(0, 1), Modifier::new(0.0, 1.5));
+    /// 
+    /// assert_eq!(1.5, simulation.board().modifier(Coord::new(0, 1)).multiply);
+    /// ```
+    pub fn set_modifier(&mut self, coord: Coord, modifier: Modifier) -> bool {
+        let Some(index) = self.board.fields.size.index_of(coord) else {
+            return false;
+        };
+        self.dirty.mark(index);
+
+        self.board.set_modifier(coord, modifier)
+    }
+
+    /// Adds a function which is called for every event of the following steps. The events of a step are
+    /// delivered in the order they happened once the step has finished. Hooks are not copied when
+    /// the simulation is cloned
+    /// 
+    /// # Parameters
+    /// 
+    /// hook: The function to call for every event
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    /// use evolution_plants::{board::BoardBuilder, events::SimEvent, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let ticks = Arc::new(AtomicUsize::new(0));
+    /// let counter = ticks.clone();
+    /// simulation.on_event(move |event| if let SimEvent::TickCompleted { .. } = event {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    /// });
+    /// simulation.step();
+    /// simulation.step();
+    /// 
+    /// assert_eq!(2, ticks.load(Ordering::Relaxed));
+    /// ```
+    pub fn on_event<F: FnMut(&SimEvent) + Send + 'static>(&mut self, hook: F) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Schedules a disturbance to happen in the step reaching a tick, disturbances scheduled for a tick
+    /// which has already been reached happen in the next step
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the step the disturbance happens in
+    /// disturbance: The disturbance
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Rect}, disturbance::{Disturbance, DisturbanceKind}, genome::Genome};
+    /// use evolution_plants::{population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[1.0, 0.0]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.schedule_disturbance(2, Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 2, 2)));
+    /// simulation.step();
+    /// 
+    /// assert_eq!(1, simulation.population().count());
+    /// 
+    /// simulation.step();
+    /// 
+    /// assert_eq!(0, simulation.population().count());
+    /// assert_eq!(1, simulation.disturbance_log()[0].killed);
+    /// ```
+    pub fn schedule_disturbance(&mut self, tick: u64, disturbance: Disturbance) {
+        self.disturbances.schedule(tick, disturbance);
+    }
+
+    /// Adds a disturbance which may happen at a random place in every following step
+    /// 
+    /// # Parameters
+    /// 
+    /// disturbance: The disturbance and its chance of happening
+    pub fn add_random_disturbance(&mut self, disturbance: RandomDisturbance) {
+        self.disturbances.add_random(disturbance);
+    }
+
+    /// Lets a disturbance hit the board right away and returns the number of plants killed,
+    /// droughts started this way last from the current tick
+    /// 
+    /// # Parameters
+    /// 
+    /// disturbance: The disturbance
+    pub fn disturb(&mut self, disturbance: Disturbance) -> usize {
+        let record = self.hooks.is_listening();
+        let mut events = Vec::new();
+        let killed = self.apply_disturbance(self.tick, disturbance, record, &mut events);
+        self.refresh_light();
+
+        if record {
+            self.hooks.emit(&events);
+        }
+
+        killed
+    }
+
+    /// Removes part of the population right away and returns the number of plants removed, the deaths are recorded like
+    /// any other death. Random culls draw from the random number generator of the simulation so they are part of a replay
+    /// 
+    /// # Parameters
+    /// 
+    /// cull: Which plants to remove
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Rect}, genome::Genome, population::{Cull, Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// for x in 0..4 {
+    ///     population.insert(Coord::new(x, x), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// }
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// 
+    /// assert_eq!(2, simulation.cull(Cull::KeepRegion(Rect::new(0, 0, 2, 2))));
+    /// assert_eq!(1, simulation.cull(Cull::Random { fraction: 0.5 }));
+    /// assert_eq!(1, simulation.population().count());
+    /// ```
+    pub fn cull(&mut self, cull: Cull) -> usize {
+        let culled = match cull {
+            Cull::Random { fraction } => self.population.cull_random(fraction, &mut self.rng),
+            Cull::Region(rect) => self.population.cull_region(rect),
+            Cull::KeepRegion(rect) => {
+                let rect = rect.clamp(self.board.fields.size);
+                self.population.keep_only(|coord, _| rect.contains(coord))
+            }
+        };
+
+        self.record_cull(culled)
+    }
+
+    /// Keeps only the plants passing a filter and returns the number of plants removed, the deaths are recorded like any other death
+    /// 
+    /// # Parameters
+    /// 
+    /// filter: Returns true for the plants to keep given their coordinate
+    pub fn keep_only<F: FnMut(Coord, &Plant) -> bool>(&mut self, filter: F) -> usize {
+        let culled = self.population.keep_only(filter);
+
+        self.record_cull(culled)
+    }
+
+    /// Returns all disturbances which have happened in the order they happened
+    pub fn disturbance_log(&self) -> &[DisturbanceRecord] {
+        self.disturbances.log()
+    }
+
+    /// Schedules plants evolved elsewhere, for example in another run, to be introduced in the step reaching a tick, such that
+    /// invasions can be studied. Introductions for a tick which has already been reached happen in the next step. Every genome
+    /// becomes a plant without a parent starting with INTRODUCED_ENERGY energy, if there are too few free cells where plants can
+    /// grow the genomes which do not fit are dropped
+    /// 
+    /// # Parameters
+    /// 
+    /// genomes: The genomes of the plants to introduce, a genome given several times gives several plants
+    /// placement: Where to place the plants
+    /// tick: The tick of the step the plants are introduced in
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::Population};
+    /// use evolution_plants::simulation::{Placement, Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// let invader = Genome::new(&[0.2, 0.8]).unwrap();
+    /// simulation.introduce(&[invader.clone(), invader], Placement::Cluster(Coord::new(2, 2), 1.0), 3);
+    /// simulation.step();
+    /// simulation.step();
+    /// 
+    /// assert_eq!(0, simulation.population().count());
+    /// 
+    /// simulation.step();
+    /// 
+    /// assert_eq!(2, simulation.population().count());
+    /// ```
+    pub fn introduce(&mut self, genomes: &[Genome], placement: Placement, tick: u64) {
+        if !genomes.is_empty() {
+            self.introductions.entry(tick).or_default().push((genomes.to_vec(), placement));
+        }
+    }
+
+    /// Carries out an action by hand right away, such as spawning, smiting or fertilizing with a brush in the window.
+    /// The action counts towards the next step and is remembered in the interventions, so a replay from the same
+    /// start with replay_scenario as its scenario carries it out at the start of that step
+    /// 
+    /// # Parameters
+    /// 
+    /// action: What to do
+    /// 
+    /// # Errors
+    /// 
+    /// See ScenarioAction::validate, nothing is done if the action cannot be carried out
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::Population};
+    /// use evolution_plants::{scenario::ScenarioAction, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(5, 5).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board.clone(), Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.step();
+    /// simulation.intervene(ScenarioAction::Spawn { center: Coord::new(2, 2), radius: 1.0, energy: 100, genome: Genome::new(&[0.5, 0.5]).unwrap() }).unwrap();
+    /// 
+    /// assert_eq!(5, simulation.population().count());
+    /// assert_eq!(2, simulation.interventions().events()[0].tick);
+    /// 
+    /// simulation.intervene(ScenarioAction::Smite { center: Coord::new(2, 2), radius: 0.0 }).unwrap();
+    /// simulation.step();
+    /// let config = SimulationConfig { scenario: simulation.replay_scenario(), ..Default::default() };
+    /// let mut replay = Simulation::new(board, Population::new(size), config).unwrap();
+    /// replay.step();
+    /// replay.step();
+    /// 
+    /// assert_eq!(simulation.snapshot(), replay.snapshot());
+    /// ```
+    pub fn intervene(&mut self, action: ScenarioAction) -> Result<(), ConfigError> {
+        action.validate()?;
+
+        let tick = self.tick + 1;
+        self.run_action(tick, action.clone());
+        self.interventions = std::mem::take(&mut self.interventions).insert(tick, action);
+
+        Ok(())
+    }
+
+    /// Returns the actions carried out by hand, each at the tick of the step it counts towards
+    pub fn interventions(&self) -> &Scenario {
+        &self.interventions
+    }
+
+    /// Creates the scenario which carries out both the scenario of the settings and the interventions when a new simulation
+    /// is run from the same start, the interventions of a step come before the actions of the settings
+    pub fn replay_scenario(&self) -> Scenario {
+        self.config.scenario.events().iter()
+            .fold(self.interventions.clone(), |scenario, event| scenario.insert(event.tick, event.action.clone()))
+    }
+
+    /// Changes some of the settings of the running simulation, the changes take effect from the next step.
+    /// The change is remembered in the config log and an event is sent to the hooks,
+    /// so a replay can apply the same changes at the same ticks
+    /// 
+    /// # Parameters
+    /// 
+    /// update: The settings to change
+    /// 
+    /// # Errors
+    /// 
+    /// ConfigError::Mutation: This will occur if the new mutation settings cannot be used, nothing is changed
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, genome::MutationConfig, population::Population, simulation::{ConfigUpdate, Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.step();
+    /// simulation.update_config(ConfigUpdate { mutation: Some(MutationConfig::new(0.5, 0.1)), light_multiplier: Some(200), ..Default::default() }).unwrap();
+    /// 
+    /// assert!(simulation.update_config(ConfigUpdate { mutation: Some(MutationConfig::new(0.5, -0.1)), ..Default::default() }).is_err());
+    /// assert_eq!(0.5, simulation.mutation_rate());
+    /// assert_eq!(200, simulation.board().multipliers.light);
+    /// assert_eq!(1, simulation.config_log()[0].tick);
+    /// ```
+    pub fn update_config(&mut self, update: ConfigUpdate) -> Result<(), ConfigError> {
+        update.validate()?;
+
+        if let Some(mutation) = update.mutation {
+            self.config.mutation = mutation;
+            if let Some(controller) = &mut self.mutation_controller {
+                controller.set_rate(mutation.rate);
+            }
+        }
+        if let Some(upkeep) = update.upkeep {
+            self.config.upkeep = upkeep;
+        }
+        if let Some(seed_cost) = update.seed_cost {
+            self.config.seed_cost = seed_cost;
+        }
+        if let Some(max_threshold) = update.max_threshold {
+            self.config.max_threshold = max_threshold;
+        }
+        if let Some(light) = update.light_multiplier {
+            self.board.multipliers.light = light.clamp(1, Multipliers::MAX);
+        }
+        if let Some(water) = update.water {
+            self.config.water = water;
+        }
+        if let Some(sun) = update.sun {
+            self.config.sun = sun;
+            self.refresh_light();
+        }
+
+        self.config_log.push(ConfigChange { tick: self.tick, update });
+
+        if self.hooks.is_listening() {
+            self.hooks.emit(&[SimEvent::ConfigUpdated { tick: self.tick, update }]);
+        }
+
+        Ok(())
+    }
+
+    /// Returns all changes made to the settings during the run in the order they were made
+    pub fn config_log(&self) -> &[ConfigChange] {
+        &self.config_log
+    }
+
+    /// Changes the size of the board keeping the top left corner in place. All fields, the water,
+    /// the dormant seeds and the droughts going on are moved along, new cells get the values of the fill
+    /// and the plants which no longer fit on the board die
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The new size of the board
+    /// fill: The values of the new cells
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Fill, Size}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.resize(Size::new(4, 1), &Fill { light: 0.5, ..Default::default() });
+    /// 
+    /// assert_eq!(0, simulation.population().count());
+    /// assert_eq!(&[1.0, 1.0, 0.5, 0.5], simulation.light());
+    /// ```
+    pub fn resize(&mut self, size: Size, fill: &Fill) {
+        let (w, h) = size.size();
+
+        self.reframe(Rect::new(0, 0, w, h), fill);
+    }
+
+    /// Cuts out a rectangle of the board which becomes the new board, the rectangle is clamped to the board.
+    /// The plants, fields, water, dormant seeds and droughts inside the rectangle move along with it
+    /// and the plants outside die
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x coordinate of the left edge
+    /// y: The y coordinate of the top edge
+    /// w: The width
+    /// h: The height
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Size}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(3, 3).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(2, 2), Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.crop(1, 1, 2, 2);
+    /// 
+    /// assert_eq!(Size::new(2, 2), simulation.board().fields.size);
+    /// assert!(simulation.population().get(Coord::new(1, 1)).is_some());
+    /// ```
+    pub fn crop(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        let rect = Rect::new(x, y, w, h).clamp(self.board.fields.size);
+
+        self.reframe(rect, &Fill::default());
+    }
+
+    /// Starts keeping the state before each of the following steps so they can be undone with rewind,
+    /// the history keeps the states of at most a number of the latest steps and the older states are dropped.
+    /// Every kept state is a full copy of the simulation, so the memory used grows with the capacity and the
+    /// size of the board. If the history is already enabled its capacity is changed, keeping the latest states
+    /// 
+    /// # Parameters
+    /// 
+    /// capacity: The largest number of steps which can be undone, a capacity of 0 disables the history
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.enable_history(2);
+    /// for _ in 0..5 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(2, simulation.history_len());
+    /// ```
+    pub fn enable_history(&mut self, capacity: usize) {
+        if capacity == 0 {
+            self.history = None;
+            return;
+        }
+
+        match &mut self.history {
+            Some(history) => history.set_capacity(capacity),
+            None => self.history = Some(History::new(capacity)),
+        }
+    }
+
+    /// Stops keeping the states before every step and drops the kept states
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    /// Returns the largest number of steps the history keeps, 0 if it is disabled
+    pub fn history_capacity(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.capacity())
+    }
+
+    /// Returns the number of steps which can currently be undone
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, |history| history.len())
+    }
+
+    /// Starts saving a checkpoint of the simulation to a directory every number of ticks, the files are written on a background
+    /// thread and only the latest files are kept. Any previous autosaving is stopped first, and the directory is created if it does
+    /// not exist. The latest autosave can be read back with autosave::load_latest to inspect the plants and the fields a run reached,
+    /// the saved snapshots leave out the settings and most of the state so a simulation cannot be resumed from them
+    /// 
+    /// # Parameters
+    /// 
+    /// dir: The directory to save to
+    /// every_ticks: The number of ticks between every save, 0 disables autosaving
+    /// keep_last_n: The number of the latest files to keep, at least 1 is kept
+    /// 
+    /// # Errors
+    /// 
+    /// AutosaveError::Io: This will occur if the directory could not be created
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use evolution_plants::{autosave, board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.enable_autosave("runs/long", 1000, 3).unwrap();
+    /// for _ in 0..10000 {
+    ///     simulation.step();
+    /// }
+    /// drop(simulation);
+    /// 
+    /// assert_eq!(10000, autosave::load_latest("runs/long").unwrap().unwrap().tick);
+    /// ```
+    pub fn enable_autosave<P: AsRef<std::path::Path>>(&mut self, dir: P, every_ticks: u64, keep_last_n: usize) -> Result<(), AutosaveError> {
+        self.autosave = Autosave::default();
+        self.autosave = Autosave::start(dir.as_ref(), every_ticks, keep_last_n)?;
+
+        Ok(())
+    }
+
+    /// Stops autosaving, this waits for the files already sent to the background thread to be written
+    pub fn disable_autosave(&mut self) {
+        self.autosave = Autosave::default();
+    }
+
+    /// Saves a final checkpoint of the current tick unless it was just autosaved, then stops autosaving and waits for
+    /// every file to be written. Call this before exiting such that the latest autosave holds the last tick stepped
+    /// 
+    /// # Errors
+    /// 
+    /// AutosaveError::Io: The latest error of writing an autosave file, the files written before it are still complete
+    pub fn finish_autosave(&mut self) -> Result<(), AutosaveError> {
+        if self.autosave.is_enabled() && self.autosave.last_saved() != Some(self.tick) {
+            self.autosave.save(self.snapshot());
+        }
+        let error = self.autosave.finish();
+        self.autosave = Autosave::default();
+
+        error.map_or(Ok(()), Err)
+    }
+
+    /// Returns the latest error of writing an autosave file, autosaving continues after an error
+    pub fn autosave_error(&self) -> Option<AutosaveError> {
+        self.autosave.error()
+    }
+
+    /// Estimates how much memory the parts of the simulation use, this is useful to find the parts to limit
+    /// before running very large boards or long runs
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(64, 64).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.2, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let before = simulation.memory_report();
+    /// simulation.enable_history(10);
+    /// for _ in 0..10 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert!(before.fields >= 64 * 64 * 4);
+    /// assert_eq!(0, before.history);
+    /// assert!(simulation.memory_report().history > 10 * before.fields);
+    /// ```
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            fields: self.board.heap_bytes() + vec_bytes(&self.light) + self.water.heap_bytes() + self.nutrients.heap_bytes() + self.toxins.heap_bytes(),
+            population: self.population.heap_bytes(),
+            genomes: self.genomes.heap_bytes(),
+            history: self.history.heap_bytes(),
+            phylogeny: self.phylogeny.heap_bytes(),
+            stats: self.species.heap_bytes() + self.fitness.heap_bytes() + self.archive.heap_bytes() + vec_bytes(&self.config_log)
+                + self.emigrants.as_ref().map_or(0, vec_bytes) + self.dirty.heap_bytes() + self.occupancy.heap_bytes() + self.demography.heap_bytes() + self.trace.heap_bytes(),
+        }
+    }
+
+    /// Undoes a number of the latest steps by going back to the state kept before them, returns the number
+    /// of steps undone which is fewer than asked for if the history does not reach that far back.
+    /// Changes made between the undone steps and after the latest step are undone as well,
+    /// the hooks and subscribers are kept
+    /// 
+    /// # Parameters
+    /// 
+    /// steps: The number of steps to undo
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.enable_history(10);
+    /// for _ in 0..5 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(2, simulation.rewind(2));
+    /// assert_eq!(3, simulation.tick());
+    /// assert_eq!(3, simulation.rewind(8));
+    /// assert_eq!(0, simulation.tick());
+    /// ```
+    pub fn rewind(&mut self, steps: usize) -> usize {
+        let Some((state, steps)) = self.history.as_mut().and_then(|history| history.rewind(steps)) else {
+            return 0;
+        };
+
+        let SavedState { board, population, config, rng, tick, mutation_controller, phylogeny, light, water, nutrients, toxins, species, disturbances, introductions, config_log, archive, fitness, occupancy, demography } = state;
+        self.board = board;
+        self.population = population;
+        self.config = config;
+        self.rng = rng;
+        self.tick = tick;
+        self.mutation_controller = mutation_controller;
+        self.phylogeny = phylogeny;
+        self.light = light;
+        self.water = water;
+        self.nutrients = nutrients;
+        self.toxins = toxins;
+        self.species = species;
+        self.disturbances = disturbances;
+        self.introductions = introductions;
+        // The state was saved at the start of the next step so it holds the interventions counting towards that step
+        self.interventions.forget_after(tick + 1);
+        self.config_log = config_log;
+        self.archive = archive;
+        self.fitness = fitness;
+        self.occupancy = occupancy;
+        self.demography = demography;
+        self.balance = None;
+        if let Some(audit) = &mut self.audit {
+            audit.clear();
+        }
+        self.dirty = DirtyCells::all(self.board.fields.size);
+
+        steps
+    }
+
+    /// Changes how the genomes of the plants are turned into their traits, the traits of all living plants
+    /// are developed again and every plant placed from now on is developed with the new development
+    /// 
+    /// # Parameters
+    /// 
+    /// development: The new development
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::phenotype::{Development, DirectDevelopment, Phenotype};
+    /// 
+    /// /// Plants whose first gene is high photosynthesize less
+    /// #[derive(Debug)]
+    /// struct TradeOff;
+    /// 
+    /// impl Development for TradeOff {
+    ///     fn develop(&self, genome: &Genome) -> Phenotype {
+    ///         Phenotype { photosynthesis: 1.0 - genome.gene(0) / 2.0, ..DirectDevelopment.develop(genome) }
+    ///     }
+    /// }
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.set_development(TradeOff);
+    /// 
+    /// assert_eq!(0.5, simulation.population().get(Coord::new(0, 0)).unwrap().phenotype.photosynthesis);
+    /// ```
+    pub fn set_development<D: Development + 'static>(&mut self, development: D) {
+        self.development = Arc::new(development);
+
+        for plant in self.population.plants_mut() {
+            plant.phenotype = self.development.develop(&plant.genome);
+        }
+    }
+
+    /// Returns how the genomes of the plants are turned into their traits
+    pub fn development(&self) -> &dyn Development {
+        self.development.as_ref()
+    }
+
+    /// Changes what the plants gain from the cells they live in, such as to feed them on the water instead of the light.
+    /// The model only decides what is gained and used up, the simulation only hosts plants
+    /// 
+    /// # Parameters
+    /// 
+    /// model: The new resource model
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::organism::{CellStock, ResourceModel, Surroundings};
+    /// 
+    /// /// The plants feed on the water of their cell instead of collecting the light
+    /// #[derive(Debug)]
+    /// struct Forage;
+    /// 
+    /// impl ResourceModel for Forage {
+    ///     fn intake(&self, _: &Plant, surroundings: &Surroundings, _: &SimulationConfig) -> u32 {
+    ///         (surroundings.water * 10.0) as u32
+    ///     }
+    /// 
+    ///     fn deplete(&self, intake: u32, stock: CellStock<'_>) {
+    ///         *stock.water -= intake as f32 / 10.0;
+    ///     }
+    /// }
+    /// 
+    /// let board = BoardBuilder::new().size(2, 1).light_uniform(1.0).water(&[3.0, 3.0]).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// let config = SimulationConfig { upkeep: 0, ..Default::default() };
+    /// let mut simulation = Simulation::new(board, population, config).unwrap();
+    /// simulation.set_resource_model(Forage);
+    /// simulation.step();
+    /// 
+    /// assert_eq!(40, simulation.population().get(Coord::new(0, 0)).unwrap().energy);
+    /// assert_eq!(&[0.0, 3.0], simulation.water().values());
+    /// ```
+    pub fn set_resource_model<M: ResourceModel + 'static>(&mut self, model: M) {
+        self.resource_model = Arc::new(model);
+    }
+
+    /// Returns what the plants gain from the cells they live in
+    pub fn resource_model(&self) -> &dyn ResourceModel {
+        self.resource_model.as_ref()
+    }
+
+    /// Changes when the plants reproduce and where their seeds land
+    /// 
+    /// # Parameters
+    /// 
+    /// model: The new reproduction model
+    pub fn set_reproduction_model<M: ReproductionModel + 'static>(&mut self, model: M) {
+        self.reproduction_model = Arc::new(model);
+    }
+
+    /// Returns when the plants reproduce and where their seeds land
+    pub fn reproduction_model(&self) -> &dyn ReproductionModel {
+        self.reproduction_model.as_ref()
+    }
+
+    /// Returns the cells which may look different since the dirty cells were last cleared, a cell is marked when a plant
+    /// is placed in it or removed from it or when its terrain changes. Changes to the light mark the entire board,
+    /// as does changing the size of the board or rewinding. The entire board is marked when the simulation is created
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(3, 3).light_uniform(0.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig { upkeep: 1, ..Default::default() }).unwrap();
+    /// 
+    /// assert!(simulation.dirty().is_all());
+    /// 
+    /// simulation.clear_dirty();
+    /// simulation.step();
+    /// 
+    /// assert_eq!(vec![4], simulation.dirty().cells().collect::<Vec<_>>());
+    /// ```
+    pub fn dirty(&self) -> &DirtyCells {
+        &self.dirty
+    }
+
+    /// Removes the marks of all dirty cells, this should be called by a renderer after it has drawn the dirty cells
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Starts or stops timing every phase of the following steps, starting again clears the earlier timings.
+    /// With the tracing feature every timed phase is also a tracing span named phase
+    /// 
+    /// # Parameters
+    /// 
+    /// enabled: True if the steps should be timed
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, profile::Phase, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.profiling(true);
+    /// for _ in 0..3 {
+    ///     simulation.step();
+    /// }
+    /// let report = simulation.profile().unwrap().report();
+    /// 
+    /// assert_eq!(3, simulation.profile().unwrap().steps());
+    /// assert_eq!(Phase::Fields, report[0].phase);
+    /// ```
+    pub fn profiling(&mut self, enabled: bool) {
+        self.profile = if enabled { Some(StepProfile::new()) } else { None };
+    }
+
+    /// Returns the time spent in every phase of the steps since profiling was started, None if profiling is disabled
+    pub fn profile(&self) -> Option<&StepProfile> {
+        self.profile.as_ref()
+    }
+
+    /// Starts or stops auditing the energy of the following steps. While auditing, the energy entering the board as light,
+    /// stored in the plants and seeds and leaving it through respiration, decay and emigrating seeds is counted in every phase
+    /// of a step, and every step panics if the books of a phase are off by more than epsilon
+    /// 
+    /// # Parameters
+    /// 
+    /// epsilon: The largest discrepancy allowed in a phase, None to stop auditing
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.2, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.audit_energy(Some(0));
+    /// for _ in 0..20 {
+    ///     simulation.step();
+    /// }
+    /// let report = simulation.energy_audit().unwrap();
+    /// 
+    /// assert_eq!(20, report.tick);
+    /// assert!(report.is_balanced(0));
+    /// assert_eq!(8, report.phases.len());
+    /// ```
+    pub fn audit_energy(&mut self, epsilon: Option<u64>) {
+        self.audit = epsilon.map(EnergyAudit::new);
+    }
+
+    /// Returns the books of the energy of the latest step, None if the energy is not audited,
+    /// before the first audited step and after going back in time
+    pub fn energy_audit(&self) -> Option<&AuditReport> {
+        self.audit.as_ref()?.latest()
+    }
+
+    /// Restricts the following steps to a rectangle of the board and freezes everything outside it, such as to look closely
+    /// at a part of a large board or to run it faster. The plants outside neither collect energy, pay upkeep, age nor reproduce,
+    /// and they do not shade, pollinate or share energy with the plants inside. The water, nutrients and toxin outside keep their
+    /// values, disturbances only hit the part inside and seeds landing outside die. The dormant seeds outside still grow older.
+    /// The rectangle is clamped to the board and changing the size of the board simulates the whole board again
+    /// 
+    /// # Parameters
+    /// 
+    /// region: The rectangle to simulate, None to simulate the whole board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Rect}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 4).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.2, 0.5]).unwrap()));
+    /// population.insert(Coord::new(6, 1), Plant::new(100, Genome::new(&[0.2, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.set_active_region(Some(Rect::new(0, 0, 4, 10)));
+    /// for _ in 0..10 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// assert_eq!(Some(Rect::new(0, 0, 4, 4)), simulation.active_region());
+    /// assert_eq!(100, simulation.population().get(Coord::new(6, 1)).unwrap().energy);
+    /// assert!(simulation.population().iter().all(|(coord, plant)| coord.x < 4 || plant.age == 0));
+    /// ```
+    pub fn set_active_region(&mut self, region: Option<Rect>) {
+        self.active = region.map(|region| region.clamp(self.board.fields.size));
+    }
+
+    /// Returns the part of the board the steps are restricted to, None if the whole board is simulated
+    pub fn active_region(&self) -> Option<Rect> {
+        self.active
+    }
+
+    /// Sets how the work on the cells of the following steps is split over threads, such as the setting found by auto_tune
+    /// or saved in a snapshot. The setting only changes how fast a step runs and never what happens in it
+    /// 
+    /// # Parameters
+    /// 
+    /// parallelism: The number of threads and the number of cells handed to a thread at a time
+    pub fn set_parallelism(&mut self, parallelism: Parallelism) {
+        self.parallelism = parallelism;
+    }
+
+    /// Returns how the work on the cells of the steps is split over threads
+    pub fn parallelism(&self) -> Parallelism {
+        self.parallelism
+    }
+
+    /// Finds the energy every plant gains from its surroundings through the resource model, None for the empty cells. The state is only read
+    /// so the cells are split over the threads of a parallel setting
+    /// 
+    /// # Parameters
+    /// 
+    /// parallelism: How the cells are split over threads
+    /// root_water: The water the roots of the plant in every cell took up, 0 for the cells which are left out
+    pub(crate) fn intakes(&self, parallelism: Parallelism, root_water: &[f32]) -> Vec<Option<u32>> {
+        let (board, light, population, config, model) = (&self.board, &self.light, &self.population, &self.config, &self.resource_model);
+        let (water, nutrients, toxins) = (self.water.values(), self.nutrients.values(), self.toxins.values());
+
+        parallelism.map(board.fields.size.len(), |index| {
+            let plant = population.plant(index)?;
+            let surroundings = Surroundings {
+                light: light_energy(board, light, index),
+                temperature: board.fields.temperature[index],
+                water: water[index],
+                nutrients: nutrients[index],
+                toxin: toxins[index],
+                root_water: root_water.get(index).copied().unwrap_or(0.0),
+                spectrum: board.spectrum(index),
+            };
+
+            Some(model.intake(plant, &surroundings, config))
+        })
+    }
+
+    /// Starts or stops counting how many steps every cell holds a plant and how many plants die in it,
+    /// starting again clears the earlier counts
+    /// 
+    /// # Parameters
+    /// 
+    /// enabled: True if the cells should be counted
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::occupancy::OccupancyLayer;
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.track_occupancy(true);
+    /// for _ in 0..10 {
+    ///     simulation.step();
+    /// }
+    /// let occupancy = simulation.occupancy().unwrap();
+    /// 
+    /// assert_eq!(10, occupancy.ticks());
+    /// assert_eq!(16, occupancy.layer(OccupancyLayer::Mortality).len());
+    /// ```
+    pub fn track_occupancy(&mut self, enabled: bool) {
+        self.occupancy = enabled.then(|| OccupancyMap::new(self.board.fields.size));
+    }
+
+    /// Returns the steps every cell held a plant and the plants which died in it since tracking was started,
+    /// None if occupancy is not tracked
+    pub fn occupancy(&self) -> Option<&OccupancyMap> {
+        self.occupancy.as_ref()
+    }
+
+    /// Starts or stops recording the age distribution after every step and the ages the plants die and reproduce at,
+    /// starting again clears the earlier records
+    /// 
+    /// # Parameters
+    /// 
+    /// bin_width: The number of ages in steps in each age class, None stops the recording
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.track_demography(Some(5));
+    /// for _ in 0..10 {
+    ///     simulation.step();
+    /// }
+    /// let demography = simulation.demography().unwrap();
+    /// 
+    /// assert_eq!(10, demography.histograms().len());
+    /// assert_eq!(1.0, demography.life_table()[0].survivorship);
+    /// ```
+    pub fn track_demography(&mut self, bin_width: Option<u64>) {
+        self.demography = bin_width.map(Demography::new);
+    }
+
+    /// Returns the age distributions and life table recorded since tracking was started, None if demography is not tracked
+    pub fn demography(&self) -> Option<&Demography> {
+        self.demography.as_ref()
+    }
+
+    /// Starts recording every energy transaction, mutation and reproduction decision of a plant and of all its descendants
+    /// born from now on, see [`PlantTrace`]. The trace is not rewound when going back in time
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let id = simulation.population().get(Coord::new(1, 1)).unwrap().id();
+    /// simulation.trace_plant(id);
+    /// for _ in 0..5 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// let trace = simulation.plant_trace().unwrap();
+    /// assert!(trace.entries_of(id).count() > 0);
+    /// std::fs::write(std::env::temp_dir().join("trace.json"), trace.to_json()).unwrap();
+    /// ```
+    pub fn trace_plant(&mut self, id: PlantId) {
+        self.trace.get_or_insert_with(PlantTrace::new).trace(id);
+    }
+
+    /// Returns the events of the traced plants, None if no plants are traced
+    pub fn plant_trace(&self) -> Option<&PlantTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Stops tracing all plants and returns the events recorded so far
+    pub fn stop_tracing(&mut self) -> Option<PlantTrace> {
+        self.trace.take()
+    }
+
+    /// Starts or stops checking rules against the statistics of every step the statistics are due, starting again clears
+    /// the earlier alerts. Every alert raised is sent to the hooks as [`SimEvent::Alert`] and the state after the step
+    /// raising it is saved if the settings ask for it. The alerts are not rewound when going back in time
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The rules and whether the states are saved, None stops checking
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, checkpoint::CheckpointConfig, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// use evolution_plants::alert::{AlertConfig, AlertRule};
+    /// 
+    /// let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// simulation.watch_alerts(Some(AlertConfig { rules: vec![AlertRule::PopulationBelow(1)], checkpoints: Some(CheckpointConfig::default()) }));
+    /// for _ in 0..5 {
+    ///     simulation.step();
+    /// }
+    /// 
+    /// // The board stays empty so the rule is only raised once
+    /// let monitor = simulation.alerts().unwrap();
+    /// assert_eq!(vec![1], monitor.alerts().iter().map(|alert| alert.tick).collect::<Vec<_>>());
+    /// assert_eq!(Some(1), monitor.state(&monitor.alerts()[0]).map(|state| state.tick));
+    /// ```
+    pub fn watch_alerts(&mut self, config: Option<AlertConfig>) {
+        self.alerts = config.map(AlertMonitor::new);
+    }
+
+    /// Returns the rules checked after every step and the alerts they raised, None if alerts are not watched
+    pub fn alerts(&self) -> Option<&AlertMonitor> {
+        self.alerts.as_ref()
+    }
+
+    /// Records an event of a plant if it is traced
+    fn trace_event(&mut self, tick: u64, id: PlantId, event: TraceEvent) {
+        if let Some(trace) = &mut self.trace {
+            trace.record(tick, id, event);
+        }
+    }
+
+    /// Returns the position in the stream of the random number generator
+    pub(crate) fn rng_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    /// Sends events to the hooks
+    pub(crate) fn emit(&mut self, events: &[SimEvent]) {
+        self.hooks.emit(events);
+    }
+
+    /// Adds a subscription the statistics of every following step are sent to
+    pub(crate) fn add_subscriber(&mut self, subscriber: Publisher) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// Gets the light of the board mutably, refresh_light must be called after changing it
+    pub(crate) fn board_light_mut(&mut self) -> &mut [f32] {
+        self.dirty.mark_all();
+        &mut self.board.fields.light
+    }
+
+    /// Recalculates the shadows and the edge band from the light of the board
+    pub(crate) fn refresh_light(&mut self) {
+        self.light = derived_light(&self.board, &self.population, &self.config);
+    }
+
+    /// Holds the cells of a resource at a multiple of their value until a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// resource: The resource to change
+    /// cells: The index of every cell and the factor its value is multiplied by
+    /// until: The tick at which the cells get their value back
+    pub(crate) fn fertilize(&mut self, resource: Resource, cells: &[(usize, f32)], until: u64) {
+        match resource {
+            Resource::Light => {
+                self.dirty.mark_all();
+                self.disturbances.scale(resource, cells, until, &mut self.board.fields.light);
+                self.refresh_light();
+            }
+            Resource::Water => self.disturbances.scale(resource, cells, until, self.water.values_mut()),
+        }
+    }
+
+    /// Gets the water on the board mutably
+    pub(crate) fn water_mut(&mut self) -> &mut WaterField {
+        &mut self.water
+    }
+
+    /// Places a new plant without a parent in an empty cell plants can grow in, returns false if it could not be placed
+    pub(crate) fn plant_founder(&mut self, index: usize, mut plant: Plant) -> bool {
+        if self.population.is_occupied(index) || self.board.fields.is_blocked(index) {
+            return false;
+        }
+
+        plant.genome = self.genomes.intern(&plant.genome);
+        let genome = plant.genome.clone();
+        plant.phenotype = self.development.develop(&genome);
+        let id = self.population.place(index, plant);
+        self.phylogeny.record_birth(id, None, None, self.tick, genome);
+        self.dirty.mark(index);
+
+        true
+    }
+
+    /// Returns the energy stored in the plants and the dormant seeds, including the plants outside the active region
+    fn stored_energy(&self) -> u64 {
+        let frozen = self.frozen.as_ref().map_or(0, |frozen| frozen.plants.iter().map(|(_, plant)| plant.energy as u64).sum());
+
+        self.population.iter().map(|(_, plant)| plant.energy as u64).sum::<u64>() + self.board.seed_bank.energy() + frozen
+    }
+
+    /// Takes the plants outside the active region off the board and keeps the fields before the step
+    /// such that the part of the board outside the region can be put back after the step
+    fn freeze(&mut self) {
+        let Some(active) = self.active else {
+            return;
+        };
+
+        let size = self.board.fields.size;
+        let cells: Vec<usize> = (0..size.len()).filter(|&index| !active.contains(size.coord_of(index).unwrap())).collect();
+        let plants = cells.iter().filter_map(|&index| Some((index, self.population.take(index)?))).collect();
+
+        self.frozen = Some(Frozen { cells, plants, water: self.water.clone(), nutrients: self.nutrients.clone(), toxins: self.toxins.clone() });
+    }
+
+    /// Puts the plants outside the active region back on the board and the fields outside it back to their state before the step
+    fn thaw(&mut self) {
+        let Some(frozen) = self.frozen.take() else {
+            return;
+        };
+
+        self.water.restore(&frozen.water, &frozen.cells);
+        self.nutrients.restore(&frozen.nutrients, &frozen.cells);
+        self.toxins.restore(&frozen.toxins, &frozen.cells);
+        for (index, plant) in frozen.plants {
+            self.population.put(index, Some(plant));
+        }
+    }
+
+    /// Counts energy entering or leaving the board in the running phase if the energy is audited
+    fn audit_flows(&mut self, count: impl FnOnce(&mut Flows)) {
+        if let Some(audit) = &mut self.audit {
+            count(&mut audit.flows);
+        }
+    }
+
+    /// Closes the books of a phase if the energy is audited
+    /// 
+    /// # Parameters
+    /// 
+    /// phase: The phase which ended
+    /// in_flight: The energy of the seeds which have been produced but have not landed yet
+    fn audit_phase(&mut self, phase: Phase, in_flight: u64) {
+        if self.audit.is_some() {
+            let stock = self.stored_energy() + in_flight;
+            if let Some(audit) = &mut self.audit {
+                audit.close(phase, stock);
+            }
+        }
+    }
+
+    /// Finds the phase of the latest step whose books are off by more than the epsilon of the audit,
+    /// None if the energy is not audited or every phase balances
+    pub(crate) fn audit_violation(&self) -> Option<InvariantViolation> {
+        let audit = self.audit.as_ref()?;
+        let report = audit.latest()?;
+        let ledger = report.worst().filter(|_| !report.is_balanced(audit.epsilon()))?;
+
+        Some(InvariantViolation::Audit { tick: report.tick, ledger: *ledger })
+    }
+
+    /// Removes the plant in a cell and records its death
+    pub(crate) fn remove_plant(&mut self, index: usize) -> Option<Plant> {
+        let plant = self.population.take(index)?;
+        self.record_death(&plant, Some(index), self.tick, DeathCause::Removed);
+        self.dirty.mark(index);
+
+        Some(plant)
+    }
+
+    /// Puts a cell back to an earlier state, keeping the id of the plant
+    pub(crate) fn restore_plant(&mut self, index: usize, plant: Option<Plant>) {
+        self.population.put(index, plant);
+        self.dirty.mark(index);
+    }
+
+    /// Makes a rectangle of the board the new board and records the deaths of the plants outside it,
+    /// the rectangle may reach outside the board in which case the cells outside get the values of the fill
+    fn reframe(&mut self, rect: Rect, fill: &Fill) {
+        let from = self.board.fields.size;
+        let record = self.hooks.is_listening();
+        let mut events = Vec::new();
+
+        self.board.reframe(rect, fill);
+        self.water.reframe(rect, fill.water);
+        self.nutrients.reframe(rect);
+        self.toxins.reframe(rect);
+        self.disturbances.reframe(from, rect);
+        if let Some(occupancy) = &mut self.occupancy {
+            occupancy.reframe(rect);
+        }
+        for (_, placement) in self.introductions.values_mut().flatten() {
+            if let Placement::Cluster(center, _) = placement {
+                *center = Coord::new(center.x.saturating_sub(rect.x), center.y.saturating_sub(rect.y));
+            }
+        }
+
+        for (coord, plant) in self.population.reframe(rect) {
+            self.record_death(&plant, None, self.tick, DeathCause::Removed);
+            if record {
+                events.push(SimEvent::PlantDied { tick: self.tick, id: plant.id(), coord });
+            }
+        }
+
+        self.active = None;
+        self.refresh_light();
+        self.dirty = DirtyCells::all(self.board.fields.size);
+
+        if record {
+            self.hooks.emit(&events);
+        }
+    }
+
+    /// Starts timing a phase of a step if profiling is enabled
+    fn start_phase(&self, phase: Phase) -> Option<Stopwatch> {
+        self.profile.as_ref().map(|_| Stopwatch::start(phase))
+    }
+
+    /// Adds the time of a phase to the profile
+    fn stop_phase(&mut self, stopwatch: Option<Stopwatch>) {
+        if let (Some(stopwatch), Some(profile)) = (stopwatch, &mut self.profile) {
+            stopwatch.stop(profile);
+        }
+    }
+
+    /// Copies the state which changes during a step
+    fn save_state(&self) -> SavedState {
+        SavedState {
+            board: self.board.clone(),
+            population: self.population.clone(),
+            config: self.config.clone(),
+            rng: self.rng.clone(),
+            tick: self.tick,
+            mutation_controller: self.mutation_controller.clone(),
+            phylogeny: self.phylogeny.clone(),
+            light: self.light.clone(),
+            water: self.water.clone(),
+            nutrients: self.nutrients.clone(),
+            toxins: self.toxins.clone(),
+            species: self.species.clone(),
+            disturbances: self.disturbances.clone(),
+            introductions: self.introductions.clone(),
+            config_log: self.config_log.clone(),
+            archive: self.archive.clone(),
+            fitness: self.fitness.clone(),
+            occupancy: self.occupancy.clone(),
+            demography: self.demography.clone(),
+        }
+    }
+
+    /// Records the deaths of the plants removed by a bottleneck and returns the number removed
+    fn record_cull(&mut self, culled: Vec<(Coord, Plant)>) -> usize {
+        let size = self.board.fields.size;
+        let record = self.hooks.is_listening();
+        let mut events = Vec::new();
+
+        for (coord, plant) in &culled {
+            let index = size.index_of(*coord).unwrap();
+            if record {
+                events.push(SimEvent::PlantDied { tick: self.tick, id: plant.id(), coord: *coord });
+            }
+            self.record_death(plant, Some(index), self.tick, DeathCause::Culled);
+            self.dirty.mark(index);
+        }
+        // The plants removed no longer shade their neighbours
+        self.refresh_light();
+
+        if record {
+            events.push(SimEvent::Culled { tick: self.tick, killed: culled.len(), survivors: self.population.count() });
+            self.hooks.emit(&events);
+        }
+
+        culled.len()
+    }
+
+    /// Records the death of a plant in the lineage tree and the cell it died in and offers its genome to the hall of fame,
+    /// the cell is None if the plant was removed together with its cell
+    fn record_death(&mut self, plant: &Plant, cell: Option<usize>, tick: u64, cause: DeathCause) {
+        let coord = cell.map(|index| self.board.fields.size.coord_of(index).unwrap());
+        self.trace_event(tick, plant.id(), TraceEvent::Died { coord, cause, energy: plant.energy });
+        self.audit_flows(|flows| flows.decay += plant.energy as u64);
+        if let Some(archive) = &mut self.archive {
+            archive.record(plant, &self.phylogeny, tick);
+        }
+        if let (Some(occupancy), Some(index)) = (&mut self.occupancy, cell) {
+            occupancy.record_death(index);
+        }
+        if let Some(demography) = &mut self.demography {
+            demography.record_death(plant.age);
+        }
+        self.phylogeny.record_death(plant.id(), tick);
+    }
+
+    /// Places the plants of the introductions due in the step reaching a tick and returns where they were placed,
+    /// the introductions are placed in the order they were scheduled
+    fn introduce_due(&mut self, tick: u64) -> Vec<(Coord, PlantId)> {
+        let later = self.introductions.split_off(&(tick + 1));
+        let due = std::mem::replace(&mut self.introductions, later);
+        let size = self.board.fields.size;
+
+        let mut introduced = Vec::new();
+        for (genomes, placement) in due.into_values().flatten() {
+            let cells = match place(&self.board, &self.population, genomes.len(), placement, &mut self.rng) {
+                Ok(cells) => cells,
+                Err(SimulationCreateError::Crowded { cells, .. }) => place(&self.board, &self.population, cells, placement, &mut self.rng).unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
+
+            for (coord, genome) in cells.into_iter().zip(genomes) {
+                let index = size.index_of(coord).unwrap();
+                if self.plant_founder(index, Plant::new(INTRODUCED_ENERGY, genome)) {
+                    introduced.push((coord, self.population.plant(index).unwrap().id()));
+                }
+            }
+        }
+
+        introduced
+    }
+
+    /// Lets a disturbance hit the board and returns the number of plants killed,
+    /// refresh_light must be called afterwards if the disturbance was a drought of light
+    fn apply_disturbance(&mut self, tick: u64, disturbance: Disturbance, record: bool, events: &mut Vec<SimEvent>) -> usize {
+        let size = self.board.fields.size;
+        let disturbance = Disturbance { region: disturbance.region.clamp(size), ..disturbance };
+        let indices: Vec<usize> = disturbance.region.coords()
+            .map(|coord| size.index_of(coord).unwrap())
+            .collect();
+        let mut killed = 0;
+
+        match disturbance.kind {
+            DisturbanceKind::Fire | DisturbanceKind::Meteor { .. } => {
+                for &index in &indices {
+                    if let Some(plant) = self.population.take(index) {
+                        if record {
+                            events.push(SimEvent::PlantDied { tick, id: plant.id(), coord: size.coord_of(index).unwrap() });
+                        }
+                        self.record_death(&plant, Some(index), tick, DeathCause::Disturbance);
+                        self.dirty.mark(index);
+                        killed += 1;
+                    }
+                }
+
+                if let DisturbanceKind::Meteor { terrain } = disturbance.kind {
+                    for &index in &indices {
+                        self.board.fields.terrain[index] = terrain;
+                        self.dirty.mark(index);
+                    }
+                }
+            }
+            DisturbanceKind::Drought { resource, duration } => {
+                let until = tick + duration.max(1);
+                let values = match resource {
+                    Resource::Light => &mut self.board.fields.light,
+                    Resource::Water => self.water.values_mut(),
+                };
+                self.disturbances.hold(resource, &indices, until, values);
+            }
+        }
+
+        self.disturbances.record(DisturbanceRecord { tick, disturbance, killed });
+        if record {
+            events.push(SimEvent::Disturbed { tick, disturbance, killed });
+        }
+
+        killed
+    }
+
+    /// Finds what the network of a plant senses about its cell
+    fn sense(&self, neural: &NeuralConfig, tick: u64, index: usize) -> Sensors {
+        let size = self.board.fields.size;
+        let coord = size.coord_of(index).unwrap();
+        let neighbours = NEIGHBOURS
+            .iter()
+            .filter_map(|&(dx, dy)| offset(size, coord, dx, dy))
+            .filter(|&neighbour| self.population.is_occupied(neighbour))
+            .count();
+
+        Sensors {
+            light: self.light[index],
+            water: self.water.values()[index],
+            density: neighbours as f32 / NEIGHBOURS.len() as f32,
+            season: neural.season(tick),
+        }
+    }
+
+    /// Starts or stops keeping the seeds which leave the board such that they can be moved to another board
+    pub(crate) fn collect_emigrants(&mut self, collect: bool) {
+        self.emigrants = if collect { Some(self.emigrants.take().unwrap_or_default()) } else { None };
+    }
+
+    /// Removes the seeds which left the board since this was last called
+    pub(crate) fn take_emigrants(&mut self) -> Vec<Emigrant> {
+        self.emigrants.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Lets a seed from another board germinate in an empty cell, it starts a new lineage on this board.
+    /// Returns false if the cell is occupied or blocked and the seed is lost
+    pub(crate) fn immigrate(&mut self, index: usize, seed: Plant) -> bool {
+        if self.population.is_occupied(index) || self.board.fields.is_blocked(index) {
+            return false;
+        }
+
+        let record = self.hooks.is_listening();
+        let mut events = Vec::new();
+        self.germinate(self.tick, index, Plant::new(seed.energy, seed.genome), record, &mut events);
+        if record {
+            self.hooks.emit(&events);
+        }
+
+        true
+    }
+
+    /// Places a seed in an empty cell and records its birth
+    fn germinate(&mut self, tick: u64, target: usize, mut seed: Plant, record: bool, events: &mut Vec<SimEvent>) {
+        let (parent, mate) = (seed.parent(), seed.mate());
+        seed.genome = self.genomes.intern(&seed.genome);
+        let genome = seed.genome.clone();
+        seed.phenotype = self.development.develop(&genome);
+        let energy = seed.energy;
+        let id = self.population.place(target, seed);
+        self.phylogeny.record_birth(id, parent, mate, tick, genome);
+        self.trace_event(tick, id, TraceEvent::Born { coord: self.board.fields.size.coord_of(target).unwrap(), parent, mate, energy });
+        if let (Some(fitness), Some(parent)) = (&mut self.fitness, parent) {
+            fitness.record(tick, parent, self.species.as_ref().and_then(|tracker| tracker.species_of(parent)));
+        }
+        if let (Some(demography), Some(parent)) = (&mut self.demography, parent.and_then(|parent| self.phylogeny.get(parent))) {
+            demography.record_birth(tick.saturating_sub(parent.birth));
+        }
+        self.dirty.mark(target);
+        if record {
+            events.push(SimEvent::PlantBorn { tick, id, coord: self.board.fields.size.coord_of(target).unwrap(), parent, mate });
+        }
+    }
+
+    /// Decides if a seed landing in an empty cell germinates right away, which it always does unless the seeds wait
+    /// for germination cues. A seed waiting for cues germinates with a chance if the cues of the cell are met and a
+    /// single step of meeting them is enough
+    fn germination_ready(&mut self, index: usize, seed: &Plant) -> bool {
+        let germination = match &self.config.germination {
+            Some(germination) => germination,
+            None => return true,
+        };
+
+        let cued = germination.cues_met(&seed.genome, self.light[index], self.water.values()[index]) as u64;
+        germination.germinates(cued, &mut self.rng)
+    }
+
+    /// Runs a single step of the simulation
+    /// 
+    /// Every plant collects the light in its cell and pays its upkeep, plants which cannot pay die.
+    /// The plants are only handled through the [`Organism`] trait, so the life cycle of the plants is defined there.
+    /// Plants with enough energy then produce a seed which lands in a random neighbouring cell,
+    /// seeds landing outside the board, in an occupied cell or on blocked terrain do not survive,
+    /// unless the simulation is part of a [`World`](crate::world::World) which moves the seeds leaving the board to another board.
+    /// When several seeds land in the same cell the winner is chosen by the competition of the config,
+    /// by default the one with the most energy wins and ties are won by the seed whose parent has the lowest id,
+    /// so the outcome never depends on the order the plants are processed in.
+    /// If the seed bank is enabled the seeds which lost or landed in an occupied cell go dormant in the ground instead,
+    /// and once they have waited long enough they germinate in empty cells with enough light.
+    /// With germination cues the seeds only germinate with a chance once the light and water of their cell have met the cues
+    /// in their genome, the seeds which do not germinate are handled like the seeds landing in an occupied cell.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[1.0; 4]).unwrap();
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+    /// let mut population = Population::new(size);
+    /// population.insert(board::Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// simulation.step();
+    /// 
+    /// assert_eq!(1, simulation.tick());
+    /// ```
+    pub fn step(&mut self) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("tick", tick = self.tick + 1).entered();
+        #[cfg(feature = "tracing")]
+        let alive = self.population.count();
+
+        if let Some(profile) = &mut self.profile {
+            profile.begin_step();
+        }
+
+        let stopwatch = self.start_phase(Phase::Bookkeeping);
+        if self.history.is_some() {
+            let state = self.save_state();
+            if let Some(history) = &mut self.history {
+                history.push(state);
+            }
+        }
+        self.stop_phase(stopwatch);
+
+        let size = self.board.fields.size;
+        let tick = self.tick + 1;
+
+        // Carry out the actions of the scenario before anything else happens in the step
+        self.run_scenario(tick);
+
+        // Introduce the plants from elsewhere before the energy is counted, their energy is not created by the step
+        let introduced = self.introduce_due(tick);
+        self.freeze();
+        let active = self.active;
+        let inside = move |index: usize| active.is_none_or(|active| active.contains(size.coord_of(index).unwrap()));
+        let energy_before = self.stored_energy();
+        let mut intake_total = 0;
+        if let Some(audit) = &mut self.audit {
+            audit.begin(energy_before);
+        }
+
+        let mut births = introduced.len();
+        let mut deaths = 0;
+        let record = self.hooks.is_listening();
+        let mut events = Vec::new();
+        if record {
+            events.extend(introduced.into_iter().map(|(coord, id)| SimEvent::PlantBorn { tick, id, coord, parent: None, mate: None }));
+        }
+
+        // End the droughts which are over and let the disturbances of this step hit the board
+        let stopwatch = self.start_phase(Phase::Fields);
+        let light_released = self.disturbances.release(Resource::Light, tick, &mut self.board.fields.light);
+        self.disturbances.release(Resource::Water, tick, self.water.values_mut());
+
+        let due = self.disturbances.due(tick, size, &mut self.rng);
+        let light_changed = light_released || due.iter().any(|disturbance| matches!(disturbance.kind, DisturbanceKind::Drought { resource: Resource::Light, .. }));
+        for mut disturbance in due {
+            if let Some(active) = active {
+                disturbance.region = disturbance.region.intersect(active);
+            }
+            deaths += self.apply_disturbance(tick, disturbance, record, &mut events);
+        }
+        if light_changed {
+            self.refresh_light();
+            self.dirty.mark_all();
+        } else if self.config.canopy.is_some() && self.config.sun.is_some() && self.config.schedule.is_due(Subsystem::Shadows, tick) {
+            // The plants which were born or died in the previous step changed the shadows
+            self.refresh_light();
+        }
+        self.audit_phase(Phase::Fields, 0);
+        self.stop_phase(stopwatch);
+
+        // Spread the pathogen before the plants pay for being infected
+        let stopwatch = self.start_phase(Phase::Energy);
+        if let Some(pathogen) = self.config.pathogen {
+            pathogen.spread(&mut self.population, &mut self.rng);
+        }
+
+        // Let the network of every plant decide how to use its energy before anything changes
+        let allocations: Vec<_> = match self.config.neural {
+            Some(neural) => (0..size.len())
+                .map(|index| {
+                    let plant = self.population.plant(index)?;
+                    neural.evaluate(&plant.genome, &self.sense(&neural, tick, index))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        // Let the roots compete for the water before the plants perceive what they took up
+        let root_water = match &self.config.roots {
+            Some(roots) => roots.absorb(&self.population, self.water.values_mut()),
+            None => Vec::new(),
+        };
+
+        // Perceive the surroundings on the threads and act on them in the order of the cells
+        let intakes = self.intakes(self.parallelism, &root_water);
+        for (index, light) in intakes.into_iter().enumerate() {
+            if let (Some(plant), Some(light)) = (self.population.plant_mut(index), light) {
+                let (mut intake, energy) = (light, plant.energy);
+                if let (Some(neural), Some(Some(allocation))) = (self.config.neural, allocations.get(index)) {
+                    intake = neural.allocate(plant, intake, allocation);
+                }
+                intake_total += intake as u64;
+                plant.act(intake);
+                if let Some(audit) = &mut self.audit {
+                    // The energy invested in growing and the energy which did not fit in the plant are spent
+                    audit.flows.light += light as u64;
+                    audit.flows.respiration += (energy as u64 + light as u64) - plant.energy as u64;
+                }
+                if let Some(trace) = &mut self.trace {
+                    trace.record(tick, plant.id(), TraceEvent::Collected { intake, energy: plant.energy });
+                }
+                self.resource_model.deplete(light, CellStock { water: &mut self.water.values_mut()[index], nutrients: &mut self.nutrients.values_mut()[index] });
+                if let Some(nutrients) = &self.config.nutrients {
+                    self.nutrients.take_up(index, nutrients);
+                }
+            }
+        }
+
+        // Share the energy collected through the underground networks
+        if let Some(mycorrhiza) = &self.config.mycorrhiza {
+            let lost = mycorrhiza.share(&mut self.population);
+            self.audit_flows(|flows| flows.respiration += lost);
+        }
+        self.audit_phase(Phase::Energy, 0);
+        self.stop_phase(stopwatch);
+
+        // Pay upkeep, the plants which cannot pay die
+        let stopwatch = self.start_phase(Phase::Death);
+        for index in 0..size.len() {
+            if let Some(plant) = self.population.plant_mut(index) {
+                let energy = plant.energy;
+                if plant.die(&self.config) {
+                    if record {
+                        events.push(SimEvent::PlantDied { tick, id: plant.id(), coord: size.coord_of(index).unwrap() });
+                    }
+                    let plant = self.population.take(index).unwrap();
+                    if self.config.nutrients.is_some() {
+                        self.nutrients.deposit(index, plant.energy);
+                    }
+                    self.record_death(&plant, Some(index), tick, DeathCause::Starvation);
+                    self.dirty.mark(index);
+                    deaths += 1;
+                } else {
+                    let upkeep = energy - plant.energy;
+                    if let Some(trace) = &mut self.trace {
+                        trace.record(tick, plant.id(), TraceEvent::UpkeepPaid { upkeep, energy: plant.energy });
+                    }
+                    self.audit_flows(|flows| flows.respiration += upkeep as u64);
+                }
+            }
+        }
+
+        self.audit_phase(Phase::Death, 0);
+        self.stop_phase(stopwatch);
+
+        // Reproduce
+        let stopwatch = self.start_phase(Phase::Reproduction);
+        let mut seeds = Vec::new();
+        let mutation = MutationConfig { rate: self.mutation_rate(), ..self.config.mutation };
+        let wind = self.config.wind.map(|wind| wind.field(size, tick));
+
+        for index in 0..size.len() {
+            let plant = match self.population.plant(index) {
+                Some(plant) => plant,
+                None => continue,
+            };
+
+            let id = plant.id();
+            if !self.reproduction_model.fertile(plant, &self.config) {
+                self.trace_event(tick, id, TraceEvent::Decided { decision: ReproductionDecision::Infertile });
+                continue;
+            }
+            if let Some(Some(allocation)) = allocations.get(index) {
+                if !allocation.reproduces() {
+                    self.trace_event(tick, id, TraceEvent::Decided { decision: ReproductionDecision::Declined });
+                    continue;
+                }
+            }
+
+            // Find the mate of the seed
+            let mate = match self.config.reproduction.mode {
+                ReproductionMode::Asexual => None,
+
+                ReproductionMode::Sexual => {
+                    let mates = find_mates(&self.population, &self.config.reproduction, size.coord_of(index).unwrap(), &plant.genome);
+                    let mate = match &self.config.pollination {
+                        Some(pollination) => pollination.choose_mate(&self.population, size.coord_of(index).unwrap(), &mates, &mut self.rng),
+                        None if mates.is_empty() => None,
+                        None => Some(mates[self.rng.gen_range(0..mates.len())]),
+                    };
+
+                    match mate {
+                        Some(mate) => self.population.plant(mate).cloned(),
+                        None if !self.config.reproduction.self_fertilize => {
+                            self.trace_event(tick, id, TraceEvent::Decided { decision: ReproductionDecision::NoMate });
+                            continue;
+                        }
+                        None => None,
+                    }
+                }
+            };
+
+            // Produce and pay for the seed, splitting its energy between a clutch of seeds. A reproduction model may
+            // pick plants which cannot pay the seed cost, they produce nothing
+            let plant = self.population.plant_mut(index).unwrap();
+            let energy = plant.energy;
+            let Some((seed, mutations)) = plant.reproduce(mate.as_ref(), &self.config, &mutation, &mut self.rng) else {
+                self.trace_event(tick, id, TraceEvent::Decided { decision: ReproductionDecision::Infertile });
+                continue;
+            };
+            self.trace_event(tick, id, TraceEvent::Decided { decision: ReproductionDecision::Reproduced });
+            let plant = self.population.plant_mut(index).unwrap();
+            let cost = energy - plant.energy;
+            let mut brood = vec![(seed, mutations)];
+            if let Some(clutch) = &self.config.clutch {
+                let shares = ClutchConfig::split(brood[0].0.energy, clutch.seeds(&plant.genome));
+                brood[0].0.energy = shares[0];
+                for share in &shares[1..] {
+                    brood.push(plant.sibling(mate.as_ref(), *share, &self.config, &mutation, &mut self.rng));
+                }
+            }
+            if let Some(audit) = &mut self.audit {
+                // The part of the cost which is not given to the seeds is spent on producing them
+                let provisioned: u64 = brood.iter().map(|(seed, _)| seed.energy as u64).sum();
+                audit.flows.respiration += cost as u64 - provisioned;
+            }
+
+            for (number, (mut seed, mut mutations)) in brood.into_iter().enumerate() {
+                if let Some(neural) = self.config.neural {
+                    if let Some(weight_mutation) = &neural.weight_mutation {
+                        mutations += seed.genome.mutate_gaussian(neural.weight_genes(), weight_mutation, &mut self.rng);
+                    }
+                }
+                if let Some(bounds) = &self.config.gene_bounds {
+                    bounds.clamp(&mut seed.genome);
+                }
+                if record && mutations > 0 {
+                    events.push(SimEvent::MutationApplied { tick, parent: plant.id(), genes: mutations });
+                }
+
+                // Disperse the seed
+                let coord = size.coord_of(index).unwrap();
+                let (mut dx, mut dy) = self.reproduction_model.disperse(plant, &mut self.rng);
+                if let Some(wind) = &wind {
+                    (dx, dy) = wind::drift(wind[index], (dx, dy), &mut self.rng);
+                }
+
+                let target = offset(size, coord, dx, dy);
+                if let Some(trace) = &mut self.trace {
+                    let event = TraceEvent::SeedProduced {
+                        cost: if number == 0 { cost } else { 0 },
+                        mutations,
+                        mate: seed.mate(),
+                        target: target.map(|target| size.coord_of(target).unwrap()),
+                        energy: plant.energy,
+                    };
+                    trace.record(tick, id, event);
+                }
+
+                match target {
+                    Some(target) if inside(target) => seeds.push((target, seed)),
+                    // The seeds landing in the frozen part of the board die
+                    Some(_) => {
+                        if let Some(audit) = &mut self.audit {
+                            audit.flows.decay += seed.energy as u64;
+                        }
+                    }
+                    None => {
+                        let energy = seed.energy as u64;
+                        let emigrated = match &mut self.emigrants {
+                            Some(emigrants) => {
+                                emigrants.push(Emigrant::leave(size, coord, dx, dy, seed));
+                                true
+                            }
+                            None => false,
+                        };
+                        if let Some(audit) = &mut self.audit {
+                            if emigrated { audit.flows.exported += energy } else { audit.flows.decay += energy }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.audit_phase(Phase::Reproduction, seed_energy(&seeds));
+        self.stop_phase(stopwatch);
+
+        // Germinate the seeds which landed on empty cells plants can grow in, the rest may go dormant
+        let stopwatch = self.start_phase(Phase::Dispersal);
+        if let Some(clutch) = &self.config.clutch {
+            let landed = seed_energy(&seeds);
+            seeds = clutch.establish(seeds, &mut self.rng);
+            let failed = landed - seed_energy(&seeds);
+            self.audit_flows(|flows| flows.decay += failed);
+        }
+        let (winners, mut dormant) = pick_winners(seeds, self.config.competition, &mut self.rng);
+        for (target, seed) in winners {
+            if self.board.fields.is_blocked(target) {
+                self.audit_flows(|flows| flows.decay += seed.energy as u64);
+                continue;
+            }
+
+            if !self.population.is_occupied(target) && self.germination_ready(target, &seed) {
+                self.germinate(tick, target, seed, record, &mut events);
+                births += 1;
+            } else {
+                dormant.push((target, seed));
+            }
+        }
+
+        // Bury the dormant seeds and germinate the ones which have waited long enough in empty cells
+        if let Some(bank) = self.config.seed_bank {
+            let buried = self.audit.as_ref().map(|_| self.board.seed_bank.energy());
+            self.board.seed_bank.expire(tick, bank.lifetime);
+            if let Some(buried) = buried {
+                let expired = buried - self.board.seed_bank.energy();
+                self.audit_flows(|flows| flows.decay += expired);
+            }
+
+            for (target, seed) in dormant {
+                let energy = seed.energy as u64;
+                if self.board.fields.is_blocked(target) || !self.board.seed_bank.bury(target, seed, tick, bank.capacity) {
+                    self.audit_flows(|flows| flows.decay += energy);
+                }
+            }
+
+            for index in 0..size.len() {
+                if !inside(index) || self.population.is_occupied(index) || self.board.fields.is_blocked(index) || self.light[index] < bank.min_light {
+                    continue;
+                }
+
+                let seed = match &self.config.germination {
+                    Some(germination) => {
+                        let cues = Cues { light: self.light[index], water: self.water.values()[index] };
+                        self.board.seed_bank.take_cued(index, tick, bank.dormancy, cues, germination, &mut self.rng)
+                    }
+                    None => self.board.seed_bank.take_germinating(index, tick, bank.dormancy),
+                };
+                if let Some(seed) = seed {
+                    self.germinate(tick, index, seed, record, &mut events);
+                    births += 1;
+                }
+            }
+        } else {
+            let lost = seed_energy(&dormant);
+            self.audit_flows(|flows| flows.decay += lost);
+        }
+
+        self.audit_phase(Phase::Dispersal, 0);
+        self.stop_phase(stopwatch);
+
+        // Move the water and let the litter decompose
+        let stopwatch = self.start_phase(Phase::Fields);
+        if let Some(water) = self.config.water.filter(|_| self.config.schedule.is_due(Subsystem::Water, tick)) {
+            self.water.step(&water, &self.light, &mut self.rng);
+            if water.runoff > 0.0 {
+                self.water.flow(&self.board.fields.elevation, water.runoff);
+            }
+
+            if let Some(band) = &self.config.edge_band {
+                band.apply(size, self.water.values_mut(), band.water_loss);
+            }
+        }
+        if let Some(nutrients) = self.config.nutrients.filter(|_| self.config.schedule.is_due(Subsystem::Nutrients, tick)) {
+            self.nutrients.decompose(&nutrients);
+        }
+        self.audit_phase(Phase::Fields, 0);
+        self.stop_phase(stopwatch);
+
+        // Let the toxin decay and the plants release new toxin into the cells next to them
+        let stopwatch = self.start_phase(Phase::Allelopathy);
+        if let Some(allelopathy) = &self.config.allelopathy {
+            self.toxins.decay(allelopathy);
+
+            for index in 0..size.len() {
+                if let Some(plant) = self.population.plant(index) {
+                    let amount = allelopathy.investment(&plant.genome) * allelopathy.emission.max(0.0);
+                    if amount > 0.0 {
+                        self.toxins.emit(index, amount);
+                    }
+                }
+            }
+        }
+        self.audit_phase(Phase::Allelopathy, 0);
+        self.stop_phase(stopwatch);
+
+        // Put the part of the board outside the active region back before the step is recorded
+        self.thaw();
+
+        self.tick = tick;
+        let stopwatch = self.start_phase(Phase::Bookkeeping);
+
+        // Cluster the plants into species
+        if let Some(tracker) = &mut self.species {
+            let found = tracker.events().len();
+            if tracker.update(tick, &self.population) && record {
+                events.extend(tracker.events()[found..].iter().map(|event| match *event {
+                    SpeciesEvent::Originated { tick, species, origin, .. } => SimEvent::SpeciesOriginated { tick, species, origin },
+                    SpeciesEvent::Extinct { tick, species, peak, .. } => SimEvent::SpeciesExtinct { tick, species, peak },
+                }));
+            }
+        }
+
+        // Let the births which are too old to count towards the fitness drop out
+        if let Some(fitness) = &mut self.fitness {
+            fitness.advance(tick);
+        }
+
+        // Publish the statistics and check the alerts before the mutation rate is adjusted for the next step
+        let mut alerted = false;
+        if (!self.subscribers.is_empty() || self.alerts.is_some()) && self.config.schedule.is_due(Subsystem::Statistics, tick) {
+            let species = self.species.as_ref().map_or(0, |tracker| tracker.living_count());
+            let fittest = self.fitness.as_ref().and_then(|fitness| fitness.fittest());
+            let stats = TickStats::new(tick, &self.population, births, deaths, mutation.rate, species, fittest);
+            self.subscribers.retain(|subscriber| subscriber.publish(stats));
+            if let Some(monitor) = &mut self.alerts {
+                let raised = monitor.check(&stats);
+                alerted = !raised.is_empty() && monitor.saves_states();
+                if record {
+                    events.extend(raised.into_iter().map(|alert| SimEvent::Alert { alert }));
+                }
+            }
+        }
+
+        // Adjust the mutation rate
+        if let Some(controller) = &mut self.mutation_controller {
+            if let Some(adjustment) = controller.update(self.tick, self.population.diversity()) {
+                if record {
+                    events.push(SimEvent::MutationRateChanged { adjustment });
+                }
+            }
+        }
+
+        // Keep the lineage tree from growing with every plant which has ever lived
+        if self.config.schedule.is_due(Subsystem::Phylogeny, tick) {
+            self.phylogeny.prune();
+        }
+
+        #[cfg(feature = "tracing")]
+        if alive > 0 && self.population.count() == 0 {
+            tracing::info!(tick, "the population went extinct");
+        }
+
+        if record {
+            events.push(SimEvent::TickCompleted { tick, population: self.population.count() });
+            self.hooks.emit(&events);
+        }
+
+        if let Some(occupancy) = &mut self.occupancy {
+            occupancy.record_step(&self.population);
+        }
+        if let Some(demography) = &mut self.demography {
+            demography.record_step(tick, &self.population);
+        }
+
+        self.balance = Some(EnergyBalance { before: energy_before, intake: intake_total, after: self.stored_energy() });
+        self.audit_phase(Phase::Bookkeeping, 0);
+        if let Some(audit) = &mut self.audit {
+            audit.finish(tick);
+        }
+        self.debug_assert_invariants();
+        if let Some(violation) = self.audit_violation() {
+            panic!("Energy books do not balance at tick {}: {}\n{}", tick, violation, self.energy_audit().unwrap().lines().join("\n"));
+        }
+        if self.autosave.is_due(self.tick) {
+            self.autosave.save(self.snapshot());
+        }
+        // Keep the state the alert was raised in once the step has finished
+        if alerted {
+            let snapshot = self.snapshot();
+            if let Some(monitor) = &mut self.alerts {
+                monitor.preserve(&snapshot);
+            }
+        }
+        self.stop_phase(stopwatch);
+    }
+}
+
+/// The energy every plant introduced with Simulation::introduce starts with
+pub const INTRODUCED_ENERGY: u32 = 100;
+
+/// Where the plants of a list of genomes are placed on the board when seeding a population
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    /// In random free cells anywhere on the board
+    Random,
+    /// Spread evenly over the board in rows and columns, a plant goes to the nearest free cell if its cell is taken
+    Grid,
+    /// In random free cells at most a radius in cells away from a center cell
+    Cluster(Coord, f32),
+}
+
+/// Builds a simulation from a board, optionally starting from an existing population
+/// and seeding plants from saved genomes, such that a run can start from the best genomes of earlier runs
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome};
+/// use evolution_plants::simulation::{Placement, SimulationBuilder};
+/// 
+/// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).build().unwrap();
+/// let champion: Genome = "g1:0.3,0.6".parse().unwrap();
+/// let simulation = SimulationBuilder::new(board)
+///     .seed_population(&[champion.clone(), champion], Placement::Cluster(Coord::new(4, 4), 1.5))
+///     .build()
+///     .unwrap();
+/// 
+/// assert_eq!(2, simulation.population().count());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SimulationBuilder {
+    /// The board the plants live on
+    board: Board,
+    /// The plants to start with before seeding, an empty population if this is None
+    population: Option<Population>,
+    /// The settings of the simulation
+    config: SimulationConfig,
+    /// The genomes to seed and where to place them, in the order they are seeded
+    seeds: Vec<(Vec<Genome>, Placement)>,
+    /// The energy every seeded plant starts with
+    seed_energy: u32,
+}
+
+impl SimulationBuilder {
+    /// Creates a new builder with an empty population, the default settings and seeded plants starting with 100 energy
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board the plants live on
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            population: None,
+            config: SimulationConfig::default(),
+            seeds: Vec::new(),
+            seed_energy: 100,
+        }
+    }
+
+    /// Sets the settings of the simulation
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings to use
+    pub fn config(mut self, config: SimulationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the plants to start with, the seeded plants are placed in the cells they leave free
+    /// 
+    /// # Parameters
+    /// 
+    /// population: The plants to start with, it must have the size of the board
+    pub fn population(mut self, population: Population) -> Self {
+        self.population = Some(population);
+        self
+    }
+
+    /// Adds a plant for every genome when the simulation is built, a genome given several times gives several plants.
+    /// The random placements are picked with a random number generator seeded from the seed of the settings
+    /// 
+    /// # Parameters
+    /// 
+    /// genomes: The genomes of the plants to add
+    /// 
+    /// placement: Where to place the plants
+    pub fn seed_population(mut self, genomes: &[Genome], placement: Placement) -> Self {
+        self.seeds.push((genomes.to_vec(), placement));
+        self
+    }
+
+    /// Sets the energy every seeded plant starts with
+    /// 
+    /// # Parameters
+    /// 
+    /// energy: The starting energy
+    pub fn seed_energy(mut self, energy: u32) -> Self {
+        self.seed_energy = energy;
+        self
+    }
+
+    /// Places the seeded plants and creates the simulation
+    /// 
+    /// # Errors
+    /// 
+    /// SimulationCreateError::Size: This will occur if the population does not have the size of the board
+    /// 
+    /// SimulationCreateError::Blocked: This will occur if a plant of the population is on terrain where plants cannot grow
+    /// 
+    /// SimulationCreateError::Crowded: This will occur if there are fewer free cells where the plants can grow than genomes to place
+    pub fn build(self) -> Result<Simulation, SimulationCreateError> {
+        let size = self.board.fields.size;
+        let mut population = self.population.unwrap_or_else(|| Population::new(size));
+        if population.size() != size {
+            return Err(SimulationCreateError::Size { board: size, population: population.size() });
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.config.seed);
+        for (genomes, placement) in &self.seeds {
+            let cells = place(&self.board, &population, genomes.len(), *placement, &mut rng)?;
+            for (coord, genome) in cells.into_iter().zip(genomes) {
+                population.insert(coord, Plant::new(self.seed_energy, genome.clone()));
+            }
+        }
+
+        Simulation::new(self.board, population, self.config)
+    }
+}
+
+/// Picks the cells for a number of new plants, only free cells where plants can grow are picked
+fn place<R: Rng>(board: &Board, population: &Population, count: usize, placement: Placement, rng: &mut R) -> Result<Vec<Coord>, SimulationCreateError> {
+    let size = board.fields.size;
+    let distance = |a: Coord, (x, y): (f32, f32)| ((a.x as f32 - x).powi(2) + (a.y as f32 - y).powi(2)).sqrt();
+    let mut free: Vec<Coord> = size.coords()
+        .filter(|&coord| population.get(coord).is_none() && !board.fields.is_blocked(size.index_of(coord).unwrap()))
+        .filter(|&coord| match placement {
+            Placement::Cluster(center, radius) => distance(coord, (center.x as f32, center.y as f32)) <= radius,
+            Placement::Random | Placement::Grid => true,
+        })
+        .collect();
+
+    if free.len() < count {
+        return Err(SimulationCreateError::Crowded { genomes: count, cells: free.len() });
+    }
+
+    match placement {
+        Placement::Random | Placement::Cluster(..) => {
+            free.shuffle(rng);
+            free.truncate(count);
+
+            Ok(free)
+        }
+        Placement::Grid => {
+            // Use about as many columns per row as the board is wide per tall
+            let (w, h) = size.size();
+            let columns = ((count as f32 * w as f32 / h as f32).sqrt().ceil() as usize).clamp(1, count.max(1));
+            let rows = count.div_ceil(columns);
+
+            Ok((0..count)
+                .map(|index| {
+                    let target = ((index % columns) as f32 + 0.5) * w as f32 / columns as f32 - 0.5;
+                    let target = (target, ((index / columns) as f32 + 0.5) * h as f32 / rows as f32 - 0.5);
+                    let (position, _) = free.iter()
+                        .enumerate()
+                        .min_by(|(_, a), (_, b)| distance(**a, target).total_cmp(&distance(**b, target)))
+                        .unwrap();
+
+                    free.remove(position)
+                })
+                .collect())
+        }
+    }
+}
+
+/// The settings of a simulation
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationConfig {
+    /// The seed for the random number generator
+    pub seed: u64,
+    /// The energy every plant must pay each step to survive
+    pub upkeep: u32,
+    /// The energy it costs to produce a seed on top of the energy given to the seed
+    pub seed_cost: u32,
+    /// The energy required to reproduce on top of the seed cost when the reproduction threshold gene is 1
+    pub max_threshold: u32,
+    /// The settings for mutating the genomes of seeds
+    pub mutation: MutationConfig,
+    /// The settings for how plants reproduce
+    pub reproduction: ReproductionConfig,
+    /// How the winner is chosen when several seeds land in the same cell
+    pub competition: Competition,
+    /// The settings for adjusting the mutation rate during the run, the rate is fixed if this is None
+    pub adaptive_mutation: Option<AdaptiveMutationConfig>,
+    /// The position of the sun casting shadows from the terrain, the terrain casts no shadows if this is None
+    pub sun: Option<Sun>,
+    /// The settings for letting plants grow tall and cast shadows under the sun, plants cast no shadows if this is None
+    /// or if there is no sun
+    pub canopy: Option<CanopyConfig>,
+    /// The settings for how the water moves, the water stays where it is if this is None
+    pub water: Option<WaterConfig>,
+    /// The settings for the litter of dead plants decomposing into nutrients, plants leave nothing behind if this is None
+    pub nutrients: Option<NutrientConfig>,
+    /// The settings for the plants competing for water with their roots, plants take up no water if this is None
+    pub roots: Option<RootConfig>,
+    /// The settings for carrying pollen with the wind in sexual reproduction, every compatible plant within pollen range
+    /// is an equally likely mate if this is None
+    pub pollination: Option<PollinationConfig>,
+    /// The settings for splitting the energy of the seeds between a clutch of several seeds which must establish themselves
+    /// where they land, plants produce a single seed which always establishes if this is None
+    pub clutch: Option<ClutchConfig>,
+    /// The settings for plants releasing toxin into the neighbouring cells, plants release no toxin if this is None
+    pub allelopathy: Option<AllelopathyConfig>,
+    /// The settings for plants sharing energy through an underground network, plants keep their energy to themselves if this is None
+    pub mycorrhiza: Option<MycorrhizaConfig>,
+    /// The settings for a prevailing wind carrying the seeds downwind, seeds land around their parent evenly if this is None
+    pub wind: Option<WindConfig>,
+    /// The settings for how temperature affects growth, temperature has no effect if this is None
+    pub thermal: Option<ThermalConfig>,
+    /// The band of harsher conditions along the edges of the board, the edges are like the rest if this is None
+    pub edge_band: Option<EdgeBand>,
+    /// The settings for clustering the plants into species, species are not tracked if this is None
+    pub species: Option<SpeciesConfig>,
+    /// The settings for how plants age, plants never age if this is None
+    pub aging: Option<AgingConfig>,
+    /// The settings for keeping seeds which could not germinate dormant in the ground, they die if this is None
+    pub seed_bank: Option<SeedBankConfig>,
+    /// The settings for letting seeds wait for light and water cues before they germinate with a chance,
+    /// seeds germinate as soon as they land in an empty cell if this is None
+    pub germination: Option<GerminationConfig>,
+    /// The settings for letting a network in the genome decide how plants use their energy, None if plants store all energy
+    pub neural: Option<NeuralConfig>,
+    /// The settings for a pathogen spreading between neighbouring plants, there is no pathogen if this is None
+    pub pathogen: Option<PathogenConfig>,
+    /// The settings for keeping the best genomes of the plants which die, no genomes are kept if this is None
+    pub archive: Option<ArchiveConfig>,
+    /// The settings for counting the offspring of every plant and species over the latest steps, nothing is counted if this is None
+    pub fitness: Option<FitnessConfig>,
+    /// The allowed values of the genes, the genomes of new seeds are moved into the ranges. Every gene may take any value
+    /// between 0 and 1 if this is None
+    pub gene_bounds: Option<GeneRegistry>,
+    /// How often the slowly changing subsystems run
+    pub schedule: Scheduler,
+    /// The timed actions the simulation carries out by itself, by default there are none
+    pub scenario: Scenario,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            upkeep: 10,
+            seed_cost: 50,
+            max_threshold: 1000,
+            mutation: MutationConfig::default(),
+            reproduction: ReproductionConfig::default(),
+            competition: Competition::default(),
+            adaptive_mutation: None,
+            sun: None,
+            canopy: None,
+            water: None,
+            nutrients: None,
+            roots: None,
+            pollination: None,
+            clutch: None,
+            allelopathy: None,
+            mycorrhiza: None,
+            wind: None,
+            thermal: None,
+            edge_band: None,
+            species: None,
+            aging: None,
+            seed_bank: None,
+            germination: None,
+            neural: None,
+            pathogen: None,
+            archive: None,
+            fitness: None,
+            gene_bounds: None,
+            schedule: Scheduler::default(),
+            scenario: Scenario::default(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// The largest upkeep, seed cost and maximum threshold allowed, below this the sums of the energy settings cannot overflow
+    pub const MAX_ENERGY: u32 = 1 << 30;
+
+    /// Checks that the settings can be used to run a simulation, including the changes made by the scenario
+    /// 
+    /// # Errors
+    /// 
+    /// ConfigError::Energy: This will occur if the upkeep, the seed cost or the maximum threshold is larger than SimulationConfig::MAX_ENERGY
+    /// 
+    /// ConfigError::Mutation: This will occur if the mutation settings or those of an update in the scenario cannot be used
+    /// 
+    /// ConfigError::Adaptive: This will occur if the limits of the adaptive mutation rate cannot be used
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::MutationConfig, simulation::SimulationConfig};
+    /// 
+    /// assert!(SimulationConfig::default().validate().is_ok());
+    /// assert!(SimulationConfig { mutation: MutationConfig::new(0.01, f32::NAN), ..Default::default() }.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_energy("upkeep", self.upkeep)?;
+        validate_energy("seed_cost", self.seed_cost)?;
+        validate_energy("max_threshold", self.max_threshold)?;
+        self.mutation.validate()?;
+        if let Some(adaptive) = &self.adaptive_mutation {
+            adaptive.validate()?;
+        }
+        for event in self.scenario.events() {
+            event.action.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of changes to the settings of a running simulation, settings which are None are left as they are
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ConfigUpdate {
+    /// The new settings for mutating the genomes of seeds, with adaptive mutation the rate is clamped to its limits
+    pub mutation: Option<MutationConfig>,
+    /// The new upkeep of every plant
+    pub upkeep: Option<u32>,
+    /// The new cost of producing a seed
+    pub seed_cost: Option<u32>,
+    /// The new energy required to reproduce when the reproduction threshold gene is 1
+    pub max_threshold: Option<u32>,
+    /// The new multiplier of the light field, it is clamped between 1 and Multipliers::MAX
+    pub light_multiplier: Option<u32>,
+    /// The new settings for the water cycle, Some(None) stops the water from moving
+    pub water: Option<Option<WaterConfig>>,
+    /// The new position of the sun, Some(None) removes the shadows
+    pub sun: Option<Option<Sun>>,
+}
+
+impl ConfigUpdate {
+    /// Checks that the changed settings can be used
+    /// 
+    /// # Errors
+    /// 
+    /// ConfigError::Energy: This will occur if the new upkeep, seed cost or maximum threshold is larger than SimulationConfig::MAX_ENERGY
+    /// 
+    /// ConfigError::Mutation: This will occur if the new mutation settings cannot be used
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (name, value) in [("upkeep", self.upkeep), ("seed_cost", self.seed_cost), ("max_threshold", self.max_threshold)] {
+            if let Some(value) = value {
+                validate_energy(name, value)?;
+            }
+        }
+        if let Some(mutation) = &self.mutation {
+            mutation.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A change made to the settings of a running simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfigChange {
+    /// The tick the change was made at, it takes effect from the step reaching the next tick
+    pub tick: u64,
+    /// The settings which were changed
+    pub update: ConfigUpdate,
+}
+
+/// The settings for how plants reproduce
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReproductionConfig {
+    /// Whether seeds are clones or combine the genomes of two plants
+    pub mode: ReproductionMode,
+    /// The method used to combine the genomes in sexual reproduction
+    pub crossover: Crossover,
+    /// The largest distance in cells pollen can travel to reach a mate
+    pub pollen_range: usize,
+    /// The largest genetic distance between two plants for them to be able to mate
+    pub compatibility: f32,
+    /// If true then a plant without any compatible mates produces a clone instead of no seed
+    pub self_fertilize: bool,
+}
+
+impl Default for ReproductionConfig {
+    fn default() -> Self {
+        Self {
+            mode: ReproductionMode::Asexual,
+            crossover: Crossover::Uniform,
+            pollen_range: 1,
+            compatibility: 0.1,
+            self_fertilize: true,
+        }
+    }
+}
+
+/// How plants reproduce
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReproductionMode {
+    /// Seeds are mutated clones of the parent
+    Asexual,
+    /// Seeds combine the genome of the parent with that of a compatible neighbour
+    Sexual,
+}
+
+/// How the winner is chosen when several seeds land in the same cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Competition {
+    /// The first seed to land wins, seeds land in order of the id of their parent
+    FirstWins,
+    /// The seed with the most energy wins, ties are won by the seed whose parent has the lowest id
+    #[default]
+    HighestEnergyWins,
+    /// A random seed wins with a chance proportional to its energy
+    Lottery,
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum SimulationCreateError {
+    #[error("Population has size {:?} but the board has size {:?}", population, board)]
+    Size {
+        board: Size,
+        population: Size,
+    },
+    #[error("The plant at {:?} is on terrain where plants cannot grow", coord)]
+    Blocked {
+        coord: Coord,
+    },
+    #[error("The plant at {:?} is outside the board", coord)]
+    Outside {
+        coord: Coord,
+    },
+    #[error("There are {:?} genomes to place but only {:?} free cells", genomes, cells)]
+    Crowded {
+        genomes: usize,
+        cells: usize,
+    },
+    #[error("The scenario uses the region {:?} which the board does not have", name)]
+    Region {
+        name: String,
+    },
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Mutation(#[from] MutationConfigError),
+    #[error(transparent)]
+    Adaptive(#[from] AdaptiveMutationError),
+    #[error("The energy setting {} is ({:?}) but must be at most ({:?})", name, value, SimulationConfig::MAX_ENERGY)]
+    Energy {
+        name: &'static str,
+        value: u32,
+    },
+}
+
+/// Checks that an energy setting is at most SimulationConfig::MAX_ENERGY
+fn validate_energy(name: &'static str, value: u32) -> Result<(), ConfigError> {
+    if value > SimulationConfig::MAX_ENERGY {
+        return Err(ConfigError::Energy { name, value });
+    }
+
+    Ok(())
+}
+
+/// Calculates the light in every cell after the terrain and the plants have cast their shadows and the edge band has been applied
+fn derived_light(board: &Board, population: &Population, config: &SimulationConfig) -> Vec<f32> {
+    let mut light = match (&config.sun, &config.canopy) {
+        (Some(sun), Some(canopy)) => shadow::canopy_light(&board.fields, population, sun, canopy),
+        (Some(sun), None) => shadow::shaded_light(&board.fields, sun),
+        (None, _) => board.fields.light.to_vec(),
+    };
+    if let Some(band) = &config.edge_band {
+        band.apply(board.fields.size, &mut light, band.light_loss);
+    }
+
+    light
+}
+
+/// Calculates the light energy collected in a cell every step after the modifier of the cell
+fn light_energy(board: &Board, light: &[f32], index: usize) -> u32 {
+    board.multipliers.scale_light(board.modifiers[index].apply(light[index]))
+}
+
+/// Finds the index of the cell a distance away from a coordinate, returns None if it is outside the board
+fn offset(size: Size, coord: Coord, dx: isize, dy: isize) -> Option<usize> {
+    size.index_of(coord.offset(dx, dy)?)
+}
+
+/// Picks where a seed lands relative to its parent, at most a distance away in each direction but never in the cell
+/// of the parent. Seeds with a range of 1 land in one of the neighbouring cells
+pub(crate) fn disperse<R: Rng + ?Sized>(range: usize, rng: &mut R) -> (isize, isize) {
+    if range <= 1 {
+        return NEIGHBOURS[rng.gen_range(0..NEIGHBOURS.len())];
+    }
+
+    let range = range as isize;
+    loop {
+        let (dx, dy) = (rng.gen_range(-range..=range), rng.gen_range(-range..=range));
+        if (dx, dy) != (0, 0) {
+            return (dx, dy);
+        }
+    }
+}
+
+/// Returns true if a seed beats the current winner of a cell, the seed with the most energy wins
+/// and ties are broken by the lowest parent id
+fn seed_wins(seed: &Plant, winner: &Plant) -> bool {
+    (seed.energy, Reverse(seed.parent())) > (winner.energy, Reverse(winner.parent()))
+}
+
+/// Picks a single winning seed for every cell seeds landed in, sorted by the index of the cell,
+/// and returns the winners together with the seeds which lost.
+/// The seeds of every cell are sorted by the id of their parent first, so the outcome never depends on
+/// the order the plants were processed in
+fn pick_winners<R: Rng>(seeds: Vec<(usize, Plant)>, competition: Competition, rng: &mut R) -> (BTreeMap<usize, Plant>, Vec<(usize, Plant)>) {
+    let mut contenders: BTreeMap<usize, Vec<Plant>> = BTreeMap::new();
+    for (target, seed) in seeds {
+        contenders.entry(target).or_default().push(seed);
+    }
+
+    let mut winners = BTreeMap::new();
+    let mut losers = Vec::new();
+
+    for (target, mut seeds) in contenders {
+        seeds.sort_by_key(|seed| seed.parent());
+
+        let winner = match competition {
+            Competition::FirstWins => 0,
+            Competition::HighestEnergyWins => (1..seeds.len()).fold(0, |winner, index| if seed_wins(&seeds[index], &seeds[winner]) { index } else { winner }),
+            Competition::Lottery => draw_lottery(&seeds, rng),
+        };
+
+        winners.insert(target, seeds.remove(winner));
+        losers.extend(seeds.into_iter().map(|seed| (target, seed)));
+    }
+
+    (winners, losers)
+}
+
+/// Returns the energy of the seeds which have been produced but have not been buried or germinated
+fn seed_energy(seeds: &[(usize, Plant)]) -> u64 {
+    seeds.iter().map(|(_, seed)| seed.energy as u64).sum()
+}
+
+/// Draws the index of the winning seed with a chance proportional to the energy of the seeds,
+/// all seeds have the same chance if none of them have any energy
+fn draw_lottery<R: Rng>(seeds: &[Plant], rng: &mut R) -> usize {
+    if seeds.len() < 2 {
+        return 0;
+    }
+
+    let total: u64 = seeds.iter().map(|seed| seed.energy as u64).sum();
+    if total == 0 {
+        return rng.gen_range(0..seeds.len());
+    }
+
+    let mut ticket = rng.gen_range(0..total);
+    for (index, seed) in seeds.iter().enumerate() {
+        if ticket < seed.energy as u64 {
+            return index;
+        }
+        ticket -= seed.energy as u64;
+    }
+
+    seeds.len() - 1
+}
+
+/// Finds the indices of all plants within pollen range which are compatible with a genome,
+/// sorted by the id of the plants such that the choice of mate does not depend on the order of the search
+fn find_mates(population: &Population, config: &ReproductionConfig, coord: Coord, genome: &Genome) -> Vec<usize> {
+    let size = population.size();
+    let mut mates: Vec<usize> = population.spatial().neighbors_within(coord, config.pollen_range)
+        .into_iter()
+        .filter_map(|neighbour| size.index_of(neighbour))
+        .filter(|&index| population.plant(index).is_some_and(|mate| genome.distance(&mate.genome) <= config.compatibility))
+        .collect();
+
+    mates.sort_by_key(|&index| population.plant(index).unwrap().id());
+
+    mates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::{Alert, AlertRule};
+    use crate::board::{Fields, Rect, Terrain};
+    use crate::checkpoint::CheckpointConfig;
+    use crate::population::PlantId;
+    use crate::species::SpeciesId;
+    use crate::phenotype::Phenotype;
+    use rand::RngCore;
+
+    fn board(size: Size, light: f32) -> Board {
+        let fields = Fields::new(size, &vec![light; size.len()]).unwrap();
+
+        Board::new(Multipliers::new(100).unwrap(), fields)
+    }
+
+    fn config() -> SimulationConfig {
+        SimulationConfig {
+            seed: 1,
+            upkeep: 10,
+            seed_cost: 20,
+            max_threshold: 100,
+            mutation: MutationConfig::new(0.0, 0.0),
+            reproduction: ReproductionConfig::default(),
+            competition: Competition::default(),
+            adaptive_mutation: None,
+            sun: None,
+            canopy: None,
+            water: None,
+            nutrients: None,
+            roots: None,
+            pollination: None,
+            clutch: None,
+            allelopathy: None,
+            mycorrhiza: None,
+            wind: None,
+            thermal: None,
+            edge_band: None,
+            species: None,
+            aging: None,
+            seed_bank: None,
+            germination: None,
+            neural: None,
+            pathogen: None,
+            archive: None,
+            fitness: None,
+            gene_bounds: None,
+            schedule: Scheduler::default(),
+            scenario: Scenario::default(),
+        }
+    }
+
+    #[test]
+    fn simulation_new() -> Result<(), SimulationCreateError> {
+        let size = Size::new(3, 3);
+        let simulation = Simulation::new(board(size, 1.0), Population::new(size), config())?;
+
+        assert_eq!(board(size, 1.0), simulation.board);
+        assert_eq!(Population::new(size), simulation.population);
+        assert_eq!(config(), simulation.config);
+        assert_eq!(0, simulation.tick);
+        assert_eq!(None, simulation.mutation_controller);
+
+        Ok(())
+    }
+
+    #[test]
+    fn simulation_new_error_size() {
+        let simulation = Simulation::new(board(Size::new(3, 3), 1.0), Population::new(Size::new(3, 2)), config());
+
+        assert_eq!(SimulationCreateError::Size { board: Size::new(3, 3), population: Size::new(3, 2) }, simulation.unwrap_err());
+    }
+
+    #[test]
+    fn simulation_new_error_blocked() {
+        let size = Size::new(2, 1);
+        let fields = Fields::new(size, &[1.0; 2]).unwrap().with_terrain(&[Terrain::Open, Terrain::Rock]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config());
+
+        assert_eq!(SimulationCreateError::Blocked { coord: Coord::new(1, 0) }, simulation.unwrap_err());
+    }
+
+    fn genomes(count: usize) -> Vec<Genome> {
+        (0..count).map(|index| Genome::new(&[index as f32 / count as f32, 0.5]).unwrap()).collect()
+    }
+
+    #[test]
+    fn simulation_builder_random() {
+        let size = Size::new(2, 1);
+        let fields = Fields::new(size, &[1.0; 2]).unwrap().with_terrain(&[Terrain::Rock, Terrain::Open]).unwrap();
+        let simulation = SimulationBuilder::new(Board::new(Multipliers::new(100).unwrap(), fields))
+            .config(config())
+            .seed_energy(40)
+            .seed_population(&genomes(1), Placement::Random)
+            .build()
+            .unwrap();
+
+        // The only cell where plants can grow is picked
+        assert_eq!(Some(40), simulation.population().get(Coord::new(1, 0)).map(|plant| plant.energy));
+        assert_eq!(1, simulation.phylogeny().len());
+    }
+
+    #[test]
+    fn simulation_builder_grid() {
+        let simulation = SimulationBuilder::new(board(Size::new(4, 4), 1.0))
+            .seed_population(&genomes(4), Placement::Grid)
+            .build()
+            .unwrap();
+        let mut coords: Vec<Coord> = simulation.population().iter().map(|(coord, _)| coord).collect();
+        coords.sort_by_key(|coord| (coord.y, coord.x));
+
+        assert_eq!(vec![Coord::new(0, 0), Coord::new(2, 0), Coord::new(0, 2), Coord::new(2, 2)], coords);
+    }
+
+    #[test]
+    fn simulation_builder_cluster() {
+        let size = Size::new(9, 9);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(4, 4), Plant::new(10, Genome::new(&[1.0, 1.0]).unwrap()));
+        let simulation = SimulationBuilder::new(board(size, 1.0))
+            .population(population)
+            .seed_population(&genomes(4), Placement::Cluster(Coord::new(4, 4), 1.0))
+            .build()
+            .unwrap();
+
+        assert_eq!(5, simulation.population().count());
+        assert!(simulation.population().iter().all(|(coord, _)| coord.x.abs_diff(4) + coord.y.abs_diff(4) <= 1));
+        assert_eq!(Some(10), simulation.population().get(Coord::new(4, 4)).map(|plant| plant.energy));
+    }
+
+    #[test]
+    fn simulation_builder_error_crowded() {
+        let simulation = SimulationBuilder::new(board(Size::new(3, 3), 1.0))
+            .seed_population(&genomes(2), Placement::Cluster(Coord::new(0, 0), 0.5))
+            .build();
+
+        assert_eq!(SimulationCreateError::Crowded { genomes: 2, cells: 1 }, simulation.unwrap_err());
+    }
+
+    #[test]
+    fn simulation_builder_error_size() {
+        let simulation = SimulationBuilder::new(board(Size::new(3, 3), 1.0))
+            .population(Population::new(Size::new(2, 2)))
+            .seed_population(&genomes(1), Placement::Random)
+            .build();
+
+        assert_eq!(SimulationCreateError::Size { board: Size::new(3, 3), population: Size::new(2, 2) }, simulation.unwrap_err());
+    }
+
+    #[test]
+    fn simulation_step_blocked() {
+        // Every neighbour is blocked so the seeds never germinate
+        let size = Size::new(3, 3);
+        let mut terrain = vec![Terrain::Water; 9];
+        terrain[4] = Terrain::Open;
+        let fields = Fields::new(size, &[1.0; 9]).unwrap().with_terrain(&terrain).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config()).unwrap();
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        assert_eq!(1, simulation.population.count());
+        assert_eq!(1, simulation.phylogeny.len());
+    }
+
+    #[test]
+    fn simulation_step_energy() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(5, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.step();
+
+        assert_eq!(5 + 50 - 10, simulation.population.get(Coord::new(1, 1)).unwrap().energy);
+        assert_eq!(1, simulation.tick);
+    }
+
+    #[test]
+    fn simulation_step_modifier() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(5, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        assert!(simulation.set_modifier(Coord::new(1, 1), Modifier::new(0.1, 2.0)));
+        assert!(simulation.set_modifier(Coord::new(0, 0), Modifier::new(-0.2, 1.0)));
+        assert!(!simulation.set_modifier(Coord::new(3, 0), Modifier::new(-0.2, 1.0)));
+        simulation.step();
+
+        assert_eq!(5 + 110 - 10, simulation.population.get(Coord::new(1, 1)).unwrap().energy);
+        assert_eq!(50 + 30 - 10, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(0.5, simulation.light()[0]);
+    }
+
+    #[test]
+    fn simulation_step_aging() {
+        // The plant gains exactly its upkeep but outlives its lifespan of 2 steps and pays more and more for respiration
+        let size = Size::new(1, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(15, Genome::new(&[1.0, 0.0, 0.5, 0.5, 0.25]).unwrap()));
+        let aging = AgingConfig { max_lifespan: 8, respiration: 4.0 };
+        let mut simulation = Simulation::new(board(size, 0.1), population, SimulationConfig { aging: Some(aging), ..config() }).unwrap();
+        for _ in 0..5 {
+            simulation.step();
+        }
+
+        assert_eq!(3, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(5, simulation.population.get(Coord::new(0, 0)).unwrap().age);
+
+        simulation.step();
+
+        assert_eq!(0, simulation.population.count());
+    }
+
+    #[test]
+    fn simulation_step_seed_bank_bury() {
+        // The board is full so every seed landing on it goes dormant
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        for index in 0..size.len() {
+            population.insert(size.coord_of(index).unwrap(), Plant::new(200, Genome::new(&[0.0, 0.5]).unwrap()));
+        }
+        let bank = SeedBankConfig { capacity: 100, ..Default::default() };
+        let mut simulation = Simulation::new(board(size, 1.0), population.clone(), SimulationConfig { seed_bank: Some(bank), ..config() }).unwrap();
+        simulation.step();
+
+        assert!(simulation.board.seed_bank.count() > 0);
+        assert_eq!(9, simulation.population.count());
+
+        let mut simulation = Simulation::new(board(size, 1.0), population, config()).unwrap();
+        simulation.step();
+
+        assert_eq!(0, simulation.board.seed_bank.count());
+    }
+
+    #[test]
+    fn simulation_step_seed_bank_germinate() {
+        let size = Size::new(2, 1);
+        let bank = SeedBankConfig { dormancy: 2, min_light: 0.5, ..Default::default() };
+        let mut board = board(size, 1.0);
+        board.fields.light[1] = 0.25;
+        board.seed_bank.bury(0, Plant::seed(PlantId(0), None, 5, Genome::new(&[1.0, 0.0]).unwrap()), 0, 4);
+        board.seed_bank.bury(1, Plant::seed(PlantId(0), None, 5, Genome::new(&[1.0, 0.0]).unwrap()), 0, 4);
+        let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig { seed_bank: Some(bank), ..config() }).unwrap();
+        simulation.step();
+
+        assert_eq!(0, simulation.population.count());
+
+        simulation.step();
+
+        // The second cell is too dark for its seed to germinate
+        assert_eq!(1, simulation.population.count());
+        assert_eq!(Some(PlantId(0)), simulation.population.get(Coord::new(0, 0)).unwrap().parent());
+        assert_eq!(1, simulation.board.seed_bank.count());
+    }
+
+    #[test]
+    fn simulation_active_region() {
+        let size = Size::new(6, 6);
+        let mut population = Population::new(size);
+        for index in 0..size.len() {
+            population.insert(size.coord_of(index).unwrap(), Plant::new(50, Genome::new(&[0.0, 0.2]).unwrap()));
+        }
+        population.remove(Coord::new(1, 1));
+        population.remove(Coord::new(4, 4));
+        let allelopathy = AllelopathyConfig { emission: 1.0, cost: 0.0, decay_rate: 0.5, inhibition: 0.0 };
+        let config = SimulationConfig { upkeep: 1, allelopathy: Some(allelopathy), ..config() };
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.audit_energy(Some(0));
+        simulation.set_active_region(Some(Rect::new(0, 0, 3, 3)));
+        let outside: Vec<Plant> = simulation.population.iter().filter(|(coord, _)| coord.x >= 3 || coord.y >= 3).map(|(_, plant)| plant.clone()).collect();
+        simulation.schedule_disturbance(2, Disturbance::new(DisturbanceKind::Fire, Rect::new(2, 2, 4, 4)));
+        for _ in 0..6 {
+            simulation.step();
+        }
+
+        // Nothing outside the region changed and no seeds landed in the empty cell outside
+        let frozen: Vec<Plant> = simulation.population.iter().filter(|(coord, _)| coord.x >= 3 || coord.y >= 3).map(|(_, plant)| plant.clone()).collect();
+        assert_eq!(outside, frozen);
+        assert!(simulation.population.get(Coord::new(4, 4)).is_none());
+        assert!(simulation.population.get(Coord::new(2, 2)).is_none_or(|plant| plant.age < 6));
+        assert!(simulation.toxins.values().iter().enumerate().all(|(index, &toxin)| Rect::new(0, 0, 3, 3).contains(size.coord_of(index).unwrap()) || toxin == 0.0));
+        assert_eq!(Ok(()), simulation.check_invariants());
+
+        simulation.crop(0, 0, 4, 4);
+
+        assert_eq!(None, simulation.active_region());
+    }
+
+    #[test]
+    fn simulation_step_germination_cues() {
+        let size = Size::new(3, 1);
+        let bank = SeedBankConfig { dormancy: 0, ..Default::default() };
+        let germination = GerminationConfig { max_light: 1.0, max_water: 1.0, chance: 1.0, persistence: 2 };
+        let seed = |light_cue: f32| {
+            let mut genes = vec![0.0; crate::genome::GENE_WATER_CUE + 1];
+            genes[0] = 1.0;
+            genes[crate::genome::GENE_LIGHT_CUE] = light_cue;
+            Plant::seed(PlantId(0), None, 5, Genome::new(&genes).unwrap())
+        };
+        let mut board = board(size, 1.0);
+        board.fields.light[1] = 0.5;
+        board.seed_bank.bury(0, seed(0.8), 0, 4);
+        board.seed_bank.bury(1, seed(0.8), 0, 4);
+        board.seed_bank.bury(2, seed(0.0), 0, 4);
+        let config = SimulationConfig { seed_bank: Some(bank), germination: Some(germination), ..config() };
+        let mut simulation = Simulation::new(board, Population::new(size), config).unwrap();
+        simulation.step();
+
+        // The seeds must meet their cues for two steps in a row
+        assert_eq!(0, simulation.population.count());
+        assert_eq!(vec![1, 0, 1], simulation.board.seed_bank.seeds(Coord::new(0, 0)).iter()
+            .chain(simulation.board.seed_bank.seeds(Coord::new(1, 0)))
+            .chain(simulation.board.seed_bank.seeds(Coord::new(2, 0)))
+            .map(|dormant| dormant.cued)
+            .collect::<Vec<_>>());
+
+        simulation.step();
+
+        // The second cell is too dark for the light cue of its seed
+        assert_eq!(2, simulation.population.count());
+        assert!(simulation.population.get(Coord::new(1, 0)).is_none());
+        assert_eq!(1, simulation.board.seed_bank.count());
+    }
+
+    #[test]
+    fn simulation_step_germination_chance() {
+        let size = Size::new(5, 5);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(1000, Genome::new(&[0.0, 0.1]).unwrap()));
+        let germination = GerminationConfig { chance: 0.0, ..Default::default() };
+        let mut simulation = Simulation::new(board(size, 0.5), population.clone(), SimulationConfig { upkeep: 0, germination: Some(germination), ..config() }).unwrap();
+        for _ in 0..5 {
+            simulation.step();
+        }
+
+        // Seeds which do not germinate die without a seed bank
+        assert_eq!(1, simulation.population.count());
+
+        let mut simulation = Simulation::new(board(size, 0.5), population, SimulationConfig { upkeep: 0, ..config() }).unwrap();
+        for _ in 0..5 {
+            simulation.step();
+        }
+
+        assert!(simulation.population.count() > 1);
+    }
+
+    #[test]
+    fn simulation_step_shadow() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[1.0; 3]).unwrap().with_elevation(&[1.5, 0.0, 0.0]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let sun = Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.5);
+        let config = SimulationConfig { sun: Some(sun), ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config).unwrap();
+        simulation.step();
+
+        assert_eq!(&[1.0, 0.5, 1.0], simulation.light());
+        assert_eq!(50 - 10, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+        assert_eq!(100 - 10, simulation.population.get(Coord::new(2, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_nutrients() {
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(5, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        let nutrients = NutrientConfig { decay_rate: 0.5, yield_per_energy: 0.2, growth: 1.0, saturation: 2.0, uptake: 0.5 };
+        let config = SimulationConfig { nutrients: Some(nutrients), max_threshold: 1000, ..config() };
+        let mut simulation = Simulation::new(board(size, 0.0), population, config).unwrap();
+        simulation.step();
+
+        // The starving plant leaves its energy behind and half of it decomposes
+        assert_eq!(&[2.5, 0.0], simulation.nutrients().litter());
+        assert_eq!(&[0.5, 0.0], simulation.nutrients().values());
+
+        simulation.step();
+
+        assert_eq!(&[1.25, 0.0], simulation.nutrients().litter());
+        assert_eq!(&[0.75, 0.0], simulation.nutrients().values());
+    }
+
+    #[test]
+    fn simulation_step_nutrients_growth() {
+        let size = Size::new(1, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let nutrients = NutrientConfig { decay_rate: 1.0, yield_per_energy: 1.0, growth: 0.5, saturation: 2.0, uptake: 0.5 };
+        let config = SimulationConfig { nutrients: Some(nutrients), max_threshold: 1000, ..config() };
+        let mut simulation = Simulation::new(board(size, 1.0), population, config).unwrap();
+        simulation.nutrients.deposit(0, 1);
+        simulation.nutrients.decompose(&nutrients);
+        simulation.step();
+
+        // The plant collects 1.5 times the light and takes up half of the nutrients
+        assert_eq!(150 - 10, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(&[0.5], simulation.nutrients().values());
+    }
+
+    #[test]
+    fn simulation_step_roots() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[0.0; 3]).unwrap().with_water(&[1.0, 2.0, 1.0]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        population.insert(Coord::new(2, 0), Plant::new(100, Genome::new(&[1.0, 0.0]).unwrap()));
+        let roots = RootConfig { max_radius: 2, uptake: 0.5, energy_per_water: 10.0, cost: 3.0 };
+        let config = SimulationConfig { roots: Some(roots), max_threshold: 1000, ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config).unwrap();
+        simulation.step();
+
+        // The plant with roots takes half the water of every cell in reach, the plant without roots takes nothing
+        assert_eq!(&[0.5, 1.0, 0.5], simulation.water().values());
+        assert_eq!(100 + 20 - 10 - 3, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(100 - 10, simulation.population.get(Coord::new(2, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_canopy() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[1.0; 3]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let sun = Sun::new(std::f32::consts::PI, std::f32::consts::FRAC_PI_4, 0.5);
+        let canopy = CanopyConfig { max_height: 1.5, height_cost: 4.0 };
+        let config = SimulationConfig { sun: Some(sun), canopy: Some(canopy), max_threshold: 1000, ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config).unwrap();
+        simulation.step();
+
+        assert_eq!(&[1.0, 0.5, 1.0], simulation.light());
+        assert_eq!(100 - 10 - 4, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(50 - 10, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+
+        // The shadow is gone in the step after the tall plant is removed
+        simulation.remove_plant(0);
+        simulation.step();
+
+        assert_eq!(&[1.0, 1.0, 1.0], simulation.light());
+        assert_eq!(40 + 100 - 10, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_water() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[0.0; 3]).unwrap().with_water(&[0.0, 1.0, 0.0]).unwrap();
+        let water = WaterConfig { diffusion: 0.25, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        let config = SimulationConfig { water: Some(water), ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), Population::new(size), config).unwrap();
+        simulation.step();
+
+        assert_eq!(&[0.25, 0.5, 0.25], simulation.water().values());
+    }
+
+    #[test]
+    fn simulation_step_runoff() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[0.0; 3]).unwrap().with_water(&[1.0, 1.0, 1.0]).unwrap().with_elevation(&[2.0, 1.0, 0.0]).unwrap();
+        let water = WaterConfig { diffusion: 0.0, evaporation: 0.0, runoff: 0.5, rainfall: None };
+        let config = SimulationConfig { water: Some(water), ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), Population::new(size), config).unwrap();
+        simulation.step();
+
+        assert_eq!(&[0.5, 1.0, 1.5], simulation.water().values());
+    }
+
+    #[test]
+    fn simulation_step_schedule() {
+        let size = Size::new(3, 1);
+        let fields = Fields::new(size, &[0.0; 3]).unwrap().with_water(&[0.0, 1.0, 0.0]).unwrap();
+        let water = WaterConfig { diffusion: 0.25, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        let schedule = Scheduler::new().every(Subsystem::Water, 2).every(Subsystem::Statistics, 0);
+        let config = SimulationConfig { water: Some(water), schedule, ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), Population::new(size), config).unwrap();
+        let mut stats = crate::stats::subscribe(&mut simulation, 4);
+        simulation.step();
+
+        // The water only flows every other step and no statistics are ever published
+        assert_eq!(&[0.0, 1.0, 0.0], simulation.water().values());
+
+        simulation.step();
+
+        assert_eq!(&[0.25, 0.5, 0.25], simulation.water().values());
+        assert_eq!(None, stats.try_next());
+    }
+
+    #[test]
+    fn simulation_step_thermal() {
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0, 0.5, 0.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0, 0.5, 0.0]).unwrap()));
+        let thermal = ThermalConfig { min_optimum: 0.0, max_optimum: 20.0, max_tolerance: 4.0, penalty: 0.25 };
+        let config = SimulationConfig { thermal: Some(thermal), ..config() };
+        let mut simulation = Simulation::new(board(size, 1.0), population, config).unwrap();
+        simulation.set_temperature(&[10.0, 12.0]).unwrap();
+        simulation.step();
+
+        assert_eq!(100 - 10, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(50 - 10, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_set_temperature_error_size() {
+        let size = Size::new(2, 1);
+        let mut simulation = Simulation::new(board(size, 1.0), Population::new(size), config()).unwrap();
+
+        assert_eq!(FieldCreateError::Size {name: "Temperature".to_string(), len: 3, size}, simulation.set_temperature(&[0.0; 3]).unwrap_err());
+    }
+
+    #[test]
+    fn simulation_step_edge_band() {
+        let size = Size::new(3, 3);
+        let fields = Fields::new(size, &[1.0; 9]).unwrap().with_water(&[1.0; 9]).unwrap();
+        let water = WaterConfig { diffusion: 0.0, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        let config = SimulationConfig { water: Some(water), edge_band: Some(EdgeBand::new(1, 0.5, 0.25)), ..config() };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), Population::new(size), config).unwrap();
+        simulation.step();
+
+        assert_eq!(&[0.5, 0.5, 0.5, 0.5, 1.0, 0.5, 0.5, 0.5, 0.5], simulation.light());
+        assert_eq!(&[0.75, 0.75, 0.75, 0.75, 1.0, 0.75, 0.75, 0.75, 0.75], simulation.water().values());
+    }
+
+    #[test]
+    fn simulation_on_event() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let config = SimulationConfig { mutation: MutationConfig::new(1.0, 0.1), ..config() };
+        let mut simulation = Simulation::new(board(size, 0.0), population, config).unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.step();
+        let events = events.lock().unwrap();
+
+        assert_eq!(SimEvent::PlantDied { tick: 1, id: PlantId(1), coord: Coord::new(0, 0) }, events[0]);
+        assert_eq!(SimEvent::MutationApplied { tick: 1, parent: PlantId(0), genes: 2 }, events[1]);
+        assert!(matches!(events[2], SimEvent::PlantBorn { tick: 1, parent: Some(PlantId(0)), .. }));
+        assert_eq!(SimEvent::TickCompleted { tick: 1, population: 2 }, events[3]);
+        assert_eq!(4, events.len());
+    }
+
+    #[test]
+    fn simulation_cull() {
+        let size = Size::new(4, 4);
+        let mut population = Population::new(size);
+        for coord in size.coords() {
+            population.insert(coord, Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        }
+        let mut simulation = Simulation::new(board(size, 1.0), population, config()).unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+
+        assert_eq!(8, simulation.cull(Cull::Region(Rect::new(0, 0, 2, 4))));
+        assert_eq!(2, simulation.cull(Cull::KeepRegion(Rect::new(2, 0, 2, 3))));
+        assert_eq!(3, simulation.cull(Cull::Random { fraction: 0.5 }));
+        assert_eq!(2, simulation.keep_only(|coord, _| coord.y == 0));
+
+        let events = events.lock().unwrap();
+        let culls: Vec<(usize, usize)> = events.iter().filter_map(|event| match event {
+            SimEvent::Culled { killed, survivors, .. } => Some((*killed, *survivors)),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![(8, 8), (2, 6), (3, 3), (2, 1)], culls);
+        assert_eq!(15, events.iter().filter(|event| matches!(event, SimEvent::PlantDied { .. })).count());
+        assert_eq!(1, simulation.population().count());
+        assert!(simulation.phylogeny().get(PlantId(0)).unwrap().death.is_some());
+    }
+
+    /// A development where seeds travel far
+    #[derive(Debug)]
+    struct FarDispersal;
+
+    impl Development for FarDispersal {
+        fn develop(&self, genome: &Genome) -> Phenotype {
+            Phenotype { dispersal: 4, ..DirectDevelopment.develop(genome) }
+        }
+    }
+
+    #[test]
+    fn simulation_development_dispersal() {
+        let size = Size::new(9, 9);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(4, 4), Plant::new(1000, Genome::new(&[0.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 1.0), population, config()).unwrap();
+        simulation.set_development(FarDispersal);
+        for _ in 0..10 {
+            simulation.step();
+        }
+
+        assert!(simulation.population().iter().all(|(_, plant)| plant.phenotype.dispersal == 4));
+        assert!(simulation.population().iter().any(|(coord, _)| coord.x.abs_diff(4) > 1 || coord.y.abs_diff(4) > 1));
+    }
+
+    /// A reproduction model where offspring always land two cells to the right and only rich organisms reproduce
+    #[derive(Debug)]
+    struct Rightwards;
+
+    impl ReproductionModel for Rightwards {
+        fn fertile(&self, organism: &Plant, _: &SimulationConfig) -> bool {
+            organism.energy >= 500
+        }
+
+        fn disperse(&self, _: &Plant, _: &mut dyn RngCore) -> (isize, isize) {
+            (2, 0)
+        }
+    }
+
+    /// A reproduction model where every organism reproduces, even those which cannot pay the seed cost
+    #[derive(Debug)]
+    struct Always;
+
+    impl ReproductionModel for Always {
+        fn fertile(&self, _: &Plant, _: &SimulationConfig) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn simulation_reproduction_model_unpaid() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(15, Genome::new(&[0.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.0), population, config()).unwrap();
+        simulation.set_reproduction_model(Always);
+        simulation.step();
+
+        // The plant pays its upkeep but is left with less than the seed cost, so no seed is produced
+        assert_eq!(1, simulation.population().count());
+        assert_eq!(5, simulation.population().get(Coord::new(1, 1)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_reproduction_model() {
+        let size = Size::new(9, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(1000, Genome::new(&[0.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 1.0), population, config()).unwrap();
+        simulation.set_reproduction_model(Rightwards);
+        simulation.step();
+
+        assert!(simulation.population().get(Coord::new(2, 0)).is_some());
+        assert!(simulation.population().iter().all(|(coord, _)| coord.x % 2 == 0));
+    }
+
+    #[test]
+    fn disperse_range() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..100 {
+            let (dx, dy) = disperse(2, &mut rng);
+            assert!((dx, dy) != (0, 0) && dx.abs() <= 2 && dy.abs() <= 2);
+            assert!(NEIGHBOURS.contains(&disperse(0, &mut rng)));
+        }
+    }
+
+    #[test]
+    fn simulation_step_neural() {
+        let neural = NeuralConfig { offset: 2, hidden: 1, ..Default::default() };
+        let size = Size::new(3, 3);
+        let config = SimulationConfig { neural: Some(neural), ..config() };
+
+        // A network which only wants to grow never reproduces but collects more light
+        let mut grow = vec![0.5; 2 + neural.weight_count()];
+        grow[0] = 0.0;
+        grow[2 + 5 + 1] = 1.0;
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(1000, Genome::new(&grow).unwrap()));
+        let mut simulation = Simulation::new(board(size, 1.0), population, config.clone()).unwrap();
+        for _ in 0..3 {
+            simulation.step();
+        }
+
+        assert_eq!(1, simulation.population().iter().count());
+        assert_eq!(1.0, simulation.population().get(Coord::new(1, 1)).unwrap().growth);
+
+        // A network which only wants to reproduce spreads like a plant without a network
+        let mut reproduce = vec![0.5; 2 + neural.weight_count()];
+        reproduce[0] = 0.0;
+        reproduce[2 + 5 + 2 * 2 + 1] = 1.0;
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(1000, Genome::new(&reproduce).unwrap()));
+        let mut simulation = Simulation::new(board(size, 1.0), population, config).unwrap();
+        simulation.step();
+
+        assert_eq!(2, simulation.population().iter().count());
+    }
+
+    #[test]
+    fn simulation_step_pathogen() {
+        let size = Size::new(3, 3);
+        let pathogen = PathogenConfig { outbreak: 1.0, duration: 5, damage: 100, ..Default::default() };
+        let genome = Genome::new(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, genome.clone()));
+        let mut simulation = Simulation::new(board(size, 0.0), population.clone(), config()).unwrap();
+        simulation.step();
+
+        assert_eq!(1, simulation.population().count());
+
+        let mut simulation = Simulation::new(board(size, 0.0), population, SimulationConfig { pathogen: Some(pathogen), ..config() }).unwrap();
+        simulation.step();
+
+        assert_eq!(0, simulation.population().count());
+    }
+
+    #[test]
+    fn simulation_dirty() {
+        // Drawing only the dirty cells every step gives the same pixels as drawing the entire board
+        let size = Size::new(6, 6);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(200, Genome::new(&[0.2, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        let mut canvas = crate::render::Canvas::new(simulation.board(), simulation.population());
+        simulation.clear_dirty();
+
+        for _ in 0..20 {
+            simulation.step();
+            canvas.update(simulation.board(), simulation.population(), simulation.dirty(), 2);
+            simulation.clear_dirty();
+
+            assert_eq!(crate::render::render_rgba(simulation.board(), simulation.population()), canvas.pixels());
+        }
+    }
+
+    #[test]
+    fn simulation_dirty_rewind() {
+        let size = Size::new(3, 3);
+        let mut simulation = Simulation::new(board(size, 0.5), Population::new(size), config()).unwrap();
+        simulation.enable_history(1);
+        simulation.step();
+        simulation.clear_dirty();
+        simulation.rewind(1);
+
+        assert!(simulation.dirty().is_all());
+    }
+
+    #[test]
+    fn simulation_profiling() {
+        // Timing the steps does not change them
+        let size = Size::new(6, 6);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(200, Genome::new(&[0.2, 0.5]).unwrap()));
+        let mut plain = Simulation::new(board(size, 0.5), population.clone(), config()).unwrap();
+        let mut profiled = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        profiled.profiling(true);
+        for _ in 0..10 {
+            plain.step();
+            profiled.step();
+        }
+
+        assert_eq!(None, plain.snapshot().diff(&profiled.snapshot()));
+        assert_eq!(10, profiled.profile().unwrap().steps());
+        assert!(plain.profile().is_none());
+
+        profiled.profiling(false);
+
+        assert!(profiled.profile().is_none());
+    }
+
+    #[test]
+    fn simulation_hall_of_fame() {
+        let size = Size::new(3, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(15, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 0), Plant::new(35, Genome::new(&[1.0, 0.5]).unwrap()));
+        let archive = ArchiveConfig { capacity: 1, criterion: crate::archive::Criterion::Longevity };
+        let mut simulation = Simulation::new(board(size, 0.0), population, SimulationConfig { archive: Some(archive), ..config() }).unwrap();
+        simulation.enable_history(10);
+        for _ in 0..2 {
+            simulation.step();
+        }
+
+        assert_eq!(vec![1], simulation.hall_of_fame().unwrap().entries().iter().map(|entry| entry.score).collect::<Vec<_>>());
+
+        for _ in 0..2 {
+            simulation.step();
+        }
+
+        // The plant which lived longer replaced the first one, going back in time brings the first one back
+        assert_eq!(Genome::new(&[1.0, 0.5]).unwrap(), simulation.hall_of_fame().unwrap().entries()[0].genome);
+        assert_eq!(3, simulation.hall_of_fame().unwrap().entries()[0].score);
+
+        simulation.rewind(2);
+
+        assert_eq!(1, simulation.hall_of_fame().unwrap().entries()[0].score);
+    }
+
+    #[test]
+    fn simulation_fitness() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        let fitness = FitnessConfig { window: 2 };
+        let mut simulation = Simulation::new(board(size, 1.0), population, SimulationConfig { fitness: Some(fitness), ..config() }).unwrap();
+        let founder = simulation.phylogeny().roots().next().unwrap().id;
+        simulation.step();
+        let births = simulation.fitness().unwrap().births();
+
+        assert!(births > 0);
+        assert_eq!(births, simulation.fitness().unwrap().offspring(founder));
+        assert_eq!(Some(founder), simulation.fitness().unwrap().fittest().map(|(id, _)| id));
+
+        // The births of the first step drop out of the window while the lineage tree keeps them
+        for _ in 0..2 {
+            simulation.step();
+        }
+
+        assert!(simulation.fitness().unwrap().offspring(founder) < simulation.phylogeny().descendants_since(founder, 0));
+    }
+
+    #[test]
+    fn simulation_rewind() {
+        let size = Size::new(4, 4);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.enable_history(10);
+        for _ in 0..5 {
+            simulation.step();
+        }
+        let middle = simulation.snapshot();
+        for _ in 0..5 {
+            simulation.step();
+        }
+        let end = simulation.snapshot();
+
+        assert_eq!(5, simulation.rewind(5));
+        assert_eq!(None, middle.diff(&simulation.snapshot()));
+        assert_eq!(5, simulation.history_len());
+
+        // Running again from the rewound state gives the same steps
+        for _ in 0..5 {
+            simulation.step();
+        }
+        assert_eq!(None, end.diff(&simulation.snapshot()));
+    }
+
+    #[test]
+    fn simulation_history_capacity() {
+        let size = Size::new(2, 2);
+        let mut simulation = Simulation::new(board(size, 0.5), Population::new(size), config()).unwrap();
+        simulation.step();
+
+        assert_eq!(0, simulation.rewind(1));
+
+        simulation.enable_history(3);
+        for _ in 0..5 {
+            simulation.step();
+        }
+        simulation.enable_history(2);
+
+        assert_eq!(2, simulation.history_capacity());
+        assert_eq!(2, simulation.rewind(5));
+        assert_eq!(4, simulation.tick());
+
+        simulation.disable_history();
+
+        assert_eq!(0, simulation.history_capacity());
+        assert_eq!(0, simulation.history_len());
+    }
+
+    #[test]
+    fn simulation_crop() {
+        let size = Size::new(3, 2);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 1), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.disturb(Disturbance::new(DisturbanceKind::Drought { resource: Resource::Light, duration: 3 }, Rect::new(2, 0, 1, 1)));
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.crop(1, 0, 2, 2);
+
+        assert_eq!(Size::new(2, 2), simulation.population().size());
+        assert_eq!(PlantId(1), simulation.population().get(Coord::new(1, 1)).unwrap().id());
+        assert_eq!(vec![SimEvent::PlantDied { tick: 0, id: PlantId(0), coord: Coord::new(0, 0) }], *events.lock().unwrap());
+        assert_eq!(Some(0), simulation.phylogeny().get(PlantId(0)).unwrap().death);
+        assert_eq!(4, simulation.water().values().len());
+        assert_eq!(0.0, simulation.light()[1]);
+
+        // The drought ends in the cell it moved to
+        for _ in 0..3 {
+            simulation.step();
+        }
+
+        assert_eq!(0.5, simulation.board().fields.light[1]);
+    }
+
+    #[test]
+    fn simulation_resize() {
+        let size = Size::new(2, 2);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.resize(Size::new(3, 1), &Fill { light: 1.0, water: 2.0, ..Default::default() });
+
+        assert_eq!(0, simulation.population().count());
+        assert_eq!(Some(0), simulation.phylogeny().get(PlantId(0)).unwrap().death);
+        assert_eq!(&[0.5, 0.5, 1.0], simulation.light());
+        assert_eq!(2.0, simulation.water().values()[2]);
+
+        simulation.step();
+
+        assert_eq!(1, simulation.tick());
+    }
+
+    #[test]
+    fn simulation_disturbance_fire_event() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        let fire = Disturbance::new(DisturbanceKind::Fire, Rect::new(1, 1, 5, 5));
+        simulation.schedule_disturbance(1, fire);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.step();
+        let events = events.lock().unwrap();
+        let clamped = Disturbance::new(DisturbanceKind::Fire, Rect::new(1, 1, 2, 2));
+
+        assert_eq!(SimEvent::PlantDied { tick: 1, id: PlantId(0), coord: Coord::new(2, 2) }, events[0]);
+        assert_eq!(SimEvent::Disturbed { tick: 1, disturbance: clamped, killed: 1 }, events[1]);
+        assert_eq!(&[DisturbanceRecord { tick: 1, disturbance: clamped, killed: 1 }], simulation.disturbance_log());
+        assert_eq!(Some(1), simulation.phylogeny().get(PlantId(0)).unwrap().death);
+    }
+
+    #[test]
+    fn simulation_disturbance_drought() {
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        let drought = Disturbance::new(DisturbanceKind::Drought { resource: Resource::Light, duration: 2 }, Rect::new(0, 0, 1, 1));
+        simulation.schedule_disturbance(2, drought);
+
+        // The plant gains 40 energy every step without the drought and loses 10 during it
+        simulation.step();
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(&[0.0, 0.5], simulation.light());
+        assert_eq!(20, simulation.population().get(Coord::new(0, 0)).unwrap().energy);
+
+        simulation.step();
+
+        assert_eq!(&[0.5, 0.5], simulation.light());
+        assert_eq!(60, simulation.population().get(Coord::new(0, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_disturbance_meteor() {
+        let size = Size::new(3, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        let meteor = Disturbance::new(DisturbanceKind::Meteor { terrain: Terrain::Rock }, Rect::new(0, 0, 2, 1));
+
+        assert_eq!(1, simulation.disturb(meteor));
+        assert_eq!(vec![Terrain::Rock, Terrain::Rock, Terrain::Open], simulation.board().fields.terrain);
+        assert_eq!(0, simulation.population().count());
+        assert_eq!(0, simulation.disturbance_log()[0].tick);
+    }
+
+    #[test]
+    fn simulation_disturbance_random() {
+        let size = Size::new(4, 4);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.add_random_disturbance(RandomDisturbance { kind: DisturbanceKind::Fire, chance: 1.0, w: 8, h: 8 });
+        simulation.step();
+
+        assert_eq!(0, simulation.population().count());
+        assert_eq!(Rect::new(0, 0, 4, 4), simulation.disturbance_log()[0].disturbance.region.clamp(size));
+    }
+
+    #[test]
+    fn simulation_introduce_crowded() {
+        let size = Size::new(2, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.enable_history(2);
+        let invader = Genome::new(&[1.0, 0.1]).unwrap();
+        simulation.introduce(&[invader.clone(), invader.clone(), invader.clone()], Placement::Random, 0);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.step();
+
+        // Only one of the genomes fits on the board
+        let plant = simulation.population().get(Coord::new(1, 0)).unwrap();
+        assert_eq!(&invader, &plant.genome);
+        assert_eq!(SimEvent::PlantBorn { tick: 1, id: plant.id(), coord: Coord::new(1, 0), parent: None, mate: None }, events.lock().unwrap()[0]);
+        assert!(simulation.energy_balance().unwrap().is_conserved());
+
+        // Going back brings the introduction back
+        simulation.rewind(1);
+        assert_eq!(1, simulation.population().count());
+        simulation.step();
+        assert_eq!(2, simulation.population().count());
+    }
+
+    #[test]
+    fn simulation_step_death() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(5, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.0), population, config()).unwrap();
+        simulation.step();
+
+        assert_eq!(0, simulation.population.count());
+    }
+
+    #[test]
+    fn simulation_step_reproduce() {
+        let size = Size::new(3, 3);
+        let genome = Genome::new(&[0.0, 0.5]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, genome.clone()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.step();
+
+        // The parent has 90 energy, pays 20 for the seed and gives half of the remaining 70 to the seed
+        assert_eq!(2, simulation.population.count());
+        let parent = simulation.population.get(Coord::new(1, 1)).unwrap();
+        assert_eq!(35, parent.energy);
+        let (_, seed) = simulation.population.iter().find(|(coord, _)| *coord != Coord::new(1, 1)).unwrap();
+        assert_eq!(35, seed.energy);
+        assert_eq!(genome, seed.genome);
+        assert_eq!(Some(parent.id()), seed.parent());
+    }
+
+    #[test]
+    fn simulation_step_gene_bounds() {
+        let size = Size::new(3, 3);
+        let mut bounds = GeneRegistry::default();
+        bounds.set_range("seed_energy", 0.0, 0.25).unwrap();
+        let mut config = config();
+        config.gene_bounds = Some(bounds);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.0, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // The parent keeps its genome but the seed is moved into the range
+        let (_, seed) = simulation.population.iter().find(|(coord, _)| *coord != Coord::new(1, 1)).unwrap();
+        assert_eq!(&[0.0, 0.25], seed.genome.genes());
+        assert_eq!(&[0.0, 0.5], simulation.population.get(Coord::new(1, 1)).unwrap().genome.genes());
+    }
+
+    #[test]
+    fn simulation_step_occupied() {
+        let size = Size::new(2, 1);
+        let genome = Genome::new(&[0.0, 0.5]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(50, genome.clone()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config()).unwrap();
+        simulation.step();
+
+        assert_eq!(Genome::new(&[1.0, 0.0]).unwrap(), simulation.population.get(Coord::new(0, 0)).unwrap().genome);
+        assert_eq!(2, simulation.population.count());
+    }
+
+    #[test]
+    fn simulation_step_sexual() {
+        let size = Size::new(3, 3);
+        let mut config = config();
+        config.reproduction.mode = ReproductionMode::Sexual;
+        config.reproduction.compatibility = 1.0;
+        config.reproduction.self_fertilize = false;
+        let genome1 = Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+        let genome2 = Genome::new(&[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]).unwrap();
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, genome1.clone()));
+        population.insert(Coord::new(0, 0), Plant::new(0, genome2.clone()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // Only the first plant can reproduce and it must be pollinated by the second
+        assert_eq!(3, simulation.population.count());
+        let (_, seed) = simulation.population.iter().find(|(_, plant)| plant.parent().is_some()).unwrap();
+        assert_eq!(Some(PlantId(0)), seed.parent());
+        assert_eq!(Some(PlantId(1)), seed.mate());
+        assert!(seed.genome.genes().iter().all(|&gene| gene == 0.0 || gene == 1.0));
+        assert_eq!(Some(PlantId(1)), simulation.phylogeny.get(seed.id()).unwrap().mate);
+    }
+
+    #[test]
+    fn simulation_step_pollination() {
+        let size = Size::new(3, 3);
+        let mut config = config();
+        config.reproduction.mode = ReproductionMode::Sexual;
+        config.reproduction.compatibility = 1.0;
+        config.reproduction.self_fertilize = false;
+        config.pollination = Some(PollinationConfig { cost: 4.0, ..Default::default() });
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.0; 9]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[0.0; 9]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // Flowering costs upkeep
+        assert_eq!(38, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(40, simulation.population.get(Coord::new(2, 2)).unwrap().energy);
+
+        // Only flowering plants release pollen so the other plant never fathers a seed
+        for _ in 0..5 {
+            simulation.step();
+        }
+        let seeds: Vec<&Plant> = simulation.population.iter().map(|(_, plant)| plant).filter(|plant| plant.parent() == Some(PlantId(0))).collect();
+        assert!(!seeds.is_empty());
+        assert!(seeds.iter().all(|seed| seed.mate().is_some() && seed.mate() != Some(PlantId(2))));
+    }
+
+    #[test]
+    fn simulation_step_allelopathy() {
+        let size = Size::new(3, 1);
+        let allelopathy = AllelopathyConfig { emission: 1.0, cost: 4.0, decay_rate: 0.5, inhibition: 1.0 };
+        let config = SimulationConfig { allelopathy: Some(allelopathy), max_threshold: 1000, ..config() };
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // Releasing toxin costs upkeep and the toxin reaches the neighbour after the step
+        assert_eq!(136, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(140, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+        assert_eq!(&[0.0, 1.0, 0.0], simulation.toxins().values());
+
+        // The poisoned neighbour collects half the light
+        simulation.step();
+
+        assert_eq!(172, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(155, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+        assert_eq!(&[0.0, 1.5, 0.0], simulation.toxins().values());
+    }
+
+    #[test]
+    fn simulation_step_mycorrhiza() {
+        let size = Size::new(2, 1);
+        let mycorrhiza = MycorrhizaConfig { reach: 1, share: 0.5, loss: 0.0 };
+        let config = SimulationConfig { mycorrhiza: Some(mycorrhiza), max_threshold: 1000, ..config() };
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(20, Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.1]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // The plants pay 75 and 3 into the network after collecting light and get 39 back each before paying upkeep
+        assert_eq!(104, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(96, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+        assert!(simulation.energy_balance().unwrap().is_conserved());
+    }
+
+    #[test]
+    fn simulation_step_spectrum() {
+        let size = Size::new(2, 1);
+        let mut board = board(size, 0.5);
+        board.fields = board.fields.with_spectrum(&[&[1.0, 0.0], &[0.0, 1.0]]).unwrap();
+        let config = SimulationConfig { max_threshold: 1000, ..config() };
+        let red = [0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.9, 0.1];
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&red).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&red).unwrap()));
+        let mut simulation = Simulation::new(board, population, config).unwrap();
+        simulation.step();
+
+        // The red specialist absorbs 1.8 of the red light and 0.2 of the blue light
+        assert_eq!(180, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(100, simulation.population.get(Coord::new(1, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_wind() {
+        let size = Size::new(7, 3);
+        let config = SimulationConfig { wind: Some(WindConfig::new(0.0, 2.0)), ..config() };
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(200, Genome::new(&[0.0, 1.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        assert_eq!(Some((2.0, 0.0)), simulation.wind().map(|wind| wind[0]));
+        simulation.step();
+
+        // A neighbouring cell blown 2 cells downwind
+        let seeds: Vec<Coord> = simulation.population.iter().filter(|(_, plant)| plant.parent() == Some(PlantId(0))).map(|(coord, _)| coord).collect();
+        assert_eq!(1, seeds.len());
+        assert!((2..=4).contains(&seeds[0].x));
+    }
+
+    #[test]
+    fn simulation_step_clutch() {
+        let size = Size::new(5, 5);
+        let mut config = config();
+        config.clutch = Some(ClutchConfig { max_seeds: 4, establishment: 0.0 });
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(200, Genome::new(&[0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // The energy of the seed is split between four seeds which can land in the same cell
+        let seeds: Vec<&Plant> = simulation.population.iter().map(|(_, plant)| plant).filter(|plant| plant.parent() == Some(PlantId(0))).collect();
+        assert_eq!(0, simulation.population.get(Coord::new(2, 2)).unwrap().energy);
+        assert!(!seeds.is_empty() && seeds.len() <= 4);
+        assert!(seeds.iter().all(|seed| seed.energy == 55));
+
+        // Seeds without energy never establish
+        let mut config = self::config();
+        config.clutch = Some(ClutchConfig { max_seeds: 4, establishment: 10.0 });
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 2), Plant::new(200, Genome::new(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        assert_eq!(1, simulation.population.count());
+    }
+
+    #[test]
+    fn simulation_audit_energy() {
+        let size = Size::new(12, 12);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let mut terrain = vec![Terrain::Open; size.len()];
+        terrain[..12].fill(Terrain::Rock);
+        let fields = Fields::new(size, &vec![0.8; size.len()]).unwrap().with_terrain(&terrain).unwrap();
+        let mut population = Population::new(size);
+        for index in 12..size.len() {
+            if rng.gen_bool(0.3) {
+                let genes: Vec<f32> = (0..20).map(|_| rng.gen()).collect();
+                population.insert(size.coord_of(index).unwrap(), Plant::new(rng.gen_range(0..200), Genome::new(&genes).unwrap()));
+            }
+        }
+        let config = SimulationConfig {
+            clutch: Some(ClutchConfig { max_seeds: 3, establishment: 5.0 }),
+            seed_bank: Some(SeedBankConfig { capacity: 1, lifetime: 3, dormancy: 1, min_light: 0.0 }),
+            mycorrhiza: Some(MycorrhizaConfig { reach: 1, share: 0.5, loss: 0.3 }),
+            aging: Some(AgingConfig { max_lifespan: 10, respiration: 3.0 }),
+            pathogen: Some(PathogenConfig { outbreak: 0.05, duration: 4, damage: 5, ..Default::default() }),
+            mutation: MutationConfig::new(0.1, 0.1),
+            ..config()
+        };
+        let mut simulation = Simulation::new(Board::new(Multipliers::new(100).unwrap(), fields), population, config).unwrap();
+        simulation.add_random_disturbance(RandomDisturbance { kind: DisturbanceKind::Fire, chance: 0.1, w: 3, h: 3 });
+        simulation.collect_emigrants(true);
+
+        assert_eq!(None, simulation.energy_audit());
+
+        simulation.audit_energy(Some(0));
+        let mut flows = (0, 0, 0);
+        for _ in 0..60 {
+            simulation.step();
+            let report = simulation.energy_audit().unwrap();
+            flows.0 += report.respiration();
+            flows.1 += report.decay();
+            flows.2 += report.exported();
+
+            assert_eq!(simulation.tick(), report.tick);
+            assert_eq!(8, report.phases.len());
+            assert_eq!(None, report.worst());
+        }
+
+        assert!(flows.0 > 0 && flows.1 > 0 && flows.2 > 0);
+        assert_eq!(Ok(()), simulation.check_invariants());
+
+        simulation.audit_energy(None);
+        simulation.step();
+
+        assert_eq!(None, simulation.energy_audit());
+    }
+
+    #[test]
+    fn simulation_step_sexual_incompatible() {
+        let size = Size::new(2, 1);
+        let mut config = config();
+        config.reproduction.mode = ReproductionMode::Sexual;
+        config.reproduction.compatibility = 0.5;
+        config.reproduction.self_fertilize = false;
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 1.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // There are no compatible mates so no energy is spent on seeds
+        assert_eq!(90, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_self_fertilize() {
+        let size = Size::new(2, 1);
+        let mut config = config();
+        config.reproduction.mode = ReproductionMode::Sexual;
+        config.reproduction.compatibility = 0.5;
+        config.reproduction.self_fertilize = true;
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[0.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        // The seed gets no energy so the parent only pays the seed cost
+        assert_eq!(70, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+    }
+
+    #[test]
+    fn simulation_step_deterministic() {
+        let size = Size::new(8, 8);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(4, 4), Plant::new(0, Genome::new(&[0.1, 0.5]).unwrap()));
+        let mut config = config();
+        config.mutation = MutationConfig::new(0.5, 0.1);
+        let mut simulation1 = Simulation::new(board(size, 0.5), population.clone(), config.clone()).unwrap();
+        let mut simulation2 = Simulation::new(board(size, 0.5), population, config).unwrap();
+
+        for _ in 0..20 {
+            simulation1.step();
+            simulation2.step();
+        }
+
+        assert_eq!(simulation1.population, simulation2.population);
+    }
+
+    #[test]
+    fn simulation_step_phylogeny() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.05), population, config()).unwrap();
+
+        assert_eq!(2, simulation.phylogeny.len());
+
+        // The plant without energy dies and the other produces a seed
+        simulation.step();
+
+        assert_eq!(Some(1), simulation.phylogeny.get(PlantId(1)).unwrap().death);
+        assert_eq!(Some(PlantId(0)), simulation.phylogeny.get(PlantId(2)).unwrap().parent);
+        assert_eq!(1, simulation.phylogeny.get(PlantId(2)).unwrap().birth);
+
+        simulation.prune_phylogeny();
+
+        assert_eq!(2, simulation.phylogeny.len());
+        assert_eq!(vec![PlantId(0)], simulation.phylogeny.ancestors(PlantId(2)));
+    }
+
+    #[test]
+    fn simulation_step_phylogeny_scheduled() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(50, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let config = SimulationConfig { schedule: Scheduler::new().every(Subsystem::Phylogeny, 1), ..config() };
+        let mut simulation = Simulation::new(board(size, 0.05), population, config).unwrap();
+        simulation.step();
+
+        // The dead plant without descendants is pruned in the step
+        assert_eq!(2, simulation.phylogeny.len());
+        assert!(simulation.phylogeny.get(PlantId(1)).is_none());
+        assert!(simulation.phylogeny.relatives(PlantId(1), 1).is_empty());
+    }
+
+    #[test]
+    fn simulation_mutation_rate() {
+        let size = Size::new(3, 3);
+        let mut config = config();
+        config.mutation.rate = 0.01;
+        let simulation = Simulation::new(board(size, 1.0), Population::new(size), config).unwrap();
+
+        assert_eq!(0.01, simulation.mutation_rate());
+        assert!(simulation.mutation_log().is_empty());
+    }
+
+    #[test]
+    fn simulation_update_config() {
+        let size = Size::new(1, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.25), population, config()).unwrap();
+        simulation.step();
+
+        assert_eq!(15, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+
+        let update = ConfigUpdate { upkeep: Some(20), light_multiplier: Some(200), ..Default::default() };
+        simulation.update_config(update).unwrap();
+        simulation.step();
+
+        assert_eq!(15 + 50 - 20, simulation.population.get(Coord::new(0, 0)).unwrap().energy);
+        assert_eq!(20, simulation.config.upkeep);
+        assert_eq!(&[ConfigChange { tick: 1, update }], simulation.config_log());
+    }
+
+    #[test]
+    fn simulation_update_config_event() {
+        let size = Size::new(2, 2);
+        let mut simulation = Simulation::new(board(size, 1.0), Population::new(size), config()).unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        let update = ConfigUpdate { sun: Some(Some(Sun::new(0.0, 0.5, 0.5))), water: Some(Some(WaterConfig::default())), ..Default::default() };
+        simulation.update_config(update).unwrap();
+
+        assert_eq!(vec![SimEvent::ConfigUpdated { tick: 0, update }], *events.lock().unwrap());
+        assert_eq!(Some(WaterConfig::default()), simulation.config.water);
+    }
+
+    #[test]
+    fn simulation_update_config_adaptive_mutation() {
+        let size = Size::new(3, 3);
+        let mut config = config();
+        config.adaptive_mutation = Some(AdaptiveMutationConfig { interval: 2, low_diversity: 0.01, high_diversity: 0.1, factor: 2.0, min_rate: 0.0, max_rate: 0.5 });
+        let mut simulation = Simulation::new(board(size, 1.0), Population::new(size), config).unwrap();
+        simulation.update_config(ConfigUpdate { mutation: Some(MutationConfig::new(0.75, 0.1)), ..Default::default() }).unwrap();
+
+        assert_eq!(0.5, simulation.mutation_rate());
+        assert_eq!(0.75, simulation.config.mutation.rate);
+    }
+
+    #[test]
+    fn simulation_update_config_invalid() {
+        let size = Size::new(2, 2);
+        let mut simulation = Simulation::new(board(size, 1.0), Population::new(size), config()).unwrap();
+        let update = ConfigUpdate { mutation: Some(MutationConfig::new(0.5, -0.1)), upkeep: Some(1), ..Default::default() };
+
+        assert_eq!(Err(ConfigError::Mutation(MutationConfigError::Strength { strength: -0.1 })), simulation.update_config(update));
+        assert_eq!(10, simulation.config.upkeep);
+        assert!(simulation.config_log().is_empty());
+    }
+
+    #[test]
+    fn simulation_new_invalid_config() {
+        let size = Size::new(2, 2);
+        let invalid = SimulationConfig { mutation: MutationConfig::new(f32::NAN, 0.1), ..config() };
+        let update = ConfigUpdate { mutation: Some(MutationConfig::new(0.1, f32::INFINITY)), ..Default::default() };
+        let scenario = SimulationConfig { scenario: Scenario::new().insert(5, ScenarioAction::Update(update)), ..config() };
+
+        assert!(matches!(Simulation::new(board(size, 1.0), Population::new(size), invalid), Err(SimulationCreateError::Config(ConfigError::Mutation(_)))));
+        assert!(matches!(Simulation::new(board(size, 1.0), Population::new(size), scenario), Err(SimulationCreateError::Config(_))));
+
+        let adaptive = AdaptiveMutationConfig { min_rate: 0.5, max_rate: 0.1, ..Default::default() };
+        let adaptive = SimulationConfig { adaptive_mutation: Some(adaptive), ..config() };
+        assert!(matches!(Simulation::new(board(size, 1.0), Population::new(size), adaptive), Err(SimulationCreateError::Config(ConfigError::Adaptive(_)))));
+
+        let costly = SimulationConfig { seed_cost: u32::MAX, ..config() };
+        assert_eq!(Err(SimulationCreateError::Config(ConfigError::Energy { name: "seed_cost", value: u32::MAX })), Simulation::new(board(size, 1.0), Population::new(size), costly).map(|_| ()));
+        assert_eq!(Err(ConfigError::Energy { name: "upkeep", value: u32::MAX }), ConfigUpdate { upkeep: Some(u32::MAX), ..Default::default() }.validate());
+    }
+
+    #[test]
+    fn simulation_step_adaptive_mutation() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(1, 1), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let mut config = config();
+        config.mutation.rate = 0.01;
+        config.adaptive_mutation = Some(AdaptiveMutationConfig { interval: 2, low_diversity: 0.01, high_diversity: 0.1, factor: 2.0, min_rate: 0.0, max_rate: 1.0 });
+        let mut simulation = Simulation::new(board(size, 0.5), population, config).unwrap();
+        simulation.step();
+
+        assert_eq!(0.01, simulation.mutation_rate());
+
+        // A single plant has no diversity so the rate is raised
+        simulation.step();
+
+        assert_eq!(0.02, simulation.mutation_rate());
+        assert_eq!(&[MutationAdjustment { tick: 2, diversity: 0.0, old_rate: 0.01, new_rate: 0.02 }], simulation.mutation_log());
+    }
+
+    #[test]
+    fn simulation_step_species() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[0.0, 1.0]).unwrap()));
+        let tracked = SimulationConfig { species: Some(SpeciesConfig { interval: 2, threshold: 0.1, ..Default::default() }), ..config() };
+        let mut simulation = Simulation::new(board(size, 0.5), population, tracked).unwrap();
+        simulation.step();
+        simulation.step();
+        let species = simulation.species().unwrap();
+
+        assert_eq!(2, species.living_count());
+        assert_eq!(vec![0, 2], species.iter().next().unwrap().curve.iter().map(|(tick, _)| *tick).collect::<Vec<_>>());
+        assert_eq!(Some(SpeciesId(1)), species.species_of(PlantId(1)));
+        assert!(Simulation::new(board(size, 0.5), Population::new(size), config()).unwrap().species().is_none());
+    }
+
+    #[test]
+    fn simulation_step_species_events() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[0.0, 1.0]).unwrap()));
+        let tracked = SimulationConfig { species: Some(SpeciesConfig { interval: 2, threshold: 0.1, ..Default::default() }), ..config() };
+        let mut simulation = Simulation::new(board(size, 0.0), population, tracked).unwrap();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.step();
+        simulation.step();
+        let events = events.lock().unwrap();
+        let extinct: Vec<SimEvent> = events.iter().filter(|event| matches!(event, SimEvent::SpeciesExtinct { .. })).copied().collect();
+
+        // The founding species were clustered before the hook was added and both die without light
+        assert_eq!(vec![SimEvent::SpeciesExtinct { tick: 2, species: SpeciesId(0), peak: 1 }, SimEvent::SpeciesExtinct { tick: 2, species: SpeciesId(1), peak: 1 }], extinct);
+        assert_eq!(4, simulation.species().unwrap().events().len());
+        assert_eq!(Rect::new(2, 2, 1, 1), simulation.species().unwrap().get(SpeciesId(1)).unwrap().origin);
+    }
+
+    #[test]
+    fn simulation_step_alerts() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[0.0, 1.0]).unwrap()));
+        let mut simulation = Simulation::new(board(size, 0.0), population, config()).unwrap();
+        let rules = vec![AlertRule::PopulationBelow(2), AlertRule::DiversityBelow(0.0)];
+        simulation.watch_alerts(Some(AlertConfig { rules, checkpoints: Some(CheckpointConfig::default()) }));
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = events.clone();
+        simulation.on_event(move |event| seen.lock().unwrap().push(*event));
+        simulation.step();
+        simulation.step();
+        let events = events.lock().unwrap();
+        let alerts: Vec<SimEvent> = events.iter().filter(|event| matches!(event, SimEvent::Alert { .. })).copied().collect();
+        let monitor = simulation.alerts().unwrap();
+
+        // Both plants die without light and the board stays empty
+        assert_eq!(vec![SimEvent::Alert { alert: Alert { tick: 1, rule: AlertRule::PopulationBelow(2), value: 0.0 } }], alerts);
+        assert_eq!(1, monitor.checkpoints().unwrap().len());
+        assert_eq!(Some(0), monitor.state(&monitor.alerts()[0]).map(|state| state.cells.iter().flatten().count()));
+    }
+
+    #[test]
+    fn find_mates_compatibility() {
+        let size = Size::new(3, 3);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 2), Plant::new(0, Genome::new(&[0.1, 0.1]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 1.0]).unwrap()));
+        let config = ReproductionConfig { compatibility: 0.2, ..Default::default() };
+        let mates = find_mates(&population, &config, Coord::new(1, 1), &Genome::new(&[0.0, 0.0]).unwrap());
+
+        assert_eq!(vec![0, 8], mates);
+    }
+
+    #[test]
+    fn find_mates_range() {
+        let size = Size::new(5, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.insert(Coord::new(3, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+        let config = ReproductionConfig { pollen_range: 2, ..Default::default() };
+        let mates = find_mates(&population, &config, Coord::new(2, 0), &Genome::new(&[0.0, 0.0]).unwrap());
+
+        assert_eq!(vec![0, 3], mates);
+    }
+
+    #[test]
+    fn find_mates_sorted_by_id() {
+        let size = Size::new(3, 1);
+        let mut population = Population::new(size);
+        population.insert(Coord::new(2, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+        let mates = find_mates(&population, &ReproductionConfig::default(), Coord::new(1, 0), &Genome::new(&[0.0, 0.0]).unwrap());
+
+        assert_eq!(vec![2, 0], mates);
+    }
+
+    #[test]
+    fn seed_wins_energy() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let strong = Plant::seed(PlantId(5), None, 20, genome.clone());
+        let weak = Plant::seed(PlantId(1), None, 10, genome.clone());
+
+        assert!(seed_wins(&strong, &weak));
+        assert!(!seed_wins(&weak, &strong));
+    }
+
+    #[test]
+    fn seed_wins_tie() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let first = Plant::seed(PlantId(1), None, 10, genome.clone());
+        let second = Plant::seed(PlantId(2), None, 10, genome.clone());
+
+        assert!(seed_wins(&first, &second));
+        assert!(!seed_wins(&second, &first));
+        assert!(!seed_wins(&first, &first));
+    }
+
+    #[test]
+    fn pick_winners_cells() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let seeds = vec![
+            (3, Plant::seed(PlantId(4), None, 10, genome.clone())),
+            (1, Plant::seed(PlantId(2), None, 10, genome.clone())),
+            (3, Plant::seed(PlantId(0), None, 30, genome.clone())),
+            (1, Plant::seed(PlantId(1), None, 10, genome.clone())),
+        ];
+        let (winners, losers) = pick_winners(seeds, Competition::HighestEnergyWins, &mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(vec![1, 3], winners.keys().copied().collect::<Vec<_>>());
+        assert_eq!(Some(PlantId(1)), winners[&1].parent());
+        assert_eq!(Some(PlantId(0)), winners[&3].parent());
+        assert_eq!(vec![(1, Some(PlantId(2))), (3, Some(PlantId(4)))], losers.iter().map(|(target, seed)| (*target, seed.parent())).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn pick_winners_first() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let seeds = vec![
+            (3, Plant::seed(PlantId(4), None, 50, genome.clone())),
+            (3, Plant::seed(PlantId(2), None, 10, genome.clone())),
+            (3, Plant::seed(PlantId(3), None, 30, genome.clone())),
+        ];
+        let (winners, _) = pick_winners(seeds, Competition::FirstWins, &mut ChaCha8Rng::seed_from_u64(0));
+
+        assert_eq!(Some(PlantId(2)), winners[&3].parent());
+    }
+
+    #[test]
+    fn pick_winners_lottery() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut wins = [0; 2];
+
+        for _ in 0..1000 {
+            let seeds = vec![
+                (0, Plant::seed(PlantId(0), None, 10, genome.clone())),
+                (0, Plant::seed(PlantId(1), None, 30, genome.clone())),
+                (1, Plant::seed(PlantId(2), None, 0, genome.clone())),
+            ];
+            let (winners, _) = pick_winners(seeds, Competition::Lottery, &mut rng);
+            wins[winners[&0].parent().unwrap().0 as usize] += 1;
+            assert_eq!(Some(PlantId(2)), winners[&1].parent());
+        }
+
+        // The seed with three times the energy wins about three times as often
+        assert!((200..300).contains(&wins[0]), "{:?}", wins);
+    }
+
+    #[test]
+    fn draw_lottery_no_energy() {
+        let genome = Genome::new(&[0.0, 0.0]).unwrap();
+        let seeds = vec![Plant::seed(PlantId(0), None, 0, genome.clone()), Plant::seed(PlantId(1), None, 0, genome.clone())];
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let draws: Vec<usize> = (0..100).map(|_| draw_lottery(&seeds, &mut rng)).collect();
+
+        assert!(draws.contains(&0) && draws.contains(&1));
+    }
+
+    #[test]
+    fn offset_bounds() {
+        let size = Size::new(3, 3);
+
+        assert_eq!(Some(4), offset(size, Coord::new(0, 0), 1, 1));
+        assert_eq!(None, offset(size, Coord::new(0, 0), -1, 0));
+        assert_eq!(None, offset(size, Coord::new(2, 2), 0, 1));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::board::{Coord, Size};
+use crate::population::{Plant, PlantId};
+use crate::simulation::Simulation;
+use crate::tuning::Parallelism;
+
+/// A copy of the full state of a simulation at a single tick, used to find where two runs diverge
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateSnapshot {
+    /// The tick the snapshot was taken at
+    pub tick: u64,
+    /// The size of the board
+    pub size: Size,
+    /// The plant in every cell
+    pub cells: Vec<Option<Plant>>,
+    /// The water in every cell
+    pub water: Vec<f32>,
+    /// The nutrients in every cell
+    pub nutrients: Vec<f32>,
+    /// The position in the stream of the random number generator
+    pub rng_position: u128,
+    /// How the work on the cells was split over threads, this does not change the outcome of the steps so it is not compared by diff
+    pub parallelism: Parallelism,
+}
+
+/// The first difference found between two snapshots
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateDiff {
+    /// The snapshots were taken at different ticks
+    Tick {
+        left: u64,
+        right: u64,
+    },
+    /// The boards have different sizes
+    Size {
+        left: Size,
+        right: Size,
+    },
+    /// A cell holds a plant in only one snapshot or holds different plants
+    Plant {
+        coord: Coord,
+        left: Option<PlantId>,
+        right: Option<PlantId>,
+    },
+    /// The same plant has different parents or mates
+    Ancestry {
+        coord: Coord,
+        id: PlantId,
+    },
+    /// The same plant has different energy
+    Energy {
+        coord: Coord,
+        id: PlantId,
+        left: u32,
+        right: u32,
+    },
+    /// The same plant has a different gene or a different number of genes, a missing gene is NaN
+    Gene {
+        coord: Coord,
+        id: PlantId,
+        index: usize,
+        left: f32,
+        right: f32,
+    },
+    /// The water in a cell differs
+    Water {
+        coord: Coord,
+        left: f32,
+        right: f32,
+    },
+    /// The nutrients in a cell differ
+    Nutrients {
+        coord: Coord,
+        left: f32,
+        right: f32,
+    },
+    /// The random number generators have drawn a different amount of numbers
+    Rng {
+        left: u128,
+        right: u128,
+    },
+}
+
+impl Simulation {
+    /// Takes a snapshot of the full state of the simulation
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+    /// let size = board.fields.size;
+    /// let simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+    /// 
+    /// assert_eq!(0, simulation.snapshot().tick);
+    /// ```
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            tick: self.tick(),
+            size: self.board().fields.size,
+            cells: self.population().cells().map(|cell| cell.cloned()).collect(),
+            water: self.water().values().to_vec(),
+            nutrients: self.nutrients().values().to_vec(),
+            rng_position: self.rng_position(),
+            parallelism: self.parallelism(),
+        }
+    }
+}
+
+impl StateSnapshot {
+    /// Finds the first difference to another snapshot, returns None if they are identical.
+    /// Floats are compared bit by bit so even the smallest rounding difference is found.
+    /// Cells are compared in index order and the plant in a cell is compared before the water and the nutrients
+    /// 
+    /// # Parameters
+    /// 
+    /// other: The snapshot to compare with
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::{simulation::{Simulation, SimulationConfig}, snapshot::StateDiff};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let before = simulation.snapshot();
+    /// simulation.step();
+    /// let mut after = simulation.snapshot();
+    /// after.tick = 0;
+    /// 
+    /// assert!(matches!(before.diff(&after), Some(StateDiff::Energy { left: 50, .. })));
+    /// assert_eq!(None, before.diff(&before));
+    /// ```
+    pub fn diff(&self, other: &StateSnapshot) -> Option<StateDiff> {
+        if self.tick != other.tick {
+            return Some(StateDiff::Tick { left: self.tick, right: other.tick });
+        }
+
+        if self.size != other.size {
+            return Some(StateDiff::Size { left: self.size, right: other.size });
+        }
+
+        for (index, (left, right)) in self.cells.iter().zip(other.cells.iter()).enumerate() {
+            let coord = self.size.coord_of(index).unwrap();
+
+            if let Some(diff) = diff_plants(coord, left.as_ref(), right.as_ref()) {
+                return Some(diff);
+            }
+
+            let (left, right) = (self.water[index], other.water[index]);
+            if left.to_bits() != right.to_bits() {
+                return Some(StateDiff::Water { coord, left, right });
+            }
+
+            let (left, right) = (self.nutrients[index], other.nutrients[index]);
+            if left.to_bits() != right.to_bits() {
+                return Some(StateDiff::Nutrients { coord, left, right });
+            }
+        }
+
+        if self.rng_position != other.rng_position {
+            return Some(StateDiff::Rng { left: self.rng_position, right: other.rng_position });
+        }
+
+        None
+    }
+}
+
+/// Finds the first difference between two series of snapshots of two runs taken at the same ticks,
+/// returns None if all pairs of snapshots are identical
+/// 
+/// # Parameters
+/// 
+/// left: The snapshots of the first run
+/// right: The snapshots of the second run
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::BoardBuilder, population::Population, simulation::{Simulation, SimulationConfig}, snapshot};
+/// 
+/// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).build().unwrap();
+/// let size = board.fields.size;
+/// let mut simulation = Simulation::new(board, Population::new(size), SimulationConfig::default()).unwrap();
+/// let mut run = Vec::new();
+/// for _ in 0..3 {
+///     run.push(simulation.snapshot());
+///     simulation.step();
+/// }
+/// 
+/// assert_eq!(None, snapshot::first_divergence(&run, &run.clone()));
+/// ```
+pub fn first_divergence(left: &[StateSnapshot], right: &[StateSnapshot]) -> Option<StateDiff> {
+    left.iter()
+        .zip(right.iter())
+        .find_map(|(left, right)| left.diff(right))
+}
+
+/// Finds the first difference between the plants in a cell
+fn diff_plants(coord: Coord, left: Option<&Plant>, right: Option<&Plant>) -> Option<StateDiff> {
+    let (left, right) = match (left, right) {
+        (None, None) => return None,
+        (Some(left), Some(right)) if left.id() == right.id() => (left, right),
+        _ => return Some(StateDiff::Plant { coord, left: left.map(Plant::id), right: right.map(Plant::id) }),
+    };
+
+    let id = left.id();
+
+    if left.parent() != right.parent() || left.mate() != right.mate() {
+        return Some(StateDiff::Ancestry { coord, id });
+    }
+
+    if left.energy != right.energy {
+        return Some(StateDiff::Energy { coord, id, left: left.energy, right: right.energy });
+    }
+
+    let (left, right) = (left.genome.genes(), right.genome.genes());
+    for index in 0..left.len().max(right.len()) {
+        let left = left.get(index).copied().unwrap_or(f32::NAN);
+        let right = right.get(index).copied().unwrap_or(f32::NAN);
+
+        if left.to_bits() != right.to_bits() {
+            return Some(StateDiff::Gene { coord, id, index, left, right });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardBuilder;
+    use crate::genome::Genome;
+    use crate::population::Population;
+    use crate::simulation::SimulationConfig;
+
+    fn simulation(seed: u64) -> Simulation {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        let config = SimulationConfig { seed, ..Default::default() };
+
+        Simulation::new(board, population, config).unwrap()
+    }
+
+    fn run(seed: u64, ticks: usize) -> Vec<StateSnapshot> {
+        let mut simulation = simulation(seed);
+        let mut snapshots = Vec::new();
+        for _ in 0..ticks {
+            snapshots.push(simulation.snapshot());
+            simulation.step();
+        }
+
+        snapshots
+    }
+
+    #[test]
+    fn simulation_snapshot() {
+        let snapshot = simulation(0).snapshot();
+
+        assert_eq!(0, snapshot.tick);
+        assert_eq!(Size::new(4, 4), snapshot.size);
+        assert_eq!(1, snapshot.cells.iter().flatten().count());
+        assert_eq!(vec![0.0; 16], snapshot.water);
+        assert_eq!(vec![0.0; 16], snapshot.nutrients);
+        assert_eq!(0, snapshot.rng_position);
+    }
+
+    #[test]
+    fn state_snapshot_diff_same() {
+        assert_eq!(None, first_divergence(&run(3, 10), &run(3, 10)));
+    }
+
+    #[test]
+    fn state_snapshot_diff_seed() {
+        assert!(first_divergence(&run(3, 10), &run(4, 10)).is_some());
+    }
+
+    #[test]
+    fn state_snapshot_diff_tick() {
+        let snapshots = run(0, 2);
+
+        assert_eq!(Some(StateDiff::Tick { left: 0, right: 1 }), snapshots[0].diff(&snapshots[1]));
+    }
+
+    #[test]
+    fn state_snapshot_diff_gene() {
+        let left = simulation(0).snapshot();
+        let mut right = left.clone();
+        right.cells[5].as_mut().unwrap().genome = Genome::new(&[0.0, 0.5 + f32::EPSILON]).unwrap();
+        let id = left.cells[5].as_ref().unwrap().id();
+
+        assert_eq!(Some(StateDiff::Gene { coord: Coord::new(1, 1), id, index: 1, left: 0.5, right: 0.5 + f32::EPSILON }), left.diff(&right));
+    }
+
+    #[test]
+    fn state_snapshot_diff_gene_count() {
+        let left = simulation(0).snapshot();
+        let mut right = left.clone();
+        right.cells[5].as_mut().unwrap().genome = Genome::new(&[0.0, 0.5, 1.0]).unwrap();
+
+        assert!(matches!(left.diff(&right), Some(StateDiff::Gene { index: 2, right: 1.0, .. })));
+    }
+
+    #[test]
+    fn state_snapshot_diff_plant() {
+        let left = simulation(0).snapshot();
+        let mut right = left.clone();
+        right.cells[5] = None;
+        let id = left.cells[5].as_ref().unwrap().id();
+
+        assert_eq!(Some(StateDiff::Plant { coord: Coord::new(1, 1), left: Some(id), right: None }), left.diff(&right));
+    }
+
+    #[test]
+    fn state_snapshot_diff_water() {
+        let left = simulation(0).snapshot();
+        let mut right = left.clone();
+        right.water[2] = -0.0;
+
+        assert_eq!(Some(StateDiff::Water { coord: Coord::new(2, 0), left: 0.0, right: -0.0 }), left.diff(&right));
+    }
+
+    #[test]
+    fn state_snapshot_diff_nutrients() {
+        let left = simulation(0).snapshot();
+        let mut right = left.clone();
+        right.nutrients[4] = 0.5;
+
+        assert_eq!(Some(StateDiff::Nutrients { coord: Coord::new(0, 1), left: 0.0, right: 0.5 }), left.diff(&right));
+    }
+
+    #[test]
+    fn state_snapshot_diff_rng() {
+        let left = simulation(0).snapshot();
+        let right = StateSnapshot { rng_position: 8, ..left.clone() };
+
+        assert_eq!(Some(StateDiff::Rng { left: 0, right: 8 }), left.diff(&right));
+    }
+}
@@ -0,0 +1,277 @@
+use crate::board::{Coord, Size};
+use crate::memory::{vec_bytes, HeapSize};
+
+/// The default width and height in cells of the buckets of a spatial index
+pub const BUCKET_SIZE: usize = 8;
+
+/// An index of the occupied cells of a board for finding the plants near a position without looking at every cell.
+/// The board is split into square buckets and every bucket keeps a list of the occupied cells inside it,
+/// so a query only visits the buckets it overlaps
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpatialIndex {
+    /// The size of the board
+    size: Size,
+    /// The width and height in cells of a bucket
+    bucket_size: usize,
+    /// The number of buckets in the x and y direction
+    buckets_size: Size,
+    /// The indices of the occupied cells in every bucket in no particular order
+    buckets: Vec<Vec<usize>>,
+}
+
+impl SpatialIndex {
+    /// Creates a new index without any occupied cells
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// bucket_size: The width and height in cells of a bucket, a size of 0 is treated as 1
+    pub fn new(size: Size, bucket_size: usize) -> Self {
+        let bucket_size = bucket_size.max(1);
+        let (w, h) = size.size();
+        let buckets_size = Size::new(w.div_ceil(bucket_size), h.div_ceil(bucket_size));
+
+        Self { size, bucket_size, buckets_size, buckets: vec![Vec::new(); buckets_size.len()] }
+    }
+
+    /// Returns the size of the board
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Returns the number of occupied cells
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    /// Returns true if no cells are occupied
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(Vec::is_empty)
+    }
+
+    /// Finds all occupied cells at most a number of cells away in both directions, not including the cell itself.
+    /// The cells are returned in the order of their indices
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position to search around
+    /// radius: The largest distance in cells in each direction
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(20, 20));
+    /// for coord in [Coord::new(3, 3), Coord::new(5, 4), Coord::new(9, 3), Coord::new(4, 1)] {
+    ///     population.insert(coord, Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// }
+    /// 
+    /// assert_eq!(vec![Coord::new(4, 1), Coord::new(5, 4)], population.spatial().neighbors_within(Coord::new(3, 3), 2));
+    /// ```
+    pub fn neighbors_within(&self, coord: Coord, radius: usize) -> Vec<Coord> {
+        let (w, h) = self.size.size();
+        if w == 0 || h == 0 {
+            return Vec::new();
+        }
+
+        let (x0, y0) = (coord.x.saturating_sub(radius), coord.y.saturating_sub(radius));
+        let (x1, y1) = ((coord.x + radius).min(w - 1), (coord.y + radius).min(h - 1));
+        if x0 > x1 || y0 > y1 {
+            return Vec::new();
+        }
+
+        let mut found: Vec<usize> = Vec::new();
+        for by in y0 / self.bucket_size..=y1 / self.bucket_size {
+            for bx in x0 / self.bucket_size..=x1 / self.bucket_size {
+                found.extend(self.buckets[bx + by * self.buckets_size.size().0].iter().copied().filter(|&index| {
+                    let cell = self.size.coord_of(index).unwrap();
+
+                    cell != coord && (x0..=x1).contains(&cell.x) && (y0..=y1).contains(&cell.y)
+                }));
+            }
+        }
+        found.sort_unstable();
+
+        found.into_iter().map(|index| self.size.coord_of(index).unwrap()).collect()
+    }
+
+    /// Finds the occupied cells closest to a position by straight line distance, not including the position itself.
+    /// The cells are returned closest first, cells at the same distance in the order of their indices
+    /// 
+    /// # Parameters
+    /// 
+    /// coord: The position to search around
+    /// k: The largest number of cells to find
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, genome::Genome, population::{Plant, Population}};
+    /// 
+    /// let mut population = Population::new(Size::new(40, 40));
+    /// for coord in [Coord::new(3, 3), Coord::new(30, 30), Coord::new(12, 3), Coord::new(3, 5)] {
+    ///     population.insert(coord, Plant::new(10, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// }
+    /// 
+    /// assert_eq!(vec![Coord::new(3, 5), Coord::new(12, 3)], population.spatial().nearest(Coord::new(3, 3), 2));
+    /// ```
+    pub fn nearest(&self, coord: Coord, k: usize) -> Vec<Coord> {
+        let (columns, rows) = self.buckets_size.size();
+        if k == 0 || columns == 0 || rows == 0 {
+            return Vec::new();
+        }
+
+        let distance = |index: usize| {
+            let cell = self.size.coord_of(index).unwrap();
+            let (dx, dy) = (cell.x.abs_diff(coord.x), cell.y.abs_diff(coord.y));
+
+            dx * dx + dy * dy
+        };
+
+        // Search rings of buckets around the bucket of the position until no unvisited bucket can hold a closer cell
+        let (cx, cy) = ((coord.x / self.bucket_size).min(columns - 1), (coord.y / self.bucket_size).min(rows - 1));
+        let mut candidates: Vec<(usize, usize)> = Vec::new();
+        for ring in 0..columns.max(rows) {
+            for by in cy.saturating_sub(ring)..=(cy + ring).min(rows - 1) {
+                for bx in cx.saturating_sub(ring)..=(cx + ring).min(columns - 1) {
+                    if bx.abs_diff(cx).max(by.abs_diff(cy)) != ring {
+                        continue;
+                    }
+
+                    candidates.extend(self.buckets[bx + by * columns].iter()
+                        .filter(|&&index| self.size.coord_of(index).unwrap() != coord)
+                        .map(|&index| (distance(index), index)));
+                }
+            }
+
+            // Every cell in the next ring is at least this many cells away along one of the axes, so none of them is closer
+            // or tied with the cells found once they are all closer than that
+            let reach = ring * self.bucket_size + 1;
+            if candidates.len() >= k {
+                candidates.sort_unstable();
+                if candidates[k - 1].0 < reach * reach {
+                    break;
+                }
+            }
+        }
+        candidates.sort_unstable();
+
+        candidates.into_iter().take(k).map(|(_, index)| self.size.coord_of(index).unwrap()).collect()
+    }
+
+    /// Marks a cell as occupied
+    pub(crate) fn insert(&mut self, index: usize) {
+        let bucket = self.bucket(index);
+        self.buckets[bucket].push(index);
+    }
+
+    /// Marks a cell as empty
+    pub(crate) fn remove(&mut self, index: usize) {
+        let bucket = self.bucket(index);
+        if let Some(position) = self.buckets[bucket].iter().position(|&cell| cell == index) {
+            self.buckets[bucket].swap_remove(position);
+        }
+    }
+
+    /// Finds the bucket holding a cell
+    fn bucket(&self, index: usize) -> usize {
+        let coord = self.size.coord_of(index).unwrap();
+
+        coord.x / self.bucket_size + coord.y / self.bucket_size * self.buckets_size.size().0
+    }
+}
+
+impl HeapSize for SpatialIndex {
+    fn heap_bytes(&self) -> usize {
+        vec_bytes(&self.buckets) + self.buckets.iter().map(vec_bytes).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(size: Size, cells: &[(usize, usize)]) -> SpatialIndex {
+        let mut index = SpatialIndex::new(size, 3);
+        for &(x, y) in cells {
+            index.insert(size.index_of(Coord::new(x, y)).unwrap());
+        }
+
+        index
+    }
+
+    #[test]
+    fn spatial_index_remove() {
+        let size = Size::new(7, 7);
+        let mut spatial = index(size, &[(0, 0), (1, 1), (6, 6)]);
+        spatial.remove(size.index_of(Coord::new(1, 1)).unwrap());
+        spatial.remove(size.index_of(Coord::new(2, 2)).unwrap());
+
+        assert_eq!(2, spatial.len());
+        assert_eq!(vec![Coord::new(0, 0)], spatial.neighbors_within(Coord::new(2, 2), 2));
+    }
+
+    #[test]
+    fn spatial_index_neighbors_within_brute_force() {
+        let size = Size::new(10, 8);
+        let cells: Vec<(usize, usize)> = (0..size.len()).filter(|index| index % 7 == 3 || index % 5 == 0).map(|index| (index % 10, index / 10)).collect();
+        let spatial = index(size, &cells);
+
+        for center in size.coords() {
+            for radius in 0..4 {
+                let mut expected: Vec<Coord> = cells.iter()
+                    .map(|&(x, y)| Coord::new(x, y))
+                    .filter(|&cell| cell != center && cell.x.abs_diff(center.x) <= radius && cell.y.abs_diff(center.y) <= radius)
+                    .collect();
+                expected.sort_by_key(|&cell| size.index_of(cell));
+
+                assert_eq!(expected, spatial.neighbors_within(center, radius));
+            }
+        }
+    }
+
+    #[test]
+    fn spatial_index_nearest_brute_force() {
+        let size = Size::new(13, 11);
+        let cells: Vec<(usize, usize)> = (0..size.len()).filter(|index| index % 11 == 4).map(|index| (index % 13, index / 13)).collect();
+        let spatial = index(size, &cells);
+
+        for center in size.coords() {
+            let mut expected: Vec<(usize, usize)> = cells.iter()
+                .map(|&(x, y)| (x.abs_diff(center.x).pow(2) + y.abs_diff(center.y).pow(2), x + y * 13))
+                .filter(|&(_, index)| index != size.index_of(center).unwrap())
+                .collect();
+            expected.sort();
+            let expected: Vec<Coord> = expected.into_iter().take(4).map(|(_, index)| size.coord_of(index).unwrap()).collect();
+
+            assert_eq!(expected, spatial.nearest(center, 4));
+        }
+    }
+
+    #[test]
+    fn spatial_index_nearest_tie_across_rings() {
+        // The cell below the position and the cell to its right are tied, with buckets of 3 the position is on the edge of
+        // its bucket so the cell below is in the same bucket and the cell to the right in the next, ties still go by index
+        let size = Size::new(12, 4);
+        for bucket_size in [1, 3] {
+            let mut spatial = SpatialIndex::new(size, bucket_size);
+            for coord in [Coord::new(2, 1), Coord::new(3, 0), Coord::new(9, 0)] {
+                spatial.insert(size.index_of(coord).unwrap());
+            }
+
+            assert_eq!(vec![Coord::new(3, 0)], spatial.nearest(Coord::new(2, 0), 1), "bucket size {}", bucket_size);
+            assert_eq!(vec![Coord::new(3, 0), Coord::new(2, 1)], spatial.nearest(Coord::new(2, 0), 2), "bucket size {}", bucket_size);
+            assert_eq!(vec![Coord::new(3, 0), Coord::new(2, 1), Coord::new(9, 0)], spatial.nearest(Coord::new(2, 0), 3), "bucket size {}", bucket_size);
+        }
+    }
+
+    #[test]
+    fn spatial_index_empty() {
+        let spatial = SpatialIndex::new(Size::new(0, 0), 4);
+
+        assert!(spatial.is_empty());
+        assert!(spatial.nearest(Coord::new(0, 0), 3).is_empty());
+        assert!(spatial.neighbors_within(Coord::new(0, 0), 3).is_empty());
+    }
+}
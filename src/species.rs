@@ -0,0 +1,454 @@
+use std::collections::BTreeMap;
+
+use crate::board::{Coord, Rect};
+use crate::distance::{GenomeDistance, Metric};
+use crate::genome::Genome;
+use crate::memory::{btree_map_bytes, vec_bytes, HeapSize};
+use crate::population::{PlantId, Population};
+
+/// The settings for clustering the living plants into species
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpeciesConfig {
+    /// The number of steps between every clustering
+    pub interval: u64,
+    /// The largest genetic distance between a plant and the representative of a species for the plant to belong to it
+    pub threshold: f32,
+    /// The distance used to compare genomes
+    pub metric: Metric,
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        Self {
+            interval: 100,
+            threshold: 0.1,
+            metric: Metric::default(),
+        }
+    }
+}
+
+/// The unique id of a species, ids are never reused
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpeciesId(pub u64);
+
+/// A cluster of genetically similar plants which is followed between clusterings
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    /// The id of the species
+    pub id: SpeciesId,
+    /// The genome new plants are compared with, this is the genome of the oldest member at the latest clustering
+    pub representative: Genome,
+    /// The tick of the clustering at which the species first appeared
+    pub first_seen: u64,
+    /// The tick of the clustering at which the species had no members left, None if it is still alive
+    pub extinct: Option<u64>,
+    /// The number of members at every clustering the species was part of as pairs of tick and count,
+    /// the curve of an extinct species ends with a count of 0
+    pub curve: Vec<(u64, usize)>,
+    /// The genome of the oldest member at the clustering at which the species first appeared
+    pub founder: Genome,
+    /// The smallest rectangle holding every member at the clustering at which the species first appeared
+    pub origin: Rect,
+    /// The largest number of members at any clustering
+    pub peak: usize,
+}
+
+/// The origin or extinction of a species, the tracker keeps every event of the run in order such that
+/// the history of the species can be studied without going through the curves
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpeciesEvent {
+    /// A species appeared for the first time
+    Originated {
+        /// The tick of the clustering at which the species appeared
+        tick: u64,
+        /// The id of the species
+        species: SpeciesId,
+        /// The genome of the oldest founding member
+        founder: Genome,
+        /// The smallest rectangle holding every founding member
+        origin: Rect,
+    },
+    /// The last member of a species was gone
+    Extinct {
+        /// The tick of the first clustering without any members
+        tick: u64,
+        /// The id of the species
+        species: SpeciesId,
+        /// The tick of the clustering at which the species appeared
+        first_seen: u64,
+        /// The largest number of members the species ever had
+        peak: usize,
+    },
+}
+
+impl SpeciesEvent {
+    /// Returns the tick of the clustering at which the event was found
+    pub fn tick(&self) -> u64 {
+        match self {
+            SpeciesEvent::Originated { tick, .. } | SpeciesEvent::Extinct { tick, .. } => *tick,
+        }
+    }
+
+    /// Returns the id of the species the event happened to
+    pub fn species(&self) -> SpeciesId {
+        match self {
+            SpeciesEvent::Originated { species, .. } | SpeciesEvent::Extinct { species, .. } => *species,
+        }
+    }
+}
+
+/// Clusters the living plants by genetic distance and keeps the ids of the species stable over time,
+/// a plant keeps the species of the closest known representative within the threshold
+/// and plants far from all known species found new ones
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeciesTracker {
+    /// The settings of the tracker
+    config: SpeciesConfig,
+    /// All species which have been found ordered by id
+    species: BTreeMap<SpeciesId, Species>,
+    /// The species of every living plant at the latest clustering
+    members: BTreeMap<PlantId, SpeciesId>,
+    /// The id given to the next new species
+    next_id: u64,
+    /// The origins and extinctions of all species in the order they were found
+    events: Vec<SpeciesEvent>,
+}
+
+impl SpeciesTracker {
+    /// Creates a new tracker without any species
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings of the tracker
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::species::{SpeciesConfig, SpeciesTracker};
+    /// 
+    /// let tracker = SpeciesTracker::new(SpeciesConfig::default());
+    /// 
+    /// assert_eq!(0, tracker.living_count());
+    /// ```
+    pub fn new(config: SpeciesConfig) -> Self {
+        Self { config, species: BTreeMap::new(), members: BTreeMap::new(), next_id: 0, events: Vec::new() }
+    }
+
+    /// Returns the settings of the tracker
+    pub fn config(&self) -> &SpeciesConfig {
+        &self.config
+    }
+
+    /// Gets a species, returns None if no species has the id
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the species
+    pub fn get(&self, id: SpeciesId) -> Option<&Species> {
+        self.species.get(&id)
+    }
+
+    /// Iterates over all species which have been found, including extinct ones, ordered by id
+    pub fn iter(&self) -> impl Iterator<Item = &Species> {
+        self.species.values()
+    }
+
+    /// Iterates over the species which were alive at the latest clustering ordered by id
+    pub fn living(&self) -> impl Iterator<Item = &Species> {
+        self.species.values().filter(|species| species.extinct.is_none())
+    }
+
+    /// Returns the number of species which were alive at the latest clustering
+    pub fn living_count(&self) -> usize {
+        self.living().count()
+    }
+
+    /// Finds the species a plant belonged to at the latest clustering, returns None if the plant was not alive then
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn species_of(&self, id: PlantId) -> Option<SpeciesId> {
+        self.members.get(&id).copied()
+    }
+
+    /// Returns the origins and extinctions of all species in the order they were found, the events of a clustering
+    /// list the origins before the extinctions and are ordered by species id
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::species::{SpeciesConfig, SpeciesEvent, SpeciesId, SpeciesTracker};
+    /// 
+    /// let mut population = Population::new(board::Size::new(3, 1));
+    /// population.insert(board::Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+    /// population.insert(board::Coord::new(2, 0), Plant::new(0, Genome::new(&[0.0, 0.05]).unwrap()));
+    /// let mut tracker = SpeciesTracker::new(SpeciesConfig::default());
+    /// tracker.cluster(0, &population);
+    /// tracker.cluster(100, &Population::new(board::Size::new(3, 1)));
+    /// 
+    /// assert!(matches!(tracker.events()[0], SpeciesEvent::Originated { tick: 0, origin: board::Rect { w: 3, h: 1, .. }, .. }));
+    /// assert_eq!(SpeciesEvent::Extinct { tick: 100, species: SpeciesId(0), first_seen: 0, peak: 2 }, tracker.events()[1]);
+    /// ```
+    pub fn events(&self) -> &[SpeciesEvent] {
+        &self.events
+    }
+
+    /// Clusters the population if it is time for a clustering, returns true if a clustering was made
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The current tick of the simulation
+    /// population: The living plants
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, species::{SpeciesConfig, SpeciesTracker}};
+    /// 
+    /// let mut population = Population::new(board::Size::new(2, 1));
+    /// population.insert(board::Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.0]).unwrap()));
+    /// population.insert(board::Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 1.0]).unwrap()));
+    /// let mut tracker = SpeciesTracker::new(SpeciesConfig { interval: 10, threshold: 0.1, ..Default::default() });
+    /// 
+    /// assert!(!tracker.update(5, &population));
+    /// assert!(tracker.update(10, &population));
+    /// assert_eq!(2, tracker.living_count());
+    /// ```
+    pub fn update(&mut self, tick: u64, population: &Population) -> bool {
+        if self.config.interval == 0 || !tick.is_multiple_of(self.config.interval) {
+            return false;
+        }
+
+        self.cluster(tick, population);
+
+        true
+    }
+
+    /// Clusters the population now and records the size of every species.
+    /// The plants are handled in order of id so the result does not depend on where they live
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The current tick of the simulation
+    /// population: The living plants
+    pub fn cluster(&mut self, tick: u64, population: &Population) {
+        let mut plants: Vec<_> = population.iter().collect();
+        plants.sort_by_key(|(_, plant)| plant.id());
+
+        let known: Vec<(SpeciesId, Genome)> = self.living()
+            .map(|species| (species.id, species.representative.clone()))
+            .collect();
+        let mut founded: Vec<(SpeciesId, Genome)> = Vec::new();
+        let mut counts: BTreeMap<SpeciesId, usize> = BTreeMap::new();
+        let mut representatives: BTreeMap<SpeciesId, Genome> = BTreeMap::new();
+        let mut origins: BTreeMap<SpeciesId, (Coord, Coord)> = BTreeMap::new();
+
+        self.members.clear();
+
+        for (coord, plant) in plants {
+            let id = match closest(&self.config, &known, &plant.genome)
+                .or_else(|| closest(&self.config, &founded, &plant.genome))
+            {
+                Some(id) => id,
+                None => {
+                    let id = SpeciesId(self.next_id);
+                    self.next_id += 1;
+                    founded.push((id, plant.genome.clone()));
+                    self.species.insert(id, Species {
+                        id,
+                        representative: plant.genome.clone(),
+                        first_seen: tick,
+                        extinct: None,
+                        curve: Vec::new(),
+                        founder: plant.genome.clone(),
+                        origin: Rect::new(coord.x, coord.y, 1, 1),
+                        peak: 0,
+                    });
+                    origins.insert(id, (coord, coord));
+                    id
+                }
+            };
+
+            // Stretch the region of origin of the species founded in this clustering over all their members
+            if let Some((low, high)) = origins.get_mut(&id) {
+                *low = Coord::new(low.x.min(coord.x), low.y.min(coord.y));
+                *high = Coord::new(high.x.max(coord.x), high.y.max(coord.y));
+            }
+
+            self.members.insert(plant.id(), id);
+            *counts.entry(id).or_insert(0) += 1;
+            // The plants are sorted by id so the first member is the oldest
+            representatives.entry(id).or_insert_with(|| plant.genome.clone());
+        }
+
+        for (id, (low, high)) in origins {
+            let species = self.species.get_mut(&id).expect("Every founded species is inserted");
+            species.origin = Rect::from_corners(low, high);
+            self.events.push(SpeciesEvent::Originated { tick, species: id, founder: species.founder.clone(), origin: species.origin });
+        }
+
+        // Record the new sizes and retire the species without members
+        for species in self.species.values_mut().filter(|species| species.extinct.is_none()) {
+            let count = counts.get(&species.id).copied().unwrap_or(0);
+            species.curve.push((tick, count));
+            species.peak = species.peak.max(count);
+
+            match representatives.remove(&species.id) {
+                Some(representative) => species.representative = representative,
+                None => {
+                    species.extinct = Some(tick);
+                    self.events.push(SpeciesEvent::Extinct { tick, species: species.id, first_seen: species.first_seen, peak: species.peak });
+                }
+            }
+        }
+    }
+}
+
+/// Finds the species with the representative closest to a genome within the threshold,
+/// ties are won by the species with the lowest id
+fn closest(config: &SpeciesConfig, candidates: &[(SpeciesId, Genome)], genome: &Genome) -> Option<SpeciesId> {
+    candidates.iter()
+        .map(|(id, representative)| (*id, config.metric.distance(representative, genome)))
+        .filter(|(_, distance)| *distance <= config.threshold)
+        .min_by(|(id1, distance1), (id2, distance2)| distance1.total_cmp(distance2).then(id1.cmp(id2)))
+        .map(|(id, _)| id)
+}
+
+impl HeapSize for SpeciesTracker {
+    fn heap_bytes(&self) -> usize {
+        btree_map_bytes(&self.species) + self.species.values().map(|species| vec_bytes(&species.curve)).sum::<usize>() + btree_map_bytes(&self.members) + vec_bytes(&self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Size};
+    use crate::distance::Hamming;
+    use crate::population::Plant;
+
+    fn population(genes: &[[f32; 2]]) -> Population {
+        let mut population = Population::new(Size::new(genes.len(), 1));
+        for (x, genes) in genes.iter().enumerate() {
+            population.place(x, Plant::new(0, Genome::new(genes).unwrap()));
+        }
+
+        population
+    }
+
+    fn tracker() -> SpeciesTracker {
+        SpeciesTracker::new(SpeciesConfig { interval: 10, threshold: 0.1, ..Default::default() })
+    }
+
+    #[test]
+    fn species_tracker_cluster() {
+        let population = population(&[[0.0, 0.0], [1.0, 1.0], [0.05, 0.05], [0.95, 1.0]]);
+        let mut tracker = tracker();
+        tracker.cluster(0, &population);
+
+        assert_eq!(2, tracker.living_count());
+        assert_eq!(vec![(0, 2)], tracker.get(SpeciesId(0)).unwrap().curve);
+        assert_eq!(vec![(0, 2)], tracker.get(SpeciesId(1)).unwrap().curve);
+    }
+
+    #[test]
+    fn species_tracker_stable_ids() {
+        let mut tracker = tracker();
+        tracker.cluster(0, &population(&[[0.0, 0.0], [1.0, 1.0]]));
+
+        // The order of the plants changes but they keep their species
+        tracker.cluster(10, &population(&[[1.0, 0.95], [0.05, 0.0], [0.0, 0.0]]));
+
+        assert_eq!(2, tracker.living_count());
+        assert_eq!(vec![(0, 1), (10, 1)], tracker.get(SpeciesId(1)).unwrap().curve);
+        assert_eq!(vec![(0, 1), (10, 2)], tracker.get(SpeciesId(0)).unwrap().curve);
+        assert!(tracker.get(SpeciesId(2)).is_none());
+    }
+
+    #[test]
+    fn species_tracker_extinct() {
+        let mut tracker = tracker();
+        tracker.cluster(0, &population(&[[0.0, 0.0], [1.0, 1.0]]));
+        tracker.cluster(10, &population(&[[0.0, 0.0], [0.5, 0.5]]));
+        let extinct = tracker.get(SpeciesId(1)).unwrap();
+
+        assert_eq!(Some(10), extinct.extinct);
+        assert_eq!(vec![(0, 1), (10, 0)], extinct.curve);
+        assert_eq!(vec![(10, 1)], tracker.get(SpeciesId(2)).unwrap().curve);
+        assert_eq!(2, tracker.living_count());
+        assert_eq!(3, tracker.iter().count());
+    }
+
+    #[test]
+    fn species_tracker_events() {
+        let mut tracker = tracker();
+        tracker.cluster(0, &population(&[[0.0, 0.0], [1.0, 1.0], [0.05, 0.0]]));
+        tracker.cluster(10, &population(&[[0.0, 0.0], [0.0, 0.05], [0.05, 0.0]]));
+        tracker.cluster(20, &population(&[[0.5, 0.5]]));
+        let events = tracker.events();
+
+        assert_eq!(5, events.len());
+        assert_eq!(SpeciesEvent::Originated { tick: 0, species: SpeciesId(0), founder: Genome::new(&[0.0, 0.0]).unwrap(), origin: Rect::new(0, 0, 3, 1) }, events[0]);
+        assert_eq!(SpeciesEvent::Originated { tick: 0, species: SpeciesId(1), founder: Genome::new(&[1.0, 1.0]).unwrap(), origin: Rect::new(1, 0, 1, 1) }, events[1]);
+        assert_eq!(SpeciesEvent::Extinct { tick: 10, species: SpeciesId(1), first_seen: 0, peak: 1 }, events[2]);
+        assert_eq!(SpeciesEvent::Originated { tick: 20, species: SpeciesId(2), founder: Genome::new(&[0.5, 0.5]).unwrap(), origin: Rect::new(0, 0, 1, 1) }, events[3]);
+        assert_eq!(SpeciesEvent::Extinct { tick: 20, species: SpeciesId(0), first_seen: 0, peak: 3 }, events[4]);
+        assert_eq!(vec![SpeciesId(0), SpeciesId(1), SpeciesId(1), SpeciesId(2), SpeciesId(0)], events.iter().map(SpeciesEvent::species).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn species_tracker_representative_drift() {
+        let mut tracker = tracker();
+        tracker.cluster(0, &population(&[[0.0, 0.0]]));
+        tracker.cluster(10, &population(&[[0.0625, 0.0625]]));
+        tracker.cluster(20, &population(&[[0.125, 0.125]]));
+
+        assert_eq!(1, tracker.iter().count());
+        assert_eq!(Genome::new(&[0.125, 0.125]).unwrap(), tracker.get(SpeciesId(0)).unwrap().representative);
+    }
+
+    #[test]
+    fn species_tracker_species_of() {
+        let population = population(&[[0.0, 0.0], [1.0, 1.0]]);
+        let mut tracker = tracker();
+        tracker.cluster(0, &population);
+        let id = population.get(Coord::new(1, 0)).unwrap().id();
+
+        assert_eq!(Some(SpeciesId(1)), tracker.species_of(id));
+        assert_eq!(None, tracker.species_of(PlantId(100)));
+    }
+
+    #[test]
+    fn species_tracker_update_interval() {
+        let population = population(&[[0.0, 0.0]]);
+        let mut tracker = SpeciesTracker::new(SpeciesConfig { interval: 0, ..Default::default() });
+
+        assert!(!tracker.update(0, &population));
+        assert_eq!(0, tracker.living_count());
+    }
+
+    #[test]
+    fn species_tracker_metric() {
+        let config = SpeciesConfig { interval: 10, threshold: 1.0, metric: Metric::Hamming(Hamming { tolerance: 0.0 }) };
+        let mut tracker = SpeciesTracker::new(config);
+        tracker.cluster(0, &population(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]));
+
+        // The second genome is a single gene from the first but the third is two genes away
+        assert_eq!(2, tracker.living_count());
+    }
+
+    #[test]
+    fn closest_tie() {
+        let genome = Genome::new(&[0.5, 0.5]).unwrap();
+        let candidates = vec![
+            (SpeciesId(3), Genome::new(&[0.25, 0.5]).unwrap()),
+            (SpeciesId(1), Genome::new(&[0.75, 0.5]).unwrap()),
+            (SpeciesId(2), Genome::new(&[0.9, 0.9]).unwrap()),
+        ];
+
+        assert_eq!(Some(SpeciesId(1)), closest(&SpeciesConfig { threshold: 0.2, ..Default::default() }, &candidates, &genome));
+        assert_eq!(None, closest(&SpeciesConfig { threshold: 0.01, ..Default::default() }, &candidates, &genome));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::board::Board;
+use crate::genome::{self, Genome};
+
+/// The largest number of light channels, such as red, green and blue light
+pub const CHANNELS: usize = 3;
+
+/// The light reaching a cell split into channels, each channel is the share of the light in it
+/// times the multiplier of the channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spectrum {
+    /// The weight of every channel, the channels past the number of channels of the board are 0
+    pub weights: [f32; CHANNELS],
+    /// The number of channels of the board
+    pub channels: usize,
+}
+
+impl Spectrum {
+    /// Finds the factor the light collected by a plant is multiplied by for how well its pigments match the light.
+    /// A plant has a fixed budget of pigment spread over the channels by its absorption genes, so a plant absorbing
+    /// every channel equally gets the weighted total of the channels and specialists gain in light rich in their channel
+    /// while losing elsewhere
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, spectrum::Spectrum};
+    /// 
+    /// let spectrum = Spectrum { weights: [0.75, 0.25, 0.0], channels: 2 };
+    /// let generalist = Genome::new(&[0.5, 0.5]).unwrap();
+    /// let red = Genome::new(&[0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]).unwrap();
+    /// 
+    /// assert_eq!(1.0, spectrum.light_factor(&generalist));
+    /// assert_eq!(1.5, spectrum.light_factor(&red));
+    /// ```
+    pub fn light_factor(&self, genome: &Genome) -> f32 {
+        let absorption = absorption(genome, self.channels);
+
+        self.weights.iter().zip(absorption).map(|(weight, absorption)| weight * absorption).sum()
+    }
+}
+
+/// Finds how much of every channel a plant absorbs, the absorption of the channels adds up to the number of channels
+/// so a plant absorbing every channel equally absorbs 1 of each. Plants without absorption genes or
+/// with every absorption gene at 0 absorb every channel equally
+/// 
+/// # Parameters
+/// 
+/// genome: The genome of the plant
+/// channels: The number of channels of the board
+pub fn absorption(genome: &Genome, channels: usize) -> [f32; CHANNELS] {
+    let channels = channels.min(CHANNELS);
+    let mut absorption = [0.0; CHANNELS];
+    for (channel, value) in absorption.iter_mut().enumerate().take(channels) {
+        *value = genome.get(genome::GENE_ABSORPTION + channel).unwrap_or(1.0);
+    }
+
+    let total: f32 = absorption.iter().sum();
+    for value in absorption.iter_mut().take(channels) {
+        *value = if total > 0.0 { *value * channels as f32 / total } else { 1.0 };
+    }
+
+    absorption
+}
+
+impl Board {
+    /// Finds the light of a cell split into channels, None if the board has a single channel
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the cell
+    pub fn spectrum(&self, index: usize) -> Option<Spectrum> {
+        let shares = &self.fields.spectrum;
+        if shares.is_empty() {
+            return None;
+        }
+
+        let mut weights = [0.0; CHANNELS];
+        for ((weight, share), multiplier) in weights.iter_mut().zip(shares).zip(self.multipliers.channels) {
+            *weight = share[index] * multiplier;
+        }
+
+        Some(Spectrum { weights, channels: shares.len() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Fields};
+
+    fn genome(absorption: &[f32]) -> Genome {
+        let mut genes = vec![0.5; genome::GENE_ABSORPTION];
+        genes.extend_from_slice(absorption);
+
+        Genome::new(&genes).unwrap()
+    }
+
+    #[test]
+    fn spectrum_absorption_budget() {
+        assert_eq!([1.0, 1.0, 0.0], absorption(&genome(&[]), 2));
+        assert_eq!([1.0, 1.0, 1.0], absorption(&genome(&[0.0, 0.0, 0.0]), 3));
+        assert_eq!([0.5, 1.5, 0.0], absorption(&genome(&[0.25, 0.75]), 2));
+        assert_eq!([0.0, 3.0, 0.0], absorption(&genome(&[0.0, 1.0, 0.0]), 3));
+    }
+
+    #[test]
+    fn spectrum_board() {
+        let mut board = BoardBuilder::new().size(2, 1).light_uniform(1.0).build().unwrap();
+        assert_eq!(None, board.spectrum(0));
+
+        board.fields = Fields::new(board.fields.size, &[1.0; 2]).unwrap().with_spectrum(&[&[0.25, 1.0], &[0.75, 0.0]]).unwrap();
+        board.multipliers = board.multipliers.with_channels([2.0, 1.0, 1.0]);
+
+        let spectrum = board.spectrum(0).unwrap();
+        assert_eq!(Spectrum { weights: [0.5, 0.75, 0.0], channels: 2 }, spectrum);
+        assert_eq!(1.25, spectrum.light_factor(&genome(&[])));
+        assert_eq!(2.0, board.spectrum(1).unwrap().light_factor(&genome(&[1.0, 1.0])));
+    }
+}
@@ -0,0 +1,432 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+
+use crate::board::{Board, Coord, Rect, Shape};
+use crate::genome::{self, Genome};
+use crate::population::{PlantId, Population};
+use crate::simulation::Simulation;
+
+/// Statistics of a region of the board
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegionStats {
+    /// The region the statistics are for or the bounds of it if it is not a rectangle, this is clamped to the board
+    pub rect: Rect,
+    /// The number of plants in the region
+    pub population: usize,
+    /// The mean energy of the plants in the region, 0 if there are no plants
+    pub mean_energy: f32,
+    /// The mean value of every gene of the plants in the region, empty if there are no plants
+    pub mean_genes: Vec<f32>,
+    /// The mean light in the region, 0 if the region is empty
+    pub mean_light: f32,
+    /// The mean genetic distance of the plants in the region to their mean genome, 0 if there are no plants
+    pub diversity: f32,
+}
+
+impl RegionStats {
+    /// Calculates the statistics of a region of the board
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board with the fields
+    /// population: The plants living on the board
+    /// rect: The region to calculate the statistics for, it is clamped to the board
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, stats::RegionStats};
+    /// 
+    /// let size = board::Size::new(2, 2);
+    /// let fields = board::Fields::new(size, &[0.0, 0.5, 0.5, 1.0]).unwrap();
+    /// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+    /// let mut population = Population::new(size);
+    /// population.insert(board::Coord::new(1, 0), Plant::new(100, Genome::new(&[0.5, 0.25]).unwrap()));
+    /// let stats = RegionStats::new(&board, &population, board::Rect::new(1, 0, 1, 2));
+    /// 
+    /// assert_eq!(1, stats.population);
+    /// assert_eq!(0.75, stats.mean_light);
+    /// assert_eq!(vec![0.5, 0.25], stats.mean_genes);
+    /// ```
+    pub fn new(board: &Board, population: &Population, rect: Rect) -> Self {
+        let rect = rect.clamp(board.fields.size);
+
+        Self::from_cells(board, population, rect, rect.coords())
+    }
+
+    /// Calculates the statistics of a region of the board with any shape
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board with the fields
+    /// population: The plants living on the board
+    /// shape: The cells to calculate the statistics for, the cells outside the board are ignored
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord, Shape}, genome::Genome, population::{Plant, Population}, stats::RegionStats};
+    /// 
+    /// let board = BoardBuilder::new().size(3, 3).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+    /// population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// population.insert(Coord::new(2, 2), Plant::new(100, Genome::new(&[1.0, 0.5]).unwrap()));
+    /// let stats = RegionStats::of_shape(&board, &population, &Shape::Polygon(vec![(0.0, 0.0), (3.0, 0.0), (0.0, 3.0)]));
+    /// 
+    /// assert_eq!(2, stats.population);
+    /// assert_eq!(0.25, stats.diversity);
+    /// ```
+    pub fn of_shape(board: &Board, population: &Population, shape: &Shape) -> Self {
+        let rect = shape.bounds().clamp(board.fields.size);
+
+        Self::from_cells(board, population, rect, shape.coords(board.fields.size))
+    }
+
+    /// Calculates the statistics of a set of cells, the cells outside the board are ignored
+    fn from_cells<I: Iterator<Item = Coord>>(board: &Board, population: &Population, rect: Rect, coords: I) -> Self {
+        let size = board.fields.size;
+        let mut cells = 0;
+        let mut light = 0.0;
+        let mut plants = Vec::new();
+        for (coord, index) in coords.filter_map(|coord| Some((coord, size.index_of(coord)?))) {
+            cells += 1;
+            light += board.fields.light[index];
+            plants.extend(population.get(coord));
+        }
+
+        let mean_energy = if plants.is_empty() {
+            0.0
+        } else {
+            plants.iter().map(|plant| plant.energy as f64).sum::<f64>() as f32 / plants.len() as f32
+        };
+
+        let mean_genes = genome::mean_genes(plants.iter().map(|plant| &plant.genome));
+
+        let mean_light = if cells > 0 { light / cells as f32 } else { 0.0 };
+
+        let diversity = if mean_genes.is_empty() {
+            0.0
+        } else {
+            let mean = Genome::new(&mean_genes).expect("The mean of valid genomes is a valid genome");
+
+            plants.iter().map(|plant| plant.genome.distance(&mean)).sum::<f32>() / plants.len() as f32
+        };
+
+        Self { rect, population: plants.len(), mean_energy, mean_genes, mean_light, diversity }
+    }
+}
+
+/// Calculates the statistics of every named region of a board in the order the regions were added
+/// 
+/// # Parameters
+/// 
+/// board: The board with the regions
+/// population: The plants living on the board
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board::{BoardBuilder, Coord, Rect}, genome::Genome, population::{Plant, Population}, stats};
+/// 
+/// let mut board = BoardBuilder::new().size(4, 2).light_uniform(1.0).build().unwrap();
+/// board.add_region("plateau", Rect::new(0, 0, 2, 2));
+/// board.add_region("valley", Rect::new(2, 0, 2, 2));
+/// let mut population = Population::new(board.fields.size);
+/// population.insert(Coord::new(3, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+/// let regions = stats::region_stats(&board, &population);
+/// 
+/// assert_eq!(vec![("plateau", 0), ("valley", 1)], regions.iter().map(|(name, stats)| (name.as_str(), stats.population)).collect::<Vec<_>>());
+/// ```
+pub fn region_stats(board: &Board, population: &Population) -> Vec<(String, RegionStats)> {
+    board.regions.iter()
+        .map(|region| (region.name.clone(), RegionStats::of_shape(board, population, &region.shape)))
+        .collect()
+}
+
+/// The statistics of the whole simulation after a single step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TickStats {
+    /// The tick the statistics are for
+    pub tick: u64,
+    /// The number of living plants
+    pub population: usize,
+    /// The number of seeds which germinated during the step
+    pub births: usize,
+    /// The number of plants which died during the step
+    pub deaths: usize,
+    /// The mean energy of the living plants, 0 if there are no plants
+    pub mean_energy: f32,
+    /// The genetic diversity of the living plants
+    pub diversity: f32,
+    /// The mutation rate used during the step
+    pub mutation_rate: f32,
+    /// The number of living species at the latest clustering, 0 if species are not tracked
+    pub species: usize,
+    /// The plant with the most offspring born within the fitness window and the number of offspring,
+    /// None if fitness is not tracked or there were no births within the window
+    pub fittest: Option<(PlantId, usize)>,
+}
+
+impl TickStats {
+    /// Calculates the statistics of a population after a step
+    pub(crate) fn new(tick: u64, population: &Population, births: usize, deaths: usize, mutation_rate: f32, species: usize, fittest: Option<(PlantId, usize)>) -> Self {
+        let count = population.count();
+        let mean_energy = if count == 0 {
+            0.0
+        } else {
+            population.iter().map(|(_, plant)| plant.energy as f64).sum::<f64>() as f32 / count as f32
+        };
+
+        Self { tick, population: count, births, deaths, mean_energy, diversity: population.diversity(), mutation_rate, species, fittest }
+    }
+}
+
+/// Receives the statistics of every step of a simulation in order.
+/// At most a fixed number of records are buffered, when the buffer is full the records of the following steps
+/// are dropped and counted until a record has been received, so a slow consumer misses records instead of slowing the simulation down
+#[derive(Debug)]
+pub struct Subscription {
+    /// The receiving end of the channel the simulation sends records to
+    receiver: mpsc::Receiver<TickStats>,
+    /// The number of records dropped because the buffer was full
+    dropped: Arc<AtomicU64>,
+}
+
+impl Subscription {
+    /// Returns the next record if one is ready without waiting, returns None if the buffer is empty
+    /// or the simulation has been dropped
+    pub fn try_next(&mut self) -> Option<TickStats> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Returns the number of records dropped so far because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Iterator for Subscription {
+    type Item = TickStats;
+
+    /// Waits for the next record, returns None once the simulation has been dropped and all records are received
+    fn next(&mut self) -> Option<TickStats> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// The sending end of a subscription kept by the simulation
+#[derive(Clone, Debug)]
+pub(crate) struct Publisher {
+    /// The channel the records are sent to
+    sender: mpsc::SyncSender<TickStats>,
+    /// The number of records dropped because the buffer was full, shared with the subscription
+    dropped: Arc<AtomicU64>,
+}
+
+impl Publisher {
+    /// Sends a record without waiting, a record which does not fit in the buffer is dropped and counted.
+    /// Returns false once the subscription has been dropped
+    /// 
+    /// # Parameters
+    /// 
+    /// stats: The record to send
+    pub(crate) fn publish(&self, stats: TickStats) -> bool {
+        match self.sender.try_send(stats) {
+            Ok(()) => true,
+            Err(mpsc::TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(mpsc::TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Subscribes to the statistics of every following step of a simulation, dropping the subscription unsubscribes
+/// 
+/// # Parameters
+/// 
+/// simulation: The simulation to receive the statistics of
+/// capacity: The largest number of records which are buffered before the records of the following steps are dropped,
+/// a capacity of 0 is treated as 1
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{board, genome::Genome, population::{Plant, Population}, simulation::{Simulation, SimulationConfig}, stats};
+/// 
+/// let size = board::Size::new(2, 2);
+/// let fields = board::Fields::new(size, &[1.0; 4]).unwrap();
+/// let board = board::Board::new(board::Multipliers::new(1024).unwrap(), fields);
+/// let mut population = Population::new(size);
+/// population.insert(board::Coord::new(0, 0), Plant::new(0, Genome::new(&[0.0, 0.5]).unwrap()));
+/// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+/// let mut subscription = stats::subscribe(&mut simulation, 16);
+/// simulation.step();
+/// simulation.step();
+/// 
+/// assert_eq!(Some(1), subscription.next().map(|stats| stats.tick));
+/// assert_eq!(Some(2), subscription.next().map(|stats| stats.tick));
+/// assert_eq!(None, subscription.try_next());
+/// assert_eq!(0, subscription.dropped());
+/// ```
+pub fn subscribe(simulation: &mut Simulation, capacity: usize) -> Subscription {
+    let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+    let dropped = Arc::new(AtomicU64::new(0));
+    simulation.add_subscriber(Publisher { sender, dropped: Arc::clone(&dropped) });
+
+    Subscription { receiver, dropped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Coord, Fields, Multipliers, Size};
+    use crate::genome::Genome;
+    use crate::population::Plant;
+    use crate::simulation::SimulationConfig;
+
+    fn board() -> Board {
+        let size = Size::new(3, 2);
+        let fields = Fields::new(size, &[0.0, 0.5, 1.0, 0.25, 0.75, 1.0]).unwrap();
+
+        Board::new(Multipliers::new(1024).unwrap(), fields)
+    }
+
+    #[test]
+    fn region_stats_new() {
+        let mut population = Population::new(Size::new(3, 2));
+        population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[0.5, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 1), Plant::new(50, Genome::new(&[1.0, 1.0]).unwrap()));
+        population.insert(Coord::new(0, 0), Plant::new(10, Genome::new(&[1.0, 1.0]).unwrap()));
+        let stats = RegionStats::new(&board(), &population, Rect::new(1, 0, 2, 2));
+
+        assert_eq!(Rect::new(1, 0, 2, 2), stats.rect);
+        assert_eq!(2, stats.population);
+        assert_eq!(75.0, stats.mean_energy);
+        assert_eq!(vec![0.75, 0.5], stats.mean_genes);
+        assert_eq!(0.8125, stats.mean_light);
+    }
+
+    #[test]
+    fn region_stats_new_empty() {
+        let population = Population::new(Size::new(3, 2));
+        let stats = RegionStats::new(&board(), &population, Rect::new(0, 0, 1, 2));
+
+        assert_eq!(0, stats.population);
+        assert_eq!(0.0, stats.mean_energy);
+        assert!(stats.mean_genes.is_empty());
+        assert_eq!(0.125, stats.mean_light);
+    }
+
+    #[test]
+    fn region_stats_new_clamp() {
+        let population = Population::new(Size::new(3, 2));
+        let stats = RegionStats::new(&board(), &population, Rect::new(2, 1, 5, 5));
+
+        assert_eq!(Rect::new(2, 1, 1, 1), stats.rect);
+        assert_eq!(1.0, stats.mean_light);
+    }
+
+    #[test]
+    fn region_stats_new_outside() {
+        let population = Population::new(Size::new(3, 2));
+        let stats = RegionStats::new(&board(), &population, Rect::new(5, 5, 5, 5));
+
+        assert_eq!(0, stats.rect.area());
+        assert_eq!(0.0, stats.mean_light);
+    }
+
+    #[test]
+    fn region_stats_of_shape() {
+        let mut population = Population::new(Size::new(3, 2));
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.0, 0.0]).unwrap()));
+        population.insert(Coord::new(0, 1), Plant::new(50, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 1), Plant::new(10, Genome::new(&[1.0, 1.0]).unwrap()));
+        let shape = Shape::Polygon(vec![(0.0, 0.0), (2.0, 0.0), (0.0, 4.0)]);
+        let stats = RegionStats::of_shape(&board(), &population, &shape);
+
+        assert_eq!(Rect::new(0, 0, 2, 2), stats.rect);
+        assert_eq!(2, stats.population);
+        assert_eq!(0.25, stats.diversity);
+        assert_eq!(0.25, stats.mean_light);
+    }
+
+    #[test]
+    fn region_stats_regions() {
+        let mut board = board();
+        board.add_region("dark", Rect::new(0, 0, 1, 2));
+        board.add_region("outside", Rect::new(5, 5, 2, 2));
+        let stats = region_stats(&board, &Population::new(Size::new(3, 2)));
+
+        assert_eq!(vec!["dark".to_string(), "outside".to_string()], stats.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>());
+        assert_eq!(0.125, stats[0].1.mean_light);
+        assert_eq!(0.0, stats[1].1.mean_light);
+    }
+
+    #[test]
+    fn tick_stats_new() {
+        let mut population = Population::new(Size::new(3, 2));
+        population.insert(Coord::new(1, 0), Plant::new(100, Genome::new(&[0.5, 0.0]).unwrap()));
+        population.insert(Coord::new(2, 1), Plant::new(50, Genome::new(&[0.5, 0.0]).unwrap()));
+        let stats = TickStats::new(4, &population, 1, 2, 0.01, 1, Some((PlantId(3), 1)));
+
+        assert_eq!(TickStats { tick: 4, population: 2, births: 1, deaths: 2, mean_energy: 75.0, diversity: 0.0, mutation_rate: 0.01, species: 1, fittest: Some((PlantId(3), 1)) }, stats);
+    }
+
+    #[test]
+    fn subscribe_records() {
+        let mut population = Population::new(Size::new(3, 2));
+        population.insert(Coord::new(0, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        population.insert(Coord::new(1, 0), Plant::new(0, Genome::new(&[1.0, 0.0]).unwrap()));
+        let config = SimulationConfig { upkeep: 400, ..Default::default() };
+        let mut simulation = Simulation::new(board(), population, config).unwrap();
+        let subscription = subscribe(&mut simulation, 4);
+        simulation.step();
+        simulation.step();
+        drop(simulation);
+        let records: Vec<TickStats> = subscription.collect();
+
+        // The plant in the dark dies in the first step and the other one gains 112 energy every step
+        assert_eq!(vec![1, 2], records.iter().map(|stats| stats.tick).collect::<Vec<_>>());
+        assert_eq!(vec![1, 1], records.iter().map(|stats| stats.population).collect::<Vec<_>>());
+        assert_eq!(vec![1, 0], records.iter().map(|stats| stats.deaths).collect::<Vec<_>>());
+        assert_eq!(vec![112.0, 224.0], records.iter().map(|stats| stats.mean_energy).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn subscribe_full() {
+        let population = Population::new(Size::new(3, 2));
+        let mut simulation = Simulation::new(board(), population, SimulationConfig::default()).unwrap();
+        let mut subscription = subscribe(&mut simulation, 1);
+        for _ in 0..3 {
+            simulation.step();
+        }
+
+        // The simulation does not wait for the full buffer, the records of the second and third step are dropped
+        assert_eq!(3, simulation.tick());
+        assert_eq!(Some(1), subscription.try_next().map(|stats| stats.tick));
+        assert_eq!(None, subscription.try_next());
+        assert_eq!(2, subscription.dropped());
+
+        simulation.step();
+
+        assert_eq!(Some(4), subscription.try_next().map(|stats| stats.tick));
+        assert_eq!(2, subscription.dropped());
+    }
+
+    #[test]
+    fn subscribe_unsubscribe() {
+        let population = Population::new(Size::new(3, 2));
+        let mut simulation = Simulation::new(board(), population, SimulationConfig::default()).unwrap();
+        let subscription = subscribe(&mut simulation, 1);
+        drop(subscription);
+
+        // The simulation does not wait for a dropped subscription
+        simulation.step();
+        simulation.step();
+
+        assert_eq!(2, simulation.tick());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::simulation::Simulation;
+
+/// A condition for when a simulation run should stop, checked before every step
+pub trait StopCondition {
+    /// Returns true if the run should stop
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation being run
+    fn should_stop(&mut self, simulation: &Simulation) -> bool;
+
+    /// Combines this condition with another such that the run stops when both are true,
+    /// both conditions are always checked so conditions which track the run stay up to date
+    fn and<C: StopCondition>(self, other: C) -> And<Self, C>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// Combines this condition with another such that the run stops when either is true,
+    /// both conditions are always checked so conditions which track the run stay up to date
+    fn or<C: StopCondition>(self, other: C) -> Or<Self, C>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+}
+
+impl<F: FnMut(&Simulation) -> bool> StopCondition for F {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        self(simulation)
+    }
+}
+
+/// Stops when there are no living plants
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Extinction;
+
+impl StopCondition for Extinction {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        simulation.population().count() == 0
+    }
+}
+
+/// Stops when the simulation has reached a tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickLimit(pub u64);
+
+impl StopCondition for TickLimit {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        simulation.tick() >= self.0
+    }
+}
+
+/// Stops when there are more living plants than a limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PopulationAbove(pub usize);
+
+impl StopCondition for PopulationAbove {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        simulation.population().count() > self.0
+    }
+}
+
+/// Stops when the diversity of the population has stayed within a tolerance for a number of checks in a row
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StagnantDiversityFor {
+    /// The number of checks the diversity must stay within the tolerance
+    ticks: u64,
+    /// The largest change in diversity from the start of the stagnant period which still counts as stagnant
+    tolerance: f32,
+    /// The diversity at the start of the current stagnant period and the number of checks since then
+    reference: Option<(f32, u64)>,
+}
+
+impl StagnantDiversityFor {
+    /// Creates a new condition
+    /// 
+    /// # Parameters
+    /// 
+    /// ticks: The number of checks the diversity must stay within the tolerance
+    /// tolerance: The largest change in diversity which still counts as stagnant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::stop::StagnantDiversityFor;
+    /// 
+    /// let condition = StagnantDiversityFor::new(100, 0.001);
+    /// ```
+    pub fn new(ticks: u64, tolerance: f32) -> Self {
+        Self { ticks, tolerance, reference: None }
+    }
+}
+
+impl StopCondition for StagnantDiversityFor {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        let diversity = simulation.population().diversity();
+
+        let checks = match self.reference {
+            Some((reference, checks)) if (diversity - reference).abs() <= self.tolerance => checks + 1,
+            _ => {
+                self.reference = Some((diversity, 0));
+                0
+            }
+        };
+
+        self.reference = self.reference.map(|(reference, _)| (reference, checks));
+
+        checks >= self.ticks
+    }
+}
+
+/// Stops when both conditions are true
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct And<A, B>(pub A, pub B);
+
+impl<A: StopCondition, B: StopCondition> StopCondition for And<A, B> {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        self.0.should_stop(simulation) & self.1.should_stop(simulation)
+    }
+}
+
+/// Stops when either condition is true
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Or<A, B>(pub A, pub B);
+
+impl<A: StopCondition, B: StopCondition> StopCondition for Or<A, B> {
+    fn should_stop(&mut self, simulation: &Simulation) -> bool {
+        self.0.should_stop(simulation) | self.1.should_stop(simulation)
+    }
+}
+
+impl Simulation {
+    /// Runs steps until a condition is true, returns the number of steps which were run.
+    /// The condition is checked before every step so no steps are run if it is already true,
+    /// combine the condition with a TickLimit to make sure the run ends
+    /// 
+    /// # Parameters
+    /// 
+    /// condition: The condition for when to stop
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::{simulation::{Simulation, SimulationConfig}, stop::{Extinction, StopCondition, TickLimit}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(0.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(0, 0), Plant::new(25, Genome::new(&[1.0, 0.0]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// 
+    /// // The plant starves after 3 steps
+    /// assert_eq!(3, simulation.run_until(Extinction.or(TickLimit(100))));
+    /// ```
+    pub fn run_until<C: StopCondition>(&mut self, mut condition: C) -> u64 {
+        let start = self.tick();
+
+        while !condition.should_stop(self) {
+            self.step();
+        }
+
+        self.tick() - start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::{Genome, MutationConfig};
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    fn simulation(light: f32) -> Simulation {
+        let board = BoardBuilder::new().size(5, 5).light_uniform(light).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(2, 2), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        let config = SimulationConfig { mutation: MutationConfig::new(0.0, 0.0), ..Default::default() };
+
+        Simulation::new(board, population, config).unwrap()
+    }
+
+    #[test]
+    fn run_until_tick_limit() {
+        let mut simulation = simulation(1.0);
+
+        assert_eq!(5, simulation.run_until(TickLimit(5)));
+        assert_eq!(0, simulation.run_until(TickLimit(3)));
+        assert_eq!(5, simulation.tick());
+    }
+
+    #[test]
+    fn run_until_extinction() {
+        let mut simulation = simulation(0.0);
+        let steps = simulation.run_until(Extinction.or(TickLimit(1000)));
+
+        assert!(steps < 1000);
+        assert_eq!(0, simulation.population().count());
+    }
+
+    #[test]
+    fn run_until_population_above() {
+        let mut simulation = simulation(1.0);
+        simulation.run_until(PopulationAbove(5).or(TickLimit(1000)));
+
+        assert!(simulation.population().count() > 5);
+    }
+
+    #[test]
+    fn run_until_closure() {
+        let mut simulation = simulation(1.0);
+
+        assert_eq!(7, simulation.run_until(|simulation: &Simulation| simulation.tick() == 7));
+    }
+
+    #[test]
+    fn run_until_and() {
+        let mut simulation = simulation(1.0);
+
+        assert_eq!(4, simulation.run_until(TickLimit(4).and(TickLimit(2))));
+    }
+
+    #[test]
+    fn stagnant_diversity_for_stop() {
+        // Without mutation the diversity of clones stays 0
+        let mut simulation = simulation(1.0);
+
+        assert_eq!(10, simulation.run_until(StagnantDiversityFor::new(10, 0.0)));
+    }
+
+    #[test]
+    fn stagnant_diversity_for_reset() {
+        let mut condition = StagnantDiversityFor::new(2, 0.01);
+        let clones = simulation(1.0);
+        let mut population = Population::new(clones.board().fields.size);
+        population.insert(Coord::new(0, 0), Plant::new(100, Genome::new(&[0.0, 0.5]).unwrap()));
+        population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[1.0, 1.0]).unwrap()));
+        let diverse = Simulation::new(clones.board().clone(), population, SimulationConfig::default()).unwrap();
+
+        assert!(!condition.should_stop(&clones));
+        assert!(!condition.should_stop(&clones));
+        // The diversity changes so the count starts over
+        assert!(!condition.should_stop(&diverse));
+        assert!(!condition.should_stop(&diverse));
+        assert!(condition.should_stop(&diverse));
+    }
+}
@@ -0,0 +1,199 @@
+use std::thread;
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::population::PlantId;
+use crate::simulation::Simulation;
+
+/// The number of random words every stream can draw in a single tick before it runs into the stream of the next tick
+pub const WORDS_PER_TICK: u128 = 1 << 20;
+
+/// Counter-based random number streams derived from the seed of a simulation. Every plant and every cell gets its own
+/// stream in every tick, found only from the seed, the tick and the id of the plant or the index of the cell. The numbers
+/// drawn for a plant therefore do not depend on which other plants were handled first or on which thread, so work split
+/// over any number of threads gives the same results as running it on a single thread
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RngStreams {
+    /// The key of the streams of the plants
+    plant_key: [u8; 32],
+    /// The key of the streams of the cells
+    cell_key: [u8; 32],
+}
+
+impl RngStreams {
+    /// Creates the streams of a seed
+    /// 
+    /// # Parameters
+    /// 
+    /// seed: The master seed, usually the seed of the settings of the simulation
+    pub fn new(seed: u64) -> Self {
+        let mut keys = ChaCha8Rng::seed_from_u64(seed);
+        let mut key = || {
+            let mut key = [0; 32];
+            keys.fill_bytes(&mut key);
+            key
+        };
+
+        Self { plant_key: key(), cell_key: key() }
+    }
+
+    /// Finds the random number generator of a plant in a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick the numbers are drawn in
+    /// id: The id of the plant
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use rand::Rng;
+    /// use evolution_plants::{population::PlantId, streams::RngStreams};
+    /// 
+    /// let streams = RngStreams::new(7);
+    /// let first: u64 = streams.plant(3, PlantId(12)).gen();
+    /// 
+    /// // The same stream is found again no matter what was drawn in between
+    /// let _: u64 = streams.plant(3, PlantId(13)).gen();
+    /// assert_eq!(first, streams.plant(3, PlantId(12)).gen::<u64>());
+    /// assert_ne!(first, streams.plant(4, PlantId(12)).gen::<u64>());
+    /// ```
+    pub fn plant(&self, tick: u64, id: PlantId) -> ChaCha8Rng {
+        stream(self.plant_key, tick, id.0)
+    }
+
+    /// Finds the random number generator of a cell in a tick
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick the numbers are drawn in
+    /// index: The index of the cell
+    pub fn cell(&self, tick: u64, index: usize) -> ChaCha8Rng {
+        stream(self.cell_key, tick, index as u64)
+    }
+}
+
+/// Finds the stream of a key and a number at the start of a tick
+fn stream(key: [u8; 32], tick: u64, number: u64) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::from_seed(key);
+    rng.set_stream(number);
+    rng.set_word_pos(tick as u128 * WORDS_PER_TICK);
+
+    rng
+}
+
+/// Maps every item on a number of threads and returns the results in the order of the items. The items are split into
+/// one contiguous chunk for every thread, so the results only depend on the items and not on the number of threads
+/// as long as the function does not share state between items
+/// 
+/// # Parameters
+/// 
+/// items: The items to map
+/// threads: The number of threads to use, 0 is treated as 1 and a single thread runs on the calling thread
+/// map: The function applied to every item
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::streams;
+/// 
+/// let items: Vec<u64> = (0..100).collect();
+/// 
+/// assert_eq!(streams::parallel_map(&items, 1, |item| item * 2), streams::parallel_map(&items, 8, |item| item * 2));
+/// ```
+pub fn parallel_map<T: Sync, R: Send, F: Fn(&T) -> R + Sync>(items: &[T], threads: usize, map: F) -> Vec<R> {
+    let threads = threads.clamp(1, items.len().max(1));
+    if threads == 1 {
+        return items.iter().map(map).collect();
+    }
+
+    let chunk = items.len().div_ceil(threads);
+    let map = &map;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items.chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(map).collect::<Vec<_>>()))
+            .collect();
+
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+impl Simulation {
+    /// Returns the counter-based random number streams derived from the seed of the settings, the streams of the plants
+    /// and cells can be drawn from in any order and on any thread without changing the results
+    pub fn rng_streams(&self) -> RngStreams {
+        RngStreams::new(self.config().seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    /// Draws a mutated copy of the genome of every plant from its own stream
+    fn mutate(streams: &RngStreams, tick: u64, plants: &[(PlantId, Genome)], threads: usize) -> Vec<Vec<f32>> {
+        parallel_map(plants, threads, |(id, genome)| {
+            let mut rng = streams.plant(tick, *id);
+            genome.genes().iter().map(|gene| (gene + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0)).collect()
+        })
+    }
+
+    #[test]
+    fn streams_thread_count() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let plants: Vec<(PlantId, Genome)> = (0..257)
+            .map(|id| (PlantId(id), Genome::new(&(0..6).map(|_| rng.gen()).collect::<Vec<f32>>()).unwrap()))
+            .collect();
+        let streams = RngStreams::new(11);
+
+        let single = mutate(&streams, 5, &plants, 1);
+        for threads in [2, 3, 4, 8, 300] {
+            assert_eq!(single, mutate(&streams, 5, &plants, threads));
+        }
+
+        // Handling the plants in another order gives every plant the same numbers
+        let mut reversed = plants.clone();
+        reversed.reverse();
+        let mut backwards = mutate(&streams, 5, &reversed, 4);
+        backwards.reverse();
+        assert_eq!(single, backwards);
+
+        assert_ne!(single, mutate(&RngStreams::new(12), 5, &plants, 1));
+        assert_ne!(single, mutate(&streams, 6, &plants, 1));
+    }
+
+    #[test]
+    fn streams_independent() {
+        let streams = RngStreams::new(0);
+        let draw = |mut rng: ChaCha8Rng| (0..4).map(|_| rng.gen::<u64>()).collect::<Vec<_>>();
+
+        assert_ne!(draw(streams.plant(0, PlantId(0))), draw(streams.plant(0, PlantId(1))));
+        assert_ne!(draw(streams.plant(0, PlantId(0))), draw(streams.cell(0, 0)));
+        assert_eq!(draw(streams.cell(9, 4)), draw(streams.cell(9, 4)));
+    }
+
+    #[test]
+    fn streams_simulation_seed() {
+        let board = BoardBuilder::new().size(4, 4).light_uniform(1.0).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(1, 1), Plant::new(100, Genome::new(&[0.5, 0.5]).unwrap()));
+        let config = SimulationConfig { seed: 42, ..Default::default() };
+        let simulation = Simulation::new(board, population, config).unwrap();
+
+        assert_eq!(RngStreams::new(42), simulation.rng_streams());
+    }
+
+    #[test]
+    fn parallel_map_order() {
+        let items: Vec<usize> = (0..10).collect();
+
+        assert_eq!(items, parallel_map(&items, 3, |item| *item));
+        assert_eq!(Vec::<usize>::new(), parallel_map(&[], 4, |item: &usize| *item));
+    }
+}
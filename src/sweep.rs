@@ -0,0 +1,465 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use thiserror::Error;
+
+use crate::board::{Board, Multipliers};
+use crate::experiment::{self, Run, Summary};
+use crate::population::Population;
+use crate::simulation::{SimulationConfig, SimulationCreateError};
+use crate::stats::TickStats;
+
+/// The parameters which can be varied in a sweep
+pub const PARAMETERS: [&str; 6] = ["upkeep", "seed_cost", "max_threshold", "mutation.rate", "mutation.strength", "board.light_multiplier"];
+
+/// A statistic collected at the end of every run, the name of its column and how to read it from the statistics of a step
+type Metric = (&'static str, fn(&TickStats) -> f64);
+
+/// The statistics of the last step collected for every run
+const METRICS: [Metric; 6] = [
+    ("population", |stats| stats.population as f64),
+    ("mean_energy", |stats| stats.mean_energy as f64),
+    ("diversity", |stats| stats.diversity as f64),
+    ("species", |stats| stats.species as f64),
+    ("births", |stats| stats.births as f64),
+    ("deaths", |stats| stats.deaths as f64),
+];
+
+/// A batch of runs of every combination of values of some parameters and every seed, the statistics of the last step
+/// of every run are collected into a tidy CSV file with a row for every statistic of every run.
+/// The file is written as the runs finish, so a sweep which was interrupted continues from the runs it had finished
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    /// The starting state every run is changed from
+    base: Run,
+    /// The number of steps every run is stepped
+    ticks: u64,
+    /// The name of every parameter varied and the values it takes
+    parameters: Vec<(String, Vec<f64>)>,
+    /// The seeds every combination of values is run with
+    seeds: Vec<u64>,
+    /// The CSV file the results are written to, the results are only kept in memory if this is None
+    output: Option<PathBuf>,
+}
+
+impl Sweep {
+    /// Creates a new sweep varying nothing, it runs the base settings once with their own seed
+    /// 
+    /// # Parameters
+    /// 
+    /// board: The board the runs start on
+    /// population: The plants the runs start with
+    /// config: The settings the parameters are changed from
+    /// ticks: The number of steps every run is stepped
+    pub fn new(board: Board, population: Population, config: SimulationConfig, ticks: u64) -> Self {
+        let seeds = vec![config.seed];
+
+        Self { base: Run { board, population, config }, ticks, parameters: Vec::new(), seeds, output: None }
+    }
+
+    /// Adds a parameter to vary, every value is run with every combination of the values of the other parameters.
+    /// The whole numbers of the settings are rounded from the values, see PARAMETERS for the parameters which can be varied
+    /// 
+    /// # Parameters
+    /// 
+    /// name: The name of the parameter such as "mutation.rate"
+    /// values: The values of the parameter
+    pub fn vary<I: IntoIterator<Item = f64>>(mut self, name: &str, values: I) -> Self {
+        self.parameters.push((name.to_string(), values.into_iter().collect()));
+        self
+    }
+
+    /// Sets the seeds every combination of values is run with, the results of the seeds are summarized together
+    /// 
+    /// # Parameters
+    /// 
+    /// seeds: The seeds of the runs
+    pub fn seeds<I: IntoIterator<Item = u64>>(mut self, seeds: I) -> Self {
+        self.seeds = seeds.into_iter().collect();
+        self
+    }
+
+    /// Sets the CSV file the results are written to as the runs finish. If the file already has results of the same sweep
+    /// the runs it has are not run again
+    /// 
+    /// # Parameters
+    /// 
+    /// path: The path of the file
+    pub fn output<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.output = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns the value of every parameter and the seed of every run in order, the seeds change the fastest
+    /// and the first parameter the slowest
+    pub fn points(&self) -> Vec<(Vec<f64>, u64)> {
+        let mut points = vec![Vec::new()];
+        for (_, values) in &self.parameters {
+            points = points.into_iter()
+                .flat_map(|point: Vec<f64>| values.iter().map(move |&value| {
+                    let mut point = point.clone();
+                    point.push(value);
+                    point
+                }))
+                .collect();
+        }
+
+        points.into_iter().flat_map(|point| self.seeds.iter().map(move |&seed| (point.clone(), seed))).collect()
+    }
+
+    /// Runs every combination of values with every seed which has not been run already and collects the statistics
+    /// of the last step of every run
+    /// 
+    /// # Parameters
+    /// 
+    /// threads: The largest number of runs stepped at the same time, a value of 0 is treated as 1
+    /// 
+    /// # Errors
+    /// 
+    /// SweepError::Parameter: This will occur if a parameter cannot be varied
+    /// 
+    /// SweepError::Run: This will occur if one of the runs could not be created
+    /// 
+    /// SweepError::Io: This will occur if the output file could not be read or written
+    /// 
+    /// SweepError::Resume: This will occur if the output file has results which are not from the same sweep
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, sweep::Sweep};
+    /// use evolution_plants::{population::{Plant, Population}, simulation::SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(8, 8).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(4, 4), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+    /// let report = Sweep::new(board, population, SimulationConfig::default(), 20)
+    ///     .vary("mutation.rate", [0.0, 0.1])
+    ///     .vary("board.light_multiplier", [50.0, 100.0, 200.0])
+    ///     .seeds(0..2)
+    ///     .run(4)
+    ///     .unwrap();
+    /// 
+    /// assert_eq!(12, report.results.len());
+    /// assert_eq!(6, report.summary("population").unwrap().len());
+    /// assert!(report.csv().starts_with("run,seed,mutation.rate,board.light_multiplier,metric,value\n"));
+    /// ```
+    pub fn run(self, threads: usize) -> Result<SweepReport, SweepError> {
+        if let Some((name, _)) = self.parameters.iter().find(|(name, _)| !PARAMETERS.contains(&name.as_str())) {
+            return Err(SweepError::Parameter { name: name.clone() });
+        }
+
+        let names: Vec<String> = self.parameters.iter().map(|(name, _)| name.clone()).collect();
+        let points = self.points();
+        let mut results = match &self.output {
+            Some(path) => resume(path, &names, &points)?,
+            None => BTreeMap::new(),
+        };
+
+        let remaining: Vec<usize> = (0..points.len()).filter(|run| !results.contains_key(run)).collect();
+        for chunk in remaining.chunks(threads.max(1)) {
+            let finished: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = chunk.iter().map(|&run| {
+                    let start = self.start(&names, &points[run]);
+                    scope.spawn(move || experiment::step_run(start, self.ticks))
+                }).collect();
+
+                handles.into_iter().map(|handle| handle.join().expect("A run panicked")).collect()
+            });
+
+            let mut batch = Vec::with_capacity(chunk.len());
+            for (&run, result) in chunk.iter().zip(finished) {
+                let (records, _) = result.map_err(|error| SweepError::Run { run, error })?;
+                let metrics = records.last().map_or([0.0; METRICS.len()], |stats| METRICS.map(|(_, metric)| metric(stats)));
+                let (values, seed) = points[run].clone();
+                batch.push(SweepResult { run, seed, values, metrics: metrics.to_vec() });
+            }
+
+            if let Some(path) = &self.output {
+                let mut text = String::new();
+                for result in &batch {
+                    write_rows(&mut text, result);
+                }
+                let mut file = OpenOptions::new().append(true).open(path).map_err(|error| io_error(path, error))?;
+                file.write_all(text.as_bytes()).map_err(|error| io_error(path, error))?;
+            }
+
+            results.extend(batch.into_iter().map(|result| (result.run, result)));
+        }
+
+        Ok(SweepReport { parameters: names, results: results.into_values().collect() })
+    }
+
+    /// Creates the starting state of a run
+    fn start(&self, names: &[String], (values, seed): &(Vec<f64>, u64)) -> Run {
+        let mut run = Run { config: SimulationConfig { seed: *seed, ..self.base.config.clone() }, ..self.base.clone() };
+        for (name, &value) in names.iter().zip(values) {
+            let whole = value.round().max(0.0).min(u32::MAX as f64) as u32;
+            match name.as_str() {
+                "upkeep" => run.config.upkeep = whole,
+                "seed_cost" => run.config.seed_cost = whole,
+                "max_threshold" => run.config.max_threshold = whole,
+                "mutation.rate" => run.config.mutation.rate = value as f32,
+                "mutation.strength" => run.config.mutation.strength = value as f32,
+                "board.light_multiplier" => run.board.multipliers.light = whole.clamp(1, Multipliers::MAX),
+                _ => unreachable!("The parameters are checked before running"),
+            }
+        }
+
+        run
+    }
+}
+
+/// Writes the header of the CSV file of a sweep
+fn header(names: &[String]) -> String {
+    let mut header = String::from("run,seed");
+    for name in names {
+        let _ = write!(header, ",{}", name);
+    }
+    header.push_str(",metric,value\n");
+
+    header
+}
+
+/// Writes a row for every statistic of a run
+fn write_rows(text: &mut String, result: &SweepResult) {
+    for ((name, _), value) in METRICS.iter().zip(&result.metrics) {
+        let _ = write!(text, "{},{}", result.run, result.seed);
+        for parameter in &result.values {
+            let _ = write!(text, ",{}", parameter);
+        }
+        let _ = writeln!(text, ",{},{}", name, value);
+    }
+}
+
+/// Reads the runs already finished from the output file and rewrites the file with only those,
+/// such that the rows of a run which was being written when the sweep was interrupted are dropped.
+/// The file is created with a header if it does not exist
+fn resume(path: &Path, names: &[String], points: &[(Vec<f64>, u64)]) -> Result<BTreeMap<usize, SweepResult>, SweepError> {
+    let header = header(names);
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(io_error(path, error)),
+    };
+
+    let mut results = BTreeMap::new();
+    if !text.is_empty() {
+        let mut lines = text.lines();
+        if lines.next() != header.lines().next() {
+            return Err(resume_error(path, 1, "the header does not match the parameters of the sweep"));
+        }
+
+        let mut rows: BTreeMap<usize, Vec<f64>> = BTreeMap::new();
+        for (number, line) in lines.enumerate().map(|(number, line)| (number + 2, line)) {
+            let fields: Vec<&str> = line.split(',').collect();
+            if fields.len() != names.len() + 4 {
+                continue;
+            }
+
+            let run: usize = fields[0].parse().map_err(|_| resume_error(path, number, "the run is not a number"))?;
+            let (values, seed) = points.get(run).ok_or_else(|| resume_error(path, number, "the run is not part of the sweep"))?;
+            let matches = fields[1].parse() == Ok(*seed)
+                && fields[2..2 + names.len()].iter().zip(values).all(|(field, value)| field.parse() == Ok(*value));
+            if !matches {
+                return Err(resume_error(path, number, "the seed or the values of the run are not the ones of the sweep"));
+            }
+
+            let metric = rows.entry(run).or_default();
+            if fields[names.len() + 2] == METRICS[metric.len().min(METRICS.len() - 1)].0 {
+                if let Ok(value) = fields[names.len() + 3].parse() {
+                    metric.push(value);
+                }
+            }
+        }
+
+        for (run, metrics) in rows.into_iter().filter(|(_, metrics)| metrics.len() == METRICS.len()) {
+            let (values, seed) = points[run].clone();
+            results.insert(run, SweepResult { run, seed, values, metrics });
+        }
+    }
+
+    let mut text = header;
+    for result in results.values() {
+        write_rows(&mut text, result);
+    }
+    fs::write(path, text).map_err(|error| io_error(path, error))?;
+
+    Ok(results)
+}
+
+/// Creates the error of a failed file operation
+fn io_error(path: &Path, error: std::io::Error) -> SweepError {
+    SweepError::Io { path: path.to_path_buf(), message: error.to_string() }
+}
+
+/// Creates the error of an output file which cannot be resumed from
+fn resume_error(path: &Path, line: usize, message: &str) -> SweepError {
+    SweepError::Resume { path: path.to_path_buf(), line, message: message.to_string() }
+}
+
+/// The statistics of the last step of a single run of a sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepResult {
+    /// The number of the run in the order of Sweep::points
+    pub run: usize,
+    /// The seed of the run
+    pub seed: u64,
+    /// The value of every parameter varied
+    pub values: Vec<f64>,
+    /// The value of every statistic in the order of SweepReport::metrics
+    pub metrics: Vec<f64>,
+}
+
+/// The results of a sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepReport {
+    /// The names of the parameters varied
+    pub parameters: Vec<String>,
+    /// The result of every run in order
+    pub results: Vec<SweepResult>,
+}
+
+impl SweepReport {
+    /// Returns the names of the statistics collected for every run
+    pub fn metrics() -> Vec<&'static str> {
+        METRICS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Summarizes a statistic across the seeds of every combination of values, returns the values and the summary
+    /// in the order the combinations were run or None if there is no statistic with the name
+    /// 
+    /// # Parameters
+    /// 
+    /// metric: The name of the statistic such as "population"
+    pub fn summary(&self, metric: &str) -> Option<Vec<(Vec<f64>, Summary)>> {
+        let column = METRICS.iter().position(|(name, _)| *name == metric)?;
+        let mut groups: Vec<(Vec<f64>, Vec<f64>)> = Vec::new();
+        for result in &self.results {
+            match groups.last_mut() {
+                Some((values, metrics)) if *values == result.values => metrics.push(result.metrics[column]),
+                _ => groups.push((result.values.clone(), vec![result.metrics[column]])),
+            }
+        }
+
+        Some(groups.into_iter().map(|(values, metrics)| (values, Summary::new(&metrics))).collect())
+    }
+
+    /// Writes the results as a tidy CSV with a header and a row for every statistic of every run
+    pub fn csv(&self) -> String {
+        let mut text = header(&self.parameters);
+        for result in &self.results {
+            write_rows(&mut text, result);
+        }
+
+        text
+    }
+}
+
+/// The errors which can occur when running a sweep
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum SweepError {
+    #[error("The parameter {:?} cannot be varied", name)]
+    Parameter {
+        name: String,
+    },
+    #[error("Run {:?} could not be created: {}", run, error)]
+    Run {
+        run: usize,
+        error: SimulationCreateError,
+    },
+    #[error("Unable to access {:?}: {}", path, message)]
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+    #[error("Unable to resume from {:?} at line {:?}: {}", path, line, message)]
+    Resume {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::Plant;
+
+    fn sweep() -> Sweep {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.1, 0.5]).unwrap()));
+
+        Sweep::new(board, population, SimulationConfig::default(), 10)
+    }
+
+    fn path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("evolution_plants_sweep_{}_{}.csv", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn sweep_points() {
+        let sweep = sweep().vary("upkeep", [1.0, 2.0]).vary("seed_cost", [3.0, 4.0, 5.0]).seeds([7, 8]);
+        let points = sweep.points();
+
+        assert_eq!(12, points.len());
+        assert_eq!((vec![1.0, 3.0], 7), points[0]);
+        assert_eq!((vec![1.0, 3.0], 8), points[1]);
+        assert_eq!((vec![1.0, 4.0], 7), points[2]);
+        assert_eq!((vec![2.0, 5.0], 8), points[11]);
+    }
+
+    #[test]
+    fn sweep_parameters() {
+        let varied = sweep().vary("upkeep", [12.4]).vary("board.light_multiplier", [0.0]).vary("mutation.rate", [0.25]).seeds([3]);
+        let run = varied.start(&["upkeep".to_string(), "board.light_multiplier".to_string(), "mutation.rate".to_string()], &varied.points()[0]);
+
+        assert_eq!((12, 1, 0.25, 3), (run.config.upkeep, run.board.multipliers.light, run.config.mutation.rate, run.config.seed));
+        assert_eq!(Err(SweepError::Parameter { name: "light".to_string() }), sweep().vary("light", [1.0]).run(1));
+    }
+
+    #[test]
+    fn sweep_deterministic() {
+        // Results are the same no matter how the runs are spread over threads
+        let first = sweep().vary("mutation.rate", [0.0, 0.2]).seeds(0..2).run(1).unwrap();
+        let second = sweep().vary("mutation.rate", [0.0, 0.2]).seeds(0..2).run(3).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(vec![0, 1, 2, 3], first.results.iter().map(|result| result.run).collect::<Vec<_>>());
+        assert_eq!(2, first.summary("diversity").unwrap()[1].1.count);
+        assert_eq!(None, first.summary("height"));
+    }
+
+    #[test]
+    fn sweep_resume() {
+        let path = path("resume");
+        let full = sweep().vary("upkeep", [5.0, 10.0, 15.0]).output(&path).run(2).unwrap();
+        assert_eq!(full.csv(), fs::read_to_string(&path).unwrap());
+
+        // Cut the file in the middle of the rows of the last run, which is then run again
+        let text = fs::read_to_string(&path).unwrap();
+        let cut: Vec<&str> = text.lines().take(1 + 2 * METRICS.len() + 2).collect();
+        fs::write(&path, cut.join("\n") + ",0.").unwrap();
+        let resumed = sweep().vary("upkeep", [5.0, 10.0, 15.0]).output(&path).run(2).unwrap();
+
+        assert_eq!(full, resumed);
+        assert_eq!(text, fs::read_to_string(&path).unwrap());
+
+        // Results of another sweep are not mixed in
+        let other = sweep().vary("upkeep", [5.0, 20.0, 15.0]).output(&path).run(1);
+        assert!(matches!(other, Err(SweepError::Resume { line: 8, .. })));
+        let other = sweep().vary("seed_cost", [5.0]).output(&path).run(1);
+        assert!(matches!(other, Err(SweepError::Resume { line: 1, .. })));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::mem;
+
+use crate::board::Coord;
+use crate::memory::{vec_bytes, HeapSize};
+use crate::population::PlantId;
+
+/// What a traced plant decided about reproducing in a step
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReproductionDecision {
+    /// The plant did not have enough energy to reproduce
+    Infertile,
+    /// The network of the plant chose not to reproduce
+    Declined,
+    /// No mate was found and the plant cannot fertilize itself
+    NoMate,
+    /// The plant produced a seed
+    Reproduced,
+}
+
+impl ReproductionDecision {
+    /// Returns the name used in the JSON dump
+    fn name(&self) -> &'static str {
+        match self {
+            ReproductionDecision::Infertile => "infertile",
+            ReproductionDecision::Declined => "declined",
+            ReproductionDecision::NoMate => "no_mate",
+            ReproductionDecision::Reproduced => "reproduced",
+        }
+    }
+}
+
+/// Why a traced plant died
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeathCause {
+    /// The plant could not pay its upkeep
+    Starvation,
+    /// The plant was killed by a disturbance
+    Disturbance,
+    /// The plant was removed by a bottleneck
+    Culled,
+    /// The plant was removed by an edit or together with its cell when the board was reframed
+    Removed,
+}
+
+impl DeathCause {
+    /// Returns the name used in the JSON dump
+    fn name(&self) -> &'static str {
+        match self {
+            DeathCause::Starvation => "starvation",
+            DeathCause::Disturbance => "disturbance",
+            DeathCause::Culled => "culled",
+            DeathCause::Removed => "removed",
+        }
+    }
+}
+
+/// Something which happened to a traced plant, the energy is always the energy of the plant afterwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// The plant germinated from a seed
+    Born {
+        coord: Coord,
+        parent: Option<PlantId>,
+        mate: Option<PlantId>,
+        energy: u32,
+    },
+    /// The plant collected energy from its surroundings
+    Collected {
+        intake: u32,
+        energy: u32,
+    },
+    /// The plant paid its upkeep and grew a step older
+    UpkeepPaid {
+        upkeep: u32,
+        energy: u32,
+    },
+    /// The plant decided whether to reproduce
+    Decided {
+        decision: ReproductionDecision,
+    },
+    /// The plant paid for a seed, the seed landed on the board unless it is None
+    SeedProduced {
+        cost: u32,
+        mutations: usize,
+        mate: Option<PlantId>,
+        target: Option<Coord>,
+        energy: u32,
+    },
+    /// The plant died, the cell is None if it was removed together with its cell
+    Died {
+        coord: Option<Coord>,
+        cause: DeathCause,
+        energy: u32,
+    },
+}
+
+/// A single event of a traced plant
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceEntry {
+    /// The tick of the step the event happened in
+    pub tick: u64,
+    /// The plant the event happened to
+    pub id: PlantId,
+    /// What happened
+    pub event: TraceEvent,
+}
+
+/// A log of everything which happened to some plants and all their descendants, every energy transaction,
+/// mutation and reproduction decision is recorded such that the fate of a lineage can be followed step by step.
+/// A seed is a descendant of a traced plant if its parent or its mate is traced. The log grows with the lineage,
+/// so tracing the founder of a successful lineage on a large board uses a lot of memory
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PlantTrace {
+    /// The plants which are traced
+    traced: BTreeSet<PlantId>,
+    /// The events of the traced plants in the order they happened
+    entries: Vec<TraceEntry>,
+}
+
+impl PlantTrace {
+    /// Creates a new trace which does not trace any plants
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracing a plant and its descendants born from now on
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn trace(&mut self, id: PlantId) {
+        self.traced.insert(id);
+    }
+
+    /// Returns true if a plant is traced
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn is_traced(&self, id: PlantId) -> bool {
+        self.traced.contains(&id)
+    }
+
+    /// Returns the ids of all traced plants in increasing order
+    pub fn traced(&self) -> impl Iterator<Item = PlantId> + '_ {
+        self.traced.iter().copied()
+    }
+
+    /// Returns the events of the traced plants in the order they happened
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Returns the events of a single plant in the order they happened
+    /// 
+    /// # Parameters
+    /// 
+    /// id: The id of the plant
+    pub fn entries_of(&self, id: PlantId) -> impl Iterator<Item = &TraceEntry> + '_ {
+        self.entries.iter().filter(move |entry| entry.id == id)
+    }
+
+    /// Records an event if the plant is traced, a plant born from a traced parent or mate becomes traced
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick of the step the event happened in
+    /// id: The plant the event happened to
+    /// event: What happened
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Coord, population::PlantId, trace::{PlantTrace, TraceEvent}};
+    /// 
+    /// let mut trace = PlantTrace::new();
+    /// trace.trace(PlantId(1));
+    /// trace.record(3, PlantId(2), TraceEvent::Collected { intake: 5, energy: 5 });
+    /// trace.record(3, PlantId(4), TraceEvent::Born { coord: Coord::new(0, 0), parent: Some(PlantId(1)), mate: None, energy: 2 });
+    /// 
+    /// assert_eq!(1, trace.entries().len());
+    /// assert!(trace.is_traced(PlantId(4)));
+    /// ```
+    pub fn record(&mut self, tick: u64, id: PlantId, event: TraceEvent) {
+        if let TraceEvent::Born { parent, mate, .. } = event {
+            if [parent, mate].into_iter().flatten().any(|ancestor| self.traced.contains(&ancestor)) {
+                self.traced.insert(id);
+            }
+        }
+
+        if self.traced.contains(&id) {
+            self.entries.push(TraceEntry { tick, id, event });
+        }
+    }
+
+    /// Writes the trace as JSON with the traced ids and a list of the events, every event is an object with
+    /// the tick, the plant, the kind of event and the values of the event
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{population::PlantId, trace::{PlantTrace, TraceEvent}};
+    /// 
+    /// let mut trace = PlantTrace::new();
+    /// trace.trace(PlantId(1));
+    /// trace.record(3, PlantId(1), TraceEvent::UpkeepPaid { upkeep: 2, energy: 8 });
+    /// 
+    /// assert_eq!(
+    ///     r#"{"traced":[1],"entries":[{"tick":3,"plant":1,"event":"upkeep_paid","upkeep":2,"energy":8}]}"#,
+    ///     trace.to_json()
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let traced: Vec<String> = self.traced.iter().map(|id| id.0.to_string()).collect();
+        let entries: Vec<String> = self.entries.iter().map(entry_json).collect();
+
+        format!("{{\"traced\":[{}],\"entries\":[{}]}}", traced.join(","), entries.join(","))
+    }
+}
+
+/// Writes a single entry as a JSON object
+fn entry_json(entry: &TraceEntry) -> String {
+    let mut json = format!("{{\"tick\":{},\"plant\":{}", entry.tick, entry.id.0);
+
+    // Writing to a string cannot fail
+    let _ = match entry.event {
+        TraceEvent::Born { coord, parent, mate, energy } => write!(
+            json, ",\"event\":\"born\",\"coord\":{},\"parent\":{},\"mate\":{},\"energy\":{}",
+            coord_json(Some(coord)), id_json(parent), id_json(mate), energy,
+        ),
+        TraceEvent::Collected { intake, energy } => write!(json, ",\"event\":\"collected\",\"intake\":{},\"energy\":{}", intake, energy),
+        TraceEvent::UpkeepPaid { upkeep, energy } => write!(json, ",\"event\":\"upkeep_paid\",\"upkeep\":{},\"energy\":{}", upkeep, energy),
+        TraceEvent::Decided { decision } => write!(json, ",\"event\":\"decided\",\"decision\":\"{}\"", decision.name()),
+        TraceEvent::SeedProduced { cost, mutations, mate, target, energy } => write!(
+            json, ",\"event\":\"seed_produced\",\"cost\":{},\"mutations\":{},\"mate\":{},\"target\":{},\"energy\":{}",
+            cost, mutations, id_json(mate), coord_json(target), energy,
+        ),
+        TraceEvent::Died { coord, cause, energy } => write!(
+            json, ",\"event\":\"died\",\"coord\":{},\"cause\":\"{}\",\"energy\":{}",
+            coord_json(coord), cause.name(), energy,
+        ),
+    };
+    json.push('}');
+
+    json
+}
+
+/// Writes an optional id as JSON
+fn id_json(id: Option<PlantId>) -> String {
+    id.map_or("null".to_string(), |id| id.0.to_string())
+}
+
+/// Writes an optional cell as JSON
+fn coord_json(coord: Option<Coord>) -> String {
+    coord.map_or("null".to_string(), |coord| format!("{{\"x\":{},\"y\":{}}}", coord.x, coord.y))
+}
+
+impl HeapSize for PlantTrace {
+    fn heap_bytes(&self) -> usize {
+        // The nodes of the tree are assumed to be two thirds full
+        self.traced.len() * mem::size_of::<PlantId>() * 3 / 2 + vec_bytes(&self.entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Rect};
+    use crate::disturbance::{Disturbance, DisturbanceKind};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::{Simulation, SimulationConfig};
+
+    #[test]
+    fn trace_descendants() {
+        let mut trace = PlantTrace::new();
+        trace.trace(PlantId(0));
+        let born = |parent, mate| TraceEvent::Born { coord: Coord::new(0, 0), parent, mate, energy: 1 };
+        trace.record(1, PlantId(1), born(Some(PlantId(0)), None));
+        trace.record(1, PlantId(2), born(Some(PlantId(5)), Some(PlantId(1))));
+        trace.record(1, PlantId(3), born(Some(PlantId(5)), None));
+        trace.record(2, PlantId(3), TraceEvent::Decided { decision: ReproductionDecision::Infertile });
+
+        assert_eq!(vec![PlantId(0), PlantId(1), PlantId(2)], trace.traced().collect::<Vec<_>>());
+        assert_eq!(2, trace.entries().len());
+        assert_eq!(1, trace.entries_of(PlantId(2)).count());
+    }
+
+    #[test]
+    fn trace_json() {
+        let mut trace = PlantTrace::new();
+        trace.trace(PlantId(7));
+        trace.record(4, PlantId(7), TraceEvent::SeedProduced { cost: 10, mutations: 1, mate: None, target: Some(Coord::new(2, 3)), energy: 5 });
+        trace.record(5, PlantId(7), TraceEvent::Died { coord: None, cause: DeathCause::Removed, energy: 5 });
+
+        assert_eq!(
+            concat!(
+                r#"{"traced":[7],"entries":["#,
+                r#"{"tick":4,"plant":7,"event":"seed_produced","cost":10,"mutations":1,"mate":null,"target":{"x":2,"y":3},"energy":5},"#,
+                r#"{"tick":5,"plant":7,"event":"died","coord":null,"cause":"removed","energy":5}]}"#,
+            ),
+            trace.to_json()
+        );
+    }
+
+    #[test]
+    fn trace_simulation() {
+        let board = BoardBuilder::new().size(6, 6).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(3, 3), Plant::new(100, Genome::new(&[0.3, 0.5]).unwrap()));
+        let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+        let founder = simulation.population().plant(3 + 3 * 6).unwrap().id();
+        simulation.trace_plant(founder);
+        simulation.schedule_disturbance(20, Disturbance::new(DisturbanceKind::Fire, Rect::new(0, 0, 6, 6)));
+        for _ in 0..20 {
+            simulation.step();
+        }
+        let trace = simulation.plant_trace().unwrap();
+        let born = trace.entries().iter().filter(|entry| matches!(entry.event, TraceEvent::Born { .. })).count();
+        let seeds = trace.entries().iter().filter(|entry| matches!(entry.event, TraceEvent::SeedProduced { .. })).count();
+
+        assert!(born > 0);
+        assert!(seeds >= born);
+        assert_eq!(born + 1, trace.traced().count());
+        assert!(matches!(trace.entries_of(founder).next().unwrap().event, TraceEvent::Collected { .. }));
+        assert!(trace.entries().iter().any(|entry| matches!(entry.event, TraceEvent::Died { cause: DeathCause::Disturbance, .. })));
+        assert_eq!(0, simulation.population().count());
+    }
+}
@@ -0,0 +1,220 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::simulation::Simulation;
+
+/// The number of cells handed to a thread at a time unless tuned
+pub const DEFAULT_CHUNK: usize = 1024;
+/// The numbers of cells handed to a thread at a time which are tried when tuning
+const CHUNKS: [usize; 5] = [64, 256, 1024, 4096, 16384];
+/// The number of times every setting is timed when tuning, the fastest time counts
+const ROUNDS: usize = 3;
+
+/// How the work on the cells of a step is split over threads. The cells are handed out in chunks to the threads as
+/// they become free and the results are put back in the order of the cells, so the setting only changes how fast
+/// a step runs and never what happens in it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Parallelism {
+    /// The number of threads, 1 runs everything on the calling thread
+    pub threads: usize,
+    /// The number of cells handed to a thread at a time
+    pub chunk: usize,
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            chunk: DEFAULT_CHUNK,
+        }
+    }
+}
+
+impl Parallelism {
+    /// Maps every index up to a length and returns the results in the order of the indices.
+    /// A thread count or chunk size of 0 is treated as 1
+    /// 
+    /// # Parameters
+    /// 
+    /// len: The number of indices
+    /// map: The function applied to every index
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::tuning::Parallelism;
+    /// 
+    /// let parallel = Parallelism { threads: 4, chunk: 7 };
+    /// 
+    /// assert_eq!(Parallelism::default().map(100, |index| index * 2), parallel.map(100, |index| index * 2));
+    /// ```
+    pub fn map<R: Send, F: Fn(usize) -> R + Sync>(&self, len: usize, map: F) -> Vec<R> {
+        let chunk = self.chunk.max(1);
+        let chunks = len.div_ceil(chunk);
+        let threads = self.threads.clamp(1, chunks.max(1));
+        if threads == 1 {
+            return (0..len).map(map).collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let (map, next) = (&map, &next);
+        let mut done: Vec<(usize, Vec<R>)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|_| scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let position = next.fetch_add(1, Ordering::Relaxed);
+                        if position >= chunks {
+                            return done;
+                        }
+                        let start = position * chunk;
+                        done.push((position, (start..(start + chunk).min(len)).map(map).collect()));
+                    }
+                }))
+                .collect();
+
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+        done.sort_unstable_by_key(|&(position, _)| position);
+
+        done.into_iter().flat_map(|(_, results)| results).collect()
+    }
+}
+
+/// The times measured when tuning the parallel settings of a simulation
+#[derive(Clone, Debug, PartialEq)]
+pub struct TuneReport {
+    /// Every setting tried together with its fastest time
+    pub trials: Vec<(Parallelism, Duration)>,
+    /// The fastest setting, which the simulation uses from now on
+    pub best: Parallelism,
+}
+
+/// Finds the settings worth trying on a board, a single thread is tried once since the chunks make no difference
+/// to it, and more threads are only tried with chunks which split the board into at least as many parts
+/// 
+/// # Parameters
+/// 
+/// cells: The number of cells of the board
+/// cores: The number of threads the machine can run at the same time
+fn candidates(cells: usize, cores: usize) -> Vec<Parallelism> {
+    let mut threads: Vec<usize> = std::iter::successors(Some(2), |&threads| Some(threads * 2))
+        .take_while(|&threads| threads < cores)
+        .collect();
+    threads.push(cores);
+
+    let mut candidates = vec![Parallelism::default()];
+    for threads in threads.into_iter().filter(|&threads| threads > 1) {
+        let even = cells.div_ceil(threads).max(1);
+        let mut chunks: Vec<usize> = CHUNKS.into_iter().filter(|&chunk| chunk < even).collect();
+        chunks.push(even);
+        candidates.extend(chunks.into_iter().map(|chunk| Parallelism { threads, chunk }));
+    }
+
+    candidates
+}
+
+impl Simulation {
+    /// Times how fast the plants perceive their surroundings on this board with different numbers of threads and chunk sizes
+    /// and uses the fastest from now on. The work is done on the current state without changing it, so this is best run once
+    /// the board has filled up. The chosen setting is saved in the snapshots so it can be set again when a run is picked up
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::simulation::{Simulation, SimulationConfig};
+    /// 
+    /// let board = BoardBuilder::new().size(64, 64).light_uniform(1.0).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(3, 3), Plant::new(50, Genome::new(&[0.5, 0.5]).unwrap()));
+    /// let mut simulation = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+    /// let report = simulation.auto_tune();
+    /// 
+    /// assert_eq!(report.best, simulation.parallelism());
+    /// assert_eq!(report.best, simulation.snapshot().parallelism);
+    /// assert!(report.trials.iter().any(|&(setting, _)| setting == report.best));
+    /// ```
+    pub fn auto_tune(&mut self) -> TuneReport {
+        let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+
+        self.tune(cores)
+    }
+
+    /// Times the settings possible with a number of cores and uses the fastest
+    fn tune(&mut self, cores: usize) -> TuneReport {
+        let trials: Vec<(Parallelism, Duration)> = candidates(self.board().fields.size.len(), cores).into_iter()
+            .map(|setting| {
+                let fastest = (0..ROUNDS)
+                    .map(|_| {
+                        let start = Instant::now();
+                        std::hint::black_box(self.intakes(setting, &[]));
+                        start.elapsed()
+                    })
+                    .min()
+                    .unwrap_or_default();
+
+                (setting, fastest)
+            })
+            .collect();
+        let best = trials.iter().min_by_key(|&&(_, time)| time).map_or_else(Parallelism::default, |&(setting, _)| setting);
+        self.set_parallelism(best);
+
+        TuneReport { trials, best }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardBuilder, Coord};
+    use crate::genome::Genome;
+    use crate::population::{Plant, Population};
+    use crate::simulation::SimulationConfig;
+
+    #[test]
+    fn parallelism_map_order() {
+        let expected: Vec<usize> = (0..1000).map(|index| index * 3).collect();
+
+        for setting in [Parallelism { threads: 3, chunk: 1 }, Parallelism { threads: 8, chunk: 64 }, Parallelism { threads: 0, chunk: 0 }, Parallelism { threads: 2, chunk: 5000 }] {
+            assert_eq!(expected, setting.map(1000, |index| index * 3));
+        }
+        assert_eq!(Vec::<usize>::new(), Parallelism { threads: 4, chunk: 16 }.map(0, |index| index));
+    }
+
+    #[test]
+    fn tune_candidates() {
+        assert_eq!(vec![Parallelism::default()], candidates(10_000, 1));
+        assert_eq!(
+            vec![Parallelism::default(), Parallelism { threads: 2, chunk: 64 }, Parallelism { threads: 2, chunk: 150 }],
+            candidates(300, 2),
+        );
+
+        let many = candidates(100_000, 6);
+        assert!(many.contains(&Parallelism { threads: 4, chunk: 25_000 }));
+        assert!(many.contains(&Parallelism { threads: 6, chunk: 16384 }));
+        assert!(many.iter().all(|setting| setting.threads <= 6));
+    }
+
+    #[test]
+    fn tune_same_steps() {
+        let board = BoardBuilder::new().size(40, 30).light_uniform(1.0).multiplier_light(100).build().unwrap();
+        let mut population = Population::new(board.fields.size);
+        population.insert(Coord::new(20, 15), Plant::new(200, Genome::new(&[0.2, 0.5]).unwrap()));
+        let mut single = Simulation::new(board, population, SimulationConfig::default()).unwrap();
+        let mut tuned = single.clone();
+
+        let report = tuned.tune(4);
+        assert_eq!(report.best, tuned.parallelism());
+        assert_eq!(candidates(1200, 4).len(), report.trials.len());
+
+        tuned.set_parallelism(Parallelism { threads: 4, chunk: 7 });
+        for _ in 0..30 {
+            single.step();
+            tuned.step();
+        }
+
+        assert_eq!(None, single.snapshot().diff(&tuned.snapshot()));
+    }
+}
@@ -0,0 +1,401 @@
+use std::ops::{Index, IndexMut};
+
+use crate::board::{Rect, Size};
+
+/// A borrowed rectangle of a field indexed in 2D relative to its top left corner, this never copies the field.
+/// The coordinates are checked against the rectangle unless the unchecked methods are used
+#[derive(Clone, Copy, Debug)]
+pub struct FieldView<'a, T = f32> {
+    /// The values of the whole field
+    data: &'a [T],
+    /// The width of the whole field
+    stride: usize,
+    /// The rectangle of the field which is viewed
+    rect: Rect,
+}
+
+/// A mutably borrowed rectangle of a field indexed in 2D relative to its top left corner, this never copies the field.
+/// The coordinates are checked against the rectangle unless the unchecked methods are used
+#[derive(Debug)]
+pub struct FieldViewMut<'a, T = f32> {
+    /// The values of the whole field
+    data: &'a mut [T],
+    /// The width of the whole field
+    stride: usize,
+    /// The rectangle of the field which is viewed
+    rect: Rect,
+}
+
+/// Finds the index in the whole field of a coordinate in a rectangle, returns None if it is outside the rectangle
+fn locate(stride: usize, rect: Rect, x: usize, y: usize) -> Option<usize> {
+    if x >= rect.w || y >= rect.h {
+        return None;
+    }
+
+    Some(rect.x + x + (rect.y + y) * stride)
+}
+
+/// Finds a rectangle inside another rectangle in the coordinates of the field, returns None if it does not fit
+fn inner(outer: Rect, rect: Rect) -> Option<Rect> {
+    if rect.x.checked_add(rect.w)? > outer.w || rect.y.checked_add(rect.h)? > outer.h {
+        return None;
+    }
+
+    Some(Rect::new(outer.x + rect.x, outer.y + rect.y, rect.w, rect.h))
+}
+
+impl<'a, T> FieldView<'a, T> {
+    /// Creates a view of a whole field
+    /// 
+    /// # Parameters
+    /// 
+    /// data: The values of the field
+    /// size: The size of the field
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the number of values does not match the size
+    pub(crate) fn new(data: &'a [T], size: Size) -> Self {
+        assert_eq!(size.len(), data.len(), "The field must have a value for every cell");
+        let (w, h) = size.size();
+
+        Self { data, stride: w, rect: Rect::new(0, 0, w, h) }
+    }
+
+    /// Returns the size of the view
+    pub fn size(&self) -> Size {
+        Size::new(self.rect.w, self.rect.h)
+    }
+
+    /// Returns the rectangle of the field which is viewed
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Gets the value at a position in the view, returns None if it is outside the view
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position relative to the left edge of the view
+    /// y: The y position relative to the top edge of the view
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Fields, Rect, Size};
+    /// 
+    /// let fields = Fields::new(Size::new(3, 2), &[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).unwrap();
+    /// let view = fields.light_view().sub(Rect::new(1, 1, 2, 1)).unwrap();
+    /// 
+    /// assert_eq!(Some(&0.5), view.get(1, 0));
+    /// assert_eq!(None, view.get(0, 1));
+    /// ```
+    pub fn get(&self, x: usize, y: usize) -> Option<&'a T> {
+        locate(self.stride, self.rect, x, y).map(|index| &self.data[index])
+    }
+
+    /// Gets the value at a position in the view without checking the position
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position relative to the left edge of the view
+    /// y: The y position relative to the top edge of the view
+    /// 
+    /// # Safety
+    /// 
+    /// The position must be inside the view, x must be less than the width and y less than the height
+    pub unsafe fn get_unchecked(&self, x: usize, y: usize) -> &'a T {
+        self.data.get_unchecked(self.rect.x + x + (self.rect.y + y) * self.stride)
+    }
+
+    /// Creates a view of a rectangle inside this view, returns None if the rectangle does not fit
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle relative to the top left corner of this view
+    pub fn sub(&self, rect: Rect) -> Option<FieldView<'a, T>> {
+        Some(FieldView { data: self.data, stride: self.stride, rect: inner(self.rect, rect)? })
+    }
+
+    /// Returns the row of the view at a y position as a slice, returns None if it is outside the view
+    /// 
+    /// # Parameters
+    /// 
+    /// y: The y position relative to the top edge of the view
+    pub fn row(&self, y: usize) -> Option<&'a [T]> {
+        if y >= self.rect.h {
+            return None;
+        }
+
+        let start = self.rect.x + (self.rect.y + y) * self.stride;
+
+        Some(&self.data[start..start + self.rect.w])
+    }
+
+    /// Iterates over the rows of the view from the top
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        (0..self.rect.h).filter_map(|y| self.row(y))
+    }
+
+    /// Iterates over all values of the view row by row
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Fields, Rect, Size};
+    /// 
+    /// let fields = Fields::new(Size::new(3, 2), &[0.0, 0.1, 0.2, 0.3, 0.4, 0.5]).unwrap();
+    /// let view = fields.light_view().sub(Rect::new(0, 0, 2, 2)).unwrap();
+    /// 
+    /// assert_eq!(vec![0.0, 0.1, 0.3, 0.4], view.iter().copied().collect::<Vec<_>>());
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + '_ {
+        self.rows().flatten()
+    }
+}
+
+impl<T> Index<(usize, usize)> for FieldView<'_, T> {
+    type Output = T;
+
+    /// Gets the value at an (x, y) position in the view
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the position is outside the view
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("The position must be inside the view")
+    }
+}
+
+impl<'a, T> FieldViewMut<'a, T> {
+    /// Creates a mutable view of a whole field
+    /// 
+    /// # Parameters
+    /// 
+    /// data: The values of the field
+    /// size: The size of the field
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the number of values does not match the size
+    pub(crate) fn new(data: &'a mut [T], size: Size) -> Self {
+        assert_eq!(size.len(), data.len(), "The field must have a value for every cell");
+        let (w, h) = size.size();
+
+        Self { data, stride: w, rect: Rect::new(0, 0, w, h) }
+    }
+
+    /// Returns the size of the view
+    pub fn size(&self) -> Size {
+        Size::new(self.rect.w, self.rect.h)
+    }
+
+    /// Returns the rectangle of the field which is viewed
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Borrows the view immutably
+    pub fn as_view(&self) -> FieldView<'_, T> {
+        FieldView { data: self.data, stride: self.stride, rect: self.rect }
+    }
+
+    /// Gets the value at a position in the view, returns None if it is outside the view
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position relative to the left edge of the view
+    /// y: The y position relative to the top edge of the view
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        locate(self.stride, self.rect, x, y).map(|index| &self.data[index])
+    }
+
+    /// Gets the value at a position in the view mutably, returns None if it is outside the view
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position relative to the left edge of the view
+    /// y: The y position relative to the top edge of the view
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        locate(self.stride, self.rect, x, y).map(|index| &mut self.data[index])
+    }
+
+    /// Gets the value at a position in the view mutably without checking the position
+    /// 
+    /// # Parameters
+    /// 
+    /// x: The x position relative to the left edge of the view
+    /// y: The y position relative to the top edge of the view
+    /// 
+    /// # Safety
+    /// 
+    /// The position must be inside the view, x must be less than the width and y less than the height
+    pub unsafe fn get_unchecked_mut(&mut self, x: usize, y: usize) -> &mut T {
+        self.data.get_unchecked_mut(self.rect.x + x + (self.rect.y + y) * self.stride)
+    }
+
+    /// Creates a mutable view of a rectangle inside this view, returns None if the rectangle does not fit
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle relative to the top left corner of this view
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::board::{Fields, Rect, Size};
+    /// 
+    /// let mut fields = Fields::new(Size::new(3, 3), &[0.0; 9]).unwrap();
+    /// let mut view = fields.light_view_mut();
+    /// let mut middle = view.sub_mut(Rect::new(1, 1, 2, 2)).unwrap();
+    /// middle[(1, 0)] = 1.0;
+    /// 
+    /// assert_eq!(1.0, fields.light[5]);
+    /// ```
+    pub fn sub_mut(&mut self, rect: Rect) -> Option<FieldViewMut<'_, T>> {
+        let rect = inner(self.rect, rect)?;
+
+        Some(FieldViewMut { data: self.data, stride: self.stride, rect })
+    }
+
+    /// Turns the view into a mutable view of a rectangle inside it, returns None if the rectangle does not fit
+    /// 
+    /// # Parameters
+    /// 
+    /// rect: The rectangle relative to the top left corner of this view
+    pub fn into_sub(self, rect: Rect) -> Option<FieldViewMut<'a, T>> {
+        let rect = inner(self.rect, rect)?;
+
+        Some(FieldViewMut { data: self.data, stride: self.stride, rect })
+    }
+
+    /// Returns the row of the view at a y position as a mutable slice, returns None if it is outside the view
+    /// 
+    /// # Parameters
+    /// 
+    /// y: The y position relative to the top edge of the view
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [T]> {
+        if y >= self.rect.h {
+            return None;
+        }
+
+        let start = self.rect.x + (self.rect.y + y) * self.stride;
+
+        Some(&mut self.data[start..start + self.rect.w])
+    }
+
+    /// Iterates mutably over the rows of the view from the top
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> + '_ {
+        let rect = self.rect;
+        let start = rect.y * self.stride;
+        let end = (start + rect.h * self.stride).min(self.data.len());
+
+        self.data[start..end].chunks_mut(self.stride.max(1)).take(rect.h).map(move |row| &mut row[rect.x..rect.x + rect.w])
+    }
+
+    /// Sets every value of the view
+    /// 
+    /// # Parameters
+    /// 
+    /// value: The new value
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for row in self.rows_mut() {
+            row.fill(value.clone());
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for FieldViewMut<'_, T> {
+    type Output = T;
+
+    /// Gets the value at an (x, y) position in the view
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the position is outside the view
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y).expect("The position must be inside the view")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for FieldViewMut<'_, T> {
+    /// Gets the value at an (x, y) position in the view mutably
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the position is outside the view
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        self.get_mut(x, y).expect("The position must be inside the view")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> Vec<u32> {
+        (0..12).collect()
+    }
+
+    #[test]
+    fn field_view_sub() {
+        let values = values();
+        let view = FieldView::new(&values, Size::new(4, 3));
+        let sub = view.sub(Rect::new(1, 1, 3, 2)).unwrap();
+        let inner = sub.sub(Rect::new(1, 0, 2, 2)).unwrap();
+
+        assert_eq!(Size::new(2, 2), inner.size());
+        assert_eq!(Rect::new(2, 1, 2, 2), inner.rect());
+        assert_eq!(vec![6, 7, 10, 11], inner.iter().copied().collect::<Vec<_>>());
+        assert!(sub.sub(Rect::new(1, 1, 3, 1)).is_none());
+    }
+
+    #[test]
+    fn field_view_index() {
+        let values = values();
+        let view = FieldView::new(&values, Size::new(4, 3)).sub(Rect::new(1, 1, 2, 2)).unwrap();
+
+        assert_eq!(10, view[(1, 1)]);
+        assert_eq!(Some(&[5, 6][..]), view.row(0));
+        assert_eq!(None, view.row(2));
+        assert_eq!(9, unsafe { *view.get_unchecked(0, 1) });
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_view_index_outside() {
+        let values = values();
+        let view = FieldView::new(&values, Size::new(4, 3)).sub(Rect::new(0, 0, 2, 2)).unwrap();
+
+        let _ = view[(2, 0)];
+    }
+
+    #[test]
+    fn field_view_mut_rows() {
+        let mut values = values();
+        let mut view = FieldViewMut::new(&mut values, Size::new(4, 3));
+        let mut sub = view.sub_mut(Rect::new(2, 1, 2, 2)).unwrap();
+        sub.fill(0);
+        *sub.get_mut(0, 0).unwrap() = 1;
+        unsafe {
+            *sub.get_unchecked_mut(1, 1) = 2;
+        }
+
+        assert_eq!(2, sub.rows_mut().count());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 1, 0, 8, 9, 0, 2], values);
+    }
+
+    #[test]
+    fn field_view_mut_into_sub() {
+        let mut values = values();
+        let view = FieldViewMut::new(&mut values, Size::new(4, 3));
+        let mut corner = view.into_sub(Rect::new(3, 2, 1, 1)).unwrap();
+        corner[(0, 0)] = 100;
+
+        assert_eq!(100, corner.as_view()[(0, 0)]);
+        assert_eq!(100, values[11]);
+    }
+}
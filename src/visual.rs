@@ -0,0 +1,168 @@
+use crate::genome::Genome;
+
+/// The golden ratio conjugate, multiplying by it spreads consecutive values evenly around the color wheel
+const GOLDEN: f32 = 0.618034;
+
+/// Maps a genome to a color, used when drawing plants so that lineages can be told apart.
+/// It is implemented for any function taking a genome and returning rgb
+pub trait GenomeColoring {
+    /// Finds the rgb color of a genome
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to color
+    fn color(&self, genome: &Genome) -> [u8; 3];
+}
+
+impl<F: Fn(&Genome) -> [u8; 3]> GenomeColoring for F {
+    fn color(&self, genome: &Genome) -> [u8; 3] {
+        self(genome)
+    }
+}
+
+/// Colors genomes by hashing a set of key genes into a hue, similar genomes get similar colors
+/// while lineages which have drifted apart end up at different places on the color wheel
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneHue {
+    /// The indices of the genes combined into the hue, all genes are used if this is None.
+    /// Genes the genome does not have are skipped
+    pub key_genes: Option<Vec<usize>>,
+    /// The saturation of the colors between 0 and 1
+    pub saturation: f32,
+    /// The brightness of the colors between 0 and 1
+    pub value: f32,
+}
+
+impl Default for GeneHue {
+    fn default() -> Self {
+        Self {
+            key_genes: None,
+            saturation: 0.75,
+            value: 0.85,
+        }
+    }
+}
+
+impl GeneHue {
+    /// Finds the hue of a genome between 0 and 1
+    /// 
+    /// # Parameters
+    /// 
+    /// genome: The genome to find the hue of
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{genome::Genome, visual::GeneHue};
+    /// 
+    /// let coloring = GeneHue { key_genes: Some(vec![1]), ..Default::default() };
+    /// 
+    /// assert_eq!(coloring.hue(&Genome::new(&[0.1, 0.5]).unwrap()), coloring.hue(&Genome::new(&[0.9, 0.5]).unwrap()));
+    /// ```
+    pub fn hue(&self, genome: &Genome) -> f32 {
+        let weighted = |(position, gene): (usize, f32)| gene * (position as f32 + 1.0) * GOLDEN;
+
+        let sum: f32 = match &self.key_genes {
+            Some(key_genes) => key_genes.iter()
+                .filter_map(|&index| genome.get(index))
+                .enumerate()
+                .map(weighted)
+                .sum(),
+            None => genome.genes()
+                .iter()
+                .copied()
+                .enumerate()
+                .map(weighted)
+                .sum(),
+        };
+
+        sum.fract()
+    }
+}
+
+impl GenomeColoring for GeneHue {
+    fn color(&self, genome: &Genome) -> [u8; 3] {
+        hsv_to_rgb(self.hue(genome), self.saturation.clamp(0.0, 1.0), self.value.clamp(0.0, 1.0))
+    }
+}
+
+/// Finds the color of a genome by hashing all of its genes into a hue, this is the coloring used by the renderer
+/// 
+/// # Parameters
+/// 
+/// genome: The genome to color
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::{genome::Genome, visual};
+/// 
+/// let genome1 = Genome::new(&[0.5, 0.5]).unwrap();
+/// let genome2 = Genome::new(&[0.1, 0.9]).unwrap();
+/// 
+/// assert_ne!(visual::genome_color(&genome1), visual::genome_color(&genome2));
+/// ```
+pub fn genome_color(genome: &Genome) -> [u8; 3] {
+    GeneHue::default().color(genome)
+}
+
+/// Converts a color from hue, saturation and value all between 0 and 1 to rgb
+pub(crate) fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let m = value - chroma;
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let convert = |channel: f32| ((channel + m) * 255.0).round() as u8;
+
+    [convert(r), convert(g), convert(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_to_rgb_primaries() {
+        assert_eq!([255, 0, 0], hsv_to_rgb(0.0, 1.0, 1.0));
+        assert_eq!([0, 255, 0], hsv_to_rgb(1.0 / 3.0, 1.0, 1.0));
+        assert_eq!([0, 0, 255], hsv_to_rgb(2.0 / 3.0, 1.0, 1.0));
+        assert_eq!([255, 255, 255], hsv_to_rgb(0.5, 0.0, 1.0));
+        assert_eq!([0, 0, 0], hsv_to_rgb(0.5, 1.0, 0.0));
+    }
+
+    #[test]
+    fn gene_hue_key_genes() {
+        let coloring = GeneHue { key_genes: Some(vec![0, 5]), ..Default::default() };
+        let genome1 = Genome::new(&[0.25, 0.1]).unwrap();
+        let genome2 = Genome::new(&[0.25, 0.9]).unwrap();
+
+        assert_eq!(coloring.color(&genome1), coloring.color(&genome2));
+        assert_eq!((0.25 * GOLDEN).fract(), coloring.hue(&genome1));
+        assert_ne!(GeneHue::default().color(&genome1), GeneHue::default().color(&genome2));
+    }
+
+    #[test]
+    fn genome_color_closure() {
+        let coloring = |genome: &Genome| [(genome.gene(0) * 255.0) as u8, 0, 0];
+
+        assert_eq!([255, 0, 0], coloring.color(&Genome::new(&[1.0, 0.0]).unwrap()));
+    }
+
+    #[test]
+    fn genome_color_stable() {
+        let genome1 = Genome::new(&[0.5, 0.5]).unwrap();
+        let genome2 = Genome::new(&[0.5, 0.5]).unwrap();
+
+        assert_eq!(genome_color(&genome1), genome_color(&genome2));
+    }
+}
@@ -0,0 +1,117 @@
+use wasm_bindgen::{prelude::*, Clamped};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
+
+use crate::jsonconfig;
+use crate::render;
+use crate::simulation::Simulation;
+
+/// The width and height in cells of the regions drawn by renderChangesTo
+const DIRTY_TILE: usize = 32;
+
+/// A simulation which can be run and drawn from JavaScript
+#[wasm_bindgen(js_name = Simulation)]
+pub struct WebSimulation {
+    /// The simulation being wrapped
+    inner: Simulation,
+    /// The pixels of the board as they were last drawn
+    canvas: render::Canvas,
+}
+
+/// Creates a new simulation from its settings in JSON
+/// 
+/// # Parameters
+/// 
+/// config_json: The settings in the format of jsonconfig::simulation_from_json, any missing setting gets its default value
+/// 
+/// # Errors
+/// 
+/// This will fail if the JSON is invalid or the settings cannot make a simulation, such as a negative mutation strength
+/// or a mutation rate outside 0 to 1. Such settings are rejected here since a failing step traps the whole module
+#[wasm_bindgen(js_name = newSimulation)]
+pub fn new_simulation(config_json: &str) -> Result<WebSimulation, JsError> {
+    let inner = jsonconfig::simulation_from_json(config_json)?;
+    let canvas = render::Canvas::new(inner.board(), inner.population());
+
+    Ok(WebSimulation { inner, canvas })
+}
+
+#[wasm_bindgen(js_class = Simulation)]
+impl WebSimulation {
+    /// Runs a single step
+    pub fn step(&mut self) {
+        self.inner.step();
+    }
+
+    /// Retrieves the number of steps which have been run
+    pub fn tick(&self) -> u64 {
+        self.inner.tick()
+    }
+
+    /// Retrieves the number of living plants
+    pub fn population(&self) -> usize {
+        self.inner.population().count()
+    }
+
+    /// Draws the board onto a canvas with one pixel per cell, the canvas is resized to the board
+    /// and can be scaled up with CSS
+    /// 
+    /// # Parameters
+    /// 
+    /// canvas: The canvas to draw on
+    /// 
+    /// # Errors
+    /// 
+    /// This will fail if the canvas has no 2d context
+    #[wasm_bindgen(js_name = renderTo)]
+    pub fn render_to(&mut self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        let (w, h) = self.inner.board().fields.size.size();
+        canvas.set_width(w as u32);
+        canvas.set_height(h as u32);
+
+        self.canvas = render::Canvas::new(self.inner.board(), self.inner.population());
+        self.inner.clear_dirty();
+
+        let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(self.canvas.pixels()), w as u32, h as u32)?;
+
+        context(canvas)?.put_image_data(&image, 0.0, 0.0)
+    }
+
+    /// Draws only the cells which changed since the board was last drawn onto the same canvas, the canvas is drawn
+    /// entirely like renderTo if its size does not match the board
+    /// 
+    /// # Parameters
+    /// 
+    /// canvas: The canvas the board was last drawn on
+    /// 
+    /// # Errors
+    /// 
+    /// This will fail if the canvas has no 2d context
+    #[wasm_bindgen(js_name = renderChangesTo)]
+    pub fn render_changes_to(&mut self, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+        let (w, h) = self.inner.board().fields.size.size();
+        if (canvas.width(), canvas.height()) != (w as u32, h as u32) {
+            return self.render_to(canvas);
+        }
+
+        let regions = self.canvas.update(self.inner.board(), self.inner.population(), self.inner.dirty(), DIRTY_TILE);
+        self.inner.clear_dirty();
+
+        let context = context(canvas)?;
+        for rect in regions {
+            let pixels = self.canvas.region(rect);
+            let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels), rect.w as u32, rect.h as u32)?;
+            context.put_image_data(&image, rect.x as f64, rect.y as f64)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the 2d context of a canvas
+fn context(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d, JsValue> {
+    let context = canvas.get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("the canvas has no 2d context"))?
+        .dyn_into()?;
+
+    Ok(context)
+}
@@ -0,0 +1,379 @@
+use std::f32::consts::SQRT_2;
+
+use rand::Rng;
+
+use crate::board::{Coord, Rect, Size};
+use crate::field::Field;
+use crate::memory::HeapSize;
+
+/// The eight neighbouring cells water can run to and the distance to each of them
+const D8: [(isize, isize, f32); 8] = [
+    (-1, -1, SQRT_2), (0, -1, 1.0), (1, -1, SQRT_2), (-1, 0, 1.0), (1, 0, 1.0), (-1, 1, SQRT_2), (0, 1, 1.0), (1, 1, SQRT_2),
+];
+/// The largest diffusion coefficient for which the diffusion step is stable
+const MAX_DIFFUSION: f32 = 0.25;
+
+/// The settings for how water moves, evaporates and falls as rain
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaterConfig {
+    /// The fraction of the difference in water to each of the four neighbouring cells which flows every step,
+    /// this is clamped between 0 and 0.25 to keep the diffusion stable
+    pub diffusion: f32,
+    /// The fraction of the water in a cell which evaporates every step in full light,
+    /// the evaporation is proportional to the light in the cell
+    pub evaporation: f32,
+    /// The fraction of the water in a cell which runs downhill every step to the neighbouring cell with the lowest water surface,
+    /// the elevation plus the water. Water stops running when the surfaces are level so it pools in the valleys
+    pub runoff: f32,
+    /// The rain falling on the board, no rain falls if this is None
+    pub rainfall: Option<Rainfall>,
+}
+
+impl Default for WaterConfig {
+    fn default() -> Self {
+        Self {
+            diffusion: 0.1,
+            evaporation: 0.01,
+            runoff: 0.0,
+            rainfall: None,
+        }
+    }
+}
+
+/// Rain events which each wet a circular area of the board
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rainfall {
+    /// The probability of a rain event every step
+    pub chance: f64,
+    /// The water added to every cell hit by the rain
+    pub amount: f32,
+    /// The radius in cells of the area hit by the rain
+    pub radius: usize,
+}
+
+/// The water on the board, stored in two buffers such that every step reads the old values and writes the new
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaterField {
+    /// The size of the board
+    size: Size,
+    /// The water in every cell
+    current: Field,
+    /// The buffer the next step is written to
+    next: Field,
+}
+
+impl WaterField {
+    /// Creates a new water field
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// water: The initial water in every cell
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the water does not have one value for every cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, water::WaterField};
+    /// 
+    /// let water = WaterField::new(Size::new(2, 1), &[1.0, 0.0]);
+    /// 
+    /// assert_eq!(&[1.0, 0.0], water.values());
+    /// ```
+    pub fn new(size: Size, water: &[f32]) -> Self {
+        let current = Field::from_slice("Water", size, water).expect("The water field must have one value for every cell");
+
+        Self { size, current, next: Field::filled(size, 0.0) }
+    }
+
+    /// Returns the water in every cell
+    pub fn values(&self) -> &[f32] {
+        &self.current
+    }
+
+    /// Returns the water in every cell for changing it
+    pub(crate) fn values_mut(&mut self) -> &mut [f32] {
+        &mut self.current
+    }
+
+    /// Puts the water of some cells back to an earlier state
+    pub(crate) fn restore(&mut self, earlier: &Self, cells: &[usize]) {
+        for &index in cells {
+            self.current[index] = earlier.current[index];
+        }
+    }
+
+    /// Makes a rectangle of the board the new board, the cells outside the board get some water
+    pub(crate) fn reframe(&mut self, rect: Rect, fill: f32) {
+        self.current.reframe(rect, || fill);
+        self.size = Size::new(rect.w, rect.h);
+        self.next = Field::filled(self.size, 0.0);
+    }
+
+    /// Returns the total amount of water on the board
+    pub fn total(&self) -> f32 {
+        self.current.sum()
+    }
+
+    /// Runs a single step of the water dynamics: rain falls, then water diffuses to the neighbouring cells
+    /// and evaporates. Water does not flow off the edges of the board
+    /// 
+    /// # Parameters
+    /// 
+    /// config: The settings for the water
+    /// light: The light in every cell, which drives the evaporation
+    /// rng: The random number generator used for the rain
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, water::{WaterConfig, WaterField}};
+    /// use rand::SeedableRng;
+    /// 
+    /// let mut water = WaterField::new(Size::new(3, 1), &[0.0, 1.0, 0.0]);
+    /// let config = WaterConfig { diffusion: 0.25, evaporation: 0.0, runoff: 0.0, rainfall: None };
+    /// water.step(&config, &[1.0; 3], &mut rand_chacha::ChaCha8Rng::seed_from_u64(0));
+    /// 
+    /// assert_eq!(&[0.25, 0.5, 0.25], water.values());
+    /// ```
+    pub fn step<R: Rng>(&mut self, config: &WaterConfig, light: &[f32], rng: &mut R) {
+        if let Some(rainfall) = &config.rainfall {
+            self.rain(rainfall, rng);
+        }
+
+        // Exchange water with all neighbours on the board
+        let diffusion = config.diffusion.clamp(0.0, MAX_DIFFUSION);
+        self.current.diffuse_into(diffusion, &mut self.next);
+
+        for (water, &light) in self.next.iter_mut().zip(light) {
+            let evaporation = (config.evaporation * light).clamp(0.0, 1.0);
+
+            *water = (*water * (1.0 - evaporation)).max(0.0);
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    /// Lets water run downhill for a single step, the water of every cell runs to the neighbour with the steepest drop
+    /// in the water surface, the elevation plus the water. At most half the drop runs such that water never runs
+    /// back and forth between two cells, so water collects in the valleys until the surface is level
+    /// 
+    /// # Parameters
+    /// 
+    /// elevation: The height of the terrain in every cell in the same units as the water
+    /// runoff: The fraction of the water in a cell which runs every step, this is clamped between 0 and 1
+    /// 
+    /// # Panics
+    /// 
+    /// This will panic if the elevation does not have one value for every cell
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, water::WaterField};
+    /// 
+    /// let mut water = WaterField::new(Size::new(3, 1), &[1.0, 1.0, 1.0]);
+    /// water.flow(&[2.0, 1.0, 0.0], 0.5);
+    /// 
+    /// assert_eq!(&[0.5, 1.0, 1.5], water.values());
+    /// ```
+    pub fn flow(&mut self, elevation: &[f32], runoff: f32) {
+        assert_eq!(self.size.len(), elevation.len(), "The elevation must have one value for every cell");
+
+        let runoff = runoff.clamp(0.0, 1.0);
+        self.next.copy_from_slice(&self.current);
+
+        for index in 0..self.size.len() {
+            let water = self.current[index];
+            if water <= 0.0 {
+                continue;
+            }
+
+            let surface = elevation[index] + water;
+            let mut steepest = None;
+            let mut steepest_slope = 0.0;
+            for (dx, dy, distance) in D8 {
+                let Some(target) = self.current.offset(index, dx, dy) else { continue };
+                let drop = surface - (elevation[target] + self.current[target]);
+                if drop / distance > steepest_slope {
+                    steepest = Some((target, drop));
+                    steepest_slope = drop / distance;
+                }
+            }
+
+            if let Some((target, drop)) = steepest {
+                let amount = (water * runoff).min(drop / 2.0);
+                self.next[index] -= amount;
+                self.next[target] += amount;
+            }
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+
+    /// Lets rain fall on a random circle of the board if a rain event happens
+    fn rain<R: Rng>(&mut self, rainfall: &Rainfall, rng: &mut R) {
+        if self.size.is_empty() || !rng.gen_bool(rainfall.chance.clamp(0.0, 1.0)) {
+            return;
+        }
+
+        let (w, h) = self.size.size();
+        let center = Coord::new(rng.gen_range(0..w), rng.gen_range(0..h));
+        let radius = rainfall.radius;
+
+        for y in center.y.saturating_sub(radius)..(center.y + radius + 1).min(h) {
+            for x in center.x.saturating_sub(radius)..(center.x + radius + 1).min(w) {
+                let (dx, dy) = (x.abs_diff(center.x), y.abs_diff(center.y));
+
+                if dx * dx + dy * dy <= radius * radius {
+                    self.current[x + y * w] += rainfall.amount;
+                }
+            }
+        }
+    }
+}
+
+impl HeapSize for WaterField {
+    fn heap_bytes(&self) -> usize {
+        self.current.heap_bytes() + self.next.heap_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn rng() -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(3)
+    }
+
+    #[test]
+    fn water_field_reframe() {
+        let mut water = WaterField::new(Size::new(2, 2), &[1.0, 2.0, 3.0, 4.0]);
+        water.reframe(Rect::new(1, 0, 2, 1), 0.5);
+
+        assert_eq!(&[2.0, 0.5], water.values());
+        assert_eq!(2, water.next.len());
+    }
+
+    #[test]
+    fn water_field_new() {
+        let water = WaterField::new(Size::new(2, 2), &[1.0, 2.0, 3.0, 4.0]);
+
+        assert_eq!(vec![1.0, 2.0, 3.0, 4.0], water.current);
+        assert_eq!(10.0, water.total());
+    }
+
+    #[test]
+    #[should_panic]
+    fn water_field_new_size() {
+        WaterField::new(Size::new(2, 2), &[1.0; 3]);
+    }
+
+    #[test]
+    fn water_field_step_diffusion() {
+        let mut water = WaterField::new(Size::new(3, 3), &[0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        let config = WaterConfig { diffusion: 0.1, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        water.step(&config, &[1.0; 9], &mut rng());
+
+        assert_eq!(&[0.0, 0.1, 0.0, 0.1, 0.6, 0.1, 0.0, 0.1, 0.0], water.values());
+    }
+
+    #[test]
+    fn water_field_step_conserved() {
+        let mut water = WaterField::new(Size::new(4, 3), &[5.0, 0.0, 1.0, 0.0, 2.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 1.0]);
+        let config = WaterConfig { diffusion: 0.2, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        for _ in 0..50 {
+            water.step(&config, &[1.0; 12], &mut rng());
+        }
+
+        assert!((water.total() - 12.0).abs() < 1e-4);
+        assert!(water.values().iter().all(|&value| (value - 1.0).abs() < 0.05));
+    }
+
+    #[test]
+    fn water_field_step_symmetric() {
+        // Updating in place would move more water to one side than the other
+        let mut water = WaterField::new(Size::new(5, 1), &[0.0, 0.0, 1.0, 0.0, 0.0]);
+        let config = WaterConfig { diffusion: 0.25, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        for _ in 0..3 {
+            water.step(&config, &[1.0; 5], &mut rng());
+        }
+        let values = water.values();
+
+        assert_eq!(values[0], values[4]);
+        assert_eq!(values[1], values[3]);
+    }
+
+    #[test]
+    fn water_field_step_unstable() {
+        let mut water = WaterField::new(Size::new(3, 1), &[0.0, 1.0, 0.0]);
+        let config = WaterConfig { diffusion: 1.0, evaporation: 0.0, runoff: 0.0, rainfall: None };
+        water.step(&config, &[1.0; 3], &mut rng());
+
+        assert_eq!(&[0.25, 0.5, 0.25], water.values());
+    }
+
+    #[test]
+    fn water_field_step_evaporation() {
+        let mut water = WaterField::new(Size::new(3, 1), &[1.0, 1.0, 1.0]);
+        let config = WaterConfig { diffusion: 0.0, evaporation: 0.5, runoff: 0.0, rainfall: None };
+        water.step(&config, &[0.0, 1.0, 4.0], &mut rng());
+
+        assert_eq!(&[1.0, 0.5, 0.0], water.values());
+    }
+
+    #[test]
+    fn water_field_step_rain() {
+        let mut water = WaterField::new(Size::new(5, 5), &[0.0; 25]);
+        let rainfall = Rainfall { chance: 1.0, amount: 2.0, radius: 1 };
+        let config = WaterConfig { diffusion: 0.0, evaporation: 0.0, runoff: 0.0, rainfall: Some(rainfall) };
+        water.step(&config, &[1.0; 25], &mut rng());
+        let wet = water.values().iter().filter(|&&value| value == 2.0).count();
+
+        // A circle of radius 1 covers 5 cells when it is not cut by an edge
+        assert!((3..=5).contains(&wet));
+        assert_eq!(wet as f32 * 2.0, water.total());
+    }
+
+    #[test]
+    fn water_field_step_no_rain() {
+        let mut water = WaterField::new(Size::new(5, 5), &[0.0; 25]);
+        let rainfall = Rainfall { chance: 0.0, amount: 2.0, radius: 1 };
+        let config = WaterConfig { diffusion: 0.0, evaporation: 0.0, runoff: 0.0, rainfall: Some(rainfall) };
+        water.step(&config, &[1.0; 25], &mut rng());
+
+        assert_eq!(0.0, water.total());
+    }
+
+    #[test]
+    fn water_field_flow_valley() {
+        // A valley in the middle of a slope on both sides collects the water until the surface is level
+        let elevation = [3.0, 2.0, 1.0, 0.0, 1.0, 2.0, 3.0, 2.0, 1.0, 0.0, 1.0, 2.0];
+        let mut water = WaterField::new(Size::new(6, 2), &[1.0; 12]);
+        for _ in 0..200 {
+            water.flow(&elevation, 0.5);
+        }
+
+        assert!((water.total() - 12.0).abs() < 1e-4);
+        assert!(water.values()[0] < 0.01 && water.values()[6] < 0.01);
+
+        // 12 water fills the valley to a level of 2.4
+        for (water, elevation) in water.values().iter().zip(elevation).filter(|(_, elevation)| *elevation < 3.0) {
+            assert!((water + elevation - 2.4).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn water_field_flow_level() {
+        let mut water = WaterField::new(Size::new(3, 3), &[1.0; 9]);
+        water.flow(&[0.0; 9], 1.0);
+
+        assert_eq!(&[1.0; 9], water.values());
+    }
+}
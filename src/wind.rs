@@ -0,0 +1,177 @@
+use std::f32::consts::TAU;
+
+use rand::Rng;
+
+use crate::board::Size;
+use crate::field::Field;
+
+/// The settings for a prevailing wind carrying the seeds. The wind blows in a main direction which can swing back and forth
+/// over time and meander across the board in bands, and every seed drifts downwind by the speed of the wind in its cell
+/// on top of the distance its parent throws it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindConfig {
+    /// The direction the wind blows towards in radians, measured from the positive x axis towards the positive y axis
+    pub azimuth: f32,
+    /// The distance in cells a seed drifts with the wind on average
+    pub speed: f32,
+    /// The largest turn in radians of the wind across the board, the wind turns back and forth once between the sides
+    /// of the board on either side of the wind
+    pub meander: f32,
+    /// The largest turn in radians of the wind over time
+    pub swing: f32,
+    /// The number of ticks for the wind to swing back and forth once, the wind does not swing if this is 0
+    pub period: u64,
+}
+
+impl Default for WindConfig {
+    fn default() -> Self {
+        Self {
+            azimuth: 0.0,
+            speed: 1.0,
+            meander: 0.0,
+            swing: 0.0,
+            period: 100,
+        }
+    }
+}
+
+impl WindConfig {
+    /// Creates a new steady wind blowing the same way across the whole board
+    /// 
+    /// # Parameters
+    /// 
+    /// azimuth: The direction the wind blows towards in radians, measured from the positive x axis towards the positive y axis
+    /// speed: The distance in cells a seed drifts with the wind on average
+    pub fn new(azimuth: f32, speed: f32) -> Self {
+        Self { azimuth, speed, meander: 0.0, swing: 0.0, period: 0 }
+    }
+
+    /// Finds the main direction of the wind at a tick in radians
+    /// 
+    /// # Parameters
+    /// 
+    /// tick: The tick to find the direction at
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::wind::WindConfig;
+    /// 
+    /// let wind = WindConfig { azimuth: 1.0, swing: 0.5, period: 8, ..Default::default() };
+    /// 
+    /// assert_eq!(1.0, wind.direction(0));
+    /// assert_eq!(1.5, wind.direction(2));
+    /// assert_eq!(0.5, wind.direction(6));
+    /// ```
+    pub fn direction(&self, tick: u64) -> f32 {
+        if self.period == 0 {
+            return self.azimuth;
+        }
+
+        let phase = (tick % self.period) as f32 / self.period as f32;
+        self.azimuth + self.swing * (phase * TAU).sin()
+    }
+
+    /// Generates the wind in every cell at a tick as the drift of a seed in cells along x and y. The wind meanders
+    /// with the distance across the wind so it blows in bands
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// tick: The tick to generate the wind at
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::Size, wind::WindConfig};
+    /// 
+    /// let field = WindConfig::new(0.0, 2.0).field(Size::new(3, 2), 0);
+    /// 
+    /// assert!(field.iter().all(|&wind| wind == (2.0, 0.0)));
+    /// ```
+    pub fn field(&self, size: Size, tick: u64) -> Field<(f32, f32)> {
+        let direction = self.direction(tick);
+        let (w, h) = size.size();
+        let (sin, cos) = direction.sin_cos();
+        let extent = (w as f32 * sin.abs() + h as f32 * cos.abs()).max(1.0);
+
+        let data = size.coords().map(|coord| {
+            let across = coord.y as f32 * cos - coord.x as f32 * sin;
+            let angle = direction + self.meander * (across / extent * TAU).sin();
+
+            (self.speed * angle.cos(), self.speed * angle.sin())
+        }).collect();
+
+        Field::from_vec("wind", size, data).expect("The wind has a value for every cell")
+    }
+}
+
+/// Moves where a seed lands downwind, the drift is rounded up or down at random such that the seeds drift
+/// by the wind on average. Returns the offset unchanged if the wind would blow the seed back into the cell of its parent
+/// 
+/// # Parameters
+/// 
+/// wind: The wind in the cell of the parent
+/// offset: Where the seed lands relative to its parent without wind
+/// rng: The random number generator to use
+/// 
+/// # Examples
+/// 
+/// ```
+/// use evolution_plants::wind;
+/// use rand::SeedableRng;
+/// use rand_chacha::ChaCha8Rng;
+/// 
+/// let mut rng = ChaCha8Rng::seed_from_u64(1);
+/// 
+/// assert_eq!((3, -1), wind::drift((2.0, -1.0), (1, 0), &mut rng));
+/// assert_eq!((-1, 0), wind::drift((1.0, 0.0), (-1, 0), &mut rng));
+/// ```
+pub fn drift<R: Rng>(wind: (f32, f32), offset: (isize, isize), rng: &mut R) -> (isize, isize) {
+    let mut round = |value: f32| (value + rng.gen::<f32>()).floor() as isize;
+    let drifted = (offset.0 + round(wind.0), offset.1 + round(wind.1));
+
+    if drifted == (0, 0) { offset } else { drifted }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn wind_drift_mean() {
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let total = (0..10000).fold((0, 0), |total, _| {
+            let (dx, dy) = drift((0.25, 1.5), (0, 3), &mut rng);
+            (total.0 + dx, total.1 + dy)
+        });
+
+        assert!((total.0 as f32 / 10000.0 - 0.25).abs() < 0.02);
+        assert!((total.1 as f32 / 10000.0 - 4.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn wind_field_meander() {
+        let wind = WindConfig { azimuth: 0.0, speed: 1.0, meander: 0.5, swing: 0.0, period: 0 };
+        let field = wind.field(Size::new(2, 4), 0);
+
+        // The wind blows along x so it only changes from row to row
+        for row in field.chunks(2) {
+            assert_eq!(row[0], row[1]);
+        }
+        assert_eq!((1.0, 0.0), field[0]);
+        assert!(field[2].1 > 0.0 && field[6].1 < 0.0);
+        assert!(field.iter().all(|(x, y)| (x * x + y * y - 1.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn wind_swing() {
+        let wind = WindConfig { swing: 1.0, period: 4, ..WindConfig::new(0.0, 1.0) };
+
+        assert_eq!(wind.direction(1), wind.direction(5));
+        assert_eq!(1.0, wind.direction(1));
+        assert!(wind.field(Size::new(1, 1), 1)[0].1 > 0.8);
+    }
+}
@@ -0,0 +1,370 @@
+use thiserror::Error;
+
+use crate::board::{Coord, Size};
+use crate::population::Plant;
+use crate::simulation::Simulation;
+
+/// One of the four edges of a board
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// The edge at y = 0
+    North,
+    /// The edge at the largest y
+    South,
+    /// The edge at the largest x
+    East,
+    /// The edge at x = 0
+    West,
+}
+
+impl Edge {
+    /// Returns the number of cells along the edge of a board
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    pub fn length(&self, size: Size) -> usize {
+        let (w, h) = size.size();
+
+        match self {
+            Edge::North | Edge::South => w,
+            Edge::East | Edge::West => h,
+        }
+    }
+
+    /// Finds the cell at a position along the edge of a board, the position is clamped to the edge
+    /// 
+    /// # Parameters
+    /// 
+    /// size: The size of the board
+    /// position: The number of cells from the start of the edge, the start is the end with the lowest coordinate
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{Coord, Size}, world::Edge};
+    /// 
+    /// let size = Size::new(4, 3);
+    /// 
+    /// assert_eq!(Coord::new(3, 1), Edge::East.cell(size, 1));
+    /// assert_eq!(Coord::new(3, 2), Edge::South.cell(size, 7));
+    /// ```
+    pub fn cell(&self, size: Size, position: usize) -> Coord {
+        let (w, h) = size.size();
+        let position = position.min(self.length(size).saturating_sub(1));
+
+        match self {
+            Edge::North => Coord::new(position, 0),
+            Edge::South => Coord::new(position, h.saturating_sub(1)),
+            Edge::East => Coord::new(w.saturating_sub(1), position),
+            Edge::West => Coord::new(0, position),
+        }
+    }
+}
+
+/// A seed which was dispersed off the edge of a board
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Emigrant {
+    /// The edge the seed left through
+    pub edge: Edge,
+    /// The position along the edge where the seed left
+    pub position: usize,
+    /// The number of cells along the edge
+    pub length: usize,
+    /// The seed
+    pub seed: Plant,
+}
+
+impl Emigrant {
+    /// Finds where a seed dispersed from a cell to a position outside the board left the board
+    pub fn leave(size: Size, coord: Coord, dx: isize, dy: isize, seed: Plant) -> Self {
+        let (w, h) = size.size();
+        let x = coord.x as isize + dx;
+        let y = coord.y as isize + dy;
+
+        let edge = if x < 0 {
+            Edge::West
+        } else if x >= w as isize {
+            Edge::East
+        } else if y < 0 {
+            Edge::North
+        } else {
+            Edge::South
+        };
+
+        let position = match edge {
+            Edge::North | Edge::South => x.clamp(0, w as isize - 1),
+            Edge::East | Edge::West => y.clamp(0, h as isize - 1),
+        } as usize;
+
+        Self { edge, position, length: edge.length(size), seed }
+    }
+}
+
+/// A one way route for seeds leaving a board through an edge to arrive at an edge of another board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Channel {
+    /// The index of the board the seeds leave
+    pub from: usize,
+    /// The edge the seeds leave through
+    pub edge: Edge,
+    /// The index of the board the seeds arrive at
+    pub to: usize,
+    /// The edge the seeds arrive at
+    pub arrival: Edge,
+}
+
+/// Several simulations stepped together where seeds dispersing off the edge of one board can arrive on another board.
+/// Seeds arrive at the cell along the arrival edge matching where they left, scaled to the length of the edge,
+/// and start a new lineage on the new board. Seeds leaving through an edge without a channel are lost
+#[derive(Debug, Default)]
+pub struct World {
+    /// The simulations of the world
+    simulations: Vec<Simulation>,
+    /// The routes between the boards
+    channels: Vec<Channel>,
+}
+
+impl World {
+    /// Creates a new world without any boards
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a simulation and returns its index
+    /// 
+    /// # Parameters
+    /// 
+    /// simulation: The simulation to add
+    pub fn add(&mut self, mut simulation: Simulation) -> usize {
+        simulation.collect_emigrants(true);
+        self.simulations.push(simulation);
+
+        self.simulations.len() - 1
+    }
+
+    /// Sends the seeds leaving a board through an edge to an edge of another board,
+    /// this replaces any channel already leaving through the same edge
+    /// 
+    /// # Parameters
+    /// 
+    /// channel: The route of the seeds
+    /// 
+    /// # Errors
+    /// 
+    /// WorldError::Index: This will occur if one of the boards of the channel does not exist
+    pub fn link(&mut self, channel: Channel) -> Result<(), WorldError> {
+        for index in [channel.from, channel.to] {
+            if index >= self.simulations.len() {
+                return Err(WorldError::Index { index, count: self.simulations.len() });
+            }
+        }
+
+        self.channels.retain(|existing| (existing.from, existing.edge) != (channel.from, channel.edge));
+        self.channels.push(channel);
+
+        Ok(())
+    }
+
+    /// Links two boards both ways such that seeds can move back and forth between two edges
+    /// 
+    /// # Parameters
+    /// 
+    /// first: The index of the first board
+    /// first_edge: The edge of the first board
+    /// second: The index of the second board
+    /// second_edge: The edge of the second board
+    /// 
+    /// # Errors
+    /// 
+    /// WorldError::Index: This will occur if one of the boards does not exist
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use evolution_plants::{board::{BoardBuilder, Coord}, genome::Genome, population::{Plant, Population}};
+    /// use evolution_plants::{simulation::{Simulation, SimulationConfig}, world::{Edge, World}};
+    /// 
+    /// let board = BoardBuilder::new().size(2, 2).light_uniform(1.0).multiplier_light(100).build().unwrap();
+    /// let mut population = Population::new(board.fields.size);
+    /// population.insert(Coord::new(1, 0), Plant::new(1000, Genome::new(&[0.0, 0.1]).unwrap()));
+    /// let mut world = World::new();
+    /// let mainland = world.add(Simulation::new(board.clone(), population, SimulationConfig::default()).unwrap());
+    /// let island = world.add(Simulation::new(board.clone(), Population::new(board.fields.size), SimulationConfig::default()).unwrap());
+    /// world.connect(mainland, Edge::East, island, Edge::West).unwrap();
+    /// world.connect(mainland, Edge::North, island, Edge::South).unwrap();
+    /// for _ in 0..20 {
+    ///     world.step();
+    /// }
+    /// 
+    /// assert!(world.simulation(island).unwrap().population().count() > 0);
+    /// ```
+    pub fn connect(&mut self, first: usize, first_edge: Edge, second: usize, second_edge: Edge) -> Result<(), WorldError> {
+        self.link(Channel { from: first, edge: first_edge, to: second, arrival: second_edge })?;
+        self.link(Channel { from: second, edge: second_edge, to: first, arrival: first_edge })
+    }
+
+    /// Returns the routes between the boards
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    /// Returns all simulations in the order they were added
+    pub fn simulations(&self) -> &[Simulation] {
+        &self.simulations
+    }
+
+    /// Returns a simulation, None if it does not exist
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the simulation
+    pub fn simulation(&self, index: usize) -> Option<&Simulation> {
+        self.simulations.get(index)
+    }
+
+    /// Returns a simulation mutably, None if it does not exist
+    /// 
+    /// # Parameters
+    /// 
+    /// index: The index of the simulation
+    pub fn simulation_mut(&mut self, index: usize) -> Option<&mut Simulation> {
+        self.simulations.get_mut(index)
+    }
+
+    /// Removes all simulations from the world in the order they were added, they no longer keep the seeds leaving their boards
+    pub fn into_simulations(self) -> Vec<Simulation> {
+        let mut simulations = self.simulations;
+        for simulation in &mut simulations {
+            simulation.collect_emigrants(false);
+        }
+
+        simulations
+    }
+
+    /// Steps every simulation once and then moves the seeds which left a board through a channel,
+    /// returns the number of seeds which germinated on another board
+    pub fn step(&mut self) -> usize {
+        for simulation in &mut self.simulations {
+            simulation.step();
+        }
+
+        let mut arrived = 0;
+        for from in 0..self.simulations.len() {
+            for emigrant in self.simulations[from].take_emigrants() {
+                let channel = match self.channels.iter().find(|channel| channel.from == from && channel.edge == emigrant.edge) {
+                    Some(channel) => *channel,
+                    None => continue,
+                };
+
+                let target = &mut self.simulations[channel.to];
+                let size = target.board().fields.size;
+                let position = emigrant.position * channel.arrival.length(size) / emigrant.length.max(1);
+                let index = size.index_of(channel.arrival.cell(size, position)).expect("The edge is on the board");
+
+                if target.immigrate(index, emigrant.seed) {
+                    arrived += 1;
+                }
+            }
+        }
+
+        arrived
+    }
+}
+
+#[derive(Clone, Error, Debug, PartialEq)]
+pub enum WorldError {
+    #[error("Board {:?} does not exist in a world with {:?} boards", index, count)]
+    Index {
+        index: usize,
+        count: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, BoardBuilder};
+    use crate::genome::Genome;
+    use crate::population::Population;
+    use crate::simulation::SimulationConfig;
+
+    fn board(w: usize, h: usize) -> Board {
+        BoardBuilder::new().size(w, h).light_uniform(1.0).multiplier_light(100).build().unwrap()
+    }
+
+    fn seed() -> Plant {
+        Plant::new(10, Genome::new(&[0.0, 0.5]).unwrap())
+    }
+
+    #[test]
+    fn emigrant_leave() {
+        let size = Size::new(4, 3);
+
+        assert_eq!((Edge::West, 2, 3), {
+            let emigrant = Emigrant::leave(size, Coord::new(0, 2), -1, 1, seed());
+            (emigrant.edge, emigrant.position, emigrant.length)
+        });
+        assert_eq!((Edge::North, 1), {
+            let emigrant = Emigrant::leave(size, Coord::new(1, 0), 0, -1, seed());
+            (emigrant.edge, emigrant.position)
+        });
+        assert_eq!(Edge::East, Emigrant::leave(size, Coord::new(3, 0), 1, -1, seed()).edge);
+        assert_eq!(Edge::South, Emigrant::leave(size, Coord::new(2, 2), 0, 1, seed()).edge);
+    }
+
+    #[test]
+    fn world_link_index() {
+        let mut world = World::new();
+        world.add(Simulation::new(board(2, 2), Population::new(Size::new(2, 2)), SimulationConfig::default()).unwrap());
+
+        assert_eq!(Err(WorldError::Index { index: 1, count: 1 }), world.link(Channel { from: 0, edge: Edge::East, to: 1, arrival: Edge::West }));
+    }
+
+    #[test]
+    fn world_link_replace() {
+        let mut world = World::new();
+        for _ in 0..3 {
+            world.add(Simulation::new(board(2, 2), Population::new(Size::new(2, 2)), SimulationConfig::default()).unwrap());
+        }
+        world.link(Channel { from: 0, edge: Edge::East, to: 1, arrival: Edge::West }).unwrap();
+        world.link(Channel { from: 0, edge: Edge::East, to: 2, arrival: Edge::North }).unwrap();
+
+        assert_eq!(&[Channel { from: 0, edge: Edge::East, to: 2, arrival: Edge::North }], world.channels());
+    }
+
+    #[test]
+    fn world_step_migration() {
+        // A single row where every seed leaves through the east or west edge
+        let mut population = Population::new(Size::new(1, 1));
+        population.insert(Coord::new(0, 0), Plant::new(1000, Genome::new(&[0.0, 0.1]).unwrap()));
+        let mut world = World::new();
+        let source = world.add(Simulation::new(board(1, 1), population, SimulationConfig::default()).unwrap());
+        let target = world.add(Simulation::new(board(3, 4), Population::new(Size::new(3, 4)), SimulationConfig::default()).unwrap());
+        for edge in [Edge::North, Edge::South, Edge::East, Edge::West] {
+            world.link(Channel { from: source, edge, to: target, arrival: Edge::West }).unwrap();
+        }
+
+        assert_eq!(1, world.step());
+
+        let arrived: Vec<_> = world.simulation(target).unwrap().population().iter().map(|(coord, plant)| (coord, plant.parent())).collect();
+
+        assert_eq!(vec![(Coord::new(0, 0), None)], arrived);
+    }
+
+    #[test]
+    fn world_step_lost() {
+        let mut population = Population::new(Size::new(1, 1));
+        population.insert(Coord::new(0, 0), Plant::new(1000, Genome::new(&[0.0, 0.1]).unwrap()));
+        let mut world = World::new();
+        world.add(Simulation::new(board(1, 1), population, SimulationConfig::default()).unwrap());
+        world.add(Simulation::new(board(2, 2), Population::new(Size::new(2, 2)), SimulationConfig::default()).unwrap());
+
+        assert_eq!(0, world.step());
+        assert_eq!(0, world.simulation(1).unwrap().population().count());
+
+        let simulations = world.into_simulations();
+
+        assert_eq!(2, simulations.len());
+    }
+}